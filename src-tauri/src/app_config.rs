@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use crate::services::skill::SkillStore;
+use crate::services::skill::{SkillStore, SyncMethod};
 
 /// MCP 服务器应用状态（标记应用到哪些客户端）
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -174,6 +174,9 @@ pub struct InstalledSkill {
     pub apps: SkillApps,
     /// 安装时间（Unix 时间戳）
     pub installed_at: i64,
+    /// 单独指定的同步方式（优先于全局 `skillSyncMethod`，None 表示跟随全局）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_method_override: Option<SyncMethod>,
 }
 
 /// 未管理的 Skill（在应用目录中发现但未被 CC Switch 管理）
@@ -9,7 +9,7 @@ use crate::error::AppError;
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
-use toml_edit::DocumentMut;
+use toml_edit::{DocumentMut, Item, RawString, Table, TomlError};
 
 /// 获取 Codex 配置目录路径
 pub fn get_codex_config_dir() -> PathBuf {
@@ -65,21 +65,33 @@ pub fn write_codex_live_atomic(
     auth: &Value,
     config_text_opt: Option<&str>,
 ) -> Result<(), AppError> {
-    let auth_path = get_codex_auth_path();
-    let config_path = get_codex_config_path();
+    write_codex_live_atomic_at(
+        &get_codex_auth_path(),
+        &get_codex_config_path(),
+        auth,
+        config_text_opt,
+    )
+}
 
+/// 同 [`write_codex_live_atomic`]，但写入指定路径（用于沙箱中的接管自检）
+pub(crate) fn write_codex_live_atomic_at(
+    auth_path: &Path,
+    config_path: &Path,
+    auth: &Value,
+    config_text_opt: Option<&str>,
+) -> Result<(), AppError> {
     if let Some(parent) = auth_path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
     }
 
     // 读取旧内容用于回滚
     let old_auth = if auth_path.exists() {
-        Some(fs::read(&auth_path).map_err(|e| AppError::io(&auth_path, e))?)
+        Some(fs::read(auth_path).map_err(|e| AppError::io(auth_path, e))?)
     } else {
         None
     };
     let _old_config = if config_path.exists() {
-        Some(fs::read(&config_path).map_err(|e| AppError::io(&config_path, e))?)
+        Some(fs::read(config_path).map_err(|e| AppError::io(config_path, e))?)
     } else {
         None
     };
@@ -90,19 +102,19 @@ pub fn write_codex_live_atomic(
         None => String::new(),
     };
     if !cfg_text.trim().is_empty() {
-        toml::from_str::<toml::Table>(&cfg_text).map_err(|e| AppError::toml(&config_path, e))?;
+        toml::from_str::<toml::Table>(&cfg_text).map_err(|e| AppError::toml(config_path, e))?;
     }
 
     // 第一步：写 auth.json
-    write_json_file(&auth_path, auth)?;
+    write_json_file(auth_path, auth)?;
 
     // 第二步：写 config.toml（失败则回滚 auth.json）
-    if let Err(e) = write_text_file(&config_path, &cfg_text) {
+    if let Err(e) = write_text_file(config_path, &cfg_text) {
         // 回滚 auth.json
         if let Some(bytes) = old_auth {
-            let _ = atomic_write(&auth_path, &bytes);
+            let _ = atomic_write(auth_path, &bytes);
         } else {
-            let _ = delete_file(&auth_path);
+            let _ = delete_file(auth_path);
         }
         return Err(e);
     }
@@ -137,6 +149,107 @@ pub fn read_and_validate_codex_config_text() -> Result<String, AppError> {
     Ok(s)
 }
 
+/// 规范化 Codex config.toml 文本（供编辑器“格式化”使用）
+///
+/// - 统一 `key = value` 间距，去掉缩进与键值对之间的空行
+/// - 顶层键按字母序排列；表保持原有顺序，表之间保留一个空行
+/// - 保留注释（键上方的注释随键移动，行尾注释保留在原行）
+/// - 点号键（`a.b = 1`）内部保持原样
+///
+/// 语法错误时返回带行列号的错误。
+pub fn format_codex_config_toml(text: &str) -> Result<String, AppError> {
+    let mut doc = text
+        .parse::<DocumentMut>()
+        .map_err(|e| toml_syntax_error(text, &e))?;
+
+    doc.as_table_mut().sort_values();
+    format_toml_table(doc.as_table_mut());
+    let trailing = toml_comment_lines(raw_str(Some(doc.trailing())));
+    doc.set_trailing(trailing);
+
+    let formatted = doc.to_string();
+    let formatted = formatted.trim_matches('\n');
+    if formatted.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(format!("{formatted}\n"))
+    }
+}
+
+fn format_toml_table(table: &mut Table) {
+    for (mut key, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => {
+                let decor = key.leaf_decor_mut();
+                let prefix = toml_comment_lines(raw_str(decor.prefix()));
+                decor.set_prefix(prefix);
+                decor.set_suffix(" ");
+
+                let decor = value.decor_mut();
+                let suffix = toml_trailing_comment(raw_str(decor.suffix()));
+                decor.set_prefix(" ");
+                decor.set_suffix(suffix);
+            }
+            Item::Table(sub) => format_toml_header_table(sub),
+            Item::ArrayOfTables(array) => {
+                for sub in array.iter_mut() {
+                    format_toml_header_table(sub);
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+fn format_toml_header_table(table: &mut Table) {
+    if table.is_dotted() {
+        return;
+    }
+    if !table.is_implicit() {
+        let decor = table.decor_mut();
+        let prefix = format!("\n{}", toml_comment_lines(raw_str(decor.prefix())));
+        let suffix = toml_trailing_comment(raw_str(decor.suffix()));
+        decor.set_prefix(prefix);
+        decor.set_suffix(suffix);
+    }
+    format_toml_table(table);
+}
+
+fn raw_str(raw: Option<&RawString>) -> &str {
+    raw.and_then(RawString::as_str).unwrap_or_default()
+}
+
+/// 只保留修饰中的注释行（去掉缩进与空行）
+fn toml_comment_lines(decor: &str) -> String {
+    decor
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// 行尾注释统一为 ` # ...`
+fn toml_trailing_comment(decor: &str) -> String {
+    decor
+        .find('#')
+        .map(|idx| format!(" {}", decor[idx..].trim_end()))
+        .unwrap_or_default()
+}
+
+fn toml_syntax_error(text: &str, err: &TomlError) -> AppError {
+    let offset = err.span().map(|span| span.start).unwrap_or(0);
+    let before = text.get(..offset).unwrap_or(text);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    let message = err.message().trim();
+    AppError::localized(
+        "provider.codex.config.invalid_toml",
+        format!("config.toml 第 {line} 行第 {column} 列存在语法错误: {message}"),
+        format!("config.toml has a syntax error at line {line}, column {column}: {message}"),
+    )
+}
+
 /// Update a field in Codex config.toml using toml_edit (syntax-preserving).
 ///
 /// Supported fields:
@@ -202,6 +315,87 @@ pub fn update_codex_toml_field(toml_str: &str, field: &str, value: &str) -> Resu
     Ok(doc.to_string())
 }
 
+/// 解析 Codex config.toml 中实际生效的 base_url
+///
+/// 优先读取 `model_provider` 指向的 `[model_providers.<key>]`；未指定 model_provider 时
+/// 依次使用顶层 base_url、唯一定义了 base_url 的 model_providers 条目。
+/// TOML 无法解析时才退回到正则匹配。
+pub fn resolve_codex_base_url(toml_str: &str) -> Result<String, AppError> {
+    let doc = match toml_str.parse::<DocumentMut>() {
+        Ok(doc) => doc,
+        Err(_) => return base_url_by_regex(toml_str).ok_or_else(missing_base_url_error),
+    };
+
+    let model_providers = doc.get("model_providers").and_then(|item| item.as_table());
+    let provider_base_url = |key: &str| {
+        model_providers
+            .and_then(|providers| providers.get(key))
+            .and_then(|provider| provider.get("base_url"))
+            .and_then(|item| item.as_str())
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+    };
+    let root_base_url = doc
+        .get("base_url")
+        .and_then(|item| item.as_str())
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string);
+    let defined: Vec<String> = model_providers
+        .map(|providers| {
+            providers
+                .iter()
+                .filter_map(|(key, _)| provider_base_url(key))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if let Some(active) = doc.get("model_provider").and_then(|item| item.as_str()) {
+        if let Some(url) = provider_base_url(active) {
+            return Ok(url);
+        }
+        if root_base_url.is_none() && defined.is_empty() {
+            return Err(missing_base_url_error());
+        }
+        return Err(AppError::localized(
+            "provider.codex.base_url.active_missing",
+            format!("当前 model_provider \"{active}\" 未配置 base_url"),
+            format!("Active model_provider \"{active}\" has no base_url"),
+        ));
+    }
+
+    if let Some(url) = root_base_url {
+        return Ok(url);
+    }
+
+    match defined.as_slice() {
+        [] => Err(missing_base_url_error()),
+        [url] => Ok(url.clone()),
+        _ => Err(AppError::localized(
+            "provider.codex.base_url.ambiguous",
+            "config.toml 中有多个 model_providers 定义了 base_url，但未指定 model_provider",
+            "Multiple model_providers define base_url in config.toml but model_provider is not set",
+        )),
+    }
+}
+
+fn missing_base_url_error() -> AppError {
+    AppError::localized(
+        "provider.codex.base_url.missing",
+        "config.toml 中缺少 base_url 配置",
+        "base_url is missing from config.toml",
+    )
+}
+
+/// 最后手段：TOML 无法解析时用正则提取第一个 base_url
+fn base_url_by_regex(toml_str: &str) -> Option<String> {
+    let re = regex::Regex::new(r#"base_url\s*=\s*["']([^"']+)["']"#).ok()?;
+    re.captures(toml_str)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
 /// Remove `base_url` from the active model_provider section only if it matches `predicate`.
 /// Also removes top-level `base_url` if it matches.
 /// Used by proxy cleanup to strip local proxy URLs without touching user-configured URLs.
@@ -254,6 +448,78 @@ pub fn remove_codex_toml_base_url_if(toml_str: &str, predicate: impl Fn(&str) ->
 mod tests {
     use super::*;
 
+    #[test]
+    fn resolve_base_url_uses_active_model_provider() {
+        let input = r#"model_provider = "relay"
+base_url = "https://root.example/v1"
+
+[model_providers.other]
+base_url = "https://other.example/v1"
+
+[model_providers.relay]
+name = "Relay"
+base_url = "https://relay.example/v1"
+"#;
+        assert_eq!(
+            resolve_codex_base_url(input).unwrap(),
+            "https://relay.example/v1"
+        );
+    }
+
+    #[test]
+    fn resolve_base_url_reports_active_provider_without_base_url() {
+        let input = r#"model_provider = "relay"
+
+[model_providers.relay]
+name = "Relay"
+
+[model_providers.other]
+base_url = "https://other.example/v1"
+"#;
+        let err = resolve_codex_base_url(input).unwrap_err().to_string();
+        assert!(err.contains("relay"), "unexpected error: {err}");
+
+        let err = resolve_codex_base_url("model_provider = \"relay\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("缺少 base_url"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn resolve_base_url_without_model_provider() {
+        assert_eq!(
+            resolve_codex_base_url("base_url = \"https://root.example/v1\"\n").unwrap(),
+            "https://root.example/v1"
+        );
+
+        let single = r#"[model_providers.relay]
+base_url = "https://relay.example/v1"
+"#;
+        assert_eq!(
+            resolve_codex_base_url(single).unwrap(),
+            "https://relay.example/v1"
+        );
+
+        let ambiguous = r#"[model_providers.a]
+base_url = "https://a.example/v1"
+
+[model_providers.b]
+base_url = "https://b.example/v1"
+"#;
+        let err = resolve_codex_base_url(ambiguous).unwrap_err().to_string();
+        assert!(err.contains("model_provider"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn resolve_base_url_falls_back_to_regex_for_invalid_toml() {
+        let input = "base_url = \"https://relay.example/v1\"\nbroken = [\n";
+        assert_eq!(
+            resolve_codex_base_url(input).unwrap(),
+            "https://relay.example/v1"
+        );
+        assert!(resolve_codex_base_url("broken = [\n").is_err());
+    }
+
     #[test]
     fn base_url_writes_into_correct_model_provider_section() {
         let input = r#"model_provider = "any"
@@ -467,4 +733,55 @@ base_url = "https://production.api/v1"
             .and_then(|v| v.as_str());
         assert_eq!(base_url, Some("https://production.api/v1"));
     }
+
+    #[test]
+    fn format_config_sorts_root_keys_and_keeps_comments() {
+        let input = r#"
+  # 当前模型
+model   =    "gpt-5"   # 主力模型
+
+
+approval_policy="never"
+
+[model_providers.relay]   # 中转
+name="Relay"
+base_url = "https://relay.example.com/v1"
+[mcp_servers.fs]
+command="npx"
+# trailing note
+"#;
+        let formatted = format_codex_config_toml(input).expect("format");
+        assert_eq!(
+            formatted,
+            r#"approval_policy = "never"
+# 当前模型
+model = "gpt-5" # 主力模型
+
+[model_providers.relay] # 中转
+name = "Relay"
+base_url = "https://relay.example.com/v1"
+
+[mcp_servers.fs]
+command = "npx"
+# trailing note
+"#
+        );
+
+        // 格式化结果再次格式化保持不变
+        assert_eq!(
+            format_codex_config_toml(&formatted).expect("reformat"),
+            formatted
+        );
+    }
+
+    #[test]
+    fn format_config_reports_error_position() {
+        let err =
+            format_codex_config_toml("model = \"gpt-5\"\nbase_url = \n").expect_err("invalid toml");
+        let AppError::Localized { zh, en, .. } = err else {
+            panic!("expected localized error");
+        };
+        assert!(zh.contains("第 2 行"), "unexpected error: {zh}");
+        assert!(en.contains("line 2"), "unexpected error: {en}");
+    }
 }
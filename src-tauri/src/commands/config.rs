@@ -7,6 +7,9 @@ use tauri_plugin_opener::OpenerExt;
 use crate::app_config::AppType;
 use crate::codex_config;
 use crate::config::{self, get_claude_settings_path, ConfigStatus};
+use crate::services::{
+    ConfigService, IntegrityIssue, LiveFileStatus, LiveVerification, MigrationReport,
+};
 use crate::settings;
 
 #[tauri::command]
@@ -104,6 +107,57 @@ pub async fn get_config_status(app: String) -> Result<ConfigStatus, String> {
     }
 }
 
+/// 列出 CC Switch 管理的 live 配置文件及其状态（诊断用）
+#[tauri::command]
+pub async fn get_live_status() -> Result<Vec<LiveFileStatus>, String> {
+    ConfigService::live_status().map_err(|e| e.to_string())
+}
+
+/// 检查配置一致性，可选自动修复
+#[tauri::command]
+pub async fn check_config_integrity(
+    state: tauri::State<'_, crate::AppState>,
+    #[allow(non_snake_case)] autoRepair: Option<bool>,
+) -> Result<Vec<IntegrityIssue>, String> {
+    ConfigService::integrity_check(&state, autoRepair.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 校验 live 配置是否仍指向当前供应商（不一致时返回差异字段）
+#[tauri::command]
+pub async fn verify_live_config(
+    state: tauri::State<'_, crate::AppState>,
+    app: String,
+) -> Result<LiveVerification, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ConfigService::verify_live_matches_current(&state, app_type)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 修改配置目录覆盖后，将旧目录中的 skills 与 live 配置迁移到新目录
+#[tauri::command]
+pub async fn migrate_app_config_dir(
+    state: tauri::State<'_, crate::AppState>,
+    app: String,
+    oldDir: String,
+    newDir: String,
+    moveFiles: bool,
+    merge: bool,
+) -> Result<MigrationReport, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ConfigService::migrate_app_dir(
+        &state,
+        app_type,
+        std::path::Path::new(&oldDir),
+        std::path::Path::new(&newDir),
+        moveFiles,
+        merge,
+    )
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_claude_code_config_path() -> Result<String, String> {
     Ok(get_claude_settings_path().to_string_lossy().to_string())
@@ -345,9 +399,11 @@ mod tests {
 pub async fn extract_common_config_snippet(
     appType: String,
     settingsConfig: Option<String>,
+    preserveNonStringEnv: Option<bool>,
     state: tauri::State<'_, crate::store::AppState>,
 ) -> Result<String, String> {
     let app = AppType::from_str(&appType).map_err(|e| e.to_string())?;
+    let preserve_non_string_env = preserveNonStringEnv.unwrap_or(false);
 
     if let Some(settings_config) = settingsConfig.filter(|s| !s.trim().is_empty()) {
         let settings: serde_json::Value =
@@ -356,10 +412,15 @@ pub async fn extract_common_config_snippet(
         return crate::services::provider::ProviderService::extract_common_config_snippet_from_settings(
             app,
             &settings,
+            preserve_non_string_env,
         )
         .map_err(|e| e.to_string());
     }
 
-    crate::services::provider::ProviderService::extract_common_config_snippet(&state, app)
-        .map_err(|e| e.to_string())
+    crate::services::provider::ProviderService::extract_common_config_snippet(
+        &state,
+        app,
+        preserve_non_string_env,
+    )
+    .map_err(|e| e.to_string())
 }
@@ -1,15 +1,21 @@
 use crate::deeplink::{
     import_mcp_from_deeplink, import_prompt_from_deeplink, import_provider_from_deeplink,
-    import_skill_from_deeplink, parse_deeplink_url, DeepLinkImportRequest,
+    import_skill_from_deeplink, parse_deeplink_url, parse_share_code, DeepLinkImportRequest,
 };
 use crate::store::AppState;
 use tauri::State;
 
 /// Parse a deep link URL and return the parsed request for frontend confirmation
+///
+/// Also accepts provider share codes (scanned from a QR code).
 #[tauri::command]
 pub fn parse_deeplink(url: String) -> Result<DeepLinkImportRequest, String> {
     log::info!("Parsing deep link URL: {url}");
-    parse_deeplink_url(&url).map_err(|e| e.to_string())
+    if url.contains("://") {
+        parse_deeplink_url(&url).map_err(|e| e.to_string())
+    } else {
+        parse_share_code(&url).map_err(|e| e.to_string())
+    }
 }
 
 /// Merge configuration from Base64/URL into a deep link request
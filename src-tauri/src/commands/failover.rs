@@ -2,8 +2,9 @@
 //!
 //! 管理代理模式下的故障转移队列（基于 providers 表的 in_failover_queue 字段）
 
-use crate::database::FailoverQueueItem;
+use crate::database::{FailoverQueueItem, ProviderGroup};
 use crate::provider::Provider;
+use crate::proxy::types::RoutingMode;
 use crate::store::AppState;
 use std::str::FromStr;
 use tauri::Emitter;
@@ -169,3 +170,70 @@ pub async fn set_auto_failover_enabled(
 
     Ok(())
 }
+
+/// 获取指定应用的供应商选择策略
+#[tauri::command]
+pub async fn get_routing_strategy(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+) -> Result<RoutingMode, String> {
+    state
+        .db
+        .get_routing_strategy(&app_type)
+        .map_err(|e| e.to_string())
+}
+
+/// 设置指定应用的供应商选择策略（下一次请求起生效）
+#[tauri::command]
+pub async fn set_routing_strategy(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+    strategy: RoutingMode,
+) -> Result<(), String> {
+    log::info!(
+        "[Failover] Setting routing_strategy: app_type='{app_type}', strategy={}",
+        strategy.as_str()
+    );
+    state
+        .db
+        .set_routing_strategy(&app_type, strategy)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取故障转移分组
+#[tauri::command]
+pub async fn get_provider_groups(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+) -> Result<Vec<ProviderGroup>, String> {
+    state
+        .db
+        .get_provider_groups(&app_type)
+        .map_err(|e| e.to_string())
+}
+
+/// 新增或更新故障转移分组
+#[tauri::command]
+pub async fn save_provider_group(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+    group: ProviderGroup,
+) -> Result<(), String> {
+    state
+        .db
+        .save_provider_group(&app_type, &group)
+        .map_err(|e| e.to_string())
+}
+
+/// 删除故障转移分组
+#[tauri::command]
+pub async fn delete_provider_group(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+    name: String,
+) -> Result<bool, String> {
+    state
+        .db
+        .delete_provider_group(&app_type, &name)
+        .map_err(|e| e.to_string())
+}
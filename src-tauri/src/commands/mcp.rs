@@ -204,3 +204,11 @@ pub async fn import_mcp_from_apps(state: State<'_, AppState>) -> Result<usize, S
     total += McpService::import_from_opencode(&state).unwrap_or(0);
     Ok(total)
 }
+
+/// 从 Claude Desktop 导入 MCP 服务器，返回导入的 id 列表
+#[tauri::command]
+pub async fn import_mcp_from_claude_desktop(
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    McpService::import_from_claude_desktop(&state).map_err(|e| e.to_string())
+}
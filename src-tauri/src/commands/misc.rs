@@ -1,7 +1,8 @@
 #![allow(non_snake_case)]
 
 use crate::app_config::AppType;
-use crate::init_status::{InitErrorPayload, SkillsMigrationPayload};
+use crate::init_status::{InitErrorPayload, SafeModeStatus, SkillsMigrationPayload};
+use crate::secret_store::SecretStoreStatus;
 use crate::services::ProviderService;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -66,6 +67,12 @@ pub async fn get_init_error() -> Result<Option<InitErrorPayload>, String> {
     Ok(crate::init_status::get_init_error())
 }
 
+/// 获取当前使用的敏感信息存储后端（钥匙串不可用时为明文降级）。
+#[tauri::command]
+pub async fn secret_store_status() -> Result<SecretStoreStatus, String> {
+    Ok(crate::secret_store::secret_store().status())
+}
+
 /// 获取 JSON→SQLite 迁移结果（若有）。
 /// 只返回一次 true，之后返回 false，用于前端显示一次性 Toast 通知。
 #[tauri::command]
@@ -80,6 +87,13 @@ pub async fn get_skills_migration_result() -> Result<Option<SkillsMigrationPaylo
     Ok(crate::init_status::take_skills_migration_result())
 }
 
+/// 获取安全模式状态及本次启动跳过的步骤。
+/// 通过环境变量 `CC_SWITCH_SAFE_MODE=1` 或启动参数 `--safe-mode` 开启。
+#[tauri::command]
+pub async fn get_safe_mode_status() -> Result<SafeModeStatus, String> {
+    Ok(crate::init_status::get_safe_mode())
+}
+
 #[derive(serde::Serialize)]
 pub struct ToolVersion {
     name: String,
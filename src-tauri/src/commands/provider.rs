@@ -1,12 +1,15 @@
 use indexmap::IndexMap;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 use crate::app_config::AppType;
 use crate::commands::copilot::CopilotAuthState;
+use crate::database::{ProviderHistoryEntry, SwitchEvent};
 use crate::error::AppError;
-use crate::provider::Provider;
+use crate::provider::{CapabilityReport, Provider, ThroughputReport};
 use crate::services::{
-    EndpointLatency, ProviderService, ProviderSortUpdate, SpeedtestService, SwitchResult,
+    BroadcastResult, EndpointLatency, EndpointStatus, FieldDiff, LintWarning, ModelNormalizeResult,
+    NativeExport, ProviderImportResult, ProviderService, ProviderSortUpdate, SpeedtestService,
+    StaleProvider, SwitchResult, TokenFreshness, UrlChange,
 };
 use crate::store::AppState;
 use std::str::FromStr;
@@ -25,10 +28,16 @@ pub fn get_providers(
     ProviderService::list(state.inner(), app_type).map_err(|e| e.to_string())
 }
 
+/// 获取当前供应商 ID（prefer_healthy 为 true 时，当前供应商不健康则返回健康的故障转移替代者）
 #[tauri::command]
-pub fn get_current_provider(state: State<'_, AppState>, app: String) -> Result<String, String> {
+pub fn get_current_provider(
+    state: State<'_, AppState>,
+    app: String,
+    prefer_healthy: Option<bool>,
+) -> Result<String, String> {
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
-    ProviderService::current(state.inner(), app_type).map_err(|e| e.to_string())
+    ProviderService::current_with(state.inner(), app_type, prefer_healthy.unwrap_or(false))
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -94,12 +103,354 @@ pub fn switch_provider_test_hook(
 
 #[tauri::command]
 pub fn switch_provider(
+    app_handle: AppHandle,
     state: State<'_, AppState>,
     app: String,
     id: String,
 ) -> Result<SwitchResult, String> {
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
-    switch_provider_internal(&state, app_type, &id).map_err(|e| e.to_string())
+    let result =
+        switch_provider_internal(&state, app_type.clone(), &id).map_err(|e| e.to_string())?;
+    spawn_warm_up_after_switch(app_handle, app_type, id);
+    Ok(result)
+}
+
+/// 切换成功后按设置在后台预热新供应商（不阻塞切换结果）
+fn spawn_warm_up_after_switch(app_handle: AppHandle, app_type: AppType, id: String) {
+    if !crate::settings::get_warm_up_on_switch() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        if let Err(e) = ProviderService::warm_up_on_switch(&state, app_type, &id).await {
+            log::warn!("切换后预热供应商 {id} 失败: {e}");
+        }
+    });
+}
+
+/// 预热到供应商 base_url 的连接，返回是否成功
+#[tauri::command]
+pub async fn warm_up_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::warm_up(state.inner(), app_type, &id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 测试供应商的生成吞吐（tokens/s）与首 token 耗时，结果写入 meta.throughput
+#[tauri::command]
+pub async fn benchmark_provider_throughput(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    target_tokens: u32,
+) -> Result<ThroughputReport, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::benchmark_throughput(state.inner(), app_type, &id, target_tokens)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置跨设备默认供应商（不切换本设备、不写入 Live 配置）
+#[tauri::command]
+pub fn set_default_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_default_provider(&state, app_type, &id)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 导出供应商的原生配置文件（如 Claude settings.json）
+#[tauri::command]
+pub fn export_provider_native(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<NativeExport, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::export_native(&state, app_type, &id).map_err(|e| e.to_string())
+}
+
+/// 切换供应商并在终端中启动对应 CLI
+#[tauri::command]
+pub async fn switch_provider_and_launch(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    command: String,
+    cwd: Option<String>,
+    custom_config: Option<String>,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::switch_and_launch(
+        state.inner(),
+        app_type.clone(),
+        &id,
+        crate::commands::session_manager::preferred_session_terminal(),
+        command,
+        cwd,
+        custom_config,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    spawn_warm_up_after_switch(app_handle, app_type, id);
+    Ok(true)
+}
+
+/// 将供应商复制为另一个应用的供应商（仅迁移 base_url 与 API Key）
+#[tauri::command]
+pub fn convert_provider(
+    state: State<'_, AppState>,
+    from_app: String,
+    to_app: String,
+    id: String,
+    new_name: String,
+) -> Result<Provider, String> {
+    let from_app = AppType::from_str(&from_app).map_err(|e| e.to_string())?;
+    let to_app = AppType::from_str(&to_app).map_err(|e| e.to_string())?;
+    ProviderService::convert_provider(state.inner(), from_app, to_app, &id, &new_name)
+        .map_err(|e| e.to_string())
+}
+
+/// 将当前供应商通用配置中的共享字段广播到其他应用的当前供应商
+#[tauri::command]
+pub fn broadcast_common_config(
+    state: State<'_, AppState>,
+    from_app: String,
+    to_apps: Vec<String>,
+) -> Result<BroadcastResult, String> {
+    let from_app = AppType::from_str(&from_app).map_err(|e| e.to_string())?;
+    let to_apps = to_apps
+        .iter()
+        .map(|app| AppType::from_str(app).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    ProviderService::broadcast_common_config(state.inner(), from_app, to_apps)
+        .map_err(|e| e.to_string())
+}
+
+/// 从 .env 文件构建供应商（仅解析与校验，不保存）
+#[tauri::command]
+pub fn import_provider_from_env_file(
+    app: String,
+    path: String,
+    name: String,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::import_from_env_file(app_type, std::path::Path::new(&path), &name)
+        .map_err(|e| e.to_string())
+}
+
+/// 从 curl 命令构建供应商（仅解析与校验，不保存）
+#[tauri::command]
+pub fn import_provider_from_curl(
+    app: String,
+    curl: String,
+    name: String,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::import_from_curl(app_type, &curl, &name).map_err(|e| e.to_string())
+}
+
+/// 合并导入一组供应商（ID 已存在或内容重复的跳过）
+#[tauri::command]
+pub fn import_providers_merge(
+    state: State<'_, AppState>,
+    app: String,
+    providers: Vec<Provider>,
+) -> Result<ProviderImportResult, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::import_merge(state.inner(), app_type, providers).map_err(|e| e.to_string())
+}
+
+/// 列出超过 `max_age_days` 天未验证成功的供应商
+#[tauri::command]
+pub fn get_stale_providers(
+    state: State<'_, AppState>,
+    app: String,
+    max_age_days: u32,
+) -> Result<Vec<StaleProvider>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let max_age = std::time::Duration::from_secs(u64::from(max_age_days) * 24 * 60 * 60);
+    ProviderService::stale_providers(state.inner(), app_type, max_age).map_err(|e| e.to_string())
+}
+
+/// 按令牌格式判断供应商令牌是否可能已失效（空令牌、接管占位符、过期的 OAuth 令牌）
+#[tauri::command]
+pub fn check_token_freshness(
+    state: State<'_, AppState>,
+    app: String,
+    provider_id: String,
+) -> Result<TokenFreshness, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::check_token_freshness(state.inner(), app_type, &provider_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 探测供应商支持流式还是非流式请求，以及工具调用、系统提示词是否生效（结果写入 meta.capabilities）
+#[tauri::command]
+pub async fn probe_provider_capabilities(
+    state: State<'_, AppState>,
+    app: String,
+    provider_id: String,
+) -> Result<CapabilityReport, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::probe_capabilities(state.inner(), app_type, &provider_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 从官方 CLI 默认配置位置构建供应商（不保存，官方配置不存在时返回 null）
+#[tauri::command]
+pub fn import_official_config(app: String) -> Result<Option<Provider>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::import_official(app_type).map_err(|e| e.to_string())
+}
+
+/// 检查供应商配置中的常见错误（仅提示，不阻止保存）
+#[tauri::command]
+pub fn lint_provider(app: String, provider: Provider) -> Result<Vec<LintWarning>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    Ok(ProviderService::lint_provider(&app_type, &provider))
+}
+
+/// 检查并规范化所有供应商的 base_url（dry_run 时只返回修正建议，不保存）
+#[tauri::command]
+pub fn normalize_base_urls(
+    state: State<'_, AppState>,
+    app: String,
+    dry_run: bool,
+) -> Result<Vec<UrlChange>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::normalize_base_urls(state.inner(), app_type, dry_run)
+        .map_err(|e| e.to_string())
+}
+
+/// 预览将通用配置片段合并到指定供应商后的字段差异（不保存）
+#[tauri::command]
+pub fn preview_apply_common_config(
+    state: State<'_, AppState>,
+    app: String,
+    provider_id: String,
+    snippet: String,
+) -> Result<Vec<FieldDiff>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::preview_apply_common_config(state.inner(), app_type, &provider_id, &snippet)
+        .map_err(|e| e.to_string())
+}
+
+/// 将所有 Claude 供应商的旧版模型字段迁移为 DEFAULT_* 写法，返回修改的数量
+#[tauri::command]
+pub fn normalize_claude_models(state: State<'_, AppState>) -> Result<usize, String> {
+    ProviderService::normalize_all_claude_models(state.inner()).map_err(|e| e.to_string())
+}
+
+/// 同 `normalize_claude_models`，同时返回跳过的冻结供应商
+#[tauri::command]
+pub fn normalize_claude_models_report(
+    state: State<'_, AppState>,
+) -> Result<ModelNormalizeResult, String> {
+    ProviderService::normalize_all_claude_models_report(state.inner()).map_err(|e| e.to_string())
+}
+
+/// 导出供应商分享码（用于生成二维码，include_secret 为 false 时不含 API Key）
+#[tauri::command]
+pub fn export_provider_share_code(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    include_secret: bool,
+) -> Result<String, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::export_share_code(state.inner(), app_type, &id, include_secret)
+        .map_err(|e| e.to_string())
+}
+
+/// 格式化 Codex config.toml 文本（不写入文件，返回格式化结果供编辑器使用）
+#[tauri::command]
+#[allow(non_snake_case)]
+pub fn format_codex_config(configText: String) -> Result<String, String> {
+    ProviderService::format_codex_config(&configText).map_err(|e| e.to_string())
+}
+
+/// 启用或禁用供应商（禁用后不参与路由与故障转移）
+#[tauri::command]
+pub fn set_provider_enabled(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    enabled: bool,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_enabled(state.inner(), app_type, &id, enabled)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 冻结或解除冻结供应商（冻结后拒绝编辑与删除，仍可切换）
+#[tauri::command]
+pub fn set_provider_frozen(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    frozen: bool,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_frozen(state.inner(), app_type, &id, frozen).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取供应商配置历史（最新的在前）
+#[tauri::command]
+pub fn get_provider_history(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<Vec<ProviderHistoryEntry>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::list_history(state.inner(), app_type, &id).map_err(|e| e.to_string())
+}
+
+/// 将供应商配置恢复到指定历史快照
+#[tauri::command]
+pub fn revert_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    history_index: usize,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::revert_to(state.inner(), app_type, &id, history_index)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取最近的手动切换记录（最新的在前，默认 50 条）
+#[tauri::command]
+pub fn get_switch_events(
+    state: State<'_, AppState>,
+    app: String,
+    limit: Option<usize>,
+) -> Result<Vec<SwitchEvent>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    state
+        .db
+        .get_switch_events(app_type.as_str(), limit.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+/// 检查 live 配置是否在 CC Switch 之外被手动修改过
+#[tauri::command]
+pub fn live_has_unmanaged_changes(state: State<'_, AppState>, app: String) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::live_has_unmanaged_changes(state.inner(), app_type).map_err(|e| e.to_string())
 }
 
 fn import_default_config_internal(state: &AppState, app_type: AppType) -> Result<bool, AppError> {
@@ -111,7 +462,7 @@ fn import_default_config_internal(state: &AppState, app_type: AppType) -> Result
             .db
             .should_auto_extract_config_snippet(app_type.as_str())?
         {
-            match ProviderService::extract_common_config_snippet(state, app_type.clone()) {
+            match ProviderService::extract_common_config_snippet(state, app_type.clone(), false) {
                 Ok(snippet) if !snippet.is_empty() && snippet != "{}" => {
                     let _ = state
                         .db
@@ -144,6 +495,17 @@ pub fn import_default_config(state: State<'_, AppState>, app: String) -> Result<
     import_default_config_internal(&state, app_type).map_err(Into::into)
 }
 
+/// 将当前 live 配置采用为供应商并设为当前（不重写 live 文件）
+#[tauri::command]
+pub fn adopt_current_live(
+    state: State<'_, AppState>,
+    app: String,
+    name: String,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::adopt_current_live(&state, app_type, &name).map_err(|e| e.to_string())
+}
+
 #[allow(non_snake_case)]
 #[tauri::command]
 pub async fn queryProviderUsage(
@@ -250,12 +612,64 @@ pub fn read_live_provider_settings(app: String) -> Result<serde_json::Value, Str
     ProviderService::read_live_settings(app_type).map_err(|e| e.to_string())
 }
 
+/// 端点测速；传入 `app` 与 `providerId` 时把结果追加到该供应商的端点测速历史
 #[tauri::command]
 pub async fn test_api_endpoints(
+    state: State<'_, AppState>,
     urls: Vec<String>,
     #[allow(non_snake_case)] timeoutSecs: Option<u64>,
+    app: Option<String>,
+    #[allow(non_snake_case)] providerId: Option<String>,
 ) -> Result<Vec<EndpointLatency>, String> {
-    SpeedtestService::test_endpoints(urls, timeoutSecs)
+    let results = SpeedtestService::test_endpoints(urls, timeoutSecs)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let (Some(app), Some(provider_id)) = (app, providerId) {
+        let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+        // 历史记录失败不影响测速结果
+        if let Err(e) = ProviderService::record_endpoint_latencies(
+            state.inner(),
+            app_type,
+            &provider_id,
+            &results,
+        ) {
+            log::warn!("记录端点测速历史失败: {e}");
+        }
+    }
+
+    Ok(results)
+}
+
+/// 端点延迟趋势（按时间从旧到新，用于迷你折线图）
+#[tauri::command]
+pub fn get_endpoint_latency_trend(
+    state: State<'_, AppState>,
+    app: String,
+    #[allow(non_snake_case)] providerId: String,
+    url: String,
+    limit: Option<usize>,
+) -> Result<Vec<crate::database::EndpointLatencySample>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::endpoint_latency_trend(
+        state.inner(),
+        app_type,
+        &providerId,
+        &url,
+        limit.unwrap_or(crate::database::ENDPOINT_LATENCY_HISTORY_LIMIT),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 并发探测供应商全部自定义端点的可达性（按延迟从快到慢）
+#[tauri::command]
+pub async fn get_endpoint_matrix(
+    state: State<'_, AppState>,
+    app: String,
+    #[allow(non_snake_case)] providerId: String,
+) -> Result<Vec<EndpointStatus>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    SpeedtestService::endpoint_matrix(state.inner(), app_type, &providerId)
         .await
         .map_err(|e| e.to_string())
 }
@@ -2,10 +2,12 @@
 //!
 //! 提供前端调用的 API 接口
 
+use crate::app_config::AppType;
 use crate::error::AppError;
 use crate::proxy::types::*;
 use crate::proxy::{CircuitBreakerConfig, CircuitBreakerStats};
 use crate::store::AppState;
+use std::str::FromStr;
 
 /// 启动代理服务器（仅启动服务，不接管 Live 配置）
 #[tauri::command]
@@ -21,6 +23,12 @@ pub async fn stop_proxy_with_restore(state: tauri::State<'_, AppState>) -> Resul
     state.proxy_service.stop_with_restore().await
 }
 
+/// 将代理恢复为默认状态（停止服务器、恢复 Live 配置、重置代理配置与健康状态）
+#[tauri::command]
+pub async fn factory_reset_proxy(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.proxy_service.factory_reset_proxy().await
+}
+
 /// 获取各应用接管状态
 #[tauri::command]
 pub async fn get_proxy_takeover_status(
@@ -259,6 +267,34 @@ pub async fn switch_proxy_provider(
         .await
 }
 
+/// 测量代理额外增加的延迟（需代理运行中）
+#[tauri::command]
+pub async fn benchmark_proxy_overhead(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+    provider_id: String,
+    samples: Option<usize>,
+) -> Result<OverheadReport, String> {
+    let app_type = AppType::from_str(&app_type).map_err(|e| e.to_string())?;
+    state
+        .proxy_service
+        .benchmark_overhead(&app_type, &provider_id, samples.unwrap_or(10))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 接管自检：在临时目录中对 Live 配置副本执行 备份 → 接管 → 恢复（不触碰真实 Live 文件）
+#[tauri::command]
+pub async fn proxy_takeover_self_test(
+    state: tauri::State<'_, AppState>,
+) -> Result<TakeoverTestReport, String> {
+    state
+        .proxy_service
+        .takeover_self_test()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ==================== 故障转移相关命令 ====================
 
 /// 获取供应商健康状态
@@ -274,6 +310,46 @@ pub async fn get_provider_health(
         .map_err(|e| e.to_string())
 }
 
+/// 清除单个供应商的健康状态（同时重置其熔断器）
+#[tauri::command]
+pub async fn clear_provider_health(
+    state: tauri::State<'_, AppState>,
+    provider_id: String,
+    app_type: String,
+) -> Result<(), String> {
+    state
+        .proxy_service
+        .clear_provider_health(&provider_id, &app_type)
+        .await
+}
+
+/// 暂停/恢复使用量记录（可选自动恢复时长，单位秒）
+#[tauri::command]
+pub async fn set_logging_paused(
+    state: tauri::State<'_, AppState>,
+    paused: bool,
+    duration_secs: Option<u64>,
+) -> Result<(), String> {
+    state
+        .proxy_service
+        .set_logging_paused(paused, duration_secs)
+        .await
+}
+
+/// 订阅实时请求流（之后每条请求记录后发出 `proxy-request-logged` 事件）
+#[tauri::command]
+pub async fn subscribe_proxy_requests(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.proxy_service.set_request_feed_subscribed(true);
+    Ok(())
+}
+
+/// 取消订阅实时请求流
+#[tauri::command]
+pub async fn unsubscribe_proxy_requests(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.proxy_service.set_request_feed_subscribed(false);
+    Ok(())
+}
+
 /// 重置熔断器
 ///
 /// 重置后会检查是否应该切回队列中优先级更高的供应商：
@@ -288,7 +364,7 @@ pub async fn reset_circuit_breaker(
 ) -> Result<(), String> {
     // 1. 重置数据库健康状态
     let db = &state.db;
-    db.update_provider_health(&provider_id, &app_type, true, None)
+    db.update_provider_health(&provider_id, &app_type, true, None, None)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -33,16 +33,7 @@ pub async fn launch_session_terminal(
     let command = command.clone();
     let cwd = cwd.clone();
     let custom_config = custom_config.clone();
-
-    // Read preferred terminal from global settings
-    let preferred = crate::settings::get_preferred_terminal();
-    // Map global setting terminal names to session terminal names
-    // Global uses "iterm2", session terminal uses "iterm"
-    let target = match preferred.as_deref() {
-        Some("iterm2") => "iterm".to_string(),
-        Some(t) => t.to_string(),
-        None => "terminal".to_string(), // Default to Terminal.app on macOS
-    };
+    let target = preferred_session_terminal();
 
     tauri::async_runtime::spawn_blocking(move || {
         session_manager::terminal::launch_terminal(
@@ -58,6 +49,16 @@ pub async fn launch_session_terminal(
     Ok(true)
 }
 
+/// Read preferred terminal from global settings, mapped to session terminal names
+pub(crate) fn preferred_session_terminal() -> String {
+    // Global uses "iterm2", session terminal uses "iterm"
+    match crate::settings::get_preferred_terminal().as_deref() {
+        Some("iterm2") => "iterm".to_string(),
+        Some(t) => t.to_string(),
+        None => "terminal".to_string(), // Default to Terminal.app on macOS
+    }
+}
+
 #[tauri::command]
 pub async fn delete_session(
     providerId: String,
@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 
-use tauri::AppHandle;
+use crate::store::AppState;
+use tauri::{AppHandle, State};
 
 fn merge_settings_for_save(
     mut incoming: crate::settings::AppSettings,
@@ -20,9 +21,17 @@ pub async fn get_settings() -> Result<crate::settings::AppSettings, String> {
 
 /// 保存设置
 #[tauri::command]
-pub async fn save_settings(settings: crate::settings::AppSettings) -> Result<bool, String> {
+pub async fn save_settings(
+    state: State<'_, AppState>,
+    settings: crate::settings::AppSettings,
+) -> Result<bool, String> {
     let existing = crate::settings::get_settings();
     let merged = merge_settings_for_save(settings, &existing);
+    // 清除或更换独立使用日志库时先把数据迁回主库，否则历史日志会留在不再挂载的文件中；
+    // 新路径在下次启动时挂载并迁入
+    if merged.resolved_usage_db_path() != existing.resolved_usage_db_path() {
+        state.db.detach_usage_db().map_err(|e| e.to_string())?;
+    }
     crate::settings::update_settings(merged).map_err(|e| e.to_string())?;
     Ok(true)
 }
@@ -193,7 +202,7 @@ pub async fn set_log_config(
         .db
         .set_log_config(&config)
         .map_err(|e| e.to_string())?;
-    log::set_max_level(config.to_level_filter());
+    crate::log_filter::apply(&config);
     log::info!(
         "日志配置已更新: enabled={}, level={}",
         config.enabled,
@@ -201,3 +210,49 @@ pub async fn set_log_config(
     );
     Ok(true)
 }
+
+/// 导出全部设置（用于问题排查，`redact` 为 true 时隐藏密钥类信息）
+#[tauri::command]
+pub async fn dump_settings(
+    state: tauri::State<'_, crate::AppState>,
+    redact: bool,
+) -> Result<serde_json::Value, String> {
+    crate::services::SettingsService::dump(&state.db, redact)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置单个模块的日志级别（`level` 为空时移除该模块的覆盖）
+#[tauri::command]
+pub async fn set_log_level(
+    state: tauri::State<'_, crate::AppState>,
+    module: String,
+    level: String,
+) -> Result<bool, String> {
+    let module = module.trim().trim_end_matches("::").to_string();
+    if module.is_empty() {
+        return Err("模块路径不能为空".to_string());
+    }
+
+    let mut config = state.db.get_log_config().map_err(|e| e.to_string())?;
+    let level = level.trim().to_lowercase();
+    if level.is_empty() {
+        config.module_levels.remove(&module);
+    } else {
+        if crate::proxy::types::parse_level_filter(&level).is_none() {
+            return Err(format!("无效的日志级别: {level}"));
+        }
+        config.module_levels.insert(module.clone(), level.clone());
+    }
+
+    state
+        .db
+        .set_log_config(&config)
+        .map_err(|e| e.to_string())?;
+    crate::log_filter::apply(&config);
+    log::info!(
+        "模块日志级别已更新: {module}={}",
+        if level.is_empty() { "默认" } else { &level }
+    );
+    Ok(true)
+}
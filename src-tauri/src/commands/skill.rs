@@ -7,8 +7,8 @@
 use crate::app_config::{AppType, InstalledSkill, UnmanagedSkill};
 use crate::error::format_skill_error;
 use crate::services::skill::{
-    DiscoverableSkill, ImportSkillSelection, Skill, SkillBackupEntry, SkillRepo, SkillService,
-    SkillUninstallResult,
+    DiscoverableSkill, ImportSkillSelection, Skill, SkillBackupEntry, SkillManifest,
+    SkillManifestApplyResult, SkillRepo, SkillService, SkillUninstallResult, SyncMethod,
 };
 use crate::store::AppState;
 use std::sync::Arc;
@@ -101,6 +101,64 @@ pub fn toggle_skill_app(
     Ok(true)
 }
 
+/// 设置 Skill 单独的同步方式（method 为空时跟随全局设置）
+#[tauri::command]
+pub fn set_skill_sync_method(
+    id: String,
+    method: Option<SyncMethod>,
+    app_state: State<'_, AppState>,
+) -> Result<bool, String> {
+    SkillService::set_sync_method_override(&app_state.db, &id, method)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 批量设置应用启用的 Skills（启用集合恰好为给定目录列表）
+#[tauri::command]
+pub fn set_app_skills(
+    app: String,
+    directories: Vec<String>,
+    app_state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let app_type = parse_app_type(&app)?;
+    SkillService::set_app_skills(&app_state.db, &app_type, directories)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 从 SKILL.md 刷新单个 Skill 的名称和描述，返回是否有变化
+#[tauri::command]
+pub fn refresh_skill_metadata(id: String, app_state: State<'_, AppState>) -> Result<bool, String> {
+    SkillService::refresh_metadata(&app_state.db, &id).map_err(|e| e.to_string())
+}
+
+/// 刷新所有 Skill 的元数据，返回有变化的 Skill id
+#[tauri::command]
+pub fn refresh_all_skill_metadata(app_state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    SkillService::refresh_all_metadata(&app_state.db).map_err(|e| e.to_string())
+}
+
+/// 导出已安装 Skills 清单
+#[tauri::command]
+pub fn export_skill_manifest(app_state: State<'_, AppState>) -> Result<SkillManifest, String> {
+    SkillService::export_manifest(&app_state.db).map_err(|e| e.to_string())
+}
+
+/// 应用 Skills 清单（可选安装缺失的 Skill，并按清单设置各应用启用状态）
+#[tauri::command]
+pub async fn apply_skill_manifest(
+    manifest: SkillManifest,
+    install_missing: bool,
+    service: State<'_, SkillServiceState>,
+    app_state: State<'_, AppState>,
+) -> Result<SkillManifestApplyResult, String> {
+    service
+        .0
+        .apply_manifest(&app_state.db, &manifest, install_missing)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 扫描未管理的 Skills
 #[tauri::command]
 pub fn scan_unmanaged_skills(
@@ -31,6 +31,17 @@ pub fn get_provider_stats(state: State<'_, AppState>) -> Result<Vec<ProviderStat
     state.db.get_provider_stats()
 }
 
+/// 按花费从高到低列出供应商
+#[tauri::command]
+pub fn get_providers_by_spend(
+    state: State<'_, AppState>,
+    app_type: Option<String>,
+    start_ts: Option<i64>,
+    end_ts: Option<i64>,
+) -> Result<Vec<ProviderSpend>, AppError> {
+    state.db.get_providers_by_spend(app_type, start_ts, end_ts)
+}
+
 /// 获取模型统计
 #[tauri::command]
 pub fn get_model_stats(state: State<'_, AppState>) -> Result<Vec<ModelStats>, AppError> {
@@ -57,6 +68,38 @@ pub fn get_request_detail(
     state.db.get_request_detail(&request_id)
 }
 
+/// 获取同一会话的全部请求日志
+#[tauri::command]
+pub fn get_session_logs(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<Vec<RequestLogDetail>, AppError> {
+    state.db.get_session_logs(&session_id)
+}
+
+/// 汇总同一会话的费用与 Token 用量
+#[tauri::command]
+pub fn get_session_cost(
+    state: State<'_, AppState>,
+    session_id: String,
+) -> Result<SessionCost, AppError> {
+    state.db.get_session_cost(&session_id)
+}
+
+/// 导出同一会话的请求日志到 JSON 文件，返回导出的请求数
+#[tauri::command]
+pub fn export_session_logs(
+    state: State<'_, AppState>,
+    session_id: String,
+    file_path: String,
+) -> Result<u32, AppError> {
+    let export = state.db.export_session_logs(&session_id)?;
+    let content = serde_json::to_string_pretty(&export)
+        .map_err(|e| AppError::Message(format!("序列化会话日志失败: {e}")))?;
+    crate::config::write_text_file(std::path::Path::new(&file_path), &content)?;
+    Ok(export.request_count)
+}
+
 /// 获取模型定价列表
 #[tauri::command]
 pub fn get_model_pricing(state: State<'_, AppState>) -> Result<Vec<ModelPricingInfo>, AppError> {
@@ -82,7 +125,8 @@ pub fn get_model_pricing(state: State<'_, AppState>) -> Result<Vec<ModelPricingI
 
     let mut stmt = conn.prepare(
         "SELECT model_id, display_name, input_cost_per_million, output_cost_per_million,
-                cache_read_cost_per_million, cache_creation_cost_per_million
+                cache_read_cost_per_million, cache_creation_cost_per_million,
+                reasoning_cost_per_million
          FROM model_pricing
          ORDER BY display_name",
     )?;
@@ -95,6 +139,7 @@ pub fn get_model_pricing(state: State<'_, AppState>) -> Result<Vec<ModelPricingI
             output_cost_per_million: row.get(3)?,
             cache_read_cost_per_million: row.get(4)?,
             cache_creation_cost_per_million: row.get(5)?,
+            reasoning_cost_per_million: row.get(6)?,
         })
     })?;
 
@@ -109,6 +154,7 @@ pub fn get_model_pricing(state: State<'_, AppState>) -> Result<Vec<ModelPricingI
 
 /// 更新模型定价
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_model_pricing(
     state: State<'_, AppState>,
     model_id: String,
@@ -117,25 +163,38 @@ pub fn update_model_pricing(
     output_cost: String,
     cache_read_cost: String,
     cache_creation_cost: String,
+    reasoning_cost: Option<String>,
 ) -> Result<(), AppError> {
     let db = state.db.clone();
-    let conn = crate::database::lock_conn!(db.conn);
 
-    conn.execute(
-        "INSERT OR REPLACE INTO model_pricing (
-            model_id, display_name, input_cost_per_million, output_cost_per_million,
-            cache_read_cost_per_million, cache_creation_cost_per_million
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        rusqlite::params![
-            model_id,
-            display_name,
-            input_cost,
-            output_cost,
-            cache_read_cost,
-            cache_creation_cost
-        ],
-    )
-    .map_err(|e| AppError::Database(format!("更新模型定价失败: {e}")))?;
+    // 空字符串视为未配置，推理 tokens 按输出价格计费
+    let reasoning_cost = reasoning_cost.filter(|v| !v.trim().is_empty());
+
+    {
+        let conn = crate::database::lock_conn!(db.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO model_pricing (
+                model_id, display_name, input_cost_per_million, output_cost_per_million,
+                cache_read_cost_per_million, cache_creation_cost_per_million,
+                reasoning_cost_per_million
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                model_id,
+                display_name,
+                input_cost,
+                output_cost,
+                cache_read_cost,
+                cache_creation_cost,
+                reasoning_cost
+            ],
+        )
+        .map_err(|e| AppError::Database(format!("更新模型定价失败: {e}")))?;
+    }
+
+    // 定价变化后补算此前因缺少定价而记为 0 的日志成本
+    if let Err(e) = db.backfill_all_zero_cost_logs() {
+        log::warn!("补算请求日志成本失败: {e}");
+    }
 
     Ok(())
 }
@@ -150,6 +209,39 @@ pub fn check_provider_limits(
     state.db.check_provider_limits(&provider_id, &app_type)
 }
 
+/// 发送前预估请求成本（按模型定价与供应商倍率计算）
+#[tauri::command]
+pub async fn estimate_request_cost(
+    state: State<'_, AppState>,
+    model: String,
+    input_tokens: u32,
+    expected_output_tokens: u32,
+    provider_id: String,
+    app_type: String,
+) -> Result<CostEstimate, AppError> {
+    let cost = state
+        .db
+        .estimate_cost(
+            &model,
+            input_tokens,
+            expected_output_tokens,
+            &provider_id,
+            &app_type,
+        )
+        .await?;
+    Ok(CostEstimate::from(&cost))
+}
+
+/// 删除指定时间范围内的请求日志（Unix 秒，闭区间），返回删除条数
+#[tauri::command]
+pub fn delete_request_logs_between(
+    state: State<'_, AppState>,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<usize, AppError> {
+    state.db.delete_request_logs_between(start_ts, end_ts)
+}
+
 /// 删除模型定价
 #[tauri::command]
 pub fn delete_model_pricing(state: State<'_, AppState>, model_id: String) -> Result<(), AppError> {
@@ -176,4 +268,7 @@ pub struct ModelPricingInfo {
     pub output_cost_per_million: String,
     pub cache_read_cost_per_million: String,
     pub cache_creation_cost_per_million: String,
+    /// 推理 tokens 单价，未配置时按输出价格计费
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_cost_per_million: Option<String>,
 }
@@ -46,7 +46,8 @@ pub fn get_default_claude_mcp_path() -> PathBuf {
     get_home_dir().join(".claude.json")
 }
 
-fn derive_mcp_path_from_override(dir: &Path) -> Option<PathBuf> {
+/// 覆盖目录对应的 Claude MCP 配置文件：与目录同级的 `<目录名>.json`
+pub(crate) fn derive_mcp_path_from_override(dir: &Path) -> Option<PathBuf> {
     let file_name = dir
         .file_name()
         .map(|name| name.to_string_lossy().to_string())?
@@ -69,6 +70,23 @@ pub fn get_claude_mcp_path() -> PathBuf {
     get_default_claude_mcp_path()
 }
 
+/// 获取 Claude Desktop 配置文件路径（claude_desktop_config.json）
+///
+/// - macOS: `~/Library/Application Support/Claude`
+/// - Windows: `%APPDATA%\Claude`（按主目录推导，便于测试隔离）
+/// - Linux: `~/.config/Claude`
+pub fn get_claude_desktop_config_path() -> PathBuf {
+    let home = get_home_dir();
+    let base = if cfg!(target_os = "macos") {
+        home.join("Library").join("Application Support")
+    } else if cfg!(windows) {
+        home.join("AppData").join("Roaming")
+    } else {
+        home.join(".config")
+    };
+    base.join("Claude").join("claude_desktop_config.json")
+}
+
 /// 获取 Claude Code 主配置文件路径
 pub fn get_claude_settings_path() -> PathBuf {
     let dir = get_claude_config_dir();
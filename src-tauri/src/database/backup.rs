@@ -139,6 +139,9 @@ impl Database {
                 .map_err(|e| AppError::Database(e.to_string()))?;
         }
 
+        // 导入的表结构会在主库中重建使用统计表，需移回独立使用日志库
+        self.relocate_usage_tables()?;
+
         let backup_id = backup_path
             .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
             .unwrap_or_default();
@@ -592,6 +595,7 @@ impl Database {
         // Step 3: Run schema migrations (backup may be from an older version)
         self.create_tables()?;
         self.apply_schema_migrations()?;
+        self.relocate_usage_tables()?;
         self.ensure_model_pricing_seeded()?;
 
         log::info!("Database restored from backup: {filename}, safety backup: {safety_id}");
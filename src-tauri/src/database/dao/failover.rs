@@ -45,13 +45,13 @@ impl Database {
         Ok(items)
     }
 
-    /// 获取故障转移队列中的供应商（完整 Provider 信息，按顺序）
+    /// 获取故障转移队列中已启用的供应商（完整 Provider 信息，按顺序）
     pub fn get_failover_providers(&self, app_type: &str) -> Result<Vec<Provider>, AppError> {
         let all_providers = self.get_all_providers(app_type)?;
 
         let result: Vec<Provider> = all_providers
             .into_values()
-            .filter(|p| p.in_failover_queue)
+            .filter(|p| p.in_failover_queue && p.enabled)
             .collect();
 
         Ok(result)
@@ -5,14 +5,25 @@
 pub mod failover;
 pub mod mcp;
 pub mod prompts;
+pub mod provider_groups;
 pub mod providers;
 pub mod proxy;
 pub mod settings;
 pub mod skills;
 pub mod stream_check;
+pub mod switch_events;
 pub mod universal_providers;
 pub mod usage_rollup;
 
 // 所有 DAO 方法都通过 Database impl 提供，无需单独导出
 // 导出 FailoverQueueItem 供外部使用
 pub use failover::FailoverQueueItem;
+// 导出 ProviderGroup 供分组路由使用
+pub use provider_groups::ProviderGroup;
+// 导出 ProviderHistoryEntry 供历史记录/撤销使用，EndpointLatencySample 供端点测速历史使用
+pub use providers::{
+    EndpointLatencySample, ProviderHistoryEntry, ENDPOINT_LATENCY_HISTORY_LIMIT,
+    PROVIDER_HISTORY_LIMIT,
+};
+// 导出 SwitchEvent 供切换历史查询使用
+pub use switch_events::{SwitchEvent, SWITCH_EVENT_LIMIT};
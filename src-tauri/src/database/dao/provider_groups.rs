@@ -0,0 +1,102 @@
+//! 供应商分组 DAO
+//!
+//! 故障转移分组（如 "cheap"、"premium"），供应商通过 `meta.group` 归属分组，
+//! 代理按请求头 `x-cc-group` 在对应分组的故障转移队列内路由。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+/// 供应商分组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderGroup {
+    pub name: String,
+    /// 默认分组：请求未指定分组（或指定的分组不存在）时使用，未设置分组的供应商也归入此组
+    pub is_default: bool,
+}
+
+impl Database {
+    /// 获取指定应用的全部分组（默认分组在前）
+    pub fn get_provider_groups(&self, app_type: &str) -> Result<Vec<ProviderGroup>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT name, is_default FROM provider_groups
+                 WHERE app_type = ?1
+                 ORDER BY is_default DESC, name ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let groups = stmt
+            .query_map([app_type], |row| {
+                Ok(ProviderGroup {
+                    name: row.get(0)?,
+                    is_default: row.get::<_, i64>(1)? != 0,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(groups)
+    }
+
+    /// 新增或更新分组；设为默认时取消其他分组的默认标记
+    pub fn save_provider_group(
+        &self,
+        app_type: &str,
+        group: &ProviderGroup,
+    ) -> Result<(), AppError> {
+        let name = group.name.trim();
+        if name.is_empty() {
+            return Err(AppError::localized(
+                "provider.group.empty_name",
+                "分组名称不能为空",
+                "Group name must not be empty",
+            ));
+        }
+
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        if group.is_default {
+            tx.execute(
+                "UPDATE provider_groups SET is_default = 0 WHERE app_type = ?1",
+                [app_type],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        tx.execute(
+            "INSERT INTO provider_groups (app_type, name, is_default, created_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(app_type, name) DO UPDATE SET is_default = excluded.is_default",
+            rusqlite::params![
+                app_type,
+                name,
+                group.is_default,
+                chrono::Utc::now().timestamp()
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 删除分组（供应商上的 meta.group 保留，分组不存在时按默认分组路由）
+    pub fn delete_provider_group(&self, app_type: &str, name: &str) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute(
+                "DELETE FROM provider_groups WHERE app_type = ?1 AND name = ?2",
+                rusqlite::params![app_type, name],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
+}
@@ -1,10 +1,38 @@
+use crate::app_config::AppType;
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
 use crate::provider::{Provider, ProviderMeta};
 use indexmap::IndexMap;
 use rusqlite::params;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// 每个供应商最多保留的配置历史条数
+pub const PROVIDER_HISTORY_LIMIT: usize = 20;
+
+/// 每个端点最多保留的测速样本数
+pub const ENDPOINT_LATENCY_HISTORY_LIMIT: usize = 100;
+
+/// 供应商配置历史快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHistoryEntry {
+    pub id: i64,
+    pub settings_config: serde_json::Value,
+    pub created_at: i64,
+}
+
+/// 端点测速样本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointLatencySample {
+    /// 延迟（毫秒），测速失败时为 None
+    pub latency_ms: Option<u64>,
+    pub status: Option<u16>,
+    /// 测速时间（Unix 毫秒）
+    pub tested_at: i64,
+}
+
 type OmoProviderRow = (
     String,
     String,
@@ -23,7 +51,7 @@ impl Database {
     ) -> Result<IndexMap<String, Provider>, AppError> {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn.prepare(
-            "SELECT id, name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
+            "SELECT id, name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue, enabled
              FROM providers WHERE app_type = ?1
              ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC"
         ).map_err(|e| AppError::Database(e.to_string()))?;
@@ -42,6 +70,7 @@ impl Database {
                 let icon_color: Option<String> = row.get(9)?;
                 let meta_str: String = row.get(10)?;
                 let in_failover_queue: bool = row.get(11)?;
+                let enabled: bool = row.get(12)?;
 
                 let settings_config =
                     serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
@@ -62,6 +91,7 @@ impl Database {
                         icon,
                         icon_color,
                         in_failover_queue,
+                        enabled,
                     },
                 ))
             })
@@ -134,7 +164,7 @@ impl Database {
     ) -> Result<Option<Provider>, AppError> {
         let conn = lock_conn!(self.conn);
         let result = conn.query_row(
-            "SELECT name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
+            "SELECT name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue, enabled
              FROM providers WHERE id = ?1 AND app_type = ?2",
             params![id, app_type],
             |row| {
@@ -149,6 +179,7 @@ impl Database {
                 let icon_color: Option<String> = row.get(8)?;
                 let meta_str: String = row.get(9)?;
                 let in_failover_queue: bool = row.get(10)?;
+                let enabled: bool = row.get(11)?;
 
                 let settings_config = serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
                 let meta: ProviderMeta = serde_json::from_str(&meta_str).unwrap_or_default();
@@ -166,6 +197,7 @@ impl Database {
                     icon,
                     icon_color,
                     in_failover_queue,
+                    enabled,
                 })
             },
         );
@@ -186,19 +218,28 @@ impl Database {
         let mut meta_clone = provider.meta.clone().unwrap_or_default();
         let endpoints = std::mem::take(&mut meta_clone.custom_endpoints);
 
-        let existing: Option<(bool, bool)> = tx
+        let existing: Option<(bool, bool, String)> = tx
             .query_row(
-                "SELECT is_current, in_failover_queue FROM providers WHERE id = ?1 AND app_type = ?2",
+                "SELECT is_current, in_failover_queue, settings_config FROM providers WHERE id = ?1 AND app_type = ?2",
                 params![provider.id, app_type],
-                |row| Ok((row.get(0)?, row.get(1)?)),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
             )
             .ok();
 
         let is_update = existing.is_some();
-        let (is_current, in_failover_queue) =
-            existing.unwrap_or((false, provider.in_failover_queue));
+        let (is_current, in_failover_queue, previous_settings) =
+            existing.unwrap_or((false, provider.in_failover_queue, String::new()));
 
         if is_update {
+            let new_settings = serde_json::to_string(&provider.settings_config).map_err(|e| {
+                AppError::Database(format!("Failed to serialize settings_config: {e}"))
+            })?;
+
+            // 配置有变化时保存编辑前的快照，便于撤销
+            if previous_settings != new_settings {
+                Self::push_provider_history(&tx, app_type, &provider.id, &previous_settings)?;
+            }
+
             tx.execute(
                 "UPDATE providers SET
                     name = ?1,
@@ -216,9 +257,7 @@ impl Database {
                 WHERE id = ?13 AND app_type = ?14",
                 params![
                     provider.name,
-                    serde_json::to_string(&provider.settings_config).map_err(|e| {
-                        AppError::Database(format!("Failed to serialize settings_config: {e}"))
-                    })?,
+                    new_settings,
                     provider.website_url,
                     provider.category,
                     provider.created_at,
@@ -240,8 +279,8 @@ impl Database {
             tx.execute(
                 "INSERT INTO providers (
                     id, app_type, name, settings_config, website_url, category,
-                    created_at, sort_index, notes, icon, icon_color, meta, is_current, in_failover_queue
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    created_at, sort_index, notes, icon, icon_color, meta, is_current, in_failover_queue, enabled
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
                 params![
                     provider.id,
                     app_type,
@@ -259,6 +298,7 @@ impl Database {
                         .map_err(|e| AppError::Database(format!("Failed to serialize meta: {e}")))?,
                     is_current,
                     in_failover_queue,
+                    provider.enabled,
                 ],
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -277,6 +317,86 @@ impl Database {
         Ok(())
     }
 
+    /// 写入一条历史快照，并裁剪超出 [`PROVIDER_HISTORY_LIMIT`] 的旧记录
+    fn push_provider_history(
+        conn: &rusqlite::Connection,
+        app_type: &str,
+        provider_id: &str,
+        settings_config: &str,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "INSERT INTO provider_history (provider_id, app_type, settings_config, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                provider_id,
+                app_type,
+                settings_config,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        conn.execute(
+            "DELETE FROM provider_history
+             WHERE provider_id = ?1 AND app_type = ?2 AND id NOT IN (
+                 SELECT id FROM provider_history
+                 WHERE provider_id = ?1 AND app_type = ?2
+                 ORDER BY id DESC LIMIT ?3
+             )",
+            params![provider_id, app_type, PROVIDER_HISTORY_LIMIT as i64],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 获取供应商配置历史（最新的在前，下标 0 为最近一次编辑前的配置）
+    pub fn get_provider_history(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<Vec<ProviderHistoryEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, settings_config, created_at FROM provider_history
+                 WHERE provider_id = ?1 AND app_type = ?2
+                 ORDER BY id DESC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(params![provider_id, app_type], |row| {
+                let settings_config_str: String = row.get(1)?;
+                Ok(ProviderHistoryEntry {
+                    id: row.get(0)?,
+                    settings_config: serde_json::from_str(&settings_config_str)
+                        .unwrap_or(serde_json::Value::Null),
+                    created_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 设置供应商启用状态（编辑保存时不会覆盖该字段）
+    pub fn set_provider_enabled(
+        &self,
+        app_type: &str,
+        id: &str,
+        enabled: bool,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute(
+                "UPDATE providers SET enabled = ?1 WHERE id = ?2 AND app_type = ?3",
+                params![enabled, id, app_type],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
+
     pub fn delete_provider(&self, app_type: &str, id: &str) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
         conn.execute(
@@ -309,6 +429,87 @@ impl Database {
         Ok(())
     }
 
+    /// 修复 is_current 标记：每个应用只保留实际生效的当前供应商
+    ///
+    /// 迁移异常可能让同一应用出现多行 `is_current = 1`（或一行都没有），此时 `get_current_provider`
+    /// 读到哪一行不确定。以 settings 中记录的当前供应商为准（不存在时保留标记中排序最前的一行），
+    /// 清除其余标记，返回被修正的行数。
+    /// 恰好一行时不做修改：数据库中的跨设备默认供应商可以与本设备的当前供应商不同。
+    /// OpenCode/OpenClaw 为累加模式，is_current 按 OMO 分类区分，不在修复范围内。
+    pub fn repair_current_flags(&self) -> Result<usize, AppError> {
+        let mut fixed = 0;
+        for app_type in AppType::all().filter(|app_type| !app_type.is_additive_mode()) {
+            let preferred = crate::settings::get_current_provider(&app_type);
+            fixed += self.repair_current_flags_for(app_type.as_str(), preferred.as_deref())?;
+        }
+        Ok(fixed)
+    }
+
+    pub(crate) fn repair_current_flags_for(
+        &self,
+        app_type: &str,
+        preferred: Option<&str>,
+    ) -> Result<usize, AppError> {
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let (ids, current): (Vec<String>, Vec<String>) = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT id, is_current FROM providers WHERE app_type = ?1
+                     ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC",
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let rows = stmt
+                .query_map(params![app_type], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+                })
+                .map_err(|e| AppError::Database(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let current = rows
+                .iter()
+                .filter(|(_, is_current)| *is_current)
+                .map(|(id, _)| id.clone())
+                .collect();
+            (rows.into_iter().map(|(id, _)| id).collect(), current)
+        };
+
+        if current.len() == 1 {
+            return Ok(0);
+        }
+        let keep = preferred
+            .filter(|id| ids.iter().any(|existing| existing == *id))
+            .or_else(|| current.first().map(String::as_str));
+        let Some(keep) = keep else {
+            return Ok(0);
+        };
+
+        let cleared = tx
+            .execute(
+                "UPDATE providers SET is_current = 0
+                 WHERE app_type = ?1 AND is_current = 1 AND id != ?2",
+                params![app_type, keep],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let marked = tx
+            .execute(
+                "UPDATE providers SET is_current = 1
+                 WHERE app_type = ?1 AND id = ?2 AND is_current = 0",
+                params![app_type, keep],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+
+        log::warn!(
+            "[{app_type}] 已修复 is_current 标记，保留 {keep}（修正 {} 行）",
+            cleared + marked
+        );
+        Ok(cleared + marked)
+    }
+
     pub fn update_provider_settings_config(
         &self,
         app_type: &str,
@@ -360,6 +561,168 @@ impl Database {
         Ok(())
     }
 
+    /// 追加一条端点测速样本，并裁剪超出 [`ENDPOINT_LATENCY_HISTORY_LIMIT`] 的旧样本
+    pub fn append_endpoint_latency(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        sample: &EndpointLatencySample,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO endpoint_latency_history (provider_id, app_type, url, latency_ms, status, tested_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                provider_id,
+                app_type,
+                url,
+                sample.latency_ms.map(|v| v as i64),
+                sample.status,
+                sample.tested_at
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        conn.execute(
+            "DELETE FROM endpoint_latency_history
+             WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3 AND id NOT IN (
+                 SELECT id FROM endpoint_latency_history
+                 WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3
+                 ORDER BY id DESC LIMIT ?4
+             )",
+            params![
+                provider_id,
+                app_type,
+                url,
+                ENDPOINT_LATENCY_HISTORY_LIMIT as i64
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 获取端点最近 `limit` 条测速样本（按时间从旧到新）
+    pub fn get_endpoint_latency_history(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        limit: usize,
+    ) -> Result<Vec<EndpointLatencySample>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT latency_ms, status, tested_at FROM (
+                     SELECT id, latency_ms, status, tested_at FROM endpoint_latency_history
+                     WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3
+                     ORDER BY id DESC LIMIT ?4
+                 ) ORDER BY id ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![provider_id, app_type, url, limit as i64], |row| {
+                Ok(EndpointLatencySample {
+                    latency_ms: row.get::<_, Option<i64>>(0)?.map(|v| v.max(0) as u64),
+                    status: row.get(1)?,
+                    tested_at: row.get(2)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 获取指定应用下各供应商最近 `window` 条成功测速的平均延迟（毫秒，跨端点合并）
+    ///
+    /// 没有成功测速样本的供应商不会出现在结果中
+    pub fn get_recent_average_latencies(
+        &self,
+        app_type: &str,
+        window: usize,
+    ) -> Result<HashMap<String, f64>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT provider_id, AVG(latency_ms) FROM (
+                     SELECT provider_id, latency_ms,
+                            ROW_NUMBER() OVER (PARTITION BY provider_id ORDER BY id DESC) AS rn
+                     FROM endpoint_latency_history
+                     WHERE app_type = ?1 AND latency_ms IS NOT NULL
+                 ) WHERE rn <= ?2 GROUP BY provider_id",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![app_type, window as i64], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 记录供应商最近一次验证成功的时间（Unix 秒）
+    pub fn set_provider_last_verified(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        verified_at: i64,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "UPDATE providers SET last_verified_at = ?1 WHERE id = ?2 AND app_type = ?3",
+            params![verified_at, provider_id, app_type],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 获取指定应用下所有供应商的最近验证时间（从未验证为 None）
+    pub fn get_providers_last_verified(
+        &self,
+        app_type: &str,
+    ) -> Result<HashMap<String, Option<i64>>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare("SELECT id, last_verified_at FROM providers WHERE app_type = ?1")
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![app_type], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 列出所属供应商已不存在的自定义端点（id, app_type, provider_id, url）
+    ///
+    /// 正常情况下外键级联删除会清理端点，但关闭外键约束的 SQL 导入/同步可能留下孤儿记录。
+    pub fn list_orphaned_endpoints(&self) -> Result<Vec<(i64, String, String, String)>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.id, e.app_type, e.provider_id, e.url FROM provider_endpoints e
+                 LEFT JOIN providers p ON p.id = e.provider_id AND p.app_type = e.app_type
+                 WHERE p.id IS NULL
+                 ORDER BY e.id",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 删除指定 id 的自定义端点
+    pub fn delete_endpoint_by_id(&self, id: i64) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM provider_endpoints WHERE id = ?1", params![id])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
     pub fn set_omo_provider_current(
         &self,
         app_type: &str,
@@ -499,6 +862,7 @@ impl Database {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }))
     }
 }
@@ -3,6 +3,7 @@
 //! 处理代理配置、Provider健康状态和使用统计的数据库操作
 
 use crate::error::AppError;
+use crate::proxy::app_detect::parse_target_app;
 use crate::proxy::types::*;
 use rust_decimal::Decimal;
 
@@ -377,6 +378,16 @@ impl Database {
         Ok(())
     }
 
+    /// 将三行代理配置恢复为 schema 默认值（代理开关、监听地址、超时、熔断与计费设置）
+    pub async fn reset_proxy_config_to_defaults(&self) -> Result<(), AppError> {
+        {
+            let conn = lock_conn!(self.conn);
+            conn.execute("DELETE FROM proxy_config", [])
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        self.init_proxy_config_rows().await
+    }
+
     // ==================== Legacy Proxy Config (兼容旧代码) ====================
 
     /// 获取代理配置（兼容旧接口，返回 claude 行的配置）
@@ -387,7 +398,9 @@ impl Database {
             conn.query_row(
                 "SELECT listen_address, listen_port, max_retries,
                         enable_logging,
-                        streaming_first_byte_timeout, streaming_idle_timeout, non_streaming_timeout
+                        streaming_first_byte_timeout, streaming_idle_timeout, non_streaming_timeout,
+                        enable_response_cache, lightweight_streaming, max_concurrent_requests,
+                        listen_socket_path, target_app
                  FROM proxy_config WHERE app_type = 'claude'",
                 [],
                 |row| {
@@ -401,6 +414,14 @@ impl Database {
                         streaming_first_byte_timeout: row.get::<_, i32>(4).unwrap_or(60) as u64,
                         streaming_idle_timeout: row.get::<_, i32>(5).unwrap_or(120) as u64,
                         non_streaming_timeout: row.get::<_, i32>(6).unwrap_or(600) as u64,
+                        enable_response_cache: row.get::<_, i32>(7).unwrap_or(0) != 0,
+                        lightweight_streaming: row.get::<_, i32>(8).unwrap_or(0) != 0,
+                        max_concurrent_requests: row.get::<_, i64>(9).unwrap_or(0).max(0) as u32,
+                        listen_socket_path: row
+                            .get::<_, Option<String>>(10)
+                            .unwrap_or(None)
+                            .filter(|path| !path.trim().is_empty()),
+                        target_app: parse_target_app(&row.get::<_, String>(11).unwrap_or_default()),
                     })
                 },
             )
@@ -432,6 +453,11 @@ impl Database {
                 streaming_first_byte_timeout = ?5,
                 streaming_idle_timeout = ?6,
                 non_streaming_timeout = ?7,
+                enable_response_cache = ?8,
+                lightweight_streaming = ?9,
+                max_concurrent_requests = ?10,
+                listen_socket_path = ?11,
+                target_app = ?12,
                 updated_at = datetime('now')",
             rusqlite::params![
                 config.listen_address,
@@ -441,6 +467,15 @@ impl Database {
                 config.streaming_first_byte_timeout as i32,
                 config.streaming_idle_timeout as i32,
                 config.non_streaming_timeout as i32,
+                if config.enable_response_cache { 1 } else { 0 },
+                if config.lightweight_streaming { 1 } else { 0 },
+                config.max_concurrent_requests as i64,
+                config
+                    .listen_socket_path
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty()),
+                config.target_app.as_str(),
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -483,7 +518,8 @@ impl Database {
 
             conn.query_row(
                 "SELECT provider_id, app_type, is_healthy, consecutive_failures,
-                        last_success_at, last_failure_at, last_error, updated_at
+                        last_success_at, last_failure_at, last_error, updated_at,
+                        last_error_kind, warmed_at
                  FROM provider_health
                  WHERE provider_id = ?1 AND app_type = ?2",
                 rusqlite::params![provider_id, app_type],
@@ -496,7 +532,11 @@ impl Database {
                         last_success_at: row.get(4)?,
                         last_failure_at: row.get(5)?,
                         last_error: row.get(6)?,
+                        last_error_kind: row
+                            .get::<_, Option<String>>(8)?
+                            .map(|kind| ProviderErrorKind::parse(&kind)),
                         updated_at: row.get(7)?,
+                        warmed_at: row.get(9)?,
                     })
                 },
             )
@@ -513,7 +553,9 @@ impl Database {
                 last_success_at: None,
                 last_failure_at: None,
                 last_error: None,
+                last_error_kind: None,
                 updated_at: chrono::Utc::now().to_rfc3339(),
+                warmed_at: None,
             }),
             Err(e) => Err(AppError::Database(e.to_string())),
         }
@@ -528,15 +570,24 @@ impl Database {
         app_type: &str,
         success: bool,
         error_msg: Option<String>,
+        error_kind: Option<ProviderErrorKind>,
     ) -> Result<(), AppError> {
         // 默认阈值与 CircuitBreakerConfig::default() 保持一致
-        self.update_provider_health_with_threshold(provider_id, app_type, success, error_msg, 5)
-            .await
+        self.update_provider_health_with_threshold(
+            provider_id,
+            app_type,
+            success,
+            error_msg,
+            error_kind,
+            5,
+        )
+        .await
     }
 
     /// 更新Provider健康状态（带阈值参数）
     ///
     /// # Arguments
+    /// * `error_kind` - 失败原因类别，供前端区分“修正密钥”与“等待恢复”
     /// * `failure_threshold` - 连续失败多少次后标记为不健康
     pub async fn update_provider_health_with_threshold(
         &self,
@@ -544,6 +595,7 @@ impl Database {
         app_type: &str,
         success: bool,
         error_msg: Option<String>,
+        error_kind: Option<ProviderErrorKind>,
         failure_threshold: u32,
     ) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
@@ -579,13 +631,16 @@ impl Database {
         conn.execute(
             "INSERT OR REPLACE INTO provider_health
              (provider_id, app_type, is_healthy, consecutive_failures,
-              last_success_at, last_failure_at, last_error, updated_at)
+              last_success_at, last_failure_at, last_error, updated_at, last_error_kind,
+              warmed_at)
              VALUES (?1, ?2, ?3, ?4,
                      COALESCE(?5, (SELECT last_success_at FROM provider_health
                                    WHERE provider_id = ?1 AND app_type = ?2)),
                      COALESCE(?6, (SELECT last_failure_at FROM provider_health
                                    WHERE provider_id = ?1 AND app_type = ?2)),
-                     ?7, ?8)",
+                     ?7, ?8, ?9,
+                     (SELECT warmed_at FROM provider_health
+                      WHERE provider_id = ?1 AND app_type = ?2))",
             rusqlite::params![
                 provider_id,
                 app_type,
@@ -595,6 +650,7 @@ impl Database {
                 last_failure_at,
                 error_msg,
                 &now,
+                error_kind.map(|kind| kind.as_str()),
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -602,8 +658,28 @@ impl Database {
         Ok(())
     }
 
-    /// 重置Provider健康状态
-    pub async fn reset_provider_health(
+    /// 记录一次成功的连接预热（不改变健康判定与失败计数）
+    pub async fn record_provider_warm_up(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO provider_health (provider_id, app_type, updated_at, warmed_at)
+             VALUES (?1, ?2, ?3, ?3)
+             ON CONFLICT(provider_id, app_type) DO UPDATE SET warmed_at = excluded.warmed_at",
+            rusqlite::params![provider_id, app_type, now],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 清除单个Provider的健康状态（删除记录后视为健康）
+    pub async fn clear_provider_health(
         &self,
         provider_id: &str,
         app_type: &str,
@@ -616,7 +692,7 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-        log::debug!("Reset health status for provider {provider_id} (app: {app_type})");
+        log::debug!("Cleared health status for provider {provider_id} (app: {app_type})");
 
         Ok(())
     }
@@ -913,4 +989,34 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_clear_provider_health_only_affects_one_provider() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        for provider_id in ["p1", "p2"] {
+            for _ in 0..5 {
+                db.update_provider_health(provider_id, "claude", false, Some("502".into()), None)
+                    .await?;
+            }
+        }
+        db.update_provider_health("p1", "codex", false, Some("502".into()), None)
+            .await?;
+
+        db.clear_provider_health("p1", "claude").await?;
+
+        let cleared = db.get_provider_health("p1", "claude").await?;
+        assert!(cleared.is_healthy);
+        assert_eq!(cleared.consecutive_failures, 0);
+        assert!(cleared.last_error.is_none());
+
+        let other = db.get_provider_health("p2", "claude").await?;
+        assert!(!other.is_healthy);
+        assert_eq!(other.consecutive_failures, 5);
+
+        let other_app = db.get_provider_health("p1", "codex").await?;
+        assert_eq!(other_app.consecutive_failures, 1);
+
+        Ok(())
+    }
 }
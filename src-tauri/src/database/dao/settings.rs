@@ -44,6 +44,19 @@ impl Database {
         Ok(())
     }
 
+    /// 获取全部设置（按 key 排序）
+    pub fn get_all_settings(&self) -> Result<Vec<(String, String)>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM settings ORDER BY key")
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
     // --- 通用配置片段 (Common Config Snippet) ---
 
     /// 获取通用配置片段
@@ -224,6 +237,33 @@ impl Database {
         Ok(())
     }
 
+    // --- 路由策略 ---
+
+    /// 获取指定应用的供应商选择策略（未设置或无法识别时使用默认策略）
+    pub fn get_routing_strategy(
+        &self,
+        app_type: &str,
+    ) -> Result<crate::proxy::types::RoutingMode, AppError> {
+        let key = format!("routing_strategy_{app_type}");
+        Ok(match self.get_setting(&key)? {
+            Some(value) => crate::proxy::types::RoutingMode::parse(&value).unwrap_or_else(|| {
+                log::warn!("[{app_type}] 无法识别的路由策略: {value}，使用默认策略");
+                Default::default()
+            }),
+            None => Default::default(),
+        })
+    }
+
+    /// 设置指定应用的供应商选择策略
+    pub fn set_routing_strategy(
+        &self,
+        app_type: &str,
+        mode: crate::proxy::types::RoutingMode,
+    ) -> Result<(), AppError> {
+        let key = format!("routing_strategy_{app_type}");
+        self.set_setting(&key, mode.as_str())
+    }
+
     // --- 整流器配置 ---
 
     /// 获取整流器配置
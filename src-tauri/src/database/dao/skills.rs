@@ -9,7 +9,7 @@
 use crate::app_config::{InstalledSkill, SkillApps};
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
-use crate::services::skill::SkillRepo;
+use crate::services::skill::{SkillRepo, SyncMethod};
 use indexmap::IndexMap;
 use rusqlite::params;
 
@@ -22,7 +22,8 @@ impl Database {
         let mut stmt = conn
             .prepare(
                 "SELECT id, name, description, directory, repo_owner, repo_name, repo_branch,
-                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at
+                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at,
+                        sync_method_override
                  FROM skills ORDER BY name ASC",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -45,6 +46,10 @@ impl Database {
                         opencode: row.get(11)?,
                     },
                     installed_at: row.get(12)?,
+                    sync_method_override: row
+                        .get::<_, Option<String>>(13)?
+                        .as_deref()
+                        .and_then(SyncMethod::parse),
                 })
             })
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -63,7 +68,8 @@ impl Database {
         let mut stmt = conn
             .prepare(
                 "SELECT id, name, description, directory, repo_owner, repo_name, repo_branch,
-                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at
+                        readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at,
+                        sync_method_override
                  FROM skills WHERE id = ?1",
             )
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -85,6 +91,10 @@ impl Database {
                     opencode: row.get(11)?,
                 },
                 installed_at: row.get(12)?,
+                sync_method_override: row
+                    .get::<_, Option<String>>(13)?
+                    .as_deref()
+                    .and_then(SyncMethod::parse),
             })
         });
 
@@ -101,8 +111,9 @@ impl Database {
         conn.execute(
             "INSERT OR REPLACE INTO skills
              (id, name, description, directory, repo_owner, repo_name, repo_branch,
-              readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+              readme_url, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, installed_at,
+              sync_method_override)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 skill.id,
                 skill.name,
@@ -117,6 +128,7 @@ impl Database {
                 skill.apps.gemini,
                 skill.apps.opencode,
                 skill.installed_at,
+                skill.sync_method_override.map(SyncMethod::as_str),
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -152,6 +164,39 @@ impl Database {
         Ok(affected > 0)
     }
 
+    /// 更新 Skill 的名称与描述（从 SKILL.md 刷新元数据时使用）
+    pub fn update_skill_metadata(
+        &self,
+        id: &str,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute(
+                "UPDATE skills SET name = ?1, description = ?2 WHERE id = ?3",
+                params![name, description, id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
+
+    /// 更新 Skill 单独的同步方式（None 表示跟随全局设置）
+    pub fn update_skill_sync_method(
+        &self,
+        id: &str,
+        method: Option<SyncMethod>,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute(
+                "UPDATE skills SET sync_method_override = ?1 WHERE id = ?2",
+                params![method.map(SyncMethod::as_str), id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
+
     // ========== SkillRepo CRUD（保持原有） ==========
 
     /// 获取所有 Skill 仓库
@@ -5,7 +5,7 @@ use crate::error::AppError;
 use crate::services::stream_check::{StreamCheckConfig, StreamCheckResult};
 
 impl Database {
-    /// 保存流式检查日志
+    /// 保存流式检查日志（检查成功时同时更新供应商的 last_verified_at）
     pub fn save_stream_check_log(
         &self,
         provider_id: &str,
@@ -35,8 +35,15 @@ impl Database {
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
+        let log_id = conn.last_insert_rowid();
+        drop(conn);
 
-        Ok(conn.last_insert_rowid())
+        // 检查成功即视为供应商已验证
+        if result.success {
+            self.set_provider_last_verified(app_type, provider_id, result.tested_at)?;
+        }
+
+        Ok(log_id)
     }
 
     /// 获取流式检查配置
@@ -0,0 +1,97 @@
+//! 供应商切换事件 DAO
+//!
+//! 记录用户手动切换供应商的审计日志（故障转移属于自动切换，不在此记录）。
+//! 不与 providers 表建立外键，供应商删除后历史记录仍然保留。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// 每个应用最多保留的切换事件条数
+pub const SWITCH_EVENT_LIMIT: usize = 500;
+
+/// 一次供应商切换
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchEvent {
+    pub id: i64,
+    pub app_type: String,
+    /// 切换前的供应商，首次切换时为 None
+    pub from_id: Option<String>,
+    pub to_id: String,
+    /// 切换方式：`live`（写入 Live 配置）或 `hot_switch`（代理接管下热切换）
+    pub mode: String,
+    /// 切换时间（Unix 毫秒）
+    pub ts: i64,
+}
+
+impl Database {
+    /// 记录一次切换事件，并裁剪超出 [`SWITCH_EVENT_LIMIT`] 的旧记录
+    pub fn record_switch_event(
+        &self,
+        app_type: &str,
+        from_id: Option<&str>,
+        to_id: &str,
+        mode: &str,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO switch_events (app_type, from_id, to_id, mode, ts)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                app_type,
+                from_id,
+                to_id,
+                mode,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        conn.execute(
+            "DELETE FROM switch_events
+             WHERE app_type = ?1 AND id NOT IN (
+                 SELECT id FROM switch_events
+                 WHERE app_type = ?1
+                 ORDER BY id DESC LIMIT ?2
+             )",
+            params![app_type, SWITCH_EVENT_LIMIT as i64],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 获取最近的切换事件（最新的在前）
+    pub fn get_switch_events(
+        &self,
+        app_type: &str,
+        limit: usize,
+    ) -> Result<Vec<SwitchEvent>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, app_type, from_id, to_id, mode, ts FROM switch_events
+                 WHERE app_type = ?1
+                 ORDER BY id DESC
+                 LIMIT ?2",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(params![app_type, limit as i64], |row| {
+                Ok(SwitchEvent {
+                    id: row.get(0)?,
+                    app_type: row.get(1)?,
+                    from_id: row.get(2)?,
+                    to_id: row.get(3)?,
+                    mode: row.get(4)?,
+                    ts: row.get(5)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+}
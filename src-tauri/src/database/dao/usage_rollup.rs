@@ -7,7 +7,8 @@ use crate::error::AppError;
 
 impl Database {
     /// Aggregate proxy_request_logs older than `retain_days` into usage_daily_rollups,
-    /// then delete the aggregated detail rows.
+    /// then delete the aggregated detail rows. Shadow requests are pruned without
+    /// being aggregated.
     /// Returns the number of deleted detail rows.
     pub fn rollup_and_prune(&self, retain_days: i64) -> Result<u64, AppError> {
         let cutoff = chrono::Utc::now().timestamp() - retain_days * 86400;
@@ -86,7 +87,7 @@ impl Database {
                     COALESCE(SUM(cache_creation_tokens), 0) as new_cc,
                     COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0) as new_cost,
                     COALESCE(AVG(latency_ms), 0) as new_lat
-                FROM proxy_request_logs WHERE created_at < ?1
+                FROM proxy_request_logs WHERE created_at < ?1 AND is_shadow = 0
                 GROUP BY d, a, p, m
             ) agg
             LEFT JOIN usage_daily_rollups old
@@ -142,10 +143,18 @@ mod tests {
                     rusqlite::params![format!("recent-{i}"), recent_ts + i as i64],
                 )?;
             }
+            conn.execute(
+                "INSERT INTO proxy_request_logs (
+                    request_id, provider_id, app_type, model,
+                    input_tokens, output_tokens, total_cost_usd,
+                    latency_ms, status_code, is_shadow, created_at
+                ) VALUES ('old-shadow', 'p1', 'claude', 'claude-3', 100, 50, '0.01', 100, 200, 1, ?1)",
+                [old_ts],
+            )?;
         }
 
         let deleted = db.rollup_and_prune(30)?;
-        assert_eq!(deleted, 5);
+        assert_eq!(deleted, 6);
 
         // Verify rollup data
         let conn = crate::database::lock_conn!(db.conn);
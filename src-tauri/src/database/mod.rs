@@ -15,6 +15,7 @@
 //! ├── schema.rs     - 表结构定义 + Schema 迁移
 //! ├── backup.rs     - SQL 导入导出 + 快照备份
 //! ├── migration.rs  - JSON → SQLite 数据迁移
+//! ├── usage_store.rs - 独立使用日志库（ATTACH）
 //! └── dao/          - 数据访问对象
 //!     ├── providers.rs
 //!     ├── mcp.rs
@@ -27,12 +28,16 @@ pub(crate) mod backup;
 mod dao;
 mod migration;
 mod schema;
+mod usage_store;
 
 #[cfg(test)]
 mod tests;
 
 // DAO 类型导出供外部使用
-pub use dao::FailoverQueueItem;
+pub use dao::{
+    EndpointLatencySample, FailoverQueueItem, ProviderGroup, ProviderHistoryEntry, SwitchEvent,
+    ENDPOINT_LATENCY_HISTORY_LIMIT, PROVIDER_HISTORY_LIMIT, SWITCH_EVENT_LIMIT,
+};
 
 use crate::config::get_app_config_dir;
 use crate::error::AppError;
@@ -44,7 +49,7 @@ use std::sync::Mutex;
 
 /// 当前 Schema 版本号
 /// 每次修改表结构时递增，并在 schema.rs 中添加相应的迁移逻辑
-pub(crate) const SCHEMA_VERSION: i32 = 6;
+pub(crate) const SCHEMA_VERSION: i32 = 27;
 
 /// 安全地序列化 JSON，避免 unwrap panic
 pub(crate) fn to_json_string<T: Serialize>(value: &T) -> Result<String, AppError> {
@@ -112,6 +117,20 @@ impl Database {
         let db = Self {
             conn: Mutex::new(conn),
         };
+        // 独立使用日志库须在建表与迁移前挂载，迁移才能作用到其中的历史日志
+        let usage_db_mounted = match crate::settings::get_usage_db_path() {
+            Some(path) => match db.mount_usage_db(&path) {
+                Ok(()) => true,
+                Err(e) => {
+                    // 挂载失败时继续使用主库记录日志，不阻止启动
+                    log::warn!(
+                        "Failed to attach usage database, keeping usage logs in main db: {e}"
+                    );
+                    false
+                }
+            },
+            None => false,
+        };
         db.create_tables()?;
 
         // Pre-migration backup: only when upgrading from an existing database
@@ -133,6 +152,15 @@ impl Database {
         if let Err(e) = db.ensure_incremental_auto_vacuum() {
             log::warn!("Failed to ensure incremental auto-vacuum: {e}");
         }
+        if usage_db_mounted {
+            // 首次挂载时主库中仍有使用统计表，迁入独立库
+            match db.relocate_usage_tables() {
+                Ok(moved) => {
+                    log::info!("Usage database attached ({moved} rows moved from main db)")
+                }
+                Err(e) => log::warn!("Failed to move usage tables into usage database: {e}"),
+            }
+        }
         db.ensure_model_pricing_seeded()?;
 
         // Startup cleanup: prune old logs and reclaim space
@@ -2,7 +2,7 @@
 //!
 //! 负责数据库表结构的创建和版本迁移。
 
-use super::{lock_conn, Database, SCHEMA_VERSION};
+use super::{lock_conn, usage_store::USAGE_DB_SCHEMA, Database, SCHEMA_VERSION};
 use crate::error::AppError;
 use rusqlite::{params, Connection};
 use serde::Serialize;
@@ -39,6 +39,8 @@ impl Database {
                 meta TEXT NOT NULL DEFAULT '{}',
                 is_current BOOLEAN NOT NULL DEFAULT 0,
                 in_failover_queue BOOLEAN NOT NULL DEFAULT 0,
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                last_verified_at INTEGER,
                 PRIMARY KEY (id, app_type)
             )",
             [],
@@ -93,7 +95,8 @@ impl Database {
             enabled_codex BOOLEAN NOT NULL DEFAULT 0,
             enabled_gemini BOOLEAN NOT NULL DEFAULT 0,
             enabled_opencode BOOLEAN NOT NULL DEFAULT 0,
-            installed_at INTEGER NOT NULL DEFAULT 0
+            installed_at INTEGER NOT NULL DEFAULT 0,
+            sync_method_override TEXT
         )",
             [],
         )
@@ -129,6 +132,11 @@ impl Database {
             circuit_min_requests INTEGER NOT NULL DEFAULT 10,
             default_cost_multiplier TEXT NOT NULL DEFAULT '1',
             pricing_model_source TEXT NOT NULL DEFAULT 'response',
+            enable_response_cache INTEGER NOT NULL DEFAULT 0,
+            lightweight_streaming INTEGER NOT NULL DEFAULT 0,
+            max_concurrent_requests INTEGER NOT NULL DEFAULT 0,
+            listen_socket_path TEXT,
+            target_app TEXT NOT NULL DEFAULT 'codex',
             created_at TEXT NOT NULL DEFAULT (datetime('now')), updated_at TEXT NOT NULL DEFAULT (datetime('now'))
         )", []).map_err(|e| AppError::Database(e.to_string()))?;
 
@@ -171,56 +179,18 @@ impl Database {
         conn.execute("CREATE TABLE IF NOT EXISTS provider_health (
             provider_id TEXT NOT NULL, app_type TEXT NOT NULL, is_healthy INTEGER NOT NULL DEFAULT 1,
             consecutive_failures INTEGER NOT NULL DEFAULT 0, last_success_at TEXT, last_failure_at TEXT,
-            last_error TEXT, updated_at TEXT NOT NULL,
+            last_error TEXT, updated_at TEXT NOT NULL, last_error_kind TEXT, warmed_at TEXT,
             PRIMARY KEY (provider_id, app_type),
             FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
         )", []).map_err(|e| AppError::Database(e.to_string()))?;
 
-        // 10. Proxy Request Logs 表
-        conn.execute("CREATE TABLE IF NOT EXISTS proxy_request_logs (
-            request_id TEXT PRIMARY KEY, provider_id TEXT NOT NULL, app_type TEXT NOT NULL, model TEXT NOT NULL,
-            request_model TEXT,
-            input_tokens INTEGER NOT NULL DEFAULT 0, output_tokens INTEGER NOT NULL DEFAULT 0,
-            cache_read_tokens INTEGER NOT NULL DEFAULT 0, cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
-            input_cost_usd TEXT NOT NULL DEFAULT '0', output_cost_usd TEXT NOT NULL DEFAULT '0',
-            cache_read_cost_usd TEXT NOT NULL DEFAULT '0', cache_creation_cost_usd TEXT NOT NULL DEFAULT '0',
-            total_cost_usd TEXT NOT NULL DEFAULT '0', latency_ms INTEGER NOT NULL, first_token_ms INTEGER,
-            duration_ms INTEGER, status_code INTEGER NOT NULL, error_message TEXT, session_id TEXT,
-            provider_type TEXT, is_streaming INTEGER NOT NULL DEFAULT 0,
-            cost_multiplier TEXT NOT NULL DEFAULT '1.0', created_at INTEGER NOT NULL
-        )", []).map_err(|e| AppError::Database(e.to_string()))?;
-
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_request_logs_provider ON proxy_request_logs(provider_id, app_type)", [])
-            .map_err(|e| AppError::Database(e.to_string()))?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_request_logs_created_at ON proxy_request_logs(created_at)", [])
-            .map_err(|e| AppError::Database(e.to_string()))?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_request_logs_model ON proxy_request_logs(model)",
-            [],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_request_logs_session ON proxy_request_logs(session_id)",
-            [],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_request_logs_status ON proxy_request_logs(status_code)",
-            [],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
-
-        // 11. Model Pricing 表
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS model_pricing (
-            model_id TEXT PRIMARY KEY, display_name TEXT NOT NULL,
-            input_cost_per_million TEXT NOT NULL, output_cost_per_million TEXT NOT NULL,
-            cache_read_cost_per_million TEXT NOT NULL DEFAULT '0',
-            cache_creation_cost_per_million TEXT NOT NULL DEFAULT '0'
-        )",
-            [],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        // 10-11, 17. 使用统计相关表（已挂载独立的使用日志库时建在其中，见 usage_store.rs）
+        let usage_schema = if Self::is_usage_db_attached(conn)? {
+            USAGE_DB_SCHEMA
+        } else {
+            "main"
+        };
+        Self::create_usage_tables(conn, usage_schema)?;
 
         // 12. Stream Check Logs 表
         conn.execute("CREATE TABLE IF NOT EXISTS stream_check_logs (
@@ -248,26 +218,17 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
-        // 17. Usage Daily Rollups 表 (日聚合统计)
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS usage_daily_rollups (
-                date TEXT NOT NULL,
-                app_type TEXT NOT NULL,
-                provider_id TEXT NOT NULL,
-                model TEXT NOT NULL,
-                request_count INTEGER NOT NULL DEFAULT 0,
-                success_count INTEGER NOT NULL DEFAULT 0,
-                input_tokens INTEGER NOT NULL DEFAULT 0,
-                output_tokens INTEGER NOT NULL DEFAULT 0,
-                cache_read_tokens INTEGER NOT NULL DEFAULT 0,
-                cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
-                total_cost_usd TEXT NOT NULL DEFAULT '0',
-                avg_latency_ms INTEGER NOT NULL DEFAULT 0,
-                PRIMARY KEY (date, app_type, provider_id, model)
-            )",
-            [],
-        )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        // 18. Provider History 表 (供应商配置历史快照)
+        Self::create_provider_history_table(conn)?;
+
+        // 19. Provider Groups 表 (故障转移分组)
+        Self::create_provider_groups_table(conn)?;
+
+        // 20. Endpoint Latency History 表 (端点测速历史)
+        Self::create_endpoint_latency_history_table(conn)?;
+
+        // 21. Switch Events 表 (手动切换供应商审计日志)
+        Self::create_switch_events_table(conn)?;
 
         // 尝试添加 live_takeover_active 列到 proxy_config 表
         let _ = conn.execute(
@@ -393,6 +354,111 @@ impl Database {
                         Self::migrate_v5_to_v6(conn)?;
                         Self::set_user_version(conn, 6)?;
                     }
+                    6 => {
+                        log::info!("迁移数据库从 v6 到 v7（推理 tokens 计费支持）");
+                        Self::migrate_v6_to_v7(conn)?;
+                        Self::set_user_version(conn, 7)?;
+                    }
+                    7 => {
+                        log::info!("迁移数据库从 v7 到 v8（供应商配置历史）");
+                        Self::migrate_v7_to_v8(conn)?;
+                        Self::set_user_version(conn, 8)?;
+                    }
+                    8 => {
+                        log::info!("迁移数据库从 v8 到 v9（供应商启用开关）");
+                        Self::migrate_v8_to_v9(conn)?;
+                        Self::set_user_version(conn, 9)?;
+                    }
+                    9 => {
+                        log::info!("迁移数据库从 v9 到 v10（健康状态错误类别）");
+                        Self::migrate_v9_to_v10(conn)?;
+                        Self::set_user_version(conn, 10)?;
+                    }
+                    10 => {
+                        log::info!("迁移数据库从 v10 到 v11（响应缓存）");
+                        Self::migrate_v10_to_v11(conn)?;
+                        Self::set_user_version(conn, 11)?;
+                    }
+                    11 => {
+                        log::info!("迁移数据库从 v11 到 v12（供应商分组）");
+                        Self::migrate_v11_to_v12(conn)?;
+                        Self::set_user_version(conn, 12)?;
+                    }
+                    12 => {
+                        log::info!("迁移数据库从 v12 到 v13（Claude Token 空字段清理）");
+                        Self::migrate_v12_to_v13(conn)?;
+                        Self::set_user_version(conn, 13)?;
+                    }
+                    13 => {
+                        log::info!("迁移数据库从 v13 到 v14（模型名称归一化）");
+                        Self::migrate_v13_to_v14(conn)?;
+                        Self::set_user_version(conn, 14)?;
+                    }
+                    14 => {
+                        log::info!("迁移数据库从 v14 到 v15（影子请求标记）");
+                        Self::migrate_v14_to_v15(conn)?;
+                        Self::set_user_version(conn, 15)?;
+                    }
+                    15 => {
+                        log::info!("迁移数据库从 v15 到 v16（供应商最近验证时间）");
+                        Self::migrate_v15_to_v16(conn)?;
+                        Self::set_user_version(conn, 16)?;
+                    }
+                    16 => {
+                        log::info!("迁移数据库从 v16 到 v17（轻量流式模式）");
+                        Self::migrate_v16_to_v17(conn)?;
+                        Self::set_user_version(conn, 17)?;
+                    }
+                    17 => {
+                        log::info!("迁移数据库从 v17 到 v18（端点测速历史）");
+                        Self::migrate_v17_to_v18(conn)?;
+                        Self::set_user_version(conn, 18)?;
+                    }
+                    18 => {
+                        log::info!("迁移数据库从 v18 到 v19（代理并发上限）");
+                        Self::migrate_v18_to_v19(conn)?;
+                        Self::set_user_version(conn, 19)?;
+                    }
+                    19 => {
+                        log::info!("迁移数据库从 v19 到 v20（供应商切换事件）");
+                        Self::migrate_v19_to_v20(conn)?;
+                        Self::set_user_version(conn, 20)?;
+                    }
+                    20 => {
+                        log::info!("迁移数据库从 v20 到 v21（代理 Unix Socket 监听）");
+                        Self::migrate_v20_to_v21(conn)?;
+                        Self::set_user_version(conn, 21)?;
+                    }
+                    21 => {
+                        log::info!("迁移数据库从 v21 到 v22（Skill 同步方式覆盖）");
+                        Self::migrate_v21_to_v22(conn)?;
+                        Self::set_user_version(conn, 22)?;
+                    }
+                    22 => {
+                        log::info!("迁移数据库从 v22 到 v23（共用路径默认应用）");
+                        Self::migrate_v22_to_v23(conn)?;
+                        Self::set_user_version(conn, 23)?;
+                    }
+                    23 => {
+                        log::info!("迁移数据库从 v23 到 v24（供应商连接预热时间）");
+                        Self::migrate_v23_to_v24(conn)?;
+                        Self::set_user_version(conn, 24)?;
+                    }
+                    24 => {
+                        log::info!("迁移数据库从 v24 到 v25（请求日志记录 Key 池序号）");
+                        Self::migrate_v24_to_v25(conn)?;
+                        Self::set_user_version(conn, 25)?;
+                    }
+                    25 => {
+                        log::info!("迁移数据库从 v25 到 v26（请求日志标记兜底供应商）");
+                        Self::migrate_v25_to_v26(conn)?;
+                        Self::set_user_version(conn, 26)?;
+                    }
+                    26 => {
+                        log::info!("迁移数据库从 v26 到 v27（请求日志记录生效的输出 token 上限）");
+                        Self::migrate_v26_to_v27(conn)?;
+                        Self::set_user_version(conn, 27)?;
+                    }
                     _ => {
                         return Err(AppError::Database(format!(
                             "未知的数据库版本 {version}，无法迁移到 {SCHEMA_VERSION}"
@@ -1045,6 +1111,521 @@ impl Database {
         Ok(())
     }
 
+    /// v6 -> v7 迁移：添加推理 tokens 统计与推理定价字段
+    fn migrate_v6_to_v7(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_request_logs")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_request_logs",
+                "reasoning_tokens",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+            Self::add_column_if_missing(
+                conn,
+                "proxy_request_logs",
+                "reasoning_cost_usd",
+                "TEXT NOT NULL DEFAULT '0'",
+            )?;
+        }
+        if Self::table_exists(conn, "model_pricing")? {
+            // NULL 表示未单独配置推理价格，按输出价格计费
+            Self::add_column_if_missing(
+                conn,
+                "model_pricing",
+                "reasoning_cost_per_million",
+                "TEXT",
+            )?;
+        }
+
+        log::info!("v6 -> v7 迁移完成：已添加推理 tokens 计费字段");
+        Ok(())
+    }
+
+    /// v7 -> v8 迁移：添加供应商配置历史表
+    fn migrate_v7_to_v8(conn: &Connection) -> Result<(), AppError> {
+        Self::create_provider_history_table(conn)?;
+        log::info!("v7 -> v8 迁移完成：已添加 provider_history 表");
+        Ok(())
+    }
+
+    /// v8 -> v9 迁移：providers 添加 enabled 列（默认启用）
+    fn migrate_v8_to_v9(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "providers")? {
+            Self::add_column_if_missing(
+                conn,
+                "providers",
+                "enabled",
+                "BOOLEAN NOT NULL DEFAULT 1",
+            )?;
+        }
+        log::info!("v8 -> v9 迁移完成：已添加供应商启用开关");
+        Ok(())
+    }
+
+    /// v9 -> v10 迁移：provider_health 添加 last_error_kind 列
+    fn migrate_v9_to_v10(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "provider_health")? {
+            Self::add_column_if_missing(conn, "provider_health", "last_error_kind", "TEXT")?;
+        }
+        log::info!("v9 -> v10 迁移完成：已添加健康状态错误类别");
+        Ok(())
+    }
+
+    /// v10 -> v11 迁移：proxy_config 添加响应缓存开关，proxy_request_logs 添加缓存命中标记
+    fn migrate_v10_to_v11(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_config")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "enable_response_cache",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        if Self::table_exists(conn, "proxy_request_logs")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_request_logs",
+                "is_cached",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        log::info!("v10 -> v11 迁移完成：已添加响应缓存字段");
+        Ok(())
+    }
+
+    /// v11 -> v12 迁移：添加供应商分组表
+    fn migrate_v11_to_v12(conn: &Connection) -> Result<(), AppError> {
+        Self::create_provider_groups_table(conn)?;
+        log::info!("v11 -> v12 迁移完成：已添加 provider_groups 表");
+        Ok(())
+    }
+
+    /// v12 -> v13 迁移：Claude 供应商只保留实际保存 Token 的认证字段
+    ///
+    /// 读取 Token 的路径都把 ANTHROPIC_AUTH_TOKEN 与 ANTHROPIC_API_KEY 视为同义字段，
+    /// 但旧供应商可能在另一个字段留有空值，写入 Live 后 Claude Code 会同时看到两个认证变量。
+    /// 这里只移除空的那一方，不向供应商补写它原本没有使用的字段；两个字段都有值时不做改动，
+    /// 重复执行无副作用。
+    fn migrate_v12_to_v13(conn: &Connection) -> Result<(), AppError> {
+        if !Self::table_exists(conn, "providers")? {
+            return Ok(());
+        }
+
+        let mut stmt = conn
+            .prepare("SELECT id, settings_config FROM providers WHERE app_type = 'claude'")
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let mut updates = Vec::new();
+        for row in rows {
+            let (id, settings_str) = row.map_err(|e| AppError::Database(e.to_string()))?;
+            let Ok(mut settings) = serde_json::from_str::<serde_json::Value>(&settings_str) else {
+                continue;
+            };
+            if Self::prune_blank_claude_token_key(&mut settings) {
+                let new_settings = serde_json::to_string(&settings)
+                    .map_err(|e| AppError::Database(e.to_string()))?;
+                updates.push((id, new_settings));
+            }
+        }
+
+        for (id, new_settings) in &updates {
+            conn.execute(
+                "UPDATE providers SET settings_config = ?1 WHERE id = ?2 AND app_type = 'claude'",
+                params![new_settings, id],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        log::info!(
+            "v12 -> v13 迁移完成：已清理 {} 个 Claude 供应商的空 Token 字段",
+            updates.len()
+        );
+        Ok(())
+    }
+
+    /// v13 -> v14 迁移：proxy_request_logs 添加 model_normalized 列并回填历史日志
+    fn migrate_v13_to_v14(conn: &Connection) -> Result<(), AppError> {
+        if !Self::table_exists(conn, "proxy_request_logs")? {
+            return Ok(());
+        }
+        Self::add_column_if_missing(conn, "proxy_request_logs", "model_normalized", "TEXT")?;
+
+        let models: Vec<String> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT DISTINCT model FROM proxy_request_logs WHERE model_normalized IS NULL",
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let rows = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            rows.collect::<Result<_, _>>()
+                .map_err(|e| AppError::Database(e.to_string()))?
+        };
+
+        for model in &models {
+            conn.execute(
+                "UPDATE proxy_request_logs SET model_normalized = ?1
+                 WHERE model = ?2 AND model_normalized IS NULL",
+                params![
+                    crate::services::usage_stats::normalize_model_name(model),
+                    model
+                ],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        log::info!(
+            "v13 -> v14 迁移完成：已回填 {} 个模型的归一化名称",
+            models.len()
+        );
+        Ok(())
+    }
+
+    /// v14 -> v15 迁移：请求日志增加影子请求标记
+    fn migrate_v14_to_v15(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_request_logs")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_request_logs",
+                "is_shadow",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        log::info!("v14 -> v15 迁移完成：已添加影子请求标记");
+        Ok(())
+    }
+
+    /// v15 -> v16 迁移：供应商增加最近验证时间（最近一次健康检查成功）
+    fn migrate_v15_to_v16(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "providers")? {
+            Self::add_column_if_missing(conn, "providers", "last_verified_at", "INTEGER")?;
+        }
+        log::info!("v15 -> v16 迁移完成：已添加供应商最近验证时间");
+        Ok(())
+    }
+
+    /// v16 -> v17 迁移：proxy_config 添加轻量流式模式开关
+    fn migrate_v16_to_v17(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_config")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "lightweight_streaming",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        log::info!("v16 -> v17 迁移完成：已添加轻量流式模式开关");
+        Ok(())
+    }
+
+    /// v17 -> v18 迁移：添加端点测速历史表
+    fn migrate_v17_to_v18(conn: &Connection) -> Result<(), AppError> {
+        Self::create_endpoint_latency_history_table(conn)?;
+        log::info!("v17 -> v18 迁移完成：已添加 endpoint_latency_history 表");
+        Ok(())
+    }
+
+    /// v18 -> v19 迁移：proxy_config 添加最大并发请求数
+    fn migrate_v18_to_v19(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_config")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "max_concurrent_requests",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        log::info!("v18 -> v19 迁移完成：已添加最大并发请求数");
+        Ok(())
+    }
+
+    /// v19 -> v20 迁移：添加供应商切换事件表
+    fn migrate_v19_to_v20(conn: &Connection) -> Result<(), AppError> {
+        Self::create_switch_events_table(conn)?;
+        log::info!("v19 -> v20 迁移完成：已添加 switch_events 表");
+        Ok(())
+    }
+
+    /// v20 -> v21 迁移：proxy_config 添加 Unix Socket 监听路径
+    fn migrate_v20_to_v21(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_config")? {
+            Self::add_column_if_missing(conn, "proxy_config", "listen_socket_path", "TEXT")?;
+        }
+        log::info!("v20 -> v21 迁移完成：已添加 Unix Socket 监听路径");
+        Ok(())
+    }
+
+    /// v21 -> v22 迁移：Skill 单独的同步方式（为空时跟随全局设置）
+    fn migrate_v21_to_v22(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "skills")? {
+            Self::add_column_if_missing(conn, "skills", "sync_method_override", "TEXT")?;
+        }
+        log::info!("v21 -> v22 迁移完成：已添加 Skill 同步方式覆盖");
+        Ok(())
+    }
+
+    /// v22 -> v23 迁移：Claude 与 Codex 共用路径的默认应用
+    fn migrate_v22_to_v23(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_config")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_config",
+                "target_app",
+                "TEXT NOT NULL DEFAULT 'codex'",
+            )?;
+        }
+        log::info!("v22 -> v23 迁移完成：已添加共用路径默认应用");
+        Ok(())
+    }
+
+    /// v23 -> v24 迁移：provider_health 记录最近一次连接预热成功的时间
+    fn migrate_v23_to_v24(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "provider_health")? {
+            Self::add_column_if_missing(conn, "provider_health", "warmed_at", "TEXT")?;
+        }
+        log::info!("v23 -> v24 迁移完成：已添加供应商连接预热时间");
+        Ok(())
+    }
+
+    /// v24 -> v25 迁移：请求日志增加所用 Key 池序号
+    fn migrate_v24_to_v25(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_request_logs")? {
+            Self::add_column_if_missing(conn, "proxy_request_logs", "api_key_index", "INTEGER")?;
+        }
+        log::info!("v24 -> v25 迁移完成：已添加请求日志 Key 池序号");
+        Ok(())
+    }
+
+    /// v25 -> v26 迁移：请求日志标记是否由兜底供应商完成
+    fn migrate_v25_to_v26(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_request_logs")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_request_logs",
+                "is_safety_net",
+                "INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        log::info!("v25 -> v26 迁移完成：已添加请求日志兜底供应商标记");
+        Ok(())
+    }
+
+    /// v26 -> v27 迁移：proxy_request_logs 添加 effective_max_tokens 列
+    fn migrate_v26_to_v27(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "proxy_request_logs")? {
+            Self::add_column_if_missing(
+                conn,
+                "proxy_request_logs",
+                "effective_max_tokens",
+                "INTEGER",
+            )?;
+        }
+        log::info!("v26 -> v27 迁移完成：已添加请求日志输出 token 上限生效值");
+        Ok(())
+    }
+
+    /// 只有一个认证字段保存了 Token 时，移除 env 中另一个空的认证字段，返回是否有改动
+    fn prune_blank_claude_token_key(settings: &mut serde_json::Value) -> bool {
+        const KEYS: [&str; 2] = ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"];
+
+        let Some(env) = settings.get_mut("env").and_then(|v| v.as_object_mut()) else {
+            return false;
+        };
+        let has_token = |key: &str| {
+            env.get(key)
+                .and_then(|v| v.as_str())
+                .is_some_and(|s| !s.trim().is_empty())
+        };
+        let blank: Vec<&str> = match KEYS.map(has_token) {
+            [true, false] => vec![KEYS[1]],
+            [false, true] => vec![KEYS[0]],
+            _ => Vec::new(),
+        };
+
+        let mut changed = false;
+        for key in blank {
+            changed |= env.remove(key).is_some();
+        }
+        changed
+    }
+
+    /// 创建供应商配置历史表（保存每次编辑前的 settings_config 快照）
+    fn create_provider_history_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider_id TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                settings_config TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 provider_history 表失败: {e}")))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_provider_history_provider
+             ON provider_history(provider_id, app_type, id)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 创建端点测速历史表（每次测速追加一条，失败的测速 latency_ms 为 NULL）
+    fn create_endpoint_latency_history_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS endpoint_latency_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider_id TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                url TEXT NOT NULL,
+                latency_ms INTEGER,
+                status INTEGER,
+                tested_at INTEGER NOT NULL,
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            )",
+            [],
+        )
+        .map_err(|e| {
+            AppError::Database(format!("创建 endpoint_latency_history 表失败: {e}"))
+        })?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_endpoint_latency_history_endpoint
+             ON endpoint_latency_history(provider_id, app_type, url, id)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 创建使用统计相关表：请求日志、模型定价、日聚合统计
+    ///
+    /// `schema` 为 `main` 或附加的独立使用日志库（`usage_store::USAGE_DB_SCHEMA`）。
+    pub(crate) fn create_usage_tables(conn: &Connection, schema: &str) -> Result<(), AppError> {
+        Self::validate_identifier(schema, "数据库名")?;
+
+        // 10. Proxy Request Logs 表
+        conn.execute(&format!("CREATE TABLE IF NOT EXISTS {schema}.proxy_request_logs (
+            request_id TEXT PRIMARY KEY, provider_id TEXT NOT NULL, app_type TEXT NOT NULL, model TEXT NOT NULL,
+            request_model TEXT,
+            input_tokens INTEGER NOT NULL DEFAULT 0, output_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0, cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+            reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+            input_cost_usd TEXT NOT NULL DEFAULT '0', output_cost_usd TEXT NOT NULL DEFAULT '0',
+            cache_read_cost_usd TEXT NOT NULL DEFAULT '0', cache_creation_cost_usd TEXT NOT NULL DEFAULT '0',
+            reasoning_cost_usd TEXT NOT NULL DEFAULT '0',
+            total_cost_usd TEXT NOT NULL DEFAULT '0', latency_ms INTEGER NOT NULL, first_token_ms INTEGER,
+            duration_ms INTEGER, status_code INTEGER NOT NULL, error_message TEXT, session_id TEXT,
+            provider_type TEXT, is_streaming INTEGER NOT NULL DEFAULT 0,
+            cost_multiplier TEXT NOT NULL DEFAULT '1.0', is_cached INTEGER NOT NULL DEFAULT 0,
+            model_normalized TEXT, is_shadow INTEGER NOT NULL DEFAULT 0,
+            api_key_index INTEGER,
+            is_safety_net INTEGER NOT NULL DEFAULT 0,
+            effective_max_tokens INTEGER,
+            created_at INTEGER NOT NULL
+        )"), []).map_err(|e| AppError::Database(e.to_string()))?;
+
+        for (index, columns) in [
+            ("idx_request_logs_provider", "provider_id, app_type"),
+            ("idx_request_logs_created_at", "created_at"),
+            ("idx_request_logs_model", "model"),
+            ("idx_request_logs_session", "session_id"),
+            ("idx_request_logs_status", "status_code"),
+        ] {
+            conn.execute(
+                &format!(
+                    "CREATE INDEX IF NOT EXISTS {schema}.{index} ON proxy_request_logs({columns})"
+                ),
+                [],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+
+        // 11. Model Pricing 表
+        conn.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {schema}.model_pricing (
+            model_id TEXT PRIMARY KEY, display_name TEXT NOT NULL,
+            input_cost_per_million TEXT NOT NULL, output_cost_per_million TEXT NOT NULL,
+            cache_read_cost_per_million TEXT NOT NULL DEFAULT '0',
+            cache_creation_cost_per_million TEXT NOT NULL DEFAULT '0',
+            reasoning_cost_per_million TEXT
+        )"
+            ),
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 17. Usage Daily Rollups 表 (日聚合统计)
+        conn.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {schema}.usage_daily_rollups (
+                date TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                model TEXT NOT NULL,
+                request_count INTEGER NOT NULL DEFAULT 0,
+                success_count INTEGER NOT NULL DEFAULT 0,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+                cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+                total_cost_usd TEXT NOT NULL DEFAULT '0',
+                avg_latency_ms INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (date, app_type, provider_id, model)
+            )"
+            ),
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 创建供应商切换事件表（不关联 providers，供应商删除后记录仍保留）
+    fn create_switch_events_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS switch_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_type TEXT NOT NULL,
+                from_id TEXT,
+                to_id TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                ts INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 switch_events 表失败: {e}")))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_switch_events_app ON switch_events(app_type, id)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 创建供应商分组表（故障转移按分组路由，供应商通过 meta.group 归属分组）
+    fn create_provider_groups_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_groups (
+                app_type TEXT NOT NULL,
+                name TEXT NOT NULL,
+                is_default INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (app_type, name)
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 provider_groups 表失败: {e}")))?;
+        Ok(())
+    }
+
     /// 插入默认模型定价数据
     /// 格式: (model_id, display_name, input, output, cache_read, cache_creation)
     /// 注意: model_id 使用短横线格式（如 claude-haiku-4-5），与 API 返回的模型名称标准化后一致
@@ -1451,7 +2032,7 @@ impl Database {
         Ok(())
     }
 
-    fn validate_identifier(s: &str, kind: &str) -> Result<(), AppError> {
+    pub(crate) fn validate_identifier(s: &str, kind: &str) -> Result<(), AppError> {
         if s.is_empty() {
             return Err(AppError::Database(format!("{kind} 不能为空")));
         }
@@ -1463,11 +2044,28 @@ impl Database {
         Ok(())
     }
 
+    /// 表是否存在于主库或已挂载的独立使用日志库（与未限定库名的 SQL 解析范围一致）
     pub(crate) fn table_exists(conn: &Connection, table: &str) -> Result<bool, AppError> {
+        if Self::table_exists_in(conn, "main", table)? {
+            return Ok(true);
+        }
+        Ok(Self::is_usage_db_attached(conn)?
+            && Self::table_exists_in(conn, USAGE_DB_SCHEMA, table)?)
+    }
+
+    /// 表是否存在于指定的库中
+    pub(crate) fn table_exists_in(
+        conn: &Connection,
+        schema: &str,
+        table: &str,
+    ) -> Result<bool, AppError> {
+        Self::validate_identifier(schema, "数据库名")?;
         Self::validate_identifier(table, "表名")?;
 
         let mut stmt = conn
-            .prepare("SELECT name FROM sqlite_master WHERE type='table'")
+            .prepare(&format!(
+                "SELECT name FROM {schema}.sqlite_master WHERE type='table'"
+            ))
             .map_err(|e| AppError::Database(format!("读取表名失败: {e}")))?;
         let mut rows = stmt
             .query([])
@@ -337,6 +337,47 @@ fn schema_migration_v4_adds_pricing_model_columns() {
     );
 }
 
+#[test]
+fn schema_migration_v6_adds_reasoning_columns() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_request_logs (request_id TEXT PRIMARY KEY, model TEXT NOT NULL);
+        CREATE TABLE model_pricing (
+            model_id TEXT PRIMARY KEY,
+            display_name TEXT NOT NULL,
+            input_cost_per_million TEXT NOT NULL,
+            output_cost_per_million TEXT NOT NULL
+        );
+        "#,
+    )
+    .expect("seed v6 schema");
+
+    Database::set_user_version(&conn, 6).expect("set user_version=6");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let reasoning_tokens = get_column_info(&conn, "proxy_request_logs", "reasoning_tokens");
+    assert_eq!(reasoning_tokens.r#type, "INTEGER");
+    assert_eq!(reasoning_tokens.notnull, 1);
+    assert_eq!(
+        normalize_default(&reasoning_tokens.default).as_deref(),
+        Some("0")
+    );
+
+    let reasoning_cost = get_column_info(&conn, "proxy_request_logs", "reasoning_cost_usd");
+    assert_eq!(reasoning_cost.r#type, "TEXT");
+    assert_eq!(reasoning_cost.notnull, 1);
+
+    let reasoning_price = get_column_info(&conn, "model_pricing", "reasoning_cost_per_million");
+    assert_eq!(reasoning_price.r#type, "TEXT");
+    assert_eq!(reasoning_price.notnull, 0);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
 #[test]
 fn schema_create_tables_repairs_legacy_proxy_config_singleton_to_per_app() {
     let conn = Connection::open_in_memory().expect("open memory db");
@@ -566,6 +607,7 @@ fn dry_run_validates_schema_compatibility() {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         },
     );
 
@@ -682,3 +724,865 @@ fn ensure_incremental_auto_vacuum_rebuilds_existing_file_db() {
         "file db should persist INCREMENTAL auto_vacuum after VACUUM rebuild"
     );
 }
+
+#[test]
+fn schema_migration_v7_adds_provider_history_table() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 7).expect("set user_version=7");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(Database::table_exists(&conn, "provider_history").expect("check table"));
+    let settings = get_column_info(&conn, "provider_history", "settings_config");
+    assert_eq!(settings.r#type, "TEXT");
+    assert_eq!(settings.notnull, 1);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v8_adds_provider_enabled_column() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE providers (
+            id TEXT NOT NULL,
+            app_type TEXT NOT NULL,
+            name TEXT NOT NULL,
+            settings_config TEXT NOT NULL,
+            PRIMARY KEY (id, app_type)
+        );
+        INSERT INTO providers (id, app_type, name, settings_config)
+        VALUES ('p1', 'claude', 'Legacy', '{}');
+        "#,
+    )
+    .expect("seed v8 schema");
+
+    Database::set_user_version(&conn, 8).expect("set user_version=8");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let enabled: bool = conn
+        .query_row(
+            "SELECT enabled FROM providers WHERE id = 'p1' AND app_type = 'claude'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read enabled");
+    assert!(enabled, "existing providers should stay enabled");
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v9_adds_provider_health_error_kind_column() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE provider_health (
+            provider_id TEXT NOT NULL,
+            app_type TEXT NOT NULL,
+            is_healthy INTEGER NOT NULL DEFAULT 1,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            last_success_at TEXT,
+            last_failure_at TEXT,
+            last_error TEXT,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (provider_id, app_type)
+        );
+        INSERT INTO provider_health (provider_id, app_type, is_healthy, updated_at)
+        VALUES ('p1', 'claude', 1, '2025-01-01T00:00:00Z');
+        "#,
+    )
+    .expect("seed v9 schema");
+
+    Database::set_user_version(&conn, 9).expect("set user_version=9");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let kind: Option<String> = conn
+        .query_row(
+            "SELECT last_error_kind FROM provider_health WHERE provider_id = 'p1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read last_error_kind");
+    assert!(kind.is_none(), "existing rows should have no error kind");
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v10_adds_response_cache_columns() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_config (
+            app_type TEXT PRIMARY KEY,
+            enable_logging INTEGER NOT NULL DEFAULT 1
+        );
+        INSERT INTO proxy_config (app_type) VALUES ('claude');
+        CREATE TABLE proxy_request_logs (
+            request_id TEXT PRIMARY KEY,
+            provider_id TEXT NOT NULL,
+            app_type TEXT NOT NULL,
+            model TEXT NOT NULL,
+            latency_ms INTEGER NOT NULL,
+            status_code INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model, latency_ms, status_code, created_at)
+        VALUES ('r1', 'p1', 'claude', 'm', 10, 200, 0);
+        "#,
+    )
+    .expect("seed v10 schema");
+
+    Database::set_user_version(&conn, 10).expect("set user_version=10");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let enabled: i64 = conn
+        .query_row(
+            "SELECT enable_response_cache FROM proxy_config WHERE app_type = 'claude'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read enable_response_cache");
+    assert_eq!(enabled, 0, "response cache should be off after migration");
+
+    let cached: i64 = conn
+        .query_row(
+            "SELECT is_cached FROM proxy_request_logs WHERE request_id = 'r1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read is_cached");
+    assert_eq!(cached, 0, "existing logs should not be marked cached");
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v11_adds_provider_groups_table() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 11).expect("set user_version=11");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(
+        Database::table_exists(&conn, "provider_groups").expect("check table"),
+        "provider_groups table should exist after migration"
+    );
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v12_keeps_only_the_claude_token_key_in_use() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE providers (
+            id TEXT NOT NULL,
+            app_type TEXT NOT NULL,
+            name TEXT NOT NULL,
+            settings_config TEXT NOT NULL,
+            meta TEXT NOT NULL DEFAULT '{}',
+            PRIMARY KEY (id, app_type)
+        );
+        INSERT INTO providers (id, app_type, name, settings_config) VALUES
+            ('legacy', 'claude', 'Legacy', '{"env":{"ANTHROPIC_API_KEY":"sk-legacy"}}'),
+            ('blank', 'claude', 'Blank', '{"env":{"ANTHROPIC_API_KEY":"sk-x","ANTHROPIC_AUTH_TOKEN":" "}}'),
+            ('both', 'claude', 'Both', '{"env":{"ANTHROPIC_API_KEY":"sk-a","ANTHROPIC_AUTH_TOKEN":"sk-b"}}'),
+            ('codex', 'codex', 'Codex', '{"auth":{"OPENAI_API_KEY":"sk-c"}}');
+        "#,
+    )
+    .expect("seed v12 schema");
+
+    Database::set_user_version(&conn, 12).expect("set user_version=12");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let settings = |id: &str, app_type: &str| -> serde_json::Value {
+        let raw: String = conn
+            .query_row(
+                "SELECT settings_config FROM providers WHERE id = ?1 AND app_type = ?2",
+                [id, app_type],
+                |r| r.get(0),
+            )
+            .expect("read settings_config");
+        serde_json::from_str(&raw).expect("parse settings_config")
+    };
+
+    // 不补写供应商原本没有使用的字段
+    let legacy = settings("legacy", "claude");
+    assert_eq!(legacy["env"]["ANTHROPIC_API_KEY"], "sk-legacy");
+    assert!(legacy["env"].get("ANTHROPIC_AUTH_TOKEN").is_none());
+
+    // 空的认证字段被移除，只保留实际保存 Token 的字段
+    let blank = settings("blank", "claude");
+    assert_eq!(blank["env"]["ANTHROPIC_API_KEY"], "sk-x");
+    assert!(blank["env"].get("ANTHROPIC_AUTH_TOKEN").is_none());
+
+    // 两个字段都已存在时不覆盖
+    let both = settings("both", "claude");
+    assert_eq!(both["env"]["ANTHROPIC_API_KEY"], "sk-a");
+    assert_eq!(both["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-b");
+
+    let codex = settings("codex", "codex");
+    assert!(codex["env"].is_null());
+
+    // 重复执行无副作用
+    Database::set_user_version(&conn, 12).expect("reset user_version=12");
+    Database::apply_schema_migrations_on_conn(&conn).expect("re-run migrations");
+    assert_eq!(settings("legacy", "claude"), legacy);
+    assert_eq!(settings("blank", "claude"), blank);
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v13_backfills_normalized_model() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_request_logs (
+            request_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        INSERT INTO proxy_request_logs (request_id, model, created_at) VALUES
+            ('r1', 'anthropic/claude-sonnet-4-5', 0),
+            ('r2', 'claude-sonnet-4-5:beta', 0);
+        "#,
+    )
+    .expect("seed v13 schema");
+
+    Database::set_user_version(&conn, 13).expect("set user_version=13");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT model_normalized FROM proxy_request_logs")
+        .expect("prepare");
+    let normalized: Vec<String> = stmt
+        .query_map([], |r| r.get(0))
+        .expect("query")
+        .collect::<Result<_, _>>()
+        .expect("collect");
+    assert_eq!(normalized, vec!["claude-sonnet-4-5".to_string()]);
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v14_adds_shadow_flag() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_request_logs (
+            request_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        INSERT INTO proxy_request_logs (request_id, model, created_at) VALUES ('r1', 'm', 0);
+        "#,
+    )
+    .expect("seed v14 schema");
+
+    Database::set_user_version(&conn, 14).expect("set user_version=14");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let shadow: i64 = conn
+        .query_row(
+            "SELECT is_shadow FROM proxy_request_logs WHERE request_id = 'r1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read is_shadow");
+    assert_eq!(shadow, 0, "existing logs should not be marked shadow");
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v15_adds_provider_last_verified_at() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE providers (
+            id TEXT NOT NULL,
+            app_type TEXT NOT NULL,
+            name TEXT NOT NULL,
+            settings_config TEXT NOT NULL,
+            PRIMARY KEY (id, app_type)
+        );
+        INSERT INTO providers (id, app_type, name, settings_config) VALUES ('p1', 'claude', 'P1', '{}');
+        "#,
+    )
+    .expect("seed v15 schema");
+
+    Database::set_user_version(&conn, 15).expect("set user_version=15");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let verified: Option<i64> = conn
+        .query_row(
+            "SELECT last_verified_at FROM providers WHERE id = 'p1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read last_verified_at");
+    assert_eq!(verified, None, "existing providers start unverified");
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v16_adds_lightweight_streaming_flag() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_config (
+            app_type TEXT PRIMARY KEY,
+            enable_logging INTEGER NOT NULL DEFAULT 1
+        );
+        INSERT INTO proxy_config (app_type) VALUES ('claude');
+        "#,
+    )
+    .expect("seed v16 schema");
+
+    Database::set_user_version(&conn, 16).expect("set user_version=16");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let lightweight: i64 = conn
+        .query_row(
+            "SELECT lightweight_streaming FROM proxy_config WHERE app_type = 'claude'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read lightweight_streaming");
+    assert_eq!(
+        lightweight, 0,
+        "lightweight streaming should be off after migration"
+    );
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v17_adds_endpoint_latency_history() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 17).expect("set user_version=17");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(
+        Database::table_exists(&conn, "endpoint_latency_history").expect("check table"),
+        "endpoint_latency_history should exist after migration"
+    );
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v18_adds_max_concurrent_requests() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_config (
+            app_type TEXT PRIMARY KEY,
+            enable_logging INTEGER NOT NULL DEFAULT 1
+        );
+        INSERT INTO proxy_config (app_type) VALUES ('claude');
+        "#,
+    )
+    .expect("seed v18 schema");
+
+    Database::set_user_version(&conn, 18).expect("set user_version=18");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let limit: i64 = conn
+        .query_row(
+            "SELECT max_concurrent_requests FROM proxy_config WHERE app_type = 'claude'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read max_concurrent_requests");
+    assert_eq!(limit, 0, "concurrency should be unlimited after migration");
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v19_adds_switch_events() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 19).expect("set user_version=19");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(
+        Database::table_exists(&conn, "switch_events").expect("check table"),
+        "switch_events should exist after migration"
+    );
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v20_adds_listen_socket_path() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_config (
+            app_type TEXT PRIMARY KEY,
+            enable_logging INTEGER NOT NULL DEFAULT 1
+        );
+        INSERT INTO proxy_config (app_type) VALUES ('claude');
+        "#,
+    )
+    .expect("seed v20 schema");
+
+    Database::set_user_version(&conn, 20).expect("set user_version=20");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let socket_path: Option<String> = conn
+        .query_row(
+            "SELECT listen_socket_path FROM proxy_config WHERE app_type = 'claude'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read listen_socket_path");
+    assert_eq!(
+        socket_path, None,
+        "unix socket should be off after migration"
+    );
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v21_adds_skill_sync_method_override() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE skills (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            directory TEXT NOT NULL,
+            installed_at INTEGER NOT NULL DEFAULT 0
+        );
+        INSERT INTO skills (id, name, directory) VALUES ('local:demo', 'Demo', 'demo');
+        "#,
+    )
+    .expect("seed v21 schema");
+
+    Database::set_user_version(&conn, 21).expect("set user_version=21");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let method: Option<String> = conn
+        .query_row(
+            "SELECT sync_method_override FROM skills WHERE id = 'local:demo'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read sync_method_override");
+    assert_eq!(
+        method, None,
+        "existing skills should follow the global method"
+    );
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v22_defaults_target_app_to_codex() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_config (
+            app_type TEXT PRIMARY KEY,
+            enable_logging INTEGER NOT NULL DEFAULT 1
+        );
+        INSERT INTO proxy_config (app_type) VALUES ('claude');
+        "#,
+    )
+    .expect("seed v22 schema");
+
+    Database::set_user_version(&conn, 22).expect("set user_version=22");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let target_app: String = conn
+        .query_row(
+            "SELECT target_app FROM proxy_config WHERE app_type = 'claude'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read target_app");
+    assert_eq!(target_app, "codex");
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v23_adds_provider_warmed_at() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE provider_health (
+            provider_id TEXT NOT NULL,
+            app_type TEXT NOT NULL,
+            is_healthy INTEGER NOT NULL DEFAULT 1,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (provider_id, app_type)
+        );
+        INSERT INTO provider_health (provider_id, app_type, updated_at)
+        VALUES ('p1', 'claude', '2026-01-01T00:00:00Z');
+        "#,
+    )
+    .expect("seed v23 schema");
+
+    Database::set_user_version(&conn, 23).expect("set user_version=23");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let warmed_at: Option<String> = conn
+        .query_row(
+            "SELECT warmed_at FROM provider_health WHERE provider_id = 'p1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read warmed_at");
+    assert_eq!(warmed_at, None);
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v24_adds_request_log_key_index() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_request_logs (
+            request_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        INSERT INTO proxy_request_logs (request_id, model, created_at) VALUES ('r1', 'm', 0);
+        "#,
+    )
+    .expect("seed v24 schema");
+
+    Database::set_user_version(&conn, 24).expect("set user_version=24");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let key_index: Option<i64> = conn
+        .query_row(
+            "SELECT api_key_index FROM proxy_request_logs WHERE request_id = 'r1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read api_key_index");
+    assert_eq!(key_index, None);
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v25_adds_request_log_safety_net_flag() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_request_logs (
+            request_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        INSERT INTO proxy_request_logs (request_id, model, created_at) VALUES ('r1', 'm', 0);
+        "#,
+    )
+    .expect("seed v25 schema");
+
+    Database::set_user_version(&conn, 25).expect("set user_version=25");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let is_safety_net: i64 = conn
+        .query_row(
+            "SELECT is_safety_net FROM proxy_request_logs WHERE request_id = 'r1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read is_safety_net");
+    assert_eq!(is_safety_net, 0);
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v26_adds_request_log_effective_max_tokens() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE proxy_request_logs (
+            request_id TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        INSERT INTO proxy_request_logs (request_id, model, created_at) VALUES ('r1', 'm', 0);
+        "#,
+    )
+    .expect("seed v26 schema");
+
+    Database::set_user_version(&conn, 26).expect("set user_version=26");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let effective: Option<i64> = conn
+        .query_row(
+            "SELECT effective_max_tokens FROM proxy_request_logs WHERE request_id = 'r1'",
+            [],
+            |r| r.get(0),
+        )
+        .expect("read effective_max_tokens");
+    assert_eq!(effective, None);
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn usage_logs_are_moved_to_attached_usage_db_and_stay_queryable() {
+    use crate::proxy::usage::{TokenUsage, UsageLogger};
+
+    let db = Database::memory().expect("create memory db");
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        conn.execute(
+            "INSERT INTO proxy_request_logs (
+                request_id, provider_id, app_type, model,
+                input_tokens, output_tokens, total_cost_usd,
+                latency_ms, status_code, created_at
+            ) VALUES ('before-attach', 'p1', 'claude', 'claude-3', 100, 50, '0.01', 100, 200, 1000)",
+            [],
+        )
+        .expect("insert existing log");
+    }
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let usage_path = dir.path().join("usage").join("usage.db");
+    db.attach_usage_db(&usage_path).expect("attach usage db");
+
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        for table in ["proxy_request_logs", "model_pricing", "usage_daily_rollups"] {
+            assert!(
+                !Database::table_exists_in(&conn, "main", table).expect("check main table"),
+                "{table} should be moved out of the main db"
+            );
+        }
+    }
+
+    // 迁移后日志写入与统计查询透明地落到独立库
+    UsageLogger::new(&db)
+        .log_with_calculation(
+            "after-attach".to_string(),
+            "p1".to_string(),
+            "claude".to_string(),
+            "claude-3".to_string(),
+            "claude-3".to_string(),
+            "claude-3".to_string(),
+            TokenUsage {
+                input_tokens: 200,
+                output_tokens: 100,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
+                reasoning_tokens: 0,
+                model: None,
+            },
+            rust_decimal::Decimal::from(1),
+            150,
+            None,
+            200,
+            None,
+            Some("claude".to_string()),
+            false,
+        )
+        .expect("log request into attached db");
+
+    let summary = db.get_usage_summary(None, None).expect("query summary");
+    assert_eq!(summary.total_requests, 2);
+
+    let usage_conn = Connection::open(&usage_path).expect("open usage db file");
+    let ids: Vec<String> = usage_conn
+        .prepare("SELECT request_id FROM proxy_request_logs ORDER BY request_id")
+        .expect("prepare")
+        .query_map([], |row| row.get(0))
+        .expect("query usage db")
+        .collect::<Result<_, _>>()
+        .expect("collect ids");
+    assert_eq!(ids, vec!["after-attach", "before-attach"]);
+    let pricing_rows: i64 = usage_conn
+        .query_row("SELECT COUNT(*) FROM model_pricing", [], |row| row.get(0))
+        .expect("count pricing");
+    assert!(pricing_rows > 0, "seeded pricing should move with the logs");
+
+    // 再次建表（模拟重启）后重新迁移，主库中的空表不会遮蔽独立库
+    db.create_tables().expect("recreate main tables");
+    assert_eq!(db.relocate_usage_tables().expect("relocate again"), 0);
+    assert_eq!(
+        db.get_usage_summary(None, None)
+            .expect("query summary")
+            .total_requests,
+        2
+    );
+
+    // 挂载后的 Schema 迁移作用于独立库中的表
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        conn.execute(
+            "ALTER TABLE usage_db.proxy_request_logs DROP COLUMN is_safety_net",
+            [],
+        )
+        .expect("drop column to simulate v25 usage db");
+        Database::set_user_version(&conn, 25).expect("set user_version=25");
+        Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+        assert!(
+            !Database::table_exists_in(&conn, "main", "proxy_request_logs").expect("check main")
+        );
+        let flagged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM usage_db.proxy_request_logs WHERE is_safety_net = 0",
+                [],
+                |row| row.get(0),
+            )
+            .expect("read migrated column");
+        assert_eq!(flagged, 2);
+    }
+
+    // 卸载独立库时数据迁回主库，历史日志仍然可见
+    assert!(db.detach_usage_db().expect("detach usage db") >= 2);
+    assert_eq!(db.detach_usage_db().expect("detach again"), 0);
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        assert!(!Database::is_usage_db_attached(&conn).expect("check attached"));
+    }
+    assert_eq!(
+        db.get_usage_summary(None, None)
+            .expect("query summary after detach")
+            .total_requests,
+        2
+    );
+}
+
+#[test]
+fn repair_current_flags_keeps_a_single_current_provider() {
+    let db = Database::memory().expect("create memory db");
+    for (index, id) in ["a", "b", "c"].into_iter().enumerate() {
+        let mut provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+        provider.sort_index = Some(index);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+    let mark_all_current = || {
+        let conn = db.conn.lock().expect("lock conn");
+        conn.execute(
+            "UPDATE providers SET is_current = 1 WHERE app_type = 'claude' AND id IN ('a', 'b')",
+            [],
+        )
+        .expect("corrupt is_current flags");
+    };
+    let current_ids = || {
+        let conn = db.conn.lock().expect("lock conn");
+        let mut stmt = conn
+            .prepare("SELECT id FROM providers WHERE app_type = 'claude' AND is_current = 1")
+            .expect("prepare");
+        stmt.query_map([], |row| row.get::<_, String>(0))
+            .expect("query")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("collect")
+    };
+
+    // settings 中记录的当前供应商优先
+    mark_all_current();
+    assert_eq!(
+        db.repair_current_flags_for("claude", Some("b"))
+            .expect("repair"),
+        1
+    );
+    assert_eq!(current_ids(), vec!["b"]);
+    assert_eq!(
+        db.get_current_provider("claude").expect("read current"),
+        Some("b".to_string())
+    );
+
+    // settings 未记录（或已失效）时保留排序最前的一行
+    mark_all_current();
+    assert_eq!(
+        db.repair_current_flags_for("claude", Some("missing"))
+            .expect("repair"),
+        1
+    );
+    assert_eq!(current_ids(), vec!["a"]);
+
+    // 已经一致时不做修改
+    assert_eq!(
+        db.repair_current_flags_for("claude", None).expect("repair"),
+        0
+    );
+
+    // 只有一行时保留（跨设备默认供应商可以与本设备的当前供应商不同）
+    assert_eq!(
+        db.repair_current_flags_for("claude", Some("c"))
+            .expect("repair"),
+        0
+    );
+    assert_eq!(current_ids(), vec!["a"]);
+
+    // 一行都没有时标记 settings 中记录的当前供应商
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        conn.execute(
+            "UPDATE providers SET is_current = 0 WHERE app_type = 'claude'",
+            [],
+        )
+        .expect("clear is_current flags");
+    }
+    assert_eq!(
+        db.repair_current_flags_for("claude", Some("c"))
+            .expect("repair"),
+        1
+    );
+    assert_eq!(current_ids(), vec!["c"]);
+}
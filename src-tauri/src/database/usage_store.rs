@@ -0,0 +1,245 @@
+//! 独立的使用日志库
+//!
+//! 请求日志增长很快。设置了 `usageDbPath` 时，启动后通过 `ATTACH DATABASE` 挂载该文件，
+//! 并把请求日志、模型定价、日聚合统计表从主库迁移过去。迁移后主库中不再有这些表，
+//! SQLite 解析未限定库名的表时会依次查找 main 与附加库，因此日志写入和统计查询无需改动。
+//!
+//! 启动时先挂载独立库再建表与迁移：挂载后 `create_tables` 直接在独立库建表，
+//! Schema 迁移（含数据回填）也就作用于其中的历史日志。
+//! 主库中残留的表（首次挂载或导入/恢复后）由 [`Database::relocate_usage_tables`] 迁入独立库。
+//! 主库的 SQL 导出与快照备份不再包含这些表。
+//!
+//! 清除或更换 `usageDbPath` 时由 [`Database::detach_usage_db`] 把数据迁回主库，
+//! 避免历史日志留在不再挂载的文件中。
+
+use super::{lock_conn, Database};
+use crate::error::AppError;
+use rusqlite::Connection;
+use std::path::Path;
+
+/// 独立使用日志库挂载后的库名
+pub(crate) const USAGE_DB_SCHEMA: &str = "usage_db";
+
+/// 存放在独立库中的表
+const USAGE_TABLES: &[&str] = &["proxy_request_logs", "model_pricing", "usage_daily_rollups"];
+
+struct ColumnInfo {
+    name: String,
+    decl_type: String,
+    not_null: bool,
+    default: Option<String>,
+}
+
+impl Database {
+    /// 挂载独立的使用日志库，并把主库中的使用统计表（含已有数据）迁移过去
+    pub fn attach_usage_db(&self, path: &Path) -> Result<(), AppError> {
+        self.mount_usage_db(path)?;
+        let moved = self.relocate_usage_tables()?;
+        log::info!(
+            "已挂载独立使用日志库 {}（从主库迁移 {moved} 行）",
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// 仅挂载独立的使用日志库（不迁移数据），已挂载时不做任何事
+    ///
+    /// 启动时需在建表与 Schema 迁移之前调用，迁移才能作用到独立库中的历史日志。
+    pub(crate) fn mount_usage_db(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+
+        let conn = lock_conn!(self.conn);
+        if Self::is_usage_db_attached(&conn)? {
+            return Ok(());
+        }
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS {USAGE_DB_SCHEMA}"),
+            [path.to_string_lossy().to_string()],
+        )
+        .map_err(|e| AppError::Database(format!("挂载使用日志库 {} 失败: {e}", path.display())))?;
+        Ok(())
+    }
+
+    /// 把独立库中的使用统计数据迁回主库并卸载独立库，返回迁回的行数（未挂载时为 0）
+    ///
+    /// 独立库文件本身保留不动；之后的日志直接写入主库。
+    pub fn detach_usage_db(&self) -> Result<usize, AppError> {
+        let conn = lock_conn!(self.conn);
+        if !Self::is_usage_db_attached(&conn)? {
+            return Ok(0);
+        }
+
+        conn.execute("SAVEPOINT detach_usage_db;", [])
+            .map_err(|e| AppError::Database(format!("开启迁移 savepoint 失败: {e}")))?;
+        let moved = match Self::restore_usage_tables_on_conn(&conn) {
+            Ok(moved) => {
+                conn.execute("RELEASE detach_usage_db;", [])
+                    .map_err(|e| AppError::Database(format!("提交迁移失败: {e}")))?;
+                moved
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK TO detach_usage_db; RELEASE detach_usage_db;");
+                return Err(e);
+            }
+        };
+
+        conn.execute(&format!("DETACH DATABASE {USAGE_DB_SCHEMA}"), [])
+            .map_err(|e| AppError::Database(format!("卸载使用日志库失败: {e}")))?;
+        log::info!("已卸载独立使用日志库（迁回主库 {moved} 行）");
+        Ok(moved)
+    }
+
+    fn restore_usage_tables_on_conn(conn: &Connection) -> Result<usize, AppError> {
+        Self::create_usage_tables(conn, "main")?;
+
+        let mut moved = 0;
+        for table in USAGE_TABLES {
+            let main_columns = Self::column_infos(conn, "main", table)?;
+            // 只迁回两边都有的列（主库表按最新结构创建，独立库可能是旧版本留下的）
+            let cols = Self::column_infos(conn, USAGE_DB_SCHEMA, table)?
+                .iter()
+                .filter(|column| {
+                    main_columns
+                        .iter()
+                        .any(|main| main.name.eq_ignore_ascii_case(&column.name))
+                })
+                .map(|column| format!("\"{}\"", column.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if cols.is_empty() {
+                continue;
+            }
+            moved += conn
+                .execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO main.\"{table}\" ({cols})
+                         SELECT {cols} FROM {USAGE_DB_SCHEMA}.\"{table}\""
+                    ),
+                    [],
+                )
+                .map_err(|e| AppError::Database(format!("迁回表 {table} 数据失败: {e}")))?;
+        }
+        Ok(moved)
+    }
+
+    /// 把主库中的使用统计表迁移到已挂载的独立库，返回迁移的行数（未挂载时为 0）
+    ///
+    /// 导入/恢复主库后需要再次调用，否则主库中重建的表会遮蔽独立库。
+    pub(crate) fn relocate_usage_tables(&self) -> Result<usize, AppError> {
+        let conn = lock_conn!(self.conn);
+        if !Self::is_usage_db_attached(&conn)? {
+            return Ok(0);
+        }
+
+        conn.execute("SAVEPOINT relocate_usage_tables;", [])
+            .map_err(|e| AppError::Database(format!("开启迁移 savepoint 失败: {e}")))?;
+        match Self::relocate_usage_tables_on_conn(&conn) {
+            Ok(moved) => {
+                conn.execute("RELEASE relocate_usage_tables;", [])
+                    .map_err(|e| AppError::Database(format!("提交迁移失败: {e}")))?;
+                Ok(moved)
+            }
+            Err(e) => {
+                let _ = conn.execute_batch(
+                    "ROLLBACK TO relocate_usage_tables; RELEASE relocate_usage_tables;",
+                );
+                Err(e)
+            }
+        }
+    }
+
+    fn relocate_usage_tables_on_conn(conn: &Connection) -> Result<usize, AppError> {
+        Self::create_usage_tables(conn, USAGE_DB_SCHEMA)?;
+
+        let mut moved = 0;
+        for table in USAGE_TABLES {
+            if !Self::table_exists_in(conn, "main", table)? {
+                continue;
+            }
+
+            let main_columns = Self::column_infos(conn, "main", table)?;
+            let usage_columns = Self::column_infos(conn, USAGE_DB_SCHEMA, table)?;
+            for column in main_columns.iter().filter(|column| {
+                !usage_columns
+                    .iter()
+                    .any(|existing| existing.name.eq_ignore_ascii_case(&column.name))
+            }) {
+                // ADD COLUMN 的 NOT NULL 必须带默认值，没有默认值时放宽为可空
+                let constraint = match (&column.default, column.not_null) {
+                    (Some(default), true) => format!(" NOT NULL DEFAULT {default}"),
+                    (Some(default), false) => format!(" DEFAULT {default}"),
+                    (None, _) => String::new(),
+                };
+                conn.execute(
+                    &format!(
+                        "ALTER TABLE {USAGE_DB_SCHEMA}.\"{table}\" ADD COLUMN \"{}\" {}{constraint}",
+                        column.name, column.decl_type
+                    ),
+                    [],
+                )
+                .map_err(|e| {
+                    AppError::Database(format!("为独立库表 {table} 添加列 {} 失败: {e}", column.name))
+                })?;
+            }
+
+            let cols = main_columns
+                .iter()
+                .map(|column| format!("\"{}\"", column.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            // 独立库已有的行（如用户修改过的定价）优先保留
+            moved += conn
+                .execute(
+                    &format!(
+                        "INSERT OR IGNORE INTO {USAGE_DB_SCHEMA}.\"{table}\" ({cols})
+                         SELECT {cols} FROM main.\"{table}\""
+                    ),
+                    [],
+                )
+                .map_err(|e| AppError::Database(format!("迁移表 {table} 数据失败: {e}")))?;
+            conn.execute(&format!("DROP TABLE main.\"{table}\""), [])
+                .map_err(|e| AppError::Database(format!("移除主库表 {table} 失败: {e}")))?;
+        }
+        Ok(moved)
+    }
+
+    pub(crate) fn is_usage_db_attached(conn: &Connection) -> Result<bool, AppError> {
+        let mut stmt = conn
+            .prepare("PRAGMA database_list")
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(names.iter().any(|name| name == USAGE_DB_SCHEMA))
+    }
+
+    fn column_infos(
+        conn: &Connection,
+        schema: &str,
+        table: &str,
+    ) -> Result<Vec<ColumnInfo>, AppError> {
+        Self::validate_identifier(schema, "数据库名")?;
+        Self::validate_identifier(table, "表名")?;
+
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA {schema}.table_info(\"{table}\")"))
+            .map_err(|e| AppError::Database(format!("读取表结构失败: {e}")))?;
+        let columns = stmt
+            .query_map([], |row| {
+                Ok(ColumnInfo {
+                    name: row.get(1)?,
+                    decl_type: row.get(2)?,
+                    not_null: row.get(3)?,
+                    default: row.get(4)?,
+                })
+            })
+            .map_err(|e| AppError::Database(format!("查询表结构失败: {e}")))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(columns)
+    }
+}
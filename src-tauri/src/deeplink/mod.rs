@@ -22,10 +22,11 @@ use serde::{Deserialize, Serialize};
 
 // Re-export public API
 pub use mcp::import_mcp_from_deeplink;
-pub use parser::parse_deeplink_url;
+pub use parser::{parse_deeplink_url, parse_share_code};
 pub use prompt::import_prompt_from_deeplink;
 pub use provider::{import_provider_from_deeplink, parse_and_merge_config};
 pub use skill::import_skill_from_deeplink;
+pub(crate) use utils::infer_homepage_from_endpoint;
 
 /// Deep link import request model
 ///
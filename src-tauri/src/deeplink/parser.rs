@@ -2,7 +2,7 @@
 //!
 //! Parses ccswitch:// URLs into DeepLinkImportRequest structures.
 
-use super::utils::validate_url;
+use super::utils::{decode_base64_param, validate_url};
 use super::DeepLinkImportRequest;
 use crate::error::AppError;
 use std::collections::HashMap;
@@ -67,6 +67,16 @@ pub fn parse_deeplink_url(url_str: &str) -> Result<DeepLinkImportRequest, AppErr
     }
 }
 
+/// Parse a provider share code (URL-safe Base64 of a ccswitch:// URL)
+///
+/// Share codes are produced by `ProviderService::export_share_code` for QR sharing.
+pub fn parse_share_code(code: &str) -> Result<DeepLinkImportRequest, AppError> {
+    let decoded = decode_base64_param("share code", code.trim())?;
+    let url = String::from_utf8(decoded)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid UTF-8 in share code: {e}")))?;
+    parse_deeplink_url(&url)
+}
+
 /// Parse provider deep link parameters
 fn parse_provider_deeplink(
     params: &HashMap<String, String>,
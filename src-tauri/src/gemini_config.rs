@@ -3,7 +3,7 @@ use crate::error::AppError;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// 获取 Gemini 配置目录路径（支持设置覆盖）
 pub fn get_gemini_dir() -> PathBuf {
@@ -141,21 +141,30 @@ pub fn serialize_env_file(map: &HashMap<String, String>) -> String {
 
 /// 读取 Gemini .env 文件
 pub fn read_gemini_env() -> Result<HashMap<String, String>, AppError> {
-    let path = get_gemini_env_path();
+    read_gemini_env_at(&get_gemini_env_path())
+}
 
+/// 读取指定路径的 .env 文件（用于沙箱中的接管自检）
+pub(crate) fn read_gemini_env_at(path: &Path) -> Result<HashMap<String, String>, AppError> {
     if !path.exists() {
         return Ok(HashMap::new());
     }
 
-    let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+    let content = fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
 
     Ok(parse_env_file(&content))
 }
 
 /// 写入 Gemini .env 文件（原子操作）
 pub fn write_gemini_env_atomic(map: &HashMap<String, String>) -> Result<(), AppError> {
-    let path = get_gemini_env_path();
+    write_gemini_env_atomic_at(&get_gemini_env_path(), map)
+}
 
+/// 写入指定路径的 .env 文件（原子操作，用于沙箱中的接管自检）
+pub(crate) fn write_gemini_env_atomic_at(
+    path: &Path,
+    map: &HashMap<String, String>,
+) -> Result<(), AppError> {
     // 确保目录存在
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
@@ -173,17 +182,17 @@ pub fn write_gemini_env_atomic(map: &HashMap<String, String>) -> Result<(), AppE
     }
 
     let content = serialize_env_file(map);
-    write_text_file(&path, &content)?;
+    write_text_file(path, &content)?;
 
     // 设置文件权限为 600（仅所有者可读写）
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&path)
-            .map_err(|e| AppError::io(&path, e))?
+        let mut perms = fs::metadata(path)
+            .map_err(|e| AppError::io(path, e))?
             .permissions();
         perms.set_mode(0o600);
-        fs::set_permissions(&path, perms).map_err(|e| AppError::io(&path, e))?;
+        fs::set_permissions(path, perms).map_err(|e| AppError::io(path, e))?;
     }
 
     Ok(())
@@ -225,12 +234,32 @@ pub fn validate_gemini_settings(settings: &Value) -> Result<(), AppError> {
     // 只验证基本结构，不强制要求 GEMINI_API_KEY
     // 如果有 env 字段，验证它是一个对象
     if let Some(env) = settings.get("env") {
-        if !env.is_object() {
+        let Some(env_obj) = env.as_object() else {
             return Err(AppError::localized(
                 "gemini.validation.invalid_env",
                 "Gemini 配置格式错误: env 必须是对象",
                 "Gemini config invalid: env must be an object",
             ));
+        };
+
+        // .env 只能保存字符串，非字符串值在 json_to_env 中会被静默丢弃，这里提前报错
+        // 数字形式的值需要以字符串写入（如 "8080"）
+        for (key, value) in env_obj {
+            if !value.is_string() {
+                let kind = match value {
+                    Value::Null => "null",
+                    Value::Bool(_) => "boolean",
+                    Value::Number(_) => "number",
+                    Value::Array(_) => "array",
+                    Value::Object(_) => "object",
+                    Value::String(_) => "string",
+                };
+                return Err(AppError::localized(
+                    "gemini.validation.env_value_not_string",
+                    format!("Gemini 配置格式错误: env.{key} 必须是字符串（当前为 {kind}），请用引号包裹，如 \"{value}\""),
+                    format!("Gemini config invalid: env.{key} must be a string (got {kind}); wrap it in quotes, e.g. \"{value}\""),
+                ));
+            }
         }
     }
 
@@ -651,4 +680,52 @@ KEY_WITH-DASH=value";
 
         assert!(validate_gemini_settings(&settings).is_err());
     }
+
+    #[test]
+    fn test_validate_rejects_numeric_env_value() {
+        let settings = serde_json::json!({
+            "env": {
+                "GEMINI_API_KEY": "sk-test123",
+                "GEMINI_TIMEOUT": 30
+            }
+        });
+
+        let err = validate_gemini_settings(&settings).expect_err("number should be rejected");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("GEMINI_TIMEOUT"),
+            "error should name key: {msg}"
+        );
+        assert!(msg.contains("number"), "error should name type: {msg}");
+    }
+
+    #[test]
+    fn test_validate_rejects_boolean_env_value() {
+        let settings = serde_json::json!({
+            "env": {
+                "GEMINI_API_KEY": "sk-test123",
+                "GEMINI_SANDBOX": true
+            }
+        });
+
+        let err = validate_gemini_settings(&settings).expect_err("boolean should be rejected");
+        let msg = err.to_string();
+        assert!(
+            msg.contains("GEMINI_SANDBOX"),
+            "error should name key: {msg}"
+        );
+        assert!(msg.contains("boolean"), "error should name type: {msg}");
+    }
+
+    #[test]
+    fn test_validate_accepts_quoted_numeric_env_value() {
+        let settings = serde_json::json!({
+            "env": {
+                "GEMINI_API_KEY": "sk-test123",
+                "GEMINI_TIMEOUT": "30"
+            }
+        });
+
+        assert!(validate_gemini_settings(&settings).is_ok());
+    }
 }
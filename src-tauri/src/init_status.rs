@@ -93,6 +93,85 @@ pub fn take_skills_migration_result() -> Option<SkillsMigrationPayload> {
     }
 }
 
+// ============================================================
+// 安全模式
+// ============================================================
+
+/// 开启安全模式的环境变量（值为 1/true/yes/on 时生效）
+pub const SAFE_MODE_ENV: &str = "CC_SWITCH_SAFE_MODE";
+/// 开启安全模式的命令行参数
+pub const SAFE_MODE_ARG: &str = "--safe-mode";
+
+/// 安全模式下跳过的启动步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StartupStep {
+    /// 异常退出后的 Live 配置恢复（含通用配置片段自动提取，避免读到接管占位符）
+    TakeoverRecovery,
+    /// 代理服务自动启动与接管状态恢复
+    ProxyAutoStart,
+    /// Skills 自动导入（SSOT 迁移）
+    SkillMigration,
+}
+
+impl StartupStep {
+    pub const ALL: [StartupStep; 3] = [
+        StartupStep::TakeoverRecovery,
+        StartupStep::ProxyAutoStart,
+        StartupStep::SkillMigration,
+    ];
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeModeStatus {
+    pub enabled: bool,
+    pub skipped_steps: Vec<StartupStep>,
+}
+
+impl SafeModeStatus {
+    /// 根据环境变量与启动参数判断是否进入安全模式
+    pub fn detect(env_value: Option<&str>, args: &[String]) -> Self {
+        let from_env = env_value.is_some_and(|v| {
+            matches!(
+                v.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+        let from_args = args.iter().any(|arg| arg == SAFE_MODE_ARG);
+        let enabled = from_env || from_args;
+        Self {
+            enabled,
+            skipped_steps: if enabled {
+                StartupStep::ALL.to_vec()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// 指定启动步骤是否应当执行
+    pub fn allows(&self, step: StartupStep) -> bool {
+        !self.skipped_steps.contains(&step)
+    }
+}
+
+static SAFE_MODE: OnceLock<SafeModeStatus> = OnceLock::new();
+
+/// 从当前进程的环境变量与参数初始化安全模式状态（仅首次调用生效）
+pub fn init_safe_mode() -> &'static SafeModeStatus {
+    SAFE_MODE.get_or_init(|| {
+        let env_value = std::env::var(SAFE_MODE_ENV).ok();
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        SafeModeStatus::detect(env_value.as_deref(), &args)
+    })
+}
+
+/// 获取安全模式状态（未初始化时视为关闭）
+pub fn get_safe_mode() -> SafeModeStatus {
+    SAFE_MODE.get().cloned().unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +187,36 @@ mod tests {
         assert_eq!(got.path, payload.path);
         assert_eq!(got.error, payload.error);
     }
+
+    #[test]
+    fn safe_mode_detects_env_and_flag() {
+        assert!(!SafeModeStatus::detect(None, &[]).enabled);
+        assert!(!SafeModeStatus::detect(Some("0"), &[]).enabled);
+        assert!(!SafeModeStatus::detect(Some(""), &["--minimized".into()]).enabled);
+
+        assert!(SafeModeStatus::detect(Some("1"), &[]).enabled);
+        assert!(SafeModeStatus::detect(Some(" TRUE "), &[]).enabled);
+        assert!(SafeModeStatus::detect(None, &[SAFE_MODE_ARG.into()]).enabled);
+    }
+
+    #[test]
+    fn safe_mode_skips_recovery_steps() {
+        let normal = SafeModeStatus::detect(None, &[]);
+        assert!(normal.skipped_steps.is_empty());
+        for step in StartupStep::ALL {
+            assert!(normal.allows(step));
+        }
+
+        let safe = SafeModeStatus::detect(Some("yes"), &[]);
+        assert!(!safe.allows(StartupStep::TakeoverRecovery));
+        assert!(!safe.allows(StartupStep::ProxyAutoStart));
+        assert!(!safe.allows(StartupStep::SkillMigration));
+        assert_eq!(
+            serde_json::to_value(&safe).expect("serialize"),
+            serde_json::json!({
+                "enabled": true,
+                "skippedSteps": ["takeoverRecovery", "proxyAutoStart", "skillMigration"]
+            })
+        );
+    }
 }
@@ -12,6 +12,7 @@ mod error;
 mod gemini_config;
 mod gemini_mcp;
 mod init_status;
+mod log_filter;
 mod mcp;
 mod openclaw_config;
 mod opencode_config;
@@ -21,6 +22,7 @@ mod prompt_files;
 mod provider;
 mod provider_defaults;
 mod proxy;
+mod secret_store;
 mod services;
 mod session_manager;
 mod settings;
@@ -33,8 +35,13 @@ pub use app_config::{AppType, InstalledSkill, McpApps, McpServer, MultiAppConfig
 pub use codex_config::{get_codex_auth_path, get_codex_config_path, write_codex_live_atomic};
 pub use commands::open_provider_terminal;
 pub use commands::*;
-pub use config::{get_claude_mcp_path, get_claude_settings_path, read_json_file};
-pub use database::Database;
+pub use config::{
+    get_claude_desktop_config_path, get_claude_mcp_path, get_claude_settings_path, read_json_file,
+};
+pub use database::{
+    Database, EndpointLatencySample, ProviderHistoryEntry, SwitchEvent,
+    ENDPOINT_LATENCY_HISTORY_LIMIT, PROVIDER_HISTORY_LIMIT,
+};
 pub use deeplink::{import_provider_from_deeplink, parse_deeplink_url, DeepLinkImportRequest};
 pub use error::AppError;
 pub use mcp::{
@@ -44,12 +51,15 @@ pub use mcp::{
     sync_single_server_to_codex, sync_single_server_to_gemini,
 };
 pub use provider::{Provider, ProviderMeta};
+pub use secret_store::{
+    PlaintextBackend, SecretBackend, SecretStore, SecretStoreKind, SecretStoreStatus,
+};
 pub use services::{
-    skill::{migrate_skills_to_ssot, ImportSkillSelection},
-    ConfigService, EndpointLatency, McpService, PromptService, ProviderService, ProxyService,
-    SkillService, SpeedtestService,
+    skill::{migrate_skills_to_ssot, ImportSkillSelection, SkillManifest, SyncMethod},
+    ConfigService, EndpointLatency, IntegrityIssueKind, McpService, PromptService, ProviderService,
+    ProxyService, SkillService, SpeedtestService,
 };
-pub use settings::{update_settings, AppSettings};
+pub use settings::{get_effective_current_provider_with, update_settings, AppSettings};
 pub use store::AppState;
 use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
@@ -289,6 +299,8 @@ pub fn run() {
                     tauri_plugin_log::Builder::default()
                         // 初始化为 Trace，允许后续通过 log::set_max_level() 动态调整级别
                         .level(log::LevelFilter::Trace)
+                        // 按模块覆盖级别（见 LogConfig.module_levels）
+                        .filter(log_filter::enabled)
                         .targets([
                             Target::new(TargetKind::Stdout),
                             Target::new(TargetKind::Folder {
@@ -307,6 +319,15 @@ pub fn run() {
                 )?;
             }
 
+            // 安全模式：只初始化数据库与界面，跳过可能因坏配置导致崩溃的恢复步骤
+            let safe_mode = crate::init_status::init_safe_mode();
+            if safe_mode.enabled {
+                log::warn!(
+                    "已进入安全模式，跳过启动步骤: {:?}",
+                    safe_mode.skipped_steps
+                );
+            }
+
             // 初始化数据库
             let app_config_dir = crate::config::get_app_config_dir();
             let db_path = app_config_dir.join("cc-switch.db");
@@ -391,6 +412,19 @@ pub fn run() {
                 }
             }
 
+            // 探测安全存储（钥匙串可能需要用户解锁，放到后台线程避免阻塞启动）
+            std::thread::spawn(|| {
+                let status = crate::secret_store::init_secret_store();
+                log::info!("✓ 敏感信息存储后端: {:?}", status.backend);
+            });
+
+            // 修复迁移异常遗留的多个 is_current 标记
+            match db.repair_current_flags() {
+                Ok(fixed) if fixed > 0 => log::warn!("✓ Repaired {fixed} provider is_current flag(s)"),
+                Ok(_) => {}
+                Err(e) => log::warn!("✗ Failed to repair provider is_current flags: {e}"),
+            }
+
             let app_state = AppState::new(db);
 
             // 设置 AppHandle 用于代理故障转移时的 UI 更新
@@ -412,6 +446,13 @@ pub fn run() {
             // 1.1. Skills 统一管理迁移：当数据库迁移到 v3 结构后，自动从各应用目录导入到 SSOT
             // 触发条件由 schema 迁移设置 settings.skills_ssot_migration_pending = true 控制。
             match app_state.db.get_setting("skills_ssot_migration_pending") {
+                Ok(Some(flag))
+                    if !safe_mode.allows(crate::init_status::StartupStep::SkillMigration)
+                        && (flag == "true" || flag == "1") =>
+                {
+                    // 安全模式：保留 pending 标志，正常启动后再导入
+                    log::info!("安全模式：跳过 Skills 自动导入");
+                }
                 Ok(Some(flag)) if flag == "true" || flag == "1" => {
                     // 安全保护：如果用户已经有 v3 结构的 Skills 数据，就不要自动清空重建。
                     let has_existing = app_state
@@ -677,7 +718,7 @@ pub fn run() {
             {
                 let db = &app.state::<AppState>().db;
                 if let Ok(log_config) = db.get_log_config() {
-                    log::set_max_level(log_config.to_level_filter());
+                    log_filter::apply(&log_config);
                     log::info!(
                         "已加载日志配置: enabled={}, level={}",
                         log_config.enabled,
@@ -738,36 +779,33 @@ pub fn run() {
             tauri::async_runtime::spawn(async move {
                 let state = app_handle.state::<AppState>();
 
-                // 检查是否有 Live 备份（表示上次异常退出时可能处于接管状态）
-                let has_backups = match state.db.has_any_live_backup().await {
-                    Ok(v) => v,
-                    Err(e) => {
-                        log::error!("检查 Live 备份失败: {e}");
-                        false
-                    }
-                };
-                // 检查 Live 配置是否仍处于被接管状态（包含占位符）
-                let live_taken_over = state.proxy_service.detect_takeover_in_live_configs();
-
-                if has_backups || live_taken_over {
-                    log::warn!("检测到上次异常退出（存在接管残留），正在恢复 Live 配置...");
-                    if let Err(e) = state.proxy_service.recover_from_crash().await {
-                        log::error!("恢复 Live 配置失败: {e}");
-                    } else {
-                        log::info!("Live 配置已恢复");
+                recover_startup_state(&state, safe_mode).await;
+
+                // 配置一致性自检（仅报告，不自动修复）
+                match crate::services::ConfigService::integrity_check(&state, false).await {
+                    Ok(issues) => {
+                        for issue in issues {
+                            log::warn!(
+                                "[{}] 配置一致性问题: {}（建议: {}）",
+                                issue.app,
+                                issue.detail,
+                                issue.suggested_fix
+                            );
+                        }
                     }
+                    Err(e) => log::warn!("配置一致性自检失败: {e}"),
                 }
 
-                initialize_common_config_snippets(&state);
-
-                // 检查 settings 表中的代理状态，自动恢复代理服务
-                restore_proxy_state_on_startup(&state).await;
-
                 // Periodic backup check (on startup)
                 if let Err(e) = state.db.periodic_backup_if_needed() {
                     log::warn!("Periodic backup failed on startup: {e}");
                 }
 
+                // 启动时会增量写入新模型定价，顺带补算此前缺少定价的日志成本
+                if let Err(e) = state.db.backfill_all_zero_cost_logs() {
+                    log::warn!("Cost backfill failed on startup: {e}");
+                }
+
                 // Periodic maintenance timer: run once per day while the app is running
                 let db_for_timer = state.db.clone();
                 tauri::async_runtime::spawn(async move {
@@ -781,6 +819,9 @@ pub fn run() {
                         if let Err(e) = db_for_timer.periodic_backup_if_needed() {
                             log::warn!("Periodic maintenance timer failed: {e}");
                         }
+                        if let Err(e) = db_for_timer.backfill_all_zero_cost_logs() {
+                            log::warn!("Periodic cost backfill failed: {e}");
+                        }
                     }
                 });
             });
@@ -829,17 +870,51 @@ pub fn run() {
             commands::delete_provider,
             commands::remove_provider_from_live_config,
             commands::switch_provider,
+            commands::set_default_provider,
+            commands::export_provider_native,
+            commands::switch_provider_and_launch,
+            commands::convert_provider,
+            commands::broadcast_common_config,
+            commands::import_provider_from_env_file,
+            commands::import_provider_from_curl,
+            commands::import_providers_merge,
+            commands::get_stale_providers,
+            commands::check_token_freshness,
+            commands::probe_provider_capabilities,
+            commands::import_official_config,
+            commands::lint_provider,
+            commands::normalize_base_urls,
+            commands::normalize_claude_models,
+            commands::normalize_claude_models_report,
+            commands::preview_apply_common_config,
+            commands::export_provider_share_code,
+            commands::warm_up_provider,
+            commands::benchmark_provider_throughput,
+            commands::format_codex_config,
+            commands::set_provider_enabled,
+            commands::set_provider_frozen,
+            commands::get_provider_history,
+            commands::revert_provider,
+            commands::get_switch_events,
+            commands::live_has_unmanaged_changes,
             commands::import_default_config,
+            commands::adopt_current_live,
             commands::get_claude_config_status,
             commands::get_config_status,
+            commands::get_live_status,
+            commands::check_config_integrity,
+            commands::verify_live_config,
+            commands::migrate_app_config_dir,
             commands::get_claude_code_config_path,
             commands::get_config_dir,
             commands::open_config_folder,
             commands::pick_directory,
             commands::open_external,
             commands::get_init_error,
+            commands::secret_store_status,
             commands::get_migration_result,
             commands::get_skills_migration_result,
+            commands::get_safe_mode_status,
             commands::get_app_config_path,
             commands::open_app_config_folder,
             commands::get_claude_common_config_snippet,
@@ -856,6 +931,8 @@ pub fn run() {
             commands::set_optimizer_config,
             commands::get_log_config,
             commands::set_log_config,
+            commands::set_log_level,
+            commands::dump_settings,
             commands::restart_app,
             commands::check_for_updates,
             commands::is_portable_mode,
@@ -885,6 +962,7 @@ pub fn run() {
             commands::delete_mcp_server,
             commands::toggle_mcp_app,
             commands::import_mcp_from_apps,
+            commands::import_mcp_from_claude_desktop,
             // Prompt management
             commands::get_prompts,
             commands::upsert_prompt,
@@ -895,9 +973,11 @@ pub fn run() {
             // ours: endpoint speed test + custom endpoint management
             commands::test_api_endpoints,
             commands::get_custom_endpoints,
+            commands::get_endpoint_matrix,
             commands::add_custom_endpoint,
             commands::remove_custom_endpoint,
             commands::update_endpoint_last_used,
+            commands::get_endpoint_latency_trend,
             // app_config_dir override via Store
             commands::get_app_config_dir_override,
             commands::set_app_config_dir_override,
@@ -938,6 +1018,12 @@ pub fn run() {
             commands::uninstall_skill_unified,
             commands::restore_skill_backup,
             commands::toggle_skill_app,
+            commands::set_skill_sync_method,
+            commands::set_app_skills,
+            commands::refresh_skill_metadata,
+            commands::refresh_all_skill_metadata,
+            commands::export_skill_manifest,
+            commands::apply_skill_manifest,
             commands::scan_unmanaged_skills,
             commands::import_skills_from_apps,
             commands::discover_available_skills,
@@ -958,6 +1044,7 @@ pub fn run() {
             // Proxy server management
             commands::start_proxy_server,
             commands::stop_proxy_with_restore,
+            commands::factory_reset_proxy,
             commands::get_proxy_takeover_status,
             commands::set_proxy_takeover_for_app,
             commands::get_proxy_status,
@@ -975,9 +1062,15 @@ pub fn run() {
             commands::is_proxy_running,
             commands::is_live_takeover_active,
             commands::switch_proxy_provider,
+            commands::benchmark_proxy_overhead,
+            commands::proxy_takeover_self_test,
             // Proxy failover commands
             commands::get_provider_health,
             commands::reset_circuit_breaker,
+            commands::clear_provider_health,
+            commands::set_logging_paused,
+            commands::subscribe_proxy_requests,
+            commands::unsubscribe_proxy_requests,
             commands::get_circuit_breaker_config,
             commands::update_circuit_breaker_config,
             commands::get_circuit_breaker_stats,
@@ -988,17 +1081,28 @@ pub fn run() {
             commands::remove_from_failover_queue,
             commands::get_auto_failover_enabled,
             commands::set_auto_failover_enabled,
+            commands::get_routing_strategy,
+            commands::set_routing_strategy,
+            commands::get_provider_groups,
+            commands::save_provider_group,
+            commands::delete_provider_group,
             // Usage statistics
             commands::get_usage_summary,
             commands::get_usage_trends,
             commands::get_provider_stats,
+            commands::get_providers_by_spend,
             commands::get_model_stats,
             commands::get_request_logs,
             commands::get_request_detail,
+            commands::get_session_logs,
+            commands::get_session_cost,
+            commands::export_session_logs,
             commands::get_model_pricing,
             commands::update_model_pricing,
             commands::delete_model_pricing,
             commands::check_provider_limits,
+            commands::estimate_request_cost,
+            commands::delete_request_logs_between,
             // Stream health check
             commands::stream_check_provider,
             commands::stream_check_all_providers,
@@ -1250,30 +1354,110 @@ pub async fn cleanup_before_exit(app_handle: &tauri::AppHandle) {
 // 启动时恢复代理状态
 // ============================================================
 
+/// 启动时的接管残留恢复、通用配置片段提取与代理状态恢复（安全模式下按步骤跳过）
+async fn recover_startup_state(
+    state: &store::AppState,
+    safe_mode: &crate::init_status::SafeModeStatus,
+) {
+    use crate::init_status::StartupStep;
+
+    // 检查是否有 Live 备份（表示上次异常退出时可能处于接管状态）
+    let has_backups = match state.db.has_any_live_backup().await {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("检查 Live 备份失败: {e}");
+            false
+        }
+    };
+    // 检查 Live 配置是否仍处于被接管状态（包含占位符）
+    let live_taken_over = state.proxy_service.detect_takeover_in_live_configs();
+
+    let recovered_from_crash = has_backups || live_taken_over;
+    if !safe_mode.allows(StartupStep::TakeoverRecovery) {
+        if recovered_from_crash {
+            log::warn!("安全模式：检测到接管残留，已跳过 Live 配置恢复");
+        }
+    } else if recovered_from_crash {
+        log::warn!("检测到上次异常退出（存在接管残留），正在恢复 Live 配置...");
+        if let Err(e) = state.proxy_service.recover_from_crash().await {
+            log::error!("恢复 Live 配置失败: {e}");
+        } else {
+            log::info!("Live 配置已恢复");
+        }
+    }
+
+    // Live 配置可能仍处于接管状态，安全模式下不从中提取通用配置片段
+    if safe_mode.allows(StartupStep::TakeoverRecovery) {
+        initialize_common_config_snippets(state);
+    }
+
+    // 检查 settings 表中的代理状态，自动恢复代理服务
+    if safe_mode.allows(StartupStep::ProxyAutoStart) {
+        restore_proxy_state_on_startup(state, recovered_from_crash).await;
+    } else {
+        log::warn!("安全模式：已跳过代理服务自动启动与接管恢复");
+    }
+}
+
+#[cfg_attr(not(feature = "test-hooks"), doc(hidden))]
+pub async fn recover_startup_state_test_hook(
+    state: &store::AppState,
+    safe_mode_env: Option<&str>,
+) {
+    let safe_mode = crate::init_status::SafeModeStatus::detect(safe_mode_env, &[]);
+    recover_startup_state(state, &safe_mode).await;
+}
+
 /// 启动时根据 proxy_config 表中的代理状态自动恢复代理服务
 ///
 /// 检查 `proxy_config.enabled` 字段，如果有任一应用的状态为 `true`，
 /// 则自动启动代理服务并接管对应应用的 Live 配置。
-async fn restore_proxy_state_on_startup(state: &store::AppState) {
+/// 开启 `auto_start_proxy` 设置时，即使没有应用需要接管也会启动代理服务（上次异常退出时跳过）。
+async fn restore_proxy_state_on_startup(state: &store::AppState, recovered_from_crash: bool) {
     // 收集需要恢复接管的应用列表（从 proxy_config.enabled 读取）
-    let mut apps_to_restore = Vec::new();
+    let mut enabled_apps = Vec::new();
     for app_type in ["claude", "codex", "gemini"] {
         if let Ok(config) = state.db.get_proxy_config_for_app(app_type).await {
             if config.enabled {
-                apps_to_restore.push(app_type);
+                enabled_apps.push(app_type);
             }
         }
     }
 
-    if apps_to_restore.is_empty() {
+    let auto_start_proxy = crate::settings::get_settings().auto_start_proxy;
+    let plan = crate::services::proxy::StartupProxyPlan::decide(
+        auto_start_proxy,
+        recovered_from_crash,
+        &enabled_apps,
+    );
+
+    if auto_start_proxy && recovered_from_crash {
+        log::warn!("上次异常退出，已跳过代理服务自动启动");
+    }
+
+    if plan.is_empty() {
         log::debug!("启动时无需恢复代理状态");
         return;
     }
 
-    log::info!("检测到上次代理状态需要恢复，应用列表: {apps_to_restore:?}");
+    if plan.start_server {
+        match state.proxy_service.start().await {
+            Ok(info) => log::info!("✓ 已自动启动代理服务: {}:{}", info.address, info.port),
+            Err(e) => log::error!("✗ 自动启动代理服务失败: {e}"),
+        }
+    }
+
+    if plan.takeover_apps.is_empty() {
+        return;
+    }
+
+    log::info!(
+        "检测到上次代理状态需要恢复，应用列表: {:?}",
+        plan.takeover_apps
+    );
 
     // 逐个恢复接管状态
-    for app_type in apps_to_restore {
+    for app_type in &plan.takeover_apps {
         match state
             .proxy_service
             .set_takeover_for_app(app_type, true)
@@ -1320,6 +1504,7 @@ fn initialize_common_config_snippets(state: &store::AppState) {
         match crate::services::provider::ProviderService::extract_common_config_snippet_from_settings(
             app_type.clone(),
             &settings,
+            false,
         ) {
             Ok(snippet) if !snippet.is_empty() && snippet != "{}" => {
                 match state.db.set_config_snippet(app_type.as_str(), Some(snippet)) {
@@ -0,0 +1,216 @@
+//! 按模块的日志级别过滤
+//!
+//! 全局级别由 `log::set_max_level()` 控制，只能整体调高或调低。这里维护一份
+//! 模块 → 级别的覆盖表（来自 `LogConfig.module_levels`），由日志插件的 filter 回调逐条判断：
+//! - 模块路径不含 crate 前缀，如 `proxy::handlers`、`services::provider`
+//! - 按 `::` 边界匹配，最长前缀优先；未命中时使用全局级别
+//! - `log::set_max_level()` 取全局级别与所有覆盖级别的最大值，避免覆盖被提前截断
+
+use crate::proxy::types::{parse_level_filter, LogConfig};
+use log::LevelFilter;
+use std::sync::{OnceLock, RwLock};
+
+/// 本 crate 的日志 target 前缀
+const CRATE_PREFIX: &str = "cc_switch_lib::";
+
+/// 模块级别过滤器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleFilter {
+    default: LevelFilter,
+    /// 按模块路径长度降序排列，保证最长前缀优先
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Default for ModuleFilter {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::Trace,
+            modules: Vec::new(),
+        }
+    }
+}
+
+impl ModuleFilter {
+    /// 从日志配置构建（无法识别的级别会被忽略并记录警告）
+    pub fn from_config(config: &LogConfig) -> Self {
+        let default = config.to_level_filter();
+        let mut modules: Vec<(String, LevelFilter)> = if config.enabled {
+            config
+                .module_levels
+                .iter()
+                .filter_map(|(module, level)| {
+                    let module = normalize_module(module);
+                    if module.is_empty() {
+                        return None;
+                    }
+                    match parse_level_filter(level) {
+                        Some(level) => Some((module.to_string(), level)),
+                        None => {
+                            log::warn!("忽略无效的模块日志级别: {module}={level}");
+                            None
+                        }
+                    }
+                })
+                .collect()
+        } else {
+            // 日志整体关闭时不启用任何覆盖
+            Vec::new()
+        };
+        modules.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        Self { default, modules }
+    }
+
+    /// 指定 target 生效的级别
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        self.modules
+            .iter()
+            .find(|(module, _)| {
+                target == module
+                    || target
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// 所有规则中最宽松的级别（用于 `log::set_max_level`）
+    pub fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, LevelFilter::max)
+    }
+
+    pub fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+}
+
+/// 去掉首尾空白与 crate 前缀
+fn normalize_module(module: &str) -> &str {
+    let module = module.trim().trim_end_matches("::");
+    module.strip_prefix(CRATE_PREFIX).unwrap_or(module)
+}
+
+fn global() -> &'static RwLock<ModuleFilter> {
+    static FILTER: OnceLock<RwLock<ModuleFilter>> = OnceLock::new();
+    FILTER.get_or_init(|| RwLock::new(ModuleFilter::default()))
+}
+
+/// 应用日志配置（全局级别 + 模块覆盖）
+pub fn apply(config: &LogConfig) {
+    let filter = ModuleFilter::from_config(config);
+    log::set_max_level(filter.max_level());
+    match global().write() {
+        Ok(mut guard) => *guard = filter,
+        Err(poisoned) => *poisoned.into_inner() = filter,
+    }
+}
+
+/// 日志插件的 filter 回调
+pub fn enabled(metadata: &log::Metadata) -> bool {
+    match global().read() {
+        Ok(guard) => guard.enabled(metadata),
+        Err(poisoned) => poisoned.into_inner().enabled(metadata),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(level: &str, modules: &[(&str, &str)]) -> LogConfig {
+        LogConfig {
+            enabled: true,
+            level: level.to_string(),
+            module_levels: modules
+                .iter()
+                .map(|(m, l)| (m.to_string(), l.to_string()))
+                .collect::<HashMap<_, _>>(),
+            ..Default::default()
+        }
+    }
+
+    fn allows(filter: &ModuleFilter, target: &str, level: log::Level) -> bool {
+        filter.enabled(&log::Metadata::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn module_override_applies_to_matching_target() {
+        let filter = ModuleFilter::from_config(&config(
+            "info",
+            &[("proxy::handlers", "warn"), ("services::provider", "debug")],
+        ));
+
+        assert!(!allows(
+            &filter,
+            "cc_switch_lib::proxy::handlers",
+            log::Level::Info
+        ));
+        assert!(allows(
+            &filter,
+            "cc_switch_lib::proxy::handlers",
+            log::Level::Warn
+        ));
+        assert!(allows(
+            &filter,
+            "cc_switch_lib::services::provider::live",
+            log::Level::Debug
+        ));
+        // 未覆盖的模块沿用全局级别
+        assert!(allows(
+            &filter,
+            "cc_switch_lib::proxy::forwarder",
+            log::Level::Info
+        ));
+        assert!(!allows(
+            &filter,
+            "cc_switch_lib::proxy::forwarder",
+            log::Level::Debug
+        ));
+        // 必须按 `::` 边界匹配
+        assert!(allows(
+            &filter,
+            "cc_switch_lib::proxy::handlers_ext",
+            log::Level::Info
+        ));
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn longest_prefix_wins_and_invalid_levels_are_ignored() {
+        let filter = ModuleFilter::from_config(&config(
+            "info",
+            &[
+                ("proxy", "error"),
+                ("cc_switch_lib::proxy::usage", "trace"),
+                ("database", "verbose"),
+            ],
+        ));
+
+        assert_eq!(
+            filter.level_for("cc_switch_lib::proxy::server"),
+            LevelFilter::Error
+        );
+        assert_eq!(
+            filter.level_for("cc_switch_lib::proxy::usage::logger"),
+            LevelFilter::Trace
+        );
+        assert_eq!(
+            filter.level_for("cc_switch_lib::database::dao"),
+            LevelFilter::Info
+        );
+    }
+
+    #[test]
+    fn disabled_logging_ignores_overrides() {
+        let mut cfg = config("info", &[("proxy", "trace")]);
+        cfg.enabled = false;
+        let filter = ModuleFilter::from_config(&cfg);
+        assert_eq!(filter.max_level(), LevelFilter::Off);
+        assert_eq!(filter.level_for("cc_switch_lib::proxy"), LevelFilter::Off);
+    }
+}
@@ -0,0 +1,92 @@
+//! Claude Desktop MCP 导入模块
+//!
+//! Claude Desktop 的 `claude_desktop_config.json` 使用与 `~/.claude.json` 相同的 `mcpServers` 结构，
+//! 条目通常省略 `type`（视为 stdio）。
+
+use serde_json::Value;
+
+use crate::error::AppError;
+
+use super::validation::validate_server_spec;
+
+/// 解析 Claude Desktop 配置中的 `mcpServers`，返回校验通过的 (id, 连接定义)
+///
+/// 单项校验失败只记录警告并跳过；整体不是合法 JSON 时返回错误。
+pub fn parse_claude_desktop_servers(text: &str) -> Result<Vec<(String, Value)>, AppError> {
+    let v: Value = serde_json::from_str(text)
+        .map_err(|e| AppError::McpValidation(format!("解析 Claude Desktop 配置失败: {e}")))?;
+    let Some(map) = v.get("mcpServers").and_then(|x| x.as_object()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut servers = Vec::new();
+    for (id, spec) in map {
+        if let Err(e) = validate_server_spec(spec) {
+            log::warn!("跳过无效的 Claude Desktop MCP 服务器 '{id}': {e}");
+            continue;
+        }
+        servers.push((id.clone(), spec.clone()));
+    }
+    Ok(servers)
+}
+
+/// 读取 Claude Desktop 配置中的 MCP 服务器；配置文件不存在时返回空列表
+pub fn read_claude_desktop_servers() -> Result<Vec<(String, Value)>, AppError> {
+    let path = crate::config::get_claude_desktop_config_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+    parse_claude_desktop_servers(&text)
+}
+
+/// 用于判断重复的命令标识：stdio 取 command，远程服务器取 url
+pub fn server_command(spec: &Value) -> Option<&str> {
+    spec.get("command")
+        .or_else(|| spec.get("url"))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "globalShortcut": "",
+        "mcpServers": {
+            "filesystem": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-filesystem", "/Users/me/Desktop"]
+            },
+            "github": {
+                "command": "docker",
+                "args": ["run", "-i", "--rm", "ghcr.io/github/github-mcp-server"],
+                "env": {"GITHUB_PERSONAL_ACCESS_TOKEN": "ghp_xxx"}
+            },
+            "broken": {"args": ["missing-command"]}
+        }
+    }"#;
+
+    #[test]
+    fn parses_sample_config_and_skips_invalid_entries() {
+        let servers = parse_claude_desktop_servers(SAMPLE).expect("parse sample");
+        let ids: Vec<&str> = servers.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["filesystem", "github"]);
+        assert_eq!(server_command(&servers[0].1), Some("npx"));
+        assert_eq!(
+            servers[1].1["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"],
+            "ghp_xxx"
+        );
+    }
+
+    #[test]
+    fn config_without_mcp_servers_is_empty() {
+        assert!(
+            parse_claude_desktop_servers(r#"{"globalShortcut": "Cmd+Space"}"#)
+                .expect("parse")
+                .is_empty()
+        );
+        assert!(parse_claude_desktop_servers("{\"mcpServers\":").is_err());
+    }
+}
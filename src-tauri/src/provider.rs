@@ -40,6 +40,13 @@ pub struct Provider {
     #[serde(default)]
     #[serde(rename = "inFailoverQueue")]
     pub in_failover_queue: bool,
+    /// 是否启用（禁用后保留配置，但不参与路由、故障转移，也不能被切换为当前供应商）
+    #[serde(default = "default_provider_enabled")]
+    pub enabled: bool,
+}
+
+fn default_provider_enabled() -> bool {
+    true
 }
 
 impl Provider {
@@ -63,8 +70,42 @@ impl Provider {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
+
+    /// 加权路由权重（见 `ProviderMeta.routing_weight`，最小为 1）
+    pub fn routing_weight(&self) -> u32 {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.routing_weight)
+            .unwrap_or(1)
+            .max(1)
+    }
+
+    /// 是否开启原样透传（见 `ProviderMeta.raw_passthrough`）
+    pub fn is_raw_passthrough(&self) -> bool {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.raw_passthrough)
+            .unwrap_or(false)
+    }
+
+    /// 是否为兜底供应商（见 `ProviderMeta.is_safety_net`）
+    pub fn is_safety_net(&self) -> bool {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.is_safety_net)
+            .unwrap_or(false)
+    }
+
+    /// 是否已冻结（见 `ProviderMeta.frozen`）
+    pub fn is_frozen(&self) -> bool {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.frozen)
+            .unwrap_or(false)
+    }
 }
 
 /// 供应商管理器
@@ -191,6 +232,52 @@ pub struct ProviderProxyConfig {
     pub proxy_password: Option<String>,
 }
 
+/// 供应商能力探测结果（由能力探测写入 meta.capabilities）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityReport {
+    /// 是否支持流式请求
+    pub streaming: bool,
+    /// 是否支持非流式请求
+    pub non_streaming: bool,
+    /// 是否遵循工具调用（非流式请求失败时无法判断，为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use: Option<bool>,
+    /// 是否遵循系统提示词（非流式请求失败时无法判断，为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<bool>,
+    /// 探测使用的模型
+    pub model: String,
+    /// 流式请求失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_error: Option<String>,
+    /// 非流式请求失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_stream_error: Option<String>,
+    /// 探测时间（Unix 秒）
+    pub tested_at: i64,
+}
+
+/// 供应商吞吐测试结果（由吞吐测试写入 meta.throughput）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputReport {
+    /// 测试使用的模型
+    pub model: String,
+    /// 输出 token 数（优先取上游 usage，否则为内容增量个数）
+    pub output_tokens: u32,
+    /// 生成速度（首个到最后一个内容增量之间）
+    pub tokens_per_second: f64,
+    /// 首 token 耗时（毫秒）
+    pub ttft_ms: u64,
+    /// 请求总耗时（毫秒）
+    pub total_ms: u64,
+    /// 输出 token 数是否来自上游上报的 usage
+    pub usage_reported: bool,
+    /// 测试时间（Unix 秒）
+    pub tested_at: i64,
+}
+
 /// 认证绑定来源
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
@@ -261,10 +348,13 @@ pub struct ProviderMeta {
     /// 供应商单独的代理配置
     #[serde(rename = "proxyConfig", skip_serializing_if = "Option::is_none")]
     pub proxy_config: Option<ProviderProxyConfig>,
-    /// Claude API 格式（仅 Claude 供应商使用）
+    /// 上游 API 格式（Claude 供应商）
     /// - "anthropic": 原生 Anthropic Messages API，直接透传
     /// - "openai_chat": OpenAI Chat Completions 格式，需要转换
     /// - "openai_responses": OpenAI Responses API 格式，需要转换
+    ///
+    /// Codex 供应商也可设置为 "openai_chat" / "openai_responses"，表示上游仅支持该协议，
+    /// 代理会在 Chat Completions 与 Responses API 之间桥接
     #[serde(rename = "apiFormat", skip_serializing_if = "Option::is_none")]
     pub api_format: Option<String>,
     /// 通用认证绑定（provider_config / managed_account）
@@ -281,8 +371,157 @@ pub struct ProviderMeta {
     /// If not set, provider ID is used automatically during format conversion.
     #[serde(rename = "promptCacheKey", skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
+    /// 系统提示词前缀（代理转发时注入到请求的系统提示词最前面）
+    #[serde(
+        rename = "systemPromptPrefix",
+        alias = "system_prompt_prefix",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub system_prompt_prefix: Option<String>,
+    /// 最大输出 token 上限（代理转发时请求值超过上限或未设置则改写为上限）
+    #[serde(
+        rename = "maxTokensCap",
+        alias = "max_tokens_cap",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_tokens_cap: Option<u64>,
+    /// 模型白名单（代理转发时仅允许这些模型，支持 `*` 结尾的前缀匹配）
+    #[serde(
+        rename = "modelAllowlist",
+        alias = "model_allowlist",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub model_allowlist: Option<Vec<String>>,
+    /// 模型黑名单（优先于白名单，命中时代理直接拒绝请求）
+    #[serde(
+        rename = "modelDenylist",
+        alias = "model_denylist",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub model_denylist: Option<Vec<String>>,
+    /// 所属故障转移分组（未设置时归入默认分组）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// 请求体转换脚本（代理转发前对发往上游的 JSON 请求体执行）
+    #[serde(
+        rename = "requestTransform",
+        alias = "request_transform",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub request_transform: Option<String>,
+    /// 响应体转换脚本（对上游返回的非流式 JSON 响应执行）
+    #[serde(
+        rename = "responseTransform",
+        alias = "response_transform",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub response_transform: Option<String>,
+    /// Gemini API Key 池（代理遇到限流/配额错误时轮换到下一个 Key）
+    #[serde(
+        rename = "apiKeyPool",
+        alias = "api_key_pool",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub api_key_pool: Option<Vec<String>>,
+    /// Azure OpenAI 模型名 → 部署名映射（providerType 为 azure_openai 时使用）
+    #[serde(
+        rename = "azureDeployments",
+        alias = "azure_deployments",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub azure_deployments: Option<HashMap<String, String>>,
+    /// Azure OpenAI API 版本（api-version 查询参数）
+    #[serde(
+        rename = "azureApiVersion",
+        alias = "azure_api_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub azure_api_version: Option<String>,
+    /// 影子供应商 ID：请求同时异步镜像到该供应商（同应用），仅记录用量与延迟用于对比
+    #[serde(
+        rename = "shadowProviderId",
+        alias = "shadow_provider_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub shadow_provider_id: Option<String>,
+    /// 最近一次能力探测结果（流式/非流式/工具调用/系统提示词）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<CapabilityReport>,
+    /// 最近一次吞吐测试结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput: Option<ThroughputReport>,
+    /// 切换到该供应商（或更新当前供应商）时跳过 MCP 同步，适用于手动管理 MCP 的场景；
+    /// 只影响该供应商自身的切换，其它供应商照常同步
+    #[serde(
+        rename = "skipMcpSync",
+        alias = "skip_mcp_sync",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub skip_mcp_sync: Option<bool>,
+    /// 是否记录该供应商的请求日志（使用量明细、实时请求流、SSE 事件内容），
+    /// 未设置时沿用代理全局的 `enable_logging`；失败请求仍会记录
+    #[serde(
+        rename = "enableRequestLogging",
+        alias = "enable_request_logging",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub enable_request_logging: Option<bool>,
+    /// 原样透传：代理不改写请求体（模型映射、格式转换、请求/响应脚本等全部跳过），
+    /// 响应字节直接转发，不解析用量、不记录内容，仅记录状态码与延迟
+    #[serde(
+        rename = "rawPassthrough",
+        alias = "raw_passthrough",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub raw_passthrough: Option<bool>,
+    /// 加权路由权重（路由策略为 weighted 时生效，未设置时为 1）
+    #[serde(
+        rename = "routingWeight",
+        alias = "routing_weight",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub routing_weight: Option<u32>,
+    /// 兜底供应商：故障转移候选全部失败后再尝试一次（每个应用最多一个）
+    #[serde(
+        rename = "isSafetyNet",
+        alias = "is_safety_net",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub is_safety_net: Option<bool>,
+    /// 冻结：拒绝编辑与删除，直到解除冻结；切换不受影响
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frozen: Option<bool>,
+    /// 代理转发时丢弃的入站请求头（不区分大小写），用于绕过拒绝未知 `anthropic-beta` 等头的中转站
+    #[serde(
+        rename = "stripHeaders",
+        alias = "strip_headers",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub strip_headers: Option<Vec<String>>,
+    /// 代理转发时额外附加的请求头（在入站头与认证头之后写入）
+    #[serde(
+        rename = "addHeaders",
+        alias = "add_headers",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub add_headers: Option<HashMap<String, String>>,
+    /// 代理转发时要求的最低 TLS 版本（`1.2` / `1.3`），未设置时使用默认值
+    #[serde(
+        rename = "tlsMinVersion",
+        alias = "tls_min_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tls_min_version: Option<String>,
+    /// 固定的上游证书 SHA-256 指纹（十六进制），不匹配时拒绝连接
+    #[serde(
+        rename = "pinnedCertSha256",
+        alias = "pinned_cert_sha256",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pinned_cert_sha256: Option<String>,
     /// 供应商类型标识（用于特殊供应商检测）
     /// - "github_copilot": GitHub Copilot 供应商
+    /// - "azure_openai": Azure OpenAI（Codex，按部署路由）
     #[serde(rename = "providerType", skip_serializing_if = "Option::is_none")]
     pub provider_type: Option<String>,
     /// GitHub Copilot 关联账号 ID（仅 github_copilot 供应商使用）
@@ -504,6 +743,7 @@ impl UniversalProvider {
             icon: self.icon.clone(),
             icon_color: self.icon_color.clone(),
             in_failover_queue: false,
+            enabled: true,
         })
     }
 
@@ -569,6 +809,7 @@ requires_openai_auth = true"#
             icon: self.icon.clone(),
             icon_color: self.icon_color.clone(),
             in_failover_queue: false,
+            enabled: true,
         })
     }
 
@@ -604,6 +845,7 @@ requires_openai_auth = true"#
             icon: self.icon.clone(),
             icon_color: self.icon_color.clone(),
             in_failover_queue: false,
+            enabled: true,
         })
     }
 }
@@ -0,0 +1,125 @@
+//! 共用路径的应用识别
+//!
+//! `/v1/chat/completions` 既可能来自 Codex，也可能来自走 OpenAI 兼容格式的 Claude 中转客户端。
+//! 按以下优先级决定路由到哪个应用的供应商：
+//! 1. `x-cc-app` 提示头（`claude` / `codex`）
+//! 2. 入站认证头风格：`x-api-key` 或 `anthropic-version` 视为 Claude 客户端
+//! 3. 代理配置中的 `target_app`（默认 Codex）
+
+use crate::app_config::AppType;
+use axum::http::HeaderMap;
+use std::str::FromStr;
+
+/// 客户端显式指定目标应用的请求头（不透传到上游）
+pub const APP_HINT_HEADER: &str = "x-cc-app";
+
+/// 共用路径可路由到的应用
+fn is_routable(app_type: &AppType) -> bool {
+    matches!(app_type, AppType::Claude | AppType::Codex)
+}
+
+/// 解析数据库中保存的 `target_app`，无效值（或非 Claude/Codex）回退为 Codex
+pub fn parse_target_app(value: &str) -> AppType {
+    AppType::from_str(value.trim())
+        .ok()
+        .filter(is_routable)
+        .unwrap_or(AppType::Codex)
+}
+
+/// 识别共用路径请求所属的应用
+pub fn detect_app_type(headers: &HeaderMap, default: &AppType) -> AppType {
+    if let Some(hint) = headers
+        .get(APP_HINT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        match AppType::from_str(hint).ok().filter(is_routable) {
+            Some(app_type) => return app_type,
+            None => log::warn!("[Proxy] 忽略无效的 {APP_HINT_HEADER} 请求头: {hint}"),
+        }
+    }
+
+    let anthropic_style = (headers.contains_key("x-api-key")
+        && !headers.contains_key("authorization"))
+        || headers.contains_key("anthropic-version");
+    if anthropic_style {
+        return AppType::Claude;
+    }
+
+    Some(default.clone())
+        .filter(is_routable)
+        .unwrap_or(AppType::Codex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn hint_header_overrides_default() {
+        assert_eq!(
+            detect_app_type(&headers(&[(APP_HINT_HEADER, "claude")]), &AppType::Codex),
+            AppType::Claude
+        );
+        assert_eq!(
+            detect_app_type(&headers(&[(APP_HINT_HEADER, "Codex")]), &AppType::Claude),
+            AppType::Codex
+        );
+    }
+
+    #[test]
+    fn hint_header_wins_over_auth_style() {
+        let map = headers(&[
+            (APP_HINT_HEADER, "codex"),
+            ("x-api-key", "sk-ant"),
+            ("anthropic-version", "2023-06-01"),
+        ]);
+        assert_eq!(detect_app_type(&map, &AppType::Codex), AppType::Codex);
+    }
+
+    #[test]
+    fn anthropic_auth_style_selects_claude() {
+        assert_eq!(
+            detect_app_type(&headers(&[("x-api-key", "sk-ant")]), &AppType::Codex),
+            AppType::Claude
+        );
+        assert_eq!(
+            detect_app_type(
+                &headers(&[
+                    ("authorization", "Bearer sk"),
+                    ("anthropic-version", "2023-06-01")
+                ]),
+                &AppType::Codex
+            ),
+            AppType::Claude
+        );
+    }
+
+    #[test]
+    fn bearer_or_invalid_hint_falls_back_to_default() {
+        let bearer = headers(&[("authorization", "Bearer sk")]);
+        assert_eq!(detect_app_type(&bearer, &AppType::Codex), AppType::Codex);
+        assert_eq!(detect_app_type(&bearer, &AppType::Claude), AppType::Claude);
+
+        let invalid = headers(&[(APP_HINT_HEADER, "gemini")]);
+        assert_eq!(detect_app_type(&invalid, &AppType::Codex), AppType::Codex);
+        assert_eq!(detect_app_type(&bearer, &AppType::Gemini), AppType::Codex);
+    }
+
+    #[test]
+    fn target_app_parsing_falls_back_to_codex() {
+        assert_eq!(parse_target_app("claude"), AppType::Claude);
+        assert_eq!(parse_target_app("gemini"), AppType::Codex);
+        assert_eq!(parse_target_app(""), AppType::Codex);
+    }
+}
@@ -0,0 +1,278 @@
+//! 供应商请求/响应体转换脚本
+//!
+//! 供应商可在 `meta.requestTransform` / `meta.responseTransform` 中配置一段 JavaScript，
+//! 脚本求值结果须为函数：接收 JSON 体，返回修改后的对象；返回 `undefined` 时沿用（可能已被原地修改的）入参。
+//! 例如 `(body) => { body.max_completion_tokens = body.max_tokens; delete body.max_tokens; return body; }`。
+//!
+//! 请求脚本作用于最终发往上游的请求体（格式转换之后），响应脚本作用于上游返回的非流式 JSON 响应
+//! （格式转换之前），用于在不改代码的情况下修正个别中转站的协议差异。
+//!
+//! 脚本复用用量查询脚本的 QuickJS 引擎：运行时不注册网络、文件等宿主能力，每次执行使用独立运行时，
+//! 并限制执行时间、内存和栈深度。脚本出错或超时只记录警告，原样转发未修改的请求/响应体。
+
+use super::ProxyError;
+use crate::provider::Provider;
+use rquickjs::{Context, Ctx, Function, Runtime};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 单次脚本执行的超时时间
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 脚本运行时内存上限（请求体可能包含 base64 图片，需留出余量）
+const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// 脚本运行时栈大小上限
+const STACK_LIMIT: usize = 1024 * 1024;
+
+fn non_empty(script: Option<&String>) -> Option<&str> {
+    script
+        .map(|s| s.as_str())
+        .filter(|script| !script.trim().is_empty())
+}
+
+/// 读取供应商配置的请求体转换脚本（空白视为未设置）
+pub fn request_script(provider: &Provider) -> Option<&str> {
+    non_empty(provider.meta.as_ref()?.request_transform.as_ref())
+}
+
+/// 读取供应商配置的响应体转换脚本（空白视为未设置）
+pub fn response_script(provider: &Provider) -> Option<&str> {
+    non_empty(provider.meta.as_ref()?.response_transform.as_ref())
+}
+
+fn describe_error(ctx: &Ctx<'_>, error: rquickjs::Error) -> String {
+    if matches!(error, rquickjs::Error::Exception) {
+        let exception = ctx.catch();
+        if let Some(message) = exception.as_exception().and_then(|e| e.message()) {
+            return message;
+        }
+        return format!("{exception:?}");
+    }
+    error.to_string()
+}
+
+/// 在沙箱中执行转换脚本
+///
+/// `body_json` 为序列化后的 JSON 体，返回脚本产出的新 JSON 对象。
+pub fn run_script(script: &str, body_json: &str, timeout: Duration) -> Result<Value, ProxyError> {
+    let runtime = Runtime::new()
+        .map_err(|e| ProxyError::TransformError(format!("创建 JS 运行时失败: {e}")))?;
+    runtime.set_memory_limit(MEMORY_LIMIT);
+    runtime.set_max_stack_size(STACK_LIMIT);
+
+    let deadline = Instant::now() + timeout;
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let flag = timed_out.clone();
+    runtime.set_interrupt_handler(Some(Box::new(move || {
+        let expired = Instant::now() >= deadline;
+        if expired {
+            flag.store(true, Ordering::Relaxed);
+        }
+        expired
+    })));
+
+    let context = Context::full(&runtime)
+        .map_err(|e| ProxyError::TransformError(format!("创建 JS 上下文失败: {e}")))?;
+
+    let result = context.with(|ctx| {
+        let transform: Function = ctx
+            .eval(script)
+            .map_err(|e| format!("脚本求值结果必须是函数: {}", describe_error(&ctx, e)))?;
+        let input: rquickjs::Value = ctx
+            .json_parse(body_json)
+            .map_err(|e| format!("解析 JSON 体失败: {}", describe_error(&ctx, e)))?;
+
+        let output: rquickjs::Value = transform
+            .call((input.clone(),))
+            .map_err(|e| format!("脚本执行失败: {}", describe_error(&ctx, e)))?;
+        let output = if output.is_undefined() { input } else { output };
+
+        let json: String = ctx
+            .json_stringify(output)
+            .map_err(|e| format!("序列化脚本结果失败: {}", describe_error(&ctx, e)))?
+            .ok_or_else(|| "脚本返回值无法序列化为 JSON".to_string())?
+            .get()
+            .map_err(|e| format!("序列化脚本结果失败: {e}"))?;
+        Ok::<_, String>(json)
+    });
+
+    let json = result.map_err(|message| {
+        if timed_out.load(Ordering::Relaxed) {
+            ProxyError::TransformError(format!("转换脚本执行超时（{}ms）", timeout.as_millis()))
+        } else {
+            ProxyError::TransformError(message)
+        }
+    })?;
+
+    match serde_json::from_str::<Value>(&json) {
+        Ok(value) if value.is_object() => Ok(value),
+        Ok(_) => Err(ProxyError::TransformError(
+            "转换脚本必须返回 JSON 对象".to_string(),
+        )),
+        Err(e) => Err(ProxyError::TransformError(format!("解析脚本结果失败: {e}"))),
+    }
+}
+
+/// 在阻塞线程中执行脚本；失败或超时时返回原始 JSON 体
+pub async fn apply(script: &str, body: Value, tag: &str, kind: &str) -> Value {
+    let body_json = match serde_json::to_string(&body) {
+        Ok(json) => json,
+        Err(e) => {
+            log::warn!("[{tag}] {kind} 转换脚本跳过：序列化失败: {e}");
+            return body;
+        }
+    };
+    let script = script.to_string();
+
+    match tokio::task::spawn_blocking(move || run_script(&script, &body_json, SCRIPT_TIMEOUT)).await
+    {
+        Ok(Ok(transformed)) => {
+            log::debug!("[{tag}] 已执行 {kind} 转换脚本");
+            transformed
+        }
+        Ok(Err(e)) => {
+            log::warn!("[{tag}] {kind} 转换脚本失败，使用原始内容: {e}");
+            body
+        }
+        Err(e) => {
+            log::warn!("[{tag}] {kind} 转换脚本任务异常，使用原始内容: {e}");
+            body
+        }
+    }
+}
+
+/// 对上游非流式响应执行响应体转换脚本（非 JSON 响应原样返回）
+pub async fn apply_to_response(
+    script: &str,
+    response: reqwest::Response,
+    tag: &str,
+) -> Result<reqwest::Response, ProxyError> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body_bytes = response
+        .bytes()
+        .await
+        .map_err(|e| ProxyError::ForwardFailed(format!("Failed to read response body: {e}")))?;
+
+    let body_bytes = match serde_json::from_slice::<Value>(&body_bytes) {
+        Ok(json) => {
+            let transformed = apply(script, json, tag, "response").await;
+            serde_json::to_vec(&transformed).map_err(|e| {
+                ProxyError::TransformError(format!("Failed to serialize response: {e}"))
+            })?
+        }
+        Err(_) => body_bytes.to_vec(),
+    };
+
+    let mut builder = axum::http::Response::builder().status(status);
+    for (key, value) in headers.iter() {
+        if key != axum::http::header::CONTENT_LENGTH && key != axum::http::header::TRANSFER_ENCODING
+        {
+            builder = builder.header(key, value);
+        }
+    }
+    let rebuilt = builder
+        .body(reqwest::Body::from(body_bytes))
+        .map_err(|e| ProxyError::Internal(format!("Failed to build response: {e}")))?;
+    Ok(reqwest::Response::from(rebuilt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    #[test]
+    fn transform_renames_field() {
+        let script = r#"(body) => {
+            body.max_completion_tokens = body.max_tokens;
+            delete body.max_tokens;
+            return body;
+        }"#;
+        let body = json!({ "model": "gpt-5", "max_tokens": 64 });
+
+        let result = run_script(script, &body.to_string(), SCRIPT_TIMEOUT).unwrap();
+        assert_eq!(
+            result,
+            json!({ "model": "gpt-5", "max_completion_tokens": 64 })
+        );
+    }
+
+    #[test]
+    fn in_place_mutation_without_return_is_kept() {
+        let script = "(body) => { body.stream = false; }";
+        let result = run_script(script, r#"{"stream":true}"#, SCRIPT_TIMEOUT).unwrap();
+        assert_eq!(result, json!({ "stream": false }));
+    }
+
+    #[test]
+    fn infinite_loop_times_out() {
+        let started = Instant::now();
+        let err = run_script(
+            "(body) => { while (true) {} }",
+            "{}",
+            Duration::from_millis(50),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, ProxyError::TransformError(_)));
+        assert!(err.to_string().contains("超时"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn script_must_evaluate_to_function_returning_object() {
+        assert!(run_script("42", "{}", SCRIPT_TIMEOUT).is_err());
+        assert!(run_script("(body) => 1", "{}", SCRIPT_TIMEOUT).is_err());
+        assert!(run_script(
+            "(body) => { throw new Error('boom') }",
+            "{}",
+            SCRIPT_TIMEOUT
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("boom"));
+    }
+
+    #[test]
+    fn sandbox_has_no_host_io() {
+        let script =
+            "(body) => ({ fetch: typeof fetch, require: typeof require, std: typeof std })";
+        let result = run_script(script, "{}", SCRIPT_TIMEOUT).unwrap();
+        assert_eq!(
+            result,
+            json!({ "fetch": "undefined", "require": "undefined", "std": "undefined" })
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_transform_keeps_original_body() {
+        let body = json!({ "model": "m", "messages": [] });
+        let result = apply(
+            "(body) => { throw new Error('bad') }",
+            body.clone(),
+            "Test",
+            "request",
+        )
+        .await;
+        assert_eq!(result, body);
+    }
+
+    #[test]
+    fn blank_scripts_are_ignored() {
+        let mut provider = Provider::with_id("p".to_string(), "P".to_string(), json!({}), None);
+        assert!(request_script(&provider).is_none());
+
+        provider.meta = Some(ProviderMeta {
+            request_transform: Some("  ".to_string()),
+            response_transform: Some("(body) => body".to_string()),
+            ..Default::default()
+        });
+        assert!(request_script(&provider).is_none());
+        assert_eq!(response_script(&provider), Some("(body) => body"));
+    }
+}
@@ -0,0 +1,213 @@
+//! 代理并发上限
+//!
+//! 突发流量下每个请求都会派生一个转发任务并占用上游连接。`ProxyConfig.max_concurrent_requests`
+//! 大于 0 时，所有 API 请求共享一个信号量：许可用尽后新请求最多排队 [`QUEUE_TIMEOUT`]，
+//! 仍未拿到许可则直接返回 503（带 `Retry-After`），既保护代理本身也保护上游。
+//!
+//! 许可随响应体一起释放，流式响应在整个流结束前都计入并发。
+
+use super::ProxyError;
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 许可用尽时新请求的最长排队时间
+pub const QUEUE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 拒绝请求时建议客户端的重试间隔（秒）
+pub const RETRY_AFTER_SECS: u64 = 1;
+
+/// 全局并发限制器（上限可在运行时调整）
+pub struct ConcurrencyLimiter {
+    /// (上限, 信号量)；None 表示不限制
+    semaphore: RwLock<Option<(u32, Arc<Semaphore>)>>,
+    queue_timeout: Duration,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(limit: u32) -> Self {
+        Self::with_queue_timeout(limit, QUEUE_TIMEOUT)
+    }
+
+    pub fn with_queue_timeout(limit: u32, queue_timeout: Duration) -> Self {
+        let limiter = Self {
+            semaphore: RwLock::new(None),
+            queue_timeout,
+        };
+        limiter.set_limit(limit);
+        limiter
+    }
+
+    /// 调整并发上限（0 表示不限制）
+    ///
+    /// 上限变化时换用新的信号量，已发出的许可仍归还给旧信号量，
+    /// 因此调整瞬间的实际并发可能短暂超过新上限。
+    pub fn set_limit(&self, limit: u32) {
+        let mut semaphore = self.semaphore.write().unwrap_or_else(|e| e.into_inner());
+        if semaphore.as_ref().map_or(0, |(current, _)| *current) == limit {
+            return;
+        }
+        *semaphore = (limit > 0).then(|| (limit, Arc::new(Semaphore::new(limit as usize))));
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.semaphore
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map_or(0, |(limit, _)| *limit)
+    }
+
+    /// 获取一个许可；不限制时返回 None，排队超时返回 [`ProxyError::Overloaded`]
+    pub async fn acquire(&self) -> Result<Option<OwnedSemaphorePermit>, ProxyError> {
+        let Some((limit, semaphore)) = self
+            .semaphore
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        else {
+            return Ok(None);
+        };
+
+        match tokio::time::timeout(self.queue_timeout, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(Some(permit)),
+            // 信号量不会被关闭，保险起见按不限制处理
+            Ok(Err(_)) => Ok(None),
+            Err(_) => Err(ProxyError::Overloaded(limit)),
+        }
+    }
+}
+
+/// 并发限制中间件（挂在所有 API 路由上）
+pub async fn limit_concurrency(
+    State(limiter): State<Arc<ConcurrencyLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let permit = match limiter.acquire().await {
+        Ok(permit) => permit,
+        Err(e) => {
+            log::warn!("[Proxy] {} {} 被拒绝: {e}", request.method(), request.uri());
+            return e.into_response();
+        }
+    };
+
+    let response = next.run(request).await;
+    match permit {
+        Some(permit) => hold_permit_until_body_ends(response, permit),
+        None => response,
+    }
+}
+
+/// 让许可跟随响应体释放；已完整缓冲的响应体直接返回，许可随函数结束释放
+fn hold_permit_until_body_ends(response: Response, permit: OwnedSemaphorePermit) -> Response {
+    if response.body().size_hint().exact().is_some() {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let stream = body.into_data_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Router};
+    use tokio::sync::{mpsc, Notify};
+
+    #[tokio::test]
+    async fn test_requests_beyond_limit_are_shed_with_503() {
+        let limiter = Arc::new(ConcurrencyLimiter::with_queue_timeout(
+            1,
+            Duration::from_millis(100),
+        ));
+        let (started_tx, mut started_rx) = mpsc::unbounded_channel::<()>();
+        let release = Arc::new(Notify::new());
+
+        let handler_release = release.clone();
+        let app = Router::new()
+            .route(
+                "/v1/messages",
+                post(move || {
+                    let started_tx = started_tx.clone();
+                    let release = handler_release.clone();
+                    async move {
+                        let _ = started_tx.send(());
+                        release.notified().await;
+                        "ok"
+                    }
+                }),
+            )
+            .route_layer(axum::middleware::from_fn_with_state(
+                limiter.clone(),
+                limit_concurrency,
+            ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/messages", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        let client = reqwest::Client::new();
+        let first = tokio::spawn({
+            let client = client.clone();
+            let url = url.clone();
+            async move { client.post(&url).send().await.unwrap().status() }
+        });
+        started_rx
+            .recv()
+            .await
+            .expect("first request reached handler");
+
+        let shed = client.post(&url).send().await.unwrap();
+        assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            shed.headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok()),
+            Some(RETRY_AFTER_SECS.to_string().as_str())
+        );
+        let body: serde_json::Value = shed.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "overloaded");
+
+        release.notify_one();
+        assert_eq!(first.await.unwrap(), StatusCode::OK);
+
+        // 许可归还后新请求可以正常通过
+        let next = tokio::spawn({
+            let client = client.clone();
+            async move { client.post(&url).send().await.unwrap().status() }
+        });
+        started_rx
+            .recv()
+            .await
+            .expect("next request reached handler");
+        release.notify_one();
+        assert_eq!(next.await.unwrap(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit_disables_limiting() {
+        let limiter = ConcurrencyLimiter::with_queue_timeout(1, Duration::from_millis(10));
+        let held = limiter.acquire().await.unwrap();
+        assert!(held.is_some());
+        assert!(matches!(
+            limiter.acquire().await,
+            Err(ProxyError::Overloaded(1))
+        ));
+
+        limiter.set_limit(0);
+        assert_eq!(limiter.limit(), 0);
+        assert!(limiter.acquire().await.unwrap().is_none());
+    }
+}
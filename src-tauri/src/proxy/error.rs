@@ -1,11 +1,13 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use thiserror::Error;
 
+use super::types::ProviderErrorKind;
+
 #[derive(Debug, Error)]
 pub enum ProxyError {
     #[error("服务器已在运行")]
@@ -55,16 +57,22 @@ pub enum ProxyError {
     #[error("格式转换错误: {0}")]
     TransformError(String),
 
-    #[allow(dead_code)]
     #[error("无效的请求: {0}")]
     InvalidRequest(String),
 
     #[error("超时: {0}")]
     Timeout(String),
 
+    /// 建立连接超时：上游不可达或已宕机
+    #[error("连接上游超时（上游可能不可达或已宕机）: {0}")]
+    ConnectTimeout(String),
+
+    /// 已发出请求但未收到首字节：上游可达但响应缓慢
+    #[error("等待上游响应超时（未收到首字节，上游可能过载或响应缓慢）: {0}")]
+    ReadTimeout(String),
+
     /// 流式响应空闲超时
-    #[allow(dead_code)]
-    #[error("流式响应空闲超时: {0}秒无数据")]
+    #[error("流式响应空闲超时: {0}秒无数据（上游流已中断或卡住）")]
     StreamIdleTimeout(u64),
 
     /// 认证错误
@@ -72,11 +80,76 @@ pub enum ProxyError {
     #[error("认证失败: {0}")]
     AuthError(String),
 
+    /// 并发请求数已达上限（`ProxyConfig.max_concurrent_requests`）
+    #[error("代理并发请求数已达上限（{0}），请稍后重试")]
+    Overloaded(u32),
+
     #[allow(dead_code)]
     #[error("内部错误: {0}")]
     Internal(String),
 }
 
+impl ProxyError {
+    /// 根据发送请求失败时的 reqwest 错误特征构造对应的错误
+    ///
+    /// - 连接阶段超时 → ConnectTimeout
+    /// - 连接成功但等待响应超时 → ReadTimeout
+    /// - 连接失败（拒绝、DNS 等）→ ForwardFailed
+    pub fn from_send_failure(is_connect: bool, is_timeout: bool, message: String) -> Self {
+        match (is_connect, is_timeout) {
+            (true, true) => ProxyError::ConnectTimeout(message),
+            (false, true) => ProxyError::ReadTimeout(message),
+            (true, false) => ProxyError::ForwardFailed(format!("连接失败: {message}")),
+            (false, false) => ProxyError::ForwardFailed(message),
+        }
+    }
+
+    /// 用于健康状态展示的错误类别
+    pub fn error_kind(&self) -> ProviderErrorKind {
+        match self {
+            ProxyError::UpstreamError { status, body } => {
+                ProviderErrorKind::from_status(*status, body.as_deref())
+            }
+            ProxyError::AuthError(_) => ProviderErrorKind::Auth,
+            ProxyError::ForwardFailed(_)
+            | ProxyError::Timeout(_)
+            | ProxyError::ConnectTimeout(_)
+            | ProxyError::ReadTimeout(_)
+            | ProxyError::StreamIdleTimeout(_) => ProviderErrorKind::Network,
+            _ => ProviderErrorKind::Unknown,
+        }
+    }
+
+    /// 机器可读的错误码，随响应体 `error.code` 返回
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProxyError::AlreadyRunning => "already_running",
+            ProxyError::NotRunning => "not_running",
+            ProxyError::BindFailed(_) => "bind_failed",
+            ProxyError::StopTimeout => "stop_timeout",
+            ProxyError::StopFailed(_) => "stop_failed",
+            ProxyError::ForwardFailed(_) => "forward_failed",
+            ProxyError::NoAvailableProvider => "no_available_provider",
+            ProxyError::AllProvidersCircuitOpen => "all_providers_circuit_open",
+            ProxyError::NoProvidersConfigured => "no_providers_configured",
+            ProxyError::ProviderUnhealthy(_) => "provider_unhealthy",
+            ProxyError::UpstreamError { .. } => "upstream_error",
+            ProxyError::MaxRetriesExceeded => "max_retries_exceeded",
+            ProxyError::DatabaseError(_) => "database_error",
+            ProxyError::ConfigError(_) => "config_error",
+            ProxyError::TransformError(_) => "transform_error",
+            ProxyError::InvalidRequest(_) => "invalid_request",
+            ProxyError::Timeout(_) => "timeout",
+            ProxyError::ConnectTimeout(_) => "connect_timeout",
+            ProxyError::ReadTimeout(_) => "read_timeout",
+            ProxyError::StreamIdleTimeout(_) => "stream_idle_timeout",
+            ProxyError::AuthError(_) => "auth_error",
+            ProxyError::Overloaded(_) => "overloaded",
+            ProxyError::Internal(_) => "internal_error",
+        }
+    }
+}
+
 impl IntoResponse for ProxyError {
     fn into_response(self) -> Response {
         let (status, body) = match &self {
@@ -98,6 +171,7 @@ impl IntoResponse for ProxyError {
                             "error": {
                                 "message": body_str,
                                 "type": "upstream_error",
+                                "code": self.code(),
                             }
                         })
                     }
@@ -106,6 +180,7 @@ impl IntoResponse for ProxyError {
                         "error": {
                             "message": format!("Upstream error (status {})", upstream_status),
                             "type": "upstream_error",
+                            "code": self.code(),
                         }
                     })
                 };
@@ -149,11 +224,16 @@ impl IntoResponse for ProxyError {
                         (StatusCode::UNPROCESSABLE_ENTITY, self.to_string())
                     }
                     ProxyError::InvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-                    ProxyError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, self.to_string()),
+                    ProxyError::Timeout(_)
+                    | ProxyError::ConnectTimeout(_)
+                    | ProxyError::ReadTimeout(_) => (StatusCode::GATEWAY_TIMEOUT, self.to_string()),
                     ProxyError::StreamIdleTimeout(_) => {
                         (StatusCode::GATEWAY_TIMEOUT, self.to_string())
                     }
                     ProxyError::AuthError(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+                    ProxyError::Overloaded(_) => {
+                        (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
+                    }
                     ProxyError::Internal(_) => {
                         (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
                     }
@@ -164,6 +244,7 @@ impl IntoResponse for ProxyError {
                     "error": {
                         "message": message,
                         "type": "proxy_error",
+                        "code": self.code(),
                     }
                 });
 
@@ -171,7 +252,14 @@ impl IntoResponse for ProxyError {
             }
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if matches!(self, ProxyError::Overloaded(_)) {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(super::concurrency::RETRY_AFTER_SECS),
+            );
+        }
+        response
     }
 }
 
@@ -205,3 +293,109 @@ pub fn categorize_error(error: &reqwest::Error) -> ErrorCategory {
         ErrorCategory::Retryable
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn response_json(error: ProxyError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read response body");
+        let body = serde_json::from_slice(&bytes).expect("response body should be JSON");
+        (status, body)
+    }
+
+    #[test]
+    fn send_failure_connect_timeout_maps_to_connect_timeout() {
+        let error = ProxyError::from_send_failure(true, true, "connect timed out".to_string());
+        assert!(matches!(error, ProxyError::ConnectTimeout(_)));
+        assert_eq!(error.code(), "connect_timeout");
+        assert!(error.to_string().contains("不可达"));
+    }
+
+    #[test]
+    fn send_failure_timeout_without_connect_maps_to_read_timeout() {
+        let error = ProxyError::from_send_failure(false, true, "operation timed out".to_string());
+        assert!(matches!(error, ProxyError::ReadTimeout(_)));
+        assert_eq!(error.code(), "read_timeout");
+        assert!(error.to_string().contains("未收到首字节"));
+    }
+
+    #[test]
+    fn send_failure_without_timeout_maps_to_forward_failed() {
+        let error = ProxyError::from_send_failure(true, false, "connection refused".to_string());
+        assert!(matches!(error, ProxyError::ForwardFailed(_)));
+        assert_eq!(error.code(), "forward_failed");
+    }
+
+    #[test]
+    fn error_kind_distinguishes_auth_from_outages() {
+        let upstream = |status: u16, body: Option<&str>| ProxyError::UpstreamError {
+            status,
+            body: body.map(str::to_string),
+        };
+
+        assert_eq!(upstream(401, None).error_kind(), ProviderErrorKind::Auth);
+        assert_eq!(
+            upstream(429, None).error_kind(),
+            ProviderErrorKind::RateLimit
+        );
+        assert_eq!(
+            upstream(503, Some("service unavailable")).error_kind(),
+            ProviderErrorKind::ServerError
+        );
+        assert_eq!(
+            ProxyError::from_send_failure(true, false, "connection refused".to_string())
+                .error_kind(),
+            ProviderErrorKind::Network
+        );
+        assert_eq!(
+            ProxyError::from_send_failure(true, true, "connect timed out".to_string()).error_kind(),
+            ProviderErrorKind::Network
+        );
+        assert_eq!(upstream(400, None).error_kind(), ProviderErrorKind::Unknown);
+    }
+
+    #[test]
+    fn error_kind_inspects_body_when_status_is_ambiguous() {
+        let body = r#"{"error":{"type":"authentication_error","message":"invalid x-api-key"}}"#;
+        assert_eq!(
+            ProviderErrorKind::from_status(400, Some(body)),
+            ProviderErrorKind::Auth
+        );
+        assert_eq!(
+            ProviderErrorKind::from_status(500, Some("Rate limit exceeded, retry later")),
+            ProviderErrorKind::RateLimit
+        );
+    }
+
+    #[test]
+    fn stream_idle_timeout_has_distinct_code() {
+        let error = ProxyError::StreamIdleTimeout(120);
+        assert_eq!(error.code(), "stream_idle_timeout");
+        assert!(error.to_string().contains("120"));
+    }
+
+    #[tokio::test]
+    async fn timeout_envelope_carries_error_code() {
+        for (error, code) in [
+            (
+                ProxyError::ConnectTimeout("relay.example".to_string()),
+                "connect_timeout",
+            ),
+            (
+                ProxyError::ReadTimeout("relay.example".to_string()),
+                "read_timeout",
+            ),
+            (ProxyError::StreamIdleTimeout(60), "stream_idle_timeout"),
+        ] {
+            let (status, body) = response_json(error).await;
+            assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+            assert_eq!(body["error"]["code"], code);
+            assert_eq!(body["error"]["type"], "proxy_error");
+        }
+    }
+}
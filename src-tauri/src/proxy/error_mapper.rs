@@ -19,7 +19,10 @@ pub fn map_proxy_error_to_status(error: &ProxyError) -> u16 {
         ProxyError::UpstreamError { status, .. } => *status,
 
         // 超时错误：504 Gateway Timeout
-        ProxyError::Timeout(_) => 504,
+        ProxyError::Timeout(_)
+        | ProxyError::ConnectTimeout(_)
+        | ProxyError::ReadTimeout(_)
+        | ProxyError::StreamIdleTimeout(_) => 504,
 
         // 转发失败/连接失败：502 Bad Gateway
         ProxyError::ForwardFailed(_) => 502,
@@ -39,6 +42,9 @@ pub fn map_proxy_error_to_status(error: &ProxyError) -> u16 {
         // Provider 不健康：503 Service Unavailable
         ProxyError::ProviderUnhealthy(_) => 503,
 
+        // 并发已达上限：503 Service Unavailable
+        ProxyError::Overloaded(_) => 503,
+
         // 数据库错误：500 Internal Server Error
         ProxyError::DatabaseError(_) => 500,
 
@@ -61,6 +67,8 @@ pub fn get_error_message(error: &ProxyError) -> String {
             }
         }
         ProxyError::Timeout(msg) => format!("请求超时: {msg}"),
+        ProxyError::ConnectTimeout(msg) => format!("连接超时（上游可能不可达）: {msg}"),
+        ProxyError::ReadTimeout(msg) => format!("首字节超时（上游响应缓慢）: {msg}"),
         ProxyError::ForwardFailed(msg) => format!("转发失败: {msg}"),
         ProxyError::NoAvailableProvider => "无可用 Provider".to_string(),
         ProxyError::AllProvidersCircuitOpen => "所有供应商已熔断，无可用渠道".to_string(),
@@ -92,6 +100,17 @@ mod tests {
         assert_eq!(map_proxy_error_to_status(&error), 504);
     }
 
+    #[test]
+    fn test_map_timeout_kinds() {
+        for error in [
+            ProxyError::ConnectTimeout("connect".to_string()),
+            ProxyError::ReadTimeout("read".to_string()),
+            ProxyError::StreamIdleTimeout(30),
+        ] {
+            assert_eq!(map_proxy_error_to_status(&error), 504);
+        }
+    }
+
     #[test]
     fn test_map_connection_error() {
         let error = ProxyError::ForwardFailed("Connection refused".to_string());
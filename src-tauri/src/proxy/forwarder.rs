@@ -4,11 +4,17 @@
 
 use super::{
     body_filter::filter_private_params_with_whitelist,
+    body_transform,
     error::*,
     failover_switch::FailoverSwitchManager,
+    key_pool,
     log_codes::fwd as log_fwd,
+    max_tokens_cap, model_policy,
     provider_router::ProviderRouter,
-    providers::{get_adapter, AuthInfo, AuthStrategy, ProviderAdapter, ProviderType},
+    providers::{azure, get_adapter, AuthInfo, AuthStrategy, ProviderAdapter, ProviderType},
+    response_processor::is_sse_response,
+    shadow::{self, ShadowSink},
+    system_prompt::{self, PromptShape},
     thinking_budget_rectifier::{rectify_thinking_budget, should_rectify_thinking_budget},
     thinking_rectifier::{
         normalize_thinking_type, rectify_anthropic_request, should_rectify_thinking_signature,
@@ -76,11 +82,21 @@ const HEADER_BLACKLIST: &[&str] = &[
     // 客户端 IP 单独处理（默认透传）
     "x-forwarded-for",
     "x-real-ip",
+    // 本地路由头（分组、共用路径的目标应用、开销测量标记）
+    "x-cc-group",
+    "x-cc-app",
+    "x-cc-benchmark",
 ];
 
 pub struct ForwardResult {
     pub response: Response,
     pub provider: Provider,
+    /// 使用的 Key 池序号（供应商未配置 Key 池时为 None）
+    pub api_key_index: Option<usize>,
+    /// 是否由兜底供应商完成
+    pub is_safety_net: bool,
+    /// 供应商输出 token 上限生效后的最大输出 token 数（未设置上限时为 None）
+    pub effective_max_tokens: Option<u64>,
 }
 
 pub struct ForwardError {
@@ -88,6 +104,7 @@ pub struct ForwardError {
     pub provider: Option<Provider>,
 }
 
+#[derive(Clone)]
 pub struct RequestForwarder {
     /// 共享的 ProviderRouter（持有熔断器状态）
     router: Arc<ProviderRouter>,
@@ -105,6 +122,8 @@ pub struct RequestForwarder {
     optimizer_config: OptimizerConfig,
     /// 非流式请求超时（秒）
     non_streaming_timeout: std::time::Duration,
+    /// 影子请求日志落点（未设置时不镜像影子请求）
+    shadow: Option<ShadowSink>,
 }
 
 impl RequestForwarder {
@@ -132,9 +151,75 @@ impl RequestForwarder {
             rectifier_config,
             optimizer_config,
             non_streaming_timeout: std::time::Duration::from_secs(non_streaming_timeout),
+            shadow: None,
         }
     }
 
+    /// 启用影子供应商镜像（主供应商配置了 `shadowProviderId` 时生效）
+    pub fn with_shadow(mut self, sink: ShadowSink) -> Self {
+        self.shadow = Some(sink);
+        self
+    }
+
+    /// 将请求异步镜像到主供应商配置的影子供应商
+    ///
+    /// 影子请求在独立任务中执行，不更新熔断器和代理状态，结果只写入请求日志。
+    fn spawn_shadow(
+        &self,
+        app_type: &AppType,
+        endpoint: &str,
+        body: &Value,
+        headers: &axum::http::HeaderMap,
+        primary: &Provider,
+    ) {
+        let Some(sink) = self.shadow.clone() else {
+            return;
+        };
+        let Some(shadow_id) = shadow::shadow_provider_id(primary) else {
+            return;
+        };
+
+        let shadow_id = shadow_id.to_string();
+        let forwarder = self.clone();
+        let app_type = app_type.clone();
+        let endpoint = endpoint.to_string();
+        let body = body.clone();
+        let headers = headers.clone();
+
+        tokio::spawn(async move {
+            let provider = match sink.db.get_provider_by_id(&shadow_id, app_type.as_str()) {
+                Ok(Some(provider)) => provider,
+                Ok(None) => {
+                    log::warn!(
+                        "[{}] 影子供应商不存在，跳过镜像: {shadow_id}",
+                        app_type.as_str()
+                    );
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("[{}] 读取影子供应商失败: {e}", app_type.as_str());
+                    return;
+                }
+            };
+
+            let adapter = get_adapter(&app_type);
+            let started_at = std::time::Instant::now();
+            let result = forwarder
+                .forward(
+                    &app_type,
+                    &provider,
+                    &endpoint,
+                    &body,
+                    &headers,
+                    adapter.as_ref(),
+                )
+                .await
+                .map(|(response, _)| response);
+            sink.record(&app_type, &provider, &body, started_at, result)
+                .await;
+        });
+    }
+
     /// 转发请求（带故障转移）
     ///
     /// # Arguments
@@ -156,15 +241,27 @@ impl RequestForwarder {
         let app_type_str = app_type.as_str();
 
         if providers.is_empty() {
+            // 候选全部熔断时只剩兜底供应商可用
+            if let Some(result) = self
+                .try_safety_net(app_type, endpoint, &body, &headers, &providers)
+                .await
+            {
+                return Ok(result);
+            }
             return Err(ForwardError {
                 error: ProxyError::NoAvailableProvider,
                 provider: None,
             });
         }
 
+        // 影子请求与主请求并行发出，主流程不等待其结果
+        self.spawn_shadow(app_type, endpoint, &body, &headers, &providers[0]);
+
         let mut last_error = None;
         let mut last_provider = None;
         let mut attempted_providers = 0usize;
+        // 被模型白名单/黑名单拦截的错误（所有供应商都被拦截时返回给客户端）
+        let mut policy_error = None;
 
         // 整流器重试标记：确保整流最多触发一次
         let mut rectifier_retried = false;
@@ -175,6 +272,13 @@ impl RequestForwarder {
 
         // 依次尝试每个供应商
         for provider in providers.iter() {
+            // 模型不被该供应商允许时跳过（不占用熔断器名额，也不计入失败）
+            if let Err(e) = model_policy::check_request(&body, endpoint, provider) {
+                log::warn!("[{app_type_str}] 跳过供应商 {}: {e}", provider.name);
+                policy_error = Some(e);
+                continue;
+            }
+
             // 发起请求前先获取熔断器放行许可（HalfOpen 会占用探测名额）
             // 单 Provider 场景下跳过此检查，避免熔断器阻塞所有请求
             let (allowed, used_half_open_permit) = if bypass_circuit_breaker {
@@ -193,19 +297,21 @@ impl RequestForwarder {
 
             // PRE-SEND 优化器：每个 provider 独立决定是否优化
             // clone body 以避免 Bedrock 优化字段泄漏到非 Bedrock provider（failover 场景）
-            let mut provider_body =
-                if self.optimizer_config.enabled && is_bedrock_provider(provider) {
-                    let mut b = body.clone();
-                    if self.optimizer_config.thinking_optimizer {
-                        super::thinking_optimizer::optimize(&mut b, &self.optimizer_config);
-                    }
-                    if self.optimizer_config.cache_injection {
-                        super::cache_injector::inject(&mut b, &self.optimizer_config);
-                    }
-                    b
-                } else {
-                    body.clone()
-                };
+            let mut provider_body = if self.optimizer_config.enabled
+                && !provider.is_raw_passthrough()
+                && is_bedrock_provider(provider)
+            {
+                let mut b = body.clone();
+                if self.optimizer_config.thinking_optimizer {
+                    super::thinking_optimizer::optimize(&mut b, &self.optimizer_config);
+                }
+                if self.optimizer_config.cache_injection {
+                    super::cache_injector::inject(&mut b, &self.optimizer_config);
+                }
+                b
+            } else {
+                body.clone()
+            };
 
             attempted_providers += 1;
 
@@ -221,6 +327,7 @@ impl RequestForwarder {
             // 转发请求（每个 Provider 只尝试一次，重试由客户端控制）
             match self
                 .forward(
+                    app_type,
                     provider,
                     endpoint,
                     &provider_body,
@@ -229,7 +336,7 @@ impl RequestForwarder {
                 )
                 .await
             {
-                Ok(response) => {
+                Ok((response, api_key_index)) => {
                     // 成功：记录成功并更新熔断器
                     let _ = self
                         .router
@@ -283,15 +390,25 @@ impl RequestForwarder {
                     return Ok(ForwardResult {
                         response,
                         provider: provider.clone(),
+                        api_key_index,
+                        is_safety_net: false,
+                        effective_max_tokens: max_tokens_cap::effective_value(
+                            provider,
+                            app_type,
+                            endpoint,
+                            &provider_body,
+                        ),
                     });
                 }
                 Err(e) => {
                     // 检测是否需要触发整流器（仅 Claude/ClaudeAuth 供应商）
                     let provider_type = ProviderType::from_app_type_and_config(app_type, provider);
-                    let is_anthropic_provider = matches!(
-                        provider_type,
-                        ProviderType::Claude | ProviderType::ClaudeAuth
-                    );
+                    // 原样透传的供应商不改写请求体，也就不参与整流重试
+                    let is_anthropic_provider = !provider.is_raw_passthrough()
+                        && matches!(
+                            provider_type,
+                            ProviderType::Claude | ProviderType::ClaudeAuth
+                        );
                     let mut signature_rectifier_non_retryable_client_error = false;
 
                     if is_anthropic_provider {
@@ -349,6 +466,7 @@ impl RequestForwarder {
                                 // 使用同一供应商重试（不计入熔断器）
                                 match self
                                     .forward(
+                                        app_type,
                                         provider,
                                         endpoint,
                                         &provider_body,
@@ -357,7 +475,7 @@ impl RequestForwarder {
                                     )
                                     .await
                                 {
-                                    Ok(response) => {
+                                    Ok((response, api_key_index)) => {
                                         log::info!("[{app_type_str}] [RECT-002] 整流重试成功");
                                         // 记录成功
                                         let _ = self
@@ -416,6 +534,14 @@ impl RequestForwarder {
                                         return Ok(ForwardResult {
                                             response,
                                             provider: provider.clone(),
+                                            api_key_index,
+                                            is_safety_net: false,
+                                            effective_max_tokens: max_tokens_cap::effective_value(
+                                                provider,
+                                                app_type,
+                                                endpoint,
+                                                &provider_body,
+                                            ),
                                         });
                                     }
                                     Err(retry_err) => {
@@ -427,6 +553,8 @@ impl RequestForwarder {
                                         // 区分错误类型：Provider 问题记录失败，客户端问题仅释放 permit
                                         let is_provider_error = match &retry_err {
                                             ProxyError::Timeout(_)
+                                            | ProxyError::ConnectTimeout(_)
+                                            | ProxyError::ReadTimeout(_)
                                             | ProxyError::ForwardFailed(_) => true,
                                             ProxyError::UpstreamError { status, .. } => {
                                                 *status >= 500
@@ -443,7 +571,7 @@ impl RequestForwarder {
                                                     app_type_str,
                                                     used_half_open_permit,
                                                     false,
-                                                    Some(retry_err.to_string()),
+                                                    Some(&retry_err),
                                                 )
                                                 .await;
                                         } else {
@@ -546,6 +674,7 @@ impl RequestForwarder {
                             // 使用同一供应商重试（不计入熔断器）
                             match self
                                 .forward(
+                                    app_type,
                                     provider,
                                     endpoint,
                                     &provider_body,
@@ -554,7 +683,7 @@ impl RequestForwarder {
                                 )
                                 .await
                             {
-                                Ok(response) => {
+                                Ok((response, api_key_index)) => {
                                     log::info!("[{app_type_str}] [RECT-011] budget 整流重试成功");
                                     let _ = self
                                         .router
@@ -606,6 +735,14 @@ impl RequestForwarder {
                                     return Ok(ForwardResult {
                                         response,
                                         provider: provider.clone(),
+                                        api_key_index,
+                                        is_safety_net: false,
+                                        effective_max_tokens: max_tokens_cap::effective_value(
+                                            provider,
+                                            app_type,
+                                            endpoint,
+                                            &provider_body,
+                                        ),
                                     });
                                 }
                                 Err(retry_err) => {
@@ -614,9 +751,10 @@ impl RequestForwarder {
                                     );
 
                                     let is_provider_error = match &retry_err {
-                                        ProxyError::Timeout(_) | ProxyError::ForwardFailed(_) => {
-                                            true
-                                        }
+                                        ProxyError::Timeout(_)
+                                        | ProxyError::ConnectTimeout(_)
+                                        | ProxyError::ReadTimeout(_)
+                                        | ProxyError::ForwardFailed(_) => true,
                                         ProxyError::UpstreamError { status, .. } => *status >= 500,
                                         _ => false,
                                     };
@@ -629,7 +767,7 @@ impl RequestForwarder {
                                                 app_type_str,
                                                 used_half_open_permit,
                                                 false,
-                                                Some(retry_err.to_string()),
+                                                Some(&retry_err),
                                             )
                                             .await;
                                     } else {
@@ -689,7 +827,7 @@ impl RequestForwarder {
                             app_type_str,
                             used_half_open_permit,
                             false,
-                            Some(e.to_string()),
+                            Some(&e),
                         )
                         .await;
 
@@ -740,19 +878,30 @@ impl RequestForwarder {
             }
         }
 
+        // 常规候选全部失败/不可用：最后尝试一次兜底供应商
+        if let Some(result) = self
+            .try_safety_net(app_type, endpoint, &body, &headers, &providers)
+            .await
+        {
+            return Ok(result);
+        }
+
         if attempted_providers == 0 {
-            // providers 列表非空，但全部被熔断器拒绝（典型：HalfOpen 探测名额被占用）
+            // providers 列表非空，但全部被模型策略拦截或被熔断器拒绝（典型：HalfOpen 探测名额被占用）
             {
                 let mut status = self.status.write().await;
                 status.failed_requests += 1;
-                status.last_error = Some("所有供应商暂时不可用（熔断器限制）".to_string());
+                status.last_error = Some(match &policy_error {
+                    Some(e) => e.to_string(),
+                    None => "所有供应商暂时不可用（熔断器限制）".to_string(),
+                });
                 if status.total_requests > 0 {
                     status.success_rate =
                         (status.success_requests as f32 / status.total_requests as f32) * 100.0;
                 }
             }
             return Err(ForwardError {
-                error: ProxyError::NoAvailableProvider,
+                error: policy_error.unwrap_or(ProxyError::NoAvailableProvider),
                 provider: None,
             });
         }
@@ -780,20 +929,177 @@ impl RequestForwarder {
         })
     }
 
+    /// 兜底供应商尝试（`meta.isSafetyNet`）
+    ///
+    /// 只尝试一次，不经过熔断器与整流器，成功后也不触发故障转移切换；
+    /// 未开启自动故障转移、兜底供应商已在本次候选列表中（已经失败过）或被模型策略拦截时直接跳过。
+    async fn try_safety_net(
+        &self,
+        app_type: &AppType,
+        endpoint: &str,
+        body: &Value,
+        headers: &axum::http::HeaderMap,
+        providers: &[Provider],
+    ) -> Option<ForwardResult> {
+        let app_type_str = app_type.as_str();
+        if !self.router.auto_failover_enabled(app_type_str).await {
+            return None;
+        }
+        let safety = self.router.safety_net_provider(app_type_str)?;
+        if providers.iter().any(|p| p.id == safety.id) {
+            return None;
+        }
+        if let Err(e) = model_policy::check_request(body, endpoint, &safety) {
+            log::warn!("[{app_type_str}] 跳过兜底供应商 {}: {e}", safety.name);
+            return None;
+        }
+
+        log::warn!(
+            "[{app_type_str}] [{}] 所有候选供应商均失败，尝试兜底供应商 {}",
+            log_fwd::SAFETY_NET_ATTEMPT,
+            safety.name
+        );
+        {
+            let mut status = self.status.write().await;
+            status.total_requests += 1;
+            status.safety_net_attempts += 1;
+            status.last_request_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+
+        let adapter = get_adapter(app_type);
+        match self
+            .forward(app_type, &safety, endpoint, body, headers, adapter.as_ref())
+            .await
+        {
+            Ok((response, api_key_index)) => {
+                log::info!(
+                    "[{app_type_str}] [{}] 兜底供应商 {} 完成请求",
+                    log_fwd::SAFETY_NET_SUCCEEDED,
+                    safety.name
+                );
+                let mut status = self.status.write().await;
+                status.success_requests += 1;
+                status.last_error = None;
+                status.success_rate =
+                    (status.success_requests as f32 / status.total_requests as f32) * 100.0;
+                let effective_max_tokens =
+                    max_tokens_cap::effective_value(&safety, app_type, endpoint, body);
+                Some(ForwardResult {
+                    response,
+                    provider: safety,
+                    api_key_index,
+                    is_safety_net: true,
+                    effective_max_tokens,
+                })
+            }
+            Err(e) => {
+                log::warn!(
+                    "[{app_type_str}] [{}] 兜底供应商 {} 也失败: {}",
+                    log_fwd::SAFETY_NET_FAILED,
+                    safety.name,
+                    summarize_proxy_error(&e)
+                );
+                None
+            }
+        }
+    }
+
     /// 转发单个请求（使用适配器）
+    ///
+    /// Gemini 供应商配置了 Key 池时，从当前可用的 Key 开始发送，
+    /// 遇到限流/配额错误依次轮换到下一个 Key，全部失败时返回最后一个错误。
+    /// 成功时一并返回所用 Key 的序号（未使用 Key 池时为 None）。
     async fn forward(
         &self,
+        app_type: &AppType,
         provider: &Provider,
         endpoint: &str,
         body: &Value,
         headers: &axum::http::HeaderMap,
         adapter: &dyn ProviderAdapter,
+    ) -> Result<(Response, Option<usize>), ProxyError> {
+        let pool = match app_type {
+            AppType::Gemini => key_pool::provider_keys(provider),
+            _ => Vec::new(),
+        };
+        if pool.is_empty() {
+            return self
+                .forward_once(app_type, provider, endpoint, body, headers, adapter, None)
+                .await
+                .map(|response| (response, None));
+        }
+
+        let app_type_str = app_type.as_str();
+        let start = self
+            .router
+            .current_key_index(&provider.id, app_type_str, pool.len())
+            .await;
+        let mut last_error = None;
+
+        for offset in 0..pool.len() {
+            let index = (start + offset) % pool.len();
+            match self
+                .forward_once(
+                    app_type,
+                    provider,
+                    endpoint,
+                    body,
+                    headers,
+                    adapter,
+                    Some(&pool[index]),
+                )
+                .await
+            {
+                Ok(response) => {
+                    log::info!(
+                        "[Gemini] 供应商 {} 使用 Key #{index}/{} 完成请求",
+                        provider.name,
+                        pool.len()
+                    );
+                    return Ok((response, Some(index)));
+                }
+                Err(e) if key_pool::is_quota_error(&e) => {
+                    log::warn!(
+                        "[Gemini] 供应商 {} 的 Key #{index} 触发限流/配额错误，轮换到下一个 Key",
+                        provider.name
+                    );
+                    self.router
+                        .rotate_key(&provider.id, app_type_str, index, pool.len())
+                        .await;
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or(ProxyError::NoAvailableProvider))
+    }
+
+    /// 向供应商发送一次请求
+    ///
+    /// `api_key` 为 Key 池中选中的 Key，未设置时使用供应商配置中的认证信息
+    #[allow(clippy::too_many_arguments)]
+    async fn forward_once(
+        &self,
+        app_type: &AppType,
+        provider: &Provider,
+        endpoint: &str,
+        body: &Value,
+        headers: &axum::http::HeaderMap,
+        adapter: &dyn ProviderAdapter,
+        api_key: Option<&str>,
     ) -> Result<Response, ProxyError> {
         // 使用适配器提取 base_url
         let base_url = adapter.extract_base_url(provider)?;
 
-        // 检查是否需要格式转换
-        let needs_transform = adapter.needs_transform(provider);
+        // 原样透传：跳过格式转换、协议桥接与所有请求体改写
+        let raw_passthrough = provider.is_raw_passthrough();
+
+        // 检查是否需要格式转换（仅针对 Claude Messages 请求；
+        // 共用路径路由到 Claude 的 Chat Completions 请求本身已是 OpenAI 格式）
+        let needs_transform = !raw_passthrough
+            && adapter.needs_transform(provider)
+            && (*app_type != AppType::Claude || endpoint == "/v1/messages");
 
         // 确定有效端点
         // GitHub Copilot API 使用 /chat/completions（无 /v1 前缀）
@@ -804,7 +1110,7 @@ impl RequestForwarder {
             == Some("github_copilot")
             || base_url.contains("githubcopilot.com");
         let effective_endpoint =
-            if needs_transform && adapter.name() == "Claude" && endpoint == "/v1/messages" {
+            if needs_transform && *app_type == AppType::Claude && endpoint == "/v1/messages" {
                 if is_copilot {
                     // GitHub Copilot uses /chat/completions without /v1 prefix
                     "/chat/completions"
@@ -821,30 +1127,101 @@ impl RequestForwarder {
                 endpoint
             };
 
+        // Codex 协议桥接：客户端与上游的 OpenAI 协议不一致时改写端点和请求体
+        let codex_bridge = if *app_type == AppType::Codex && !raw_passthrough {
+            super::providers::codex_bridge::get_codex_bridge(provider, endpoint)
+        } else {
+            None
+        };
+        let effective_endpoint = codex_bridge
+            .map(|bridge| bridge.upstream_endpoint())
+            .unwrap_or(effective_endpoint);
+
         // 使用适配器构建 URL
         let url = adapter.build_url(&base_url, effective_endpoint);
 
-        // 应用模型映射（独立于格式转换）
-        let (mapped_body, _original_model, _mapped_model) =
-            super::model_mapper::apply_model_mapping(body.clone(), provider);
+        let mapped_body = if raw_passthrough {
+            body.clone()
+        } else {
+            // 应用模型映射（独立于格式转换）
+            let (mapped_body, _original_model, _mapped_model) =
+                super::model_mapper::apply_model_mapping(body.clone(), provider);
+
+            // 与 CCH 对齐：请求前不做 thinking 主动改写（仅保留兼容入口）
+            let mut mapped_body = normalize_thinking_type(mapped_body);
+
+            // 注入供应商配置的系统提示词前缀（按客户端协议注入，格式转换会随之带到上游）
+            if let Some(prefix) = system_prompt::provider_prefix(provider) {
+                if let Some(shape) = PromptShape::detect(app_type, endpoint) {
+                    if system_prompt::inject(&mut mapped_body, shape, prefix) {
+                        log::debug!("[{}] 已注入系统提示词前缀 ({shape:?})", adapter.name());
+                    }
+                }
+            }
 
-        // 与 CCH 对齐：请求前不做 thinking 主动改写（仅保留兼容入口）
-        let mapped_body = normalize_thinking_type(mapped_body);
+            // 限制最大输出 token 数（按客户端协议改写，格式转换会随之带到上游；仅生成端点）
+            if let Some(cap) = max_tokens_cap::cap_for_request(provider, app_type, endpoint) {
+                if let Some(effective) =
+                    max_tokens_cap::apply(&mut mapped_body, app_type, endpoint, cap)
+                {
+                    log::debug!(
+                        "[{}] 供应商 {} 的最大输出 token 数生效值: {effective}（上限 {cap}）",
+                        adapter.name(),
+                        provider.name
+                    );
+                }
+            }
+            mapped_body
+        };
 
-        // 转换请求体（如果需要）
-        let request_body = if needs_transform {
-            adapter.transform_request(mapped_body, provider)?
+        // Azure OpenAI：按映射后的模型选择部署，改写为部署路由 URL
+        let url = if *app_type == AppType::Codex && azure::is_azure(provider) {
+            let model = mapped_body
+                .get("model")
+                .and_then(|m| m.as_str())
+                .unwrap_or_default();
+            let deployment = azure::deployment_for(provider, model);
+            azure::build_url(
+                &base_url,
+                effective_endpoint,
+                &deployment,
+                azure::api_version(provider),
+            )
         } else {
-            mapped_body
+            url
         };
 
-        // 过滤私有参数（以 `_` 开头的字段），防止内部信息泄露到上游
-        // 默认使用空白名单，过滤所有 _ 前缀字段
-        let filtered_body = filter_private_params_with_whitelist(request_body, &[]);
+        let filtered_body = if raw_passthrough {
+            mapped_body
+        } else {
+            // 转换请求体（如果需要）
+            let request_body = if needs_transform {
+                adapter.transform_request(mapped_body, provider)?
+            } else {
+                mapped_body
+            };
+            let request_body = match codex_bridge {
+                Some(bridge) => bridge.transform_request(request_body)?,
+                None => request_body,
+            };
+
+            // 执行供应商配置的请求体转换脚本（作用于最终发往上游的请求体）
+            let request_body = match body_transform::request_script(provider) {
+                Some(script) => {
+                    body_transform::apply(script, request_body, adapter.name(), "request").await
+                }
+                None => request_body,
+            };
+
+            // 过滤私有参数（以 `_` 开头的字段），防止内部信息泄露到上游
+            // 默认使用空白名单，过滤所有 _ 前缀字段
+            filter_private_params_with_whitelist(request_body, &[])
+        };
 
-        // 获取 HTTP 客户端：优先使用供应商单独代理配置，否则使用全局客户端
-        let proxy_config = provider.meta.as_ref().and_then(|m| m.proxy_config.as_ref());
-        let client = super::http_client::get_for_provider(proxy_config);
+        // 获取 HTTP 客户端：优先使用供应商单独代理配置，否则使用全局客户端；
+        // 配置了最低 TLS 版本或证书指纹时使用专用客户端
+        let client = super::http_client::get_for_provider_meta(provider.meta.as_ref())
+            .map_err(ProxyError::ConfigError)?;
         let mut request = client.post(&url);
 
         // 只有当 timeout > 0 时才设置请求超时
@@ -854,34 +1231,25 @@ impl RequestForwarder {
             request = request.timeout(self.non_streaming_timeout);
         }
 
+        // 供应商配置的 Header 删除/附加规则
+        let strip_headers = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.strip_headers.as_deref())
+            .unwrap_or_default();
+
         // 过滤黑名单 Headers，保护隐私并避免冲突
         for (key, value) in headers {
-            let key_str = key.as_str();
-            if HEADER_BLACKLIST
-                .iter()
-                .any(|h| key_str.eq_ignore_ascii_case(h))
-            {
-                continue;
+            if forward_inbound_header(key.as_str(), is_copilot, strip_headers) {
+                request = request.header(key, value);
             }
-            // Copilot 请求：过滤会由 add_auth_headers 注入的固定指纹头，
-            // 防止客户端原始头与注入头重复（reqwest header() 是追加语义）
-            if is_copilot
-                && (key_str.eq_ignore_ascii_case("user-agent")
-                    || key_str.eq_ignore_ascii_case("editor-version")
-                    || key_str.eq_ignore_ascii_case("editor-plugin-version")
-                    || key_str.eq_ignore_ascii_case("copilot-integration-id")
-                    || key_str.eq_ignore_ascii_case("x-github-api-version")
-                    || key_str.eq_ignore_ascii_case("openai-intent"))
-            {
-                continue;
-            }
-            request = request.header(key, value);
         }
 
         // 处理 anthropic-beta Header（仅 Claude）
         // 关键：确保包含 claude-code-20250219 标记，这是上游服务验证请求来源的依据
         // 如果客户端发送的 beta 标记中没有包含 claude-code-20250219，需要补充
-        if adapter.name() == "Claude" {
+        // 供应商配置了删除 anthropic-beta 时整体不发送
+        if adapter.name() == "Claude" && !is_stripped_header("anthropic-beta", strip_headers) {
             const CLAUDE_CODE_BETA: &str = "claude-code-20250219";
             let beta_value = if let Some(beta) = headers.get("anthropic-beta") {
                 if let Ok(beta_str) = beta.to_str() {
@@ -903,25 +1271,35 @@ impl RequestForwarder {
         }
 
         // 客户端 IP 透传（默认开启）
-        if let Some(xff) = headers.get("x-forwarded-for") {
+        if let Some(xff) = headers
+            .get("x-forwarded-for")
+            .filter(|_| !is_stripped_header("x-forwarded-for", strip_headers))
+        {
             if let Ok(xff_str) = xff.to_str() {
                 request = request.header("x-forwarded-for", xff_str);
             }
         }
-        if let Some(real_ip) = headers.get("x-real-ip") {
+        if let Some(real_ip) = headers
+            .get("x-real-ip")
+            .filter(|_| !is_stripped_header("x-real-ip", strip_headers))
+        {
             if let Ok(real_ip_str) = real_ip.to_str() {
                 request = request.header("x-real-ip", real_ip_str);
             }
         }
 
         // 流式请求保守禁用压缩，避免上游压缩 SSE 在连接中断时触发解压错误。
-        // 非流式请求不显式设置 Accept-Encoding，让 reqwest 自动协商压缩并透明解压。
+        // 非流式请求不显式设置 Accept-Encoding，让 reqwest 自动协商压缩（gzip/deflate/br）并透明解压。
         if should_force_identity_encoding(effective_endpoint, &filtered_body, headers) {
             request = request.header("accept-encoding", "identity");
         }
 
-        // 使用适配器添加认证头
-        if let Some(mut auth) = adapter.extract_auth(provider) {
+        // 使用适配器添加认证头（Key 池仅用于 Gemini，按 API Key 方式认证）
+        let auth = match api_key {
+            Some(key) => Some(AuthInfo::new(key.to_string(), AuthStrategy::Google)),
+            None => adapter.extract_auth(provider),
+        };
+        if let Some(mut auth) = auth {
             // GitHub Copilot 特殊处理：从 CopilotAuthManager 获取真实 token
             if auth.strategy == AuthStrategy::GitHubCopilot {
                 if let Some(app_handle) = &self.app_handle {
@@ -985,6 +1363,24 @@ impl RequestForwarder {
             request = request.header("anthropic-version", version_str);
         }
 
+        // 供应商配置的附加 Headers（无效的名称或值跳过）
+        // 通过 `headers()` 整体设置：同名的透传头或认证头会被替换，而不是重复发送
+        if let Some(add_headers) = provider.meta.as_ref().and_then(|m| m.add_headers.as_ref()) {
+            let mut extra = reqwest::header::HeaderMap::new();
+            for (name, value) in add_headers {
+                match (
+                    reqwest::header::HeaderName::from_bytes(name.trim().as_bytes()),
+                    reqwest::header::HeaderValue::from_str(value),
+                ) {
+                    (Ok(name), Ok(value)) => {
+                        extra.insert(name, value);
+                    }
+                    _ => log::warn!("[{}] 忽略无效的附加请求头: {name}", adapter.name()),
+                }
+            }
+            request = request.headers(extra);
+        }
+
         // 输出请求信息日志
         let tag = adapter.name();
         let request_model = filtered_body
@@ -992,7 +1388,9 @@ impl RequestForwarder {
             .and_then(|v| v.as_str())
             .unwrap_or("<none>");
         log::info!("[{tag}] >>> 请求 URL: {url} (model={request_model})");
-        if let Ok(body_str) = serde_json::to_string(&filtered_body) {
+        if raw_passthrough {
+            log::debug!("[{tag}] >>> 原样透传，不记录请求体内容");
+        } else if let Ok(body_str) = serde_json::to_string(&filtered_body) {
             log::debug!(
                 "[{tag}] >>> 请求体内容 ({}字节): {}",
                 body_str.len(),
@@ -1002,20 +1400,24 @@ impl RequestForwarder {
 
         // 发送请求
         let response = request.json(&filtered_body).send().await.map_err(|e| {
-            if e.is_timeout() {
-                ProxyError::Timeout(format!("请求超时: {e}"))
-            } else if e.is_connect() {
-                ProxyError::ForwardFailed(format!("连接失败: {e}"))
-            } else {
-                ProxyError::ForwardFailed(e.to_string())
-            }
+            ProxyError::from_send_failure(
+                e.is_connect(),
+                e.is_timeout(),
+                super::http_client::describe_error(&e),
+            )
         })?;
 
         // 检查响应状态
         let status = response.status();
 
         if status.is_success() {
-            Ok(response)
+            // 响应体转换脚本仅作用于非流式 JSON 响应
+            match body_transform::response_script(provider) {
+                Some(script) if !raw_passthrough && !is_sse_response(&response) => {
+                    body_transform::apply_to_response(script, response, adapter.name()).await
+                }
+                _ => Ok(response),
+            }
         } else {
             let status_code = status.as_u16();
             let body_text = response.text().await.ok();
@@ -1031,6 +1433,8 @@ impl RequestForwarder {
         match error {
             // 网络和上游错误：都应该尝试下一个供应商
             ProxyError::Timeout(_) => ErrorCategory::Retryable,
+            ProxyError::ConnectTimeout(_) => ErrorCategory::Retryable,
+            ProxyError::ReadTimeout(_) => ErrorCategory::Retryable,
             ProxyError::ForwardFailed(_) => ErrorCategory::Retryable,
             ProxyError::ProviderUnhealthy(_) => ErrorCategory::Retryable,
             // 上游 HTTP 错误：无论状态码如何，都尝试下一个供应商
@@ -1129,6 +1533,12 @@ fn summarize_proxy_error(error: &ProxyError) -> String {
         ProxyError::Timeout(message) => {
             format!("请求超时: {}", summarize_text_for_log(message, 180))
         }
+        ProxyError::ConnectTimeout(message) => {
+            format!("连接超时: {}", summarize_text_for_log(message, 180))
+        }
+        ProxyError::ReadTimeout(message) => {
+            format!("首字节超时: {}", summarize_text_for_log(message, 180))
+        }
         ProxyError::ForwardFailed(message) => {
             format!("请求转发失败: {}", summarize_text_for_log(message, 180))
         }
@@ -1173,6 +1583,36 @@ fn extract_json_error_message(body: &Value) -> Option<String> {
         .find_map(|value| value.as_str().map(ToString::to_string))
 }
 
+/// 供应商是否配置删除该请求头（不区分大小写）
+fn is_stripped_header(name: &str, strip_headers: &[String]) -> bool {
+    strip_headers
+        .iter()
+        .any(|h| h.trim().eq_ignore_ascii_case(name))
+}
+
+/// 入站请求头是否透传到上游
+fn forward_inbound_header(name: &str, is_copilot: bool, strip_headers: &[String]) -> bool {
+    if HEADER_BLACKLIST
+        .iter()
+        .any(|h| name.eq_ignore_ascii_case(h))
+    {
+        return false;
+    }
+    // Copilot 请求：过滤会由 add_auth_headers 注入的固定指纹头，
+    // 防止客户端原始头与注入头重复（reqwest header() 是追加语义）
+    if is_copilot
+        && (name.eq_ignore_ascii_case("user-agent")
+            || name.eq_ignore_ascii_case("editor-version")
+            || name.eq_ignore_ascii_case("editor-plugin-version")
+            || name.eq_ignore_ascii_case("copilot-integration-id")
+            || name.eq_ignore_ascii_case("x-github-api-version")
+            || name.eq_ignore_ascii_case("openai-intent"))
+    {
+        return false;
+    }
+    !is_stripped_header(name, strip_headers)
+}
+
 fn should_force_identity_encoding(
     endpoint: &str,
     body: &Value,
@@ -1325,4 +1765,745 @@ mod tests {
             &headers
         ));
     }
+
+    #[tokio::test]
+    async fn gemini_key_pool_rotates_on_429_and_sticks_with_working_key() {
+        use crate::database::Database;
+        use crate::provider::ProviderMeta;
+        use std::sync::Mutex;
+
+        // 模拟上游：key-0 配额耗尽，其余 Key 正常
+        let seen_keys = Arc::new(Mutex::new(Vec::<String>::new()));
+        let upstream_keys = seen_keys.clone();
+        let upstream = axum::Router::new().route(
+            "/v1beta/models/:model",
+            axum::routing::post(move |headers: HeaderMap| {
+                let seen = upstream_keys.clone();
+                async move {
+                    let key = headers
+                        .get("x-goog-api-key")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string();
+                    seen.lock().unwrap().push(key.clone());
+                    if key == "key-0" {
+                        (
+                            axum::http::StatusCode::TOO_MANY_REQUESTS,
+                            r#"{"error":{"code":429,"status":"RESOURCE_EXHAUSTED"}}"#,
+                        )
+                    } else {
+                        (axum::http::StatusCode::OK, r#"{"candidates":[]}"#)
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.ok();
+        });
+
+        let mut provider = Provider::with_id(
+            "g1".to_string(),
+            "Gemini Free".to_string(),
+            json!({
+                "env": {
+                    "GOOGLE_GEMINI_BASE_URL": format!("http://{addr}"),
+                    "GEMINI_API_KEY": "key-0"
+                }
+            }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            api_key_pool: Some(vec!["key-0".to_string(), "key-1".to_string()]),
+            ..Default::default()
+        });
+
+        let db = Arc::new(Database::memory().unwrap());
+        let router = Arc::new(ProviderRouter::new(db.clone()));
+        let forwarder = RequestForwarder::new(
+            router.clone(),
+            30,
+            Arc::new(RwLock::new(ProxyStatus::default())),
+            Arc::new(RwLock::new(std::collections::HashMap::new())),
+            Arc::new(FailoverSwitchManager::new(db)),
+            None,
+            "g1".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        );
+
+        let body = json!({ "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }] });
+        for _ in 0..2 {
+            let result = forwarder
+                .forward_with_retry(
+                    &AppType::Gemini,
+                    "/v1beta/models/gemini-2.5-flash:generateContent",
+                    body.clone(),
+                    HeaderMap::new(),
+                    vec![provider.clone()],
+                )
+                .await;
+            let Ok(result) = result else {
+                panic!("request should succeed with the second key");
+            };
+            assert!(result.response.status().is_success());
+            assert_eq!(result.api_key_index, Some(1));
+        }
+
+        // 第一次请求：key-0 返回 429 后轮换到 key-1；第二次请求直接沿用 key-1
+        assert_eq!(*seen_keys.lock().unwrap(), vec!["key-0", "key-1", "key-1"]);
+        assert_eq!(router.current_key_index("g1", "gemini", 2).await, 1);
+    }
+
+    #[tokio::test]
+    async fn shadow_provider_is_mirrored_and_logged_without_affecting_primary() {
+        use crate::database::Database;
+        use crate::provider::ProviderMeta;
+        use crate::proxy::usage::{LoggingPause, UsageLogWriter};
+
+        // 模拟上游：主供应商立即返回；影子供应商稍后返回并携带用量
+        let upstream = |name: &'static str, delay_ms: u64| {
+            axum::Router::new().route(
+                "/v1/messages",
+                axum::routing::post(move || async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                    axum::Json(json!({
+                        "id": name,
+                        "model": "claude-sonnet-4-5",
+                        "content": [{ "type": "text", "text": name }],
+                        "usage": { "input_tokens": 11, "output_tokens": 22 }
+                    }))
+                }),
+            )
+        };
+        let mut addrs = Vec::new();
+        for (name, delay_ms) in [("primary", 0), ("shadow", 50)] {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            addrs.push(listener.local_addr().unwrap());
+            let app = upstream(name, delay_ms);
+            tokio::spawn(async move {
+                axum::serve(listener, app).await.ok();
+            });
+        }
+        let claude_provider = |id: &str, addr: std::net::SocketAddr| {
+            Provider::with_id(
+                id.to_string(),
+                id.to_string(),
+                json!({
+                    "env": {
+                        "ANTHROPIC_BASE_URL": format!("http://{addr}"),
+                        "ANTHROPIC_AUTH_TOKEN": format!("{id}-token")
+                    }
+                }),
+                None,
+            )
+        };
+
+        let db = Arc::new(Database::memory().unwrap());
+        let mut primary = claude_provider("primary", addrs[0]);
+        primary.meta = Some(ProviderMeta {
+            shadow_provider_id: Some("shadow".to_string()),
+            ..Default::default()
+        });
+        db.save_provider("claude", &claude_provider("shadow", addrs[1]))
+            .unwrap();
+
+        let writer = Arc::new(UsageLogWriter::spawn(db.clone()));
+        let status = Arc::new(RwLock::new(ProxyStatus::default()));
+        let forwarder = RequestForwarder::new(
+            Arc::new(ProviderRouter::new(db.clone())),
+            30,
+            status.clone(),
+            Arc::new(RwLock::new(std::collections::HashMap::new())),
+            Arc::new(FailoverSwitchManager::new(db.clone())),
+            None,
+            "primary".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        )
+        .with_shadow(ShadowSink::new(
+            db.clone(),
+            writer.clone(),
+            Arc::new(LoggingPause::default()),
+            true,
+        ));
+
+        let Ok(result) = forwarder
+            .forward_with_retry(
+                &AppType::Claude,
+                "/v1/messages",
+                json!({ "model": "claude-sonnet-4-5", "messages": [] }),
+                HeaderMap::new(),
+                vec![primary],
+            )
+            .await
+        else {
+            panic!("primary request should succeed");
+        };
+        assert_eq!(result.provider.id, "primary");
+        let body: Value = result.response.json().await.unwrap();
+        assert_eq!(body["id"], "primary");
+
+        // 等待影子请求完成并落盘
+        let mut shadow_rows = Vec::new();
+        for _ in 0..100 {
+            writer.flush().await;
+            let conn = crate::database::lock_conn!(db.conn);
+            let mut stmt = conn
+                .prepare(
+                    "SELECT provider_id, input_tokens, output_tokens, status_code
+                     FROM proxy_request_logs WHERE is_shadow = 1",
+                )
+                .unwrap();
+            shadow_rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, i64>(2)?,
+                        row.get::<_, i64>(3)?,
+                    ))
+                })
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            drop(stmt);
+            drop(conn);
+            if !shadow_rows.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(shadow_rows, vec![("shadow".to_string(), 11, 22, 200)]);
+
+        // 影子请求不计入代理状态
+        let status = status.read().await;
+        assert_eq!(status.total_requests, 1);
+        assert_eq!(status.current_provider_id.as_deref(), Some("primary"));
+    }
+
+    #[tokio::test]
+    async fn gzip_encoded_response_is_decompressed_for_usage_parsing() {
+        use crate::database::Database;
+        use crate::proxy::handler_config::CLAUDE_PARSER_CONFIG;
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        // 模拟上游：无论客户端是否声明支持，都返回 gzip 压缩的 JSON
+        let upstream = axum::Router::new().route(
+            "/v1/messages",
+            axum::routing::post(|| async {
+                let body = json!({
+                    "id": "msg_gzip",
+                    "model": "claude-sonnet-4-5",
+                    "content": [{ "type": "text", "text": "hi" }],
+                    "usage": { "input_tokens": 7, "output_tokens": 9 }
+                })
+                .to_string();
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body.as_bytes()).unwrap();
+                (
+                    [
+                        (axum::http::header::CONTENT_TYPE, "application/json"),
+                        (axum::http::header::CONTENT_ENCODING, "gzip"),
+                    ],
+                    encoder.finish().unwrap(),
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.ok();
+        });
+
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": format!("http://{addr}"),
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test"
+                }
+            }),
+            None,
+        );
+        let db = Arc::new(Database::memory().unwrap());
+        let forwarder = RequestForwarder::new(
+            Arc::new(ProviderRouter::new(db.clone())),
+            30,
+            Arc::new(RwLock::new(ProxyStatus::default())),
+            Arc::new(RwLock::new(std::collections::HashMap::new())),
+            Arc::new(FailoverSwitchManager::new(db)),
+            None,
+            "p1".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        );
+
+        let Ok(result) = forwarder
+            .forward_with_retry(
+                &AppType::Claude,
+                "/v1/messages",
+                json!({ "model": "claude-sonnet-4-5", "messages": [] }),
+                HeaderMap::new(),
+                vec![provider],
+            )
+            .await
+        else {
+            panic!("request should succeed");
+        };
+
+        // 解压后不再携带 Content-Encoding，转发给客户端的头与响应体保持一致
+        assert!(result
+            .response
+            .headers()
+            .get(axum::http::header::CONTENT_ENCODING)
+            .is_none());
+        let body = result.response.bytes().await.unwrap();
+        let json: Value = serde_json::from_slice(&body).expect("decompressed JSON body");
+        let usage = (CLAUDE_PARSER_CONFIG.response_parser)(&json).expect("usage");
+        assert_eq!((usage.input_tokens, usage.output_tokens), (7, 9));
+    }
+
+    #[tokio::test]
+    async fn raw_passthrough_forwards_request_body_unmodified() {
+        use crate::database::Database;
+        use crate::provider::ProviderMeta;
+
+        // 模拟上游：原样回显收到的请求体
+        let upstream = axum::Router::new().route(
+            "/v1/messages",
+            axum::routing::post(|body: axum::body::Bytes| async move {
+                (
+                    [(axum::http::header::CONTENT_TYPE, "application/json")],
+                    body,
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.ok();
+        });
+
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": format!("http://{addr}"),
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test",
+                    "ANTHROPIC_DEFAULT_SONNET_MODEL": "sonnet-mapped"
+                }
+            }),
+            None,
+        );
+        let db = Arc::new(Database::memory().unwrap());
+        let forwarder = RequestForwarder::new(
+            Arc::new(ProviderRouter::new(db.clone())),
+            30,
+            Arc::new(RwLock::new(ProxyStatus::default())),
+            Arc::new(RwLock::new(std::collections::HashMap::new())),
+            Arc::new(FailoverSwitchManager::new(db)),
+            None,
+            "p1".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        );
+        let body = json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [],
+            "_client_trace": "keep-me"
+        });
+
+        let echoed = |result: Result<ForwardResult, ForwardError>| async move {
+            let Ok(result) = result else {
+                panic!("request should succeed");
+            };
+            let bytes = result.response.bytes().await.unwrap();
+            serde_json::from_slice::<Value>(&bytes).expect("echoed JSON body")
+        };
+
+        // 默认：应用模型映射并过滤私有参数
+        let normal = echoed(
+            forwarder
+                .forward_with_retry(
+                    &AppType::Claude,
+                    "/v1/messages",
+                    body.clone(),
+                    HeaderMap::new(),
+                    vec![provider.clone()],
+                )
+                .await,
+        )
+        .await;
+        assert_eq!(normal["model"], "sonnet-mapped");
+        assert!(normal.get("_client_trace").is_none());
+
+        // 原样透传：请求体不做任何改写
+        provider.meta = Some(ProviderMeta {
+            raw_passthrough: Some(true),
+            ..Default::default()
+        });
+        let raw = echoed(
+            forwarder
+                .forward_with_retry(
+                    &AppType::Claude,
+                    "/v1/messages",
+                    body.clone(),
+                    HeaderMap::new(),
+                    vec![provider],
+                )
+                .await,
+        )
+        .await;
+        assert_eq!(raw, body);
+    }
+
+    #[test]
+    fn stripped_headers_are_not_forwarded() {
+        let strip = vec!["Anthropic-Beta".to_string(), " x-custom ".to_string()];
+
+        assert!(!forward_inbound_header("anthropic-beta", false, &strip));
+        assert!(!forward_inbound_header("X-Custom", false, &strip));
+        assert!(forward_inbound_header("x-other", false, &strip));
+        assert!(forward_inbound_header("user-agent", false, &strip));
+        // 黑名单仍然生效
+        assert!(!forward_inbound_header("authorization", false, &[]));
+    }
+
+    #[tokio::test]
+    async fn provider_header_rules_strip_and_add_upstream_headers() {
+        use crate::database::Database;
+        use crate::provider::ProviderMeta;
+
+        // 模拟上游：以 JSON 回显关心的请求头
+        let upstream = axum::Router::new().route(
+            "/v1/messages",
+            axum::routing::post(|headers: HeaderMap| async move {
+                let echo: serde_json::Map<String, Value> = [
+                    "anthropic-beta",
+                    "anthropic-version",
+                    "x-client-flag",
+                    "x-client-version",
+                    "x-relay-tag",
+                    "x-forwarded-for",
+                ]
+                .iter()
+                .map(|name| {
+                    // 同名头全部拼接返回，便于断言没有重复发送
+                    let values: Vec<&str> = headers
+                        .get_all(*name)
+                        .iter()
+                        .filter_map(|v| v.to_str().ok())
+                        .collect();
+                    let value = if values.is_empty() {
+                        Value::Null
+                    } else {
+                        Value::String(values.join(","))
+                    };
+                    (name.to_string(), value)
+                })
+                .collect();
+                axum::Json(Value::Object(echo))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.ok();
+        });
+
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": format!("http://{addr}"),
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test"
+                }
+            }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            strip_headers: Some(vec![
+                "anthropic-beta".to_string(),
+                "X-Client-Flag".to_string(),
+            ]),
+            add_headers: Some(
+                [
+                    ("x-relay-tag", "team-a"),
+                    ("x-client-version", "relay"),
+                    ("anthropic-version", "2024-01-01"),
+                ]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            ),
+            ..Default::default()
+        });
+        let db = Arc::new(Database::memory().unwrap());
+        let forwarder = RequestForwarder::new(
+            Arc::new(ProviderRouter::new(db.clone())),
+            30,
+            Arc::new(RwLock::new(ProxyStatus::default())),
+            Arc::new(RwLock::new(std::collections::HashMap::new())),
+            Arc::new(FailoverSwitchManager::new(db)),
+            None,
+            "p1".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-beta",
+            HeaderValue::from_static("interleaved-thinking-2025-05-14"),
+        );
+        headers.insert("x-client-flag", HeaderValue::from_static("1"));
+        headers.insert("x-client-version", HeaderValue::from_static("1.0"));
+        headers.insert("x-forwarded-for", HeaderValue::from_static("10.0.0.1"));
+
+        let Ok(result) = forwarder
+            .forward_with_retry(
+                &AppType::Claude,
+                "/v1/messages",
+                json!({ "model": "claude-sonnet-4-5", "messages": [] }),
+                headers,
+                vec![provider],
+            )
+            .await
+        else {
+            panic!("request should succeed");
+        };
+        let bytes = result.response.bytes().await.unwrap();
+        let seen: Value = serde_json::from_slice(&bytes).expect("echoed headers");
+
+        // 列出的头（包括代理补充的 anthropic-beta）不再发送，其余保持透传
+        assert_eq!(seen["anthropic-beta"], Value::Null);
+        assert_eq!(seen["x-client-flag"], Value::Null);
+        assert_eq!(seen["x-forwarded-for"], "10.0.0.1");
+        assert_eq!(seen["x-relay-tag"], "team-a");
+        // 附加头替换同名的透传头与代理设置的头，只发送一次
+        assert_eq!(seen["x-client-version"], "relay");
+        assert_eq!(seen["anthropic-version"], "2024-01-01");
+    }
+
+    #[tokio::test]
+    async fn safety_net_provider_is_tried_after_all_candidates_fail() {
+        use crate::database::Database;
+        use crate::provider::ProviderMeta;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // 模拟上游：两个常规供应商返回 500，兜底供应商正常
+        let hits = Arc::new(AtomicUsize::new(0));
+        let mut addrs = Vec::new();
+        for healthy in [false, false, true] {
+            let hits = hits.clone();
+            let app = axum::Router::new().route(
+                "/v1/messages",
+                axum::routing::post(move || {
+                    let hits = hits.clone();
+                    async move {
+                        hits.fetch_add(1, Ordering::SeqCst);
+                        if healthy {
+                            (axum::http::StatusCode::OK, r#"{"id":"safety"}"#)
+                        } else {
+                            (
+                                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                                r#"{"error":{"message":"boom"}}"#,
+                            )
+                        }
+                    }
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            addrs.push(listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                axum::serve(listener, app).await.ok();
+            });
+        }
+        let claude_provider = |id: &str, addr: std::net::SocketAddr| {
+            Provider::with_id(
+                id.to_string(),
+                id.to_string(),
+                json!({
+                    "env": {
+                        "ANTHROPIC_BASE_URL": format!("http://{addr}"),
+                        "ANTHROPIC_AUTH_TOKEN": format!("{id}-token")
+                    }
+                }),
+                None,
+            )
+        };
+
+        let db = Arc::new(Database::memory().unwrap());
+        let mut app_config = db.get_proxy_config_for_app("claude").await.unwrap();
+        app_config.auto_failover_enabled = false;
+        db.update_proxy_config_for_app(app_config.clone())
+            .await
+            .unwrap();
+        let mut safety = claude_provider("safety", addrs[2]);
+        safety.meta = Some(ProviderMeta {
+            is_safety_net: Some(true),
+            ..Default::default()
+        });
+        db.save_provider("claude", &safety).unwrap();
+
+        let status = Arc::new(RwLock::new(ProxyStatus::default()));
+        let current_providers = Arc::new(RwLock::new(std::collections::HashMap::new()));
+        let forwarder = RequestForwarder::new(
+            Arc::new(ProviderRouter::new(db.clone())),
+            30,
+            status.clone(),
+            current_providers.clone(),
+            Arc::new(FailoverSwitchManager::new(db.clone())),
+            None,
+            "a".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        );
+        let forward = || {
+            forwarder.forward_with_retry(
+                &AppType::Claude,
+                "/v1/messages",
+                json!({ "model": "claude-sonnet-4-5", "messages": [] }),
+                HeaderMap::new(),
+                vec![
+                    claude_provider("a", addrs[0]),
+                    claude_provider("b", addrs[1]),
+                ],
+            )
+        };
+
+        // 未开启自动故障转移时不尝试兜底供应商
+        assert!(forward().await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        app_config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(app_config).await.unwrap();
+        hits.store(0, Ordering::SeqCst);
+        let Ok(result) = forward().await else {
+            panic!("safety net provider should serve the request");
+        };
+        assert_eq!(result.provider.id, "safety");
+        assert!(result.is_safety_net);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // 兜底尝试单独计数，且不会成为当前供应商
+        let status = status.read().await;
+        assert_eq!(status.safety_net_attempts, 1);
+        assert_eq!(status.failover_count, 0);
+        assert!(current_providers.read().await.get("claude").is_none());
+    }
+
+    #[tokio::test]
+    async fn safety_net_provider_is_tried_when_all_circuits_are_open() {
+        use crate::database::Database;
+        use crate::error::AppError;
+        use crate::provider::ProviderMeta;
+        use crate::proxy::circuit_breaker::CircuitBreakerConfig;
+
+        let app = axum::Router::new().route(
+            "/v1/messages",
+            axum::routing::post(|| async { r#"{"id":"safety"}"# }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        let db = Arc::new(Database::memory().unwrap());
+        db.update_circuit_breaker_config(&CircuitBreakerConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        for id in ["a", "b"] {
+            let provider = Provider::with_id(id.to_string(), id.to_string(), json!({}), None);
+            db.save_provider("claude", &provider).unwrap();
+            db.add_to_failover_queue("claude", id).unwrap();
+        }
+        let mut safety = Provider::with_id(
+            "safety".to_string(),
+            "safety".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": format!("http://{addr}"),
+                    "ANTHROPIC_AUTH_TOKEN": "safety-token"
+                }
+            }),
+            None,
+        );
+        safety.meta = Some(ProviderMeta {
+            is_safety_net: Some(true),
+            ..Default::default()
+        });
+        db.save_provider("claude", &safety).unwrap();
+        let mut app_config = db.get_proxy_config_for_app("claude").await.unwrap();
+        app_config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(app_config).await.unwrap();
+
+        // 两个常规供应商的熔断器全部打开
+        let router = Arc::new(ProviderRouter::new(db.clone()));
+        let fail = ProxyError::ForwardFailed("fail".to_string());
+        for id in ["a", "b"] {
+            router
+                .record_result(id, "claude", false, false, Some(&fail))
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            router.select_providers("claude").await,
+            Err(AppError::AllProvidersCircuitOpen)
+        ));
+        let fallback = router.safety_net_fallback("claude").await.unwrap();
+        assert_eq!(fallback.id, "safety");
+
+        let status = Arc::new(RwLock::new(ProxyStatus::default()));
+        let forwarder = RequestForwarder::new(
+            router,
+            30,
+            status.clone(),
+            Arc::new(RwLock::new(std::collections::HashMap::new())),
+            Arc::new(FailoverSwitchManager::new(db.clone())),
+            None,
+            "a".to_string(),
+            0,
+            0,
+            RectifierConfig::default(),
+            OptimizerConfig::default(),
+        );
+        let Ok(result) = forwarder
+            .forward_with_retry(
+                &AppType::Claude,
+                "/v1/messages",
+                json!({ "model": "claude-sonnet-4-5", "messages": [] }),
+                HeaderMap::new(),
+                Vec::new(),
+            )
+            .await
+        else {
+            panic!("safety net provider should serve the request");
+        };
+        assert_eq!(result.provider.id, "safety");
+        assert!(result.is_safety_net);
+        assert_eq!(status.read().await.safety_net_attempts, 1);
+    }
 }
@@ -0,0 +1,496 @@
+//! Gemini CLI OAuth token 定时刷新
+//!
+//! Gemini CLI 的 OAuth access_token 大约一小时过期。代理接管后如果长时间空闲，
+//! 下一个请求会带着过期的 token 失败。代理运行期间定期检查所有 OAuth 方式的 Gemini 供应商，
+//! 在过期前用保存的 refresh_token 换取新 token 并写回供应商记录。
+//!
+//! 每个供应商按 ID 计算一个固定的错峰偏移，多个供应商不会在同一时刻集中刷新。
+//! API Key 方式的供应商、缺少 refresh_token / client_id / client_secret 的凭证均跳过。
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::providers::{GeminiAdapter, OAuthCredentials};
+use crate::database::Database;
+use crate::provider::Provider;
+
+/// Google OAuth token 端点（凭证中未指定 token_uri 时使用）
+const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// 提前刷新的时间（毫秒）
+const REFRESH_MARGIN_MS: i64 = 5 * 60 * 1000;
+
+/// 错峰窗口（毫秒）：实际刷新时间落在过期前 5～7 分钟之间
+const STAGGER_WINDOW_MS: i64 = 2 * 60 * 1000;
+
+/// 检查间隔
+pub const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 刷新请求超时
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<i64>,
+    refresh_token: Option<String>,
+}
+
+/// 按供应商 ID 计算固定的错峰偏移（FNV-1a），范围 [0, STAGGER_WINDOW_MS)
+fn stagger_offset_ms(provider_id: &str) -> i64 {
+    let hash = provider_id
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    (hash % STAGGER_WINDOW_MS as u64) as i64
+}
+
+/// 判断凭证此刻是否需要刷新
+fn refresh_due(creds: &OAuthCredentials, provider_id: &str, now_ms: i64) -> bool {
+    if !creds.can_refresh() {
+        return false;
+    }
+    if creds.needs_refresh() {
+        return true;
+    }
+    // 未记录过期时间时无法判断，交给请求失败后的正常流程处理
+    creds
+        .expiry_date
+        .is_some_and(|expiry| now_ms >= expiry - REFRESH_MARGIN_MS - stagger_offset_ms(provider_id))
+}
+
+/// 读取 OAuth 方式 Gemini 供应商的原始凭证 JSON
+fn oauth_key(provider: &Provider) -> Option<&str> {
+    provider
+        .settings_config
+        .pointer("/env/GEMINI_API_KEY")
+        .and_then(Value::as_str)
+        .filter(|key| key.starts_with('{'))
+}
+
+/// 用 refresh_token 换取新 token（沿用供应商的代理与 TLS 设置）
+async fn request_refresh(
+    provider: &Provider,
+    creds: &OAuthCredentials,
+) -> Result<TokenResponse, String> {
+    let (Some(refresh_token), Some(client_id), Some(client_secret)) = (
+        creds.refresh_token.as_deref(),
+        creds.client_id.as_deref(),
+        creds.client_secret.as_deref(),
+    ) else {
+        return Err("缺少 refresh_token / client_id / client_secret".to_string());
+    };
+    let token_uri = creds.token_uri.as_deref().unwrap_or(GOOGLE_TOKEN_URL);
+
+    let response = super::http_client::get_for_provider_meta(provider.meta.as_ref())?
+        .post(token_uri)
+        .timeout(REFRESH_TIMEOUT)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("请求 token 端点失败: {e}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("token 端点返回 HTTP {}: {body}", status.as_u16()));
+    }
+    response
+        .json::<TokenResponse>()
+        .await
+        .map_err(|e| format!("解析 token 响应失败: {e}"))
+}
+
+/// 将新 token 写回凭证 JSON，保留其它字段
+fn apply_refresh(key: &str, token: &TokenResponse, now_ms: i64) -> Option<String> {
+    let mut creds: Value = serde_json::from_str(key).ok()?;
+    let object = creds.as_object_mut()?;
+    object.insert(
+        "access_token".to_string(),
+        Value::String(token.access_token.clone()),
+    );
+    if let Some(expires_in) = token.expires_in {
+        object.insert(
+            "expiry_date".to_string(),
+            Value::from(now_ms + expires_in * 1000),
+        );
+    }
+    // Google 通常不会轮换 refresh_token，返回新值时才覆盖
+    if let Some(refresh_token) = &token.refresh_token {
+        object.insert(
+            "refresh_token".to_string(),
+            Value::String(refresh_token.clone()),
+        );
+    }
+    serde_json::to_string(&creds).ok()
+}
+
+/// 刷新所有即将过期的 Gemini OAuth token，返回成功刷新的供应商数量
+///
+/// 单个供应商刷新失败只记录警告，不影响其它供应商。
+/// token 请求可能耗时较长，保存前重新读取供应商，只替换 `GEMINI_API_KEY`，避免覆盖期间的编辑；
+/// 期间凭证已被替换（refresh_token 不同）时丢弃刷新结果。
+pub async fn refresh_expiring_gemini_tokens(db: &Database, now_ms: i64) -> usize {
+    let providers = match db.get_all_providers("gemini") {
+        Ok(providers) => providers,
+        Err(e) => {
+            log::warn!("[GeminiOAuth] 读取 Gemini 供应商失败: {e}");
+            return 0;
+        }
+    };
+
+    let adapter = GeminiAdapter::new();
+    let mut refreshed = 0;
+    for provider in providers.into_values() {
+        let Some(key) = oauth_key(&provider) else {
+            continue;
+        };
+        let Some(creds) = adapter.parse_oauth_credentials(key) else {
+            continue;
+        };
+        if !refresh_due(&creds, &provider.id, now_ms) {
+            continue;
+        }
+
+        let token = match request_refresh(&provider, &creds).await {
+            Ok(token) => token,
+            Err(e) => {
+                log::warn!("[GeminiOAuth] 供应商 {} 刷新 token 失败: {e}", provider.id);
+                continue;
+            }
+        };
+        let mut latest = match db.get_provider_by_id(&provider.id, "gemini") {
+            Ok(Some(latest)) => latest,
+            Ok(None) => continue,
+            Err(e) => {
+                log::warn!("[GeminiOAuth] 重新读取供应商 {} 失败: {e}", provider.id);
+                continue;
+            }
+        };
+        // 期间凭证被改为 API Key 等非 OAuth 形式，或换成了另一组 OAuth 凭证时放弃本次刷新
+        let Some(latest_key) = oauth_key(&latest) else {
+            continue;
+        };
+        let same_credentials = adapter
+            .parse_oauth_credentials(latest_key)
+            .is_some_and(|latest_creds| latest_creds.refresh_token == creds.refresh_token);
+        if !same_credentials {
+            log::info!(
+                "[GeminiOAuth] 供应商 {} 的凭证在刷新期间已更换，丢弃本次刷新结果",
+                provider.id
+            );
+            continue;
+        }
+        let Some(updated_key) = apply_refresh(latest_key, &token, now_ms) else {
+            continue;
+        };
+        latest.settings_config["env"]["GEMINI_API_KEY"] = Value::String(updated_key);
+        match db.save_provider("gemini", &latest) {
+            Ok(()) => {
+                log::info!(
+                    "[GeminiOAuth] 供应商 {} 的 access_token 已刷新",
+                    provider.id
+                );
+                refreshed += 1;
+            }
+            Err(e) => log::warn!("[GeminiOAuth] 保存供应商 {} 失败: {e}", provider.id),
+        }
+    }
+    refreshed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Form, Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const NOW_MS: i64 = 1_700_000_000_000;
+
+    fn creds(expiry_date: Option<i64>) -> OAuthCredentials {
+        OAuthCredentials {
+            access_token: "ya29.old".to_string(),
+            refresh_token: Some("1//refresh".to_string()),
+            client_id: Some("client".to_string()),
+            client_secret: Some("secret".to_string()),
+            expiry_date,
+            token_uri: None,
+        }
+    }
+
+    fn gemini_provider(id: &str, key: Value) -> Provider {
+        let key = match key {
+            Value::String(key) => key,
+            other => other.to_string(),
+        };
+        Provider::with_id(
+            id.to_string(),
+            id.to_string(),
+            json!({
+                "env": {
+                    "GEMINI_API_KEY": key,
+                    "GOOGLE_GEMINI_BASE_URL": "https://generativelanguage.googleapis.com"
+                }
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn refresh_is_due_inside_margin_plus_stagger() {
+        let offset = stagger_offset_ms("p1");
+        assert!((0..STAGGER_WINDOW_MS).contains(&offset));
+        assert_eq!(offset, stagger_offset_ms("p1"), "offset must be stable");
+
+        let expiry = NOW_MS + REFRESH_MARGIN_MS + offset;
+        assert!(refresh_due(&creds(Some(expiry)), "p1", NOW_MS));
+        assert!(!refresh_due(&creds(Some(expiry + 1)), "p1", NOW_MS));
+        // 已过期
+        assert!(refresh_due(&creds(Some(NOW_MS - 1)), "p1", NOW_MS));
+        // 远未过期
+        assert!(!refresh_due(&creds(Some(NOW_MS + 3_600_000)), "p1", NOW_MS));
+    }
+
+    #[test]
+    fn refresh_is_staggered_across_providers() {
+        let offsets: std::collections::HashSet<i64> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| stagger_offset_ms(id))
+            .collect();
+        assert!(
+            offsets.len() > 1,
+            "providers should not share one refresh time"
+        );
+    }
+
+    #[test]
+    fn refresh_requires_refreshable_credentials() {
+        let mut missing_secret = creds(Some(NOW_MS));
+        missing_secret.client_secret = None;
+        assert!(!refresh_due(&missing_secret, "p1", NOW_MS));
+
+        // 没有过期时间且 access_token 仍在：无法判断，不刷新
+        assert!(!refresh_due(&creds(None), "p1", NOW_MS));
+        // 只有 refresh_token：立即刷新
+        let mut empty = creds(None);
+        empty.access_token.clear();
+        assert!(refresh_due(&empty, "p1", NOW_MS));
+    }
+
+    async fn spawn_token_endpoint(hits: Arc<AtomicUsize>) -> String {
+        let router = Router::new().route(
+            "/token",
+            post(move |Form(form): Form<HashMap<String, String>>| {
+                let hits = hits.clone();
+                async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(
+                        form.get("grant_type").map(String::as_str),
+                        Some("refresh_token")
+                    );
+                    assert_eq!(
+                        form.get("refresh_token").map(String::as_str),
+                        Some("1//due")
+                    );
+                    Json(json!({ "access_token": "ya29.new", "expires_in": 3600 }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind token endpoint");
+        let addr = listener.local_addr().expect("token endpoint addr");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.ok();
+        });
+        format!("http://{addr}/token")
+    }
+
+    #[tokio::test]
+    async fn only_expiring_oauth_providers_are_refreshed() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let token_uri = spawn_token_endpoint(hits.clone()).await;
+        let db = Database::memory().expect("init db");
+
+        let oauth = |refresh_token: &str, expiry_date: i64| {
+            json!({
+                "access_token": "ya29.old",
+                "refresh_token": refresh_token,
+                "client_id": "client",
+                "client_secret": "secret",
+                "expiry_date": expiry_date,
+                "token_uri": token_uri
+            })
+        };
+        for provider in [
+            gemini_provider("due", oauth("1//due", NOW_MS + 60_000)),
+            gemini_provider("fresh", oauth("1//fresh", NOW_MS + 3_600_000)),
+            gemini_provider("api-key", json!("AIza-plain-key")),
+        ] {
+            db.save_provider("gemini", &provider)
+                .expect("save provider");
+        }
+
+        let refreshed = refresh_expiring_gemini_tokens(&db, NOW_MS).await;
+
+        assert_eq!(refreshed, 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let due = db
+            .get_provider_by_id("due", "gemini")
+            .expect("load provider")
+            .expect("provider exists");
+        let key = due.settings_config["env"]["GEMINI_API_KEY"]
+            .as_str()
+            .expect("key string");
+        let updated: Value = serde_json::from_str(key).expect("credentials json");
+        assert_eq!(updated["access_token"], "ya29.new");
+        assert_eq!(updated["expiry_date"], NOW_MS + 3_600_000);
+        assert_eq!(updated["refresh_token"], "1//due");
+        assert_eq!(updated["client_id"], "client");
+
+        let fresh = db
+            .get_provider_by_id("fresh", "gemini")
+            .expect("load provider")
+            .expect("provider exists");
+        assert!(fresh.settings_config["env"]["GEMINI_API_KEY"]
+            .as_str()
+            .unwrap()
+            .contains("ya29.old"));
+
+        // 刷新后的过期时间远在未来，再次检查不会重复请求
+        assert_eq!(refresh_expiring_gemini_tokens(&db, NOW_MS).await, 0);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_edits_made_during_token_request() {
+        let db = Arc::new(Database::memory().expect("init db"));
+
+        // token 请求进行期间用户修改了供应商名称与 base_url
+        let editor = db.clone();
+        let router = Router::new().route(
+            "/token",
+            post(move || {
+                let db = editor.clone();
+                async move {
+                    let mut provider = db
+                        .get_provider_by_id("due", "gemini")
+                        .expect("load provider")
+                        .expect("provider exists");
+                    provider.name = "Renamed".to_string();
+                    provider.settings_config["env"]["GOOGLE_GEMINI_BASE_URL"] =
+                        json!("https://relay.example.com");
+                    db.save_provider("gemini", &provider).expect("save edit");
+                    Json(json!({ "access_token": "ya29.new", "expires_in": 3600 }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind token endpoint");
+        let addr = listener.local_addr().expect("token endpoint addr");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.ok();
+        });
+
+        let key = json!({
+            "access_token": "ya29.old",
+            "refresh_token": "1//due",
+            "client_id": "client",
+            "client_secret": "secret",
+            "expiry_date": NOW_MS + 60_000,
+            "token_uri": format!("http://{addr}/token")
+        });
+        db.save_provider("gemini", &gemini_provider("due", key))
+            .expect("save provider");
+
+        assert_eq!(refresh_expiring_gemini_tokens(&db, NOW_MS).await, 1);
+
+        let due = db
+            .get_provider_by_id("due", "gemini")
+            .expect("load provider")
+            .expect("provider exists");
+        assert_eq!(due.name, "Renamed");
+        assert_eq!(
+            due.settings_config["env"]["GOOGLE_GEMINI_BASE_URL"],
+            "https://relay.example.com"
+        );
+        assert!(due.settings_config["env"]["GEMINI_API_KEY"]
+            .as_str()
+            .unwrap()
+            .contains("ya29.new"));
+    }
+
+    #[tokio::test]
+    async fn refresh_is_dropped_when_credentials_change_during_request() {
+        let db = Arc::new(Database::memory().expect("init db"));
+
+        // token 请求进行期间用户重新登录，换成了另一组 OAuth 凭证
+        let editor = db.clone();
+        let router = Router::new().route(
+            "/token",
+            post(move || {
+                let db = editor.clone();
+                async move {
+                    let mut provider = db
+                        .get_provider_by_id("due", "gemini")
+                        .expect("load provider")
+                        .expect("provider exists");
+                    let relogin = json!({
+                        "access_token": "ya29.relogin",
+                        "refresh_token": "1//relogin",
+                        "client_id": "client",
+                        "client_secret": "secret",
+                        "expiry_date": NOW_MS + 3_600_000
+                    });
+                    provider.settings_config["env"]["GEMINI_API_KEY"] = json!(relogin.to_string());
+                    db.save_provider("gemini", &provider).expect("save edit");
+                    Json(json!({ "access_token": "ya29.new", "expires_in": 3600 }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind token endpoint");
+        let addr = listener.local_addr().expect("token endpoint addr");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.ok();
+        });
+
+        let key = json!({
+            "access_token": "ya29.old",
+            "refresh_token": "1//due",
+            "client_id": "client",
+            "client_secret": "secret",
+            "expiry_date": NOW_MS + 60_000,
+            "token_uri": format!("http://{addr}/token")
+        });
+        db.save_provider("gemini", &gemini_provider("due", key))
+            .expect("save provider");
+
+        assert_eq!(refresh_expiring_gemini_tokens(&db, NOW_MS).await, 0);
+
+        let due = db
+            .get_provider_by_id("due", "gemini")
+            .expect("load provider")
+            .expect("provider exists");
+        let key = due.settings_config["env"]["GEMINI_API_KEY"]
+            .as_str()
+            .expect("key string");
+        let stored: Value = serde_json::from_str(key).expect("credentials json");
+        assert_eq!(stored["access_token"], "ya29.relogin");
+        assert_eq!(stored["refresh_token"], "1//relogin");
+    }
+}
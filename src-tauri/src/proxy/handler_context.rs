@@ -7,13 +7,19 @@ use crate::provider::Provider;
 use crate::proxy::{
     extract_session_id,
     forwarder::RequestForwarder,
+    provider_router::GROUP_HEADER,
+    response_processor::SseLogMode,
     server::ProxyState,
+    shadow::ShadowSink,
     types::{AppProxyConfig, OptimizerConfig, RectifierConfig},
     ProxyError,
 };
 use axum::http::HeaderMap;
 use std::time::Instant;
 
+/// 代理开销测量请求的标记头：这类请求不记录日志、不计费，也不镜像到影子供应商
+pub const BENCHMARK_HEADER: &str = "x-cc-benchmark";
+
 /// 流式超时配置
 #[derive(Debug, Clone, Copy)]
 pub struct StreamingTimeoutConfig {
@@ -61,6 +67,20 @@ pub struct RequestContext {
     pub rectifier_config: RectifierConfig,
     /// 优化器配置
     pub optimizer_config: OptimizerConfig,
+    /// 是否在调试日志中脱敏 SSE 消息内容
+    pub redact_sse_logs: bool,
+    /// 轻量流式模式（不逐条记录 SSE 事件）
+    pub lightweight_streaming: bool,
+    /// 代理全局的请求日志开关（供应商未单独设置时生效）
+    pub global_logging: bool,
+    /// 是否为代理开销测量请求（带 [`BENCHMARK_HEADER`]）
+    pub benchmark: bool,
+    /// 转发成功时使用的 Key 池序号（供应商未配置 Key 池时为 None）
+    pub api_key_index: Option<usize>,
+    /// 是否由兜底供应商完成转发
+    pub is_safety_net: bool,
+    /// 供应商输出 token 上限生效后的最大输出 token 数
+    pub effective_max_tokens: Option<u64>,
 }
 
 impl RequestContext {
@@ -69,7 +89,7 @@ impl RequestContext {
     /// # Arguments
     /// * `state` - 代理服务器状态
     /// * `body` - 请求体 JSON
-    /// * `headers` - 请求头（用于提取 Session ID）
+    /// * `headers` - 请求头（用于提取 Session ID 和故障转移分组）
     /// * `app_type` - 应用类型
     /// * `tag` - 日志标签
     /// * `app_type_str` - 应用类型字符串
@@ -96,9 +116,19 @@ impl RequestContext {
         // 从数据库读取整流器配置
         let rectifier_config = state.db.get_rectifier_config().unwrap_or_default();
         let optimizer_config = state.db.get_optimizer_config().unwrap_or_default();
+        let redact_sse_logs = state
+            .db
+            .get_log_config()
+            .map(|config| config.redact_sse_content)
+            .unwrap_or(false);
+        let (lightweight_streaming, global_logging) = {
+            let config = state.config.read().await;
+            (config.lightweight_streaming, config.enable_logging)
+        };
 
         let current_provider_id =
             crate::settings::get_current_provider(&app_type).unwrap_or_default();
+        let benchmark = headers.contains_key(BENCHMARK_HEADER);
 
         // 从请求体提取模型名称
         let request_model = body
@@ -121,22 +151,35 @@ impl RequestContext {
 
         // 使用共享的 ProviderRouter 选择 Provider（熔断器状态跨请求保持）
         // 注意：只在这里调用一次，结果传递给 forwarder，避免重复消耗 HalfOpen 名额
-        let providers = state
+        let requested_group = headers
+            .get(GROUP_HEADER)
+            .and_then(|value| value.to_str().ok());
+        let (provider, providers) = match state
             .provider_router
-            .select_providers(app_type_str)
+            .select_providers_for_group(app_type_str, requested_group)
             .await
-            .map_err(|e| match e {
-                crate::error::AppError::AllProvidersCircuitOpen => {
-                    ProxyError::AllProvidersCircuitOpen
-                }
-                crate::error::AppError::NoProvidersConfigured => ProxyError::NoProvidersConfigured,
-                _ => ProxyError::DatabaseError(e.to_string()),
-            })?;
-
-        let provider = providers
-            .first()
-            .cloned()
-            .ok_or(ProxyError::NoAvailableProvider)?;
+        {
+            Ok(providers) => {
+                let provider = providers
+                    .first()
+                    .cloned()
+                    .ok_or(ProxyError::NoAvailableProvider)?;
+                (provider, providers)
+            }
+            // 全部熔断时交给兜底供应商：候选列表为空，转发器直接进入兜底尝试
+            Err(crate::error::AppError::AllProvidersCircuitOpen) => {
+                let safety = state
+                    .provider_router
+                    .safety_net_fallback(app_type_str)
+                    .await
+                    .ok_or(ProxyError::AllProvidersCircuitOpen)?;
+                (safety, Vec::new())
+            }
+            Err(crate::error::AppError::NoProvidersConfigured) => {
+                return Err(ProxyError::NoProvidersConfigured)
+            }
+            Err(e) => return Err(ProxyError::DatabaseError(e.to_string())),
+        };
 
         log::debug!(
             "[{}] Provider: {}, model: {}, failover chain: {} providers, session: {}",
@@ -160,9 +203,38 @@ impl RequestContext {
             session_id,
             rectifier_config,
             optimizer_config,
+            redact_sse_logs,
+            lightweight_streaming,
+            global_logging,
+            benchmark,
+            api_key_index: None,
+            is_safety_net: false,
+            effective_max_tokens: None,
         })
     }
 
+    /// 当前供应商是否记录请求日志（供应商设置优先于全局 `enable_logging`，测量请求从不记录）
+    pub fn logging_enabled(&self) -> bool {
+        !self.benchmark
+            && self
+                .provider
+                .meta
+                .as_ref()
+                .and_then(|meta| meta.enable_request_logging)
+                .unwrap_or(self.global_logging)
+    }
+
+    /// 透传流的 SSE 日志方式（关闭日志或轻量模式时只收集用量，其次是脱敏）
+    pub fn sse_log_mode(&self) -> SseLogMode {
+        if self.lightweight_streaming || !self.logging_enabled() {
+            SseLogMode::UsageOnly
+        } else if self.redact_sse_logs {
+            SseLogMode::Redacted
+        } else {
+            SseLogMode::Full
+        }
+    }
+
     /// 从 URI 提取模型名称（Gemini 专用）
     ///
     /// Gemini API 的模型名称在 URI 中，格式如：
@@ -209,7 +281,7 @@ impl RequestContext {
                 (0, 0, 0)
             };
 
-        RequestForwarder::new(
+        let forwarder = RequestForwarder::new(
             state.provider_router.clone(),
             non_streaming_timeout,
             state.status.clone(),
@@ -221,7 +293,12 @@ impl RequestContext {
             idle_timeout,
             self.rectifier_config.clone(),
             self.optimizer_config.clone(),
-        )
+        );
+        if self.benchmark {
+            forwarder
+        } else {
+            forwarder.with_shadow(ShadowSink::from_state(state, self.global_logging))
+        }
     }
 
     /// 获取 Provider 列表（用于故障转移）
@@ -8,20 +8,27 @@
 //! - Claude 的格式转换逻辑保留在此文件（用于 OpenRouter 旧接口回退）
 
 use super::{
+    app_detect,
     error_mapper::{get_error_message, map_proxy_error_to_status},
     handler_config::{
         CLAUDE_PARSER_CONFIG, CODEX_PARSER_CONFIG, GEMINI_PARSER_CONFIG, OPENAI_PARSER_CONFIG,
     },
     handler_context::RequestContext,
     providers::{
-        get_adapter, get_claude_api_format, streaming::create_anthropic_sse_stream,
-        streaming_responses::create_anthropic_sse_stream_from_responses, transform,
-        transform_responses,
+        codex_bridge::{get_codex_bridge, CodexBridge},
+        get_adapter, get_claude_api_format,
+        streaming::create_anthropic_sse_stream,
+        streaming_responses::create_anthropic_sse_stream_from_responses,
+        transform, transform_responses,
+    },
+    response_cache,
+    response_processor::{
+        create_logged_passthrough_stream, handle_raw_passthrough, is_sse_response,
+        process_response, spawn_log_cached_hit, SseUsageCollector,
     },
-    response_processor::{create_logged_passthrough_stream, process_response, SseUsageCollector},
     server::ProxyState,
     types::*,
-    usage::parser::TokenUsage,
+    usage::{logger::CLIENT_ABORTED_STATUS, parser::TokenUsage},
     ProxyError,
 };
 use crate::app_config::AppType;
@@ -72,6 +79,11 @@ pub async fn handle_messages(
         .and_then(|s| s.as_bool())
         .unwrap_or(false);
 
+    let cache_key = response_cache_key(&state, &ctx, "/v1/messages", &body).await;
+    if let Some(response) = cached_hit(&state, &ctx, cache_key.as_deref()) {
+        return Ok(response);
+    }
+
     // 转发请求
     let forwarder = ctx.create_forwarder(&state);
     let result = match forwarder
@@ -95,6 +107,12 @@ pub async fn handle_messages(
     };
 
     ctx.provider = result.provider;
+    ctx.api_key_index = result.api_key_index;
+    ctx.is_safety_net = result.is_safety_net;
+    ctx.effective_max_tokens = result.effective_max_tokens;
+    if ctx.provider.is_raw_passthrough() {
+        return Ok(handle_raw_passthrough(result.response, &ctx, &state));
+    }
     let response = result.response;
 
     // 检查是否需要格式转换（OpenRouter 等中转服务）
@@ -103,11 +121,13 @@ pub async fn handle_messages(
 
     // Claude 特有：格式转换处理
     if needs_transform {
-        return handle_claude_transform(response, &ctx, &state, &body, is_stream).await;
+        let result = handle_claude_transform(response, &ctx, &state, &body, is_stream).await;
+        return store_response(&state, cache_key, result).await;
     }
 
     // 通用响应处理（透传模式）
-    process_response(response, &ctx, &state, &CLAUDE_PARSER_CONFIG).await
+    let result = process_response(response, &ctx, &state, &CLAUDE_PARSER_CONFIG).await;
+    store_response(&state, cache_key, result).await
 }
 
 /// Claude 格式转换处理（独有逻辑）
@@ -141,8 +161,20 @@ async fn handle_claude_transform(
             let model = ctx.request_model.clone();
             let status_code = status.as_u16();
             let start_time = ctx.start_time;
-
-            SseUsageCollector::new(start_time, move |events, first_token_ms| {
+            let logging_enabled = ctx.logging_enabled();
+            let api_key_index = ctx.api_key_index;
+            let is_safety_net = ctx.is_safety_net;
+            let effective_max_tokens = ctx.effective_max_tokens;
+
+            SseUsageCollector::new(start_time, move |events, first_token_ms, aborted| {
+                if !logging_enabled {
+                    return;
+                }
+                let status_code = if aborted {
+                    CLIENT_ABORTED_STATUS
+                } else {
+                    status_code
+                };
                 if let Some(usage) = TokenUsage::from_claude_stream_events(&events) {
                     let latency_ms = start_time.elapsed().as_millis() as u64;
                     let state = state.clone();
@@ -161,6 +193,9 @@ async fn handle_claude_transform(
                             first_token_ms,
                             true,
                             status_code,
+                            api_key_index,
+                            is_safety_net,
+                            effective_max_tokens,
                         )
                         .await;
                     });
@@ -178,6 +213,7 @@ async fn handle_claude_transform(
             "Claude/OpenRouter",
             Some(usage_collector),
             timeout_config,
+            ctx.sse_log_mode(),
         );
 
         let mut headers = axum::http::HeaderMap::new();
@@ -224,8 +260,10 @@ async fn handle_claude_transform(
         e
     })?;
 
-    // 记录使用量
-    if let Some(usage) = TokenUsage::from_claude_response(&anthropic_response) {
+    // 记录使用量（供应商关闭日志时跳过）
+    if let Some(usage) =
+        TokenUsage::from_claude_response(&anthropic_response).filter(|_| ctx.logging_enabled())
+    {
         let model = anthropic_response
             .get("model")
             .and_then(|m| m.as_str())
@@ -233,6 +271,9 @@ async fn handle_claude_transform(
         let latency_ms = ctx.latency_ms();
 
         let request_model = ctx.request_model.clone();
+        let api_key_index = ctx.api_key_index;
+        let is_safety_net = ctx.is_safety_net;
+        let effective_max_tokens = ctx.effective_max_tokens;
         tokio::spawn({
             let state = state.clone();
             let provider_id = ctx.provider.id.clone();
@@ -249,6 +290,9 @@ async fn handle_claude_transform(
                     None,
                     false,
                     status.as_u16(),
+                    api_key_index,
+                    is_safety_net,
+                    effective_max_tokens,
                 )
                 .await;
             }
@@ -284,29 +328,56 @@ async fn handle_claude_transform(
 // Codex API 处理器
 // ============================================================================
 
-/// 处理 /v1/chat/completions 请求（OpenAI Chat Completions API - Codex CLI）
+/// 处理 /v1/chat/completions 请求（OpenAI Chat Completions API）
+///
+/// 该路径 Codex 与 Claude 兼容中转客户端共用，按 [`app_detect::detect_app_type`] 选择应用。
 pub async fn handle_chat_completions(
     State(state): State<ProxyState>,
     headers: axum::http::HeaderMap,
     Json(body): Json<Value>,
 ) -> Result<axum::response::Response, ProxyError> {
+    let default_app = state.config.read().await.target_app.clone();
+    let app_type = app_detect::detect_app_type(&headers, &default_app);
+    forward_chat_completions(state, headers, body, app_type).await
+}
+
+/// 处理 /codex/v1/chat/completions 请求（显式带 Codex 前缀，不做应用识别）
+pub async fn handle_codex_chat_completions(
+    State(state): State<ProxyState>,
+    headers: axum::http::HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<axum::response::Response, ProxyError> {
+    forward_chat_completions(state, headers, body, AppType::Codex).await
+}
+
+async fn forward_chat_completions(
+    state: ProxyState,
+    headers: axum::http::HeaderMap,
+    body: Value,
+    app_type: AppType,
+) -> Result<axum::response::Response, ProxyError> {
+    // Claude 供应商的 base_url 不含版本段，需要带上 /v1
+    let (tag, app_type_str, endpoint) = match app_type {
+        AppType::Claude => ("Claude", "claude", "/v1/chat/completions"),
+        _ => ("Codex", "codex", "/chat/completions"),
+    };
+
     let mut ctx =
-        RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex").await?;
+        RequestContext::new(&state, &body, &headers, app_type.clone(), tag, app_type_str).await?;
 
     let is_stream = body
         .get("stream")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let cache_key = response_cache_key(&state, &ctx, endpoint, &body).await;
+    if let Some(response) = cached_hit(&state, &ctx, cache_key.as_deref()) {
+        return Ok(response);
+    }
+
     let forwarder = ctx.create_forwarder(&state);
     let result = match forwarder
-        .forward_with_retry(
-            &AppType::Codex,
-            "/chat/completions",
-            body,
-            headers,
-            ctx.get_providers(),
-        )
+        .forward_with_retry(&app_type, endpoint, body, headers, ctx.get_providers())
         .await
     {
         Ok(result) => result,
@@ -320,9 +391,23 @@ pub async fn handle_chat_completions(
     };
 
     ctx.provider = result.provider;
-    let response = result.response;
+    ctx.api_key_index = result.api_key_index;
+    ctx.is_safety_net = result.is_safety_net;
+    ctx.effective_max_tokens = result.effective_max_tokens;
+    if ctx.provider.is_raw_passthrough() {
+        return Ok(handle_raw_passthrough(result.response, &ctx, &state));
+    }
+    let mut response = result.response;
+
+    // 上游仅支持 Responses API 时，将响应转换回 Chat Completions 格式
+    if app_type == AppType::Codex {
+        if let Some(bridge) = get_codex_bridge(&ctx.provider, "/chat/completions") {
+            response = bridge_codex_response(response, bridge).await?;
+        }
+    }
 
-    process_response(response, &ctx, &state, &OPENAI_PARSER_CONFIG).await
+    let result = process_response(response, &ctx, &state, &OPENAI_PARSER_CONFIG).await;
+    store_response(&state, cache_key, result).await
 }
 
 /// 处理 /v1/responses 请求（OpenAI Responses API - Codex CLI 透传）
@@ -339,6 +424,11 @@ pub async fn handle_responses(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let cache_key = response_cache_key(&state, &ctx, "/responses", &body).await;
+    if let Some(response) = cached_hit(&state, &ctx, cache_key.as_deref()) {
+        return Ok(response);
+    }
+
     let forwarder = ctx.create_forwarder(&state);
     let result = match forwarder
         .forward_with_retry(
@@ -361,9 +451,21 @@ pub async fn handle_responses(
     };
 
     ctx.provider = result.provider;
-    let response = result.response;
+    ctx.api_key_index = result.api_key_index;
+    ctx.is_safety_net = result.is_safety_net;
+    ctx.effective_max_tokens = result.effective_max_tokens;
+    if ctx.provider.is_raw_passthrough() {
+        return Ok(handle_raw_passthrough(result.response, &ctx, &state));
+    }
+    let mut response = result.response;
 
-    process_response(response, &ctx, &state, &CODEX_PARSER_CONFIG).await
+    // 上游仅支持 Chat Completions 时，将响应转换回 Responses API 格式
+    if let Some(bridge) = get_codex_bridge(&ctx.provider, "/responses") {
+        response = bridge_codex_response(response, bridge).await?;
+    }
+
+    let result = process_response(response, &ctx, &state, &CODEX_PARSER_CONFIG).await;
+    store_response(&state, cache_key, result).await
 }
 
 /// 处理 /v1/responses/compact 请求（OpenAI Responses Compact API - Codex CLI 透传）
@@ -380,6 +482,11 @@ pub async fn handle_responses_compact(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let cache_key = response_cache_key(&state, &ctx, "/responses/compact", &body).await;
+    if let Some(response) = cached_hit(&state, &ctx, cache_key.as_deref()) {
+        return Ok(response);
+    }
+
     let forwarder = ctx.create_forwarder(&state);
     let result = match forwarder
         .forward_with_retry(
@@ -402,9 +509,62 @@ pub async fn handle_responses_compact(
     };
 
     ctx.provider = result.provider;
+    ctx.api_key_index = result.api_key_index;
+    ctx.is_safety_net = result.is_safety_net;
+    ctx.effective_max_tokens = result.effective_max_tokens;
+    if ctx.provider.is_raw_passthrough() {
+        return Ok(handle_raw_passthrough(result.response, &ctx, &state));
+    }
     let response = result.response;
 
-    process_response(response, &ctx, &state, &CODEX_PARSER_CONFIG).await
+    let result = process_response(response, &ctx, &state, &CODEX_PARSER_CONFIG).await;
+    store_response(&state, cache_key, result).await
+}
+
+/// 将 Codex 上游响应转换为客户端使用的协议格式
+///
+/// 转换后重新包装为 `reqwest::Response`，以便复用通用的响应处理与用量记录。
+/// 上游错误响应保持原样透传。
+async fn bridge_codex_response(
+    response: reqwest::Response,
+    bridge: CodexBridge,
+) -> Result<reqwest::Response, ProxyError> {
+    let status = response.status();
+    if !status.is_success() {
+        return Ok(response);
+    }
+
+    let mut builder = axum::http::Response::builder().status(status);
+    for (key, value) in response.headers().iter() {
+        if key != axum::http::header::CONTENT_LENGTH && key != axum::http::header::TRANSFER_ENCODING
+        {
+            builder = builder.header(key, value);
+        }
+    }
+
+    let body = if is_sse_response(&response) {
+        reqwest::Body::wrap_stream(bridge.transform_stream(response.bytes_stream()))
+    } else {
+        let body_bytes = response.bytes().await.map_err(|e| {
+            log::error!("[Codex] 读取响应体失败: {e}");
+            ProxyError::ForwardFailed(format!("Failed to read response body: {e}"))
+        })?;
+        let upstream_response: Value = serde_json::from_slice(&body_bytes).map_err(|e| {
+            log::error!("[Codex] 解析上游响应失败: {e}");
+            ProxyError::TransformError(format!("Failed to parse upstream response: {e}"))
+        })?;
+        let converted = bridge.transform_response(upstream_response)?;
+        let converted_bytes = serde_json::to_vec(&converted).map_err(|e| {
+            ProxyError::TransformError(format!("Failed to serialize response: {e}"))
+        })?;
+        reqwest::Body::from(converted_bytes)
+    };
+
+    let bridged = builder.body(body).map_err(|e| {
+        log::error!("[Codex] 构建桥接响应失败: {e}");
+        ProxyError::Internal(format!("Failed to build response: {e}"))
+    })?;
+    Ok(reqwest::Response::from(bridged))
 }
 
 // ============================================================================
@@ -434,6 +594,16 @@ pub async fn handle_gemini(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    // Gemini 流式请求通过端点区分（:streamGenerateContent）
+    let cache_key = if endpoint.contains("streamGenerateContent") {
+        None
+    } else {
+        response_cache_key(&state, &ctx, endpoint, &body).await
+    };
+    if let Some(response) = cached_hit(&state, &ctx, cache_key.as_deref()) {
+        return Ok(response);
+    }
+
     let forwarder = ctx.create_forwarder(&state);
     let result = match forwarder
         .forward_with_retry(
@@ -456,15 +626,64 @@ pub async fn handle_gemini(
     };
 
     ctx.provider = result.provider;
+    ctx.api_key_index = result.api_key_index;
+    ctx.is_safety_net = result.is_safety_net;
+    ctx.effective_max_tokens = result.effective_max_tokens;
+    if ctx.provider.is_raw_passthrough() {
+        return Ok(handle_raw_passthrough(result.response, &ctx, &state));
+    }
     let response = result.response;
 
-    process_response(response, &ctx, &state, &GEMINI_PARSER_CONFIG).await
+    let result = process_response(response, &ctx, &state, &GEMINI_PARSER_CONFIG).await;
+    store_response(&state, cache_key, result).await
 }
 
 // ============================================================================
 // 使用量记录（保留用于 Claude 转换逻辑）
 // ============================================================================
 
+/// 计算响应缓存指纹（未开启缓存或流式请求时返回 None）
+async fn response_cache_key(
+    state: &ProxyState,
+    ctx: &RequestContext,
+    endpoint: &str,
+    body: &Value,
+) -> Option<String> {
+    if !state.config.read().await.enable_response_cache {
+        return None;
+    }
+    response_cache::request_key(&ctx.provider.id, endpoint, &ctx.request_model, body)
+}
+
+/// 查找缓存响应，命中时记录一条零费用的缓存日志
+fn cached_hit(
+    state: &ProxyState,
+    ctx: &RequestContext,
+    key: Option<&str>,
+) -> Option<axum::response::Response> {
+    let response = state.response_cache.lookup(key?)?;
+    log::info!(
+        "[{}] 响应缓存命中，跳过上游请求 (provider={}, model={})",
+        ctx.tag,
+        ctx.provider.id,
+        ctx.request_model
+    );
+    spawn_log_cached_hit(state, ctx, response.status().as_u16());
+    Some(response)
+}
+
+/// 将成功的响应写入缓存
+async fn store_response(
+    state: &ProxyState,
+    key: Option<String>,
+    result: Result<axum::response::Response, ProxyError>,
+) -> Result<axum::response::Response, ProxyError> {
+    match key {
+        Some(key) => state.response_cache.store(&key, result?).await,
+        None => result,
+    }
+}
+
 fn log_forward_error(
     state: &ProxyState,
     ctx: &RequestContext,
@@ -473,7 +692,15 @@ fn log_forward_error(
 ) {
     use super::usage::logger::UsageLogger;
 
-    let logger = UsageLogger::new(&state.db);
+    if state.logging_paused.is_paused() || ctx.benchmark {
+        return;
+    }
+
+    let logger = UsageLogger::new(&state.db)
+        .with_writer(&state.usage_writer)
+        .with_api_key_index(ctx.api_key_index)
+        .with_safety_net(ctx.is_safety_net)
+        .with_effective_max_tokens(ctx.effective_max_tokens);
     let status_code = map_proxy_error_to_status(error);
     let error_message = get_error_message(error);
     let request_id = uuid::Uuid::new_v4().to_string();
@@ -507,10 +734,22 @@ async fn log_usage(
     first_token_ms: Option<u64>,
     is_streaming: bool,
     status_code: u16,
+    api_key_index: Option<usize>,
+    is_safety_net: bool,
+    effective_max_tokens: Option<u64>,
 ) {
+    use super::request_feed::{publish_logged, RequestLogSummary};
     use super::usage::logger::UsageLogger;
 
-    let logger = UsageLogger::new(&state.db);
+    if state.logging_paused.is_paused() {
+        return;
+    }
+
+    let logger = UsageLogger::new(&state.db)
+        .with_writer(&state.usage_writer)
+        .with_api_key_index(api_key_index)
+        .with_safety_net(is_safety_net)
+        .with_effective_max_tokens(effective_max_tokens);
 
     let (multiplier, pricing_model_source) =
         logger.resolve_pricing_config(provider_id, app_type).await;
@@ -521,6 +760,17 @@ async fn log_usage(
     };
 
     let request_id = uuid::Uuid::new_v4().to_string();
+    let summary = RequestLogSummary {
+        request_id: request_id.clone(),
+        provider_id: provider_id.to_string(),
+        app_type: app_type.to_string(),
+        model: model.to_string(),
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        status_code,
+        latency_ms,
+        is_streaming,
+    };
 
     if let Err(e) = logger.log_with_calculation(
         request_id,
@@ -539,5 +789,8 @@ async fn log_usage(
         is_streaming,
     ) {
         log::warn!("[USG-001] 记录使用量失败: {e}");
+        return;
     }
+
+    publish_logged(state, summary).await;
 }
@@ -3,9 +3,11 @@
 //! 提供支持全局代理配置的 HTTP 客户端。
 //! 所有需要发送 HTTP 请求的模块都应使用此模块提供的客户端。
 
-use crate::provider::ProviderProxyConfig;
+use super::provider_tls::ProviderTls;
+use crate::provider::{ProviderMeta, ProviderProxyConfig};
 use once_cell::sync::OnceCell;
-use reqwest::Client;
+use reqwest::{Client, ClientBuilder};
+use std::collections::HashMap;
 use std::env;
 use std::net::IpAddr;
 use std::sync::RwLock;
@@ -17,6 +19,10 @@ static GLOBAL_CLIENT: OnceCell<RwLock<Client>> = OnceCell::new();
 /// 当前代理 URL（用于日志和状态查询）
 static CURRENT_PROXY_URL: OnceCell<RwLock<Option<String>>> = OnceCell::new();
 
+/// 带 TLS 要求的专用客户端缓存，按（TLS 要求, 代理 URL）复用连接池
+static TLS_CLIENTS: OnceCell<RwLock<HashMap<(ProviderTls, Option<String>), Client>>> =
+    OnceCell::new();
+
 /// CC Switch 代理服务器当前监听的端口
 static CC_SWITCH_PROXY_PORT: OnceCell<RwLock<u16>> = OnceCell::new();
 
@@ -215,12 +221,28 @@ pub fn is_proxy_enabled() -> bool {
 
 /// 构建 HTTP 客户端
 fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
-    let mut builder = Client::builder()
+    configure_proxy(base_builder(), proxy_url)?
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+}
+
+fn base_builder() -> ClientBuilder {
+    // 显式开启 gzip/deflate/br 自动解压：上游返回压缩响应体时，
+    // 代理拿到的是解压后的明文（Content-Encoding 头会被移除），用量解析不受影响
+    Client::builder()
         .timeout(Duration::from_secs(600))
         .connect_timeout(Duration::from_secs(30))
         .pool_max_idle_per_host(10)
-        .tcp_keepalive(Duration::from_secs(60));
+        .tcp_keepalive(Duration::from_secs(60))
+        .gzip(true)
+        .deflate(true)
+        .brotli(true)
+}
 
+fn configure_proxy(
+    mut builder: ClientBuilder,
+    proxy_url: Option<&str>,
+) -> Result<ClientBuilder, String> {
     // 有代理地址则使用代理，否则跟随系统代理
     if let Some(url) = proxy_url {
         // 先验证 URL 格式和 scheme
@@ -253,9 +275,7 @@ fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
         }
     }
 
-    builder
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+    Ok(builder)
 }
 
 fn system_proxy_points_to_loopback() -> bool {
@@ -387,6 +407,9 @@ pub fn build_client_for_provider(proxy_config: Option<&ProviderProxyConfig>) ->
         .connect_timeout(Duration::from_secs(30))
         .pool_max_idle_per_host(10)
         .tcp_keepalive(Duration::from_secs(60))
+        .gzip(true)
+        .deflate(true)
+        .brotli(true)
         .proxy(proxy)
         .build()
     {
@@ -423,6 +446,55 @@ pub fn get_for_provider(proxy_config: Option<&ProviderProxyConfig>) -> Client {
     get()
 }
 
+/// 按供应商 meta 获取 HTTP 客户端
+///
+/// 配置了最低 TLS 版本或证书指纹时使用专用客户端，按（TLS 要求, 代理 URL）缓存复用，
+/// 代理沿用供应商单独代理或全局代理；均未配置时等同于 [`get_for_provider`]。
+pub fn get_for_provider_meta(meta: Option<&ProviderMeta>) -> Result<Client, String> {
+    let proxy_config = meta.and_then(|m| m.proxy_config.as_ref());
+    let Some(tls) = ProviderTls::from_meta(meta)? else {
+        return Ok(get_for_provider(proxy_config));
+    };
+
+    let proxy_url = proxy_config
+        .filter(|c| c.enabled)
+        .and_then(build_proxy_url_from_config)
+        .or_else(get_current_proxy_url);
+    let key = (tls, proxy_url);
+    let cache = TLS_CLIENTS.get_or_init(|| RwLock::new(HashMap::new()));
+    if let Some(client) = cache
+        .read()
+        .ok()
+        .and_then(|clients| clients.get(&key).cloned())
+    {
+        return Ok(client);
+    }
+
+    let client = tls
+        .apply(configure_proxy(base_builder(), key.1.as_deref())?)?
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+    if let Ok(mut clients) = cache.write() {
+        clients.entry(key).or_insert_with(|| client.clone());
+    }
+    Ok(client)
+}
+
+/// 展开错误链（reqwest 的顶层错误不含 TLS 握手失败等底层原因）
+pub fn describe_error(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        let text = cause.to_string();
+        if !message.contains(&text) {
+            message.push_str(": ");
+            message.push_str(&text);
+        }
+        source = cause.source();
+    }
+    message
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,6 +505,38 @@ mod tests {
         LOCK.get_or_init(|| Mutex::new(()))
     }
 
+    #[test]
+    fn tls_clients_are_cached_per_requirement() {
+        let meta = |min_version: &str| ProviderMeta {
+            tls_min_version: Some(min_version.to_string()),
+            pinned_cert_sha256: Some("5e".repeat(32)),
+            ..Default::default()
+        };
+        let cached = |min_version: &str| {
+            let tls = ProviderTls::from_meta(Some(&meta(min_version)))
+                .unwrap()
+                .unwrap();
+            TLS_CLIENTS
+                .get()
+                .map(|cache| {
+                    cache
+                        .read()
+                        .unwrap()
+                        .keys()
+                        .filter(|(key, _)| *key == tls)
+                        .count()
+                })
+                .unwrap_or(0)
+        };
+
+        get_for_provider_meta(Some(&meta("1.2"))).expect("build client");
+        get_for_provider_meta(Some(&meta("1.2"))).expect("reuse client");
+        assert_eq!(cached("1.2"), 1);
+
+        get_for_provider_meta(Some(&meta("1.3"))).expect("build second client");
+        assert_eq!(cached("1.3"), 1);
+    }
+
     #[test]
     fn test_mask_url() {
         assert_eq!(mask_url("http://127.0.0.1:7890"), "http://127.0.0.1:7890");
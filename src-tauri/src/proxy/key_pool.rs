@@ -0,0 +1,80 @@
+//! Gemini API Key 池
+//!
+//! 免费层 Gemini Key 的配额按 Key 计算，很快就会用完。供应商可在 `meta.apiKeyPool` 中配置多个 Key，
+//! 转发时从上次可用的 Key 开始发送；遇到限流/配额错误（429 或 RESOURCE_EXHAUSTED）时轮换到下一个 Key，
+//! 之后一直沿用这个可用的 Key，直到它也触发配额错误。日志只记录 Key 的序号，不输出 Key 本身。
+
+use super::error::ProxyError;
+use crate::provider::Provider;
+
+/// 读取供应商配置的 Key 池（去除空白和重复项）
+pub fn provider_keys(provider: &Provider) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for key in provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.api_key_pool.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        let key = key.trim();
+        if !key.is_empty() && !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// 是否为应切换 Key 的限流/配额错误
+pub fn is_quota_error(error: &ProxyError) -> bool {
+    match error {
+        ProxyError::UpstreamError { status: 429, .. } => true,
+        ProxyError::UpstreamError {
+            status,
+            body: Some(body),
+        } if (400..500).contains(status) => {
+            body.contains("RESOURCE_EXHAUSTED") || body.to_ascii_lowercase().contains("quota")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    #[test]
+    fn pool_keys_are_trimmed_and_deduplicated() {
+        let mut provider = Provider::with_id("g".to_string(), "G".to_string(), json!({}), None);
+        assert!(provider_keys(&provider).is_empty());
+
+        provider.meta = Some(ProviderMeta {
+            api_key_pool: Some(vec![
+                " key-a ".to_string(),
+                String::new(),
+                "key-b".to_string(),
+                "key-a".to_string(),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(provider_keys(&provider), vec!["key-a", "key-b"]);
+    }
+
+    #[test]
+    fn quota_errors_are_detected() {
+        let upstream = |status: u16, body: &str| ProxyError::UpstreamError {
+            status,
+            body: Some(body.to_string()),
+        };
+        assert!(is_quota_error(&upstream(429, "")));
+        assert!(is_quota_error(&upstream(
+            403,
+            r#"{"error":{"status":"RESOURCE_EXHAUSTED"}}"#
+        )));
+        assert!(!is_quota_error(&upstream(400, "invalid argument")));
+        assert!(!is_quota_error(&upstream(500, "quota backend down")));
+        assert!(!is_quota_error(&ProxyError::Timeout("slow".to_string())));
+    }
+}
@@ -33,6 +33,9 @@ pub mod fwd {
     pub const PROVIDER_FAILED_RETRY: &str = "FWD-001";
     pub const ALL_PROVIDERS_FAILED: &str = "FWD-002";
     pub const SINGLE_PROVIDER_FAILED: &str = "FWD-003";
+    pub const SAFETY_NET_ATTEMPT: &str = "FWD-004";
+    pub const SAFETY_NET_SUCCEEDED: &str = "FWD-005";
+    pub const SAFETY_NET_FAILED: &str = "FWD-006";
 }
 
 /// 故障转移日志码
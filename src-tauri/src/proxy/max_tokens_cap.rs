@@ -0,0 +1,296 @@
+//! 输出 token 上限
+//!
+//! 根据供应商的 `meta.maxTokensCap`，在请求转发前限制请求的最大输出 token 数：
+//! 客户端请求的值超过上限或未设置时改写为上限，防止按量计费的中转站出现失控的长输出。
+//! 按客户端协议改写，格式转换会把该值带到上游对应的字段。
+//!
+//! 只作用于生成端点：`count_tokens` / `countTokens` 等端点不接受输出上限字段，原样转发。
+//! Claude 请求开启了 extended thinking 时，`thinking.budget_tokens` 必须小于 `max_tokens`，
+//! 因此同时收紧思考预算；上限低于思考预算下限时去掉 thinking。
+
+use crate::app_config::AppType;
+use crate::provider::Provider;
+use serde_json::{json, Map, Value};
+
+/// Claude extended thinking 的最小预算
+const MIN_THINKING_BUDGET: u64 = 1024;
+
+/// 读取供应商配置的输出 token 上限（0 视为未设置）
+pub fn provider_cap(provider: &Provider) -> Option<u64> {
+    provider
+        .meta
+        .as_ref()?
+        .max_tokens_cap
+        .filter(|cap| *cap > 0)
+}
+
+/// 该请求适用的输出 token 上限：原样透传的供应商与非生成端点不限制
+pub fn cap_for_request(provider: &Provider, app_type: &AppType, endpoint: &str) -> Option<u64> {
+    if provider.is_raw_passthrough() || !is_generation_endpoint(app_type, endpoint) {
+        return None;
+    }
+    provider_cap(provider)
+}
+
+/// 是否为生成内容的端点（忽略查询参数）
+fn is_generation_endpoint(app_type: &AppType, endpoint: &str) -> bool {
+    let path = endpoint.split('?').next().unwrap_or(endpoint);
+    match app_type {
+        AppType::Gemini => {
+            path.ends_with(":generateContent") || path.ends_with(":streamGenerateContent")
+        }
+        _ => {
+            path.ends_with("/messages")
+                || path.ends_with("/chat/completions")
+                || path.ends_with("/responses")
+        }
+    }
+}
+
+/// 承载最大输出 token 数的字段：(是否位于 `generationConfig` 内, 字段名)
+///
+/// - Claude Messages API: `max_tokens`
+/// - OpenAI Chat Completions: `max_completion_tokens`（客户端仍使用旧字段 `max_tokens` 时改写该字段，避免两者同时出现）
+/// - OpenAI Responses API: `max_output_tokens`
+/// - Gemini: `generationConfig.maxOutputTokens`
+fn target_field(
+    body: &Map<String, Value>,
+    app_type: &AppType,
+    endpoint: &str,
+) -> (bool, &'static str) {
+    let path = endpoint.split('?').next().unwrap_or(endpoint);
+    match app_type {
+        AppType::Gemini => (true, "maxOutputTokens"),
+        _ if path.ends_with("/responses") => (false, "max_output_tokens"),
+        _ if path.ends_with("/chat/completions") => {
+            if body.contains_key("max_tokens") && !body.contains_key("max_completion_tokens") {
+                (false, "max_tokens")
+            } else {
+                (false, "max_completion_tokens")
+            }
+        }
+        _ => (false, "max_tokens"),
+    }
+}
+
+/// 上限对该请求生效的最大输出 token 数（不修改请求体，用于记录到请求日志）
+pub fn effective_value(
+    provider: &Provider,
+    app_type: &AppType,
+    endpoint: &str,
+    body: &Value,
+) -> Option<u64> {
+    let cap = cap_for_request(provider, app_type, endpoint)?;
+    let body = body.as_object()?;
+    let (nested, key) = target_field(body, app_type, endpoint);
+    let container = if nested {
+        body.get("generationConfig").and_then(Value::as_object)
+    } else {
+        Some(body)
+    };
+    let requested = container.and_then(|c| c.get(key)).and_then(Value::as_u64);
+    Some(requested.map_or(cap, |requested| requested.min(cap)))
+}
+
+/// 按客户端协议限制请求体的最大输出 token 数，返回生效的值
+pub fn apply(body: &mut Value, app_type: &AppType, endpoint: &str, cap: u64) -> Option<u64> {
+    let body = body.as_object_mut()?;
+    let (nested, key) = target_field(body, app_type, endpoint);
+    let container = if nested {
+        body.entry("generationConfig")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()?
+    } else {
+        &mut *body
+    };
+
+    let requested = container.get(key).and_then(|v| v.as_u64());
+    let effective = requested.map_or(cap, |requested| requested.min(cap));
+    if requested != Some(effective) {
+        container.insert(key.to_string(), json!(effective));
+    }
+    if *app_type == AppType::Claude && key == "max_tokens" {
+        clamp_thinking_budget(body, effective);
+    }
+    Some(effective)
+}
+
+/// 思考预算必须小于 `max_tokens`：收紧到 `max_tokens - 1`，低于最小预算时去掉 thinking
+fn clamp_thinking_budget(body: &mut Map<String, Value>, max_tokens: u64) {
+    let Some(budget) = body
+        .get("thinking")
+        .and_then(|thinking| thinking.get("budget_tokens"))
+        .and_then(Value::as_u64)
+    else {
+        return;
+    };
+    if budget < max_tokens {
+        return;
+    }
+    if max_tokens > MIN_THINKING_BUDGET {
+        body["thinking"]["budget_tokens"] = json!(max_tokens - 1);
+    } else {
+        body.remove("thinking");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+
+    #[test]
+    fn clamps_too_large_request() {
+        let mut body = json!({"model": "claude-sonnet-4-5", "max_tokens": 64000});
+        assert_eq!(
+            apply(&mut body, &AppType::Claude, "/v1/messages", 8192),
+            Some(8192)
+        );
+        assert_eq!(body["max_tokens"], 8192);
+
+        // 低于上限的请求保持不变
+        let mut body = json!({"max_tokens": 1024});
+        assert_eq!(
+            apply(&mut body, &AppType::Claude, "/v1/messages", 8192),
+            Some(1024)
+        );
+        assert_eq!(body["max_tokens"], 1024);
+    }
+
+    #[test]
+    fn fills_in_absent_value() {
+        let mut body = json!({"model": "gpt-5", "messages": []});
+        assert_eq!(
+            apply(&mut body, &AppType::Codex, "/chat/completions", 4096),
+            Some(4096)
+        );
+        assert_eq!(body["max_completion_tokens"], 4096);
+        assert!(body.get("max_tokens").is_none());
+
+        let mut body = json!({"model": "gpt-5", "input": "hi"});
+        assert_eq!(
+            apply(&mut body, &AppType::Codex, "/responses", 4096),
+            Some(4096)
+        );
+        assert_eq!(body["max_output_tokens"], 4096);
+
+        let mut body = json!({"contents": []});
+        assert_eq!(
+            apply(
+                &mut body,
+                &AppType::Gemini,
+                "/v1beta/models/gemini-2.5-pro:generateContent",
+                2048
+            ),
+            Some(2048)
+        );
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 2048);
+    }
+
+    #[test]
+    fn rewrites_max_completion_tokens_for_chat_requests() {
+        let mut body = json!({"messages": [], "max_completion_tokens": 100000});
+        assert_eq!(
+            apply(&mut body, &AppType::Codex, "/chat/completions", 16000),
+            Some(16000)
+        );
+        assert_eq!(body["max_completion_tokens"], 16000);
+        assert!(body.get("max_tokens").is_none());
+
+        // 共用路径路由到 Claude 的 Chat 请求同样是 OpenAI 格式
+        let mut body = json!({"messages": []});
+        assert_eq!(
+            apply(&mut body, &AppType::Claude, "/v1/chat/completions", 16000),
+            Some(16000)
+        );
+        assert_eq!(body["max_completion_tokens"], 16000);
+
+        // 客户端仍使用旧字段时只改写旧字段
+        let mut body = json!({"messages": [], "max_tokens": 100000});
+        assert_eq!(
+            apply(&mut body, &AppType::Codex, "/v1/chat/completions", 16000),
+            Some(16000)
+        );
+        assert_eq!(body["max_tokens"], 16000);
+        assert!(body.get("max_completion_tokens").is_none());
+    }
+
+    #[test]
+    fn clamps_thinking_budget_below_max_tokens() {
+        let thinking = |budget: u64| {
+            json!({
+                "max_tokens": 32000,
+                "thinking": {"type": "enabled", "budget_tokens": budget}
+            })
+        };
+
+        let mut body = thinking(31999);
+        apply(&mut body, &AppType::Claude, "/v1/messages", 8192);
+        assert_eq!(body["thinking"]["budget_tokens"], 8191);
+
+        // 预算本来就在上限以内时保持不变
+        let mut body = thinking(4000);
+        apply(&mut body, &AppType::Claude, "/v1/messages", 8192);
+        assert_eq!(body["thinking"]["budget_tokens"], 4000);
+
+        // 上限不足以容纳最小思考预算时去掉 thinking
+        let mut body = thinking(31999);
+        apply(&mut body, &AppType::Claude, "/v1/messages", 1024);
+        assert!(body.get("thinking").is_none());
+    }
+
+    #[test]
+    fn only_generation_endpoints_are_capped() {
+        let mut provider = Provider::with_id("p".into(), "P".into(), json!({}), None);
+        provider.meta = Some(ProviderMeta {
+            max_tokens_cap: Some(4096),
+            ..Default::default()
+        });
+
+        for (app_type, endpoint) in [
+            (AppType::Claude, "/v1/messages"),
+            (AppType::Claude, "/v1/chat/completions"),
+            (AppType::Codex, "/responses"),
+            (
+                AppType::Gemini,
+                "/v1beta/models/gemini-2.5-pro:generateContent",
+            ),
+            (
+                AppType::Gemini,
+                "/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse",
+            ),
+        ] {
+            assert_eq!(
+                cap_for_request(&provider, &app_type, endpoint),
+                Some(4096),
+                "{endpoint}"
+            );
+        }
+        for (app_type, endpoint) in [
+            (AppType::Claude, "/v1/messages/count_tokens"),
+            (AppType::Codex, "/responses/compact"),
+            (AppType::Gemini, "/v1beta/models/gemini-2.5-pro:countTokens"),
+        ] {
+            assert_eq!(
+                cap_for_request(&provider, &app_type, endpoint),
+                None,
+                "{endpoint}"
+            );
+        }
+
+        let body = json!({"max_tokens": 1000});
+        assert_eq!(
+            effective_value(&provider, &AppType::Claude, "/v1/messages", &body),
+            Some(1000)
+        );
+        assert_eq!(
+            effective_value(
+                &provider,
+                &AppType::Claude,
+                "/v1/messages/count_tokens",
+                &body
+            ),
+            None
+        );
+    }
+}
@@ -2,26 +2,38 @@
 //!
 //! 提供本地HTTP代理服务，支持多Provider故障转移和请求透传
 
+pub mod app_detect;
 pub mod body_filter;
+pub mod body_transform;
 pub mod cache_injector;
 pub mod circuit_breaker;
+pub mod concurrency;
 pub mod error;
 pub mod error_mapper;
 pub(crate) mod failover_switch;
 mod forwarder;
+pub mod gemini_token_refresh;
 pub mod handler_config;
 pub mod handler_context;
 mod handlers;
 mod health;
 pub mod http_client;
+pub mod key_pool;
 pub mod log_codes;
+pub mod max_tokens_cap;
 pub mod model_mapper;
+pub mod model_policy;
 pub mod provider_router;
+pub mod provider_tls;
 pub mod providers;
+pub mod request_feed;
+pub mod response_cache;
 pub mod response_handler;
 pub mod response_processor;
 pub(crate) mod server;
 pub mod session;
+pub mod shadow;
+pub mod system_prompt;
 pub mod thinking_budget_rectifier;
 pub mod thinking_optimizer;
 pub mod thinking_rectifier;
@@ -173,6 +173,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
@@ -190,6 +191,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
@@ -211,6 +213,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
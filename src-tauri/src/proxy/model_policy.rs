@@ -0,0 +1,170 @@
+//! 供应商模型白名单/黑名单
+//!
+//! 根据 `meta.modelAllowlist` / `meta.modelDenylist` 在转发前拦截不允许的模型，
+//! 避免在按量计费的中转站上误用高价模型。检查对象为模型映射后实际发送给上游的模型名；
+//! 模型取自请求体的 `model` 字段，Gemini 原生接口则取自路径（`models/{model}:method`）。
+//!
+//! 匹配规则：忽略大小写的完整匹配，条目以 `*` 结尾时按前缀匹配（如 `claude-opus-*`）。
+//! 黑名单优先于白名单；白名单为空视为不限制。
+
+use super::error::ProxyError;
+use super::model_mapper::{has_thinking_enabled, ModelMapping};
+use crate::provider::Provider;
+use serde_json::Value;
+
+fn matches_pattern(pattern: &str, model: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let model = model.to_ascii_lowercase();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => model.starts_with(prefix),
+        None => !pattern.is_empty() && pattern == model,
+    }
+}
+
+/// Gemini 原生接口路径中的模型名（如 `/v1beta/models/gemini-2.5-pro:generateContent`）
+fn path_model(endpoint: &str) -> Option<&str> {
+    let path = endpoint.split('?').next().unwrap_or(endpoint);
+    let (_, rest) = path.split_once("models/")?;
+    let model = rest.split([':', '/']).next()?;
+    (!model.is_empty()).then_some(model)
+}
+
+/// 请求最终发送给该供应商的模型名（请求体与路径中都没有模型时返回 None）
+pub fn effective_model(body: &Value, endpoint: &str, provider: &Provider) -> Option<String> {
+    let requested = body
+        .get("model")
+        .and_then(|m| m.as_str())
+        .or_else(|| path_model(endpoint))?;
+    let mapping = ModelMapping::from_provider(provider);
+    if mapping.has_mapping() {
+        Some(mapping.map_model(requested, has_thinking_enabled(body)))
+    } else {
+        Some(requested.to_string())
+    }
+}
+
+/// 检查模型是否允许发送给该供应商
+pub fn check_model(provider: &Provider, model: &str) -> Result<(), ProxyError> {
+    let Some(meta) = provider.meta.as_ref() else {
+        return Ok(());
+    };
+
+    if let Some(pattern) = meta
+        .model_denylist
+        .iter()
+        .flatten()
+        .find(|pattern| matches_pattern(pattern, model))
+    {
+        return Err(ProxyError::InvalidRequest(format!(
+            "模型 {model} 已被供应商 {} 的黑名单禁止（匹配 {pattern}）",
+            provider.name
+        )));
+    }
+
+    let allowlist: Vec<&String> = meta
+        .model_allowlist
+        .iter()
+        .flatten()
+        .filter(|pattern| !pattern.trim().is_empty())
+        .collect();
+    if !allowlist.is_empty() && !allowlist.iter().any(|p| matches_pattern(p, model)) {
+        return Err(ProxyError::InvalidRequest(format!(
+            "模型 {model} 不在供应商 {} 的白名单中",
+            provider.name
+        )));
+    }
+
+    Ok(())
+}
+
+/// 按请求体与请求路径检查模型策略
+pub fn check_request(body: &Value, endpoint: &str, provider: &Provider) -> Result<(), ProxyError> {
+    match effective_model(body, endpoint, provider) {
+        Some(model) => check_model(provider, &model),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider(allow: &[&str], deny: &[&str]) -> Provider {
+        let mut provider = Provider::with_id(
+            "relay".to_string(),
+            "Relay".to_string(),
+            json!({ "env": {} }),
+            None,
+        );
+        let to_vec = |items: &[&str]| {
+            (!items.is_empty()).then(|| items.iter().map(|s| s.to_string()).collect())
+        };
+        provider.meta = Some(ProviderMeta {
+            model_allowlist: to_vec(allow),
+            model_denylist: to_vec(deny),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn allowed_model_passes() {
+        let p = provider(&["claude-sonnet-*", "claude-haiku-4-5"], &[]);
+        assert!(check_model(&p, "claude-sonnet-4-5").is_ok());
+        assert!(check_model(&p, "Claude-Haiku-4-5").is_ok());
+        assert!(check_model(&provider(&[], &[]), "claude-opus-4-1").is_ok());
+    }
+
+    #[test]
+    fn denied_model_is_rejected_with_bad_request() {
+        let p = provider(&[], &["claude-opus-*"]);
+        let err = check_model(&p, "claude-opus-4-1").unwrap_err();
+        assert!(matches!(err, ProxyError::InvalidRequest(_)));
+        assert!(err.to_string().contains("claude-opus-4-1"));
+        assert!(check_model(&p, "claude-sonnet-4-5").is_ok());
+    }
+
+    #[test]
+    fn model_not_in_allowlist_is_rejected() {
+        let p = provider(&["claude-haiku-*"], &[]);
+        let err = check_model(&p, "claude-sonnet-4-5").unwrap_err();
+        assert!(err.to_string().contains("白名单"));
+    }
+
+    #[test]
+    fn denylist_takes_precedence_over_allowlist() {
+        let p = provider(&["claude-*"], &["claude-opus-4-1"]);
+        assert!(check_model(&p, "claude-opus-4-1").is_err());
+        assert!(check_model(&p, "claude-opus-4-5").is_ok());
+    }
+
+    #[test]
+    fn policy_checks_mapped_model() {
+        let mut p = provider(&[], &["claude-opus-*"]);
+        p.settings_config = json!({ "env": { "ANTHROPIC_MODEL": "claude-opus-4-1" } });
+
+        let body = json!({ "model": "claude-sonnet-4-5", "messages": [] });
+        assert!(check_request(&body, "/v1/messages", &p).is_err());
+        assert!(check_request(&json!({ "contents": [] }), "/v1/messages", &p).is_ok());
+    }
+
+    #[test]
+    fn gemini_path_model_is_checked() {
+        let p = provider(&[], &["gemini-2.5-pro"]);
+        let body = json!({ "contents": [] });
+
+        let err = check_request(
+            &body,
+            "/v1beta/models/gemini-2.5-pro:streamGenerateContent?alt=sse",
+            &p,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("gemini-2.5-pro"));
+        assert!(
+            check_request(&body, "/v1beta/models/gemini-2.5-flash:generateContent", &p).is_ok()
+        );
+        assert!(check_request(&body, "/v1beta/models", &p).is_ok());
+    }
+}
@@ -3,21 +3,39 @@
 //! 负责选择和管理代理目标供应商，实现智能故障转移
 
 use crate::app_config::AppType;
-use crate::database::Database;
+use crate::database::{Database, ProviderGroup};
 use crate::error::AppError;
 use crate::provider::Provider;
 use crate::proxy::circuit_breaker::{AllowResult, CircuitBreaker, CircuitBreakerConfig};
+use crate::proxy::error::ProxyError;
+use crate::proxy::types::RoutingMode;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// 选择故障转移分组的请求头（仅用于本地路由，不会转发到上游）
+pub const GROUP_HEADER: &str = "x-cc-group";
+
+/// 最低延迟路由使用的测速数据缓存时长（过期后重新从数据库读取）
+const LATENCY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 最低延迟路由计算平均值时每个供应商使用的最近样本数
+const LATENCY_SAMPLE_WINDOW: usize = 10;
+
 /// 供应商路由器
 pub struct ProviderRouter {
     /// 数据库连接
     db: Arc<Database>,
     /// 熔断器管理器 - key 格式: "app_type:provider_id"
     circuit_breakers: Arc<RwLock<HashMap<String, Arc<CircuitBreaker>>>>,
+    /// Key 池游标 - key 格式: "app_type:provider_id"，值为当前使用的 Key 序号
+    key_cursors: Arc<RwLock<HashMap<String, usize>>>,
+    /// 路由策略状态 - key 为 app_type
+    routing_states: Arc<RwLock<HashMap<String, RoutingState>>>,
+    /// 各供应商最近平均延迟缓存 - key 为 app_type，值为（读取时间, provider_id → 毫秒）
+    latency_cache: Arc<RwLock<HashMap<String, (Instant, HashMap<String, f64>)>>>,
 }
 
 impl ProviderRouter {
@@ -26,6 +44,9 @@ impl ProviderRouter {
         Self {
             db,
             circuit_breakers: Arc::new(RwLock::new(HashMap::new())),
+            key_cursors: Arc::new(RwLock::new(HashMap::new())),
+            routing_states: Arc::new(RwLock::new(HashMap::new())),
+            latency_cache: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -33,24 +54,34 @@ impl ProviderRouter {
     ///
     /// 返回按优先级排序的可用供应商列表：
     /// - 故障转移关闭时：仅返回当前供应商
-    /// - 故障转移开启时：仅使用故障转移队列，按队列顺序依次尝试（P1 → P2 → ...）
+    /// - 故障转移开启时：仅使用故障转移队列，按队列顺序依次尝试（P1 → P2 → ...），
+    ///   首选供应商由该应用的路由策略（`RoutingMode`）决定
     pub async fn select_providers(&self, app_type: &str) -> Result<Vec<Provider>, AppError> {
+        self.select_providers_for_group(app_type, None).await
+    }
+
+    /// 按分组选择可用的供应商
+    ///
+    /// 故障转移开启且配置了分组时，只在目标分组内按队列顺序路由：
+    /// 请求指定的分组存在时使用该分组，否则回退到默认分组；未配置默认分组时不做过滤。
+    /// 故障转移关闭时始终使用当前供应商，忽略分组。
+    pub async fn select_providers_for_group(
+        &self,
+        app_type: &str,
+        requested_group: Option<&str>,
+    ) -> Result<Vec<Provider>, AppError> {
         let mut result = Vec::new();
         let mut total_providers = 0usize;
         let mut circuit_open_count = 0usize;
 
-        // 检查该应用的自动故障转移开关是否开启（从 proxy_config 表读取）
-        let auto_failover_enabled = match self.db.get_proxy_config_for_app(app_type).await {
-            Ok(config) => config.auto_failover_enabled,
-            Err(e) => {
-                log::error!("[{app_type}] 读取 proxy_config 失败: {e}，默认禁用故障转移");
-                false
-            }
-        };
-
-        if auto_failover_enabled {
+        if self.auto_failover_enabled(app_type).await {
             // 故障转移开启：仅按队列顺序依次尝试（P1 → P2 → ...）
             let all_providers = self.db.get_all_providers(app_type)?;
+            let groups = self.db.get_provider_groups(app_type)?;
+            let target_group = resolve_group(&groups, requested_group);
+            if let Some(group) = target_group {
+                log::debug!("[{app_type}] 按分组路由: {}", group.name);
+            }
 
             // 使用 DAO 返回的排序结果，确保和前端展示一致
             let ordered_ids: Vec<String> = self
@@ -58,6 +89,10 @@ impl ProviderRouter {
                 .get_failover_queue(app_type)?
                 .into_iter()
                 .map(|item| item.provider_id)
+                .filter(|id| match (target_group, all_providers.get(id)) {
+                    (Some(group), Some(provider)) => in_group(provider, group, &groups),
+                    _ => true,
+                })
                 .collect();
 
             total_providers = ordered_ids.len();
@@ -67,6 +102,12 @@ impl ProviderRouter {
                     continue;
                 };
 
+                // 已禁用的供应商保留在队列中，但不参与路由
+                if !provider.enabled {
+                    log::debug!("[{app_type}] 跳过已禁用的供应商: {}", provider.name);
+                    continue;
+                }
+
                 let circuit_key = format!("{app_type}:{}", provider.id);
                 let breaker = self.get_or_create_circuit_breaker(&circuit_key).await;
 
@@ -76,21 +117,41 @@ impl ProviderRouter {
                     circuit_open_count += 1;
                 }
             }
+
+            let mode = self.db.get_routing_strategy(app_type).unwrap_or_else(|e| {
+                log::error!("[{app_type}] 读取路由策略失败: {e}，使用默认策略");
+                RoutingMode::default()
+            });
+            if mode != RoutingMode::Sticky && result.len() > 1 {
+                let first = match mode {
+                    RoutingMode::LowestLatency => self.pick_lowest_latency(app_type, &result).await,
+                    _ => {
+                        let mut states = self.routing_states.write().await;
+                        let state = states.entry(app_type.to_string()).or_default();
+                        state.pick(mode, &result)
+                    }
+                };
+                let chosen = result.remove(first);
+                result.insert(0, chosen);
+                log::debug!(
+                    "[{app_type}] 路由策略 {}: 首选 {}",
+                    mode.as_str(),
+                    result[0].name
+                );
+            }
         } else {
             // 故障转移关闭：仅使用当前供应商，跳过熔断器检查
-            let current_id = AppType::from_str(app_type)
-                .ok()
-                .and_then(|app_enum| {
-                    crate::settings::get_effective_current_provider(&self.db, &app_enum)
-                        .ok()
-                        .flatten()
-                })
-                .or_else(|| self.db.get_current_provider(app_type).ok().flatten());
-
-            if let Some(current_id) = current_id {
+            if let Some(current_id) = self.current_provider_id(app_type) {
                 if let Some(current) = self.db.get_provider_by_id(&current_id, app_type)? {
-                    total_providers = 1;
-                    result.push(current);
+                    if current.enabled {
+                        total_providers = 1;
+                        result.push(current);
+                    } else {
+                        log::warn!(
+                            "[{app_type}] 当前供应商 {} 已禁用，不参与路由",
+                            current.name
+                        );
+                    }
                 }
             }
         }
@@ -108,6 +169,97 @@ impl ProviderRouter {
         Ok(result)
     }
 
+    /// 当前供应商 ID（优先本地设置，其次数据库）
+    fn current_provider_id(&self, app_type: &str) -> Option<String> {
+        AppType::from_str(app_type)
+            .ok()
+            .and_then(|app_enum| {
+                crate::settings::get_effective_current_provider(&self.db, &app_enum)
+                    .ok()
+                    .flatten()
+            })
+            .or_else(|| self.db.get_current_provider(app_type).ok().flatten())
+    }
+
+    /// 选出最近平均延迟最低的供应商
+    ///
+    /// 没有测速数据的供应商不参与比较；所有候选都没有数据时优先当前供应商，
+    /// 当前供应商不在候选中则保持队列顺序
+    async fn pick_lowest_latency(&self, app_type: &str, providers: &[Provider]) -> usize {
+        let latencies = self.recent_latencies(app_type).await;
+        providers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, provider)| latencies.get(&provider.id).map(|ms| (index, *ms)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+            .or_else(|| {
+                let current_id = self.current_provider_id(app_type)?;
+                providers
+                    .iter()
+                    .position(|provider| provider.id == current_id)
+            })
+            .unwrap_or(0)
+    }
+
+    /// 各供应商最近平均延迟（缓存 `LATENCY_REFRESH_INTERVAL` 后重新读取）
+    async fn recent_latencies(&self, app_type: &str) -> HashMap<String, f64> {
+        if let Some((fetched_at, latencies)) = self.latency_cache.read().await.get(app_type) {
+            if fetched_at.elapsed() < LATENCY_REFRESH_INTERVAL {
+                return latencies.clone();
+            }
+        }
+
+        let latencies = self
+            .db
+            .get_recent_average_latencies(app_type, LATENCY_SAMPLE_WINDOW)
+            .unwrap_or_else(|e| {
+                log::warn!("[{app_type}] 读取测速数据失败: {e}");
+                HashMap::new()
+            });
+        self.latency_cache
+            .write()
+            .await
+            .insert(app_type.to_string(), (Instant::now(), latencies.clone()));
+        latencies
+    }
+
+    /// 该应用的自动故障转移开关是否开启（从 proxy_config 表读取，读取失败视为关闭）
+    pub async fn auto_failover_enabled(&self, app_type: &str) -> bool {
+        match self.db.get_proxy_config_for_app(app_type).await {
+            Ok(config) => config.auto_failover_enabled,
+            Err(e) => {
+                log::error!("[{app_type}] 读取 proxy_config 失败: {e}，默认禁用故障转移");
+                false
+            }
+        }
+    }
+
+    /// 该应用已启用的兜底供应商（`meta.isSafetyNet`），读取失败或未设置时返回 None
+    ///
+    /// 兜底供应商不经过熔断器，只在故障转移开启且候选全部失败后由转发器尝试一次。
+    pub fn safety_net_provider(&self, app_type: &str) -> Option<Provider> {
+        match self.db.get_all_providers(app_type) {
+            Ok(providers) => providers
+                .into_values()
+                .find(|provider| provider.enabled && provider.is_safety_net()),
+            Err(e) => {
+                log::warn!("[{app_type}] 读取兜底供应商失败: {e}");
+                None
+            }
+        }
+    }
+
+    /// 候选供应商全部熔断时改用的兜底供应商
+    ///
+    /// 仅在故障转移开启时返回，请求随后由转发器按兜底流程只尝试一次。
+    pub async fn safety_net_fallback(&self, app_type: &str) -> Option<Provider> {
+        if !self.auto_failover_enabled(app_type).await {
+            return None;
+        }
+        self.safety_net_provider(app_type)
+    }
+
     /// 请求执行前获取熔断器“放行许可”
     ///
     /// - Closed：直接放行
@@ -123,13 +275,15 @@ impl ProviderRouter {
     }
 
     /// 记录供应商请求结果
+    ///
+    /// 失败时传入对应的 `ProxyError`，其消息和错误类别会写入健康状态
     pub async fn record_result(
         &self,
         provider_id: &str,
         app_type: &str,
         used_half_open_permit: bool,
         success: bool,
-        error_msg: Option<String>,
+        error: Option<&ProxyError>,
     ) -> Result<(), AppError> {
         // 1. 按应用独立获取熔断器配置
         let failure_threshold = match self.db.get_proxy_config_for_app(app_type).await {
@@ -153,7 +307,8 @@ impl ProviderRouter {
                 provider_id,
                 app_type,
                 success,
-                error_msg.clone(),
+                error.map(|e| e.to_string()),
+                error.map(ProxyError::error_kind),
                 failure_threshold,
             )
             .await?;
@@ -175,6 +330,49 @@ impl ProviderRouter {
         self.reset_circuit_breaker(&circuit_key).await;
     }
 
+    /// 获取供应商 Key 池当前使用的 Key 序号
+    pub async fn current_key_index(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        pool_len: usize,
+    ) -> usize {
+        let cursor_key = format!("{app_type}:{provider_id}");
+        let index = self
+            .key_cursors
+            .read()
+            .await
+            .get(&cursor_key)
+            .copied()
+            .unwrap_or(0);
+        if pool_len == 0 {
+            0
+        } else {
+            index % pool_len
+        }
+    }
+
+    /// Key 触发配额错误后轮换到下一个 Key
+    ///
+    /// 仅当游标仍指向失败的 Key 时才前移，避免并发请求重复轮换跳过可用的 Key
+    pub async fn rotate_key(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+        failed_index: usize,
+        pool_len: usize,
+    ) {
+        if pool_len == 0 {
+            return;
+        }
+        let cursor_key = format!("{app_type}:{provider_id}");
+        let mut cursors = self.key_cursors.write().await;
+        let current = cursors.get(&cursor_key).copied().unwrap_or(0) % pool_len;
+        if current == failed_index {
+            cursors.insert(cursor_key, (failed_index + 1) % pool_len);
+        }
+    }
+
     /// 仅释放 HalfOpen permit，不影响健康统计（neutral 接口）
     ///
     /// 用于整流器等场景：请求结果不应计入 Provider 健康度，
@@ -258,10 +456,89 @@ impl ProviderRouter {
     }
 }
 
+/// 路由策略的运行时状态（按应用，不持久化）
+#[derive(Debug, Default)]
+struct RoutingState {
+    /// 轮询游标
+    next: usize,
+    /// 平滑加权轮询中各供应商的当前权重
+    current_weights: HashMap<String, i64>,
+}
+
+impl RoutingState {
+    /// 按策略选出首选供应商的下标（最低延迟由路由器根据测速数据选择，这里不处理）
+    fn pick(&mut self, mode: RoutingMode, providers: &[Provider]) -> usize {
+        if providers.is_empty() {
+            return 0;
+        }
+        match mode {
+            RoutingMode::Sticky | RoutingMode::LowestLatency => 0,
+            RoutingMode::RoundRobin => {
+                let index = self.next % providers.len();
+                self.next = self.next.wrapping_add(1);
+                index
+            }
+            RoutingMode::Weighted => self.pick_weighted(providers),
+        }
+    }
+
+    /// 平滑加权轮询（同 nginx）：每轮各供应商累加自身权重，选当前权重最大者并减去总权重
+    fn pick_weighted(&mut self, providers: &[Provider]) -> usize {
+        self.current_weights
+            .retain(|id, _| providers.iter().any(|provider| provider.id == *id));
+        let total: i64 = providers
+            .iter()
+            .map(|provider| i64::from(provider.routing_weight()))
+            .sum();
+
+        let mut best = 0;
+        let mut best_weight = i64::MIN;
+        for (index, provider) in providers.iter().enumerate() {
+            let weight = self.current_weights.entry(provider.id.clone()).or_insert(0);
+            *weight += i64::from(provider.routing_weight());
+            if *weight > best_weight {
+                best = index;
+                best_weight = *weight;
+            }
+        }
+        if let Some(weight) = self.current_weights.get_mut(&providers[best].id) {
+            *weight -= total;
+        }
+        best
+    }
+}
+
+/// 解析请求使用的分组：指定的分组存在时使用该分组，否则回退到默认分组
+fn resolve_group<'a>(
+    groups: &'a [ProviderGroup],
+    requested: Option<&str>,
+) -> Option<&'a ProviderGroup> {
+    requested
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .and_then(|name| groups.iter().find(|group| group.name == name))
+        .or_else(|| groups.iter().find(|group| group.is_default))
+}
+
+/// 供应商是否属于分组（未设置分组或分组已不存在的供应商归入默认分组）
+fn in_group(provider: &Provider, group: &ProviderGroup, groups: &[ProviderGroup]) -> bool {
+    let tagged = provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.group.as_deref())
+        .map(str::trim)
+        .filter(|name| groups.iter().any(|g| g.name == *name));
+    match tagged {
+        Some(name) => name == group.name,
+        None => group.is_default,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::database::Database;
+    use crate::proxy::types::ProviderErrorKind;
     use serde_json::json;
     use serial_test::serial;
     use std::env;
@@ -405,6 +682,339 @@ mod tests {
         assert_eq!(providers[0].id, "b");
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_failover_enabled_skips_disabled_providers() {
+        let _home = TempHome::new();
+        let db = Arc::new(Database::memory().unwrap());
+
+        let mut provider_a =
+            Provider::with_id("a".to_string(), "Provider A".to_string(), json!({}), None);
+        provider_a.sort_index = Some(1);
+        let mut provider_b =
+            Provider::with_id("b".to_string(), "Provider B".to_string(), json!({}), None);
+        provider_b.sort_index = Some(2);
+
+        db.save_provider("claude", &provider_a).unwrap();
+        db.save_provider("claude", &provider_b).unwrap();
+        db.add_to_failover_queue("claude", "a").unwrap();
+        db.add_to_failover_queue("claude", "b").unwrap();
+        db.set_provider_enabled("claude", "a", false).unwrap();
+
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        let providers = router.select_providers("claude").await.unwrap();
+
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id, "b");
+        assert!(db
+            .get_failover_providers("claude")
+            .unwrap()
+            .iter()
+            .all(|p| p.id != "a"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_failover_disabled_never_selects_disabled_current_provider() {
+        let _home = TempHome::new();
+        let db = Arc::new(Database::memory().unwrap());
+
+        let provider_a =
+            Provider::with_id("a".to_string(), "Provider A".to_string(), json!({}), None);
+        db.save_provider("claude", &provider_a).unwrap();
+        db.set_current_provider("claude", "a").unwrap();
+        db.set_provider_enabled("claude", "a", false).unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        let result = router.select_providers("claude").await;
+
+        assert!(matches!(result, Err(AppError::NoProvidersConfigured)));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_disabled_flag_survives_provider_edit() {
+        let _home = TempHome::new();
+        let db = Arc::new(Database::memory().unwrap());
+
+        let provider_a =
+            Provider::with_id("a".to_string(), "Provider A".to_string(), json!({}), None);
+        db.save_provider("claude", &provider_a).unwrap();
+        db.set_provider_enabled("claude", "a", false).unwrap();
+
+        // 前端保存编辑时可能不携带 enabled 字段（反序列化默认 true），不应重新启用
+        let mut edited = provider_a.clone();
+        edited.name = "Renamed".to_string();
+        db.save_provider("claude", &edited).unwrap();
+
+        let stored = db.get_provider_by_id("a", "claude").unwrap().unwrap();
+        assert_eq!(stored.name, "Renamed");
+        assert!(!stored.enabled);
+    }
+
+    /// 队列顺序为 a → b → c 的三个供应商（权重 3/1/1），并开启故障转移
+    async fn weighted_queue_router(mode: RoutingMode) -> ProviderRouter {
+        let db = Arc::new(Database::memory().unwrap());
+        for (index, (id, weight)) in [("a", 3), ("b", 1), ("c", 1)].into_iter().enumerate() {
+            let mut provider =
+                Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+            provider.sort_index = Some(index + 1);
+            provider.meta = Some(crate::provider::ProviderMeta {
+                routing_weight: Some(weight),
+                ..Default::default()
+            });
+            db.save_provider("claude", &provider).unwrap();
+            db.add_to_failover_queue("claude", id).unwrap();
+        }
+
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+        db.set_routing_strategy("claude", mode).unwrap();
+
+        ProviderRouter::new(db)
+    }
+
+    /// 连续选择 `rounds` 次，返回每次的首选供应商
+    async fn first_choices(router: &ProviderRouter, rounds: usize) -> Vec<String> {
+        let mut choices = Vec::new();
+        for _ in 0..rounds {
+            let providers = router.select_providers("claude").await.unwrap();
+            assert_eq!(providers.len(), 3);
+            choices.push(providers[0].id.clone());
+        }
+        choices
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_routing_strategy_changes_first_choice() {
+        let _home = TempHome::new();
+
+        let sticky = weighted_queue_router(RoutingMode::Sticky).await;
+        assert_eq!(first_choices(&sticky, 5).await, ["a", "a", "a", "a", "a"]);
+
+        let round_robin = weighted_queue_router(RoutingMode::RoundRobin).await;
+        assert_eq!(
+            first_choices(&round_robin, 5).await,
+            ["a", "b", "c", "a", "b"]
+        );
+        // 其余供应商保持队列顺序作为后备
+        let providers = round_robin.select_providers("claude").await.unwrap();
+        let ids: Vec<_> = providers.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a", "b"]);
+
+        let weighted = weighted_queue_router(RoutingMode::Weighted).await;
+        assert_eq!(first_choices(&weighted, 5).await, ["a", "b", "a", "c", "a"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_lowest_latency_prefers_fastest_provider() {
+        let _home = TempHome::new();
+        let router = weighted_queue_router(RoutingMode::LowestLatency).await;
+
+        // 没有测速数据：优先当前供应商
+        router.db.set_current_provider("claude", "b").unwrap();
+        assert_eq!(first_choices(&router, 2).await, ["b", "b"]);
+
+        let router = weighted_queue_router(RoutingMode::LowestLatency).await;
+        router.db.set_current_provider("claude", "a").unwrap();
+        let samples = [
+            ("a", vec![Some(300), Some(320)]),
+            ("b", vec![Some(900), None]),
+            // c 最近的样本更快，更早的慢样本不在统计窗口内
+            ("c", {
+                let mut samples = vec![Some(5000); 5];
+                samples.extend(vec![Some(120); LATENCY_SAMPLE_WINDOW]);
+                samples
+            }),
+        ];
+        for (id, latencies) in samples {
+            for (index, latency_ms) in latencies.into_iter().enumerate() {
+                router
+                    .db
+                    .append_endpoint_latency(
+                        "claude",
+                        id,
+                        "https://api.example.com",
+                        &crate::database::EndpointLatencySample {
+                            latency_ms,
+                            status: latency_ms.map(|_| 200),
+                            tested_at: index as i64,
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+
+        let providers = router.select_providers("claude").await.unwrap();
+        let ids: Vec<_> = providers.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a", "b"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_routing_strategy_is_persisted_per_app() {
+        let _home = TempHome::new();
+        let db = Database::memory().unwrap();
+
+        assert_eq!(
+            db.get_routing_strategy("claude").unwrap(),
+            RoutingMode::Sticky
+        );
+        db.set_routing_strategy("codex", RoutingMode::RoundRobin)
+            .unwrap();
+        assert_eq!(
+            db.get_routing_strategy("codex").unwrap(),
+            RoutingMode::RoundRobin
+        );
+        assert_eq!(
+            db.get_routing_strategy("claude").unwrap(),
+            RoutingMode::Sticky
+        );
+
+        // 无法识别的值回退到默认策略
+        db.set_setting("routing_strategy_gemini", "random").unwrap();
+        assert_eq!(
+            db.get_routing_strategy("gemini").unwrap(),
+            RoutingMode::Sticky
+        );
+    }
+
+    fn grouped_provider(id: &str, sort_index: usize, group: Option<&str>) -> Provider {
+        let mut provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+        provider.sort_index = Some(sort_index);
+        provider.meta = Some(crate::provider::ProviderMeta {
+            group: group.map(str::to_string),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_group_header_routes_within_group_healthy_providers() {
+        let _home = TempHome::new();
+        let db = Arc::new(Database::memory().unwrap());
+
+        db.update_circuit_breaker_config(&CircuitBreakerConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        for group in [("cheap", true), ("premium", false)] {
+            db.save_provider_group(
+                "claude",
+                &ProviderGroup {
+                    name: group.0.to_string(),
+                    is_default: group.1,
+                },
+            )
+            .unwrap();
+        }
+
+        let providers = [
+            grouped_provider("a", 1, Some("cheap")),
+            grouped_provider("b", 2, Some("premium")),
+            grouped_provider("c", 3, Some("premium")),
+            grouped_provider("d", 4, None),
+        ];
+        for provider in &providers {
+            db.save_provider("claude", provider).unwrap();
+            db.add_to_failover_queue("claude", &provider.id).unwrap();
+        }
+
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        let fail = ProxyError::ForwardFailed("fail".to_string());
+        router
+            .record_result("b", "claude", false, false, Some(&fail))
+            .await
+            .unwrap();
+
+        let ids = |providers: Vec<Provider>| -> Vec<String> {
+            providers.into_iter().map(|p| p.id).collect()
+        };
+
+        // 指定分组：只在该分组的健康供应商中路由（b 已熔断）
+        let premium = router
+            .select_providers_for_group("claude", Some("premium"))
+            .await
+            .unwrap();
+        assert_eq!(ids(premium), vec!["c"]);
+
+        // 未指定或分组不存在：回退到默认分组（未设置分组的 d 归入默认分组）
+        let default = router.select_providers("claude").await.unwrap();
+        assert_eq!(ids(default), vec!["a", "d"]);
+        let unknown = router
+            .select_providers_for_group("claude", Some("missing"))
+            .await
+            .unwrap();
+        assert_eq!(ids(unknown), vec!["a", "d"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_group_with_all_providers_tripped_reports_circuit_open() {
+        let _home = TempHome::new();
+        let db = Arc::new(Database::memory().unwrap());
+
+        db.update_circuit_breaker_config(&CircuitBreakerConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db.save_provider_group(
+            "claude",
+            &ProviderGroup {
+                name: "premium".to_string(),
+                is_default: false,
+            },
+        )
+        .unwrap();
+
+        for provider in [
+            grouped_provider("a", 1, None),
+            grouped_provider("b", 2, Some("premium")),
+        ] {
+            db.save_provider("claude", &provider).unwrap();
+            db.add_to_failover_queue("claude", &provider.id).unwrap();
+        }
+
+        let mut config = db.get_proxy_config_for_app("claude").await.unwrap();
+        config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(config).await.unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        let fail = ProxyError::ForwardFailed("fail".to_string());
+        router
+            .record_result("b", "claude", false, false, Some(&fail))
+            .await
+            .unwrap();
+
+        // 分组内无健康供应商时不会借用其他分组
+        let result = router
+            .select_providers_for_group("claude", Some("premium"))
+            .await;
+        assert!(matches!(result, Err(AppError::AllProvidersCircuitOpen)));
+
+        // 没有默认分组时不做过滤
+        let all = router.select_providers("claude").await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, "a");
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_select_providers_does_not_consume_half_open_permit() {
@@ -436,9 +1046,10 @@ mod tests {
         db.update_proxy_config_for_app(config).await.unwrap();
 
         let router = ProviderRouter::new(db.clone());
+        let fail = ProxyError::ForwardFailed("fail".to_string());
 
         router
-            .record_result("b", "claude", false, false, Some("fail".to_string()))
+            .record_result("b", "claude", false, false, Some(&fail))
             .await
             .unwrap();
 
@@ -448,6 +1059,37 @@ mod tests {
         assert!(router.allow_provider_request("b", "claude").await.allowed);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_record_result_stores_error_kind() {
+        let _home = TempHome::new();
+        let db = Arc::new(Database::memory().unwrap());
+        let provider_a =
+            Provider::with_id("a".to_string(), "Provider A".to_string(), json!({}), None);
+        db.save_provider("claude", &provider_a).unwrap();
+
+        let router = ProviderRouter::new(db.clone());
+        let unauthorized = ProxyError::UpstreamError {
+            status: 401,
+            body: Some("invalid api key".to_string()),
+        };
+        router
+            .record_result("a", "claude", false, false, Some(&unauthorized))
+            .await
+            .unwrap();
+
+        let health = db.get_provider_health("a", "claude").await.unwrap();
+        assert_eq!(health.last_error_kind, Some(ProviderErrorKind::Auth));
+
+        // 成功后清空错误类别
+        router
+            .record_result("a", "claude", false, true, None)
+            .await
+            .unwrap();
+        let health = db.get_provider_health("a", "claude").await.unwrap();
+        assert_eq!(health.last_error_kind, None);
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_release_permit_neutral_frees_half_open_slot() {
@@ -476,8 +1118,9 @@ mod tests {
         let router = ProviderRouter::new(db.clone());
 
         // 触发熔断：1 次失败
+        let fail = ProxyError::ForwardFailed("fail".to_string());
         router
-            .record_result("a", "claude", false, false, Some("fail".to_string()))
+            .record_result("a", "claude", false, false, Some(&fail))
             .await
             .unwrap();
 
@@ -119,6 +119,11 @@ pub enum AuthStrategy {
     ///
     /// 使用动态获取的 Copilot Token（通过 GitHub OAuth 设备码流程获取）
     GitHubCopilot,
+
+    /// Azure OpenAI 认证方式
+    ///
+    /// - Header: `api-key: <api_key>`
+    AzureApiKey,
 }
 
 #[cfg(test)]
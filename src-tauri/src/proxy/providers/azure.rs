@@ -0,0 +1,140 @@
+//! Azure OpenAI 部署路由
+//!
+//! Azure OpenAI 不按模型名路由，而是按部署名（deployment）路由，并要求 `api-version` 查询参数：
+//! `{base}/openai/deployments/{deployment}/chat/completions?api-version=...`，认证使用 `api-key` 头。
+//!
+//! Codex 供应商在 `meta.providerType` 设为 `"azure_openai"` 时启用：
+//! - 请求模型通过 `meta.azureDeployments`（模型名 → 部署名）映射到部署，未配置时直接以模型名作为部署名
+//! - `meta.azureApiVersion` 指定 API 版本，未配置时使用 [`DEFAULT_API_VERSION`]
+
+use crate::provider::Provider;
+
+/// `meta.providerType` 中 Azure OpenAI 的标识
+pub const AZURE_PROVIDER_TYPE: &str = "azure_openai";
+
+/// 未配置 `azureApiVersion` 时使用的 API 版本
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// 按部署路由的端点（其余端点如 `responses` 直接挂在 `/openai` 下）
+const DEPLOYMENT_ENDPOINTS: [&str; 3] = ["chat/completions", "completions", "embeddings"];
+
+/// 是否为 Azure OpenAI 供应商
+pub fn is_azure(provider: &Provider) -> bool {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.provider_type.as_deref())
+        == Some(AZURE_PROVIDER_TYPE)
+}
+
+/// 将请求模型映射到部署名（未配置映射时使用模型名本身）
+pub fn deployment_for(provider: &Provider, model: &str) -> String {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.azure_deployments.as_ref())
+        .and_then(|deployments| deployments.get(model))
+        .map(|d| d.trim())
+        .filter(|d| !d.is_empty())
+        .unwrap_or(model)
+        .to_string()
+}
+
+/// 供应商配置的 API 版本
+pub fn api_version(provider: &Provider) -> &str {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.azure_api_version.as_deref())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or(DEFAULT_API_VERSION)
+}
+
+/// 构建 Azure 请求 URL
+///
+/// `base_url` 为资源地址（如 `https://my-res.openai.azure.com`，可带 `/openai` 后缀），
+/// `endpoint` 为 OpenAI 风格端点（如 `/v1/chat/completions`）。
+pub fn build_url(base_url: &str, endpoint: &str, deployment: &str, api_version: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    let base = base.strip_suffix("/openai").unwrap_or(base);
+    let endpoint = endpoint.trim_start_matches('/');
+    let endpoint = endpoint.strip_prefix("v1/").unwrap_or(endpoint);
+
+    if DEPLOYMENT_ENDPOINTS.contains(&endpoint) {
+        format!("{base}/openai/deployments/{deployment}/{endpoint}?api-version={api_version}")
+    } else {
+        format!("{base}/openai/{endpoint}?api-version={api_version}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn azure_provider(deployments: &[(&str, &str)], api_version: Option<&str>) -> Provider {
+        let mut provider = Provider::with_id(
+            "azure".to_string(),
+            "Azure".to_string(),
+            json!({ "env": { "OPENAI_API_KEY": "azure-key" } }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            provider_type: Some(AZURE_PROVIDER_TYPE.to_string()),
+            azure_deployments: Some(
+                deployments
+                    .iter()
+                    .map(|(m, d)| (m.to_string(), d.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            azure_api_version: api_version.map(str::to_string),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn chat_completions_is_routed_to_deployment() {
+        let provider = azure_provider(&[("gpt-5", "prod-gpt5")], Some("2025-01-01-preview"));
+        assert!(is_azure(&provider));
+
+        let deployment = deployment_for(&provider, "gpt-5");
+        let url = build_url(
+            "https://my-res.openai.azure.com/",
+            "/v1/chat/completions",
+            &deployment,
+            api_version(&provider),
+        );
+        assert_eq!(
+            url,
+            "https://my-res.openai.azure.com/openai/deployments/prod-gpt5/chat/completions?api-version=2025-01-01-preview"
+        );
+    }
+
+    #[test]
+    fn unmapped_model_and_defaults_are_used() {
+        let provider = azure_provider(&[], None);
+        assert_eq!(deployment_for(&provider, "gpt-4o"), "gpt-4o");
+        assert_eq!(api_version(&provider), DEFAULT_API_VERSION);
+
+        let url = build_url(
+            "https://my-res.openai.azure.com/openai",
+            "/v1/responses",
+            "gpt-4o",
+            DEFAULT_API_VERSION,
+        );
+        assert_eq!(
+            url,
+            format!("https://my-res.openai.azure.com/openai/responses?api-version={DEFAULT_API_VERSION}")
+        );
+    }
+
+    #[test]
+    fn non_azure_provider_is_not_flagged() {
+        let provider = Provider::with_id("p".to_string(), "P".to_string(), json!({}), None);
+        assert!(!is_azure(&provider));
+    }
+}
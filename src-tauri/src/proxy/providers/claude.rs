@@ -429,6 +429,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
@@ -446,6 +447,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
@@ -2,10 +2,12 @@
 //!
 //! 仅透传模式，支持直连 OpenAI API
 //!
+//! 供应商标记为 Azure OpenAI 时按部署路由并使用 `api-key` 认证（见 `azure` 模块）
+//!
 //! ## 客户端检测
 //! 支持检测官方 Codex 客户端 (codex_vscode, codex_cli_rs)
 
-use super::{AuthInfo, AuthStrategy, ProviderAdapter};
+use super::{azure, AuthInfo, AuthStrategy, ProviderAdapter};
 use crate::provider::Provider;
 use crate::proxy::error::ProxyError;
 use regex::Regex;
@@ -133,8 +135,13 @@ impl ProviderAdapter for CodexAdapter {
     }
 
     fn extract_auth(&self, provider: &Provider) -> Option<AuthInfo> {
+        let strategy = if azure::is_azure(provider) {
+            AuthStrategy::AzureApiKey
+        } else {
+            AuthStrategy::Bearer
+        };
         self.extract_key(provider)
-            .map(|key| AuthInfo::new(key, AuthStrategy::Bearer))
+            .map(|key| AuthInfo::new(key, strategy))
     }
 
     fn build_url(&self, base_url: &str, endpoint: &str) -> String {
@@ -175,7 +182,11 @@ impl ProviderAdapter for CodexAdapter {
     }
 
     fn add_auth_headers(&self, request: RequestBuilder, auth: &AuthInfo) -> RequestBuilder {
-        request.header("Authorization", format!("Bearer {}", auth.api_key))
+        match auth.strategy {
+            // Azure OpenAI: api-key 头
+            AuthStrategy::AzureApiKey => request.header("api-key", &auth.api_key),
+            _ => request.header("Authorization", format!("Bearer {}", auth.api_key)),
+        }
     }
 }
 
@@ -198,6 +209,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
@@ -239,6 +251,30 @@ mod tests {
         assert_eq!(auth.api_key, "sk-env-key-12345678");
     }
 
+    #[test]
+    fn test_azure_provider_uses_api_key_header() {
+        let adapter = CodexAdapter::new();
+        let mut provider = create_provider(json!({
+            "auth": {
+                "OPENAI_API_KEY": "azure-key-12345678"
+            }
+        }));
+        provider.meta = Some(crate::provider::ProviderMeta {
+            provider_type: Some(azure::AZURE_PROVIDER_TYPE.to_string()),
+            ..Default::default()
+        });
+
+        let auth = adapter.extract_auth(&provider).unwrap();
+        assert_eq!(auth.strategy, AuthStrategy::AzureApiKey);
+
+        let request = adapter
+            .add_auth_headers(reqwest::Client::new().post("http://localhost/"), &auth)
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["api-key"], "azure-key-12345678");
+        assert!(request.headers().get("authorization").is_none());
+    }
+
     #[test]
     fn test_build_url() {
         let adapter = CodexAdapter::new();
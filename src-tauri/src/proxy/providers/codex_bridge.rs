@@ -0,0 +1,1422 @@
+//! Codex 协议桥接模块
+//!
+//! 当客户端与上游使用的 OpenAI 协议不一致时，在 Chat Completions 与
+//! Responses API 之间做请求体、非流式响应和 SSE 流的双向转换：
+//!
+//! - 客户端 `/v1/chat/completions`，上游仅支持 `/v1/responses`（`apiFormat = "openai_responses"`）
+//! - 客户端 `/v1/responses`，上游仅支持 `/v1/chat/completions`（`apiFormat = "openai_chat"`）
+//!
+//! 未设置 `meta.apiFormat` 的 Codex 供应商保持原样透传。
+
+use crate::provider::Provider;
+use crate::proxy::error::ProxyError;
+use bytes::Bytes;
+use futures::stream::{Stream, StreamExt};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Codex 请求需要的桥接方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodexBridge {
+    /// 客户端发送 Chat Completions，上游使用 Responses API
+    ChatViaResponses,
+    /// 客户端发送 Responses API，上游使用 Chat Completions
+    ResponsesViaChat,
+}
+
+/// 根据供应商的 `meta.apiFormat` 与客户端端点判断是否需要桥接
+pub fn get_codex_bridge(provider: &Provider, endpoint: &str) -> Option<CodexBridge> {
+    let api_format = provider.meta.as_ref()?.api_format.as_deref()?;
+    match (api_format, endpoint.trim_start_matches("/v1")) {
+        ("openai_responses", "/chat/completions") => Some(CodexBridge::ChatViaResponses),
+        ("openai_chat", "/responses") => Some(CodexBridge::ResponsesViaChat),
+        _ => None,
+    }
+}
+
+impl CodexBridge {
+    /// 上游实际请求的端点
+    pub fn upstream_endpoint(self) -> &'static str {
+        match self {
+            Self::ChatViaResponses => "/responses",
+            Self::ResponsesViaChat => "/chat/completions",
+        }
+    }
+
+    /// 转换请求体（客户端格式 → 上游格式）
+    pub fn transform_request(self, body: Value) -> Result<Value, ProxyError> {
+        match self {
+            Self::ChatViaResponses => chat_request_to_responses(body),
+            Self::ResponsesViaChat => responses_request_to_chat(body),
+        }
+    }
+
+    /// 转换非流式响应体（上游格式 → 客户端格式）
+    pub fn transform_response(self, body: Value) -> Result<Value, ProxyError> {
+        match self {
+            Self::ChatViaResponses => responses_response_to_chat(body),
+            Self::ResponsesViaChat => chat_response_to_responses(body),
+        }
+    }
+
+    /// 转换 SSE 流（上游格式 → 客户端格式）
+    pub fn transform_stream<S, E>(
+        self,
+        stream: S,
+    ) -> Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + Unpin>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+        E: std::fmt::Display + Send + 'static,
+    {
+        match self {
+            Self::ChatViaResponses => {
+                Box::new(Box::pin(create_chat_sse_stream_from_responses(stream)))
+            }
+            Self::ResponsesViaChat => {
+                Box::new(Box::pin(create_responses_sse_stream_from_chat(stream)))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// 通用辅助
+// ============================================================================
+
+/// 提取 Chat 消息内容中的纯文本（字符串或 text 片段数组）
+fn chat_content_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
+/// 将 Responses usage 映射为 Chat usage
+fn chat_usage_from_responses(usage: &Value) -> Value {
+    let input = usage
+        .get("input_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let output = usage
+        .get("output_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let mut result = json!({
+        "prompt_tokens": input,
+        "completion_tokens": output,
+        "total_tokens": usage
+            .get("total_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(input + output),
+    });
+    if let Some(cached) = usage
+        .pointer("/input_tokens_details/cached_tokens")
+        .and_then(|v| v.as_u64())
+    {
+        result["prompt_tokens_details"] = json!({ "cached_tokens": cached });
+    }
+    if let Some(reasoning) = usage
+        .pointer("/output_tokens_details/reasoning_tokens")
+        .and_then(|v| v.as_u64())
+    {
+        result["completion_tokens_details"] = json!({ "reasoning_tokens": reasoning });
+    }
+    result
+}
+
+/// 将 Chat usage 映射为 Responses usage
+fn responses_usage_from_chat(usage: &Value) -> Value {
+    let input = usage
+        .get("prompt_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let output = usage
+        .get("completion_tokens")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let mut result = json!({
+        "input_tokens": input,
+        "output_tokens": output,
+        "total_tokens": usage
+            .get("total_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(input + output),
+    });
+    if let Some(cached) = usage
+        .pointer("/prompt_tokens_details/cached_tokens")
+        .and_then(|v| v.as_u64())
+    {
+        result["input_tokens_details"] = json!({ "cached_tokens": cached });
+    }
+    if let Some(reasoning) = usage
+        .pointer("/completion_tokens_details/reasoning_tokens")
+        .and_then(|v| v.as_u64())
+    {
+        result["output_tokens_details"] = json!({ "reasoning_tokens": reasoning });
+    }
+    result
+}
+
+/// Responses 状态 → Chat finish_reason
+fn chat_finish_reason(
+    status: Option<&str>,
+    incomplete_reason: Option<&str>,
+    has_tool: bool,
+) -> &'static str {
+    if has_tool {
+        return "tool_calls";
+    }
+    match (status, incomplete_reason) {
+        (Some("incomplete"), Some("max_output_tokens")) => "length",
+        (Some("incomplete"), Some("content_filter")) => "content_filter",
+        _ => "stop",
+    }
+}
+
+/// Chat finish_reason → Responses 状态与 incomplete_details
+fn responses_status(finish_reason: Option<&str>) -> (&'static str, Value) {
+    match finish_reason {
+        Some("length") => ("incomplete", json!({ "reason": "max_output_tokens" })),
+        Some("content_filter") => ("incomplete", json!({ "reason": "content_filter" })),
+        _ => ("completed", Value::Null),
+    }
+}
+
+fn now_unix() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+// ============================================================================
+// 请求体转换
+// ============================================================================
+
+/// Chat Completions 请求 → Responses 请求
+pub fn chat_request_to_responses(body: Value) -> Result<Value, ProxyError> {
+    let obj = body
+        .as_object()
+        .ok_or_else(|| ProxyError::TransformError("请求体必须是 JSON 对象".to_string()))?;
+
+    let mut result = Map::new();
+    let mut instructions: Vec<String> = Vec::new();
+    let mut input: Vec<Value> = Vec::new();
+
+    for message in obj
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let role = message
+            .get("role")
+            .and_then(|r| r.as_str())
+            .unwrap_or("user");
+        match role {
+            "system" | "developer" => {
+                let text = chat_content_text(message.get("content"));
+                if !text.is_empty() {
+                    instructions.push(text);
+                }
+            }
+            "tool" => input.push(json!({
+                "type": "function_call_output",
+                "call_id": message.get("tool_call_id").cloned().unwrap_or(Value::Null),
+                "output": chat_content_text(message.get("content")),
+            })),
+            _ => {
+                let text_type = if role == "assistant" {
+                    "output_text"
+                } else {
+                    "input_text"
+                };
+                let content: Vec<Value> = match message.get("content") {
+                    Some(Value::String(text)) if !text.is_empty() => {
+                        vec![json!({ "type": text_type, "text": text })]
+                    }
+                    Some(Value::Array(parts)) => parts
+                        .iter()
+                        .filter_map(|part| match part.get("type").and_then(|t| t.as_str()) {
+                            Some("text") => Some(json!({
+                                "type": text_type,
+                                "text": part.get("text").cloned().unwrap_or(Value::Null),
+                            })),
+                            Some("image_url") => {
+                                let url = part
+                                    .get("image_url")
+                                    .and_then(|i| i.get("url").or(Some(i)))
+                                    .cloned()?;
+                                Some(json!({ "type": "input_image", "image_url": url }))
+                            }
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                if !content.is_empty() {
+                    input.push(json!({ "type": "message", "role": role, "content": content }));
+                }
+
+                for call in message
+                    .get("tool_calls")
+                    .and_then(|t| t.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    input.push(json!({
+                        "type": "function_call",
+                        "call_id": call.get("id").cloned().unwrap_or(Value::Null),
+                        "name": call.pointer("/function/name").cloned().unwrap_or(Value::Null),
+                        "arguments": call
+                            .pointer("/function/arguments")
+                            .cloned()
+                            .unwrap_or_else(|| json!("{}")),
+                    }));
+                }
+            }
+        }
+    }
+
+    if !instructions.is_empty() {
+        result.insert("instructions".to_string(), json!(instructions.join("\n\n")));
+    }
+    result.insert("input".to_string(), Value::Array(input));
+
+    for (key, value) in obj {
+        match key.as_str() {
+            "messages" | "stream_options" | "n" => {}
+            "max_tokens" | "max_completion_tokens" => {
+                result.insert("max_output_tokens".to_string(), value.clone());
+            }
+            "reasoning_effort" => {
+                result.insert("reasoning".to_string(), json!({ "effort": value }));
+            }
+            "tools" => {
+                let tools: Vec<Value> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|tool| {
+                        let function = tool.get("function")?;
+                        let mut converted = json!({
+                            "type": "function",
+                            "name": function.get("name").cloned().unwrap_or(Value::Null),
+                            "parameters": function
+                                .get("parameters")
+                                .cloned()
+                                .unwrap_or_else(|| json!({ "type": "object" })),
+                        });
+                        if let Some(description) = function.get("description") {
+                            converted["description"] = description.clone();
+                        }
+                        if let Some(strict) = function.get("strict") {
+                            converted["strict"] = strict.clone();
+                        }
+                        Some(converted)
+                    })
+                    .collect();
+                result.insert("tools".to_string(), Value::Array(tools));
+            }
+            "tool_choice" => {
+                let choice = match value.pointer("/function/name") {
+                    Some(name) => json!({ "type": "function", "name": name }),
+                    None => value.clone(),
+                };
+                result.insert("tool_choice".to_string(), choice);
+            }
+            _ => {
+                result.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    Ok(Value::Object(result))
+}
+
+/// Responses 输入片段 → Chat 用户消息片段
+fn chat_part_from_responses(part: &Value) -> Option<Value> {
+    match part.get("type").and_then(|t| t.as_str()) {
+        Some("input_text") | Some("output_text") => Some(json!({
+            "type": "text",
+            "text": part.get("text").cloned().unwrap_or(Value::Null),
+        })),
+        Some("input_image") => Some(json!({
+            "type": "image_url",
+            "image_url": { "url": part.get("image_url").cloned()? },
+        })),
+        _ => None,
+    }
+}
+
+/// Responses 请求 → Chat Completions 请求
+pub fn responses_request_to_chat(body: Value) -> Result<Value, ProxyError> {
+    let obj = body
+        .as_object()
+        .ok_or_else(|| ProxyError::TransformError("请求体必须是 JSON 对象".to_string()))?;
+
+    let mut result = Map::new();
+    let mut messages: Vec<Value> = Vec::new();
+
+    if let Some(instructions) = obj.get("instructions").and_then(|i| i.as_str()) {
+        if !instructions.is_empty() {
+            messages.push(json!({ "role": "system", "content": instructions }));
+        }
+    }
+
+    match obj.get("input") {
+        Some(Value::String(text)) => messages.push(json!({ "role": "user", "content": text })),
+        Some(Value::Array(items)) => {
+            for item in items {
+                let item_type = item
+                    .get("type")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or("message");
+                match item_type {
+                    "message" => {
+                        let role = item.get("role").and_then(|r| r.as_str()).unwrap_or("user");
+                        let role = if role == "developer" { "system" } else { role };
+                        let content = match item.get("content") {
+                            Some(Value::Array(parts)) if role == "user" => Value::Array(
+                                parts.iter().filter_map(chat_part_from_responses).collect(),
+                            ),
+                            other => json!(chat_content_text(other)),
+                        };
+                        messages.push(json!({ "role": role, "content": content }));
+                    }
+                    "function_call" => {
+                        let call = json!({
+                            "id": item.get("call_id").cloned().unwrap_or(Value::Null),
+                            "type": "function",
+                            "function": {
+                                "name": item.get("name").cloned().unwrap_or(Value::Null),
+                                "arguments": item
+                                    .get("arguments")
+                                    .cloned()
+                                    .unwrap_or_else(|| json!("{}")),
+                            },
+                        });
+                        // 连续的 function_call 合并到同一条 assistant 消息
+                        match messages.last_mut() {
+                            Some(last)
+                                if last.get("role").and_then(|r| r.as_str())
+                                    == Some("assistant") =>
+                            {
+                                match last.get_mut("tool_calls").and_then(|t| t.as_array_mut()) {
+                                    Some(calls) => calls.push(call),
+                                    None => last["tool_calls"] = json!([call]),
+                                }
+                            }
+                            _ => messages.push(json!({
+                                "role": "assistant",
+                                "content": Value::Null,
+                                "tool_calls": [call],
+                            })),
+                        }
+                    }
+                    "function_call_output" => messages.push(json!({
+                        "role": "tool",
+                        "tool_call_id": item.get("call_id").cloned().unwrap_or(Value::Null),
+                        "content": match item.get("output") {
+                            Some(Value::String(text)) => text.clone(),
+                            Some(other) => other.to_string(),
+                            None => String::new(),
+                        },
+                    })),
+                    other => {
+                        log::debug!("[Codex/Bridge] 忽略 Chat Completions 不支持的输入项: {other}");
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    result.insert("messages".to_string(), Value::Array(messages));
+
+    for (key, value) in obj {
+        match key.as_str() {
+            "input"
+            | "instructions"
+            | "store"
+            | "previous_response_id"
+            | "include"
+            | "truncation"
+            | "text" => {}
+            "max_output_tokens" => {
+                result.insert("max_tokens".to_string(), value.clone());
+            }
+            "reasoning" => {
+                if let Some(effort) = value.get("effort") {
+                    result.insert("reasoning_effort".to_string(), effort.clone());
+                }
+            }
+            "tools" => {
+                let tools: Vec<Value> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|tool| tool.get("type").and_then(|t| t.as_str()) == Some("function"))
+                    .map(|tool| {
+                        let mut function = json!({
+                            "name": tool.get("name").cloned().unwrap_or(Value::Null),
+                            "parameters": tool
+                                .get("parameters")
+                                .cloned()
+                                .unwrap_or_else(|| json!({ "type": "object" })),
+                        });
+                        if let Some(description) = tool.get("description") {
+                            function["description"] = description.clone();
+                        }
+                        if let Some(strict) = tool.get("strict") {
+                            function["strict"] = strict.clone();
+                        }
+                        json!({ "type": "function", "function": function })
+                    })
+                    .collect();
+                if !tools.is_empty() {
+                    result.insert("tools".to_string(), Value::Array(tools));
+                }
+            }
+            "tool_choice" => {
+                let choice = match (
+                    value.get("type").and_then(|t| t.as_str()),
+                    value.get("name"),
+                ) {
+                    (Some("function"), Some(name)) => {
+                        json!({ "type": "function", "function": { "name": name } })
+                    }
+                    _ => value.clone(),
+                };
+                result.insert("tool_choice".to_string(), choice);
+            }
+            _ => {
+                result.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    // 流式请求需要显式要求上游在最后一个 chunk 返回 usage
+    if obj.get("stream").and_then(|s| s.as_bool()) == Some(true) {
+        result.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
+    }
+
+    Ok(Value::Object(result))
+}
+
+// ============================================================================
+// 非流式响应转换
+// ============================================================================
+
+/// Responses 响应 → Chat Completions 响应
+pub fn responses_response_to_chat(body: Value) -> Result<Value, ProxyError> {
+    let mut text = String::new();
+    let mut tool_calls: Vec<Value> = Vec::new();
+
+    for item in body
+        .get("output")
+        .and_then(|o| o.as_array())
+        .into_iter()
+        .flatten()
+    {
+        match item.get("type").and_then(|t| t.as_str()) {
+            Some("message") => {
+                for part in item
+                    .get("content")
+                    .and_then(|c| c.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    if part.get("type").and_then(|t| t.as_str()) == Some("output_text") {
+                        text.push_str(part.get("text").and_then(|t| t.as_str()).unwrap_or(""));
+                    }
+                }
+            }
+            Some("function_call") => tool_calls.push(json!({
+                "id": item.get("call_id").cloned().unwrap_or(Value::Null),
+                "type": "function",
+                "function": {
+                    "name": item.get("name").cloned().unwrap_or(Value::Null),
+                    "arguments": item.get("arguments").cloned().unwrap_or_else(|| json!("{}")),
+                },
+            })),
+            _ => {}
+        }
+    }
+
+    let finish_reason = chat_finish_reason(
+        body.get("status").and_then(|s| s.as_str()),
+        body.pointer("/incomplete_details/reason")
+            .and_then(|r| r.as_str()),
+        !tool_calls.is_empty(),
+    );
+
+    let content = if text.is_empty() && !tool_calls.is_empty() {
+        Value::Null
+    } else {
+        json!(text)
+    };
+    let mut message = json!({ "role": "assistant", "content": content });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = Value::Array(tool_calls);
+    }
+
+    let mut result = json!({
+        "id": body.get("id").cloned().unwrap_or(Value::Null),
+        "object": "chat.completion",
+        "created": body.get("created_at").cloned().unwrap_or_else(|| json!(now_unix())),
+        "model": body.get("model").cloned().unwrap_or(Value::Null),
+        "choices": [{
+            "index": 0,
+            "message": message,
+            "finish_reason": finish_reason,
+        }],
+    });
+    if let Some(usage) = body.get("usage") {
+        result["usage"] = chat_usage_from_responses(usage);
+    }
+    Ok(result)
+}
+
+/// Chat Completions 响应 → Responses 响应
+pub fn chat_response_to_responses(body: Value) -> Result<Value, ProxyError> {
+    let choice = body
+        .pointer("/choices/0")
+        .ok_or_else(|| ProxyError::TransformError("Chat 响应缺少 choices".to_string()))?;
+    let message = choice.get("message").cloned().unwrap_or(Value::Null);
+    let id = body.get("id").and_then(|i| i.as_str()).unwrap_or("chat");
+
+    let mut output: Vec<Value> = Vec::new();
+    let text = chat_content_text(message.get("content"));
+    if !text.is_empty() {
+        output.push(json!({
+            "type": "message",
+            "id": format!("msg_{id}"),
+            "role": "assistant",
+            "status": "completed",
+            "content": [{ "type": "output_text", "text": text, "annotations": [] }],
+        }));
+    }
+    for call in message
+        .get("tool_calls")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let call_id = call.get("id").and_then(|i| i.as_str()).unwrap_or_default();
+        output.push(json!({
+            "type": "function_call",
+            "id": format!("fc_{call_id}"),
+            "call_id": call_id,
+            "name": call.pointer("/function/name").cloned().unwrap_or(Value::Null),
+            "arguments": call
+                .pointer("/function/arguments")
+                .cloned()
+                .unwrap_or_else(|| json!("{}")),
+            "status": "completed",
+        }));
+    }
+
+    let (status, incomplete_details) =
+        responses_status(choice.get("finish_reason").and_then(|f| f.as_str()));
+
+    let mut result = json!({
+        "id": format!("resp_{id}"),
+        "object": "response",
+        "created_at": body.get("created").cloned().unwrap_or_else(|| json!(now_unix())),
+        "model": body.get("model").cloned().unwrap_or(Value::Null),
+        "status": status,
+        "incomplete_details": incomplete_details,
+        "output": output,
+    });
+    if let Some(usage) = body.get("usage") {
+        result["usage"] = responses_usage_from_chat(usage);
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// 流式转换
+// ============================================================================
+
+/// 从缓冲区中取出完整的 SSE 块，返回 (event, data)
+fn drain_sse_blocks(buffer: &mut String) -> Vec<(Option<String>, String)> {
+    let mut blocks = Vec::new();
+    while let Some(pos) = buffer.find("\n\n") {
+        let block = buffer[..pos].to_string();
+        *buffer = buffer[pos + 2..].to_string();
+
+        let mut event_type: Option<String> = None;
+        let mut data_parts: Vec<&str> = Vec::new();
+        for line in block.lines() {
+            if let Some(evt) = line.strip_prefix("event:") {
+                event_type = Some(evt.trim().to_string());
+            } else if let Some(d) = line.strip_prefix("data:") {
+                data_parts.push(d.strip_prefix(' ').unwrap_or(d));
+            }
+        }
+        if !data_parts.is_empty() {
+            blocks.push((event_type, data_parts.join("\n")));
+        }
+    }
+    blocks
+}
+
+fn chat_chunk(
+    id: &str,
+    created: &Value,
+    model: &Value,
+    delta: Value,
+    finish_reason: Value,
+) -> Value {
+    json!({
+        "id": id,
+        "object": "chat.completion.chunk",
+        "created": created,
+        "model": model,
+        "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+    })
+}
+
+fn chat_chunk_bytes(chunk: &Value) -> Bytes {
+    Bytes::from(format!("data: {chunk}\n\n"))
+}
+
+fn responses_event_bytes(event: &Value) -> Bytes {
+    let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    Bytes::from(format!("event: {event_type}\ndata: {event}\n\n"))
+}
+
+/// Responses API SSE → Chat Completions SSE
+pub fn create_chat_sse_stream_from_responses<S, E>(
+    stream: S,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: std::fmt::Display + Send + 'static,
+{
+    async_stream::stream! {
+        let mut buffer = String::new();
+        let mut id = String::from("chatcmpl");
+        let mut model = Value::Null;
+        let mut created = json!(now_unix());
+        let mut sent_role = false;
+        let mut finished = false;
+        // item_id / output_index → tool_calls 下标
+        let mut tool_index_by_item: BTreeMap<String, usize> = BTreeMap::new();
+        let mut tool_count: usize = 0;
+
+        tokio::pin!(stream);
+
+        while let Some(chunk) = stream.next().await {
+            let bytes = match chunk {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    log::error!("[Codex/Bridge] Responses 流读取失败: {e}");
+                    yield Ok(chat_chunk_bytes(&json!({
+                        "error": { "type": "stream_error", "message": format!("Stream error: {e}") }
+                    })));
+                    break;
+                }
+            };
+            buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+            for (event_type, data) in drain_sse_blocks(&mut buffer) {
+                let Ok(data) = serde_json::from_str::<Value>(&data) else {
+                    continue;
+                };
+                let event_type = event_type
+                    .or_else(|| data.get("type").and_then(|t| t.as_str()).map(str::to_string))
+                    .unwrap_or_default();
+
+                match event_type.as_str() {
+                    "response.created" => {
+                        let response = data.get("response").unwrap_or(&data);
+                        if let Some(resp_id) = response.get("id").and_then(|i| i.as_str()) {
+                            id = resp_id.to_string();
+                        }
+                        if let Some(m) = response.get("model") {
+                            model = m.clone();
+                        }
+                        if let Some(c) = response.get("created_at") {
+                            created = c.clone();
+                        }
+                    }
+                    "response.output_text.delta" => {
+                        let delta = data.get("delta").and_then(|d| d.as_str()).unwrap_or("");
+                        let mut chunk_delta = json!({ "content": delta });
+                        if !sent_role {
+                            chunk_delta["role"] = json!("assistant");
+                            sent_role = true;
+                        }
+                        let chunk = chat_chunk(&id, &created, &model, chunk_delta, Value::Null);
+                        yield Ok(chat_chunk_bytes(&chunk));
+                    }
+                    "response.output_item.added" => {
+                        let Some(item) = data.get("item") else { continue };
+                        if item.get("type").and_then(|t| t.as_str()) != Some("function_call") {
+                            continue;
+                        }
+                        let index = tool_count;
+                        tool_count += 1;
+                        if let Some(item_id) = item.get("id").and_then(|i| i.as_str()) {
+                            tool_index_by_item.insert(item_id.to_string(), index);
+                        }
+                        if let Some(output_index) =
+                            data.get("output_index").and_then(|o| o.as_u64())
+                        {
+                            tool_index_by_item.insert(format!("out:{output_index}"), index);
+                        }
+                        let mut chunk_delta = json!({
+                            "tool_calls": [{
+                                "index": index,
+                                "id": item.get("call_id").cloned().unwrap_or(Value::Null),
+                                "type": "function",
+                                "function": {
+                                    "name": item.get("name").cloned().unwrap_or(Value::Null),
+                                    "arguments": "",
+                                },
+                            }],
+                        });
+                        if !sent_role {
+                            chunk_delta["role"] = json!("assistant");
+                            sent_role = true;
+                        }
+                        let chunk = chat_chunk(&id, &created, &model, chunk_delta, Value::Null);
+                        yield Ok(chat_chunk_bytes(&chunk));
+                    }
+                    "response.function_call_arguments.delta" => {
+                        let index = data
+                            .get("item_id")
+                            .and_then(|i| i.as_str())
+                            .and_then(|item_id| tool_index_by_item.get(item_id))
+                            .or_else(|| {
+                                data.get("output_index")
+                                    .and_then(|o| o.as_u64())
+                                    .and_then(|o| tool_index_by_item.get(&format!("out:{o}")))
+                            })
+                            .copied()
+                            .unwrap_or(tool_count.saturating_sub(1));
+                        let delta = data.get("delta").and_then(|d| d.as_str()).unwrap_or("");
+                        yield Ok(chat_chunk_bytes(&chat_chunk(
+                            &id,
+                            &created,
+                            &model,
+                            json!({
+                                "tool_calls": [{ "index": index, "function": { "arguments": delta } }],
+                            }),
+                            Value::Null,
+                        )));
+                    }
+                    "response.completed" | "response.incomplete" => {
+                        let response = data.get("response").unwrap_or(&data);
+                        let finish_reason = chat_finish_reason(
+                            response.get("status").and_then(|s| s.as_str()),
+                            response
+                                .pointer("/incomplete_details/reason")
+                                .and_then(|r| r.as_str()),
+                            tool_count > 0,
+                        );
+                        let delta = if sent_role {
+                            json!({})
+                        } else {
+                            json!({ "role": "assistant" })
+                        };
+                        let chunk = chat_chunk(&id, &created, &model, delta, json!(finish_reason));
+                        yield Ok(chat_chunk_bytes(&chunk));
+
+                        if let Some(usage) = response.get("usage") {
+                            yield Ok(chat_chunk_bytes(&json!({
+                                "id": id,
+                                "object": "chat.completion.chunk",
+                                "created": created,
+                                "model": model,
+                                "choices": [],
+                                "usage": chat_usage_from_responses(usage),
+                            })));
+                        }
+                        yield Ok(Bytes::from("data: [DONE]\n\n"));
+                        finished = true;
+                    }
+                    "response.failed" | "error" => {
+                        let error = data
+                            .pointer("/response/error")
+                            .or_else(|| data.get("error"))
+                            .cloned()
+                            .unwrap_or_else(|| json!({ "message": "upstream response failed" }));
+                        log::warn!("[Codex/Bridge] 上游 Responses 流返回错误: {error}");
+                        yield Ok(chat_chunk_bytes(&json!({ "error": error })));
+                        yield Ok(Bytes::from("data: [DONE]\n\n"));
+                        finished = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if !finished {
+            log::debug!("[Codex/Bridge] Responses 流未收到完成事件，补发 [DONE]");
+            yield Ok(Bytes::from("data: [DONE]\n\n"));
+        }
+    }
+}
+
+/// Chat 流中正在累积的工具调用
+struct PendingToolCall {
+    item_id: String,
+    call_id: String,
+    name: String,
+    arguments: String,
+    output_index: usize,
+}
+
+/// Chat Completions SSE → Responses API SSE
+pub fn create_responses_sse_stream_from_chat<S, E>(
+    stream: S,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: std::fmt::Display + Send + 'static,
+{
+    async_stream::stream! {
+        let mut buffer = String::new();
+        let mut response_id: Option<String> = None;
+        let mut model = Value::Null;
+        let mut created = json!(now_unix());
+        let mut next_output_index: usize = 0;
+        // 文本消息项：(item_id, output_index, 已累积文本)
+        let mut message_item: Option<(String, usize, String)> = None;
+        let mut tool_calls: BTreeMap<u64, PendingToolCall> = BTreeMap::new();
+        let mut finish_reason: Option<String> = None;
+        let mut usage: Option<Value> = None;
+        let mut done = false;
+
+        tokio::pin!(stream);
+
+        while let Some(chunk) = stream.next().await {
+            let bytes = match chunk {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    log::error!("[Codex/Bridge] Chat 流读取失败: {e}");
+                    yield Ok(responses_event_bytes(&json!({
+                        "type": "error",
+                        "message": format!("Stream error: {e}"),
+                    })));
+                    done = true;
+                    break;
+                }
+            };
+            buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+            for (_, data) in drain_sse_blocks(&mut buffer) {
+                if data.trim() == "[DONE]" {
+                    done = true;
+                    break;
+                }
+                let Ok(data) = serde_json::from_str::<Value>(&data) else {
+                    continue;
+                };
+
+                if response_id.is_none() {
+                    let chat_id = data.get("id").and_then(|i| i.as_str()).unwrap_or("chat");
+                    let resp_id = if chat_id.starts_with("resp_") {
+                        chat_id.to_string()
+                    } else {
+                        format!("resp_{chat_id}")
+                    };
+                    if let Some(m) = data.get("model") {
+                        model = m.clone();
+                    }
+                    if let Some(c) = data.get("created") {
+                        created = c.clone();
+                    }
+                    yield Ok(responses_event_bytes(&json!({
+                        "type": "response.created",
+                        "response": {
+                            "id": resp_id,
+                            "object": "response",
+                            "created_at": created,
+                            "model": model,
+                            "status": "in_progress",
+                            "output": [],
+                        },
+                    })));
+                    response_id = Some(resp_id);
+                }
+
+                if let Some(u) = data.get("usage").filter(|u| !u.is_null()) {
+                    usage = Some(u.clone());
+                }
+
+                let Some(choice) = data.pointer("/choices/0") else {
+                    continue;
+                };
+                if let Some(reason) = choice.get("finish_reason").and_then(|f| f.as_str()) {
+                    finish_reason = Some(reason.to_string());
+                }
+                let delta = choice.get("delta").cloned().unwrap_or(Value::Null);
+
+                let text = delta
+                    .get("content")
+                    .and_then(|c| c.as_str())
+                    .filter(|t| !t.is_empty());
+                if let Some(text) = text {
+                    if message_item.is_none() {
+                        let item_id = format!("msg_{}", response_id.as_deref().unwrap_or_default());
+                        let output_index = next_output_index;
+                        next_output_index += 1;
+                        yield Ok(responses_event_bytes(&json!({
+                            "type": "response.output_item.added",
+                            "output_index": output_index,
+                            "item": {
+                                "type": "message",
+                                "id": item_id,
+                                "role": "assistant",
+                                "status": "in_progress",
+                                "content": [],
+                            },
+                        })));
+                        yield Ok(responses_event_bytes(&json!({
+                            "type": "response.content_part.added",
+                            "item_id": item_id,
+                            "output_index": output_index,
+                            "content_index": 0,
+                            "part": { "type": "output_text", "text": "", "annotations": [] },
+                        })));
+                        message_item = Some((item_id, output_index, String::new()));
+                    }
+                    if let Some((item_id, output_index, accumulated)) = message_item.as_mut() {
+                        accumulated.push_str(text);
+                        yield Ok(responses_event_bytes(&json!({
+                            "type": "response.output_text.delta",
+                            "item_id": item_id,
+                            "output_index": output_index,
+                            "content_index": 0,
+                            "delta": text,
+                        })));
+                    }
+                }
+
+                for call in delta
+                    .get("tool_calls")
+                    .and_then(|t| t.as_array())
+                    .into_iter()
+                    .flatten()
+                {
+                    let index = call.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+                    if !tool_calls.contains_key(&index) {
+                        let call_id = call
+                            .get("id")
+                            .and_then(|i| i.as_str())
+                            .map(str::to_string)
+                            .unwrap_or_else(|| format!("call_{index}"));
+                        let pending = PendingToolCall {
+                            item_id: format!("fc_{call_id}"),
+                            name: call
+                                .pointer("/function/name")
+                                .and_then(|n| n.as_str())
+                                .unwrap_or_default()
+                                .to_string(),
+                            call_id,
+                            arguments: String::new(),
+                            output_index: next_output_index,
+                        };
+                        next_output_index += 1;
+                        yield Ok(responses_event_bytes(&json!({
+                            "type": "response.output_item.added",
+                            "output_index": pending.output_index,
+                            "item": {
+                                "type": "function_call",
+                                "id": pending.item_id,
+                                "call_id": pending.call_id,
+                                "name": pending.name,
+                                "arguments": "",
+                                "status": "in_progress",
+                            },
+                        })));
+                        tool_calls.insert(index, pending);
+                    }
+                    if let (Some(pending), Some(arguments)) = (
+                        tool_calls.get_mut(&index),
+                        call.pointer("/function/arguments").and_then(|a| a.as_str()),
+                    ) {
+                        if !arguments.is_empty() {
+                            pending.arguments.push_str(arguments);
+                            yield Ok(responses_event_bytes(&json!({
+                                "type": "response.function_call_arguments.delta",
+                                "item_id": pending.item_id,
+                                "output_index": pending.output_index,
+                                "delta": arguments,
+                            })));
+                        }
+                    }
+                }
+            }
+
+            if done {
+                break;
+            }
+        }
+
+        if response_id.is_none() {
+            log::warn!("[Codex/Bridge] Chat 流未返回任何数据");
+            return;
+        }
+
+        // 收尾：关闭所有输出项并发送 response.completed
+        let mut output: Vec<(usize, Value)> = Vec::new();
+        if let Some((item_id, output_index, text)) = message_item.take() {
+            yield Ok(responses_event_bytes(&json!({
+                "type": "response.output_text.done",
+                "item_id": item_id,
+                "output_index": output_index,
+                "content_index": 0,
+                "text": text,
+            })));
+            yield Ok(responses_event_bytes(&json!({
+                "type": "response.content_part.done",
+                "item_id": item_id,
+                "output_index": output_index,
+                "content_index": 0,
+                "part": { "type": "output_text", "text": text, "annotations": [] },
+            })));
+            let item = json!({
+                "type": "message",
+                "id": item_id,
+                "role": "assistant",
+                "status": "completed",
+                "content": [{ "type": "output_text", "text": text, "annotations": [] }],
+            });
+            yield Ok(responses_event_bytes(&json!({
+                "type": "response.output_item.done",
+                "output_index": output_index,
+                "item": item,
+            })));
+            output.push((output_index, item));
+        }
+        for pending in tool_calls.into_values() {
+            yield Ok(responses_event_bytes(&json!({
+                "type": "response.function_call_arguments.done",
+                "item_id": pending.item_id,
+                "output_index": pending.output_index,
+                "arguments": pending.arguments,
+            })));
+            let item = json!({
+                "type": "function_call",
+                "id": pending.item_id,
+                "call_id": pending.call_id,
+                "name": pending.name,
+                "arguments": pending.arguments,
+                "status": "completed",
+            });
+            yield Ok(responses_event_bytes(&json!({
+                "type": "response.output_item.done",
+                "output_index": pending.output_index,
+                "item": item,
+            })));
+            output.push((pending.output_index, item));
+        }
+        output.sort_by_key(|(index, _)| *index);
+
+        let (status, incomplete_details) = responses_status(finish_reason.as_deref());
+        let mut response = json!({
+            "id": response_id,
+            "object": "response",
+            "created_at": created,
+            "model": model,
+            "status": status,
+            "incomplete_details": incomplete_details,
+            "output": output.into_iter().map(|(_, item)| item).collect::<Vec<_>>(),
+        });
+        if let Some(usage) = usage.as_ref() {
+            response["usage"] = responses_usage_from_chat(usage);
+        }
+        let event_type = if status == "completed" {
+            "response.completed"
+        } else {
+            "response.incomplete"
+        };
+        yield Ok(responses_event_bytes(&json!({
+            "type": event_type,
+            "response": response,
+        })));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use futures::stream;
+
+    const SAMPLE_RESPONSES_STREAM: &str = concat!(
+        "event: response.created\n",
+        "data: {\"type\":\"response.created\",\"response\":{\"id\":\"resp_abc\",\"object\":\"response\",\"created_at\":1700000000,\"model\":\"gpt-5-codex\",\"status\":\"in_progress\",\"output\":[]}}\n\n",
+        "event: response.output_item.added\n",
+        "data: {\"type\":\"response.output_item.added\",\"output_index\":0,\"item\":{\"type\":\"message\",\"id\":\"msg_1\",\"role\":\"assistant\",\"content\":[]}}\n\n",
+        "event: response.content_part.added\n",
+        "data: {\"type\":\"response.content_part.added\",\"item_id\":\"msg_1\",\"output_index\":0,\"content_index\":0,\"part\":{\"type\":\"output_text\",\"text\":\"\"}}\n\n",
+        "event: response.output_text.delta\n",
+        "data: {\"type\":\"response.output_text.delta\",\"item_id\":\"msg_1\",\"output_index\":0,\"content_index\":0,\"delta\":\"Hello\"}\n\n",
+        "event: response.output_text.delta\n",
+        "data: {\"type\":\"response.output_text.delta\",\"item_id\":\"msg_1\",\"output_index\":0,\"content_index\":0,\"delta\":\" world\"}\n\n",
+        "event: response.output_text.done\n",
+        "data: {\"type\":\"response.output_text.done\",\"item_id\":\"msg_1\",\"output_index\":0,\"content_index\":0,\"text\":\"Hello world\"}\n\n",
+        "event: response.output_item.done\n",
+        "data: {\"type\":\"response.output_item.done\",\"output_index\":0,\"item\":{\"type\":\"message\",\"id\":\"msg_1\",\"role\":\"assistant\",\"content\":[{\"type\":\"output_text\",\"text\":\"Hello world\"}]}}\n\n",
+        "event: response.completed\n",
+        "data: {\"type\":\"response.completed\",\"response\":{\"id\":\"resp_abc\",\"status\":\"completed\",\"model\":\"gpt-5-codex\",\"usage\":{\"input_tokens\":12,\"input_tokens_details\":{\"cached_tokens\":4},\"output_tokens\":5,\"total_tokens\":17}}}\n\n"
+    );
+
+    const SAMPLE_TOOL_STREAM: &str = concat!(
+        "event: response.created\n",
+        "data: {\"type\":\"response.created\",\"response\":{\"id\":\"resp_tool\",\"model\":\"gpt-5-codex\"}}\n\n",
+        "event: response.output_item.added\n",
+        "data: {\"type\":\"response.output_item.added\",\"output_index\":0,\"item\":{\"type\":\"function_call\",\"id\":\"fc_1\",\"call_id\":\"call_1\",\"name\":\"shell\",\"arguments\":\"\"}}\n\n",
+        "event: response.function_call_arguments.delta\n",
+        "data: {\"type\":\"response.function_call_arguments.delta\",\"item_id\":\"fc_1\",\"output_index\":0,\"delta\":\"{\\\"cmd\\\":\"}\n\n",
+        "event: response.function_call_arguments.delta\n",
+        "data: {\"type\":\"response.function_call_arguments.delta\",\"item_id\":\"fc_1\",\"output_index\":0,\"delta\":\"\\\"ls\\\"}\"}\n\n",
+        "event: response.completed\n",
+        "data: {\"type\":\"response.completed\",\"response\":{\"id\":\"resp_tool\",\"status\":\"completed\",\"usage\":{\"input_tokens\":20,\"output_tokens\":7}}}\n\n"
+    );
+
+    async fn collect(stream: impl Stream<Item = Result<Bytes, std::io::Error>>) -> String {
+        let chunks: Vec<_> = stream.collect().await;
+        chunks
+            .into_iter()
+            .map(|c| String::from_utf8_lossy(c.unwrap().as_ref()).to_string())
+            .collect()
+    }
+
+    fn data_values(sse: &str) -> Vec<Value> {
+        sse.split("\n\n")
+            .filter_map(|block| {
+                let data = block.lines().find_map(|line| line.strip_prefix("data: "))?;
+                serde_json::from_str::<Value>(data).ok()
+            })
+            .collect()
+    }
+
+    fn upstream(input: &str) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+        // 按小块切分，覆盖跨 chunk 的 SSE 缓冲逻辑
+        let chunks: Vec<Result<Bytes, std::io::Error>> = input
+            .as_bytes()
+            .chunks(37)
+            .map(|c| Ok(Bytes::from(c.to_vec())))
+            .collect();
+        stream::iter(chunks)
+    }
+
+    fn provider_with_format(api_format: Option<&str>) -> Provider {
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "Relay".to_string(),
+            json!({ "auth": {}, "config": "" }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            api_format: api_format.map(str::to_string),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn bridge_selection_follows_api_format_and_endpoint() {
+        let responses_only = provider_with_format(Some("openai_responses"));
+        assert_eq!(
+            get_codex_bridge(&responses_only, "/chat/completions"),
+            Some(CodexBridge::ChatViaResponses)
+        );
+        assert_eq!(get_codex_bridge(&responses_only, "/responses"), None);
+
+        let chat_only = provider_with_format(Some("openai_chat"));
+        assert_eq!(
+            get_codex_bridge(&chat_only, "/responses"),
+            Some(CodexBridge::ResponsesViaChat)
+        );
+        assert_eq!(get_codex_bridge(&chat_only, "/responses/compact"), None);
+
+        assert_eq!(
+            get_codex_bridge(&provider_with_format(None), "/chat/completions"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn responses_stream_maps_text_deltas_and_usage_to_chat_chunks() {
+        let chat = collect(create_chat_sse_stream_from_responses(upstream(
+            SAMPLE_RESPONSES_STREAM,
+        )))
+        .await;
+        let chunks = data_values(&chat);
+
+        let content: String = chunks
+            .iter()
+            .filter_map(|c| {
+                c.pointer("/choices/0/delta/content")
+                    .and_then(|v| v.as_str())
+            })
+            .collect();
+        assert_eq!(content, "Hello world");
+        assert_eq!(chunks[0]["object"], "chat.completion.chunk");
+        assert_eq!(chunks[0]["id"], "resp_abc");
+        assert_eq!(chunks[0]["model"], "gpt-5-codex");
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+
+        let finish = chunks.iter().find_map(|c| {
+            c.pointer("/choices/0/finish_reason")
+                .and_then(|v| v.as_str())
+        });
+        assert_eq!(finish, Some("stop"));
+
+        let usage = chunks.iter().find_map(|c| c.get("usage")).unwrap();
+        assert_eq!(usage["prompt_tokens"], 12);
+        assert_eq!(usage["completion_tokens"], 5);
+        assert_eq!(usage["total_tokens"], 17);
+        assert_eq!(usage["prompt_tokens_details"]["cached_tokens"], 4);
+        assert!(chat.trim_end().ends_with("data: [DONE]"));
+    }
+
+    #[tokio::test]
+    async fn responses_stream_round_trips_through_chat() {
+        let chat = collect(create_chat_sse_stream_from_responses(upstream(
+            SAMPLE_RESPONSES_STREAM,
+        )))
+        .await;
+        let responses = collect(create_responses_sse_stream_from_chat(upstream(&chat))).await;
+        let events = data_values(&responses);
+
+        assert_eq!(events[0]["type"], "response.created");
+        assert_eq!(events[0]["response"]["model"], "gpt-5-codex");
+
+        let text: String = events
+            .iter()
+            .filter(|e| e["type"] == "response.output_text.delta")
+            .filter_map(|e| e["delta"].as_str())
+            .collect();
+        assert_eq!(text, "Hello world");
+
+        let completed = events.last().unwrap();
+        assert_eq!(completed["type"], "response.completed");
+        let response = &completed["response"];
+        assert_eq!(response["status"], "completed");
+        assert_eq!(response["output"][0]["content"][0]["text"], "Hello world");
+        assert_eq!(response["usage"]["input_tokens"], 12);
+        assert_eq!(response["usage"]["output_tokens"], 5);
+        assert_eq!(
+            response["usage"]["input_tokens_details"]["cached_tokens"],
+            4
+        );
+
+        // 往返后的流仍能被 Codex 用量解析器识别
+        let usage = crate::proxy::usage::parser::TokenUsage::from_codex_stream_events_auto(&events)
+            .unwrap();
+        assert_eq!(usage.input_tokens, 12);
+        assert_eq!(usage.output_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn tool_call_stream_round_trips_through_chat() {
+        let chat = collect(create_chat_sse_stream_from_responses(upstream(
+            SAMPLE_TOOL_STREAM,
+        )))
+        .await;
+        let chunks = data_values(&chat);
+        let arguments: String = chunks
+            .iter()
+            .filter_map(|c| {
+                c.pointer("/choices/0/delta/tool_calls/0/function/arguments")
+                    .and_then(|v| v.as_str())
+            })
+            .collect();
+        assert_eq!(arguments, "{\"cmd\":\"ls\"}");
+        assert!(chunks
+            .iter()
+            .any(|c| c.pointer("/choices/0/finish_reason") == Some(&json!("tool_calls"))));
+
+        let responses = collect(create_responses_sse_stream_from_chat(upstream(&chat))).await;
+        let events = data_values(&responses);
+        let completed = events.last().unwrap();
+        let call = &completed["response"]["output"][0];
+        assert_eq!(call["type"], "function_call");
+        assert_eq!(call["call_id"], "call_1");
+        assert_eq!(call["name"], "shell");
+        assert_eq!(call["arguments"], "{\"cmd\":\"ls\"}");
+        assert_eq!(completed["response"]["usage"]["input_tokens"], 20);
+    }
+
+    #[test]
+    fn chat_request_converts_to_responses_and_back() {
+        let chat = json!({
+            "model": "gpt-5-codex",
+            "stream": true,
+            "max_tokens": 256,
+            "messages": [
+                { "role": "system", "content": "Be terse." },
+                { "role": "user", "content": "list files" },
+                { "role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1", "type": "function",
+                    "function": { "name": "shell", "arguments": "{\"cmd\":\"ls\"}" }
+                }]},
+                { "role": "tool", "tool_call_id": "call_1", "content": "a.txt" }
+            ],
+            "tools": [{ "type": "function", "function": {
+                "name": "shell", "description": "run", "parameters": { "type": "object" }
+            }}],
+            "tool_choice": { "type": "function", "function": { "name": "shell" } }
+        });
+
+        let responses = chat_request_to_responses(chat).unwrap();
+        assert_eq!(responses["instructions"], "Be terse.");
+        assert_eq!(responses["max_output_tokens"], 256);
+        assert_eq!(responses["input"][0]["content"][0]["type"], "input_text");
+        assert_eq!(responses["input"][1]["type"], "function_call");
+        assert_eq!(responses["input"][2]["type"], "function_call_output");
+        assert_eq!(responses["tools"][0]["name"], "shell");
+        assert_eq!(responses["tool_choice"]["name"], "shell");
+        assert!(responses.get("messages").is_none());
+
+        let back = responses_request_to_chat(responses).unwrap();
+        assert_eq!(back["messages"][0]["role"], "system");
+        assert_eq!(back["messages"][1]["content"][0]["text"], "list files");
+        assert_eq!(back["messages"][2]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(back["messages"][3]["role"], "tool");
+        assert_eq!(back["max_tokens"], 256);
+        assert_eq!(back["tools"][0]["function"]["name"], "shell");
+        assert_eq!(back["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn non_streaming_responses_round_trip() {
+        let upstream = json!({
+            "id": "resp_1",
+            "object": "response",
+            "created_at": 1700000000,
+            "model": "gpt-5-codex",
+            "status": "completed",
+            "output": [{
+                "type": "message", "id": "msg_1", "role": "assistant",
+                "content": [{ "type": "output_text", "text": "Done." }]
+            }],
+            "usage": { "input_tokens": 9, "output_tokens": 2, "total_tokens": 11 }
+        });
+
+        let chat = responses_response_to_chat(upstream).unwrap();
+        assert_eq!(chat["choices"][0]["message"]["content"], "Done.");
+        assert_eq!(chat["choices"][0]["finish_reason"], "stop");
+        assert_eq!(chat["usage"]["prompt_tokens"], 9);
+
+        let responses = chat_response_to_responses(chat).unwrap();
+        assert_eq!(responses["status"], "completed");
+        assert_eq!(responses["output"][0]["content"][0]["text"], "Done.");
+        assert_eq!(responses["usage"]["input_tokens"], 9);
+        assert_eq!(responses["usage"]["output_tokens"], 2);
+    }
+}
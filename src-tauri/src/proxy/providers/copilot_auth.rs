@@ -21,9 +21,11 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::{Mutex, RwLock};
 
+use crate::secret_store::SecretStore;
+
 /// GitHub OAuth 客户端 ID（VS Code 使用的 ID）
 const GITHUB_CLIENT_ID: &str = "Iv1.b507a08c87ecfe98";
 
@@ -273,7 +275,9 @@ struct GitHubAccountData {
     /// GitHub OAuth Token
     ///
     /// 安全说明：为了复用登录状态，本地会持久化该令牌。
-    /// 当前实现未接入系统钥匙串，依赖私有文件权限（Unix 下 0600）保护。
+    /// 令牌保存在敏感信息存储（系统钥匙串或降级的明文存储）中，存储文件里留空；
+    /// 仅当写入敏感信息存储失败时才写入存储文件（Unix 下 0600）。
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub github_token: String,
     /// 用户信息
     pub user: GitHubUser,
@@ -318,11 +322,28 @@ pub struct CopilotAuthManager {
     pending_migration: Arc<RwLock<Option<String>>>,
     /// 旧认证数据迁移失败时的状态消息
     migration_error: Arc<RwLock<Option<String>>>,
+    /// GitHub OAuth Token 的敏感信息存储（首次读写令牌时才获取，避免启动时在主线程探测钥匙串）
+    secrets: OnceLock<Arc<SecretStore>>,
+}
+
+/// GitHub OAuth Token 在敏感信息存储中的键
+fn github_token_secret_key(account_id: &str) -> String {
+    format!("copilot_github_token:{account_id}")
 }
 
 impl CopilotAuthManager {
     /// 创建新的认证管理器
     pub fn new(data_dir: PathBuf) -> Self {
+        Self::with_secrets(data_dir, OnceLock::new())
+    }
+
+    /// 使用指定的敏感信息存储创建认证管理器
+    #[cfg(test)]
+    pub(crate) fn with_secret_store(data_dir: PathBuf, secrets: Arc<SecretStore>) -> Self {
+        Self::with_secrets(data_dir, OnceLock::from(secrets))
+    }
+
+    fn with_secrets(data_dir: PathBuf, secrets: OnceLock<Arc<SecretStore>>) -> Self {
         let storage_path = data_dir.join("copilot_auth.json");
 
         let manager = Self {
@@ -334,6 +355,7 @@ impl CopilotAuthManager {
             storage_path,
             pending_migration: Arc::new(RwLock::new(None)),
             migration_error: Arc::new(RwLock::new(None)),
+            secrets,
         };
 
         // 尝试从磁盘加载（同步，不发起网络请求）
@@ -379,6 +401,7 @@ impl CopilotAuthManager {
             let mut refresh_locks = self.refresh_locks.write().await;
             refresh_locks.remove(account_id);
         }
+        self.delete_github_token_secret(account_id);
 
         {
             let accounts = self.accounts.read().await;
@@ -593,13 +616,7 @@ impl CopilotAuthManager {
         }
 
         // 获取账号的 GitHub token
-        let github_token = {
-            let accounts = self.accounts.read().await;
-            accounts
-                .get(account_id)
-                .map(|a| a.github_token.clone())
-                .ok_or_else(|| CopilotAuthError::AccountNotFound(account_id.to_string()))?
-        };
+        let github_token = self.github_token(account_id).await?;
 
         // 刷新 Copilot token
         self.fetch_copilot_token_with_github_token(&github_token, account_id)
@@ -691,13 +708,7 @@ impl CopilotAuthManager {
         &self,
         account_id: &str,
     ) -> Result<CopilotUsageResponse, CopilotAuthError> {
-        let github_token = {
-            let accounts = self.accounts.read().await;
-            accounts
-                .get(account_id)
-                .map(|a| a.github_token.clone())
-                .ok_or_else(|| CopilotAuthError::AccountNotFound(account_id.to_string()))?
-        };
+        let github_token = self.github_token(account_id).await?;
 
         log::info!("[CopilotAuth] 获取账号 {} 的 Copilot 使用量", account_id);
 
@@ -796,6 +807,9 @@ impl CopilotAuthManager {
 
         {
             let mut accounts = self.accounts.write().await;
+            for account_id in accounts.keys() {
+                self.delete_github_token_secret(account_id);
+            }
             accounts.clear();
         }
         {
@@ -822,6 +836,51 @@ impl CopilotAuthManager {
 
     // ==================== 内部方法 ====================
 
+    /// 敏感信息存储（未注入时使用全局存储，首次调用时完成探测）
+    fn secrets(&self) -> &SecretStore {
+        self.secrets
+            .get_or_init(crate::secret_store::secret_store)
+            .as_ref()
+    }
+
+    /// 账号的 GitHub Token：从磁盘加载时令牌留空，首次使用时从敏感信息存储读取并缓存
+    async fn github_token(&self, account_id: &str) -> Result<String, CopilotAuthError> {
+        let cached = self
+            .accounts
+            .read()
+            .await
+            .get(account_id)
+            .map(|a| a.github_token.clone())
+            .ok_or_else(|| CopilotAuthError::AccountNotFound(account_id.to_string()))?;
+        if !cached.is_empty() {
+            return Ok(cached);
+        }
+
+        let token = match self.secrets().get(&github_token_secret_key(account_id)) {
+            Ok(Some(token)) => token,
+            Ok(None) => {
+                log::warn!("[CopilotAuth] 账号 {account_id} 的令牌不在敏感信息存储中");
+                return Ok(cached);
+            }
+            Err(e) => {
+                log::warn!("[CopilotAuth] 读取账号 {account_id} 的令牌失败: {e}");
+                return Ok(cached);
+            }
+        };
+        if let Some(account) = self.accounts.write().await.get_mut(account_id) {
+            if account.github_token.is_empty() {
+                account.github_token = token.clone();
+            }
+        }
+        Ok(token)
+    }
+
+    fn delete_github_token_secret(&self, account_id: &str) {
+        if let Err(e) = self.secrets().delete(&github_token_secret_key(account_id)) {
+            log::warn!("[CopilotAuth] 删除账号 {account_id} 的令牌失败: {e}");
+        }
+    }
+
     fn fallback_default_account_id(
         accounts: &HashMap<String, GitHubAccountData>,
     ) -> Option<String> {
@@ -1041,9 +1100,10 @@ impl CopilotAuthManager {
             .map_err(|e| CopilotAuthError::ParseError(e.to_string()))?;
 
         if store.version >= 2 {
-            // v2 多账号格式
+            // v2 多账号格式；令牌留空的账号在首次使用时从敏感信息存储读取
+            let loaded = store.accounts;
             if let Ok(mut accounts) = self.accounts.try_write() {
-                *accounts = store.accounts;
+                *accounts = loaded;
                 log::info!("[CopilotAuth] 从磁盘加载 {} 个账号", accounts.len());
             }
             if let Ok(mut default_account_id) = self.default_account_id.try_write() {
@@ -1113,9 +1173,24 @@ impl CopilotAuthManager {
         Ok(())
     }
 
-    /// 保存到磁盘
+    /// 保存到磁盘（令牌写入敏感信息存储，失败时保留在存储文件中）
     async fn save_to_disk(&self) -> Result<(), CopilotAuthError> {
-        let accounts = self.accounts.read().await.clone();
+        let mut accounts = self.accounts.read().await.clone();
+        for (account_id, account) in accounts.iter_mut() {
+            // 尚未从敏感信息存储读取的令牌保持原样
+            if account.github_token.is_empty() {
+                continue;
+            }
+            match self
+                .secrets()
+                .set(&github_token_secret_key(account_id), &account.github_token)
+            {
+                Ok(()) => account.github_token.clear(),
+                Err(e) => log::warn!(
+                    "[CopilotAuth] 账号 {account_id} 的令牌写入敏感信息存储失败，保留在存储文件中: {e}"
+                ),
+            }
+        }
         let default_account_id = self.resolve_default_account_id().await;
 
         let store = CopilotAuthStore {
@@ -1315,4 +1390,57 @@ mod tests {
             Some("67890".to_string())
         );
     }
+
+    #[test]
+    fn test_new_does_not_resolve_secret_store() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = CopilotAuthManager::new(dir.path().to_path_buf());
+        assert!(manager.secrets.get().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_github_token_is_kept_in_secret_store() {
+        use crate::secret_store::PlaintextBackend;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let secrets_path = dir.path().join("secrets.json");
+        let secrets = || {
+            Arc::new(SecretStore::select(
+                Box::new(PlaintextBackend::new(secrets_path.clone())),
+                Box::new(PlaintextBackend::new(secrets_path.clone())),
+            ))
+        };
+
+        let manager = CopilotAuthManager::with_secret_store(dir.path().to_path_buf(), secrets());
+        let user = GitHubUser {
+            login: "alice".to_string(),
+            id: 12345,
+            avatar_url: None,
+        };
+        manager
+            .add_account_internal("gho_secret_token".to_string(), user)
+            .await
+            .unwrap();
+
+        let stored = std::fs::read_to_string(dir.path().join("copilot_auth.json")).unwrap();
+        assert!(!stored.contains("gho_secret_token"));
+
+        let reloaded = CopilotAuthManager::with_secret_store(dir.path().to_path_buf(), secrets());
+        // 加载时不读取敏感信息存储，首次使用时才读取
+        assert!(reloaded.accounts.read().await["12345"]
+            .github_token
+            .is_empty());
+        assert_eq!(
+            reloaded.github_token("12345").await.unwrap(),
+            "gho_secret_token"
+        );
+        assert_eq!(
+            reloaded.accounts.read().await["12345"].github_token,
+            "gho_secret_token"
+        );
+
+        reloaded.remove_account("12345").await.unwrap();
+        let remaining = std::fs::read_to_string(&secrets_path).unwrap();
+        assert!(!remaining.contains("gho_secret_token"));
+    }
 }
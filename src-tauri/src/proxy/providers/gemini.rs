@@ -16,15 +16,17 @@ pub struct GeminiAdapter;
 
 /// OAuth 凭证结构
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct OAuthCredentials {
     pub access_token: String,
     pub refresh_token: Option<String>,
     pub client_id: Option<String>,
     pub client_secret: Option<String>,
+    /// access_token 过期时间（Unix 毫秒，与 Gemini CLI 的 oauth_creds.json 一致）
+    pub expiry_date: Option<i64>,
+    /// 自定义 token 端点，缺省为 Google OAuth 端点
+    pub token_uri: Option<String>,
 }
 
-#[allow(dead_code)]
 impl OAuthCredentials {
     /// 检查是否需要刷新 token（有 refresh_token 但没有有效的 access_token）
     pub fn needs_refresh(&self) -> bool {
@@ -78,6 +80,8 @@ impl GeminiAdapter {
                 refresh_token: None,
                 client_id: None,
                 client_secret: None,
+                expiry_date: None,
+                token_uri: None,
             });
         }
 
@@ -101,6 +105,11 @@ impl GeminiAdapter {
                     .get("client_secret")
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string());
+                let expiry_date = json.get("expiry_date").and_then(|v| v.as_i64());
+                let token_uri = json
+                    .get("token_uri")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
 
                 // 如果有 access_token 或 refresh_token，返回凭证
                 if !access_token.is_empty() || refresh_token.is_some() {
@@ -109,6 +118,8 @@ impl GeminiAdapter {
                         refresh_token,
                         client_id,
                         client_secret,
+                        expiry_date,
+                        token_uri,
                     });
                 }
             }
@@ -251,6 +262,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
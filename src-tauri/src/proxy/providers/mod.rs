@@ -5,16 +5,20 @@
 //! ## 模块结构
 //! - `adapter`: 定义 `ProviderAdapter` trait
 //! - `auth`: 认证类型和策略
+//! - `azure`: Azure OpenAI 部署路由
 //! - `claude`: Claude (Anthropic) 适配器
 //! - `codex`: Codex (OpenAI) 适配器
+//! - `codex_bridge`: Codex Chat Completions ↔ Responses API 协议桥接
 //! - `gemini`: Gemini (Google) 适配器
 //! - `models`: API 数据模型
 //! - `transform`: 格式转换
 
 mod adapter;
 mod auth;
+pub mod azure;
 mod claude;
 mod codex;
+pub mod codex_bridge;
 pub mod copilot_auth;
 mod gemini;
 pub mod models;
@@ -32,7 +36,7 @@ pub use adapter::ProviderAdapter;
 pub use auth::{AuthInfo, AuthStrategy};
 pub use claude::{get_claude_api_format, ClaudeAdapter};
 pub use codex::CodexAdapter;
-pub use gemini::GeminiAdapter;
+pub use gemini::{GeminiAdapter, OAuthCredentials};
 
 /// 供应商类型枚举
 ///
@@ -250,6 +254,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            enabled: true,
         }
     }
 
@@ -0,0 +1,137 @@
+//! 实时请求流
+//!
+//! 界面原先只能轮询请求日志。每条请求写入使用日志后，这里推送一条轻量摘要，
+//! 由 [`RequestFeed::forward_to`] 转发为 Tauri 事件 [`REQUEST_LOGGED_EVENT`]。
+//!
+//! - 仅在界面订阅（`subscribe_proxy_requests`）且该请求的供应商开启日志时推送
+//!   （`ProviderMeta.enable_request_logging`，未设置时沿用 `ProxyConfig.enable_logging`）
+//! - 订阅状态由 ProxyService 持有，代理重启后保持
+
+use super::server::ProxyState;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::Emitter;
+use tokio::sync::broadcast;
+
+/// 请求记录完成后发出的 Tauri 事件名
+pub const REQUEST_LOGGED_EVENT: &str = "proxy-request-logged";
+
+/// 广播缓冲区大小（界面处理过慢时丢弃最旧的摘要）
+const FEED_CAPACITY: usize = 256;
+
+/// 单条请求摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestLogSummary {
+    pub request_id: String,
+    pub provider_id: String,
+    pub app_type: String,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub status_code: u16,
+    pub latency_ms: u64,
+    pub is_streaming: bool,
+}
+
+/// 实时请求流（订阅开关 + 广播通道）
+pub struct RequestFeed {
+    subscribed: AtomicBool,
+    sender: broadcast::Sender<RequestLogSummary>,
+}
+
+impl Default for RequestFeed {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+        Self {
+            subscribed: AtomicBool::new(false),
+            sender,
+        }
+    }
+}
+
+impl RequestFeed {
+    /// 界面订阅/取消订阅
+    pub fn set_subscribed(&self, subscribed: bool) {
+        self.subscribed.store(subscribed, Ordering::Relaxed);
+    }
+
+    pub fn is_subscribed(&self) -> bool {
+        self.subscribed.load(Ordering::Relaxed)
+    }
+
+    /// 推送一条摘要（未订阅时直接丢弃）
+    pub fn publish(&self, summary: RequestLogSummary) {
+        if !self.is_subscribed() {
+            return;
+        }
+        // 没有接收端时 send 返回 Err，忽略即可
+        let _ = self.sender.send(summary);
+    }
+
+    /// 新建接收端
+    pub fn receiver(&self) -> broadcast::Receiver<RequestLogSummary> {
+        self.sender.subscribe()
+    }
+
+    /// 将摘要转发为 Tauri 事件（应用启动时调用一次）
+    pub fn forward_to(&self, app: tauri::AppHandle) {
+        let mut receiver = self.receiver();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(summary) => {
+                        if let Err(e) = app.emit(REQUEST_LOGGED_EVENT, &summary) {
+                            log::warn!("发射 {REQUEST_LOGGED_EVENT} 事件失败: {e}");
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::debug!("实时请求流积压，丢弃 {skipped} 条摘要");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// 请求记录完成后推送摘要（需已订阅；日志开关已由调用方按供应商判断）
+pub(crate) async fn publish_logged(state: &ProxyState, summary: RequestLogSummary) {
+    state.request_feed.publish(summary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str) -> RequestLogSummary {
+        RequestLogSummary {
+            request_id: id.to_string(),
+            provider_id: "p1".to_string(),
+            app_type: "claude".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            input_tokens: 10,
+            output_tokens: 20,
+            status_code: 200,
+            latency_ms: 30,
+            is_streaming: false,
+        }
+    }
+
+    #[test]
+    fn publish_is_dropped_until_subscribed() {
+        let feed = RequestFeed::default();
+        let mut receiver = feed.receiver();
+
+        feed.publish(summary("ignored"));
+        assert!(receiver.try_recv().is_err());
+
+        feed.set_subscribed(true);
+        feed.publish(summary("seen"));
+        assert_eq!(receiver.try_recv().unwrap().request_id, "seen");
+
+        feed.set_subscribed(false);
+        feed.publish(summary("ignored-again"));
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! 相同请求的响应缓存
+//!
+//! 开启 `ProxyConfig.enable_response_cache` 后，对非流式请求按 (供应商, 端点, 模型, 请求体)
+//! 计算指纹，在短 TTL 内重复发送完全相同的请求时直接返回缓存的响应，不再请求上游。
+//! 主要用于确定性测试时反复重放同一请求。流式请求、非 2xx 响应和 SSE 响应不会被缓存。
+
+use super::ProxyError;
+use axum::body::Body;
+use axum::http::HeaderMap;
+use axum::response::Response;
+use bytes::Bytes;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 缓存有效期
+pub const RESPONSE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// 最多缓存的响应数量
+const MAX_ENTRIES: usize = 128;
+
+/// 单个响应体大小上限，超过则不缓存
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Clone)]
+struct CachedResponse {
+    status: u16,
+    headers: HeaderMap,
+    body: Bytes,
+    stored_at: Instant,
+}
+
+/// 当前时间来源（测试中替换为可控时钟）
+type Clock = Box<dyn Fn() -> Instant + Send + Sync>;
+
+/// 内存响应缓存（随代理服务器生命周期存在）
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    ttl: Duration,
+    clock: Clock,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(RESPONSE_CACHE_TTL)
+    }
+}
+
+/// 计算请求指纹；流式请求返回 None
+pub fn request_key(provider_id: &str, endpoint: &str, model: &str, body: &Value) -> Option<String> {
+    if body.get("stream").and_then(|v| v.as_bool()) == Some(true) {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    for part in [provider_id, endpoint, model] {
+        hasher.update(part.as_bytes());
+        hasher.update(b"\0");
+    }
+    hasher.update(body.to_string().as_bytes());
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_clock(ttl, Box::new(Instant::now))
+    }
+
+    fn with_clock(ttl: Duration, clock: Clock) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            clock,
+        }
+    }
+
+    /// 缓存条目自写入以来经过的时间
+    fn age(&self, entry: &CachedResponse) -> Duration {
+        (self.clock)().saturating_duration_since(entry.stored_at)
+    }
+
+    /// 查找未过期的缓存响应
+    pub fn lookup(&self, key: &str) -> Option<Response> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if self.age(entries.get(key)?) > self.ttl {
+            entries.remove(key);
+            return None;
+        }
+        let entry = entries.get(key)?;
+
+        let mut response = Response::new(Body::from(entry.body.clone()));
+        *response.status_mut() = axum::http::StatusCode::from_u16(entry.status).ok()?;
+        *response.headers_mut() = entry.headers.clone();
+        Some(response)
+    }
+
+    /// 缓存可缓存的响应（2xx、非 SSE），并原样返回给客户端
+    pub async fn store(&self, key: &str, response: Response) -> Result<Response, ProxyError> {
+        let is_sse = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.contains("text/event-stream"));
+        if !response.status().is_success() || is_sse {
+            return Ok(response);
+        }
+
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .map_err(|e| ProxyError::Internal(format!("Failed to buffer response body: {e}")))?;
+
+        if bytes.len() <= MAX_BODY_BYTES {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            entries.retain(|_, entry| self.age(entry) <= self.ttl);
+            if entries.len() >= MAX_ENTRIES {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.stored_at)
+                    .map(|(key, _)| key.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(
+                key.to_string(),
+                CachedResponse {
+                    status: parts.status.as_u16(),
+                    headers: parts.headers.clone(),
+                    body: bytes.clone(),
+                    stored_at: (self.clock)(),
+                },
+            );
+        }
+
+        Ok(Response::from_parts(parts, Body::from(bytes)))
+    }
+
+    /// 清空缓存（关闭缓存开关时调用）
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn upstream_response(content_type: &str, status: u16, body: &str) -> Response {
+        let mut response = Response::new(Body::from(body.to_string()));
+        *response.status_mut() = axum::http::StatusCode::from_u16(status).unwrap();
+        response
+            .headers_mut()
+            .insert("content-type", content_type.parse().unwrap());
+        response
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn stored_response_is_served_from_lookup() {
+        let cache = ResponseCache::default();
+        let body = json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{ "role": "user", "content": "hi" }]
+        });
+        let key = request_key("p1", "/v1/messages", "claude-sonnet-4-5", &body).unwrap();
+        assert!(cache.lookup(&key).is_none());
+
+        let first = cache
+            .store(
+                &key,
+                upstream_response("application/json", 200, r#"{"id":"msg_1"}"#),
+            )
+            .await
+            .unwrap();
+        let second = cache.lookup(&key).expect("second request hits the cache");
+
+        assert_eq!(second.status(), 200);
+        assert_eq!(
+            second.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+        assert_eq!(body_text(first).await, body_text(second).await);
+    }
+
+    #[test]
+    fn streaming_requests_have_no_cache_key() {
+        let body = json!({ "model": "m", "stream": true, "messages": [] });
+        assert!(request_key("p1", "/v1/messages", "m", &body).is_none());
+
+        let other = json!({ "model": "m", "messages": [] });
+        assert_ne!(
+            request_key("p1", "/v1/messages", "m", &other),
+            request_key("p2", "/v1/messages", "m", &other)
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_and_sse_responses_are_not_cached() {
+        let cache = ResponseCache::default();
+        let failed = cache
+            .store("k1", upstream_response("application/json", 500, "{}"))
+            .await
+            .unwrap();
+        assert_eq!(failed.status(), 500);
+        cache
+            .store(
+                "k2",
+                upstream_response("text/event-stream", 200, "data: {}\n\n"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_not_served() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        // 可手动推进的时钟
+        let start = Instant::now();
+        let elapsed_ms = Arc::new(AtomicU64::new(0));
+        let clock = {
+            let elapsed_ms = elapsed_ms.clone();
+            move || start + Duration::from_millis(elapsed_ms.load(Ordering::SeqCst))
+        };
+        let cache = ResponseCache::with_clock(Duration::from_secs(60), Box::new(clock));
+        cache
+            .store("k", upstream_response("application/json", 200, "{}"))
+            .await
+            .unwrap();
+
+        elapsed_ms.store(60_000, Ordering::SeqCst);
+        assert!(cache.lookup("k").is_some(), "entry is valid until the TTL");
+
+        elapsed_ms.store(60_001, Ordering::SeqCst);
+        assert!(cache.lookup("k").is_none());
+        assert_eq!(cache.len(), 0, "expired entry is evicted on lookup");
+    }
+}
@@ -114,8 +114,9 @@ impl StreamHandler {
                     }
                     Err(_) => {
                         // 空闲超时
-                        log::warn!("流式响应空闲超时: {idle_timeout:?} 无数据");
-                        yield Err(std::io::Error::other("Stream idle timeout"));
+                        let error = ProxyError::StreamIdleTimeout(idle_timeout.as_secs());
+                        log::warn!("{error}");
+                        yield Err(std::io::Error::other(error));
                         break;
                     }
                 }
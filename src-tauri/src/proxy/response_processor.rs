@@ -6,7 +6,7 @@ use super::{
     handler_config::UsageParserConfig,
     handler_context::{RequestContext, StreamingTimeoutConfig},
     server::ProxyState,
-    usage::parser::TokenUsage,
+    usage::{logger::CLIENT_ABORTED_STATUS, parser::TokenUsage},
     ProxyError,
 };
 use axum::response::{IntoResponse, Response};
@@ -71,8 +71,13 @@ pub async fn handle_streaming(
     let timeout_config = ctx.streaming_timeout_config();
 
     // 创建带日志和超时的透传流
-    let logged_stream =
-        create_logged_passthrough_stream(stream, ctx.tag, Some(usage_collector), timeout_config);
+    let logged_stream = create_logged_passthrough_stream(
+        stream,
+        ctx.tag,
+        Some(usage_collector),
+        timeout_config,
+        ctx.sse_log_mode(),
+    );
 
     let body = axum::body::Body::from_stream(logged_stream);
     match builder.body(body) {
@@ -107,11 +112,13 @@ pub async fn handle_non_streaming(
         format_headers(&response_headers)
     );
 
-    log::debug!(
-        "[{}] 上游响应体内容: {}",
-        ctx.tag,
-        String::from_utf8_lossy(&body_bytes)
-    );
+    if ctx.logging_enabled() {
+        log::debug!(
+            "[{}] 上游响应体内容: {}",
+            ctx.tag,
+            String::from_utf8_lossy(&body_bytes)
+        );
+    }
 
     // 解析并记录使用量
     if let Ok(json_value) = serde_json::from_slice::<Value>(&body_bytes) {
@@ -156,6 +163,17 @@ pub async fn handle_non_streaming(
             );
         }
     } else {
+        // HTTP 客户端会自动解压 gzip/deflate/br；仍带 Content-Encoding 说明是无法解压的编码
+        if let Some(encoding) = response_headers
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.eq_ignore_ascii_case("identity"))
+        {
+            log::warn!(
+                "[{}] 上游响应体使用了不支持的压缩编码 ({encoding})，无法解析 usage",
+                ctx.tag
+            );
+        }
         log::debug!(
             "[{}] <<< 响应 (非 JSON): {} bytes",
             ctx.tag,
@@ -201,13 +219,58 @@ pub async fn process_response(
     }
 }
 
+/// 原样透传响应（供应商开启 `raw_passthrough` 时使用）
+///
+/// 状态码、响应头与响应字节直接转发，不解析 SSE/JSON、不解析用量、不记录内容；
+/// 仅记录一条状态码与延迟（收到响应头为止）的请求日志。
+pub fn handle_raw_passthrough(
+    response: reqwest::Response,
+    ctx: &RequestContext,
+    state: &ProxyState,
+) -> Response {
+    let status = response.status();
+    let is_streaming = is_sse_response(&response);
+    log::debug!(
+        "[{}] 原样透传上游响应: status={}, streaming={is_streaming}",
+        ctx.tag,
+        status.as_u16()
+    );
+    spawn_log_usage(
+        state,
+        ctx,
+        TokenUsage::default(),
+        &ctx.request_model,
+        &ctx.request_model,
+        status.as_u16(),
+        is_streaming,
+    );
+
+    let mut builder = axum::response::Response::builder().status(status);
+    for (key, value) in response.headers() {
+        builder = builder.header(key, value);
+    }
+    let body = axum::body::Body::from_stream(response.bytes_stream());
+    match builder.body(body) {
+        Ok(resp) => resp,
+        Err(e) => {
+            log::error!("[{}] 构建透传响应失败: {e}", ctx.tag);
+            ProxyError::Internal(format!("Failed to build passthrough response: {e}"))
+                .into_response()
+        }
+    }
+}
+
 // ============================================================================
 // SSE 使用量收集器
 // ============================================================================
 
-type UsageCallbackWithTiming = Arc<dyn Fn(Vec<Value>, Option<u64>) + Send + Sync + 'static>;
+/// 使用量回调：(事件, 首 token 耗时, 是否因客户端断开而提前结束)
+type UsageCallbackWithTiming = Arc<dyn Fn(Vec<Value>, Option<u64>, bool) + Send + Sync + 'static>;
 
 /// SSE 使用量收集器
+///
+/// 按到达顺序保存全部事件，结束时整体交给解析器；解析器会在完整集合中查找最终用量，
+/// 不依赖上游的事件顺序。
 #[derive(Clone)]
 pub struct SseUsageCollector {
     inner: Arc<SseUsageCollectorInner>,
@@ -219,13 +282,14 @@ struct SseUsageCollectorInner {
     start_time: std::time::Instant,
     on_complete: UsageCallbackWithTiming,
     finished: AtomicBool,
+    aborted: AtomicBool,
 }
 
 impl SseUsageCollector {
     /// 创建新的使用量收集器
     pub fn new(
         start_time: std::time::Instant,
-        callback: impl Fn(Vec<Value>, Option<u64>) + Send + Sync + 'static,
+        callback: impl Fn(Vec<Value>, Option<u64>, bool) + Send + Sync + 'static,
     ) -> Self {
         let on_complete: UsageCallbackWithTiming = Arc::new(callback);
         Self {
@@ -235,23 +299,26 @@ impl SseUsageCollector {
                 start_time,
                 on_complete,
                 finished: AtomicBool::new(false),
+                aborted: AtomicBool::new(false),
             }),
         }
     }
 
     /// 推送 SSE 事件
     pub async fn push(&self, event: Value) {
-        // 记录首个事件时间
-        {
-            let mut first_time = self.inner.first_event_time.lock().await;
-            if first_time.is_none() {
-                *first_time = Some(std::time::Instant::now());
-            }
-        }
+        self.mark_first_event().await;
         let mut events = self.inner.events.lock().await;
         events.push(event);
     }
 
+    /// 记录首个事件时间（轻量模式下未推送的事件也计入首 token 耗时）
+    pub async fn mark_first_event(&self) {
+        let mut first_time = self.inner.first_event_time.lock().await;
+        if first_time.is_none() {
+            *first_time = Some(std::time::Instant::now());
+        }
+    }
+
     /// 完成收集并触发回调
     pub async fn finish(&self) {
         if self.inner.finished.swap(true, Ordering::SeqCst) {
@@ -268,7 +335,42 @@ impl SseUsageCollector {
             first_time.map(|t| (t - self.inner.start_time).as_millis() as u64)
         };
 
-        (self.inner.on_complete)(events, first_token_ms);
+        let aborted = self.inner.aborted.load(Ordering::SeqCst);
+        (self.inner.on_complete)(events, first_token_ms, aborted);
+    }
+
+    /// 客户端提前断开：以已收到的事件完成收集，并标记为 client_aborted
+    pub async fn abort(&self) {
+        self.inner.aborted.store(true, Ordering::SeqCst);
+        self.finish().await;
+    }
+}
+
+/// 透传流被提前丢弃（客户端断开）时的处理
+///
+/// 响应体被丢弃时透传流随之析构，内部持有的上游字节流一并释放，reqwest 会关闭上游连接，
+/// 不再继续生成（和计费）；这里负责记录日志，并以已收到的用量记录一条 client_aborted 请求。
+struct ClientAbortGuard {
+    tag: &'static str,
+    collector: Option<SseUsageCollector>,
+    completed: bool,
+}
+
+impl Drop for ClientAbortGuard {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        log::warn!("[{}] 客户端已断开，取消上游流式请求", self.tag);
+        let Some(collector) = self.collector.take() else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move { collector.abort().await });
+            }
+            Err(_) => log::debug!("[{}] 无 Tokio 运行时，跳过 client_aborted 记录", self.tag),
+        }
     }
 }
 
@@ -283,11 +385,7 @@ fn create_usage_collector(
     status_code: u16,
     parser_config: &UsageParserConfig,
 ) -> SseUsageCollector {
-    let logging_enabled = state
-        .config
-        .try_read()
-        .map(|c| c.enable_logging)
-        .unwrap_or(true);
+    let logging_enabled = ctx.logging_enabled();
     let state = state.clone();
     let provider_id = ctx.provider.id.clone();
     let request_model = ctx.request_model.clone();
@@ -297,11 +395,19 @@ fn create_usage_collector(
     let stream_parser = parser_config.stream_parser;
     let model_extractor = parser_config.model_extractor;
     let session_id = ctx.session_id.clone();
+    let api_key_index = ctx.api_key_index;
+    let is_safety_net = ctx.is_safety_net;
+    let effective_max_tokens = ctx.effective_max_tokens;
 
-    SseUsageCollector::new(start_time, move |events, first_token_ms| {
+    SseUsageCollector::new(start_time, move |events, first_token_ms, aborted| {
         if !logging_enabled {
             return;
         }
+        let status_code = if aborted {
+            CLIENT_ABORTED_STATUS
+        } else {
+            status_code
+        };
         if let Some(usage) = stream_parser(&events) {
             let model = model_extractor(&events, &request_model);
             let latency_ms = start_time.elapsed().as_millis() as u64;
@@ -324,6 +430,9 @@ fn create_usage_collector(
                     true, // is_streaming
                     status_code,
                     Some(session_id),
+                    api_key_index,
+                    is_safety_net,
+                    effective_max_tokens,
                 )
                 .await;
             });
@@ -348,6 +457,9 @@ fn create_usage_collector(
                     true, // is_streaming
                     status_code,
                     Some(session_id),
+                    api_key_index,
+                    is_safety_net,
+                    effective_max_tokens,
                 )
                 .await;
             });
@@ -366,11 +478,9 @@ fn spawn_log_usage(
     status_code: u16,
     is_streaming: bool,
 ) {
-    // Check enable_logging before spawning the log task
-    if let Ok(config) = state.config.try_read() {
-        if !config.enable_logging {
-            return;
-        }
+    // 供应商关闭日志（或未设置且全局关闭）时不记录
+    if !ctx.logging_enabled() {
+        return;
     }
 
     let state = state.clone();
@@ -380,6 +490,9 @@ fn spawn_log_usage(
     let request_model = request_model.to_string();
     let latency_ms = ctx.latency_ms();
     let session_id = ctx.session_id.clone();
+    let api_key_index = ctx.api_key_index;
+    let is_safety_net = ctx.is_safety_net;
+    let effective_max_tokens = ctx.effective_max_tokens;
 
     tokio::spawn(async move {
         log_usage_internal(
@@ -394,11 +507,53 @@ fn spawn_log_usage(
             is_streaming,
             status_code,
             Some(session_id),
+            api_key_index,
+            is_safety_net,
+            effective_max_tokens,
         )
         .await;
     });
 }
 
+/// 异步记录响应缓存命中（未请求上游，tokens 与费用均为 0）
+pub(crate) fn spawn_log_cached_hit(state: &ProxyState, ctx: &RequestContext, status_code: u16) {
+    if state.logging_paused.is_paused() || !ctx.logging_enabled() {
+        return;
+    }
+
+    let log = super::usage::logger::RequestLog {
+        request_id: uuid::Uuid::new_v4().to_string(),
+        provider_id: ctx.provider.id.clone(),
+        app_type: ctx.app_type_str.to_string(),
+        model: ctx.request_model.clone(),
+        request_model: ctx.request_model.clone(),
+        usage: TokenUsage::default(),
+        cost: None,
+        latency_ms: ctx.latency_ms(),
+        first_token_ms: None,
+        status_code,
+        error_message: None,
+        session_id: Some(ctx.session_id.clone()),
+        provider_type: None,
+        is_streaming: false,
+        cost_multiplier: "1".to_string(),
+        is_cached: true,
+        is_shadow: false,
+        api_key_index: None,
+        is_safety_net: false,
+        effective_max_tokens: None,
+    };
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        let logger =
+            super::usage::logger::UsageLogger::new(&state.db).with_writer(&state.usage_writer);
+        if let Err(e) = logger.log_request(&log) {
+            log::warn!("[USG-001] 记录缓存命中失败: {e}");
+        }
+    });
+}
+
 /// 内部使用量记录函数
 #[allow(clippy::too_many_arguments)]
 async fn log_usage_internal(
@@ -413,10 +568,22 @@ async fn log_usage_internal(
     is_streaming: bool,
     status_code: u16,
     session_id: Option<String>,
+    api_key_index: Option<usize>,
+    is_safety_net: bool,
+    effective_max_tokens: Option<u64>,
 ) {
+    use super::request_feed::{publish_logged, RequestLogSummary};
     use super::usage::logger::UsageLogger;
 
-    let logger = UsageLogger::new(&state.db);
+    if state.logging_paused.is_paused() {
+        return;
+    }
+
+    let logger = UsageLogger::new(&state.db)
+        .with_writer(&state.usage_writer)
+        .with_api_key_index(api_key_index)
+        .with_safety_net(is_safety_net)
+        .with_effective_max_tokens(effective_max_tokens);
     let (multiplier, pricing_model_source) =
         logger.resolve_pricing_config(provider_id, app_type).await;
     let pricing_model = if pricing_model_source == "request" {
@@ -426,6 +593,17 @@ async fn log_usage_internal(
     };
 
     let request_id = uuid::Uuid::new_v4().to_string();
+    let summary = RequestLogSummary {
+        request_id: request_id.clone(),
+        provider_id: provider_id.to_string(),
+        app_type: app_type.to_string(),
+        model: model.to_string(),
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        status_code,
+        latency_ms,
+        is_streaming,
+    };
 
     log::debug!(
         "[{app_type}] 记录请求日志: id={request_id}, provider={provider_id}, model={model}, streaming={is_streaming}, status={status_code}, latency_ms={latency_ms}, first_token_ms={first_token_ms:?}, session={}, input={}, output={}, cache_read={}, cache_creation={}",
@@ -453,7 +631,10 @@ async fn log_usage_internal(
         is_streaming,
     ) {
         log::warn!("[USG-001] 记录使用量失败: {e}");
+        return;
     }
+
+    publish_logged(state, summary).await;
 }
 
 /// 创建带日志记录和超时控制的透传流
@@ -462,11 +643,17 @@ pub fn create_logged_passthrough_stream(
     tag: &'static str,
     usage_collector: Option<SseUsageCollector>,
     timeout_config: StreamingTimeoutConfig,
+    log_mode: SseLogMode,
 ) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send {
     async_stream::stream! {
         let mut buffer = String::new();
         let mut collector = usage_collector;
         let mut is_first_chunk = true;
+        let mut abort_guard = ClientAbortGuard {
+            tag,
+            collector: collector.clone(),
+            completed: false,
+        };
 
         // 超时配置
         let first_byte_timeout = if timeout_config.first_byte_timeout > 0 {
@@ -496,10 +683,18 @@ pub fn create_logged_passthrough_stream(
                         Ok(Some(chunk)) => Some(chunk),
                         Ok(None) => None, // 流结束
                         Err(_) => {
-                            // 超时
-                            let timeout_type = if is_first_chunk { "首字节" } else { "静默期" };
-                            log::error!("[{tag}] 流式响应{}超时 ({}秒)", timeout_type, duration.as_secs());
-                            yield Err(std::io::Error::other(format!("流式响应{timeout_type}超时")));
+                            // 超时：首字节超时视为读取超时，之后的静默期超时单独归类
+                            let error = if is_first_chunk {
+                                ProxyError::ReadTimeout(format!(
+                                    "流式响应首字节超时 ({}秒)",
+                                    duration.as_secs()
+                                ))
+                            } else {
+                                ProxyError::StreamIdleTimeout(duration.as_secs())
+                            };
+                            log::error!("[{tag}] {error}");
+                            abort_guard.completed = true;
+                            yield Err(std::io::Error::other(error));
                             break;
                         }
                     }
@@ -520,35 +715,15 @@ pub fn create_logged_passthrough_stream(
                     buffer.push_str(&text);
 
                     // 尝试解析并记录完整的 SSE 事件
-                    while let Some(pos) = buffer.find("\n\n") {
-                        let event_text = buffer[..pos].to_string();
-                        buffer = buffer[pos + 2..].to_string();
-
-                        if !event_text.trim().is_empty() {
-                            // 提取 data 部分并尝试解析为 JSON
-                            for line in event_text.lines() {
-                                if let Some(data) = line.strip_prefix("data: ") {
-                                    if data.trim() != "[DONE]" {
-                                        if let Ok(json_value) = serde_json::from_str::<Value>(data) {
-                                            if let Some(c) = &collector {
-                                                c.push(json_value.clone()).await;
-                                            }
-                                            log::debug!("[{tag}] <<< SSE 事件: {data}");
-                                        } else {
-                                            log::debug!("[{tag}] <<< SSE 数据: {data}");
-                                        }
-                                    } else {
-                                        log::debug!("[{tag}] <<< SSE: [DONE]");
-                                    }
-                                }
-                            }
-                        }
+                    while let Some(event_text) = take_sse_event(&mut buffer) {
+                        process_sse_event(&event_text, collector.as_ref(), log_mode, tag).await;
                     }
 
                     yield Ok(bytes);
                 }
                 Some(Err(e)) => {
                     log::error!("[{tag}] 流错误: {e}");
+                    abort_guard.completed = true;
                     yield Err(std::io::Error::other(e.to_string()));
                     break;
                 }
@@ -559,12 +734,175 @@ pub fn create_logged_passthrough_stream(
             }
         }
 
+        // 上游已结束（正常结束、出错或超时），之后不再视为客户端断开
+        abort_guard.completed = true;
+
+        // 最后一个事件可能没有结尾空行，结束前处理缓冲区中剩余的内容
+        if !buffer.trim().is_empty() {
+            process_sse_event(&buffer, collector.as_ref(), log_mode, tag).await;
+        }
+
         if let Some(c) = collector.take() {
             c.finish().await;
         }
     }
 }
 
+/// 从缓冲区取出一个完整的 SSE 事件（不含分隔符）
+///
+/// 事件之间以空行分隔，兼容 `\n\n` 与 `\r\n\r\n` 两种写法
+fn take_sse_event(buffer: &mut String) -> Option<String> {
+    let (pos, separator_len) = [
+        buffer.find("\n\n").map(|pos| (pos, 2)),
+        buffer.find("\r\n\r\n").map(|pos| (pos, 4)),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by_key(|(pos, _)| *pos)?;
+    let event = buffer[..pos].to_string();
+    buffer.drain(..pos + separator_len);
+    Some(event)
+}
+
+/// 处理透传流中的一个完整 SSE 事件：收集 usage 并按日志方式记录
+async fn process_sse_event(
+    event_text: &str,
+    collector: Option<&SseUsageCollector>,
+    log_mode: SseLogMode,
+    tag: &str,
+) {
+    if log_mode == SseLogMode::UsageOnly {
+        collect_usage_event(event_text, collector).await;
+        return;
+    }
+    if event_text.trim().is_empty() {
+        return;
+    }
+
+    let redact_content = log_mode == SseLogMode::Redacted;
+    // 提取 data 部分并尝试解析为 JSON
+    for line in event_text.lines() {
+        let Some(data) = line.strip_prefix("data: ") else {
+            continue;
+        };
+        if data.trim() == "[DONE]" {
+            log::debug!("[{tag}] <<< SSE: [DONE]");
+            continue;
+        }
+        if let Ok(json_value) = serde_json::from_str::<Value>(data) {
+            if let Some(c) = collector {
+                c.push(json_value.clone()).await;
+            }
+            if redact_content {
+                log::debug!(
+                    "[{tag}] <<< SSE 事件: {}",
+                    redact_sse_event_for_log(&json_value)
+                );
+            } else {
+                log::debug!("[{tag}] <<< SSE 事件: {data}");
+            }
+        } else if redact_content {
+            log::debug!(
+                "[{tag}] <<< SSE 数据: [redacted {} chars]",
+                data.chars().count()
+            );
+        } else {
+            log::debug!("[{tag}] <<< SSE 数据: {data}");
+        }
+    }
+}
+
+/// 透传流中 SSE 事件的日志方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseLogMode {
+    /// 逐条记录完整事件
+    Full,
+    /// 逐条记录脱敏后的事件（见 `LogConfig.redact_sse_content`）
+    Redacted,
+    /// 轻量模式：不记录事件，只解析携带 usage 的事件（见 `ProxyConfig.lightweight_streaming`）
+    UsageOnly,
+}
+
+/// 轻量模式下处理一个完整 SSE 事件
+///
+/// 先做字符串预筛，只有包含 usage（`usage` / `usageMetadata`）的事件才解析为 JSON：
+/// Claude 的 message_start/message_delta、Codex 的 response.completed、
+/// OpenAI 末尾的 usage chunk 与 Gemini 的 usageMetadata 均能命中，模型名也随这些事件一并保留。
+async fn collect_usage_event(event_text: &str, collector: Option<&SseUsageCollector>) {
+    let Some(collector) = collector else {
+        return;
+    };
+    for line in event_text.lines() {
+        let Some(data) = line.strip_prefix("data:").map(str::trim_start) else {
+            continue;
+        };
+        if data.is_empty() || data == "[DONE]" {
+            continue;
+        }
+        collector.mark_first_event().await;
+        if !data.contains("\"usage") {
+            continue;
+        }
+        if let Ok(json_value) = serde_json::from_str::<Value>(data) {
+            collector.push(json_value).await;
+        }
+    }
+}
+
+/// 承载消息文本的字段，其字符串值在脱敏日志中被替换
+const SSE_TEXT_KEYS: &[&str] = &[
+    "text",
+    "thinking",
+    "partial_json",
+    "content",
+    "reasoning_content",
+    "delta",
+    "refusal",
+];
+
+/// 承载工具参数等任意内容的字段，其下所有字符串都会被替换
+const SSE_OPAQUE_KEYS: &[&str] = &["input", "arguments"];
+
+/// 生成用于日志的 SSE 事件副本：去掉消息文本，保留结构、事件类型与 usage
+///
+/// 仅影响日志输出，转发给客户端的字节不受影响。
+pub(crate) fn redact_sse_event_for_log(value: &Value) -> Value {
+    redact_value(value, false)
+}
+
+fn redact_value(value: &Value, opaque: bool) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, child)| {
+                    let redacted = if key == "usage" || key == "usageMetadata" {
+                        child.clone()
+                    } else if child.is_string() && (opaque || SSE_TEXT_KEYS.contains(&key.as_str()))
+                    {
+                        redacted_placeholder(child)
+                    } else {
+                        redact_value(child, opaque || SSE_OPAQUE_KEYS.contains(&key.as_str()))
+                    };
+                    (key.clone(), redacted)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| redact_value(item, opaque))
+                .collect(),
+        ),
+        Value::String(_) if opaque => redacted_placeholder(value),
+        other => other.clone(),
+    }
+}
+
+fn redacted_placeholder(value: &Value) -> Value {
+    let len = value.as_str().map(|s| s.chars().count()).unwrap_or(0);
+    Value::String(format!("[redacted {len} chars]"))
+}
+
 fn format_headers(headers: &HeaderMap) -> String {
     headers
         .iter()
@@ -582,9 +920,13 @@ mod tests {
     use crate::database::Database;
     use crate::error::AppError;
     use crate::provider::ProviderMeta;
+    use crate::proxy::concurrency::ConcurrencyLimiter;
     use crate::proxy::failover_switch::FailoverSwitchManager;
     use crate::proxy::provider_router::ProviderRouter;
+    use crate::proxy::request_feed::RequestFeed;
+    use crate::proxy::response_cache::ResponseCache;
     use crate::proxy::types::{ProxyConfig, ProxyStatus};
+    use crate::proxy::usage::{LoggingPause, UsageLogWriter};
     use rust_decimal::Decimal;
     use std::collections::HashMap;
     use std::str::FromStr;
@@ -600,7 +942,12 @@ mod tests {
             current_providers: Arc::new(RwLock::new(HashMap::new())),
             provider_router: Arc::new(ProviderRouter::new(db.clone())),
             app_handle: None,
-            failover_manager: Arc::new(FailoverSwitchManager::new(db)),
+            failover_manager: Arc::new(FailoverSwitchManager::new(db.clone())),
+            response_cache: Arc::new(ResponseCache::default()),
+            logging_paused: Arc::new(LoggingPause::default()),
+            usage_writer: Arc::new(UsageLogWriter::spawn(db)),
+            request_feed: Arc::new(RequestFeed::default()),
+            concurrency: Arc::new(ConcurrencyLimiter::new(0)),
         }
     }
 
@@ -659,6 +1006,7 @@ mod tests {
             output_tokens: 0,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -674,8 +1022,12 @@ mod tests {
             false,
             200,
             None,
+            None,
+            false,
+            None,
         )
         .await;
+        state.usage_writer.flush().await;
 
         let conn = crate::database::lock_conn!(db.conn);
         let (model, request_model, total_cost, cost_multiplier): (String, String, String, String) =
@@ -718,6 +1070,7 @@ mod tests {
             output_tokens: 0,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -733,8 +1086,12 @@ mod tests {
             false,
             200,
             None,
+            None,
+            false,
+            None,
         )
         .await;
+        state.usage_writer.flush().await;
 
         let conn = crate::database::lock_conn!(db.conn);
         let (total_cost, cost_multiplier): (String, String) = conn
@@ -756,4 +1113,471 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_paused_logging_writes_no_rows() -> Result<(), AppError> {
+        let db = Arc::new(Database::memory()?);
+        let state = build_state(db.clone());
+        let log = |state: ProxyState| async move {
+            log_usage_internal(
+                &state,
+                "provider-3",
+                "claude",
+                "resp-model",
+                "req-model",
+                TokenUsage::default(),
+                10,
+                None,
+                false,
+                200,
+                None,
+                None,
+                false,
+                None,
+            )
+            .await;
+        };
+        let count = || -> Result<i64, AppError> {
+            let conn = crate::database::lock_conn!(db.conn);
+            conn.query_row(
+                "SELECT COUNT(*) FROM proxy_request_logs WHERE provider_id = 'provider-3'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| AppError::Database(e.to_string()))
+        };
+
+        state.logging_paused.set(true, None);
+        log(state.clone()).await;
+        log(state.clone()).await;
+        state.usage_writer.flush().await;
+        assert_eq!(count()?, 0);
+
+        state.logging_paused.set(false, None);
+        log(state.clone()).await;
+        state.usage_writer.flush().await;
+        assert_eq!(count()?, 1);
+        Ok(())
+    }
+
+    /// 以指定 meta 的单个 Claude 供应商构建请求上下文
+    async fn context_with_meta(
+        meta: ProviderMeta,
+        global_logging: bool,
+    ) -> Result<(Arc<Database>, ProxyState, RequestContext), AppError> {
+        use crate::app_config::AppType;
+        use crate::provider::Provider;
+
+        let db = Arc::new(Database::memory()?);
+        let mut provider = Provider::with_id(
+            "relay".to_string(),
+            "Relay".to_string(),
+            serde_json::json!({}),
+            None,
+        );
+        provider.meta = Some(meta);
+        db.save_provider("claude", &provider)?;
+        // 开启故障转移后由队列选出供应商，避免依赖设备级当前供应商
+        let mut app_config = db.get_proxy_config_for_app("claude").await?;
+        app_config.auto_failover_enabled = true;
+        db.update_proxy_config_for_app(app_config).await?;
+        db.add_to_failover_queue("claude", "relay")?;
+
+        let state = build_state(db.clone());
+        state.config.write().await.enable_logging = global_logging;
+        let ctx = RequestContext::new(
+            &state,
+            &serde_json::json!({ "model": "req-model" }),
+            &axum::http::HeaderMap::new(),
+            AppType::Claude,
+            "Claude",
+            "claude",
+        )
+        .await
+        .map_err(|e| AppError::Message(e.to_string()))?;
+        Ok((db, state, ctx))
+    }
+
+    /// 轮询该供应商的请求日志条数，直到达到 `expected` 或超时，返回最后一次的条数
+    ///
+    /// 日志由 `tokio::spawn` 的任务提交给写入器，每轮先 flush 再计数。
+    /// 关闭日志时 `spawn_log_usage` 直接返回、不会派生任务，预期为 0 时无需等待。
+    async fn relay_log_count(
+        db: &Database,
+        state: &ProxyState,
+        expected: i64,
+    ) -> Result<i64, AppError> {
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            state.usage_writer.flush().await;
+            let count: i64 = {
+                let conn = crate::database::lock_conn!(db.conn);
+                conn.query_row(
+                    "SELECT COUNT(*) FROM proxy_request_logs WHERE provider_id = 'relay'",
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?
+            };
+            if count == expected || tokio::time::Instant::now() >= deadline {
+                return Ok(count);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+    }
+
+    /// 按供应商日志开关走一次非流式记录
+    async fn log_with_provider_setting(
+        provider_setting: Option<bool>,
+        global_logging: bool,
+    ) -> Result<(Arc<Database>, ProxyState, RequestContext), AppError> {
+        let meta = ProviderMeta {
+            enable_request_logging: provider_setting,
+            ..Default::default()
+        };
+        let (db, state, ctx) = context_with_meta(meta, global_logging).await?;
+
+        spawn_log_usage(
+            &state,
+            &ctx,
+            TokenUsage::default(),
+            "resp-model",
+            "req-model",
+            200,
+            false,
+        );
+        Ok((db, state, ctx))
+    }
+
+    #[tokio::test]
+    async fn test_provider_logging_setting_overrides_global() -> Result<(), AppError> {
+        let cases = [
+            // 未设置时沿用全局开关
+            (None, true, 1, SseLogMode::Full),
+            (None, false, 0, SseLogMode::UsageOnly),
+            // 供应商关闭日志：不写明细，也不逐条记录 SSE 内容
+            (Some(false), true, 0, SseLogMode::UsageOnly),
+            // 全局关闭时仍可只为该供应商开启
+            (Some(true), false, 1, SseLogMode::Full),
+        ];
+        for (provider_setting, global_logging, expected_rows, expected_mode) in cases {
+            let (db, state, ctx) =
+                log_with_provider_setting(provider_setting, global_logging).await?;
+            assert_eq!(
+                relay_log_count(&db, &state, expected_rows).await?,
+                expected_rows,
+                "provider={provider_setting:?}, global={global_logging}"
+            );
+            assert_eq!(ctx.sse_log_mode(), expected_mode);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_raw_passthrough_records_status_without_usage() -> Result<(), AppError> {
+        let meta = ProviderMeta {
+            raw_passthrough: Some(true),
+            ..Default::default()
+        };
+        let (db, state, ctx) = context_with_meta(meta, true).await?;
+        assert!(ctx.provider.is_raw_passthrough());
+
+        // 响应体带有 usage，但透传模式不解析
+        let upstream_body =
+            br#"{"model":"resp-model","usage":{"input_tokens":12,"output_tokens":34}}  "#;
+        let upstream = axum::http::Response::builder()
+            .status(201)
+            .header("content-type", "application/json")
+            .header("x-upstream", "kept")
+            .body(upstream_body.to_vec())
+            .map_err(|e| AppError::Message(e.to_string()))?;
+
+        let response = handle_raw_passthrough(reqwest::Response::from(upstream), &ctx, &state);
+        assert_eq!(response.status().as_u16(), 201);
+        assert_eq!(response.headers()["x-upstream"], "kept");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map_err(|e| AppError::Message(e.to_string()))?;
+        assert_eq!(body.as_ref(), upstream_body);
+
+        assert_eq!(relay_log_count(&db, &state, 1).await?, 1);
+        let conn = crate::database::lock_conn!(db.conn);
+        let (status, input, output, model): (i64, i64, i64, String) = conn
+            .query_row(
+                "SELECT status_code, input_tokens, output_tokens, model
+                 FROM proxy_request_logs WHERE provider_id = 'relay'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        assert_eq!(status, 201);
+        assert_eq!((input, output), (0, 0));
+        assert_eq!(model, "req-model");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_logged_request_is_published_to_feed() -> Result<(), AppError> {
+        let db = Arc::new(Database::memory()?);
+        let state = build_state(db);
+        let mut receiver = state.request_feed.receiver();
+        state.request_feed.set_subscribed(true);
+
+        let usage = TokenUsage {
+            input_tokens: 120,
+            output_tokens: 45,
+            ..Default::default()
+        };
+        log_usage_internal(
+            &state,
+            "provider-4",
+            "claude",
+            "resp-model",
+            "req-model",
+            usage,
+            321,
+            None,
+            true,
+            200,
+            None,
+            None,
+            false,
+            None,
+        )
+        .await;
+
+        let summary = receiver.try_recv().expect("one summary published");
+        assert!(!summary.request_id.is_empty());
+        assert_eq!(summary.provider_id, "provider-4");
+        assert_eq!(summary.app_type, "claude");
+        assert_eq!(summary.model, "resp-model");
+        assert_eq!(summary.input_tokens, 120);
+        assert_eq!(summary.output_tokens, 45);
+        assert_eq!(summary.status_code, 200);
+        assert_eq!(summary.latency_ms, 321);
+        assert!(summary.is_streaming);
+        assert!(receiver.try_recv().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn redacted_sse_log_omits_content_but_keeps_usage() {
+        let delta = serde_json::json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": { "type": "text_delta", "text": "my secret prompt answer" }
+        });
+        let redacted = redact_sse_event_for_log(&delta);
+        assert_eq!(redacted["type"], "content_block_delta");
+        assert_eq!(redacted["delta"]["type"], "text_delta");
+        assert_eq!(redacted["delta"]["text"], "[redacted 23 chars]");
+
+        let chat_chunk = serde_json::json!({
+            "model": "gpt-5",
+            "choices": [{
+                "index": 0,
+                "delta": {
+                    "content": "secret",
+                    "tool_calls": [{ "function": { "name": "read_file", "arguments": "{\"path\":\"/etc/passwd\"}" } }]
+                }
+            }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15 }
+        });
+        let redacted = redact_sse_event_for_log(&chat_chunk);
+        let logged = redacted.to_string();
+        assert!(!logged.contains("secret"));
+        assert!(!logged.contains("/etc/passwd"));
+        assert_eq!(redacted["model"], "gpt-5");
+        assert_eq!(
+            redacted["choices"][0]["delta"]["tool_calls"][0]["function"]["name"],
+            "read_file"
+        );
+        assert_eq!(redacted["usage"], chat_chunk["usage"]);
+    }
+
+    const CLAUDE_STREAM_CHUNKS: [&str; 6] = [
+        "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet-4-5\",\"usage\":{\"input_tokens\":10,\"output_tokens\":1}}}\n\n",
+        "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+        "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"hello\"}}\n\n",
+        "event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+        "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":25}}\n\n",
+        "event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+    ];
+
+    async fn run_claude_passthrough(tag: &'static str, log_mode: SseLogMode) -> Vec<Value> {
+        let chunks = CLAUDE_STREAM_CHUNKS.map(str::to_string).to_vec();
+        run_passthrough(tag, log_mode, chunks).await
+    }
+
+    async fn run_passthrough(
+        tag: &'static str,
+        log_mode: SseLogMode,
+        chunks: Vec<String>,
+    ) -> Vec<Value> {
+        let chunk_count = chunks.len();
+        let upstream = futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::from(chunk))),
+        );
+
+        let collected: Arc<std::sync::Mutex<Vec<Value>>> = Arc::default();
+        let sink = collected.clone();
+        let collector = SseUsageCollector::new(std::time::Instant::now(), move |events, _, _| {
+            *sink.lock().unwrap() = events;
+        });
+        let timeout_config = StreamingTimeoutConfig {
+            first_byte_timeout: 0,
+            idle_timeout: 0,
+        };
+
+        let passthrough = create_logged_passthrough_stream(
+            upstream,
+            tag,
+            Some(collector),
+            timeout_config,
+            log_mode,
+        );
+        let forwarded: Vec<_> = passthrough.collect().await;
+        let events = collected.lock().unwrap().clone();
+        assert_eq!(forwarded.len(), chunk_count, "all chunks are forwarded");
+        events
+    }
+
+    #[tokio::test]
+    async fn test_lightweight_streaming_records_usage_without_event_logs() {
+        // 对照：完整模式逐条解析全部事件
+        let full_events = run_claude_passthrough("stream-full", SseLogMode::Full).await;
+        assert_eq!(full_events.len(), 6);
+
+        let events = run_claude_passthrough("stream-lightweight", SseLogMode::UsageOnly).await;
+        assert_eq!(events.len(), 2, "only usage-bearing events are parsed");
+
+        let usage = TokenUsage::from_claude_stream_events(&events).expect("usage parsed");
+        assert_eq!(usage.input_tokens, 10);
+        assert_eq!(usage.output_tokens, 25);
+        assert_eq!(usage.model.as_deref(), Some("claude-sonnet-4-5"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_stream_yields_stream_idle_timeout() {
+        let upstream = futures::stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from(
+            CLAUDE_STREAM_CHUNKS[0],
+        ))])
+        .chain(futures::stream::pending());
+        let timeout_config = StreamingTimeoutConfig {
+            first_byte_timeout: 0,
+            idle_timeout: 1,
+        };
+
+        let forwarded: Vec<_> = create_logged_passthrough_stream(
+            upstream,
+            "stream-idle",
+            None,
+            timeout_config,
+            SseLogMode::UsageOnly,
+        )
+        .collect()
+        .await;
+
+        assert_eq!(forwarded.len(), 2);
+        let error = forwarded[1].as_ref().expect_err("idle timeout error");
+        let proxy_error = error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<ProxyError>())
+            .expect("wrapped proxy error");
+        assert!(matches!(proxy_error, ProxyError::StreamIdleTimeout(1)));
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_handles_crlf_event_separators() {
+        let chunks: Vec<String> = CLAUDE_STREAM_CHUNKS
+            .iter()
+            .map(|chunk| chunk.replace('\n', "\r\n"))
+            .collect();
+
+        for log_mode in [SseLogMode::Full, SseLogMode::UsageOnly] {
+            let events = run_passthrough("stream-crlf", log_mode, chunks.clone()).await;
+            let usage = TokenUsage::from_claude_stream_events(&events).expect("usage parsed");
+            assert_eq!(usage.input_tokens, 10);
+            assert_eq!(usage.output_tokens, 25);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_flushes_last_event_without_blank_line() {
+        // 最后一个携带 usage 的事件没有结尾空行，且被拆成两个分片
+        let chunks = vec![
+            CLAUDE_STREAM_CHUNKS[0].to_string(),
+            CLAUDE_STREAM_CHUNKS[2].to_string(),
+            "event: message_delta\ndata: {\"type\":\"message_delta\",".to_string(),
+            "\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":25}}"
+                .to_string(),
+        ];
+
+        for log_mode in [SseLogMode::Full, SseLogMode::UsageOnly] {
+            let events = run_passthrough("stream-unterminated", log_mode, chunks.clone()).await;
+            let usage = TokenUsage::from_claude_stream_events(&events).expect("usage parsed");
+            assert_eq!(usage.output_tokens, 25);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropped_client_cancels_upstream_stream() {
+        struct DropFlag(Arc<AtomicBool>);
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        // 上游：发送首个事件后一直挂起，模拟仍在生成的长回复
+        let upstream_dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(upstream_dropped.clone());
+        let upstream = futures::stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from(
+            CLAUDE_STREAM_CHUNKS[0],
+        ))])
+        .chain(futures::stream::pending())
+        .map(move |chunk| {
+            let _keep_alive = &flag;
+            chunk
+        });
+
+        let outcome: Arc<std::sync::Mutex<Option<(usize, bool)>>> = Arc::default();
+        let sink = outcome.clone();
+        let collector =
+            SseUsageCollector::new(std::time::Instant::now(), move |events, _, aborted| {
+                *sink.lock().unwrap() = Some((events.len(), aborted));
+            });
+        let passthrough = create_logged_passthrough_stream(
+            upstream,
+            "stream-abort",
+            Some(collector),
+            StreamingTimeoutConfig {
+                first_byte_timeout: 0,
+                idle_timeout: 0,
+            },
+            SseLogMode::Full,
+        );
+        let mut passthrough = Box::pin(passthrough);
+
+        assert!(passthrough.next().await.is_some_and(|chunk| chunk.is_ok()));
+        assert!(!upstream_dropped.load(Ordering::SeqCst));
+
+        // 客户端断开：响应体（透传流）被丢弃
+        drop(passthrough);
+        assert!(
+            upstream_dropped.load(Ordering::SeqCst),
+            "upstream stream must be released with the client body"
+        );
+
+        // client_aborted 记录在后台任务中完成
+        for _ in 0..50 {
+            if outcome.lock().unwrap().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*outcome.lock().unwrap(), Some((1, true)));
+    }
 }
@@ -3,8 +3,16 @@
 //! 基于Axum的HTTP服务器，处理代理请求
 
 use super::{
-    failover_switch::FailoverSwitchManager, handlers, log_codes::srv as log_srv,
-    provider_router::ProviderRouter, types::*, ProxyError,
+    concurrency::{self, ConcurrencyLimiter},
+    failover_switch::FailoverSwitchManager,
+    handlers,
+    log_codes::srv as log_srv,
+    provider_router::ProviderRouter,
+    request_feed::RequestFeed,
+    response_cache::ResponseCache,
+    types::*,
+    usage::{LoggingPause, UsageLogWriter},
+    ProxyError,
 };
 use crate::database::Database;
 use axum::{
@@ -13,6 +21,7 @@ use axum::{
     Router,
 };
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{oneshot, RwLock};
 use tokio::task::JoinHandle;
@@ -33,6 +42,83 @@ pub struct ProxyState {
     pub app_handle: Option<tauri::AppHandle>,
     /// 故障转移切换管理器
     pub failover_manager: Arc<FailoverSwitchManager>,
+    /// 相同非流式请求的响应缓存（由 enable_response_cache 控制）
+    pub response_cache: Arc<ResponseCache>,
+    /// 运行时暂停使用量记录（压测时避免污染统计，可定时自动恢复）
+    pub logging_paused: Arc<LoggingPause>,
+    /// 使用日志单写入任务（批量事务写入，避免请求任务争抢数据库锁）
+    pub usage_writer: Arc<UsageLogWriter>,
+    /// 实时请求流（界面订阅后推送每条请求摘要）
+    pub request_feed: Arc<RequestFeed>,
+    /// 全局并发上限（由 max_concurrent_requests 控制）
+    pub concurrency: Arc<ConcurrencyLimiter>,
+}
+
+/// 绑定 Unix Domain Socket 并在后台处理连接，返回监听任务与绑定路径
+///
+/// 路径上遗留的旧 socket 文件（上次异常退出）会被删除；路径已被普通文件占用时报错。
+#[cfg(unix)]
+fn bind_unix_socket(path: &Path, app: Router) -> Result<(JoinHandle<()>, PathBuf), ProxyError> {
+    use hyper_util::rt::TokioIo;
+    use std::os::unix::fs::FileTypeExt;
+    use tower::Service;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(ProxyError::BindFailed(format!(
+                "{} 已存在且不是 socket 文件",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(path)
+            .map_err(|e| ProxyError::BindFailed(format!("{}: {e}", path.display())))?;
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| ProxyError::BindFailed(format!("{}: {e}", parent.display())))?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)
+        .map_err(|e| ProxyError::BindFailed(format!("{}: {e}", path.display())))?;
+    log::info!(
+        "[{}] 代理服务器同时监听 Unix Socket {}",
+        log_srv::STARTED,
+        path.display()
+    );
+
+    let handle = tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("Unix Socket 接受连接失败: {e}");
+                    continue;
+                }
+            };
+            let app = app.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(
+                    move |request: hyper::Request<hyper::body::Incoming>| app.clone().call(request),
+                );
+                if let Err(e) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    log::debug!("Unix Socket 连接异常结束: {e}");
+                }
+            });
+        }
+    });
+    Ok((handle, path.to_path_buf()))
+}
+
+/// 当前平台不支持 Unix Domain Socket，回退为仅监听 TCP
+#[cfg(not(unix))]
+fn bind_unix_socket(path: &Path, _app: Router) -> Result<(JoinHandle<()>, PathBuf), ProxyError> {
+    Err(ProxyError::BindFailed(format!(
+        "当前平台不支持 Unix Socket（{}）",
+        path.display()
+    )))
 }
 
 /// 代理HTTP服务器
@@ -42,6 +128,8 @@ pub struct ProxyServer {
     shutdown_tx: Arc<RwLock<Option<oneshot::Sender<()>>>>,
     /// 服务器任务句柄，用于等待服务器实际关闭
     server_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// Unix Socket 监听任务与实际绑定的路径（停止时中止任务并删除 socket 文件）
+    socket_listener: Arc<RwLock<Option<(JoinHandle<()>, PathBuf)>>>,
 }
 
 impl ProxyServer {
@@ -55,6 +143,8 @@ impl ProxyServer {
         // 创建故障转移切换管理器
         let failover_manager = Arc::new(FailoverSwitchManager::new(db.clone()));
 
+        let usage_writer = Arc::new(UsageLogWriter::spawn(db.clone()));
+
         let state = ProxyState {
             db,
             config: Arc::new(RwLock::new(config.clone())),
@@ -64,6 +154,11 @@ impl ProxyServer {
             provider_router,
             app_handle,
             failover_manager,
+            response_cache: Arc::new(ResponseCache::default()),
+            logging_paused: Arc::new(LoggingPause::default()),
+            usage_writer,
+            request_feed: Arc::new(RequestFeed::default()),
+            concurrency: Arc::new(ConcurrencyLimiter::new(config.max_concurrent_requests)),
         };
 
         Self {
@@ -71,9 +166,16 @@ impl ProxyServer {
             state,
             shutdown_tx: Arc::new(RwLock::new(None)),
             server_handle: Arc::new(RwLock::new(None)),
+            socket_listener: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// 使用外部持有的实时请求流（订阅状态跨代理重启保持）
+    pub fn with_request_feed(mut self, feed: Arc<RequestFeed>) -> Self {
+        self.state.request_feed = feed;
+        self
+    }
+
     pub async fn start(&self) -> Result<ProxyServerInfo, ProxyError> {
         // 检查是否已在运行
         if self.shutdown_tx.read().await.is_some() {
@@ -98,6 +200,24 @@ impl ProxyServer {
 
         log::info!("[{}] 代理服务器启动于 {addr}", log_srv::STARTED);
 
+        // 额外监听 Unix Socket（失败时仅记录警告，TCP 端口照常提供服务）
+        let socket_path = match self.config.listen_socket_path.as_deref() {
+            Some(path) if !path.trim().is_empty() => {
+                match bind_unix_socket(Path::new(path.trim()), app.clone()) {
+                    Ok(listener) => {
+                        let path = listener.1.clone();
+                        *self.socket_listener.write().await = Some(listener);
+                        Some(path)
+                    }
+                    Err(e) => {
+                        log::warn!("[{}] {e}，仅监听 TCP", log_srv::STARTED);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
         // 更新全局代理端口，用于系统代理检测
         crate::proxy::http_client::set_proxy_port(self.config.listen_port);
 
@@ -135,6 +255,7 @@ impl ProxyServer {
         Ok(ProxyServerInfo {
             address: self.config.listen_address.clone(),
             port: self.config.listen_port,
+            socket_path: socket_path.map(|path| path.display().to_string()),
             started_at: chrono::Utc::now().to_rfc3339(),
         })
     }
@@ -147,6 +268,12 @@ impl ProxyServer {
             return Err(ProxyError::NotRunning);
         }
 
+        // Unix Socket 监听没有优雅关闭，直接中止并清理 socket 文件
+        if let Some((handle, path)) = self.socket_listener.write().await.take() {
+            handle.abort();
+            let _ = std::fs::remove_file(&path);
+        }
+
         // 2. 等待服务器任务结束（带 5 秒超时保护）
         if let Some(handle) = self.server_handle.write().await.take() {
             match tokio::time::timeout(std::time::Duration::from_secs(5), handle).await {
@@ -193,6 +320,15 @@ impl ProxyServer {
         status
     }
 
+    /// 实际监听的 Unix Socket 路径
+    pub async fn socket_path(&self) -> Option<String> {
+        self.socket_listener
+            .read()
+            .await
+            .as_ref()
+            .map(|(_, path)| path.display().to_string())
+    }
+
     /// 更新某个应用类型当前“目标供应商”（用于 UI 展示 active_targets）
     ///
     /// 注意：这不代表该供应商一定已经处理过请求，而是用于“热切换/启用故障转移立即切 P1”
@@ -212,9 +348,6 @@ impl ProxyServer {
             .allow_headers(Any);
 
         Router::new()
-            // 健康检查
-            .route("/health", get(handlers::health_check))
-            .route("/status", get(handlers::get_status))
             // Claude API (支持带前缀和不带前缀两种格式)
             .route("/v1/messages", post(handlers::handle_messages))
             .route("/claude/v1/messages", post(handlers::handle_messages))
@@ -230,7 +363,7 @@ impl ProxyServer {
             )
             .route(
                 "/codex/v1/chat/completions",
-                post(handlers::handle_chat_completions),
+                post(handlers::handle_codex_chat_completions),
             )
             // OpenAI Responses API (Codex CLI，支持带前缀和不带前缀)
             .route("/responses", post(handlers::handle_responses))
@@ -257,6 +390,14 @@ impl ProxyServer {
             // Gemini API (支持带前缀和不带前缀)
             .route("/v1beta/*path", post(handlers::handle_gemini))
             .route("/gemini/v1beta/*path", post(handlers::handle_gemini))
+            // 并发上限只作用于以上 API 路由，健康检查与状态查询不受影响
+            .route_layer(axum::middleware::from_fn_with_state(
+                self.state.concurrency.clone(),
+                concurrency::limit_concurrency,
+            ))
+            // 健康检查
+            .route("/health", get(handlers::health_check))
+            .route("/status", get(handlers::get_status))
             // 提高默认请求体大小限制（避免 413 Payload Too Large）
             .layer(DefaultBodyLimit::max(200 * 1024 * 1024))
             .layer(cors)
@@ -266,6 +407,12 @@ impl ProxyServer {
     /// 在不重启服务的情况下更新运行时配置
     pub async fn apply_runtime_config(&self, config: &ProxyConfig) {
         *self.state.config.write().await = config.clone();
+        self.state
+            .concurrency
+            .set_limit(config.max_concurrent_requests);
+        if !config.enable_response_cache {
+            self.state.response_cache.clear();
+        }
     }
 
     /// 热更新熔断器配置
@@ -285,4 +432,61 @@ impl ProxyServer {
             .reset_provider_breaker(provider_id, app_type)
             .await;
     }
+
+    /// 暂停/恢复使用量记录；`duration` 到期后自动恢复
+    pub fn set_logging_paused(&self, paused: bool, duration: Option<std::time::Duration>) {
+        self.state.logging_paused.set(paused, duration);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use hyper_util::rt::TokioIo;
+
+    #[tokio::test]
+    async fn test_unix_socket_listener_routes_requests() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let socket_path = dir.path().join("proxy.sock");
+        let config = ProxyConfig {
+            listen_port: 0,
+            listen_socket_path: Some(socket_path.display().to_string()),
+            ..ProxyConfig::default()
+        };
+        let db = Arc::new(Database::memory().expect("init db"));
+        let server = ProxyServer::new(config, db, None);
+
+        let info = server.start().await.expect("start proxy");
+        assert_eq!(
+            info.socket_path.as_deref(),
+            Some(socket_path.display().to_string().as_str())
+        );
+
+        let stream = tokio::net::UnixStream::connect(&socket_path)
+            .await
+            .expect("connect unix socket");
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .expect("http handshake");
+        tokio::spawn(connection);
+
+        let request = hyper::Request::get("/health")
+            .header(hyper::header::HOST, "localhost")
+            .body(Body::empty())
+            .expect("build request");
+        let response = sender.send_request(request).await.expect("send request");
+        assert_eq!(response.status(), hyper::StatusCode::OK);
+        let body = axum::body::to_bytes(Body::new(response.into_body()), usize::MAX)
+            .await
+            .expect("read body");
+        let body: serde_json::Value = serde_json::from_slice(&body).expect("parse body");
+        assert_eq!(body["status"], "healthy");
+
+        server.stop().await.expect("stop proxy");
+        assert!(
+            !socket_path.exists(),
+            "socket file should be removed on stop"
+        );
+    }
 }
@@ -0,0 +1,294 @@
+//! 影子供应商请求镜像
+//!
+//! 供应商配置 `meta.shadowProviderId` 后，每个请求在发往主供应商的同时，
+//! 异步复制一份发往影子供应商（fire-and-forget），用于对比响应与延迟：
+//! - 客户端只会收到主供应商的响应，影子请求的成败不影响主流程
+//! - 影子请求不参与熔断器、故障转移和代理状态统计
+//! - 影子请求的用量与延迟单独写入请求日志（`is_shadow = 1`），
+//!   同样遵循全局与影子供应商自身的请求日志开关，且不计入用量统计
+
+use super::{
+    error_mapper::{get_error_message, map_proxy_error_to_status},
+    handler_config::{
+        UsageParserConfig, CLAUDE_PARSER_CONFIG, CODEX_PARSER_CONFIG, GEMINI_PARSER_CONFIG,
+        OPENAI_PARSER_CONFIG,
+    },
+    server::ProxyState,
+    usage::{
+        logger::{RequestLog, UsageLogger},
+        CostCalculator, LoggingPause, TokenUsage, UsageLogWriter,
+    },
+    ProxyError,
+};
+use crate::{app_config::AppType, database::Database, provider::Provider};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 影子请求的日志落点
+#[derive(Clone)]
+pub struct ShadowSink {
+    pub(crate) db: Arc<Database>,
+    usage_writer: Arc<UsageLogWriter>,
+    logging_paused: Arc<LoggingPause>,
+    /// 代理全局的请求日志开关（影子供应商未单独设置时生效）
+    global_logging: bool,
+}
+
+impl ShadowSink {
+    pub fn new(
+        db: Arc<Database>,
+        usage_writer: Arc<UsageLogWriter>,
+        logging_paused: Arc<LoggingPause>,
+        global_logging: bool,
+    ) -> Self {
+        Self {
+            db,
+            usage_writer,
+            logging_paused,
+            global_logging,
+        }
+    }
+
+    pub fn from_state(state: &ProxyState, global_logging: bool) -> Self {
+        Self::new(
+            state.db.clone(),
+            state.usage_writer.clone(),
+            state.logging_paused.clone(),
+            global_logging,
+        )
+    }
+
+    /// 是否记录该影子供应商的请求日志（供应商设置优先于全局 `enable_logging`）
+    fn logging_enabled(&self, provider: &Provider) -> bool {
+        provider
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.enable_request_logging)
+            .unwrap_or(self.global_logging)
+    }
+
+    /// 记录一次影子请求的结果（读取完整响应体以解析用量）
+    pub(crate) async fn record(
+        &self,
+        app_type: &AppType,
+        provider: &Provider,
+        request_body: &Value,
+        started_at: Instant,
+        result: Result<reqwest::Response, ProxyError>,
+    ) {
+        let request_model = request_body
+            .get("model")
+            .and_then(|m| m.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let is_streaming = request_body
+            .get("stream")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+
+        let (status_code, usage, error_message) = match result {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                match response.bytes().await {
+                    Ok(body) => (status, parse_usage(app_type, &body), None),
+                    Err(e) => (status, None, Some(format!("读取影子响应失败: {e}"))),
+                }
+            }
+            Err(e) => (
+                map_proxy_error_to_status(&e),
+                None,
+                Some(get_error_message(&e)),
+            ),
+        };
+        let latency_ms = started_at.elapsed().as_millis() as u64;
+
+        log::info!(
+            "[{}] 影子供应商 {} 响应: status={status_code}, latency_ms={latency_ms}",
+            app_type.as_str(),
+            provider.name
+        );
+
+        if self.logging_paused.is_paused() || !self.logging_enabled(provider) {
+            return;
+        }
+
+        let usage = usage.unwrap_or_default();
+        let model = usage.model.clone().unwrap_or_else(|| request_model.clone());
+        let logger = UsageLogger::new(&self.db).with_writer(&self.usage_writer);
+        let (multiplier, _) = logger
+            .resolve_pricing_config(&provider.id, app_type.as_str())
+            .await;
+        let cost = match logger.get_model_pricing(&model) {
+            Ok(pricing) => CostCalculator::try_calculate(&usage, pricing.as_ref(), multiplier),
+            Err(e) => {
+                log::warn!("[USG-002] 影子请求定价查询失败 ({model}): {e}");
+                None
+            }
+        };
+
+        let log = RequestLog {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            provider_id: provider.id.clone(),
+            app_type: app_type.as_str().to_string(),
+            model,
+            request_model,
+            usage,
+            cost,
+            latency_ms,
+            first_token_ms: None,
+            status_code,
+            error_message,
+            session_id: None,
+            provider_type: None,
+            is_streaming,
+            cost_multiplier: multiplier.to_string(),
+            is_cached: false,
+            is_shadow: true,
+            api_key_index: None,
+            is_safety_net: false,
+            effective_max_tokens: None,
+        };
+        if let Err(e) = logger.log_request(&log) {
+            log::warn!("[USG-001] 记录影子请求失败: {e}");
+        }
+    }
+}
+
+/// 供应商配置的影子供应商 ID（未配置、为空或指向自身时返回 None）
+pub fn shadow_provider_id(provider: &Provider) -> Option<&str> {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.shadow_provider_id.as_deref())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && *id != provider.id)
+}
+
+fn parser_for(app_type: &AppType) -> Option<&'static UsageParserConfig> {
+    match app_type {
+        AppType::Claude => Some(&CLAUDE_PARSER_CONFIG),
+        AppType::Codex => Some(&CODEX_PARSER_CONFIG),
+        AppType::Gemini => Some(&GEMINI_PARSER_CONFIG),
+        _ => None,
+    }
+}
+
+/// 从完整的上游响应体解析用量（兼容 JSON 与 SSE）
+///
+/// 影子请求拿到的是未经格式转换的上游原始响应，应用自身格式解析失败时再按 OpenAI 格式尝试。
+pub(crate) fn parse_usage(app_type: &AppType, body: &[u8]) -> Option<TokenUsage> {
+    let parser = parser_for(app_type)?;
+    let text = String::from_utf8_lossy(body);
+
+    if let Ok(json) = serde_json::from_str::<Value>(&text) {
+        return (parser.response_parser)(&json)
+            .or_else(|| (OPENAI_PARSER_CONFIG.response_parser)(&json));
+    }
+
+    let events: Vec<Value> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim)
+        .filter(|data| !data.is_empty() && *data != "[DONE]")
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect();
+    if events.is_empty() {
+        return None;
+    }
+    (parser.stream_parser)(&events).or_else(|| (OPENAI_PARSER_CONFIG.stream_parser)(&events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    #[test]
+    fn shadow_provider_id_ignores_empty_and_self() {
+        let mut provider = Provider::with_id("p1".into(), "P1".into(), json!({}), None);
+        assert_eq!(shadow_provider_id(&provider), None);
+
+        for (value, expected) in [("", None), ("p1", None), (" p2 ", Some("p2"))] {
+            provider.meta = Some(ProviderMeta {
+                shadow_provider_id: Some(value.to_string()),
+                ..Default::default()
+            });
+            assert_eq!(shadow_provider_id(&provider), expected);
+        }
+    }
+
+    #[test]
+    fn parse_usage_handles_json_and_sse_bodies() {
+        let json_body = json!({
+            "id": "msg_1",
+            "model": "claude-sonnet-4-5",
+            "usage": { "input_tokens": 12, "output_tokens": 34 }
+        })
+        .to_string();
+        let usage = parse_usage(&AppType::Claude, json_body.as_bytes()).expect("json usage");
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 34));
+
+        let sse_body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet-4-5\",\"usage\":{\"input_tokens\":5,\"output_tokens\":1}}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":7}}\n\n",
+        );
+        let usage = parse_usage(&AppType::Claude, sse_body.as_bytes()).expect("sse usage");
+        assert_eq!((usage.input_tokens, usage.output_tokens), (5, 7));
+    }
+
+    #[tokio::test]
+    async fn record_honors_logging_switches() {
+        let db = Arc::new(Database::memory().expect("init db"));
+        let writer = Arc::new(UsageLogWriter::spawn(db.clone()));
+        let sink = ShadowSink::new(
+            db.clone(),
+            writer.clone(),
+            Arc::new(LoggingPause::default()),
+            false,
+        );
+        let body = json!({ "model": "claude-sonnet-4-5" });
+        let shadow_rows = || -> i64 {
+            let conn = crate::database::lock_conn!(db.conn);
+            conn.query_row(
+                "SELECT COUNT(*) FROM proxy_request_logs WHERE is_shadow = 1",
+                [],
+                |row| row.get(0),
+            )
+            .expect("count shadow rows")
+        };
+
+        // 全局关闭日志且供应商未单独设置：不记录
+        let mut provider = Provider::with_id("shadow".into(), "Shadow".into(), json!({}), None);
+        let timeout = || Err(ProxyError::Timeout("slow".to_string()));
+        sink.record(
+            &AppType::Claude,
+            &provider,
+            &body,
+            Instant::now(),
+            timeout(),
+        )
+        .await;
+        writer.flush().await;
+        assert_eq!(shadow_rows(), 0);
+
+        // 影子供应商单独开启日志：记录
+        provider.meta = Some(ProviderMeta {
+            enable_request_logging: Some(true),
+            ..Default::default()
+        });
+        sink.record(
+            &AppType::Claude,
+            &provider,
+            &body,
+            Instant::now(),
+            timeout(),
+        )
+        .await;
+        writer.flush().await;
+        assert_eq!(shadow_rows(), 1);
+    }
+}
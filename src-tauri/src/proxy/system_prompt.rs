@@ -0,0 +1,265 @@
+//! 系统提示词前缀注入
+//!
+//! 根据供应商的 `meta.systemPromptPrefix`，在请求转发前把前缀注入到系统提示词最前面。
+//! 客户端已发送相同前缀时不重复注入。
+
+use crate::app_config::AppType;
+use crate::provider::Provider;
+use serde_json::{json, Value};
+
+/// 前缀与原有系统提示词之间的分隔
+const SEPARATOR: &str = "\n\n";
+
+/// 请求体的 API 形态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptShape {
+    /// Anthropic Messages API：顶层 `system` 字段
+    Anthropic,
+    /// OpenAI Chat Completions：`messages` 中的 system 消息
+    OpenAIChat,
+    /// OpenAI Responses API：顶层 `instructions` 字段
+    OpenAIResponses,
+}
+
+impl PromptShape {
+    /// 根据应用类型和客户端端点推断 API 形态（Gemini 等不支持的格式返回 None）
+    ///
+    /// 共用的 `/chat/completions` 路径路由到 Claude 时，请求体本身是 OpenAI Chat 格式。
+    pub fn detect(app_type: &AppType, endpoint: &str) -> Option<Self> {
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        let is_chat = path.ends_with("/chat/completions");
+        match app_type {
+            AppType::Claude | AppType::Codex if is_chat => Some(Self::OpenAIChat),
+            AppType::Claude => Some(Self::Anthropic),
+            AppType::Codex if path.contains("/responses") => Some(Self::OpenAIResponses),
+            _ => None,
+        }
+    }
+}
+
+/// 读取供应商配置的系统提示词前缀（空白视为未设置）
+pub fn provider_prefix(provider: &Provider) -> Option<&str> {
+    provider
+        .meta
+        .as_ref()?
+        .system_prompt_prefix
+        .as_deref()
+        .filter(|prefix| !prefix.trim().is_empty())
+}
+
+/// 将前缀注入请求体，返回是否发生了修改
+pub fn inject(body: &mut Value, shape: PromptShape, prefix: &str) -> bool {
+    match shape {
+        PromptShape::Anthropic => inject_top_level(body, "system", prefix),
+        PromptShape::OpenAIResponses => inject_top_level(body, "instructions", prefix),
+        PromptShape::OpenAIChat => inject_chat(body, prefix),
+    }
+}
+
+/// 字符串前置拼接；已以该前缀开头时返回 None
+fn prepend_text(existing: &str, prefix: &str) -> Option<String> {
+    if existing.starts_with(prefix) {
+        return None;
+    }
+    if existing.is_empty() {
+        Some(prefix.to_string())
+    } else {
+        Some(format!("{prefix}{SEPARATOR}{existing}"))
+    }
+}
+
+/// 内容块数组前置插入文本块；首个文本块已以该前缀开头时不修改
+fn prepend_block(blocks: &mut Vec<Value>, prefix: &str) -> bool {
+    let first_text = blocks
+        .iter()
+        .find_map(|block| block.get("text").and_then(|t| t.as_str()));
+    if first_text.is_some_and(|text| text.starts_with(prefix)) {
+        return false;
+    }
+    blocks.insert(0, json!({ "type": "text", "text": prefix }));
+    true
+}
+
+/// Anthropic `system` / Responses `instructions`
+fn inject_top_level(body: &mut Value, field: &str, prefix: &str) -> bool {
+    let Some(obj) = body.as_object_mut() else {
+        return false;
+    };
+
+    match obj.get_mut(field) {
+        Some(Value::String(existing)) => match prepend_text(existing, prefix) {
+            Some(updated) => {
+                *existing = updated;
+                true
+            }
+            None => false,
+        },
+        Some(Value::Array(blocks)) => prepend_block(blocks, prefix),
+        _ => {
+            obj.insert(field.to_string(), json!(prefix));
+            true
+        }
+    }
+}
+
+/// OpenAI Chat：合并到开头的 system 消息，没有则插入一条
+fn inject_chat(body: &mut Value, prefix: &str) -> bool {
+    let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) else {
+        return false;
+    };
+
+    let leading_system = messages.first_mut().filter(|message| {
+        matches!(
+            message.get("role").and_then(|r| r.as_str()),
+            Some("system") | Some("developer")
+        )
+    });
+
+    if let Some(message) = leading_system {
+        match message.get_mut("content") {
+            Some(Value::String(existing)) => {
+                return match prepend_text(existing, prefix) {
+                    Some(updated) => {
+                        *existing = updated;
+                        true
+                    }
+                    None => false,
+                };
+            }
+            Some(Value::Array(parts)) => return prepend_block(parts, prefix),
+            _ => {
+                message["content"] = json!(prefix);
+                return true;
+            }
+        }
+    }
+
+    messages.insert(0, json!({ "role": "system", "content": prefix }));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: &str = "Follow the house style.";
+
+    #[test]
+    fn anthropic_prefix_prepended_to_string_system() {
+        let mut body = json!({ "system": "You are helpful.", "messages": [] });
+        assert!(inject(&mut body, PromptShape::Anthropic, PREFIX));
+        assert_eq!(
+            body["system"],
+            "Follow the house style.\n\nYou are helpful."
+        );
+    }
+
+    #[test]
+    fn anthropic_prefix_inserted_as_first_block() {
+        let mut body = json!({
+            "system": [{ "type": "text", "text": "You are Claude Code.", "cache_control": { "type": "ephemeral" } }],
+            "messages": []
+        });
+        assert!(inject(&mut body, PromptShape::Anthropic, PREFIX));
+        assert_eq!(body["system"][0]["text"], PREFIX);
+        assert_eq!(body["system"][1]["text"], "You are Claude Code.");
+        assert!(body["system"][1].get("cache_control").is_some());
+    }
+
+    #[test]
+    fn anthropic_prefix_added_when_system_missing() {
+        let mut body = json!({ "messages": [] });
+        assert!(inject(&mut body, PromptShape::Anthropic, PREFIX));
+        assert_eq!(body["system"], PREFIX);
+    }
+
+    #[test]
+    fn anthropic_prefix_not_duplicated() {
+        let mut body = json!({ "system": format!("{PREFIX}\n\nYou are helpful.") });
+        let original = body.clone();
+        assert!(!inject(&mut body, PromptShape::Anthropic, PREFIX));
+        assert_eq!(body, original);
+
+        let mut blocks = json!({ "system": [{ "type": "text", "text": PREFIX }] });
+        assert!(!inject(&mut blocks, PromptShape::Anthropic, PREFIX));
+        assert_eq!(blocks["system"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn openai_chat_prefix_merged_into_leading_system_message() {
+        let mut body = json!({
+            "messages": [
+                { "role": "system", "content": "You are helpful." },
+                { "role": "user", "content": "hi" }
+            ]
+        });
+        assert!(inject(&mut body, PromptShape::OpenAIChat, PREFIX));
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            messages[0]["content"],
+            "Follow the house style.\n\nYou are helpful."
+        );
+    }
+
+    #[test]
+    fn openai_chat_prefix_inserts_system_message() {
+        let mut body = json!({ "messages": [{ "role": "user", "content": "hi" }] });
+        assert!(inject(&mut body, PromptShape::OpenAIChat, PREFIX));
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][0]["content"], PREFIX);
+        assert_eq!(body["messages"][1]["role"], "user");
+    }
+
+    #[test]
+    fn openai_chat_prefix_not_duplicated() {
+        let mut body = json!({
+            "messages": [
+                { "role": "system", "content": PREFIX },
+                { "role": "user", "content": "hi" }
+            ]
+        });
+        let original = body.clone();
+        assert!(!inject(&mut body, PromptShape::OpenAIChat, PREFIX));
+        assert_eq!(body, original);
+    }
+
+    #[test]
+    fn responses_prefix_prepended_to_instructions() {
+        let mut body = json!({ "instructions": "Be terse.", "input": [] });
+        assert!(inject(&mut body, PromptShape::OpenAIResponses, PREFIX));
+        assert_eq!(body["instructions"], "Follow the house style.\n\nBe terse.");
+        assert!(!inject(&mut body, PromptShape::OpenAIResponses, PREFIX));
+    }
+
+    #[test]
+    fn detect_shape_by_app_type_and_endpoint() {
+        assert_eq!(
+            PromptShape::detect(&AppType::Claude, "/v1/messages"),
+            Some(PromptShape::Anthropic)
+        );
+        assert_eq!(
+            PromptShape::detect(&AppType::Claude, "/v1/chat/completions"),
+            Some(PromptShape::OpenAIChat)
+        );
+        assert_eq!(
+            PromptShape::detect(&AppType::Codex, "/chat/completions"),
+            Some(PromptShape::OpenAIChat)
+        );
+        assert_eq!(
+            PromptShape::detect(&AppType::Codex, "/v1/responses"),
+            Some(PromptShape::OpenAIResponses)
+        );
+        assert_eq!(
+            PromptShape::detect(&AppType::Codex, "/responses/compact"),
+            Some(PromptShape::OpenAIResponses)
+        );
+        assert_eq!(
+            PromptShape::detect(
+                &AppType::Gemini,
+                "/v1beta/models/gemini-2.5-pro:generateContent"
+            ),
+            None
+        );
+    }
+}
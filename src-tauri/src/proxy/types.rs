@@ -1,4 +1,6 @@
+use crate::app_config::AppType;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 代理服务器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +27,26 @@ pub struct ProxyConfig {
     /// 非流式总超时（秒）- 非流式请求的总超时时间，范围 60-1200 秒，默认 600 秒（10 分钟）
     #[serde(default = "default_non_streaming_timeout")]
     pub non_streaming_timeout: u64,
+    /// 是否缓存相同的非流式请求（短 TTL，命中时不请求上游、不计费）
+    #[serde(default)]
+    pub enable_response_cache: bool,
+    /// 轻量流式模式：不逐条记录 SSE 事件日志，只解析携带 usage 的事件用于计费
+    #[serde(default)]
+    pub lightweight_streaming: bool,
+    /// 最大并发请求数，超出时短暂排队后返回 503（带 Retry-After），0 表示不限制
+    #[serde(default)]
+    pub max_concurrent_requests: u32,
+    /// 额外监听的 Unix Domain Socket 路径（仅 Linux/macOS，TCP 端口始终监听）
+    ///
+    /// Claude Code / Codex / Gemini CLI 目前都不支持以 socket 作为 base_url，
+    /// 因此接管时仍写入 TCP 地址；socket 供支持 UDS 的客户端或本机脚本使用。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen_socket_path: Option<String>,
+    /// Claude 与 Codex 共用路径（如 `/v1/chat/completions`）的默认应用：`codex` 或 `claude`
+    ///
+    /// 请求带 `x-cc-app` 头或 Anthropic 风格认证头时以请求为准。
+    #[serde(default = "default_target_app")]
+    pub target_app: AppType,
 }
 
 fn default_streaming_first_byte_timeout() -> u64 {
@@ -39,6 +61,10 @@ fn default_non_streaming_timeout() -> u64 {
     600
 }
 
+fn default_target_app() -> AppType {
+    AppType::Codex
+}
+
 impl Default for ProxyConfig {
     fn default() -> Self {
         Self {
@@ -51,6 +77,11 @@ impl Default for ProxyConfig {
             streaming_first_byte_timeout: 60,
             streaming_idle_timeout: 120,
             non_streaming_timeout: 600,
+            enable_response_cache: false,
+            lightweight_streaming: false,
+            max_concurrent_requests: 0,
+            listen_socket_path: None,
+            target_app: default_target_app(),
         }
     }
 }
@@ -86,6 +117,9 @@ pub struct ProxyStatus {
     pub last_error: Option<String>,
     /// Provider故障转移次数
     pub failover_count: u64,
+    /// 兜底供应商尝试次数（故障转移候选全部失败后触发）
+    #[serde(default)]
+    pub safety_net_attempts: u64,
     /// 当前活跃的代理目标列表
     #[serde(default)]
     pub active_targets: Vec<ActiveTarget>,
@@ -104,9 +138,100 @@ pub struct ActiveTarget {
 pub struct ProxyServerInfo {
     pub address: String,
     pub port: u16,
+    /// 实际监听的 Unix Socket 路径（未配置或平台不支持时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<String>,
     pub started_at: String,
 }
 
+/// 代理开销测量结果（毫秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverheadReport {
+    pub app_type: String,
+    pub provider_id: String,
+    /// 直连与经代理各自的有效样本数
+    pub samples: usize,
+    pub direct_median_ms: f64,
+    pub proxy_median_ms: f64,
+    /// 逐对（代理 - 直连）耗时差的中位数
+    pub median_overhead_ms: f64,
+    /// 逐对耗时差的方差（ms²）
+    pub overhead_variance_ms2: f64,
+    pub direct_ms: Vec<f64>,
+    pub proxy_ms: Vec<f64>,
+}
+
+/// 接管自检结果（在临时目录中对 Live 配置副本执行 备份 → 接管 → 恢复）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeoverTestReport {
+    /// 自检写入的代理地址
+    pub proxy_url: String,
+    pub apps: Vec<TakeoverAppTestResult>,
+    /// 所有未跳过的应用均通过自检
+    pub passed: bool,
+}
+
+/// 单个应用的接管自检结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TakeoverAppTestResult {
+    pub app_type: String,
+    /// Live 配置不存在，未参与自检
+    pub skipped: bool,
+    /// 接管后能否识别出 Token 占位符
+    pub placeholder_detected: bool,
+    /// 恢复后各文件与原文件逐字节一致
+    pub round_trip_ok: bool,
+    /// 恢复后内容与原文件不一致的文件名
+    pub mismatched_files: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl TakeoverAppTestResult {
+    pub fn passed(&self) -> bool {
+        !self.skipped && self.error.is_none() && self.placeholder_detected && self.round_trip_ok
+    }
+}
+
+/// 供应商选择策略（按应用持久化）
+///
+/// 仅在开启故障转移时生效，决定本次请求优先尝试队列中的哪个供应商，
+/// 其余供应商仍按队列顺序作为后备；关闭故障转移时始终使用当前供应商。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingMode {
+    /// 按队列顺序（P1 → P2 → ...），即原有行为
+    #[default]
+    Sticky,
+    /// 轮询：每次请求从下一个供应商开始
+    RoundRobin,
+    /// 平滑加权轮询：按供应商的 `routingWeight` 分配首选次数
+    Weighted,
+    /// 最低延迟：优先最近平均测速延迟最低的供应商，无测速数据时优先当前供应商
+    LowestLatency,
+}
+
+impl RoutingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sticky => "sticky",
+            Self::RoundRobin => "round_robin",
+            Self::Weighted => "weighted",
+            Self::LowestLatency => "lowest_latency",
+        }
+    }
+
+    /// 从数据库存储值解析
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "sticky" => Some(Self::Sticky),
+            "round_robin" => Some(Self::RoundRobin),
+            "weighted" => Some(Self::Weighted),
+            "lowest_latency" => Some(Self::LowestLatency),
+            _ => None,
+        }
+    }
+}
+
 /// 各应用的接管状态（是否改写该应用的 Live 配置指向本地代理）
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProxyTakeoverStatus {
@@ -136,7 +261,88 @@ pub struct ProviderHealth {
     pub last_success_at: Option<String>,
     pub last_failure_at: Option<String>,
     pub last_error: Option<String>,
+    /// 最近一次失败的错误类别（成功后清空）
+    #[serde(default)]
+    pub last_error_kind: Option<ProviderErrorKind>,
     pub updated_at: String,
+    /// 最近一次连接预热成功的时间（见 `ProviderService::warm_up`）
+    #[serde(default)]
+    pub warmed_at: Option<String>,
+}
+
+/// Provider 失败原因类别
+///
+/// 用于提示用户是需要修正密钥（Auth）还是等待恢复（RateLimit/ServerError/Network）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderErrorKind {
+    /// 认证失败（密钥无效、过期或无权限）
+    Auth,
+    /// 触发限流或配额用尽
+    RateLimit,
+    /// 上游 5xx 错误
+    ServerError,
+    /// 连接失败或超时
+    Network,
+    Unknown,
+}
+
+impl ProviderErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::RateLimit => "rate_limit",
+            Self::ServerError => "server_error",
+            Self::Network => "network",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// 从数据库存储值解析（未知值视为 Unknown）
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "auth" => Self::Auth,
+            "rate_limit" => Self::RateLimit,
+            "server_error" => Self::ServerError,
+            "network" => Self::Network,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// 根据上游状态码和响应体判断错误类别
+    ///
+    /// 部分中转站会用 400/403/500 等状态码返回认证或限流错误，因此状态码无法判断时再检查响应体
+    pub fn from_status(status: u16, body: Option<&str>) -> Self {
+        match status {
+            401 | 403 => return Self::Auth,
+            429 => return Self::RateLimit,
+            _ => {}
+        }
+
+        let body = body.unwrap_or_default().to_ascii_lowercase();
+        const AUTH_MARKERS: &[&str] = &[
+            "invalid api key",
+            "invalid_api_key",
+            "invalid x-api-key",
+            "authentication_error",
+            "unauthorized",
+            "incorrect api key",
+        ];
+        const RATE_LIMIT_MARKERS: &[&str] =
+            &["rate limit", "rate_limit", "too many requests", "quota"];
+        if AUTH_MARKERS.iter().any(|marker| body.contains(marker)) {
+            Self::Auth
+        } else if RATE_LIMIT_MARKERS
+            .iter()
+            .any(|marker| body.contains(marker))
+        {
+            Self::RateLimit
+        } else if (500..600).contains(&status) {
+            Self::ServerError
+        } else {
+            Self::Unknown
+        }
+    }
 }
 
 /// Live 配置备份记录
@@ -281,6 +487,12 @@ pub struct LogConfig {
     /// 日志级别: error, warn, info, debug, trace
     #[serde(default = "default_log_level")]
     pub level: String,
+    /// 是否在日志中脱敏 SSE 事件的消息内容（仅保留结构、类型与 usage）
+    #[serde(default)]
+    pub redact_sse_content: bool,
+    /// 按模块覆盖日志级别（模块路径 → 级别，如 `proxy::handlers` → `warn`），最长前缀优先
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub module_levels: HashMap<String, String>,
 }
 
 impl Default for LogConfig {
@@ -288,6 +500,8 @@ impl Default for LogConfig {
         Self {
             enabled: true,
             level: "info".to_string(),
+            redact_sse_content: false,
+            module_levels: HashMap::new(),
         }
     }
 }
@@ -298,14 +512,20 @@ impl LogConfig {
         if !self.enabled {
             return log::LevelFilter::Off;
         }
-        match self.level.to_lowercase().as_str() {
-            "error" => log::LevelFilter::Error,
-            "warn" => log::LevelFilter::Warn,
-            "info" => log::LevelFilter::Info,
-            "debug" => log::LevelFilter::Debug,
-            "trace" => log::LevelFilter::Trace,
-            _ => log::LevelFilter::Info,
-        }
+        parse_level_filter(&self.level).unwrap_or(log::LevelFilter::Info)
+    }
+}
+
+/// 解析日志级别名称（off, error, warn, info, debug, trace，大小写不敏感）
+pub fn parse_level_filter(level: &str) -> Option<log::LevelFilter> {
+    match level.trim().to_lowercase().as_str() {
+        "off" => Some(log::LevelFilter::Off),
+        "error" => Some(log::LevelFilter::Error),
+        "warn" => Some(log::LevelFilter::Warn),
+        "info" => Some(log::LevelFilter::Info),
+        "debug" => Some(log::LevelFilter::Debug),
+        "trace" => Some(log::LevelFilter::Trace),
+        _ => None,
     }
 }
 
@@ -372,6 +592,7 @@ mod tests {
         let config: LogConfig = serde_json::from_str(json).unwrap();
         assert!(config.enabled);
         assert_eq!(config.level, "info");
+        assert!(!config.redact_sse_content);
     }
 
     #[test]
@@ -417,6 +638,7 @@ mod tests {
         let config = LogConfig {
             enabled: false,
             level: "debug".to_string(),
+            ..Default::default()
         };
         assert_eq!(config.to_level_filter(), log::LevelFilter::Off);
     }
@@ -426,10 +648,13 @@ mod tests {
         let config = LogConfig {
             enabled: true,
             level: "debug".to_string(),
+            module_levels: HashMap::from([("proxy::handlers".to_string(), "warn".to_string())]),
+            ..Default::default()
         };
         let json = serde_json::to_string(&config).unwrap();
         let parsed: LogConfig = serde_json::from_str(&json).unwrap();
         assert!(parsed.enabled);
         assert_eq!(parsed.level, "debug");
+        assert_eq!(parsed.module_levels["proxy::handlers"], "warn");
     }
 }
@@ -13,6 +13,7 @@ pub struct CostBreakdown {
     pub output_cost: Decimal,
     pub cache_read_cost: Decimal,
     pub cache_creation_cost: Decimal,
+    pub reasoning_cost: Decimal,
     pub total_cost: Decimal,
 }
 
@@ -23,6 +24,8 @@ pub struct ModelPricing {
     pub output_cost_per_million: Decimal,
     pub cache_read_cost_per_million: Decimal,
     pub cache_creation_cost_per_million: Decimal,
+    /// 推理 tokens 单价，未单独配置时与输出价格一致
+    pub reasoning_cost_per_million: Decimal,
 }
 
 /// 成本计算器
@@ -40,6 +43,8 @@ impl CostCalculator {
     /// - input_cost: (input_tokens - cache_read_tokens) × 输入价格
     /// - cache_read_cost: cache_read_tokens × 缓存读取价格
     /// - 这样避免缓存部分被重复计费
+    /// - output_cost: (output_tokens - reasoning_tokens) × 输出价格
+    /// - reasoning_cost: reasoning_tokens × 推理价格（推理 tokens 已包含在 output_tokens 中）
     /// - total_cost: 各项成本之和 × 倍率（倍率只作用于最终总价）
     pub fn calculate(
         usage: &TokenUsage,
@@ -54,16 +59,23 @@ impl CostCalculator {
         // 各项基础成本（不含倍率）
         let input_cost =
             Decimal::from(billable_input_tokens) * pricing.input_cost_per_million / million;
+        // 推理 tokens 包含在 output_tokens 中，单独按推理价格计费
+        let reasoning_tokens = usage.reasoning_tokens.min(usage.output_tokens);
+        let billable_output_tokens = usage.output_tokens - reasoning_tokens;
+
         let output_cost =
-            Decimal::from(usage.output_tokens) * pricing.output_cost_per_million / million;
+            Decimal::from(billable_output_tokens) * pricing.output_cost_per_million / million;
         let cache_read_cost =
             Decimal::from(usage.cache_read_tokens) * pricing.cache_read_cost_per_million / million;
         let cache_creation_cost = Decimal::from(usage.cache_creation_tokens)
             * pricing.cache_creation_cost_per_million
             / million;
+        let reasoning_cost =
+            Decimal::from(reasoning_tokens) * pricing.reasoning_cost_per_million / million;
 
         // 总成本 = 各项基础成本之和 × 倍率
-        let base_total = input_cost + output_cost + cache_read_cost + cache_creation_cost;
+        let base_total =
+            input_cost + output_cost + cache_read_cost + cache_creation_cost + reasoning_cost;
         let total_cost = base_total * cost_multiplier;
 
         CostBreakdown {
@@ -71,6 +83,7 @@ impl CostCalculator {
             output_cost,
             cache_read_cost,
             cache_creation_cost,
+            reasoning_cost,
             total_cost,
         }
     }
@@ -86,20 +99,28 @@ impl CostCalculator {
 }
 
 impl ModelPricing {
-    /// 从字符串创建定价信息
+    /// 从字符串创建定价信息（推理价格默认与输出价格一致）
     pub fn from_strings(
         input: &str,
         output: &str,
         cache_read: &str,
         cache_creation: &str,
     ) -> Result<Self, rust_decimal::Error> {
+        let output_cost_per_million = Decimal::from_str(output)?;
         Ok(Self {
             input_cost_per_million: Decimal::from_str(input)?,
-            output_cost_per_million: Decimal::from_str(output)?,
+            output_cost_per_million,
             cache_read_cost_per_million: Decimal::from_str(cache_read)?,
             cache_creation_cost_per_million: Decimal::from_str(cache_creation)?,
+            reasoning_cost_per_million: output_cost_per_million,
         })
     }
+
+    /// 覆盖推理 tokens 单价
+    pub fn with_reasoning_cost(mut self, reasoning: &str) -> Result<Self, rust_decimal::Error> {
+        self.reasoning_cost_per_million = Decimal::from_str(reasoning)?;
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +134,7 @@ mod tests {
             output_tokens: 500,
             cache_read_tokens: 200,
             cache_creation_tokens: 100,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -143,6 +165,7 @@ mod tests {
             output_tokens: 0,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -164,6 +187,7 @@ mod tests {
             output_tokens: 500,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -180,6 +204,7 @@ mod tests {
             output_tokens: 1,
             cache_read_tokens: 1,
             cache_creation_tokens: 1,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -192,4 +217,76 @@ mod tests {
         assert!(cost.total_cost > Decimal::ZERO);
         assert!(cost.total_cost.to_string().len() > 2); // 确保保留了小数位
     }
+
+    #[test]
+    fn test_reasoning_tokens_default_to_output_price() {
+        let usage = TokenUsage {
+            input_tokens: 0,
+            output_tokens: 1000,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            reasoning_tokens: 400,
+            model: None,
+        };
+
+        let pricing = ModelPricing::from_strings("3.0", "15.0", "0", "0").unwrap();
+        let cost = CostCalculator::calculate(&usage, &pricing, Decimal::ONE);
+
+        // output: (1000 - 400) * 15.0 / 1M = 0.009
+        assert_eq!(cost.output_cost, Decimal::from_str("0.009").unwrap());
+        // reasoning: 400 * 15.0 / 1M = 0.006
+        assert_eq!(cost.reasoning_cost, Decimal::from_str("0.006").unwrap());
+        // 未配置推理价格时总价与不拆分时一致
+        assert_eq!(cost.total_cost, Decimal::from_str("0.015").unwrap());
+    }
+
+    #[test]
+    fn test_reasoning_tokens_use_dedicated_price() {
+        let usage = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 1000,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            reasoning_tokens: 400,
+            model: None,
+        };
+
+        let pricing = ModelPricing::from_strings("3.0", "15.0", "0", "0")
+            .unwrap()
+            .with_reasoning_cost("5.0")
+            .unwrap();
+        let multiplier = Decimal::from_str("2").unwrap();
+        let cost = CostCalculator::calculate(&usage, &pricing, multiplier);
+
+        // input: 1000 * 3.0 / 1M = 0.003
+        assert_eq!(cost.input_cost, Decimal::from_str("0.003").unwrap());
+        // output: 600 * 15.0 / 1M = 0.009
+        assert_eq!(cost.output_cost, Decimal::from_str("0.009").unwrap());
+        // reasoning: 400 * 5.0 / 1M = 0.002
+        assert_eq!(cost.reasoning_cost, Decimal::from_str("0.002").unwrap());
+        // total: (0.003 + 0.009 + 0.002) * 2 = 0.028
+        assert_eq!(cost.total_cost, Decimal::from_str("0.028").unwrap());
+    }
+
+    #[test]
+    fn test_reasoning_tokens_capped_by_output_tokens() {
+        let usage = TokenUsage {
+            input_tokens: 0,
+            output_tokens: 100,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+            reasoning_tokens: 500,
+            model: None,
+        };
+
+        let pricing = ModelPricing::from_strings("3.0", "15.0", "0", "0")
+            .unwrap()
+            .with_reasoning_cost("10.0")
+            .unwrap();
+        let cost = CostCalculator::calculate(&usage, &pricing, Decimal::ONE);
+
+        assert_eq!(cost.output_cost, Decimal::ZERO);
+        // reasoning: 100 * 10.0 / 1M = 0.001
+        assert_eq!(cost.reasoning_cost, Decimal::from_str("0.001").unwrap());
+    }
 }
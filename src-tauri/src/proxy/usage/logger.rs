@@ -2,12 +2,19 @@
 
 use super::calculator::{CostBreakdown, CostCalculator, ModelPricing};
 use super::parser::TokenUsage;
+use super::writer::UsageLogWriter;
 use crate::database::Database;
 use crate::error::AppError;
-use crate::services::usage_stats::find_model_pricing_row;
+use crate::services::usage_stats::{find_model_pricing_row, normalize_model_name};
 use rust_decimal::Decimal;
 use std::{str::FromStr, time::SystemTime};
 
+/// 客户端在流式响应完成前断开时记录的状态码（沿用 nginx 的 499 Client Closed Request）
+pub const CLIENT_ABORTED_STATUS: u16 = 499;
+
+/// 客户端断开时写入请求日志的错误信息
+pub const CLIENT_ABORTED: &str = "client_aborted";
+
 /// 请求日志
 #[derive(Debug, Clone)]
 pub struct RequestLog {
@@ -29,41 +36,66 @@ pub struct RequestLog {
     pub is_streaming: bool,
     /// 成本倍数
     pub cost_multiplier: String,
+    /// 是否为响应缓存命中（未请求上游，不计费）
+    pub is_cached: bool,
+    /// 是否为影子请求（镜像到影子供应商，用于对比）
+    pub is_shadow: bool,
+    /// 使用的 Key 池序号（供应商未配置 Key 池时为 None）
+    pub api_key_index: Option<usize>,
+    /// 是否由兜底供应商完成（常规候选全部失败后的兜底尝试）
+    pub is_safety_net: bool,
+    /// 供应商输出 token 上限生效后的最大输出 token 数（未设置上限时为 None）
+    pub effective_max_tokens: Option<u64>,
 }
 
 /// 使用量记录器
 pub struct UsageLogger<'a> {
     db: &'a Database,
+    writer: Option<&'a UsageLogWriter>,
+    api_key_index: Option<usize>,
+    is_safety_net: bool,
+    effective_max_tokens: Option<u64>,
 }
 
 impl<'a> UsageLogger<'a> {
     pub fn new(db: &'a Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            writer: None,
+            api_key_index: None,
+            is_safety_net: false,
+            effective_max_tokens: None,
+        }
     }
 
-    /// 记录成功的请求
-    pub fn log_request(&self, log: &RequestLog) -> Result<(), AppError> {
-        let conn = crate::database::lock_conn!(self.db.conn);
+    /// 日志写入交给单写入任务批量提交（读取定价等仍直接访问数据库）
+    pub fn with_writer(mut self, writer: &'a UsageLogWriter) -> Self {
+        self.writer = Some(writer);
+        self
+    }
 
-        let (input_cost, output_cost, cache_read_cost, cache_creation_cost, total_cost) =
-            if let Some(cost) = &log.cost {
-                (
-                    cost.input_cost.to_string(),
-                    cost.output_cost.to_string(),
-                    cost.cache_read_cost.to_string(),
-                    cost.cache_creation_cost.to_string(),
-                    cost.total_cost.to_string(),
-                )
-            } else {
-                (
-                    "0".to_string(),
-                    "0".to_string(),
-                    "0".to_string(),
-                    "0".to_string(),
-                    "0".to_string(),
-                )
-            };
+    /// 记录请求所用的 Key 池序号
+    pub fn with_api_key_index(mut self, api_key_index: Option<usize>) -> Self {
+        self.api_key_index = api_key_index;
+        self
+    }
+
+    /// 标记请求由兜底供应商完成
+    pub fn with_safety_net(mut self, is_safety_net: bool) -> Self {
+        self.is_safety_net = is_safety_net;
+        self
+    }
 
+    /// 记录输出 token 上限生效后的最大输出 token 数
+    pub fn with_effective_max_tokens(mut self, effective_max_tokens: Option<u64>) -> Self {
+        self.effective_max_tokens = effective_max_tokens;
+        self
+    }
+
+    /// 记录成功的请求
+    ///
+    /// 配置了单写入任务时加入批量写入队列，否则直接写入数据库
+    pub fn log_request(&self, log: &RequestLog) -> Result<(), AppError> {
         let created_at = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .map(|d| d.as_secs() as i64)
@@ -72,14 +104,59 @@ impl<'a> UsageLogger<'a> {
                 0
             });
 
+        if let Some(writer) = self.writer {
+            if writer.submit(log.clone(), created_at) {
+                return Ok(());
+            }
+        }
+
+        let conn = crate::database::lock_conn!(self.db.conn);
+        Self::insert_log(&conn, log, created_at)
+    }
+
+    /// 在给定连接（或事务）上插入一条请求日志
+    pub(crate) fn insert_log(
+        conn: &rusqlite::Connection,
+        log: &RequestLog,
+        created_at: i64,
+    ) -> Result<(), AppError> {
+        let (
+            input_cost,
+            output_cost,
+            cache_read_cost,
+            cache_creation_cost,
+            reasoning_cost,
+            total_cost,
+        ) = if let Some(cost) = &log.cost {
+            (
+                cost.input_cost.to_string(),
+                cost.output_cost.to_string(),
+                cost.cache_read_cost.to_string(),
+                cost.cache_creation_cost.to_string(),
+                cost.reasoning_cost.to_string(),
+                cost.total_cost.to_string(),
+            )
+        } else {
+            (
+                "0".to_string(),
+                "0".to_string(),
+                "0".to_string(),
+                "0".to_string(),
+                "0".to_string(),
+                "0".to_string(),
+            )
+        };
+
         conn.execute(
             "INSERT INTO proxy_request_logs (
                 request_id, provider_id, app_type, model, request_model,
                 input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
                 input_cost_usd, output_cost_usd, cache_read_cost_usd, cache_creation_cost_usd, total_cost_usd,
                 latency_ms, first_token_ms, status_code, error_message, session_id,
-                provider_type, is_streaming, cost_multiplier, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
+                provider_type, is_streaming, cost_multiplier, created_at,
+                reasoning_tokens, reasoning_cost_usd, is_cached, model_normalized, is_shadow,
+                api_key_index, is_safety_net, effective_max_tokens
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31)",
             rusqlite::params![
                 log.request_id,
                 log.provider_id,
@@ -104,6 +181,14 @@ impl<'a> UsageLogger<'a> {
                 log.is_streaming as i64,
                 log.cost_multiplier,
                 created_at,
+                log.usage.reasoning_tokens,
+                reasoning_cost,
+                log.is_cached as i64,
+                normalize_model_name(&log.model),
+                log.is_shadow as i64,
+                log.api_key_index.map(|v| v as i64),
+                log.is_safety_net as i64,
+                log.effective_max_tokens.map(|v| v as i64),
             ],
         )
         .map_err(|e| AppError::Database(format!("记录请求日志失败: {e}")))?;
@@ -142,6 +227,11 @@ impl<'a> UsageLogger<'a> {
             provider_type: None,
             is_streaming: false,
             cost_multiplier: "1.0".to_string(),
+            is_cached: false,
+            is_shadow: false,
+            api_key_index: None,
+            is_safety_net: false,
+            effective_max_tokens: None,
         };
 
         self.log_request(&log)
@@ -181,6 +271,11 @@ impl<'a> UsageLogger<'a> {
             provider_type,
             is_streaming,
             cost_multiplier: "1.0".to_string(),
+            is_cached: false,
+            is_shadow: false,
+            api_key_index: self.api_key_index,
+            is_safety_net: self.is_safety_net,
+            effective_max_tokens: self.effective_max_tokens,
         };
 
         self.log_request(&log)
@@ -191,8 +286,17 @@ impl<'a> UsageLogger<'a> {
         let conn = crate::database::lock_conn!(self.db.conn);
         let row = find_model_pricing_row(&conn, model_id)?;
         match row {
-            Some((input, output, cache_read, cache_creation)) => {
-                ModelPricing::from_strings(&input, &output, &cache_read, &cache_creation)
+            Some((input, output, cache_read, cache_creation, reasoning)) => {
+                let pricing =
+                    ModelPricing::from_strings(&input, &output, &cache_read, &cache_creation);
+                // 未单独配置推理价格时沿用输出价格
+                let pricing = match reasoning.as_deref().map(str::trim) {
+                    Some(value) if !value.is_empty() => {
+                        pricing.and_then(|p| p.with_reasoning_cost(value))
+                    }
+                    _ => pricing,
+                };
+                pricing
                     .map(Some)
                     .map_err(|e| AppError::Database(format!("解析定价数据失败: {e}")))
             }
@@ -320,11 +424,17 @@ impl<'a> UsageLogger<'a> {
             latency_ms,
             first_token_ms,
             status_code,
-            error_message: None,
+            error_message: (status_code == CLIENT_ABORTED_STATUS)
+                .then(|| CLIENT_ABORTED.to_string()),
             session_id,
             provider_type,
             is_streaming,
             cost_multiplier: cost_multiplier.to_string(),
+            is_cached: false,
+            is_shadow: false,
+            api_key_index: self.api_key_index,
+            is_safety_net: self.is_safety_net,
+            effective_max_tokens: self.effective_max_tokens,
         };
 
         self.log_request(&log)
@@ -350,13 +460,17 @@ mod tests {
             .unwrap();
         }
 
-        let logger = UsageLogger::new(&db);
+        let logger = UsageLogger::new(&db)
+            .with_api_key_index(Some(2))
+            .with_safety_net(true)
+            .with_effective_max_tokens(Some(4096));
 
         let usage = TokenUsage {
             input_tokens: 1000,
             output_tokens: 500,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            reasoning_tokens: 0,
             model: None,
         };
 
@@ -379,15 +493,69 @@ mod tests {
 
         // 验证记录已插入
         let conn = crate::database::lock_conn!(db.conn);
-        let (count, request_model): (i64, String) = conn
+        let (count, request_model, api_key_index, is_safety_net, effective_max_tokens): (
+            i64,
+            String,
+            Option<i64>,
+            i64,
+            Option<i64>,
+        ) = conn
             .query_row(
-                "SELECT COUNT(*), request_model FROM proxy_request_logs WHERE request_id = 'req-123'",
+                "SELECT COUNT(*), request_model, api_key_index, is_safety_net, effective_max_tokens FROM proxy_request_logs WHERE request_id = 'req-123'",
                 [],
-                |row| Ok((row.get(0)?, row.get(1)?)),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
             )
             .unwrap();
         assert_eq!(count, 1);
         assert_eq!(request_model, "req-model");
+        assert_eq!(api_key_index, Some(2));
+        assert_eq!(is_safety_net, 1);
+        assert_eq!(effective_max_tokens, Some(4096));
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_aliases_are_normalized_for_stats() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let logger = UsageLogger::new(&db);
+
+        for (i, model) in [
+            "claude-sonnet-4-5",
+            "anthropic/claude-sonnet-4-5",
+            "openrouter/anthropic/claude-sonnet-4-5:beta",
+            " claude-sonnet-4-5 ",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            logger.log_error(
+                format!("req-{i}"),
+                "provider-1".to_string(),
+                "claude".to_string(),
+                model.to_string(),
+                500,
+                "boom".to_string(),
+                10,
+            )?;
+        }
+
+        {
+            let conn = crate::database::lock_conn!(db.conn);
+            let (raw, normalized): (String, String) = conn
+                .query_row(
+                    "SELECT model, model_normalized FROM proxy_request_logs WHERE request_id = 'req-1'",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap();
+            assert_eq!(raw, "anthropic/claude-sonnet-4-5");
+            assert_eq!(normalized, "claude-sonnet-4-5");
+        }
+
+        let stats = db.get_model_stats()?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].model, "claude-sonnet-4-5");
+        assert_eq!(stats[0].request_count, 4);
         Ok(())
     }
 
@@ -5,6 +5,8 @@
 pub mod calculator;
 pub mod logger;
 pub mod parser;
+pub mod pause;
+pub mod writer;
 
 // 仅导出内部使用的类型,避免未使用警告
 #[allow(unused_imports)]
@@ -13,3 +15,7 @@ pub use calculator::{CostBreakdown, CostCalculator, ModelPricing};
 pub use logger::{RequestLog, UsageLogger};
 #[allow(unused_imports)]
 pub use parser::{ApiType, TokenUsage};
+#[allow(unused_imports)]
+pub use pause::LoggingPause;
+#[allow(unused_imports)]
+pub use writer::UsageLogWriter;
@@ -16,6 +16,9 @@ pub struct TokenUsage {
     pub output_tokens: u32,
     pub cache_read_tokens: u32,
     pub cache_creation_tokens: u32,
+    /// 推理（thinking）tokens，已包含在 output_tokens 中
+    #[serde(default)]
+    pub reasoning_tokens: u32,
     /// 从响应中提取的实际模型名称（如果可用）
     pub model: Option<String>,
 }
@@ -30,6 +33,43 @@ pub enum ApiType {
     Gemini,
 }
 
+/// 从 usage 对象中提取推理 tokens
+///
+/// - OpenAI Chat Completions: `completion_tokens_details.reasoning_tokens`
+/// - Codex Responses API / 部分 Claude 兼容网关: `output_tokens_details.reasoning_tokens`
+fn extract_reasoning_tokens(usage: &Value) -> u32 {
+    ["completion_tokens_details", "output_tokens_details"]
+        .iter()
+        .find_map(|key| usage.get(*key)?.get("reasoning_tokens")?.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// 合并 Anthropic 流式事件中的累计 token 计数
+///
+/// message_delta 中的 usage 是截至当前的累计值，会重复 message_start 已报告的部分，
+/// 因此取较大值而不是相加，避免重复计费。
+fn merge_cumulative(total: &mut u32, reported: Option<&Value>) {
+    if let Some(value) = reported.and_then(|v| v.as_u64()) {
+        *total = (*total).max(value as u32);
+    }
+}
+
+/// 从多个候选 usage 中选出最完整的一个（总 token 数最大，相同时取靠后的）
+///
+/// 流式事件偶尔会乱序到达，累计值较小的中间快照可能排在最终结果之后，
+/// 因此不能简单地取第一个或最后一个。
+fn most_complete(candidates: impl Iterator<Item = TokenUsage>) -> Option<TokenUsage> {
+    candidates.max_by_key(|usage| usage.input_tokens as u64 + usage.output_tokens as u64)
+}
+
+/// 所有 response.completed 事件中的 response 对象
+fn completed_responses(events: &[Value]) -> impl Iterator<Item = &Value> {
+    events
+        .iter()
+        .filter(|event| event.get("type").and_then(|v| v.as_str()) == Some("response.completed"))
+        .filter_map(|event| event.get("response"))
+}
+
 impl TokenUsage {
     /// 从 Claude API 非流式响应解析
     pub fn from_claude_response(body: &Value) -> Option<Self> {
@@ -51,15 +91,21 @@ impl TokenUsage {
                 .get("cache_creation_input_tokens")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(0) as u32,
+            reasoning_tokens: extract_reasoning_tokens(usage),
             model,
         })
     }
 
     /// 从 Claude API 流式响应解析
+    ///
+    /// 扫描全部事件后再汇总，不依赖事件顺序：累计计数一律取最大值，
+    /// message_start 中的 input_tokens 优先于 message_delta 中的值。
     #[allow(dead_code)]
     pub fn from_claude_stream_events(events: &[Value]) -> Option<Self> {
         let mut usage = Self::default();
         let mut model: Option<String> = None;
+        let mut start_input = 0u32;
+        let mut delta_input = 0u32;
 
         for event in events {
             if let Some(event_type) = event.get("type").and_then(|v| v.as_str()) {
@@ -75,59 +121,39 @@ impl TokenUsage {
                         }
                         if let Some(msg_usage) = event.get("message").and_then(|m| m.get("usage")) {
                             // 从 message_start 获取 input_tokens（原生 Claude API）
-                            if let Some(input) =
-                                msg_usage.get("input_tokens").and_then(|v| v.as_u64())
-                            {
-                                usage.input_tokens = input as u32;
-                            }
-                            usage.cache_read_tokens = msg_usage
-                                .get("cache_read_input_tokens")
-                                .and_then(|v| v.as_u64())
-                                .unwrap_or(0)
-                                as u32;
-                            usage.cache_creation_tokens = msg_usage
-                                .get("cache_creation_input_tokens")
-                                .and_then(|v| v.as_u64())
-                                .unwrap_or(0)
-                                as u32;
+                            merge_cumulative(&mut start_input, msg_usage.get("input_tokens"));
+                            merge_cumulative(
+                                &mut usage.cache_read_tokens,
+                                msg_usage.get("cache_read_input_tokens"),
+                            );
+                            merge_cumulative(
+                                &mut usage.cache_creation_tokens,
+                                msg_usage.get("cache_creation_input_tokens"),
+                            );
                         }
                     }
                     "message_delta" => {
                         if let Some(delta_usage) = event.get("usage") {
-                            // 从 message_delta 获取 output_tokens
-                            if let Some(output) =
-                                delta_usage.get("output_tokens").and_then(|v| v.as_u64())
-                            {
-                                usage.output_tokens = output as u32;
-                            }
+                            // 从 message_delta 获取 output_tokens（累计值，乱序时取最大）
+                            merge_cumulative(
+                                &mut usage.output_tokens,
+                                delta_usage.get("output_tokens"),
+                            );
+                            usage.reasoning_tokens = usage
+                                .reasoning_tokens
+                                .max(extract_reasoning_tokens(delta_usage));
                             // OpenRouter 转换后的流式响应：input_tokens 也在 message_delta 中
-                            // 如果 message_start 中没有 input_tokens，则从 message_delta 获取
-                            if usage.input_tokens == 0 {
-                                if let Some(input) =
-                                    delta_usage.get("input_tokens").and_then(|v| v.as_u64())
-                                {
-                                    usage.input_tokens = input as u32;
-                                }
-                            }
-                            // 从 message_delta 中处理缓存命中(cache_read_input_tokens)
-                            if usage.cache_read_tokens == 0 {
-                                if let Some(cache_read) = delta_usage
-                                    .get("cache_read_input_tokens")
-                                    .and_then(|v| v.as_u64())
-                                {
-                                    usage.cache_read_tokens = cache_read as u32;
-                                }
-                            }
-                            // 从 message_delta 中处理缓存创建(cache_creation_input_tokens)
-                            // 注: 现在 zhipu 没有返回 cache_creation_input_tokens 字段
-                            if usage.cache_creation_tokens == 0 {
-                                if let Some(cache_creation) = delta_usage
-                                    .get("cache_creation_input_tokens")
-                                    .and_then(|v| v.as_u64())
-                                {
-                                    usage.cache_creation_tokens = cache_creation as u32;
-                                }
-                            }
+                            merge_cumulative(&mut delta_input, delta_usage.get("input_tokens"));
+                            // 缓存字段：message_start 与 message_delta 都可能携带（累计值），
+                            // 部分中转只在 message_delta 中返回（如 zhipu 不返回 cache_creation）
+                            merge_cumulative(
+                                &mut usage.cache_read_tokens,
+                                delta_usage.get("cache_read_input_tokens"),
+                            );
+                            merge_cumulative(
+                                &mut usage.cache_creation_tokens,
+                                delta_usage.get("cache_creation_input_tokens"),
+                            );
                         }
                     }
                     _ => {}
@@ -135,6 +161,13 @@ impl TokenUsage {
             }
         }
 
+        // 如果 message_start 中没有 input_tokens，则使用 message_delta 中的值
+        usage.input_tokens = if start_input > 0 {
+            start_input
+        } else {
+            delta_input
+        };
+
         if usage.input_tokens > 0 || usage.output_tokens > 0 {
             usage.model = model;
             Some(usage)
@@ -152,6 +185,7 @@ impl TokenUsage {
             output_tokens: usage.get("completion_tokens")?.as_u64()? as u32,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            reasoning_tokens: extract_reasoning_tokens(usage),
             model: None,
         })
     }
@@ -201,6 +235,7 @@ impl TokenUsage {
                 .get("cache_creation_input_tokens")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(0) as u32,
+            reasoning_tokens: extract_reasoning_tokens(usage),
             model,
         })
     }
@@ -244,6 +279,7 @@ impl TokenUsage {
                 .get("cache_creation_input_tokens")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(0) as u32,
+            reasoning_tokens: extract_reasoning_tokens(usage),
             model,
         })
     }
@@ -252,19 +288,13 @@ impl TokenUsage {
     #[allow(dead_code)]
     pub fn from_codex_stream_events(events: &[Value]) -> Option<Self> {
         log::debug!("[Codex] 解析流式事件，共 {} 个事件", events.len());
-        for event in events {
-            if let Some(event_type) = event.get("type").and_then(|v| v.as_str()) {
-                log::debug!("[Codex] 事件类型: {event_type}");
-                if event_type == "response.completed" {
-                    if let Some(response) = event.get("response") {
-                        log::debug!("[Codex] 找到 response.completed 事件，解析 usage");
-                        return Self::from_codex_response_adjusted(response);
-                    }
-                }
-            }
+        let usage = most_complete(
+            completed_responses(events).filter_map(Self::from_codex_response_adjusted),
+        );
+        if usage.is_none() {
+            log::debug!("[Codex] 未找到带 usage 的 response.completed 事件");
         }
-        log::debug!("[Codex] 未找到 response.completed 事件");
-        None
+        usage
     }
 
     /// 智能 Codex 响应解析 - 自动检测 OpenAI 或 Codex 格式
@@ -296,15 +326,11 @@ impl TokenUsage {
         log::debug!("[Codex] 智能解析流式事件，共 {} 个事件", events.len());
 
         // 先尝试 Codex Responses API 格式 (response.completed 事件)
-        for event in events {
-            if let Some(event_type) = event.get("type").and_then(|v| v.as_str()) {
-                if event_type == "response.completed" {
-                    if let Some(response) = event.get("response") {
-                        log::debug!("[Codex] 找到 response.completed 事件");
-                        return Self::from_codex_response_auto(response);
-                    }
-                }
-            }
+        if completed_responses(events).next().is_some() {
+            log::debug!("[Codex] 找到 response.completed 事件");
+            return most_complete(
+                completed_responses(events).filter_map(Self::from_codex_response_auto),
+            );
         }
 
         // 回退到 OpenAI Chat Completions 格式 (最后一个 chunk 包含 usage)
@@ -338,6 +364,7 @@ impl TokenUsage {
             output_tokens: completion_tokens as u32,
             cache_read_tokens: cached_tokens,
             cache_creation_tokens: 0,
+            reasoning_tokens: extract_reasoning_tokens(usage),
             model,
         })
     }
@@ -345,17 +372,18 @@ impl TokenUsage {
     /// 从 OpenAI Chat Completions API 流式响应解析
     pub fn from_openai_stream_events(events: &[Value]) -> Option<Self> {
         log::debug!("[Codex] 解析 OpenAI 流式事件，共 {} 个事件", events.len());
-        // OpenAI 流式响应在最后一个 chunk 中包含 usage
-        for event in events.iter().rev() {
-            if let Some(usage) = event.get("usage") {
-                if !usage.is_null() {
-                    log::debug!("[Codex] 找到 usage: {usage:?}");
-                    return Self::from_openai_response(event);
-                }
-            }
+        // OpenAI 流式响应通常在最后一个 chunk 中包含 usage；
+        // 部分中转会在中途附带累计 usage，乱序时取最完整的一份
+        let usage = most_complete(
+            events
+                .iter()
+                .filter(|event| event.get("usage").is_some_and(|u| !u.is_null()))
+                .filter_map(Self::from_openai_response),
+        );
+        if usage.is_none() {
+            log::debug!("[Codex] 未找到 usage 信息");
         }
-        log::debug!("[Codex] 未找到 usage 信息");
-        None
+        usage
     }
 
     /// 从 Gemini API 非流式响应解析
@@ -368,70 +396,60 @@ impl TokenUsage {
             .map(|s| s.to_string());
 
         let prompt_tokens = usage.get("promptTokenCount")?.as_u64()? as u32;
-        let total_tokens = usage.get("totalTokenCount")?.as_u64()? as u32;
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        let thoughts_tokens = count("thoughtsTokenCount");
 
         // 输出 tokens = 总 tokens - 输入 tokens
         // 这包含了 candidatesTokenCount + thoughtsTokenCount
-        let output_tokens = total_tokens.saturating_sub(prompt_tokens);
+        // 部分网关不返回 totalTokenCount，此时按 candidates + thoughts 计算
+        let output_tokens = match usage.get("totalTokenCount").and_then(|v| v.as_u64()) {
+            Some(total_tokens) => (total_tokens as u32).saturating_sub(prompt_tokens),
+            None => count("candidatesTokenCount").saturating_add(thoughts_tokens),
+        };
 
         Some(Self {
             input_tokens: prompt_tokens,
             output_tokens,
-            cache_read_tokens: usage
-                .get("cachedContentTokenCount")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as u32,
+            cache_read_tokens: count("cachedContentTokenCount"),
             cache_creation_tokens: 0,
+            reasoning_tokens: thoughts_tokens,
             model,
         })
     }
 
     /// 从 Gemini API 流式响应解析
+    ///
+    /// 每个 chunk 的 usageMetadata 都是累计快照，取 totalTokenCount 最大的一份，
+    /// 而不是假设最后到达的就是最终值。
     #[allow(dead_code)]
     pub fn from_gemini_stream_chunks(chunks: &[Value]) -> Option<Self> {
-        let mut total_input = 0u32;
-        let mut total_tokens = 0u32;
-        let mut total_cache_read = 0u32;
-        let mut model: Option<String> = None;
+        let count =
+            |usage: &Value, key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
 
-        for chunk in chunks {
-            if let Some(usage) = chunk.get("usageMetadata") {
-                // 输入 tokens (通常在所有 chunk 中保持不变)
-                total_input = usage
-                    .get("promptTokenCount")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
+        // 总 tokens (包含输入 + 输出 + 思考)，最大者即最终快照
+        let usage = chunks
+            .iter()
+            .filter_map(|chunk| chunk.get("usageMetadata"))
+            .max_by_key(|usage| count(usage, "totalTokenCount"))?;
 
-                // 总 tokens (包含输入 + 输出 + 思考)
-                total_tokens = usage
-                    .get("totalTokenCount")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
-
-                // 缓存读取 tokens
-                total_cache_read = usage
-                    .get("cachedContentTokenCount")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
-            }
-
-            // 提取实际使用的模型名称（modelVersion 字段）
-            if model.is_none() {
-                if let Some(model_version) = chunk.get("modelVersion").and_then(|v| v.as_str()) {
-                    model = Some(model_version.to_string());
-                }
-            }
-        }
+        // 提取实际使用的模型名称（modelVersion 字段）
+        let model = chunks
+            .iter()
+            .find_map(|chunk| chunk.get("modelVersion").and_then(|v| v.as_str()))
+            .map(|s| s.to_string());
 
+        let total_input = count(usage, "promptTokenCount");
         // 输出 tokens = 总 tokens - 输入 tokens
-        let total_output = total_tokens.saturating_sub(total_input);
+        let total_output = count(usage, "totalTokenCount").saturating_sub(total_input);
 
         if total_input > 0 || total_output > 0 {
             Some(Self {
                 input_tokens: total_input,
                 output_tokens: total_output,
-                cache_read_tokens: total_cache_read,
+                cache_read_tokens: count(usage, "cachedContentTokenCount"),
                 cache_creation_tokens: 0,
+                // 思考 tokens (已包含在 totalTokenCount 中)
+                reasoning_tokens: count(usage, "thoughtsTokenCount"),
                 model,
             })
         } else {
@@ -637,9 +655,32 @@ mod tests {
         assert_eq!(usage.output_tokens, 164);
         assert_eq!(usage.cache_read_tokens, 0);
         assert_eq!(usage.cache_creation_tokens, 0);
+        assert_eq!(usage.reasoning_tokens, 114);
         assert_eq!(usage.model, Some("gemini-3-pro-high".to_string()));
     }
 
+    #[test]
+    fn test_gemini_response_with_thoughts_without_total() {
+        // 部分网关只返回分项计数，没有 totalTokenCount
+        let response = json!({
+            "modelVersion": "gemini-2.5-pro-preview-06-05",
+            "usageMetadata": {
+                "promptTokenCount": 200,
+                "candidatesTokenCount": 40,
+                "thoughtsTokenCount": 360
+            }
+        });
+
+        let usage = TokenUsage::from_gemini_response(&response).unwrap();
+        assert_eq!(usage.input_tokens, 200);
+        assert_eq!(usage.output_tokens, 400);
+        assert_eq!(usage.reasoning_tokens, 360);
+        assert_eq!(
+            usage.model,
+            Some("gemini-2.5-pro-preview-06-05".to_string())
+        );
+    }
+
     #[test]
     fn test_codex_response_parsing_cached_tokens_in_details() {
         let response = json!({
@@ -792,6 +833,46 @@ mod tests {
         assert_eq!(usage.model, Some("claude-sonnet-4-20250514".to_string()));
     }
 
+    #[test]
+    fn test_claude_stream_cache_tokens_from_sse_transcript() {
+        // 原生 Anthropic SSE：message_start 报告缓存写入，message_delta 报告累计值
+        let transcript = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","model":"claude-sonnet-4-5-20250929","content":[],"stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":12,"cache_creation_input_tokens":2048,"cache_read_input_tokens":0,"output_tokens":1}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}
+
+event: ping
+data: {"type":"ping"}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello"}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":0}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"input_tokens":12,"cache_creation_input_tokens":2048,"cache_read_input_tokens":4096,"output_tokens":35}}
+
+event: message_stop
+data: {"type":"message_stop"}
+"#;
+        let events: Vec<Value> = transcript
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+
+        let usage = TokenUsage::from_claude_stream_events(&events).unwrap();
+        assert_eq!(usage.input_tokens, 12);
+        assert_eq!(usage.output_tokens, 35);
+        // 累计值不重复相加
+        assert_eq!(usage.cache_creation_tokens, 2048);
+        // message_delta 中新增的缓存命中不丢失
+        assert_eq!(usage.cache_read_tokens, 4096);
+        assert_eq!(usage.model, Some("claude-sonnet-4-5-20250929".to_string()));
+    }
+
     // ============================================================================
     // 智能 Codex 解析测试
     // ============================================================================
@@ -897,4 +978,211 @@ mod tests {
         assert_eq!(usage.output_tokens, 50);
         assert_eq!(usage.model, Some("gpt-4o".to_string()));
     }
+
+    #[test]
+    fn test_openai_response_reasoning_tokens() {
+        let response = json!({
+            "model": "o3",
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": 500,
+                "completion_tokens_details": {
+                    "reasoning_tokens": 320
+                }
+            }
+        });
+
+        let usage = TokenUsage::from_openai_response(&response).unwrap();
+        assert_eq!(usage.output_tokens, 500);
+        assert_eq!(usage.reasoning_tokens, 320);
+    }
+
+    #[test]
+    fn test_codex_response_reasoning_tokens() {
+        let response = json!({
+            "model": "gpt-5.2-codex",
+            "usage": {
+                "input_tokens": 1000,
+                "output_tokens": 800,
+                "output_tokens_details": {
+                    "reasoning_tokens": 600
+                }
+            }
+        });
+
+        let usage = TokenUsage::from_codex_response_auto(&response).unwrap();
+        assert_eq!(usage.output_tokens, 800);
+        assert_eq!(usage.reasoning_tokens, 600);
+
+        let usage = TokenUsage::from_codex_response_adjusted(&response).unwrap();
+        assert_eq!(usage.reasoning_tokens, 600);
+    }
+
+    #[test]
+    fn test_codex_stream_events_reasoning_tokens() {
+        let events = vec![
+            json!({"type": "response.created", "response": {}}),
+            json!({
+                "type": "response.completed",
+                "response": {
+                    "model": "o3",
+                    "usage": {
+                        "input_tokens": 200,
+                        "output_tokens": 150,
+                        "output_tokens_details": {
+                            "reasoning_tokens": 100
+                        }
+                    }
+                }
+            }),
+        ];
+
+        let usage = TokenUsage::from_codex_stream_events_auto(&events).unwrap();
+        assert_eq!(usage.output_tokens, 150);
+        assert_eq!(usage.reasoning_tokens, 100);
+    }
+
+    #[test]
+    fn test_openai_stream_events_reasoning_tokens() {
+        let events = vec![
+            json!({
+                "model": "o4-mini",
+                "choices": [{"delta": {"content": "Hi"}}]
+            }),
+            json!({
+                "model": "o4-mini",
+                "choices": [],
+                "usage": {
+                    "prompt_tokens": 20,
+                    "completion_tokens": 90,
+                    "completion_tokens_details": {
+                        "reasoning_tokens": 64
+                    }
+                }
+            }),
+        ];
+
+        let usage = TokenUsage::from_openai_stream_events(&events).unwrap();
+        assert_eq!(usage.output_tokens, 90);
+        assert_eq!(usage.reasoning_tokens, 64);
+    }
+
+    #[test]
+    fn test_claude_stream_reasoning_tokens() {
+        let events = vec![
+            json!({
+                "type": "message_start",
+                "message": {
+                    "model": "claude-sonnet-4-5",
+                    "usage": {"input_tokens": 50, "output_tokens": 1}
+                }
+            }),
+            json!({
+                "type": "message_delta",
+                "usage": {
+                    "output_tokens": 400,
+                    "output_tokens_details": {
+                        "reasoning_tokens": 250
+                    }
+                }
+            }),
+        ];
+
+        let usage = TokenUsage::from_claude_stream_events(&events).unwrap();
+        assert_eq!(usage.output_tokens, 400);
+        assert_eq!(usage.reasoning_tokens, 250);
+    }
+
+    #[test]
+    fn test_gemini_stream_reasoning_tokens() {
+        let chunks = vec![
+            json!({
+                "modelVersion": "gemini-2.5-pro",
+                "usageMetadata": {
+                    "promptTokenCount": 100,
+                    "thoughtsTokenCount": 30,
+                    "totalTokenCount": 130
+                }
+            }),
+            json!({
+                "usageMetadata": {
+                    "promptTokenCount": 100,
+                    "candidatesTokenCount": 20,
+                    "thoughtsTokenCount": 80,
+                    "totalTokenCount": 200
+                }
+            }),
+        ];
+
+        let usage = TokenUsage::from_gemini_stream_chunks(&chunks).unwrap();
+        assert_eq!(usage.output_tokens, 100);
+        assert_eq!(usage.reasoning_tokens, 80);
+    }
+
+    #[test]
+    fn test_stream_parsers_tolerate_shuffled_events() {
+        // Claude：累计的 message_delta 先于最终值到达，message_start 排在末尾
+        let claude = vec![
+            json!({"type": "message_delta", "usage": {"output_tokens": 80, "cache_read_input_tokens": 40}}),
+            json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "hi"}}),
+            json!({"type": "message_delta", "usage": {"output_tokens": 12}}),
+            json!({
+                "type": "message_start",
+                "message": {"model": "claude-sonnet-4-5", "usage": {"input_tokens": 300, "output_tokens": 1}}
+            }),
+        ];
+        let usage = TokenUsage::from_claude_stream_events(&claude).unwrap();
+        assert_eq!(usage.input_tokens, 300);
+        assert_eq!(usage.output_tokens, 80);
+        assert_eq!(usage.cache_read_tokens, 40);
+        assert_eq!(usage.model.as_deref(), Some("claude-sonnet-4-5"));
+
+        // OpenAI：中途的累计 usage 晚于最终 usage 到达
+        let openai = vec![
+            json!({"model": "gpt-4o", "usage": {"prompt_tokens": 50, "completion_tokens": 40}}),
+            json!({"choices": [{"delta": {"content": "x"}}], "usage": null}),
+            json!({"model": "gpt-4o", "usage": {"prompt_tokens": 50, "completion_tokens": 5}}),
+        ];
+        let usage = TokenUsage::from_openai_stream_events(&openai).unwrap();
+        assert_eq!(usage.output_tokens, 40);
+
+        // Codex：存在多个 response.completed 时取最完整的一份
+        let codex = vec![
+            json!({"type": "response.completed", "response": {"usage": {"input_tokens": 100, "output_tokens": 60}}}),
+            json!({"type": "response.output_text.delta", "delta": "x"}),
+            json!({"type": "response.completed", "response": {"usage": {"input_tokens": 100, "output_tokens": 3}}}),
+        ];
+        let usage = TokenUsage::from_codex_stream_events_auto(&codex).unwrap();
+        assert_eq!(usage.output_tokens, 60);
+        let usage = TokenUsage::from_codex_stream_events(&codex).unwrap();
+        assert_eq!(usage.output_tokens, 60);
+
+        // Gemini：最终快照排在中间
+        let gemini = vec![
+            json!({"usageMetadata": {"promptTokenCount": 100, "totalTokenCount": 110}}),
+            json!({
+                "modelVersion": "gemini-2.5-pro",
+                "usageMetadata": {"promptTokenCount": 100, "thoughtsTokenCount": 30, "totalTokenCount": 250}
+            }),
+            json!({"usageMetadata": {"promptTokenCount": 100, "totalTokenCount": 160}}),
+        ];
+        let usage = TokenUsage::from_gemini_stream_chunks(&gemini).unwrap();
+        assert_eq!(usage.input_tokens, 100);
+        assert_eq!(usage.output_tokens, 150);
+        assert_eq!(usage.reasoning_tokens, 30);
+        assert_eq!(usage.model.as_deref(), Some("gemini-2.5-pro"));
+    }
+
+    #[test]
+    fn test_response_without_reasoning_details() {
+        let response = json!({
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 5
+            }
+        });
+
+        let usage = TokenUsage::from_openai_response(&response).unwrap();
+        assert_eq!(usage.reasoning_tokens, 0);
+    }
 }
@@ -0,0 +1,87 @@
+//! 临时暂停使用量记录
+//!
+//! 压测时不希望污染真实的使用统计：暂停期间请求照常转发，但不写入 proxy_request_logs。
+//! 可指定暂停时长，到期后自动恢复（在下一次检查时生效，无需后台定时任务）。
+//! 状态仅存在于内存中，代理重启后恢复记录。
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+enum PauseState {
+    Running,
+    /// 暂停；`until` 为 None 表示直到手动恢复
+    Paused {
+        until: Option<Instant>,
+    },
+}
+
+/// 使用量记录暂停开关
+#[derive(Debug)]
+pub struct LoggingPause {
+    state: Mutex<PauseState>,
+}
+
+impl Default for LoggingPause {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(PauseState::Running),
+        }
+    }
+}
+
+impl LoggingPause {
+    /// 暂停或恢复记录；`duration` 为暂停时长，None 表示直到手动恢复
+    pub fn set(&self, paused: bool, duration: Option<Duration>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = if paused {
+            PauseState::Paused {
+                until: duration.map(|d| Instant::now() + d),
+            }
+        } else {
+            PauseState::Running
+        };
+    }
+
+    /// 当前是否暂停（暂停到期时自动恢复）
+    pub fn is_paused(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match *state {
+            PauseState::Running => false,
+            PauseState::Paused { until: Some(until) } if Instant::now() >= until => {
+                *state = PauseState::Running;
+                log::info!("使用量记录暂停已到期，自动恢复记录");
+                false
+            }
+            PauseState::Paused { .. } => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_without_duration_lasts_until_resumed() {
+        let pause = LoggingPause::default();
+        assert!(!pause.is_paused());
+
+        pause.set(true, None);
+        assert!(pause.is_paused());
+
+        pause.set(false, None);
+        assert!(!pause.is_paused());
+    }
+
+    #[test]
+    fn pause_with_duration_auto_resumes() {
+        let pause = LoggingPause::default();
+        pause.set(true, Some(Duration::from_millis(0)));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(!pause.is_paused());
+
+        pause.set(true, Some(Duration::from_secs(60)));
+        assert!(pause.is_paused());
+    }
+}
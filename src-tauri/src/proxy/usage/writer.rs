@@ -0,0 +1,256 @@
+//! 使用日志单写入任务
+//!
+//! 处理器为每个请求 `tokio::spawn` 记录使用量，高并发时这些任务逐个争抢 `lock_conn!` 互斥锁，
+//! 与界面查询相互阻塞。这里由一个后台任务独占写入：日志通过 mpsc 通道排队，
+//! 每次取出当前积压的全部日志（至多 [`MAX_BATCH_SIZE`] 条），在一个事务中批量插入；
+//! 事务失败时逐条重试，只丢弃本身写入失败的日志。
+//! 读取（定价、倍率等）仍直接访问数据库。
+
+use super::logger::{RequestLog, UsageLogger};
+use crate::database::Database;
+use crate::error::AppError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// 单个事务最多写入的日志条数
+const MAX_BATCH_SIZE: usize = 256;
+
+enum WriterMessage {
+    Log(Box<RequestLog>, i64),
+    /// 之前排队的日志全部写入后回执
+    #[cfg(test)]
+    Flush(tokio::sync::oneshot::Sender<()>),
+}
+
+/// 使用日志写入器（随代理服务器生命周期存在，所有发送端释放后后台任务退出）
+pub struct UsageLogWriter {
+    sender: Option<mpsc::UnboundedSender<WriterMessage>>,
+    #[cfg(test)]
+    batches: Arc<AtomicU64>,
+}
+
+impl UsageLogWriter {
+    /// 启动后台写入任务；不在 tokio 运行时中时退化为直接写入
+    pub fn spawn(db: Arc<Database>) -> Self {
+        let batches = Arc::new(AtomicU64::new(0));
+        let sender = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                handle.spawn(run(db, receiver, batches.clone()));
+                Some(sender)
+            }
+            Err(_) => {
+                log::warn!("[USG-001] 无 tokio 运行时，使用日志将直接写入数据库");
+                None
+            }
+        };
+        Self {
+            sender,
+            #[cfg(test)]
+            batches,
+        }
+    }
+
+    /// 提交一条日志；写入任务不可用时返回 false，由调用方直接写入
+    pub fn submit(&self, log: RequestLog, created_at: i64) -> bool {
+        match &self.sender {
+            Some(sender) => sender
+                .send(WriterMessage::Log(Box::new(log), created_at))
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    /// 等待此前提交的日志全部落盘
+    #[cfg(test)]
+    pub async fn flush(&self) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        if sender.send(WriterMessage::Flush(tx)).is_ok() {
+            let _ = rx.await;
+        }
+    }
+
+    /// 已提交的写入事务数
+    #[cfg(test)]
+    pub fn batches_written(&self) -> u64 {
+        self.batches.load(Ordering::Relaxed)
+    }
+}
+
+async fn run(
+    db: Arc<Database>,
+    mut receiver: mpsc::UnboundedReceiver<WriterMessage>,
+    batches: Arc<AtomicU64>,
+) {
+    while let Some(message) = receiver.recv().await {
+        let mut logs = Vec::new();
+        #[cfg(test)]
+        let mut flushes = Vec::new();
+        let mut next = Some(message);
+        while let Some(message) = next.take() {
+            match message {
+                WriterMessage::Log(log, created_at) => logs.push((*log, created_at)),
+                #[cfg(test)]
+                WriterMessage::Flush(ack) => flushes.push(ack),
+            }
+            if logs.len() < MAX_BATCH_SIZE {
+                next = receiver.try_recv().ok();
+            }
+        }
+
+        if !logs.is_empty() && write_logs(&db, &logs) {
+            batches.fetch_add(1, Ordering::Relaxed);
+        }
+        #[cfg(test)]
+        for ack in flushes {
+            let _ = ack.send(());
+        }
+    }
+}
+
+/// 写入一批日志，返回是否在单个事务中完成
+fn write_logs(db: &Database, logs: &[(RequestLog, i64)]) -> bool {
+    let Err(e) = write_batch(db, logs) else {
+        return true;
+    };
+    log::warn!(
+        "[USG-001] 批量写入 {} 条使用日志失败，改为逐条写入: {e}",
+        logs.len()
+    );
+
+    let conn = crate::database::lock_conn!(db.conn);
+    for (log, created_at) in logs {
+        if let Err(e) = UsageLogger::insert_log(&conn, log, *created_at) {
+            log::warn!("[USG-001] 写入使用日志 {} 失败: {e}", log.request_id);
+        }
+    }
+    false
+}
+
+fn write_batch(db: &Database, logs: &[(RequestLog, i64)]) -> Result<(), AppError> {
+    let mut conn = crate::database::lock_conn!(db.conn);
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    for (log, created_at) in logs {
+        UsageLogger::insert_log(&tx, log, *created_at)?;
+    }
+    tx.commit().map_err(|e| AppError::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::usage::parser::TokenUsage;
+
+    fn request_log(i: usize) -> RequestLog {
+        RequestLog {
+            request_id: format!("req-{i}"),
+            provider_id: "p1".to_string(),
+            app_type: "claude".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            request_model: "claude-sonnet-4-5".to_string(),
+            usage: TokenUsage::default(),
+            cost: None,
+            latency_ms: 10,
+            first_token_ms: None,
+            status_code: 200,
+            error_message: None,
+            session_id: None,
+            provider_type: None,
+            is_streaming: false,
+            cost_multiplier: "1".to_string(),
+            is_cached: false,
+            is_shadow: false,
+            api_key_index: None,
+            is_safety_net: false,
+            effective_max_tokens: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_logs_are_batched_without_deadlock() -> Result<(), AppError> {
+        const REQUESTS: usize = 200;
+
+        let db = Arc::new(Database::memory()?);
+        let writer = Arc::new(UsageLogWriter::spawn(db.clone()));
+
+        let tasks: Vec<_> = (0..REQUESTS)
+            .map(|i| {
+                let db = db.clone();
+                let writer = writer.clone();
+                tokio::spawn(async move {
+                    UsageLogger::new(&db)
+                        .with_writer(&writer)
+                        .log_request(&request_log(i))
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("logging task panicked")?;
+        }
+
+        tokio::time::timeout(std::time::Duration::from_secs(10), writer.flush())
+            .await
+            .expect("writer flush timed out");
+
+        let count: i64 = {
+            let conn = crate::database::lock_conn!(db.conn);
+            conn.query_row("SELECT COUNT(*) FROM proxy_request_logs", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?
+        };
+        assert_eq!(count, REQUESTS as i64);
+
+        let batches = writer.batches_written();
+        assert!(batches >= 1);
+        assert!(
+            batches < REQUESTS as u64,
+            "expected logs to be batched, got {batches} transactions"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn failed_batch_is_retried_row_by_row() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        UsageLogger::new(&db).log_request(&request_log(1))?;
+
+        // req-1 已存在，整批事务失败后其余日志仍应写入
+        let logs: Vec<_> = (0..3).map(|i| (request_log(i), 0)).collect();
+        assert!(!write_logs(&db, &logs));
+
+        let conn = crate::database::lock_conn!(db.conn);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM proxy_request_logs", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        assert_eq!(count, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn writer_outside_runtime_falls_back_to_direct_writes() -> Result<(), AppError> {
+        let db = Arc::new(Database::memory()?);
+        let writer = UsageLogWriter::spawn(db.clone());
+        assert!(!writer.submit(request_log(0), 0));
+
+        UsageLogger::new(&db)
+            .with_writer(&writer)
+            .log_request(&request_log(1))?;
+        let conn = crate::database::lock_conn!(db.conn);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM proxy_request_logs", [], |row| {
+                row.get(0)
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        assert_eq!(count, 1);
+        Ok(())
+    }
+}
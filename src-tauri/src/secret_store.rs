@@ -0,0 +1,428 @@
+//! 敏感信息存储
+//!
+//! 优先使用系统钥匙串（macOS Keychain / Linux Secret Service），
+//! 启动时探测失败则降级为明文文件存储，应用照常运行，并通过
+//! `secret_store_status` 命令向前端报告当前使用的后端与警告信息。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock};
+
+use crate::config::{get_app_config_dir, read_json_file, write_json_file};
+use crate::error::AppError;
+
+/// 钥匙串中使用的服务名
+#[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+const KEYCHAIN_SERVICE: &str = "cc-switch";
+/// 启动探测时写入并立即删除的条目
+const PROBE_KEY: &str = "__cc_switch_probe__";
+
+/// 存储后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretStoreKind {
+    Keychain,
+    Plaintext,
+}
+
+/// 当前存储状态（供前端展示）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretStoreStatus {
+    pub backend: SecretStoreKind,
+    /// 降级为明文时的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// 敏感信息存储后端
+pub trait SecretBackend: Send + Sync {
+    fn kind(&self) -> SecretStoreKind;
+    fn get(&self, key: &str) -> Result<Option<String>, AppError>;
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError>;
+    fn delete(&self, key: &str) -> Result<(), AppError>;
+
+    /// 检查后端是否可用：写入、读回并删除一个探测条目
+    fn probe(&self) -> Result<(), AppError> {
+        self.set(PROBE_KEY, "ok")?;
+        let read_back = self.get(PROBE_KEY)?;
+        self.delete(PROBE_KEY)?;
+        if read_back.as_deref() == Some("ok") {
+            Ok(())
+        } else {
+            Err(AppError::Message("探测条目读回结果不一致".to_string()))
+        }
+    }
+}
+
+/// 系统钥匙串后端（通过系统自带命令行工具访问，不支持的平台探测失败）
+pub struct KeychainBackend;
+
+impl KeychainBackend {
+    #[cfg_attr(not(any(target_os = "macos", target_os = "linux")), allow(dead_code))]
+    fn run(program: &str, args: &[&str], stdin: Option<&str>) -> Result<String, AppError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| AppError::Message(format!("无法执行 {program}: {e}")))?;
+
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            use std::io::Write;
+            pipe.write_all(input.as_bytes())
+                .map_err(|e| AppError::Message(format!("写入 {program} 输入失败: {e}")))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| AppError::Message(format!("等待 {program} 结束失败: {e}")))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .trim_end_matches(['\r', '\n'])
+                .to_string())
+        } else {
+            Err(AppError::Message(format!(
+                "{program} 执行失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+impl SecretBackend for KeychainBackend {
+    fn kind(&self) -> SecretStoreKind {
+        SecretStoreKind::Keychain
+    }
+
+    #[cfg(target_os = "macos")]
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        // 条目不存在时 security 以非零状态退出
+        Ok(Self::run(
+            "security",
+            &[
+                "find-generic-password",
+                "-s",
+                KEYCHAIN_SERVICE,
+                "-a",
+                key,
+                "-w",
+            ],
+            None,
+        )
+        .ok())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        // 通过 `security -i` 从 stdin 读取命令，避免密码出现在进程参数中（`ps` 可见）；
+        // 密码以十六进制传入，无需处理引号转义
+        let hex: String = value.bytes().map(|b| format!("{b:02x}")).collect();
+        let command = format!(
+            "add-generic-password -U -s {} -a {} -X {hex}\n",
+            security_quote(KEYCHAIN_SERVICE),
+            security_quote(key)
+        );
+        // 交互模式下命令失败不会改变退出码，错误只输出到 stderr
+        let mut child = Command::new("security")
+            .arg("-i")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| AppError::Message(format!("无法执行 security: {e}")))?;
+        if let Some(mut pipe) = child.stdin.take() {
+            use std::io::Write;
+            pipe.write_all(command.as_bytes())
+                .map_err(|e| AppError::Message(format!("写入 security 输入失败: {e}")))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| AppError::Message(format!("等待 security 结束失败: {e}")))?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() && stderr.trim().is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Message(format!(
+                "security 执行失败: {}",
+                stderr.trim()
+            )))
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        let _ = Self::run(
+            "security",
+            &["delete-generic-password", "-s", KEYCHAIN_SERVICE, "-a", key],
+            None,
+        );
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        // 条目不存在时 secret-tool 以非零状态退出
+        Ok(Self::run(
+            "secret-tool",
+            &["lookup", "service", KEYCHAIN_SERVICE, "account", key],
+            None,
+        )
+        .ok())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        Self::run(
+            "secret-tool",
+            &[
+                "store",
+                "--label",
+                KEYCHAIN_SERVICE,
+                "service",
+                KEYCHAIN_SERVICE,
+                "account",
+                key,
+            ],
+            Some(value),
+        )
+        .map(|_| ())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        Self::run(
+            "secret-tool",
+            &["clear", "service", KEYCHAIN_SERVICE, "account", key],
+            None,
+        )
+        .map(|_| ())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn get(&self, _key: &str) -> Result<Option<String>, AppError> {
+        Err(AppError::Message("当前平台不支持系统钥匙串".to_string()))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn set(&self, _key: &str, _value: &str) -> Result<(), AppError> {
+        Err(AppError::Message("当前平台不支持系统钥匙串".to_string()))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn delete(&self, _key: &str) -> Result<(), AppError> {
+        Err(AppError::Message("当前平台不支持系统钥匙串".to_string()))
+    }
+}
+
+/// 为 `security -i` 的命令行参数加引号
+#[cfg(target_os = "macos")]
+fn security_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 明文后端：以 JSON 文件保存在配置目录中（Unix 下权限为 600）
+pub struct PlaintextBackend {
+    path: PathBuf,
+}
+
+impl PlaintextBackend {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 默认位置：`~/.cc-switch/secrets.json`
+    pub fn default_path() -> PathBuf {
+        get_app_config_dir().join("secrets.json")
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>, AppError> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        read_json_file(&self.path)
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<(), AppError> {
+        write_json_file(&self.path, secrets)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(&self.path)
+                .map_err(|e| AppError::io(&self.path, e))?
+                .permissions();
+            perms.set_mode(0o600);
+            std::fs::set_permissions(&self.path, perms).map_err(|e| AppError::io(&self.path, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SecretBackend for PlaintextBackend {
+    fn kind(&self) -> SecretStoreKind {
+        SecretStoreKind::Plaintext
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(self.load()?.get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        let mut secrets = self.load()?;
+        secrets.insert(key.to_string(), value.to_string());
+        self.save(&secrets)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        let mut secrets = self.load()?;
+        if secrets.remove(key).is_some() {
+            self.save(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// 经过启动探测选定后端的敏感信息存储
+pub struct SecretStore {
+    backend: Box<dyn SecretBackend>,
+    warning: Option<String>,
+}
+
+impl SecretStore {
+    /// 探测首选后端，不可用时降级到备用后端并记录警告
+    pub fn select(preferred: Box<dyn SecretBackend>, fallback: Box<dyn SecretBackend>) -> Self {
+        match preferred.probe() {
+            Ok(()) => Self {
+                backend: preferred,
+                warning: None,
+            },
+            Err(e) => {
+                let warning = format!("安全存储不可用，已降级为明文存储: {e}");
+                log::warn!("{warning}");
+                Self {
+                    backend: fallback,
+                    warning: Some(warning),
+                }
+            }
+        }
+    }
+
+    /// 使用系统钥匙串，失败时降级为默认位置的明文存储
+    pub fn detect() -> Self {
+        Self::select(
+            Box::new(KeychainBackend),
+            Box::new(PlaintextBackend::new(PlaintextBackend::default_path())),
+        )
+    }
+
+    pub fn status(&self) -> SecretStoreStatus {
+        SecretStoreStatus {
+            backend: self.backend.kind(),
+            warning: self.warning.clone(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        self.backend.get(key)
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        self.backend.set(key, value)
+    }
+
+    pub fn delete(&self, key: &str) -> Result<(), AppError> {
+        self.backend.delete(key)
+    }
+}
+
+static SECRET_STORE: OnceLock<Arc<SecretStore>> = OnceLock::new();
+
+/// 启动时探测并初始化全局存储（已初始化时直接返回当前状态）
+pub fn init_secret_store() -> SecretStoreStatus {
+    secret_store().status()
+}
+
+/// 获取全局存储（未初始化时按需探测，并发调用只会探测一次）
+pub fn secret_store() -> Arc<SecretStore> {
+    SECRET_STORE
+        .get_or_init(|| Arc::new(SecretStore::detect()))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct FailingBackend;
+
+    impl SecretBackend for FailingBackend {
+        fn kind(&self) -> SecretStoreKind {
+            SecretStoreKind::Keychain
+        }
+
+        fn get(&self, _key: &str) -> Result<Option<String>, AppError> {
+            Err(AppError::Message("keychain locked".to_string()))
+        }
+
+        fn set(&self, _key: &str, _value: &str) -> Result<(), AppError> {
+            Err(AppError::Message("keychain locked".to_string()))
+        }
+
+        fn delete(&self, _key: &str) -> Result<(), AppError> {
+            Err(AppError::Message("keychain locked".to_string()))
+        }
+    }
+
+    #[test]
+    fn failing_keychain_falls_back_to_plaintext() {
+        let dir = TempDir::new().expect("create temp dir");
+        let path = dir.path().join("secrets.json");
+
+        let store = SecretStore::select(
+            Box::new(FailingBackend),
+            Box::new(PlaintextBackend::new(path.clone())),
+        );
+
+        let status = store.status();
+        assert_eq!(status.backend, SecretStoreKind::Plaintext);
+        assert!(status
+            .warning
+            .as_deref()
+            .is_some_and(|w| w.contains("keychain locked")));
+
+        store.set("api_key", "sk-secret").expect("set secret");
+        assert_eq!(
+            store.get("api_key").expect("get secret").as_deref(),
+            Some("sk-secret")
+        );
+        assert!(path.exists());
+
+        store.delete("api_key").expect("delete secret");
+        assert_eq!(store.get("api_key").expect("get deleted"), None);
+    }
+
+    #[test]
+    fn working_backend_is_kept_without_warning() {
+        let dir = TempDir::new().expect("create temp dir");
+        let store = SecretStore::select(
+            Box::new(PlaintextBackend::new(dir.path().join("primary.json"))),
+            Box::new(FailingBackend),
+        );
+
+        let status = store.status();
+        assert_eq!(status.backend, SecretStoreKind::Plaintext);
+        assert!(status.warning.is_none());
+        // 探测条目不应残留
+        assert_eq!(store.get(PROBE_KEY).expect("get probe"), None);
+    }
+}
@@ -1,14 +1,101 @@
 use super::provider::{sanitize_claude_settings_for_live, ProviderService};
+use super::proxy::{ProxyService, PROXY_TOKEN_PLACEHOLDER};
 use crate::app_config::{AppType, MultiAppConfig};
 use crate::error::AppError;
 use crate::provider::Provider;
+use crate::store::AppState;
 use chrono::Utc;
-use serde_json::Value;
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 const MAX_BACKUPS: usize = 10;
 
+/// 单个 live 配置文件的状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveFileInfo {
+    pub path: String,
+    pub exists: bool,
+    /// 最后修改时间（Unix 毫秒）
+    pub modified_at: Option<i64>,
+}
+
+/// 某个应用的 live 配置状态（诊断视图）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveFileStatus {
+    pub app: String,
+    pub files: Vec<LiveFileInfo>,
+    /// live 配置当前是否被代理接管（检测到占位符）
+    pub taken_over: bool,
+}
+
+/// 配置一致性问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrityIssueKind {
+    /// 本地 settings 指向的当前供应商在数据库中不存在
+    MissingCurrent,
+    /// live 配置仍处于代理接管状态，但代理并未运行
+    LiveTakenOverWithoutProxy,
+    /// 所属供应商已不存在的自定义端点
+    OrphanedEndpoint,
+}
+
+/// 单条配置一致性问题
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub app: String,
+    pub detail: String,
+    /// 建议的修复方式（自动修复执行的就是这一步）
+    pub suggested_fix: String,
+    /// 是否已自动修复
+    pub repaired: bool,
+}
+
+/// live 配置中与预期不一致的字段（Token 已脱敏）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveFieldDiff {
+    /// `base_url` 或 `token`
+    pub field: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// live 配置与当前供应商的比对结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveVerification {
+    pub app: String,
+    pub provider_id: String,
+    pub matches: bool,
+    /// live 配置处于代理接管状态（此时预期指向本地代理与占位符）
+    pub taken_over: bool,
+    pub diffs: Vec<LiveFieldDiff>,
+}
+
+/// 应用配置目录迁移结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub app: String,
+    /// true 表示移动（旧目录中的条目已删除），false 表示复制
+    pub moved: bool,
+    /// 已迁移的 skills 目录条目
+    pub skills: Vec<String>,
+    /// 已迁移的 live 配置文件
+    pub live_files: Vec<String>,
+    /// 新目录中已存在而跳过的条目（合并模式，保留新目录中的版本）
+    pub skipped: Vec<String>,
+    /// 新目录是当前生效的配置目录，已按当前供应商重新写入 live 配置
+    pub live_rewritten: bool,
+}
+
 /// 配置导入导出相关业务逻辑
 pub struct ConfigService;
 
@@ -83,6 +170,445 @@ impl ConfigService {
         Ok(())
     }
 
+    /// 列出 CC Switch 管理的各应用 live 配置文件及其状态
+    pub fn live_status() -> Result<Vec<LiveFileStatus>, AppError> {
+        AppType::all()
+            .map(|app| {
+                let files = Self::live_paths(&app)
+                    .into_iter()
+                    .map(|path| Self::live_file_info(&path))
+                    .collect();
+                Ok(LiveFileStatus {
+                    app: app.as_str().to_string(),
+                    files,
+                    taken_over: Self::is_live_taken_over(&app),
+                })
+            })
+            .collect()
+    }
+
+    /// 检查配置一致性，`auto_repair` 为 true 时按建议方式修复
+    ///
+    /// 检查项：失效的当前供应商引用、代理未运行时残留的 live 接管、孤儿自定义端点。
+    /// 本地 settings 的当前供应商与数据库 `is_current` 不同不算问题：数据库记录的是
+    /// 跨设备默认供应商（见 `ProviderService::set_default_provider`）。
+    pub async fn integrity_check(
+        state: &AppState,
+        auto_repair: bool,
+    ) -> Result<Vec<IntegrityIssue>, AppError> {
+        let mut issues = Vec::new();
+
+        for app in AppType::all().filter(|app| !app.is_additive_mode()) {
+            let Some(local_id) = crate::settings::get_current_provider(&app) else {
+                continue;
+            };
+            let providers = state.db.get_all_providers(app.as_str())?;
+            if !providers.contains_key(&local_id) {
+                let repaired = auto_repair
+                    && Self::repair(|| crate::settings::set_current_provider(&app, None));
+                issues.push(IntegrityIssue {
+                    kind: IntegrityIssueKind::MissingCurrent,
+                    app: app.as_str().to_string(),
+                    detail: format!("本地设置的当前供应商 {local_id} 在数据库中不存在"),
+                    suggested_fix: "清除本地设置，回退到数据库中的当前供应商".to_string(),
+                    repaired,
+                });
+            }
+        }
+
+        if !state.proxy_service.is_running().await {
+            let taken_over: Vec<AppType> = [AppType::Claude, AppType::Codex, AppType::Gemini]
+                .into_iter()
+                .filter(|app| {
+                    state
+                        .proxy_service
+                        .detect_takeover_in_live_config_for_app(app)
+                })
+                .collect();
+            // 恢复逻辑一次处理所有应用
+            let repaired = auto_repair
+                && !taken_over.is_empty()
+                && match state.proxy_service.recover_from_crash().await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        log::warn!("自动修复失败: {e}");
+                        false
+                    }
+                };
+            issues.extend(taken_over.into_iter().map(|app| IntegrityIssue {
+                kind: IntegrityIssueKind::LiveTakenOverWithoutProxy,
+                app: app.as_str().to_string(),
+                detail: "live 配置仍指向本地代理，但代理未运行".to_string(),
+                suggested_fix: "从备份或当前供应商恢复 live 配置".to_string(),
+                repaired,
+            }));
+        }
+
+        for (id, app, provider_id, url) in state.db.list_orphaned_endpoints()? {
+            let repaired = auto_repair && Self::repair(|| state.db.delete_endpoint_by_id(id));
+            issues.push(IntegrityIssue {
+                kind: IntegrityIssueKind::OrphanedEndpoint,
+                app,
+                detail: format!("端点 {url} 所属的供应商 {provider_id} 不存在"),
+                suggested_fix: "删除该端点".to_string(),
+                repaired,
+            });
+        }
+
+        Ok(issues)
+    }
+
+    /// 校验 live 配置的 base_url 与 Token 是否仍指向当前供应商
+    ///
+    /// 外部工具可能悄悄改写 live 配置。live 中的 Token 为代理占位符时视为接管状态，
+    /// 预期值改为本地代理地址与占位符；当前供应商未配置的字段不参与比较。
+    pub async fn verify_live_matches_current(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<LiveVerification, AppError> {
+        if app_type.is_additive_mode() {
+            return Err(AppError::localized(
+                "config.verify_live.unsupported",
+                format!("{} 为累加模式，没有当前供应商", app_type.as_str()),
+                format!(
+                    "{} uses additive mode and has no current provider",
+                    app_type.as_str()
+                ),
+            ));
+        }
+        let provider_id = crate::settings::get_effective_current_provider(&state.db, &app_type)?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "config.verify_live.no_current",
+                    format!("{} 未设置当前供应商", app_type.as_str()),
+                    format!("No current provider for {}", app_type.as_str()),
+                )
+            })?;
+        let provider = state
+            .db
+            .get_provider_by_id(&provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let live = ProviderService::read_live_settings(app_type.clone())?;
+        let (actual_url, actual_token) = Self::endpoint_fields(&app_type, &live);
+        let taken_over = actual_token.as_deref() == Some(PROXY_TOKEN_PLACEHOLDER);
+
+        let (expected_url, expected_token) = if taken_over {
+            let (proxy_url, proxy_codex_base_url) = state
+                .proxy_service
+                .build_proxy_urls()
+                .await
+                .map_err(AppError::Message)?;
+            let url = match app_type {
+                AppType::Codex => proxy_codex_base_url,
+                _ => proxy_url,
+            };
+            (Some(url), Some(PROXY_TOKEN_PLACEHOLDER.to_string()))
+        } else {
+            Self::endpoint_fields(&app_type, &provider.settings_config)
+        };
+
+        let normalize = |url: &Option<String>| {
+            url.as_deref()
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+        };
+        let mut diffs = Vec::new();
+        if expected_url.is_some() && normalize(&expected_url) != normalize(&actual_url) {
+            diffs.push(LiveFieldDiff {
+                field: "base_url".to_string(),
+                expected: expected_url,
+                actual: actual_url,
+            });
+        }
+        if expected_token.is_some() && expected_token != actual_token {
+            diffs.push(LiveFieldDiff {
+                field: "token".to_string(),
+                expected: expected_token.as_deref().map(mask_secret),
+                actual: actual_token.as_deref().map(mask_secret),
+            });
+        }
+
+        Ok(LiveVerification {
+            app: app_type.as_str().to_string(),
+            provider_id,
+            matches: diffs.is_empty(),
+            taken_over,
+            diffs,
+        })
+    }
+
+    /// 从供应商配置（或同结构的 live 配置）中读取 (base_url, token)
+    fn endpoint_fields(app: &AppType, settings: &Value) -> (Option<String>, Option<String>) {
+        let env_str = |key: &str| {
+            settings
+                .get("env")
+                .and_then(|env| env.get(key))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        match app {
+            AppType::Claude => (
+                env_str("ANTHROPIC_BASE_URL"),
+                [
+                    "ANTHROPIC_AUTH_TOKEN",
+                    "ANTHROPIC_API_KEY",
+                    "OPENROUTER_API_KEY",
+                    "OPENAI_API_KEY",
+                ]
+                .into_iter()
+                .find_map(env_str),
+            ),
+            AppType::Codex => (
+                settings
+                    .get("config")
+                    .and_then(Value::as_str)
+                    .and_then(|config| crate::codex_config::resolve_codex_base_url(config).ok()),
+                settings
+                    .get("auth")
+                    .and_then(|auth| auth.get("OPENAI_API_KEY"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            ),
+            AppType::Gemini => (env_str("GOOGLE_GEMINI_BASE_URL"), env_str("GEMINI_API_KEY")),
+            AppType::OpenCode | AppType::OpenClaw => (None, None),
+        }
+    }
+
+    /// 修改配置目录覆盖后，将旧目录中的 skills 与 live 配置迁移到新目录
+    ///
+    /// 新目录中已存在同名条目时：`merge` 为 true 则跳过这些条目（保留新目录中的版本），
+    /// 否则返回错误且不做任何改动。新目录是当前生效的配置目录时，迁移后按当前供应商重写 live 配置。
+    pub fn migrate_app_dir(
+        state: &AppState,
+        app_type: AppType,
+        old_dir: &Path,
+        new_dir: &Path,
+        move_files: bool,
+        merge: bool,
+    ) -> Result<MigrationReport, AppError> {
+        if old_dir == new_dir {
+            return Err(AppError::localized(
+                "config.migrate_dir.same_dir",
+                "新旧配置目录相同，无需迁移",
+                "Old and new config directories are the same",
+            ));
+        }
+
+        let old_skills = old_dir.join("skills");
+        let new_skills = new_dir.join("skills");
+        let mut entries: Vec<(String, PathBuf, PathBuf)> = Vec::new();
+        if old_skills.is_dir() {
+            let mut names: Vec<String> = fs::read_dir(&old_skills)
+                .map_err(|e| AppError::io(&old_skills, e))?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            entries.extend(names.into_iter().map(|name| {
+                (
+                    format!("skills/{name}"),
+                    old_skills.join(&name),
+                    new_skills.join(&name),
+                )
+            }));
+        }
+        for name in Self::live_file_names(&app_type) {
+            let src = old_dir.join(name);
+            if src.is_file() {
+                entries.push((name.to_string(), src, new_dir.join(name)));
+            }
+        }
+        // Claude 的 MCP 配置不在目录内，而是与目录同级的 `<目录名>.json`
+        if app_type == AppType::Claude {
+            if let (Some(src), Some(dest)) = (
+                crate::config::derive_mcp_path_from_override(old_dir),
+                crate::config::derive_mcp_path_from_override(new_dir),
+            ) {
+                if src.is_file() {
+                    let name = src
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    entries.push((name, src, dest));
+                }
+            }
+        }
+
+        let (conflicts, pending): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|(_, _, dest)| dest.exists() || dest.symlink_metadata().is_ok());
+        if !conflicts.is_empty() && !merge {
+            let names: Vec<&str> = conflicts.iter().map(|(name, _, _)| name.as_str()).collect();
+            return Err(AppError::localized(
+                "config.migrate_dir.conflict",
+                format!("新目录中已存在: {}", names.join(", ")),
+                format!("Already present in the new directory: {}", names.join(", ")),
+            ));
+        }
+
+        let mut report = MigrationReport {
+            app: app_type.as_str().to_string(),
+            moved: move_files,
+            skipped: conflicts.into_iter().map(|(name, _, _)| name).collect(),
+            ..Default::default()
+        };
+        for (name, src, dest) in pending {
+            Self::relocate_entry(&src, &dest, move_files)?;
+            match name.strip_prefix("skills/") {
+                Some(skill) => report.skills.push(skill.to_string()),
+                None => report.live_files.push(name),
+            }
+        }
+
+        if Self::effective_config_dir(&app_type) == new_dir {
+            ProviderService::sync_current_provider_for_app(state, app_type)?;
+            report.live_rewritten = true;
+        }
+
+        log::info!(
+            "配置目录迁移完成: {} -> {}（skills {} 个，live 文件 {} 个，跳过 {} 个）",
+            old_dir.display(),
+            new_dir.display(),
+            report.skills.len(),
+            report.live_files.len(),
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+
+    /// 各应用配置目录中的 live 配置文件名
+    fn live_file_names(app: &AppType) -> &'static [&'static str] {
+        match app {
+            AppType::Claude => &["settings.json"],
+            AppType::Codex => &["auth.json", "config.toml"],
+            AppType::Gemini => &[".env", "settings.json"],
+            AppType::OpenCode => &["opencode.json"],
+            AppType::OpenClaw => &["openclaw.json"],
+        }
+    }
+
+    fn effective_config_dir(app: &AppType) -> PathBuf {
+        match app {
+            AppType::Claude => crate::config::get_claude_config_dir(),
+            AppType::Codex => crate::codex_config::get_codex_config_dir(),
+            AppType::Gemini => crate::gemini_config::get_gemini_dir(),
+            AppType::OpenCode => crate::opencode_config::get_opencode_dir(),
+            AppType::OpenClaw => crate::openclaw_config::get_openclaw_dir(),
+        }
+    }
+
+    /// 移动或复制单个条目；符号链接（symlink 方式同步的 Skill）按原目标重建
+    fn relocate_entry(src: &Path, dest: &Path, move_files: bool) -> Result<(), AppError> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+        // 同一文件系统内直接重命名；跨设备时回退为复制后删除
+        if move_files && fs::rename(src, dest).is_ok() {
+            return Ok(());
+        }
+
+        let metadata = fs::symlink_metadata(src).map_err(|e| AppError::io(src, e))?;
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(src).map_err(|e| AppError::io(src, e))?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&target, dest).map_err(|e| AppError::io(dest, e))?;
+            #[cfg(windows)]
+            std::os::windows::fs::symlink_dir(&target, dest).map_err(|e| AppError::io(dest, e))?;
+        } else if metadata.is_dir() {
+            copy_dir_all(src, dest)?;
+        } else {
+            fs::copy(src, dest).map_err(|e| AppError::io(dest, e))?;
+        }
+
+        if move_files {
+            let removed = if metadata.is_dir() {
+                fs::remove_dir_all(src)
+            } else {
+                fs::remove_file(src)
+            };
+            removed.map_err(|e| AppError::io(src, e))?;
+        }
+        Ok(())
+    }
+
+    fn repair(action: impl FnOnce() -> Result<(), AppError>) -> bool {
+        match action() {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("自动修复失败: {e}");
+                false
+            }
+        }
+    }
+
+    fn live_paths(app: &AppType) -> Vec<PathBuf> {
+        match app {
+            AppType::Claude => vec![crate::config::get_claude_settings_path()],
+            AppType::Codex => vec![
+                crate::codex_config::get_codex_auth_path(),
+                crate::codex_config::get_codex_config_path(),
+            ],
+            AppType::Gemini => vec![
+                crate::gemini_config::get_gemini_env_path(),
+                crate::gemini_config::get_gemini_settings_path(),
+            ],
+            AppType::OpenCode => vec![crate::opencode_config::get_opencode_config_path()],
+            AppType::OpenClaw => vec![crate::openclaw_config::get_openclaw_config_path()],
+        }
+    }
+
+    fn live_file_info(path: &Path) -> LiveFileInfo {
+        let metadata = fs::metadata(path).ok();
+        let modified_at = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .map(|time| chrono::DateTime::<Utc>::from(time).timestamp_millis());
+        LiveFileInfo {
+            path: path.to_string_lossy().to_string(),
+            exists: metadata.is_some_and(|m| m.is_file()),
+            modified_at,
+        }
+    }
+
+    /// 读取 live 配置并检测代理占位符（累加模式应用不会被接管）
+    fn is_live_taken_over(app: &AppType) -> bool {
+        match app {
+            AppType::Claude => {
+                let path = crate::config::get_claude_settings_path();
+                path.exists()
+                    && crate::config::read_json_file::<Value>(&path)
+                        .map(|config| ProxyService::is_claude_live_taken_over(&config))
+                        .unwrap_or(false)
+            }
+            AppType::Codex => {
+                let path = crate::codex_config::get_codex_auth_path();
+                path.exists()
+                    && crate::config::read_json_file::<Value>(&path)
+                        .map(|auth| {
+                            ProxyService::is_codex_live_taken_over(&json!({ "auth": auth }))
+                        })
+                        .unwrap_or(false)
+            }
+            AppType::Gemini => {
+                crate::gemini_config::get_gemini_env_path().exists()
+                    && crate::gemini_config::read_gemini_env()
+                        .map(|env| {
+                            ProxyService::is_gemini_live_taken_over(
+                                &crate::gemini_config::env_to_json(&env),
+                            )
+                        })
+                        .unwrap_or(false)
+            }
+            AppType::OpenCode | AppType::OpenClaw => false,
+        }
+    }
+
     /// 同步当前供应商到对应的 live 配置。
     pub fn sync_current_providers_to_live(config: &mut MultiAppConfig) -> Result<(), AppError> {
         Self::sync_current_provider_for_app(config, &AppType::Claude)?;
@@ -229,3 +755,33 @@ impl ConfigService {
         Ok(())
     }
 }
+
+/// Token 脱敏：保留前后各 4 个字符，代理占位符原样显示
+fn mask_secret(secret: &str) -> String {
+    if secret == PROXY_TOKEN_PLACEHOLDER {
+        return secret.to_string();
+    }
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() > 8 {
+        let prefix: String = chars[..4].iter().collect();
+        let suffix: String = chars[chars.len() - 4..].iter().collect();
+        format!("{prefix}...{suffix}")
+    } else {
+        "***".to_string()
+    }
+}
+
+fn copy_dir_all(src: &Path, dest: &Path) -> Result<(), AppError> {
+    fs::create_dir_all(dest).map_err(|e| AppError::io(dest, e))?;
+    for entry in fs::read_dir(src).map_err(|e| AppError::io(src, e))? {
+        let entry = entry.map_err(|e| AppError::io(src, e))?;
+        let path = entry.path();
+        let target = dest.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir_all(&path, &target)?;
+        } else {
+            fs::copy(&path, &target).map_err(|e| AppError::io(&target, e))?;
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use indexmap::IndexMap;
 use std::collections::HashMap;
 
-use crate::app_config::{AppType, McpServer};
+use crate::app_config::{AppType, McpApps, McpServer};
 use crate::error::AppError;
 use crate::mcp;
 use crate::store::AppState;
@@ -381,4 +381,60 @@ impl McpService {
 
         Ok(new_count)
     }
+
+    /// 从 Claude Desktop 导入 MCP（claude_desktop_config.json 的 mcpServers）
+    ///
+    /// 名称与命令均相同的服务器视为重复并跳过；已存在的同 id 服务器仅启用 Claude，
+    /// 不覆盖其配置（与 `import_from_claude` 语义保持一致）。返回新导入或新启用 Claude 的 id 列表。
+    pub fn import_from_claude_desktop(state: &AppState) -> Result<Vec<String>, AppError> {
+        let servers = mcp::read_claude_desktop_servers()?;
+        let mut existing = state.db.get_all_mcp_servers()?;
+        let mut imported = Vec::new();
+
+        for (id, spec) in servers {
+            let command = mcp::server_command(&spec);
+            let duplicate = existing
+                .values()
+                .any(|s| s.name == id && mcp::server_command(&s.server) == command);
+            if duplicate {
+                log::debug!("跳过重复的 Claude Desktop MCP 服务器 '{id}'");
+                continue;
+            }
+
+            let to_save = match existing.get(&id) {
+                Some(existing_server) if existing_server.apps.claude => {
+                    log::debug!(
+                        "MCP 服务器 '{id}' 已启用 Claude，跳过 Claude Desktop 中的同 id 配置"
+                    );
+                    continue;
+                }
+                Some(existing_server) => {
+                    let mut merged = existing_server.clone();
+                    merged.apps.claude = true;
+                    merged
+                }
+                None => McpServer {
+                    id: id.clone(),
+                    name: id.clone(),
+                    server: spec,
+                    apps: McpApps {
+                        claude: true,
+                        ..Default::default()
+                    },
+                    description: None,
+                    homepage: None,
+                    docs: None,
+                    tags: Vec::new(),
+                },
+            };
+
+            state.db.save_mcp_server(&to_save)?;
+            Self::sync_server_to_apps(state, &to_save)?;
+            log::info!("已从 Claude Desktop 导入 MCP 服务器 '{id}'");
+            existing.insert(id.clone(), to_save);
+            imported.push(id);
+        }
+
+        Ok(imported)
+    }
 }
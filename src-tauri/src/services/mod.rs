@@ -6,6 +6,7 @@ pub mod omo;
 pub mod prompt;
 pub mod provider;
 pub mod proxy;
+pub mod settings;
 pub mod skill;
 pub mod speedtest;
 pub mod stream_check;
@@ -14,17 +15,25 @@ pub mod webdav;
 pub mod webdav_auto_sync;
 pub mod webdav_sync;
 
-pub use config::ConfigService;
+pub use config::{
+    ConfigService, IntegrityIssue, IntegrityIssueKind, LiveFieldDiff, LiveFileInfo, LiveFileStatus,
+    LiveVerification, MigrationReport,
+};
 pub use mcp::McpService;
 pub use omo::OmoService;
 pub use prompt::PromptService;
-pub use provider::{ProviderService, ProviderSortUpdate, SwitchResult};
+pub use provider::{
+    BroadcastResult, FieldDiff, LintWarning, ModelNormalizeResult, NativeExport, NativeFile,
+    ProviderImportResult, ProviderService, ProviderSortUpdate, StaleProvider, SwitchResult,
+    TokenFreshness, UrlChange,
+};
 pub use proxy::ProxyService;
+pub use settings::SettingsService;
 #[allow(unused_imports)]
 pub use skill::{DiscoverableSkill, Skill, SkillRepo, SkillService};
-pub use speedtest::{EndpointLatency, SpeedtestService};
+pub use speedtest::{EndpointLatency, EndpointStatus, SpeedtestService};
 #[allow(unused_imports)]
 pub use usage_stats::{
-    DailyStats, LogFilters, ModelStats, PaginatedLogs, ProviderLimitStatus, ProviderStats,
-    RequestLogDetail, UsageSummary,
+    CostEstimate, DailyStats, LogFilters, ModelStats, PaginatedLogs, ProviderLimitStatus,
+    ProviderSpend, ProviderStats, RequestLogDetail, SessionCost, SessionLogExport, UsageSummary,
 };
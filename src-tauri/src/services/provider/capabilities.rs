@@ -0,0 +1,713 @@
+//! Provider capability probe
+//!
+//! 很多中转站只支持流式或只支持非流式请求，或者会丢弃 `tools` / 系统提示词。
+//! 这里分别发送一个极小的流式请求和非流式请求，并尽力检测工具调用与系统提示词是否生效，
+//! 结果写入 `meta.capabilities` 供前端展示：
+//! - 流式：响应必须是 SSE（忽略 `stream: true` 直接返回 JSON 的视为不支持）
+//! - 非流式：响应必须是 JSON
+//! - 系统提示词：系统提示词中给出暗号，要求模型复述
+//! - 工具调用：强制调用探测工具，检查响应中是否出现对应的工具调用
+//!
+//! 系统提示词与工具调用的检测依赖非流式请求，非流式不可用时无法判断（为 None）。
+
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::{CapabilityReport, Provider, ProviderMeta};
+use crate::proxy::providers::{get_adapter, get_claude_api_format, AuthInfo, AuthStrategy};
+use crate::services::stream_check::StreamCheckService;
+use crate::store::AppState;
+
+/// 系统提示词探测的暗号
+const PROBE_SECRET: &str = "PINEAPPLE";
+/// 工具调用探测的工具名
+const PROBE_TOOL: &str = "get_probe_token";
+/// 探测请求的输出上限（工具调用需要留出生成参数的余量）
+const PROBE_MAX_TOKENS: u32 = 64;
+/// 错误信息写入 meta 前的最大长度
+const MAX_ERROR_CHARS: usize = 300;
+
+/// 上游请求格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WireFormat {
+    /// Anthropic Messages API
+    Anthropic,
+    /// OpenAI Chat Completions
+    OpenAiChat,
+    /// OpenAI Responses API
+    Responses,
+    /// Gemini generateContent
+    Gemini,
+}
+
+/// 探测请求类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProbeKind {
+    /// 仅包含用户消息
+    Plain,
+    /// 附带系统提示词
+    SystemPrompt,
+    /// 附带并强制调用探测工具
+    ToolUse,
+}
+
+struct Probe {
+    client: Client,
+    app_type: AppType,
+    base_url: String,
+    auth: AuthInfo,
+    format: WireFormat,
+    model: String,
+    reasoning_effort: Option<String>,
+    timeout: Duration,
+}
+
+impl ProviderService {
+    /// 探测供应商支持的请求模式（流式/非流式）以及工具调用、系统提示词是否生效
+    ///
+    /// 探测结果写入 `meta.capabilities` 并返回。请求失败不会返回错误，而是记录在报告中；
+    /// 只有供应商不存在、配置缺失或应用不支持探测时才返回错误。
+    pub async fn probe_capabilities(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<CapabilityReport, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let config = StreamCheckService::merge_provider_config(
+            &provider,
+            &state.db.get_stream_check_config()?,
+        );
+        let model = StreamCheckService::resolve_test_model(&app_type, &provider, &config);
+        let probe = Probe::new(&app_type, &provider, &model, config.timeout_secs)?;
+
+        let report = probe.run().await;
+        log::info!(
+            "[{}] 供应商 {} 能力探测: streaming={}, non_streaming={}, tool_use={:?}, system_prompt={:?}",
+            app_type.as_str(),
+            provider.name,
+            report.streaming,
+            report.non_streaming,
+            report.tool_use,
+            report.system_prompt
+        );
+
+        // 重新读取，避免覆盖探测期间的编辑
+        if let Some(mut latest) = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+        {
+            latest
+                .meta
+                .get_or_insert_with(ProviderMeta::default)
+                .capabilities = Some(report.clone());
+            state.db.save_provider(app_type.as_str(), &latest)?;
+        }
+        // 任一请求模式可用即视为供应商已验证
+        if report.streaming || report.non_streaming {
+            state.db.set_provider_last_verified(
+                app_type.as_str(),
+                provider_id,
+                report.tested_at,
+            )?;
+        }
+
+        Ok(report)
+    }
+}
+
+impl Probe {
+    fn new(
+        app_type: &AppType,
+        provider: &Provider,
+        model: &str,
+        timeout_secs: u64,
+    ) -> Result<Self, AppError> {
+        let format = match app_type {
+            AppType::Claude => match get_claude_api_format(provider) {
+                "openai_chat" => WireFormat::OpenAiChat,
+                "openai_responses" => WireFormat::Responses,
+                _ => WireFormat::Anthropic,
+            },
+            AppType::Codex => WireFormat::Responses,
+            AppType::Gemini => WireFormat::Gemini,
+            AppType::OpenCode | AppType::OpenClaw => {
+                return Err(AppError::localized(
+                    "provider.capabilities.unsupported_app",
+                    format!("{} 暂不支持能力探测", app_type.as_str()),
+                    format!(
+                        "{} does not support capability probing yet",
+                        app_type.as_str()
+                    ),
+                ));
+            }
+        };
+
+        let adapter = get_adapter(app_type);
+        let base_url = adapter
+            .extract_base_url(provider)
+            .map_err(|e| AppError::Message(format!("Failed to extract base_url: {e}")))?;
+        let auth = adapter
+            .extract_auth(provider)
+            .ok_or_else(|| AppError::Message("API Key not found".to_string()))?;
+        if auth.strategy == AuthStrategy::GitHubCopilot {
+            // Copilot 需要先换取会话 token，且只提供 Chat Completions，探测意义不大
+            return Err(AppError::localized(
+                "provider.capabilities.copilot_unsupported",
+                "GitHub Copilot 供应商暂不支持能力探测",
+                "GitHub Copilot providers do not support capability probing yet",
+            ));
+        }
+
+        let (model, reasoning_effort) = StreamCheckService::parse_model_with_effort(model);
+        let proxy_config = provider.meta.as_ref().and_then(|m| m.proxy_config.as_ref());
+
+        Ok(Self {
+            client: crate::proxy::http_client::get_for_provider(proxy_config),
+            app_type: app_type.clone(),
+            base_url,
+            auth,
+            format,
+            model,
+            reasoning_effort,
+            timeout: Duration::from_secs(timeout_secs),
+        })
+    }
+
+    async fn run(&self) -> CapabilityReport {
+        let (stream_result, non_stream_result) = tokio::join!(
+            self.probe_stream(),
+            self.request_json(ProbeKind::SystemPrompt)
+        );
+
+        let mut report = CapabilityReport {
+            model: self.model.clone(),
+            tested_at: chrono::Utc::now().timestamp(),
+            ..Default::default()
+        };
+
+        match stream_result {
+            Ok(()) => report.streaming = true,
+            Err(e) => report.stream_error = Some(e),
+        }
+
+        match non_stream_result {
+            Ok(response) => {
+                report.non_streaming = true;
+                report.system_prompt = Some(
+                    extract_text(self.format, &response)
+                        .to_uppercase()
+                        .contains(PROBE_SECRET),
+                );
+                // 上游拒绝 tools 参数（如 400）同样视为不支持工具调用
+                report.tool_use = Some(
+                    self.request_json(ProbeKind::ToolUse)
+                        .await
+                        .is_ok_and(|response| has_tool_call(self.format, &response)),
+                );
+            }
+            Err(e) => report.non_stream_error = Some(e),
+        }
+
+        report
+    }
+
+    /// 流式探测：只读取首个数据块，确认上游返回的是 SSE
+    async fn probe_stream(&self) -> Result<(), String> {
+        let mut response = self.send(ProbeKind::Plain, true).await?;
+        let is_event_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("text/event-stream"));
+
+        let chunk = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Stream read failed: {e}"))?
+            .ok_or_else(|| "No response data received".to_string())?;
+
+        let head = String::from_utf8_lossy(&chunk);
+        let head = head.trim_start();
+        if is_event_stream || head.starts_with("data:") || head.starts_with("event:") {
+            Ok(())
+        } else {
+            Err(truncate_error(format!(
+                "Upstream did not return an SSE stream: {head}"
+            )))
+        }
+    }
+
+    /// 非流式请求，响应必须是 JSON
+    async fn request_json(&self, kind: ProbeKind) -> Result<Value, String> {
+        let response = self.send(kind, false).await?;
+        let text = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read response: {e}"))?;
+        serde_json::from_str(&text)
+            .map_err(|_| truncate_error(format!("Upstream did not return a JSON response: {text}")))
+    }
+
+    async fn send(&self, kind: ProbeKind, stream: bool) -> Result<reqwest::Response, String> {
+        let body = self.body(kind, stream);
+        let urls = self.urls(stream);
+        for (i, url) in urls.iter().enumerate() {
+            let response = self
+                .request(url, stream)
+                .timeout(self.timeout)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_timeout() {
+                        "Request timeout".to_string()
+                    } else if e.is_connect() {
+                        format!("Connection failed: {e}")
+                    } else {
+                        e.to_string()
+                    }
+                })?;
+
+            let status = response.status();
+            if !status.is_success() {
+                // 与流式检查一致：首选地址返回 404 时回退到下一个候选地址
+                if status == StatusCode::NOT_FOUND && i + 1 < urls.len() {
+                    continue;
+                }
+                let text = response.text().await.unwrap_or_default();
+                return Err(truncate_error(format!("HTTP {}: {text}", status.as_u16())));
+            }
+            return Ok(response);
+        }
+        Err("No valid responses endpoint found".to_string())
+    }
+
+    /// 候选请求地址（与流式检查使用相同的拼接规则）
+    fn urls(&self, stream: bool) -> Vec<String> {
+        match self.format {
+            WireFormat::Anthropic => vec![StreamCheckService::resolve_claude_stream_url(
+                &self.base_url,
+                self.auth.strategy,
+                "anthropic",
+            )],
+            WireFormat::OpenAiChat => vec![StreamCheckService::resolve_claude_stream_url(
+                &self.base_url,
+                self.auth.strategy,
+                "openai_chat",
+            )],
+            WireFormat::Responses => StreamCheckService::resolve_responses_urls(&self.base_url),
+            WireFormat::Gemini => vec![StreamCheckService::resolve_gemini_url(
+                &self.base_url,
+                &self.model,
+                stream,
+            )],
+        }
+    }
+
+    /// 复用流式检查的请求构建（Claude CLI / Codex CLI 请求头）
+    fn request(&self, url: &str, stream: bool) -> RequestBuilder {
+        match self.app_type {
+            AppType::Codex => {
+                StreamCheckService::codex_request(&self.client, url, &self.auth, stream)
+            }
+            AppType::Gemini => {
+                StreamCheckService::gemini_request(&self.client, url, &self.auth, stream)
+            }
+            _ => StreamCheckService::claude_request(
+                &self.client,
+                url,
+                &self.auth,
+                self.format != WireFormat::Anthropic,
+                stream,
+            ),
+        }
+    }
+
+    fn body(&self, kind: ProbeKind, stream: bool) -> Value {
+        let user_prompt = match kind {
+            ProbeKind::Plain => "Say hi.",
+            ProbeKind::SystemPrompt => "What is the secret word?",
+            ProbeKind::ToolUse => "Call the tool to get the probe token.",
+        };
+        let system_prompt = format!(
+            "The secret word is {PROBE_SECRET}. When asked for the secret word, reply with it only."
+        );
+        let tool_description = "Returns the probe token.";
+        let tool_parameters = json!({ "type": "object", "properties": {} });
+
+        match self.format {
+            WireFormat::Anthropic => {
+                let mut body = json!({
+                    "model": self.model,
+                    "max_tokens": PROBE_MAX_TOKENS,
+                    "messages": [{ "role": "user", "content": user_prompt }],
+                    "stream": stream
+                });
+                match kind {
+                    ProbeKind::Plain => {}
+                    ProbeKind::SystemPrompt => body["system"] = json!(system_prompt),
+                    ProbeKind::ToolUse => {
+                        body["tools"] = json!([{
+                            "name": PROBE_TOOL,
+                            "description": tool_description,
+                            "input_schema": tool_parameters
+                        }]);
+                        body["tool_choice"] = json!({ "type": "tool", "name": PROBE_TOOL });
+                    }
+                }
+                body
+            }
+            WireFormat::OpenAiChat => {
+                let mut messages = Vec::new();
+                if kind == ProbeKind::SystemPrompt {
+                    messages.push(json!({ "role": "system", "content": system_prompt }));
+                }
+                messages.push(json!({ "role": "user", "content": user_prompt }));
+                let mut body = json!({
+                    "model": self.model,
+                    "max_tokens": PROBE_MAX_TOKENS,
+                    "messages": messages,
+                    "stream": stream
+                });
+                if kind == ProbeKind::ToolUse {
+                    body["tools"] = json!([{
+                        "type": "function",
+                        "function": {
+                            "name": PROBE_TOOL,
+                            "description": tool_description,
+                            "parameters": tool_parameters
+                        }
+                    }]);
+                    body["tool_choice"] = json!("required");
+                }
+                body
+            }
+            WireFormat::Responses => {
+                let mut body = json!({
+                    "model": self.model,
+                    "input": [{ "role": "user", "content": user_prompt }],
+                    "max_output_tokens": PROBE_MAX_TOKENS,
+                    "stream": stream
+                });
+                if let Some(effort) = &self.reasoning_effort {
+                    body["reasoning"] = json!({ "effort": effort });
+                }
+                match kind {
+                    ProbeKind::Plain => {}
+                    ProbeKind::SystemPrompt => body["instructions"] = json!(system_prompt),
+                    ProbeKind::ToolUse => {
+                        body["tools"] = json!([{
+                            "type": "function",
+                            "name": PROBE_TOOL,
+                            "description": tool_description,
+                            "parameters": tool_parameters
+                        }]);
+                        body["tool_choice"] = json!("required");
+                    }
+                }
+                body
+            }
+            WireFormat::Gemini => {
+                let mut body = json!({
+                    "contents": [{ "role": "user", "parts": [{ "text": user_prompt }] }],
+                    "generationConfig": { "maxOutputTokens": PROBE_MAX_TOKENS }
+                });
+                match kind {
+                    ProbeKind::Plain => {}
+                    ProbeKind::SystemPrompt => {
+                        body["systemInstruction"] = json!({ "parts": [{ "text": system_prompt }] });
+                    }
+                    ProbeKind::ToolUse => {
+                        body["tools"] = json!([{
+                            "functionDeclarations": [{
+                                "name": PROBE_TOOL,
+                                "description": tool_description,
+                                "parameters": tool_parameters
+                            }]
+                        }]);
+                        body["toolConfig"] = json!({
+                            "functionCallingConfig": { "mode": "ANY" }
+                        });
+                    }
+                }
+                body
+            }
+        }
+    }
+}
+
+/// 提取非流式响应中的文本内容
+fn extract_text(format: WireFormat, response: &Value) -> String {
+    let texts: Vec<&str> = match format {
+        WireFormat::Anthropic => blocks(response.get("content"))
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .collect(),
+        WireFormat::OpenAiChat => blocks(response.get("choices"))
+            .filter_map(|c| c.pointer("/message/content").and_then(Value::as_str))
+            .collect(),
+        WireFormat::Responses => blocks(response.get("output"))
+            .flat_map(|item| blocks(item.get("content")))
+            .filter_map(|c| c.get("text").and_then(Value::as_str))
+            .chain(response.get("output_text").and_then(Value::as_str))
+            .collect(),
+        WireFormat::Gemini => blocks(response.get("candidates"))
+            .flat_map(|c| blocks(c.pointer("/content/parts")))
+            .filter_map(|p| p.get("text").and_then(Value::as_str))
+            .collect(),
+    };
+    texts.concat()
+}
+
+/// 非流式响应中是否包含对探测工具的调用
+fn has_tool_call(format: WireFormat, response: &Value) -> bool {
+    let is_probe_tool = |name: Option<&Value>| name.and_then(Value::as_str) == Some(PROBE_TOOL);
+    match format {
+        WireFormat::Anthropic => blocks(response.get("content")).any(|b| {
+            b.get("type").and_then(Value::as_str) == Some("tool_use")
+                && is_probe_tool(b.get("name"))
+        }),
+        WireFormat::OpenAiChat => blocks(response.get("choices"))
+            .flat_map(|c| blocks(c.pointer("/message/tool_calls")))
+            .any(|call| is_probe_tool(call.pointer("/function/name"))),
+        WireFormat::Responses => blocks(response.get("output")).any(|item| {
+            item.get("type").and_then(Value::as_str) == Some("function_call")
+                && is_probe_tool(item.get("name"))
+        }),
+        WireFormat::Gemini => blocks(response.get("candidates"))
+            .flat_map(|c| blocks(c.pointer("/content/parts")))
+            .any(|p| is_probe_tool(p.pointer("/functionCall/name"))),
+    }
+}
+
+fn blocks(value: Option<&Value>) -> impl Iterator<Item = &Value> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| items.iter())
+        .into_iter()
+        .flatten()
+}
+
+fn truncate_error(message: String) -> String {
+    if message.chars().count() <= MAX_ERROR_CHARS {
+        return message;
+    }
+    let mut truncated: String = message.chars().take(MAX_ERROR_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use axum::{
+        extract::RawQuery,
+        http::{header, HeaderMap},
+        response::IntoResponse,
+        routing::post,
+        Json, Router,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// 只支持流式：非流式请求返回 400
+    async fn stream_only_upstream(Json(body): Json<Value>) -> axum::response::Response {
+        if body.get("stream").and_then(Value::as_bool) == Some(true) {
+            (
+                [(header::CONTENT_TYPE, "text/event-stream")],
+                "event: message_start\ndata: {\"type\":\"message_start\"}\n\n",
+            )
+                .into_response()
+        } else {
+            (
+                axum::http::StatusCode::BAD_REQUEST,
+                "only streaming requests are supported",
+            )
+                .into_response()
+        }
+    }
+
+    /// 只支持非流式：忽略 stream 参数始终返回 JSON；遵循系统提示词与工具调用
+    async fn non_stream_only_upstream(Json(body): Json<Value>) -> Json<Value> {
+        let content = if body.get("tools").is_some() {
+            json!([{ "type": "tool_use", "id": "toolu_1", "name": PROBE_TOOL, "input": {} }])
+        } else if body
+            .get("system")
+            .and_then(Value::as_str)
+            .is_some_and(|s| s.contains(PROBE_SECRET))
+        {
+            json!([{ "type": "text", "text": "pineapple" }])
+        } else {
+            json!([{ "type": "text", "text": "hi" }])
+        };
+        Json(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": content,
+            "usage": { "input_tokens": 1, "output_tokens": 1 }
+        }))
+    }
+
+    async fn spawn_upstream(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind upstream");
+        let addr = listener.local_addr().expect("upstream addr");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.ok();
+        });
+        format!("http://{addr}")
+    }
+
+    fn setup(base_url: &str) -> AppState {
+        let db = Arc::new(Database::memory().expect("init db"));
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": base_url,
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test"
+                }
+            }),
+            None,
+        );
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        AppState::new(db)
+    }
+
+    fn stored_capabilities(state: &AppState) -> Option<CapabilityReport> {
+        state
+            .db
+            .get_provider_by_id("p1", "claude")
+            .expect("load provider")
+            .and_then(|p| p.meta)
+            .and_then(|m| m.capabilities)
+    }
+
+    #[tokio::test]
+    async fn probe_detects_stream_only_upstream() {
+        let base_url =
+            spawn_upstream(Router::new().route("/v1/messages", post(stream_only_upstream))).await;
+        let state = setup(&base_url);
+
+        let report = ProviderService::probe_capabilities(&state, AppType::Claude, "p1")
+            .await
+            .expect("probe");
+
+        assert!(report.streaming);
+        assert!(!report.non_streaming);
+        assert!(report
+            .non_stream_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("HTTP 400")));
+        // 非流式不可用时无法判断工具调用与系统提示词
+        assert_eq!(report.tool_use, None);
+        assert_eq!(report.system_prompt, None);
+        let verified = state
+            .db
+            .get_providers_last_verified("claude")
+            .expect("load last verified");
+        assert_eq!(
+            verified.get("p1").copied().flatten(),
+            Some(report.tested_at)
+        );
+        assert_eq!(stored_capabilities(&state), Some(report));
+    }
+
+    #[tokio::test]
+    async fn probe_detects_non_stream_only_upstream() {
+        let base_url =
+            spawn_upstream(Router::new().route("/v1/messages", post(non_stream_only_upstream)))
+                .await;
+        let state = setup(&base_url);
+
+        let report = ProviderService::probe_capabilities(&state, AppType::Claude, "p1")
+            .await
+            .expect("probe");
+
+        assert!(!report.streaming);
+        assert!(report
+            .stream_error
+            .as_deref()
+            .is_some_and(|e| e.contains("SSE")));
+        assert!(report.non_streaming);
+        assert_eq!(report.tool_use, Some(true));
+        assert_eq!(report.system_prompt, Some(true));
+        assert_eq!(stored_capabilities(&state), Some(report));
+    }
+
+    #[tokio::test]
+    async fn probe_sends_claude_cli_requests() {
+        let seen: Arc<Mutex<Vec<(Option<String>, Option<String>)>>> = Arc::default();
+        let recorder = seen.clone();
+        let router = Router::new().route(
+            "/v1/messages",
+            post(
+                move |RawQuery(query): RawQuery, headers: HeaderMap, body: Json<Value>| {
+                    let user_agent = headers
+                        .get(header::USER_AGENT)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    recorder
+                        .lock()
+                        .expect("record lock")
+                        .push((query, user_agent));
+                    non_stream_only_upstream(body)
+                },
+            ),
+        );
+        let state = setup(&spawn_upstream(router).await);
+
+        ProviderService::probe_capabilities(&state, AppType::Claude, "p1")
+            .await
+            .expect("probe");
+
+        let seen = seen.lock().expect("record lock");
+        assert_eq!(seen.len(), 3, "stream, system prompt and tool use probes");
+        for (query, user_agent) in seen.iter() {
+            assert_eq!(query.as_deref(), Some("beta=true"));
+            assert!(user_agent
+                .as_deref()
+                .is_some_and(|ua| ua.starts_with("claude-cli/")));
+        }
+    }
+
+    #[test]
+    fn tool_call_detection_covers_all_formats() {
+        let cases = [
+            (
+                WireFormat::OpenAiChat,
+                json!({ "choices": [{ "message": { "tool_calls": [{ "function": { "name": PROBE_TOOL } }] } }] }),
+            ),
+            (
+                WireFormat::Responses,
+                json!({ "output": [{ "type": "function_call", "name": PROBE_TOOL }] }),
+            ),
+            (
+                WireFormat::Gemini,
+                json!({ "candidates": [{ "content": { "parts": [{ "functionCall": { "name": PROBE_TOOL } }] } }] }),
+            ),
+        ];
+        for (format, response) in cases {
+            assert!(has_tool_call(format, &response), "{format:?}");
+            assert!(!has_tool_call(format, &json!({})), "{format:?}");
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Broadcast shared config across apps
+//!
+//! 各应用的通用配置格式不同（Claude settings.json、Codex config.toml、Gemini .env），
+//! 其中能跨应用共享的只有与厂商无关的环境变量（代理、遥测开关等）。
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+
+/// 厂商专属的环境变量前缀，不会被广播
+const VENDOR_PREFIXES: &[&str] = &[
+    "ANTHROPIC_",
+    "CLAUDE_",
+    "GEMINI_",
+    "GOOGLE_",
+    "OPENAI_",
+    "CODEX_",
+    "OPENROUTER_",
+];
+
+/// 疑似凭证的变量名片段，不会被广播
+const CREDENTIAL_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD"];
+
+/// 判断环境变量是否可以在应用间共享
+pub(crate) fn is_shared_env_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    !VENDOR_PREFIXES
+        .iter()
+        .any(|prefix| upper.starts_with(prefix))
+        && !CREDENTIAL_MARKERS
+            .iter()
+            .any(|marker| upper.contains(marker))
+}
+
+/// 目标应用是否通过 `settings_config.env` 接收环境变量
+pub(crate) fn accepts_shared_env(app_type: &AppType) -> bool {
+    matches!(app_type, AppType::Claude | AppType::Gemini)
+}
+
+/// 从源应用的通用配置片段中提取可共享的环境变量
+pub(crate) fn shared_env_from_snippet(
+    app_type: &AppType,
+    snippet: &str,
+) -> Result<BTreeMap<String, String>, AppError> {
+    if !accepts_shared_env(app_type) {
+        return Err(AppError::localized(
+            "provider.broadcast.unsupported_source",
+            format!("{} 的通用配置无法广播到其他应用", app_type.as_str()),
+            format!(
+                "Common config of {} cannot be broadcast to other apps",
+                app_type.as_str()
+            ),
+        ));
+    }
+
+    let trimmed = snippet.trim();
+    if trimmed.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let value: Value = serde_json::from_str(trimmed)
+        .map_err(|e| AppError::Message(format!("解析通用配置失败: {e}")))?;
+
+    // Claude 片段为 settings.json 结构（env 位于 env 字段），Gemini 片段本身即 env 键值
+    let env = match app_type {
+        AppType::Claude => value.get("env").and_then(|v| v.as_object()),
+        _ => value.as_object(),
+    };
+
+    Ok(env
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| is_shared_env_key(key))
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            Some((key.clone(), value))
+        })
+        .collect())
+}
+
+/// 将共享变量合并到供应商的 `env`，返回是否有变化
+pub(crate) fn merge_shared_env(settings: &mut Value, shared: &BTreeMap<String, String>) -> bool {
+    let Some(obj) = settings.as_object_mut() else {
+        return false;
+    };
+    let env = obj
+        .entry("env")
+        .or_insert_with(|| Value::Object(Map::new()));
+    let Some(env) = env.as_object_mut() else {
+        return false;
+    };
+
+    let mut changed = false;
+    for (key, value) in shared {
+        if env.get(key).and_then(|v| v.as_str()) != Some(value.as_str()) {
+            env.insert(key.clone(), Value::String(value.clone()));
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn shared_keys_exclude_vendor_and_credential_vars() {
+        assert!(is_shared_env_key("HTTPS_PROXY"));
+        assert!(is_shared_env_key("DISABLE_TELEMETRY"));
+        assert!(!is_shared_env_key("ANTHROPIC_BASE_URL"));
+        assert!(!is_shared_env_key("GEMINI_API_KEY"));
+        assert!(!is_shared_env_key("MY_RELAY_TOKEN"));
+    }
+
+    #[test]
+    fn claude_snippet_yields_shared_env_only() {
+        let snippet = r#"{
+            "env": { "HTTPS_PROXY": "http://127.0.0.1:7890", "CLAUDE_CODE_MAX_OUTPUT_TOKENS": "8192", "MAX_THINKING": 4 },
+            "permissions": { "allow": [] }
+        }"#;
+        let shared = shared_env_from_snippet(&AppType::Claude, snippet).unwrap();
+        assert_eq!(shared.len(), 2);
+        assert_eq!(shared["HTTPS_PROXY"], "http://127.0.0.1:7890");
+        assert_eq!(shared["MAX_THINKING"], "4");
+    }
+
+    #[test]
+    fn codex_source_is_rejected() {
+        assert!(shared_env_from_snippet(&AppType::Codex, "approval_policy = \"never\"").is_err());
+    }
+
+    #[test]
+    fn merge_reports_changes() {
+        let mut settings = json!({ "env": { "GEMINI_API_KEY": "gm" } });
+        let shared = BTreeMap::from([("HTTPS_PROXY".to_string(), "http://proxy".to_string())]);
+        assert!(merge_shared_env(&mut settings, &shared));
+        assert!(!merge_shared_env(&mut settings, &shared));
+        assert_eq!(settings["env"]["GEMINI_API_KEY"], "gm");
+        assert_eq!(settings["env"]["HTTPS_PROXY"], "http://proxy");
+    }
+}
@@ -0,0 +1,243 @@
+//! Common config apply preview
+//!
+//! 在保存前预览把通用配置片段合并到某个供应商后会改动哪些字段。
+//! 合并逻辑与写入 Live 配置时相同；Codex 的 config.toml 先解析为结构化数据再比较，
+//! 字段路径形如 `config.model_providers.relay.base_url`。
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::live::apply_common_config_to_settings;
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::store::AppState;
+
+/// 单个字段的变化；`before` 为空表示新增，`after` 为空表示删除
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDiff {
+    /// 以 `.` 连接的字段路径（如 `env.HTTPS_PROXY`）
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl ProviderService {
+    /// Preview how applying a common config snippet would change a provider (nothing is saved)
+    ///
+    /// 返回按路径排序的字段差异；片段已全部包含在供应商配置中时返回空列表。
+    pub fn preview_apply_common_config(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        snippet: &str,
+    ) -> Result<Vec<FieldDiff>, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let merged =
+            apply_common_config_to_settings(&app_type, &provider.settings_config, snippet)?;
+
+        let before = comparable_settings(&app_type, &provider.settings_config)?;
+        let after = comparable_settings(&app_type, &merged)?;
+        let mut diffs = Vec::new();
+        diff_values(String::new(), Some(&before), Some(&after), &mut diffs);
+        diffs.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(diffs)
+    }
+}
+
+/// Codex 的 config 字段是 TOML 文本，解析为 JSON 结构后再比较
+fn comparable_settings(app_type: &AppType, settings: &Value) -> Result<Value, AppError> {
+    let mut settings = settings.clone();
+    if *app_type == AppType::Codex {
+        if let Some(config) = settings.get("config").and_then(Value::as_str) {
+            let parsed: toml::Value = toml::from_str(config)
+                .map_err(|e| AppError::Message(format!("Invalid Codex config.toml: {e}")))?;
+            settings["config"] = serde_json::to_value(parsed)
+                .map_err(|e| AppError::Message(format!("Invalid Codex config.toml: {e}")))?;
+        }
+    }
+    Ok(settings)
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// 递归比较两个值：对象逐键展开（含整体新增或删除的非空对象），其余类型（含数组）整体比较
+fn diff_values(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    out: &mut Vec<FieldDiff>,
+) {
+    let before_map = before.and_then(Value::as_object);
+    let after_map = after.and_then(Value::as_object);
+    let expand = match (before_map, after_map) {
+        (Some(_), Some(_)) => true,
+        (Some(map), None) => after.is_none() && !map.is_empty(),
+        (None, Some(map)) => before.is_none() && !map.is_empty(),
+        (None, None) => false,
+    };
+    if expand {
+        let mut keys: Vec<&String> = before_map.into_iter().flat_map(Map::keys).collect();
+        for key in after_map.into_iter().flat_map(Map::keys) {
+            if !before_map.is_some_and(|map| map.contains_key(key)) {
+                keys.push(key);
+            }
+        }
+        for key in keys {
+            diff_values(
+                join_path(&path, key),
+                before_map.and_then(|map| map.get(key)),
+                after_map.and_then(|map| map.get(key)),
+                out,
+            );
+        }
+        return;
+    }
+    if before != after {
+        out.push(FieldDiff {
+            path,
+            before: before.cloned(),
+            after: after.cloned(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::provider::Provider;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn state_with(app_type: &AppType, settings: Value) -> AppState {
+        let db = Arc::new(Database::memory().expect("init db"));
+        db.save_provider(
+            app_type.as_str(),
+            &Provider::with_id("p1".to_string(), "P1".to_string(), settings, None),
+        )
+        .expect("save provider");
+        AppState::new(db)
+    }
+
+    #[test]
+    fn claude_json_snippet_reports_added_and_changed_fields() {
+        let settings = json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "https://relay.example",
+                "HTTPS_PROXY": "http://old-proxy:7890"
+            }
+        });
+        let state = state_with(&AppType::Claude, settings.clone());
+
+        let diffs = ProviderService::preview_apply_common_config(
+            &state,
+            AppType::Claude,
+            "p1",
+            r#"{
+                "env": { "HTTPS_PROXY": "http://proxy:7890", "DISABLE_TELEMETRY": "1" },
+                "includeCoAuthoredBy": false,
+                "permissions": { "allow": ["Bash(git:*)"] }
+            }"#,
+        )
+        .expect("preview");
+
+        assert_eq!(
+            diffs,
+            vec![
+                FieldDiff {
+                    path: "env.DISABLE_TELEMETRY".to_string(),
+                    before: None,
+                    after: Some(json!("1")),
+                },
+                FieldDiff {
+                    path: "env.HTTPS_PROXY".to_string(),
+                    before: Some(json!("http://old-proxy:7890")),
+                    after: Some(json!("http://proxy:7890")),
+                },
+                FieldDiff {
+                    path: "includeCoAuthoredBy".to_string(),
+                    before: None,
+                    after: Some(json!(false)),
+                },
+                FieldDiff {
+                    path: "permissions.allow".to_string(),
+                    before: None,
+                    after: Some(json!(["Bash(git:*)"])),
+                },
+            ]
+        );
+
+        // 预览不保存
+        let stored = state
+            .db
+            .get_provider_by_id("p1", "claude")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.settings_config, settings);
+    }
+
+    #[test]
+    fn codex_toml_snippet_is_diffed_structurally() {
+        let state = state_with(
+            &AppType::Codex,
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk" },
+                "config": "model_provider = \"relay\"\nmodel = \"gpt-5\"\n\n[model_providers.relay]\nbase_url = \"https://relay.example/v1\"\n"
+            }),
+        );
+
+        let diffs = ProviderService::preview_apply_common_config(
+            &state,
+            AppType::Codex,
+            "p1",
+            "model = \"gpt-5\"\ndisable_response_storage = true\n\n[mcp_servers.fs]\ncommand = \"npx\"\n",
+        )
+        .expect("preview");
+
+        let paths: Vec<&str> = diffs.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "config.disable_response_storage",
+                "config.mcp_servers.fs.command"
+            ]
+        );
+        assert_eq!(diffs[0].before, None);
+        assert_eq!(diffs[0].after, Some(json!(true)));
+        assert_eq!(diffs[1].after, Some(json!("npx")));
+    }
+
+    #[test]
+    fn snippet_already_applied_yields_no_diff() {
+        let state = state_with(
+            &AppType::Claude,
+            json!({ "env": { "HTTPS_PROXY": "http://proxy:7890" } }),
+        );
+        let diffs = ProviderService::preview_apply_common_config(
+            &state,
+            AppType::Claude,
+            "p1",
+            r#"{ "env": { "HTTPS_PROXY": "http://proxy:7890" } }"#,
+        )
+        .expect("preview");
+        assert!(diffs.is_empty());
+    }
+}
@@ -0,0 +1,509 @@
+//! Import providers from curl commands
+//!
+//! 开发者通常手里有一条能跑通的 curl 命令。这里从中提取请求地址、认证头与请求体中的模型，
+//! 去掉各应用固定追加的 API 路径（如 `/v1/messages`、`/chat/completions`）得到 base_url，
+//! 再按目标应用的配置结构生成供应商。
+//!
+//! 支持的认证方式：`x-api-key`、`Authorization: Bearer`、`x-goog-api-key`，
+//! 以及 Gemini 常见的 `?key=` 查询参数。
+
+use serde_json::{json, Map, Value};
+use url::Url;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::{CodexModelConfig, Provider, UniversalProvider};
+
+/// 从 curl 命令中提取的请求信息
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct CurlRequest {
+    pub url: String,
+    /// 请求头（名称已转为小写）
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+/// 认证凭证及其来源
+#[derive(Debug, PartialEq, Eq)]
+enum Credential {
+    ApiKey(String),
+    Bearer(String),
+    GoogApiKey(String),
+}
+
+impl Credential {
+    fn token(&self) -> &str {
+        match self {
+            Self::ApiKey(token) | Self::Bearer(token) | Self::GoogApiKey(token) => token,
+        }
+    }
+}
+
+fn curl_error(key: &'static str, zh: &str, en: &str) -> AppError {
+    AppError::localized(key, zh.to_string(), en.to_string())
+}
+
+/// 按 POSIX shell 规则拆分命令行（支持单双引号、反斜杠转义与续行）
+fn split_shell_words(command: &str) -> Result<Vec<String>, AppError> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(unterminated_quote()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(unterminated_quote()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(unterminated_quote()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // 行尾反斜杠是续行符
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => {
+                    in_word = true;
+                    current.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn unterminated_quote() -> AppError {
+    curl_error(
+        "provider.curl_import.unterminated_quote",
+        "curl 命令中存在未闭合的引号",
+        "The curl command has an unterminated quote",
+    )
+}
+
+/// 解析 curl 命令中的 URL、请求头与请求体
+pub(crate) fn parse_curl(command: &str) -> Result<CurlRequest, AppError> {
+    let words = split_shell_words(command)?;
+    let mut args = words.into_iter();
+    match args.next() {
+        Some(program) if program == "curl" || program.ends_with("/curl") => {}
+        _ => {
+            return Err(curl_error(
+                "provider.curl_import.not_curl",
+                "请粘贴以 curl 开头的命令",
+                "Paste a command that starts with curl",
+            ))
+        }
+    }
+
+    let mut request = CurlRequest::default();
+    while let Some(arg) = args.next() {
+        // 长选项支持 `--name=value` 写法
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let value = |args: &mut std::vec::IntoIter<String>| inline.clone().or_else(|| args.next());
+
+        match flag.as_str() {
+            "-H" | "--header" => {
+                if let Some((name, header_value)) = value(&mut args)
+                    .as_deref()
+                    .and_then(|header| header.split_once(':'))
+                {
+                    request.headers.push((
+                        name.trim().to_ascii_lowercase(),
+                        header_value.trim().to_string(),
+                    ));
+                }
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--json" => {
+                request.body = value(&mut args);
+            }
+            "--url" => {
+                if let Some(url) = value(&mut args) {
+                    request.url = url;
+                }
+            }
+            _ if arg.starts_with("-H") && arg.len() > 2 => {
+                if let Some((name, header_value)) = arg[2..].split_once(':') {
+                    request.headers.push((
+                        name.trim().to_ascii_lowercase(),
+                        header_value.trim().to_string(),
+                    ));
+                }
+            }
+            // 其它选项的取值不会是 http(s) 地址，按位置参数识别 URL 即可
+            _ if request.url.is_empty()
+                && (arg.starts_with("http://") || arg.starts_with("https://")) =>
+            {
+                request.url = arg;
+            }
+            _ => {}
+        }
+    }
+
+    if request.url.is_empty() {
+        return Err(curl_error(
+            "provider.curl_import.missing_url",
+            "curl 命令中未找到 http(s) 请求地址",
+            "No http(s) URL found in the curl command",
+        ));
+    }
+    Ok(request)
+}
+
+fn header<'a>(request: &'a CurlRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+fn extract_credential(request: &CurlRequest, url: &Url) -> Option<Credential> {
+    if let Some(token) = header(request, "x-api-key") {
+        return Some(Credential::ApiKey(token.to_string()));
+    }
+    if let Some(token) = header(request, "authorization").and_then(|value| {
+        value
+            .strip_prefix("Bearer ")
+            .or_else(|| value.strip_prefix("bearer "))
+    }) {
+        return Some(Credential::Bearer(token.trim().to_string()));
+    }
+    if let Some(token) = header(request, "x-goog-api-key") {
+        return Some(Credential::GoogApiKey(token.to_string()));
+    }
+    url.query_pairs()
+        .find(|(key, value)| key == "key" && !value.is_empty())
+        .map(|(_, value)| Credential::GoogApiKey(value.to_string()))
+}
+
+/// 各应用客户端会自行追加的 API 路径；命中时截掉得到 base_url
+fn api_path_suffixes(app_type: &AppType) -> &'static [&'static str] {
+    match app_type {
+        AppType::Claude => &["/v1/messages/count_tokens", "/v1/messages"],
+        AppType::Codex | AppType::OpenCode | AppType::OpenClaw => {
+            &["/chat/completions", "/responses", "/completions", "/models"]
+        }
+        AppType::Gemini => &["/v1beta/models", "/v1/models"],
+    }
+}
+
+/// 去掉查询参数与 API 路径，返回 (base_url, 截掉的路径剩余部分)
+fn infer_base_url(app_type: &AppType, url: &Url) -> (String, String) {
+    let origin = url.origin().ascii_serialization();
+    let path = url.path().trim_end_matches('/');
+    for suffix in api_path_suffixes(app_type) {
+        if let Some(index) = path.find(suffix) {
+            let rest = &path[index + suffix.len()..];
+            if rest.is_empty() || rest.starts_with('/') {
+                return (format!("{origin}{}", &path[..index]), rest.to_string());
+            }
+        }
+    }
+    (format!("{origin}{path}"), String::new())
+}
+
+/// 模型：优先取请求体中的 `model`，Gemini 则取路径中的 `models/{model}:method`
+fn infer_model(app_type: &AppType, body: Option<&str>, rest: &str) -> Option<String> {
+    if *app_type == AppType::Gemini {
+        let model = rest
+            .trim_start_matches('/')
+            .split(':')
+            .next()
+            .unwrap_or_default();
+        if !model.is_empty() && !model.contains('/') {
+            return Some(model.to_string());
+        }
+    }
+    body.and_then(|body| serde_json::from_str::<Value>(body).ok())
+        .and_then(|body| body.get("model")?.as_str().map(str::to_string))
+        .filter(|model| !model.trim().is_empty())
+}
+
+/// 将 curl 命令映射为指定应用的供应商（未校验、未持久化）
+pub(crate) fn build_provider_from_curl(
+    app_type: &AppType,
+    command: &str,
+    name: &str,
+) -> Result<Provider, AppError> {
+    let request = parse_curl(command)?;
+    let url = Url::parse(&request.url).map_err(|e| {
+        AppError::localized(
+            "provider.curl_import.invalid_url",
+            format!("curl 请求地址无效: {e}"),
+            format!("Invalid URL in the curl command: {e}"),
+        )
+    })?;
+    let credential = extract_credential(&request, &url).ok_or_else(|| {
+        curl_error(
+            "provider.curl_import.missing_key",
+            "curl 命令中未找到 x-api-key、Authorization: Bearer 或 x-goog-api-key 认证信息",
+            "No x-api-key, Authorization: Bearer or x-goog-api-key credential found in the curl command",
+        )
+    })?;
+    let (base_url, rest) = infer_base_url(app_type, &url);
+    let model = infer_model(app_type, request.body.as_deref(), &rest);
+    let token = credential.token().to_string();
+
+    let name = name.trim();
+    let name = if name.is_empty() {
+        url.host_str().unwrap_or("curl").to_string()
+    } else {
+        name.to_string()
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+
+    let settings_config = match app_type {
+        AppType::Claude => {
+            // x-api-key 对应 ANTHROPIC_API_KEY，Bearer 对应 ANTHROPIC_AUTH_TOKEN
+            let token_key = match &credential {
+                Credential::ApiKey(_) => "ANTHROPIC_API_KEY",
+                _ => "ANTHROPIC_AUTH_TOKEN",
+            };
+            let mut env = Map::new();
+            env.insert("ANTHROPIC_BASE_URL".to_string(), json!(base_url));
+            env.insert(token_key.to_string(), json!(token));
+            if let Some(model) = model {
+                env.insert("ANTHROPIC_MODEL".to_string(), json!(model));
+            }
+            json!({ "env": env })
+        }
+        AppType::Codex => {
+            let mut universal = UniversalProvider::new(
+                id.clone(),
+                name.clone(),
+                "custom".to_string(),
+                base_url,
+                token,
+            );
+            universal.apps.codex = true;
+            universal.models.codex = model.map(|model| CodexModelConfig {
+                model: Some(model),
+                reasoning_effort: None,
+            });
+            let provider = universal.to_codex_provider().ok_or_else(|| {
+                AppError::Message("Failed to build codex provider config".to_string())
+            })?;
+            provider.settings_config
+        }
+        AppType::Gemini => {
+            let mut env = Map::new();
+            env.insert("GEMINI_API_KEY".to_string(), json!(token));
+            env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(base_url));
+            if let Some(model) = model {
+                env.insert("GEMINI_MODEL".to_string(), json!(model));
+            }
+            json!({ "env": env, "config": {} })
+        }
+        AppType::OpenCode => {
+            let models: Map<String, Value> = model
+                .map(|model| (model.clone(), json!({ "name": model })))
+                .into_iter()
+                .collect();
+            json!({
+                "npm": "@ai-sdk/openai-compatible",
+                "options": { "baseURL": base_url, "apiKey": token },
+                "models": models
+            })
+        }
+        AppType::OpenClaw => {
+            let mut config = json!({
+                "baseUrl": base_url,
+                "apiKey": token,
+                "api": "openai-completions"
+            });
+            if let Some(model) = model {
+                config["models"] = json!([{ "id": model, "name": model }]);
+            }
+            config
+        }
+    };
+
+    Ok(Provider::with_id(id, name, settings_config, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_words_handle_quotes_and_continuations() {
+        let words = split_shell_words(
+            "curl 'https://a.example/v1' \\\n  -H \"x-api-key: sk-\\\"q\\\"\" -d '{\"a\": 1}'",
+        )
+        .unwrap();
+        assert_eq!(
+            words,
+            vec![
+                "curl",
+                "https://a.example/v1",
+                "-H",
+                "x-api-key: sk-\"q\"",
+                "-d",
+                "{\"a\": 1}"
+            ]
+        );
+        assert!(split_shell_words("curl 'https://a.example").is_err());
+    }
+
+    #[test]
+    fn claude_curl_with_x_api_key() {
+        let provider = build_provider_from_curl(
+            &AppType::Claude,
+            r#"curl https://relay.example/api/v1/messages \
+  -H "x-api-key: sk-ant-relay" \
+  -H "anthropic-version: 2023-06-01" \
+  -H "content-type: application/json" \
+  -d '{"model": "claude-sonnet-4-5", "max_tokens": 16, "messages": []}'"#,
+            "",
+        )
+        .unwrap();
+
+        let env = &provider.settings_config["env"];
+        assert_eq!(env["ANTHROPIC_BASE_URL"], "https://relay.example/api");
+        assert_eq!(env["ANTHROPIC_API_KEY"], "sk-ant-relay");
+        assert_eq!(env["ANTHROPIC_MODEL"], "claude-sonnet-4-5");
+        assert!(env.get("ANTHROPIC_AUTH_TOKEN").is_none());
+        assert_eq!(provider.name, "relay.example");
+    }
+
+    #[test]
+    fn claude_curl_with_bearer_uses_auth_token() {
+        let provider = build_provider_from_curl(
+            &AppType::Claude,
+            "curl -X POST --url=https://relay.example/v1/messages -H 'Authorization: Bearer sk-bearer'",
+            "Relay",
+        )
+        .unwrap();
+
+        let env = &provider.settings_config["env"];
+        assert_eq!(env["ANTHROPIC_BASE_URL"], "https://relay.example");
+        assert_eq!(env["ANTHROPIC_AUTH_TOKEN"], "sk-bearer");
+        assert_eq!(provider.name, "Relay");
+    }
+
+    #[test]
+    fn codex_curl_keeps_version_prefix() {
+        let provider = build_provider_from_curl(
+            &AppType::Codex,
+            r#"curl https://relay.example/v1/chat/completions -H "Authorization: Bearer sk-openai" -H "Content-Type: application/json" --data-raw '{"model":"gpt-5-codex","messages":[]}'"#,
+            "Codex Relay",
+        )
+        .unwrap();
+
+        assert_eq!(
+            provider.settings_config["auth"]["OPENAI_API_KEY"],
+            "sk-openai"
+        );
+        let config = provider.settings_config["config"].as_str().unwrap();
+        assert!(config.contains("base_url = \"https://relay.example/v1\""));
+        assert!(config.contains("model = \"gpt-5-codex\""));
+    }
+
+    #[test]
+    fn gemini_curl_with_goog_header_and_path_model() {
+        let provider = build_provider_from_curl(
+            &AppType::Gemini,
+            r#"curl "https://gemini.example/v1beta/models/gemini-2.5-pro:generateContent" -H 'x-goog-api-key: gm-key' -H 'Content-Type: application/json' -X POST -d '{"contents":[]}'"#,
+            "",
+        )
+        .unwrap();
+
+        let env = &provider.settings_config["env"];
+        assert_eq!(env["GEMINI_API_KEY"], "gm-key");
+        assert_eq!(env["GOOGLE_GEMINI_BASE_URL"], "https://gemini.example");
+        assert_eq!(env["GEMINI_MODEL"], "gemini-2.5-pro");
+    }
+
+    #[test]
+    fn gemini_curl_with_key_query_param() {
+        let provider = build_provider_from_curl(
+            &AppType::Gemini,
+            "curl 'https://gemini.example/v1beta/models/gemini-2.5-flash:streamGenerateContent?alt=sse&key=gm-query'",
+            "",
+        )
+        .unwrap();
+
+        let env = &provider.settings_config["env"];
+        assert_eq!(env["GEMINI_API_KEY"], "gm-query");
+        assert_eq!(env["GEMINI_MODEL"], "gemini-2.5-flash");
+    }
+
+    #[test]
+    fn additive_app_curls_build_openai_compatible_config() {
+        let curl = r#"curl https://relay.example/v1/chat/completions -H "Authorization: Bearer sk-oc" -d '{"model":"qwen3-coder"}'"#;
+
+        let opencode = build_provider_from_curl(&AppType::OpenCode, curl, "OC").unwrap();
+        assert_eq!(
+            opencode.settings_config["options"]["baseURL"],
+            "https://relay.example/v1"
+        );
+        assert_eq!(opencode.settings_config["options"]["apiKey"], "sk-oc");
+        assert!(opencode.settings_config["models"]["qwen3-coder"].is_object());
+
+        let openclaw = build_provider_from_curl(&AppType::OpenClaw, curl, "OC").unwrap();
+        assert_eq!(
+            openclaw.settings_config["baseUrl"],
+            "https://relay.example/v1"
+        );
+        assert_eq!(openclaw.settings_config["models"][0]["id"], "qwen3-coder");
+    }
+
+    #[test]
+    fn curl_without_url_or_credential_is_rejected() {
+        assert!(build_provider_from_curl(&AppType::Claude, "curl -H 'x-api-key: sk'", "").is_err());
+        assert!(build_provider_from_curl(
+            &AppType::Claude,
+            "curl https://relay.example/v1/messages",
+            ""
+        )
+        .is_err());
+        assert!(
+            build_provider_from_curl(&AppType::Claude, "wget https://relay.example", "").is_err()
+        );
+    }
+}
@@ -5,10 +5,14 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::app_config::AppType;
+use crate::database::{EndpointLatencySample, ENDPOINT_LATENCY_HISTORY_LIMIT};
 use crate::error::AppError;
+use crate::services::speedtest::EndpointLatency;
 use crate::settings::CustomEndpoint;
 use crate::store::AppState;
 
+use super::ProviderService;
+
 /// Get custom endpoints list for a provider
 pub fn get_custom_endpoints(
     state: &AppState,
@@ -38,6 +42,7 @@ pub fn add_custom_endpoint(
     provider_id: &str,
     url: String,
 ) -> Result<(), AppError> {
+    ProviderService::ensure_not_frozen(state, &app_type, provider_id)?;
     let normalized = url.trim().trim_end_matches('/').to_string();
     if normalized.is_empty() {
         return Err(AppError::localized(
@@ -60,6 +65,7 @@ pub fn remove_custom_endpoint(
     provider_id: &str,
     url: String,
 ) -> Result<(), AppError> {
+    ProviderService::ensure_not_frozen(state, &app_type, provider_id)?;
     let normalized = url.trim().trim_end_matches('/').to_string();
     state
         .db
@@ -89,6 +95,61 @@ pub fn update_endpoint_last_used(
     Ok(())
 }
 
+/// Append speedtest results to the endpoint latency history
+///
+/// Any reachable endpoint also counts as a successful verification of the provider.
+pub fn record_endpoint_latencies(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    results: &[EndpointLatency],
+) -> Result<(), AppError> {
+    let tested_at = now_millis();
+    for result in results {
+        let url = normalize_url(&result.url);
+        if url.is_empty() {
+            continue;
+        }
+        let sample = EndpointLatencySample {
+            latency_ms: result.latency.map(|v| u64::try_from(v).unwrap_or(u64::MAX)),
+            status: result.status,
+            tested_at,
+        };
+        state
+            .db
+            .append_endpoint_latency(app_type.as_str(), provider_id, &url, &sample)?;
+    }
+    if results.iter().any(|result| result.latency.is_some()) {
+        state.db.set_provider_last_verified(
+            app_type.as_str(),
+            provider_id,
+            chrono::Utc::now().timestamp(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Get the most recent latency samples of an endpoint (oldest first)
+pub fn endpoint_latency_trend(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    url: &str,
+    limit: usize,
+) -> Result<Vec<EndpointLatencySample>, AppError> {
+    let limit = limit.clamp(1, ENDPOINT_LATENCY_HISTORY_LIMIT);
+    state.db.get_endpoint_latency_history(
+        app_type.as_str(),
+        provider_id,
+        &normalize_url(url),
+        limit,
+    )
+}
+
+fn normalize_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
 /// Get current timestamp in milliseconds
 fn now_millis() -> i64 {
     SystemTime::now()
@@ -0,0 +1,241 @@
+//! Import providers from dotenv files
+//!
+//! 将用户已有的 `.env` 文件映射为各应用的供应商配置。
+
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::gemini_config::parse_env_file;
+use crate::provider::{CodexModelConfig, Provider, UniversalProvider};
+
+/// Codex 能识别的环境变量（其余键无法映射到 auth.json / config.toml）
+const CODEX_API_KEY_KEYS: &[&str] = &["OPENAI_API_KEY", "CODEX_API_KEY"];
+const CODEX_BASE_URL_KEYS: &[&str] = &["OPENAI_BASE_URL", "OPENAI_API_BASE"];
+const CODEX_MODEL_KEYS: &[&str] = &["OPENAI_MODEL", "CODEX_MODEL"];
+const CODEX_DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// 解析 dotenv 内容
+///
+/// 在 [`parse_env_file`] 的基础上兼容 `export KEY=...` 前缀和成对的引号。
+pub(crate) fn parse_dotenv(content: &str) -> HashMap<String, String> {
+    let normalized = content
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            trimmed.strip_prefix("export ").unwrap_or(trimmed)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    parse_env_file(&normalized)
+        .into_iter()
+        .map(|(key, value)| (key, unquote(&value).to_string()))
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+fn first_value<'a>(env: &'a HashMap<String, String>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .filter_map(|key| env.get(*key))
+        .map(|value| value.trim())
+        .find(|value| !value.is_empty())
+}
+
+/// 将 env 键值映射为指定应用的供应商（未校验、未持久化）
+pub(crate) fn build_provider_from_env(
+    app_type: &AppType,
+    env: &HashMap<String, String>,
+    name: &str,
+) -> Result<Provider, AppError> {
+    let id = uuid::Uuid::new_v4().to_string();
+
+    // 排序后写入，保证生成的配置稳定
+    let mut sorted: Vec<(&String, &String)> = env.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    let env_object: Map<String, Value> = sorted
+        .into_iter()
+        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+        .collect();
+
+    match app_type {
+        AppType::Claude => {
+            if first_value(env, &["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"]).is_none() {
+                return Err(AppError::localized(
+                    "provider.env_import.missing_key",
+                    ".env 文件缺少 ANTHROPIC_AUTH_TOKEN 或 ANTHROPIC_API_KEY",
+                    ".env file is missing ANTHROPIC_AUTH_TOKEN or ANTHROPIC_API_KEY",
+                ));
+            }
+            Ok(Provider::with_id(
+                id,
+                name.to_string(),
+                json!({ "env": env_object }),
+                None,
+            ))
+        }
+        AppType::Gemini => Ok(Provider::with_id(
+            id,
+            name.to_string(),
+            json!({ "env": env_object, "config": {} }),
+            None,
+        )),
+        AppType::Codex => {
+            let api_key = first_value(env, CODEX_API_KEY_KEYS).ok_or_else(|| {
+                AppError::localized(
+                    "provider.env_import.missing_key",
+                    ".env 文件缺少 OPENAI_API_KEY",
+                    ".env file is missing OPENAI_API_KEY",
+                )
+            })?;
+            let base_url = first_value(env, CODEX_BASE_URL_KEYS).unwrap_or(CODEX_DEFAULT_BASE_URL);
+
+            let mut dropped: Vec<&str> = env
+                .keys()
+                .map(String::as_str)
+                .filter(|key| {
+                    !CODEX_API_KEY_KEYS.contains(key)
+                        && !CODEX_BASE_URL_KEYS.contains(key)
+                        && !CODEX_MODEL_KEYS.contains(key)
+                })
+                .collect();
+            if !dropped.is_empty() {
+                dropped.sort_unstable();
+                log::warn!(
+                    "Codex 不支持任意环境变量，导入时已忽略: {}",
+                    dropped.join(", ")
+                );
+            }
+
+            let mut universal = UniversalProvider::new(
+                id,
+                name.to_string(),
+                "custom".to_string(),
+                base_url.to_string(),
+                api_key.to_string(),
+            );
+            universal.apps.codex = true;
+            if let Some(model) = first_value(env, CODEX_MODEL_KEYS) {
+                universal.models.codex = Some(CodexModelConfig {
+                    model: Some(model.to_string()),
+                    reasoning_effort: None,
+                });
+            }
+
+            let mut provider = universal.to_codex_provider().ok_or_else(|| {
+                AppError::Message("Failed to build codex provider config".to_string())
+            })?;
+            provider.id = universal.id;
+            provider.category = None;
+            Ok(provider)
+        }
+        AppType::OpenCode | AppType::OpenClaw => Err(AppError::localized(
+            "provider.env_import.unsupported_app",
+            format!("{} 不支持从 .env 文件导入", app_type.as_str()),
+            format!(
+                "Importing from a .env file is not supported for {}",
+                app_type.as_str()
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dotenv_handles_export_quotes_and_comments() {
+        let env = parse_dotenv(
+            "# comment\nexport ANTHROPIC_AUTH_TOKEN=\"sk-ant\"\nANTHROPIC_BASE_URL='https://relay.example'\n\nEMPTY=\n",
+        );
+        assert_eq!(
+            env.get("ANTHROPIC_AUTH_TOKEN").map(String::as_str),
+            Some("sk-ant")
+        );
+        assert_eq!(
+            env.get("ANTHROPIC_BASE_URL").map(String::as_str),
+            Some("https://relay.example")
+        );
+        assert_eq!(env.get("EMPTY").map(String::as_str), Some(""));
+    }
+
+    #[test]
+    fn claude_env_keeps_unknown_keys() {
+        let env = parse_dotenv(
+            "ANTHROPIC_AUTH_TOKEN=sk-ant\nANTHROPIC_BASE_URL=https://relay.example\nDISABLE_TELEMETRY=1\n",
+        );
+        let provider = build_provider_from_env(&AppType::Claude, &env, "Relay").unwrap();
+
+        let settings_env = &provider.settings_config["env"];
+        assert_eq!(settings_env["ANTHROPIC_AUTH_TOKEN"], "sk-ant");
+        assert_eq!(settings_env["ANTHROPIC_BASE_URL"], "https://relay.example");
+        assert_eq!(settings_env["DISABLE_TELEMETRY"], "1");
+        assert_eq!(provider.name, "Relay");
+    }
+
+    #[test]
+    fn claude_env_requires_credentials() {
+        let env = parse_dotenv("ANTHROPIC_BASE_URL=https://relay.example\n");
+        assert!(build_provider_from_env(&AppType::Claude, &env, "Relay").is_err());
+    }
+
+    #[test]
+    fn gemini_env_keeps_unknown_keys() {
+        let env = parse_dotenv(
+            "GEMINI_API_KEY=gm-key\nGOOGLE_GEMINI_BASE_URL=https://gemini.example\nHTTPS_PROXY=http://127.0.0.1:7890\n",
+        );
+        let provider = build_provider_from_env(&AppType::Gemini, &env, "Gemini Relay").unwrap();
+
+        let settings_env = &provider.settings_config["env"];
+        assert_eq!(settings_env["GEMINI_API_KEY"], "gm-key");
+        assert_eq!(
+            settings_env["GOOGLE_GEMINI_BASE_URL"],
+            "https://gemini.example"
+        );
+        assert_eq!(settings_env["HTTPS_PROXY"], "http://127.0.0.1:7890");
+        assert!(provider.settings_config["config"].is_object());
+    }
+
+    #[test]
+    fn codex_env_maps_known_keys_and_drops_others() {
+        let env = parse_dotenv(
+            "OPENAI_API_KEY=sk-openai\nOPENAI_BASE_URL=https://relay.example/v1\nOPENAI_MODEL=gpt-5-codex\nHTTPS_PROXY=http://127.0.0.1:7890\n",
+        );
+        let provider = build_provider_from_env(&AppType::Codex, &env, "Codex Relay").unwrap();
+
+        assert_eq!(
+            provider.settings_config["auth"]["OPENAI_API_KEY"],
+            "sk-openai"
+        );
+        let config = provider.settings_config["config"].as_str().unwrap();
+        assert!(config.contains("base_url = \"https://relay.example/v1\""));
+        assert!(config.contains("model = \"gpt-5-codex\""));
+        assert!(!config.contains("HTTPS_PROXY"));
+        assert!(!provider.id.starts_with("universal-"));
+    }
+
+    #[test]
+    fn codex_env_defaults_to_openai_endpoint() {
+        let env = parse_dotenv("OPENAI_API_KEY=sk-openai\n");
+        let provider = build_provider_from_env(&AppType::Codex, &env, "OpenAI").unwrap();
+        let config = provider.settings_config["config"].as_str().unwrap();
+        assert!(config.contains("base_url = \"https://api.openai.com/v1\""));
+    }
+
+    #[test]
+    fn additive_apps_are_rejected() {
+        let env = parse_dotenv("OPENAI_API_KEY=sk-openai\n");
+        assert!(build_provider_from_env(&AppType::OpenCode, &env, "x").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Provider lint checks
+//!
+//! 保存前检查供应商配置中的常见错误（只给出提示，不阻止保存）。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app_config::AppType;
+use crate::codex_config::resolve_codex_base_url;
+use crate::error::AppError;
+use crate::provider::Provider;
+
+/// 提示级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    /// 很可能导致请求失败
+    Warning,
+    /// 可能是笔误，但也可能是有意为之
+    Info,
+}
+
+/// 单条检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintWarning {
+    pub code: String,
+    pub severity: LintSeverity,
+    /// 中英文提示，格式与 `AppError::localized` 的错误信息一致
+    pub message: String,
+    /// 对应的配置字段（如 `env.ANTHROPIC_BASE_URL`）
+    pub field: String,
+}
+
+fn lint(
+    code: &'static str,
+    severity: LintSeverity,
+    zh: impl Into<String>,
+    en: impl Into<String>,
+    field: &str,
+) -> LintWarning {
+    LintWarning {
+        code: code.to_string(),
+        severity,
+        message: AppError::localized(code, zh, en).to_string(),
+        field: field.to_string(),
+    }
+}
+
+/// 检查指定应用的供应商配置
+pub(crate) fn lint_provider(app_type: &AppType, provider: &Provider) -> Vec<LintWarning> {
+    let settings = &provider.settings_config;
+    let mut warnings = Vec::new();
+
+    match app_type {
+        AppType::Claude => {
+            let env = settings.get("env");
+            if let Some(url) = env_str(env, "ANTHROPIC_BASE_URL") {
+                lint_base_url(url, "env.ANTHROPIC_BASE_URL", true, &mut warnings);
+            }
+            for key in ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"] {
+                if let Some(token) = env_str(env, key) {
+                    lint_token(token, &format!("env.{key}"), &mut warnings);
+                }
+            }
+            lint_claude_bearer_only(provider, &mut warnings);
+        }
+        AppType::Codex => {
+            if let Some(token) = settings
+                .get("auth")
+                .and_then(|auth| auth.get("OPENAI_API_KEY"))
+                .and_then(Value::as_str)
+            {
+                lint_token(token, "auth.OPENAI_API_KEY", &mut warnings);
+            }
+            if let Some(url) = settings
+                .get("config")
+                .and_then(Value::as_str)
+                .and_then(|config| resolve_codex_base_url(config).ok())
+            {
+                // Codex 的 base_url 本身就应包含 /v1
+                lint_base_url(&url, "config.base_url", false, &mut warnings);
+            }
+        }
+        AppType::Gemini => {
+            let env = settings.get("env");
+            if let Some(url) = env_str(env, "GOOGLE_GEMINI_BASE_URL") {
+                lint_base_url(url, "env.GOOGLE_GEMINI_BASE_URL", true, &mut warnings);
+            }
+            if let Some(token) = env_str(env, "GEMINI_API_KEY") {
+                lint_token(token, "env.GEMINI_API_KEY", &mut warnings);
+            }
+        }
+        AppType::OpenCode | AppType::OpenClaw => {
+            let (url_key, key_key) = match app_type {
+                AppType::OpenCode => ("options.baseURL", "options.apiKey"),
+                _ => ("baseUrl", "apiKey"),
+            };
+            if let Some(url) = json_path_str(settings, url_key) {
+                lint_base_url(url, url_key, false, &mut warnings);
+            }
+            if let Some(token) = json_path_str(settings, key_key) {
+                lint_token(token, key_key, &mut warnings);
+            }
+        }
+    }
+
+    warnings
+}
+
+fn env_str<'a>(env: Option<&'a Value>, key: &str) -> Option<&'a str> {
+    env?.get(key)?.as_str()
+}
+
+fn json_path_str<'a>(value: &'a Value, path: &str) -> Option<&'a str> {
+    path.split('.')
+        .try_fold(value, |current, segment| current.get(segment))?
+        .as_str()
+}
+
+/// base_url：缺少协议头、首尾空白、客户端会自行拼接 /v1 时重复的版本后缀
+fn lint_base_url(url: &str, field: &str, client_appends_version: bool, out: &mut Vec<LintWarning>) {
+    if url.trim().is_empty() {
+        return;
+    }
+
+    if url.trim() != url {
+        out.push(lint(
+            "base_url_whitespace",
+            LintSeverity::Warning,
+            "Base URL 首尾包含空白字符",
+            "Base URL has leading or trailing whitespace.",
+            field,
+        ));
+    }
+
+    let trimmed = url.trim();
+    if !trimmed.starts_with("http://") && !trimmed.starts_with("https://") {
+        out.push(lint(
+            "base_url_missing_scheme",
+            LintSeverity::Warning,
+            format!("Base URL '{trimmed}' 缺少 http:// 或 https:// 协议头"),
+            format!("Base URL '{trimmed}' is missing the http:// or https:// scheme."),
+            field,
+        ));
+    }
+
+    if client_appends_version {
+        let path = trimmed.trim_end_matches('/');
+        if path.ends_with("/v1") || path.ends_with("/v1beta") {
+            out.push(lint(
+                "base_url_duplicate_version",
+                LintSeverity::Warning,
+                format!("Base URL '{trimmed}' 以 API 版本结尾，客户端会再次拼接，产生 /v1/v1 这样的路径"),
+                format!(
+                    "Base URL '{trimmed}' ends with an API version; the client appends it again, \
+                     producing paths like /v1/v1."
+                ),
+                field,
+            ));
+        }
+    }
+}
+
+/// token：首尾空白会被原样放进请求头，导致认证失败
+fn lint_token(token: &str, field: &str, out: &mut Vec<LintWarning>) {
+    if !token.is_empty() && token.trim() != token {
+        out.push(lint(
+            "token_whitespace",
+            LintSeverity::Warning,
+            "API Key 首尾包含空白字符",
+            "API key has leading or trailing whitespace.",
+            field,
+        ));
+    }
+}
+
+/// 仅接受 Bearer 认证的中转站不会读取 `x-api-key`，需要改用 ANTHROPIC_AUTH_TOKEN
+fn lint_claude_bearer_only(provider: &Provider, out: &mut Vec<LintWarning>) {
+    let settings = &provider.settings_config;
+    let env = settings.get("env");
+    let bearer_only = settings.get("auth_mode").and_then(Value::as_str) == Some("bearer_only")
+        || env_str(env, "AUTH_MODE") == Some("bearer_only");
+    if !bearer_only {
+        return;
+    }
+
+    let uses_api_key = env_str(env, "ANTHROPIC_API_KEY").is_some()
+        && env_str(env, "ANTHROPIC_AUTH_TOKEN").is_none();
+    let api_key_field = provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.api_key_field.as_deref());
+    if uses_api_key || api_key_field == Some("ANTHROPIC_API_KEY") {
+        out.push(lint(
+            "api_key_with_bearer_only",
+            LintSeverity::Warning,
+            "该中转站仅接受 Bearer 认证，请使用 ANTHROPIC_AUTH_TOKEN 代替 ANTHROPIC_API_KEY",
+            "This relay only accepts Bearer auth; use ANTHROPIC_AUTH_TOKEN instead of \
+             ANTHROPIC_API_KEY.",
+            "env.ANTHROPIC_API_KEY",
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider(settings: Value) -> Provider {
+        Provider::with_id("p".to_string(), "P".to_string(), settings, None)
+    }
+
+    fn codes(warnings: &[LintWarning]) -> Vec<&str> {
+        warnings.iter().map(|w| w.code.as_str()).collect()
+    }
+
+    #[test]
+    fn clean_claude_provider_has_no_warnings() {
+        let p = provider(json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "https://relay.example",
+                "ANTHROPIC_AUTH_TOKEN": "sk-ant"
+            }
+        }));
+        assert!(lint_provider(&AppType::Claude, &p).is_empty());
+    }
+
+    #[test]
+    fn base_url_without_scheme_is_flagged() {
+        let p = provider(json!({ "env": { "ANTHROPIC_BASE_URL": "relay.example" } }));
+        let warnings = lint_provider(&AppType::Claude, &p);
+        assert_eq!(codes(&warnings), vec!["base_url_missing_scheme"]);
+        assert_eq!(warnings[0].field, "env.ANTHROPIC_BASE_URL");
+        assert_eq!(warnings[0].severity, LintSeverity::Warning);
+        assert!(warnings[0]
+            .message
+            .contains("缺少 http:// 或 https:// 协议头"));
+        assert!(warnings[0]
+            .message
+            .contains("is missing the http:// or https:// scheme"));
+    }
+
+    #[test]
+    fn trailing_v1_is_flagged_only_when_client_appends_version() {
+        let claude =
+            provider(json!({ "env": { "ANTHROPIC_BASE_URL": "https://relay.example/v1/" } }));
+        assert_eq!(
+            codes(&lint_provider(&AppType::Claude, &claude)),
+            vec!["base_url_duplicate_version"]
+        );
+
+        let codex = provider(json!({
+            "auth": { "OPENAI_API_KEY": "sk" },
+            "config": "model_provider = \"relay\"\n[model_providers.relay]\nbase_url = \"https://relay.example/v1\"\n"
+        }));
+        assert!(lint_provider(&AppType::Codex, &codex).is_empty());
+    }
+
+    #[test]
+    fn codex_base_url_without_scheme_is_flagged() {
+        let codex = provider(json!({
+            "auth": { "OPENAI_API_KEY": "sk" },
+            "config": "base_url = \"relay.example/v1\"\n"
+        }));
+        let warnings = lint_provider(&AppType::Codex, &codex);
+        assert_eq!(codes(&warnings), vec!["base_url_missing_scheme"]);
+        assert_eq!(warnings[0].field, "config.base_url");
+    }
+
+    #[test]
+    fn token_with_whitespace_is_flagged() {
+        let claude = provider(json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-ant \n" } }));
+        let warnings = lint_provider(&AppType::Claude, &claude);
+        assert_eq!(codes(&warnings), vec!["token_whitespace"]);
+        assert_eq!(warnings[0].field, "env.ANTHROPIC_AUTH_TOKEN");
+
+        let gemini = provider(json!({ "env": { "GEMINI_API_KEY": " gm-key" } }));
+        assert_eq!(
+            codes(&lint_provider(&AppType::Gemini, &gemini)),
+            vec!["token_whitespace"]
+        );
+    }
+
+    #[test]
+    fn api_key_with_bearer_only_relay_is_flagged() {
+        let p = provider(json!({
+            "auth_mode": "bearer_only",
+            "env": { "ANTHROPIC_API_KEY": "sk-relay" }
+        }));
+        let warnings = lint_provider(&AppType::Claude, &p);
+        assert_eq!(codes(&warnings), vec!["api_key_with_bearer_only"]);
+
+        let mut via_meta = provider(json!({
+            "env": { "AUTH_MODE": "bearer_only", "ANTHROPIC_AUTH_TOKEN": "sk-relay" }
+        }));
+        via_meta.meta = Some(ProviderMeta {
+            api_key_field: Some("ANTHROPIC_API_KEY".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            codes(&lint_provider(&AppType::Claude, &via_meta)),
+            vec!["api_key_with_bearer_only"]
+        );
+
+        let bearer = provider(json!({
+            "auth_mode": "bearer_only",
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk-relay" }
+        }));
+        assert!(lint_provider(&AppType::Claude, &bearer).is_empty());
+    }
+}
@@ -3,8 +3,11 @@
 //! Handles reading and writing live configuration files for Claude, Codex, and Gemini.
 
 use std::collections::HashMap;
+use std::path::Path;
 
+use serde::Serialize;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use toml_edit::{DocumentMut, Item, TableLike};
 
 use crate::app_config::AppType;
@@ -406,7 +409,7 @@ pub(crate) fn remove_common_config_from_settings(
     }
 }
 
-fn apply_common_config_to_settings(
+pub(super) fn apply_common_config_to_settings(
     app_type: &AppType,
     settings: &Value,
     snippet: &str,
@@ -496,7 +499,111 @@ pub(crate) fn write_live_with_common_config(
     effective_provider.settings_config =
         build_effective_settings_with_common_config(db, app_type, provider)?;
 
-    write_live_snapshot(app_type, &effective_provider)
+    write_live_snapshot(app_type, &effective_provider)?;
+
+    // 指纹记录失败不影响写入本身，只会让后续的手动修改检测失效
+    if let Err(e) = record_live_fingerprint(db, app_type) {
+        log::warn!("记录 {} live 配置指纹失败: {e}", app_type.as_str());
+    }
+    Ok(())
+}
+
+fn live_fingerprint_key(app_type: &AppType) -> String {
+    format!("live_fingerprint_{}", app_type.as_str())
+}
+
+fn read_optional_text(path: &Path) -> Result<Option<String>, AppError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| AppError::io(path, e))
+}
+
+/// 规范化 JSON 文本，可选地忽略某个顶层字段；无法解析时原样返回
+fn normalize_json_text(text: &str, ignored_key: Option<&str>) -> String {
+    match serde_json::from_str::<Value>(text) {
+        Ok(mut value) => {
+            if let (Some(key), Some(obj)) = (ignored_key, value.as_object_mut()) {
+                obj.remove(key);
+            }
+            value.to_string()
+        }
+        Err(_) => text.to_string(),
+    }
+}
+
+/// 计算 live 配置的指纹
+///
+/// 仅覆盖切换供应商时会被整体覆盖的文件（Claude / Codex / Gemini），
+/// 并忽略由 MCP 同步维护的字段，避免 MCP 同步被误判为手动修改。
+/// 文件均不存在或为累加模式应用时返回 `None`。
+pub(crate) fn live_fingerprint(app_type: &AppType) -> Result<Option<String>, AppError> {
+    let parts: Vec<Option<String>> = match app_type {
+        AppType::Claude => {
+            let settings = read_optional_text(&get_claude_settings_path())?;
+            vec![settings.map(|text| normalize_json_text(&text, None))]
+        }
+        AppType::Codex => {
+            let auth = read_optional_text(&get_codex_auth_path())?;
+            let config = read_optional_text(&get_codex_config_path())?;
+            vec![
+                auth.map(|text| normalize_json_text(&text, None)),
+                config.map(|text| match text.parse::<DocumentMut>() {
+                    Ok(mut doc) => {
+                        doc.remove("mcp_servers");
+                        doc.to_string()
+                    }
+                    Err(_) => text,
+                }),
+            ]
+        }
+        AppType::Gemini => {
+            use crate::gemini_config::{get_gemini_env_path, get_gemini_settings_path};
+            let env = read_optional_text(&get_gemini_env_path())?;
+            let settings = read_optional_text(&get_gemini_settings_path())?;
+            vec![
+                env,
+                settings.map(|text| normalize_json_text(&text, Some("mcpServers"))),
+            ]
+        }
+        AppType::OpenCode | AppType::OpenClaw => return Ok(None),
+    };
+
+    if parts.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+
+    let mut hasher = Sha256::new();
+    for part in &parts {
+        // 用分隔符区分“文件缺失”和“文件为空”
+        match part {
+            Some(text) => {
+                hasher.update(b"1:");
+                hasher.update(text.as_bytes());
+            }
+            None => hasher.update(b"0:"),
+        }
+        hasher.update(b"\n");
+    }
+    Ok(Some(format!("{:x}", hasher.finalize())))
+}
+
+/// 记录 CC Switch 最近一次写入后的 live 配置指纹
+pub(crate) fn record_live_fingerprint(db: &Database, app_type: &AppType) -> Result<(), AppError> {
+    if let Some(fingerprint) = live_fingerprint(app_type)? {
+        db.set_setting(&live_fingerprint_key(app_type), &fingerprint)?;
+    }
+    Ok(())
+}
+
+/// 读取最近一次记录的 live 配置指纹
+pub(crate) fn recorded_live_fingerprint(
+    db: &Database,
+    app_type: &AppType,
+) -> Result<Option<String>, AppError> {
+    db.get_setting(&live_fingerprint_key(app_type))
 }
 
 pub(crate) fn strip_common_config_from_live_settings(
@@ -648,6 +755,105 @@ impl LiveSnapshot {
     }
 }
 
+/// 原生配置文件（文件名 + 内容）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeFile {
+    pub file_name: String,
+    pub content: String,
+}
+
+/// 供应商配置的原生格式导出（可直接放入目标 CLI 的配置目录）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeExport {
+    pub app_type: String,
+    pub provider_id: String,
+    pub files: Vec<NativeFile>,
+}
+
+/// Codex 供应商配置中的 auth.json 与 config.toml 内容
+fn codex_live_parts(provider: &Provider) -> Result<(&Value, &str), AppError> {
+    let obj = provider
+        .settings_config
+        .as_object()
+        .ok_or_else(|| AppError::Config("Codex 供应商配置必须是 JSON 对象".to_string()))?;
+    let auth = obj
+        .get("auth")
+        .ok_or_else(|| AppError::Config("Codex 供应商配置缺少 'auth' 字段".to_string()))?;
+    let config_str = obj.get("config").and_then(|v| v.as_str()).ok_or_else(|| {
+        AppError::Config("Codex 供应商配置缺少 'config' 字段或不是字符串".to_string())
+    })?;
+    Ok((auth, config_str))
+}
+
+/// Gemini 写入 .env 的环境变量（Google 官方使用 OAuth，不写入任何变量）
+fn gemini_live_env(
+    provider: &Provider,
+    auth_type: GeminiAuthType,
+) -> Result<HashMap<String, String>, AppError> {
+    use crate::gemini_config::{json_to_env, validate_gemini_settings_strict};
+
+    let mut env_map = json_to_env(&provider.settings_config)?;
+    match auth_type {
+        GeminiAuthType::GoogleOfficial => env_map.clear(),
+        // PackyCode 与通用供应商使用 API Key（切换时严格校验）
+        GeminiAuthType::Packycode | GeminiAuthType::Generic => {
+            validate_gemini_settings_strict(&provider.settings_config)?;
+        }
+    }
+    Ok(env_map)
+}
+
+fn to_pretty_json(value: &Value) -> Result<String, AppError> {
+    serde_json::to_string_pretty(value).map_err(|e| AppError::JsonSerialize { source: e })
+}
+
+/// 渲染供应商写入 Live 时的文件内容（与 [`write_live_snapshot`] 写盘格式一致，但不写入磁盘）
+///
+/// 仅支持独占配置文件的应用；OpenCode / OpenClaw 为累加模式，多个供应商共享同一配置文件。
+pub(crate) fn render_live_files(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Vec<NativeFile>, AppError> {
+    let file = |name: &str, content: String| NativeFile {
+        file_name: name.to_string(),
+        content,
+    };
+
+    match app_type {
+        AppType::Claude => {
+            let settings = sanitize_claude_settings_for_live(&provider.settings_config);
+            Ok(vec![file("settings.json", to_pretty_json(&settings)?)])
+        }
+        AppType::Codex => {
+            let (auth, config_str) = codex_live_parts(provider)?;
+            Ok(vec![
+                file("auth.json", to_pretty_json(auth)?),
+                file("config.toml", config_str.to_string()),
+            ])
+        }
+        AppType::Gemini => {
+            let env_map = gemini_live_env(provider, detect_gemini_auth_type(provider))?;
+            Ok(vec![file(
+                ".env",
+                crate::gemini_config::serialize_env_file(&env_map),
+            )])
+        }
+        AppType::OpenCode | AppType::OpenClaw => Err(AppError::localized(
+            "provider.export_native.unsupported_app",
+            format!(
+                "{} 的供应商共享同一配置文件，不支持原生格式导出",
+                app_type.as_str()
+            ),
+            format!(
+                "{} providers share one config file; native export is not supported",
+                app_type.as_str()
+            ),
+        )),
+    }
+}
+
 /// Write live configuration snapshot for a provider
 pub(crate) fn write_live_snapshot(app_type: &AppType, provider: &Provider) -> Result<(), AppError> {
     match app_type {
@@ -657,16 +863,7 @@ pub(crate) fn write_live_snapshot(app_type: &AppType, provider: &Provider) -> Re
             write_json_file(&path, &settings)?;
         }
         AppType::Codex => {
-            let obj = provider
-                .settings_config
-                .as_object()
-                .ok_or_else(|| AppError::Config("Codex 供应商配置必须是 JSON 对象".to_string()))?;
-            let auth = obj
-                .get("auth")
-                .ok_or_else(|| AppError::Config("Codex 供应商配置缺少 'auth' 字段".to_string()))?;
-            let config_str = obj.get("config").and_then(|v| v.as_str()).ok_or_else(|| {
-                AppError::Config("Codex 供应商配置缺少 'config' 字段或不是字符串".to_string())
-            })?;
+            let (auth, config_str) = codex_live_parts(provider)?;
 
             let auth_path = get_codex_auth_path();
             write_json_file(&auth_path, auth)?;
@@ -986,6 +1183,100 @@ pub fn import_default_config(state: &AppState, app_type: AppType) -> Result<bool
         }
     }
 
+    let settings_config = read_live_settings_for_import(&app_type)?;
+
+    let mut provider = Provider::with_id(
+        "default".to_string(),
+        "default".to_string(),
+        settings_config,
+        None,
+    );
+    provider.category = Some("custom".to_string());
+
+    state.db.save_provider(app_type.as_str(), &provider)?;
+    state
+        .db
+        .set_current_provider(app_type.as_str(), &provider.id)?;
+
+    Ok(true) // 真正导入了
+}
+
+/// Adopt the current live config as a new provider
+///
+/// 以当前 live 配置创建供应商（新 ID，名称为空时使用 "default"）并设为当前供应商。
+/// live 文件本身就是该供应商的内容，因此不会重写，只记录指纹供后续的手动修改检测使用。
+/// 代理接管期间 live 中是代理占位配置，此时拒绝采用。
+pub fn adopt_current_live(
+    state: &AppState,
+    app_type: AppType,
+    name: &str,
+) -> Result<Provider, AppError> {
+    if app_type.is_additive_mode() {
+        return Err(AppError::localized(
+            "provider.adopt.additive_unsupported",
+            format!("{} 为累加模式，请使用从 Live 导入供应商", app_type.as_str()),
+            format!(
+                "{} uses additive mode; import providers from live instead",
+                app_type.as_str()
+            ),
+        ));
+    }
+
+    let has_live_backup = futures::executor::block_on(state.db.get_live_backup(app_type.as_str()))
+        .ok()
+        .flatten()
+        .is_some();
+    if has_live_backup
+        || state
+            .proxy_service
+            .detect_takeover_in_live_config_for_app(&app_type)
+    {
+        return Err(AppError::localized(
+            "provider.adopt.takeover_active",
+            format!(
+                "{} 的 Live 配置正被代理接管，请先关闭接管再采用",
+                app_type.as_str()
+            ),
+            format!(
+                "The {} live config is taken over by the proxy; disable takeover before adopting it",
+                app_type.as_str()
+            ),
+        ));
+    }
+
+    let settings_config = read_live_settings_for_import(&app_type)?;
+
+    let name = name.trim();
+    let name = if name.is_empty() { "default" } else { name };
+    let mut provider = Provider::with_id(
+        uuid::Uuid::new_v4().to_string(),
+        name.to_string(),
+        settings_config,
+        None,
+    );
+    provider.category = Some("custom".to_string());
+
+    state.db.save_provider(app_type.as_str(), &provider)?;
+    state
+        .db
+        .set_current_provider(app_type.as_str(), &provider.id)?;
+    crate::settings::set_current_provider(&app_type, Some(&provider.id))?;
+
+    if let Err(e) = record_live_fingerprint(state.db.as_ref(), &app_type) {
+        log::warn!("记录 {} live 配置指纹失败: {e}", app_type.as_str());
+    }
+
+    log::info!(
+        "[{}] 已将当前 live 配置采用为供应商 '{}' ({})",
+        app_type.as_str(),
+        provider.name,
+        provider.id
+    );
+    Ok(provider)
+}
+
+/// 读取 live 配置并转换为供应商的 `settings_config`（仅支持非累加模式应用）
+fn read_live_settings_for_import(app_type: &AppType) -> Result<Value, AppError> {
     let settings_config = match app_type {
         AppType::Codex => {
             let auth_path = get_codex_auth_path();
@@ -1046,40 +1337,21 @@ pub fn import_default_config(state: &AppState, app_type: AppType) -> Result<bool
                 "config": config_obj
             })
         }
-        // OpenCode and OpenClaw use additive mode and are handled by early return above
+        // OpenCode and OpenClaw use additive mode and are handled by the callers
         AppType::OpenCode | AppType::OpenClaw => {
             unreachable!("additive mode apps are handled by early return")
         }
     };
-
-    let mut provider = Provider::with_id(
-        "default".to_string(),
-        "default".to_string(),
-        settings_config,
-        None,
-    );
-    provider.category = Some("custom".to_string());
-
-    state.db.save_provider(app_type.as_str(), &provider)?;
-    state
-        .db
-        .set_current_provider(app_type.as_str(), &provider.id)?;
-
-    Ok(true) // 真正导入了
+    Ok(settings_config)
 }
 
 /// Write Gemini live configuration with authentication handling
 pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
-    use crate::gemini_config::{
-        get_gemini_settings_path, json_to_env, validate_gemini_settings_strict,
-        write_gemini_env_atomic,
-    };
+    use crate::gemini_config::{get_gemini_settings_path, write_gemini_env_atomic};
 
     // One-time auth type detection to avoid repeated detection
     let auth_type = detect_gemini_auth_type(provider);
 
-    let mut env_map = json_to_env(&provider.settings_config)?;
-
     // Prepare config to write to ~/.gemini/settings.json
     // Behavior:
     // - config is object: use it (merge with existing to preserve mcpServers etc.)
@@ -1120,23 +1392,8 @@ pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
         config_to_write = Some(read_json_file(&settings_path)?);
     }
 
-    match auth_type {
-        GeminiAuthType::GoogleOfficial => {
-            // Google official uses OAuth, clear env
-            env_map.clear();
-            write_gemini_env_atomic(&env_map)?;
-        }
-        GeminiAuthType::Packycode => {
-            // PackyCode provider, uses API Key (strict validation on switch)
-            validate_gemini_settings_strict(&provider.settings_config)?;
-            write_gemini_env_atomic(&env_map)?;
-        }
-        GeminiAuthType::Generic => {
-            // Generic provider, uses API Key (strict validation on switch)
-            validate_gemini_settings_strict(&provider.settings_config)?;
-            write_gemini_env_atomic(&env_map)?;
-        }
-    }
+    let env_map = gemini_live_env(provider, auth_type)?;
+    write_gemini_env_atomic(&env_map)?;
 
     if let Some(config_value) = config_to_write {
         write_json_file(&settings_path, &config_value)?;
@@ -1315,6 +1572,110 @@ pub fn remove_openclaw_provider_from_live(provider_id: &str) -> Result<(), AppEr
 mod tests {
     use super::*;
     use serde_json::json;
+    use serial_test::serial;
+    use std::path::PathBuf;
+
+    /// 在临时 HOME 中执行，结束后恢复环境变量
+    fn with_temp_home<T>(test: impl FnOnce() -> T) -> T {
+        let temp = tempfile::tempdir().unwrap();
+        let old_test_home = std::env::var_os("CC_SWITCH_TEST_HOME");
+        std::env::set_var("CC_SWITCH_TEST_HOME", temp.path());
+        let result = test();
+        match old_test_home {
+            Some(value) => std::env::set_var("CC_SWITCH_TEST_HOME", value),
+            None => std::env::remove_var("CC_SWITCH_TEST_HOME"),
+        }
+        result
+    }
+
+    /// 写入 Live 后逐个比较磁盘文件与导出内容
+    fn assert_export_matches_live(
+        app_type: AppType,
+        provider: &Provider,
+        live_paths: impl Fn() -> Vec<PathBuf>,
+    ) {
+        with_temp_home(|| {
+            write_live_snapshot(&app_type, provider).expect("write live");
+            let files = render_live_files(&app_type, provider).expect("render native files");
+            let paths = live_paths();
+            assert_eq!(files.len(), paths.len());
+            for (file, path) in files.iter().zip(&paths) {
+                assert_eq!(
+                    path.file_name().and_then(|n| n.to_str()),
+                    Some(file.file_name.as_str())
+                );
+                let on_disk = std::fs::read_to_string(path).expect("read live file");
+                assert_eq!(file.content, on_disk, "{} differs", file.file_name);
+            }
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn native_export_matches_claude_live_write() {
+        let provider = Provider::with_id(
+            "claude".to_string(),
+            "Claude".to_string(),
+            json!({
+                "env": { "ANTHROPIC_AUTH_TOKEN": "sk-ant", "ANTHROPIC_BASE_URL": "https://relay.example" },
+                "api_format": "anthropic",
+                "permissions": { "allow": [] }
+            }),
+            None,
+        );
+        assert_export_matches_live(AppType::Claude, &provider, || {
+            vec![get_claude_settings_path()]
+        });
+
+        let files = render_live_files(&AppType::Claude, &provider).unwrap();
+        assert!(
+            !files[0].content.contains("api_format"),
+            "internal fields must be stripped"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn native_export_matches_codex_live_write() {
+        let provider = Provider::with_id(
+            "codex".to_string(),
+            "Codex".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-codex" },
+                "config": "model_provider = \"relay\"\nmodel = \"gpt-5\"\n\n[model_providers.relay]\nbase_url = \"https://relay.example/v1\"\n"
+            }),
+            None,
+        );
+        assert_export_matches_live(AppType::Codex, &provider, || {
+            vec![get_codex_auth_path(), get_codex_config_path()]
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn native_export_matches_gemini_live_write() {
+        let provider = Provider::with_id(
+            "gemini".to_string(),
+            "Gemini".to_string(),
+            json!({
+                "env": {
+                    "GEMINI_API_KEY": "gm-key",
+                    "GOOGLE_GEMINI_BASE_URL": "https://gemini.example",
+                    "GEMINI_MODEL": "gemini-2.5-pro"
+                }
+            }),
+            None,
+        );
+        assert_export_matches_live(AppType::Gemini, &provider, || {
+            vec![crate::gemini_config::get_gemini_env_path()]
+        });
+    }
+
+    #[test]
+    fn native_export_rejects_additive_apps() {
+        let provider = Provider::with_id("oc".to_string(), "OC".to_string(), json!({}), None);
+        assert!(render_live_files(&AppType::OpenCode, &provider).is_err());
+    }
 
     #[test]
     fn claude_common_config_apply_and_remove_roundtrip_for_non_overlapping_fields() {
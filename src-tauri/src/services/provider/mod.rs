@@ -2,43 +2,71 @@
 //!
 //! Handles provider CRUD operations, switching, and configuration management.
 
+mod capabilities;
+mod common_broadcast;
+mod common_preview;
+mod curl_import;
 mod endpoints;
+mod env_import;
 mod gemini_auth;
+mod lint;
 mod live;
+mod notes;
+mod official;
+mod share;
+mod throughput;
+mod token_freshness;
+mod url_normalize;
 mod usage;
+mod warm_up;
 
 use indexmap::IndexMap;
-use regex::Regex;
 use serde::Deserialize;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::Path;
 
 use crate::app_config::AppType;
+use crate::database::ProviderHistoryEntry;
 use crate::error::AppError;
-use crate::provider::{Provider, UsageResult};
+use crate::provider::{Provider, ProviderMeta, UsageResult};
 use crate::services::mcp::McpService;
 use crate::settings::CustomEndpoint;
 use crate::store::AppState;
 
 // Re-export sub-module functions for external access
+pub use common_preview::FieldDiff;
+pub use lint::{LintSeverity, LintWarning};
 pub use live::{
-    import_default_config, import_openclaw_providers_from_live,
-    import_opencode_providers_from_live, read_live_settings, sync_current_to_live,
+    adopt_current_live, import_default_config, import_openclaw_providers_from_live,
+    import_opencode_providers_from_live, read_live_settings, sync_current_to_live, NativeExport,
+    NativeFile,
 };
+pub use token_freshness::TokenFreshness;
+pub use url_normalize::UrlChange;
 
 // Internal re-exports (pub(crate))
 pub(crate) use live::sanitize_claude_settings_for_live;
 pub(crate) use live::{
-    build_effective_settings_with_common_config, normalize_provider_common_config_for_storage,
-    strip_common_config_from_live_settings, sync_current_provider_for_app_to_live,
-    write_live_with_common_config,
+    build_effective_settings_with_common_config, live_fingerprint,
+    normalize_provider_common_config_for_storage, record_live_fingerprint,
+    recorded_live_fingerprint, strip_common_config_from_live_settings,
+    sync_current_provider_for_app_to_live, write_live_with_common_config,
 };
 
 // Internal re-exports
 use live::{
-    remove_openclaw_provider_from_live, remove_opencode_provider_from_live, write_gemini_live,
+    remove_openclaw_provider_from_live, remove_opencode_provider_from_live, render_live_files,
+    write_gemini_live,
 };
 use usage::validate_usage_script;
 
+/// 切换事件的切换方式：写入 Live 配置
+const SWITCH_MODE_LIVE: &str = "live";
+/// 切换事件的切换方式：代理接管下热切换（不写 Live）
+const SWITCH_MODE_HOT: &str = "hot_switch";
+
 /// Provider business logic service
 pub struct ProviderService;
 
@@ -49,6 +77,48 @@ pub struct SwitchResult {
     pub warnings: Vec<String>,
 }
 
+/// Result of a merge-mode provider import
+#[derive(Debug, serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderImportResult {
+    /// 新增的供应商 ID
+    pub imported: Vec<String>,
+    /// 因 ID 已存在或内容重复而跳过的供应商 ID
+    pub skipped: Vec<String>,
+}
+
+/// Result of batch Claude model key normalization
+#[derive(Debug, serde::Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelNormalizeResult {
+    /// 已规范化的供应商数量
+    pub changed: usize,
+    /// 需要规范化但已冻结而跳过的供应商 ID
+    pub frozen: Vec<String>,
+}
+
+/// Result of broadcasting common config to other apps
+#[derive(Debug, serde::Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastResult {
+    /// 已更新当前供应商的目标应用
+    pub updated: Vec<String>,
+    /// 当前供应商已冻结而跳过的目标应用
+    pub frozen: Vec<String>,
+    /// 没有环境变量配置段（如 Codex）而跳过的目标应用
+    pub unsupported: Vec<String>,
+}
+
+/// A provider that has not been verified recently
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleProvider {
+    pub provider_id: String,
+    pub name: String,
+    /// 最近一次验证成功的时间（Unix 秒），从未验证为 None
+    pub last_verified_at: Option<i64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,6 +159,55 @@ mod tests {
         assert_eq!(base_url, "https://claude.example");
     }
 
+    #[test]
+    fn extract_gemini_common_config_handles_non_string_env_per_mode() {
+        let settings = json!({
+            "env": {
+                "GEMINI_API_KEY": "secret",
+                "GOOGLE_GEMINI_BASE_URL": "https://gemini.example",
+                "GEMINI_MODEL": " gemini-2.5-pro ",
+                "GEMINI_TIMEOUT": 30,
+                "GEMINI_SANDBOX": true,
+                "GEMINI_EXTRA": { "region": "us" },
+                "GEMINI_UNSET": null
+            }
+        });
+
+        let default_snippet = ProviderService::extract_gemini_common_config(&settings, false)
+            .expect("extract string-only snippet");
+        let default_value: Value =
+            serde_json::from_str(&default_snippet).expect("string-only snippet is json");
+        assert_eq!(default_value, json!({ "GEMINI_MODEL": "gemini-2.5-pro" }));
+
+        let preserved_snippet = ProviderService::extract_gemini_common_config(&settings, true)
+            .expect("extract preserving snippet");
+        let preserved_value: Value =
+            serde_json::from_str(&preserved_snippet).expect("preserving snippet is json");
+        assert_eq!(
+            preserved_value,
+            json!({
+                "GEMINI_MODEL": "gemini-2.5-pro",
+                "GEMINI_TIMEOUT": "30",
+                "GEMINI_SANDBOX": "true",
+                "GEMINI_EXTRA": r#"{"region":"us"}"#
+            })
+        );
+
+        // 应用到其他供应商后写入 .env 时，保留的值不能被丢弃
+        let target = json!({ "env": { "GEMINI_API_KEY": "other" } });
+        let applied =
+            live::apply_common_config_to_settings(&AppType::Gemini, &target, &preserved_snippet)
+                .expect("apply preserving snippet");
+        let env = crate::gemini_config::json_to_env(&applied).expect("convert to env");
+        assert_eq!(env.get("GEMINI_API_KEY").map(String::as_str), Some("other"));
+        assert_eq!(env.get("GEMINI_TIMEOUT").map(String::as_str), Some("30"));
+        assert_eq!(env.get("GEMINI_SANDBOX").map(String::as_str), Some("true"));
+        assert_eq!(
+            env.get("GEMINI_EXTRA").map(String::as_str),
+            Some(r#"{"region":"us"}"#)
+        );
+    }
+
     #[test]
     fn extract_codex_common_config_preserves_mcp_servers_base_url() {
         let config_toml = r#"model_provider = "azure"
@@ -141,6 +260,40 @@ impl ProviderService {
         }
     }
 
+    /// Re-run Claude model key normalization for every stored Claude provider
+    ///
+    /// 旧版本添加的供应商可能仍使用 `ANTHROPIC_SMALL_FAST_MODEL`；有变化的供应商通过
+    /// [`ProviderService::update`] 保存（当前供应商同步写入 Live 配置），返回修改的数量。
+    /// 已冻结的供应商不修改，需要跳过明细时使用 [`ProviderService::normalize_all_claude_models_report`]。
+    pub fn normalize_all_claude_models(state: &AppState) -> Result<usize, AppError> {
+        Ok(Self::normalize_all_claude_models_report(state)?.changed)
+    }
+
+    /// Same as [`ProviderService::normalize_all_claude_models`], also listing skipped frozen providers
+    pub fn normalize_all_claude_models_report(
+        state: &AppState,
+    ) -> Result<ModelNormalizeResult, AppError> {
+        let providers = state.db.get_all_providers(AppType::Claude.as_str())?;
+        let mut result = ModelNormalizeResult::default();
+
+        for mut provider in providers.into_values() {
+            if !normalize_claude_models_in_value(&mut provider.settings_config) {
+                continue;
+            }
+            if provider.is_frozen() {
+                log::info!("Claude 供应商 {} 已冻结，跳过模型字段规范化", provider.id);
+                result.frozen.push(provider.id);
+                continue;
+            }
+            log::info!("规范化 Claude 供应商 {} 的模型字段", provider.id);
+            Self::update(state, AppType::Claude, provider)?;
+            result.changed += 1;
+        }
+
+        result.frozen.sort();
+        Ok(result)
+    }
+
     /// List all providers for an app type
     pub fn list(
         state: &AppState,
@@ -157,11 +310,23 @@ impl ProviderService {
     ///
     /// 对于累加模式应用（OpenCode, OpenClaw），不存在"当前供应商"概念，直接返回空字符串。
     pub fn current(state: &AppState, app_type: AppType) -> Result<String, AppError> {
+        Self::current_with(state, app_type, false)
+    }
+
+    /// Get current provider ID, optionally preferring a healthy failover alternative
+    ///
+    /// `prefer_healthy` 为 true 且当前供应商不健康时，返回故障转移队列中第一个健康的供应商，
+    /// 用于展示实际可用的供应商；只读，不会切换当前供应商。
+    pub fn current_with(
+        state: &AppState,
+        app_type: AppType,
+        prefer_healthy: bool,
+    ) -> Result<String, AppError> {
         // Additive mode apps have no "current" provider concept
         if app_type.is_additive_mode() {
             return Ok(String::new());
         }
-        crate::settings::get_effective_current_provider(&state.db, &app_type)
+        crate::settings::get_effective_current_provider_with(&state.db, &app_type, prefer_healthy)
             .map(|opt| opt.unwrap_or_default())
     }
 
@@ -171,6 +336,9 @@ impl ProviderService {
         // Normalize Claude model keys
         Self::normalize_provider_if_claude(&app_type, &mut provider);
         Self::validate_provider_settings(&app_type, &provider)?;
+        Self::ensure_single_safety_net(state, &app_type, &provider)?;
+        Self::log_lint_warnings(&app_type, &provider);
+        notes::prefill_notes(&app_type, &mut provider);
         normalize_provider_common_config_for_storage(state.db.as_ref(), &app_type, &mut provider)?;
 
         // Save to database
@@ -209,10 +377,13 @@ impl ProviderService {
         app_type: AppType,
         provider: Provider,
     ) -> Result<bool, AppError> {
+        Self::ensure_not_frozen(state, &app_type, &provider.id)?;
         let mut provider = provider;
         // Normalize Claude model keys
         Self::normalize_provider_if_claude(&app_type, &mut provider);
         Self::validate_provider_settings(&app_type, &provider)?;
+        Self::ensure_single_safety_net(state, &app_type, &provider)?;
+        Self::log_lint_warnings(&app_type, &provider);
         normalize_provider_common_config_for_storage(state.db.as_ref(), &app_type, &mut provider)?;
 
         // Save to database
@@ -281,7 +452,7 @@ impl ProviderService {
             } else {
                 write_live_with_common_config(state.db.as_ref(), &app_type, &provider)?;
                 // Sync MCP
-                McpService::sync_all_enabled(state)?;
+                Self::sync_mcp_unless_skipped(state, &provider)?;
             }
         }
 
@@ -293,6 +464,8 @@ impl ProviderService {
     /// 同时检查本地 settings 和数据库的当前供应商，防止删除任一端正在使用的供应商。
     /// 对于累加模式应用（OpenCode, OpenClaw），可以随时删除任意供应商，同时从 live 配置中移除。
     pub fn delete(state: &AppState, app_type: AppType, id: &str) -> Result<(), AppError> {
+        Self::ensure_not_frozen(state, &app_type, id)?;
+
         // Additive mode apps - no current provider concept
         if app_type.is_additive_mode() {
             if matches!(app_type, AppType::OpenCode) {
@@ -425,6 +598,53 @@ impl ProviderService {
         Ok(())
     }
 
+    /// 设置跨设备默认供应商（仅更新数据库 is_current）
+    ///
+    /// 与 [`Self::switch`] 的区别：switch 会更新本设备的 current_provider_xxx 并写入 Live 配置；
+    /// 此方法只修改数据库中的默认值，本设备已选择的供应商与 Live 文件保持不变。
+    /// 新设备（本地 settings 未记录当前供应商）首次同步后会使用这里设置的默认值。
+    pub fn set_default_provider(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+    ) -> Result<(), AppError> {
+        if app_type.is_additive_mode() {
+            return Err(AppError::localized(
+                "provider.default.unsupported_app",
+                format!(
+                    "{} 没有当前供应商的概念，无法设置默认供应商",
+                    app_type.as_str()
+                ),
+                format!(
+                    "{} has no current provider, so a default cannot be set",
+                    app_type.as_str()
+                ),
+            ));
+        }
+
+        let provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {id}"),
+                    format!("Provider not found: {id}"),
+                )
+            })?;
+        if !provider.enabled {
+            return Err(AppError::localized(
+                "provider.disabled",
+                format!("供应商 {id} 已禁用，请先启用后再设为默认"),
+                format!("Provider {id} is disabled; enable it before making it the default"),
+            ));
+        }
+
+        state.db.set_current_provider(app_type.as_str(), id)?;
+        log::info!("已将 {id} 设为 {} 的默认供应商", app_type.as_str());
+        Ok(())
+    }
+
     /// Switch to a provider
     ///
     /// Switch flow:
@@ -437,23 +657,60 @@ impl ProviderService {
     ///    c. Update database is_current (as default for new devices)
     ///    d. Write target provider config to live files
     ///    e. Sync MCP configuration
+    ///
+    /// 切换成功后记录一条切换事件（见 `Database::get_switch_events`），记录失败不影响切换结果。
     pub fn switch(state: &AppState, app_type: AppType, id: &str) -> Result<SwitchResult, AppError> {
+        let from_id = if app_type.is_additive_mode() {
+            None
+        } else {
+            crate::settings::get_effective_current_provider(&state.db, &app_type)
+                .ok()
+                .flatten()
+        };
+
+        let (result, mode) = Self::switch_inner(state, app_type.clone(), id)?;
+
+        if let Err(e) =
+            state
+                .db
+                .record_switch_event(app_type.as_str(), from_id.as_deref(), id, mode)
+        {
+            log::warn!("记录 {} 切换事件失败: {e}", app_type.as_str());
+        }
+        Ok(result)
+    }
+
+    /// 执行切换，返回结果与切换方式（`live` / `hot_switch`）
+    fn switch_inner(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+    ) -> Result<(SwitchResult, &'static str), AppError> {
         // Check if provider exists
         let providers = state.db.get_all_providers(app_type.as_str())?;
-        let _provider = providers
+        let provider = providers
             .get(id)
             .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
 
+        if !provider.enabled {
+            return Err(AppError::localized(
+                "provider.disabled",
+                format!("供应商 {id} 已禁用，请先启用后再切换"),
+                format!("Provider {id} is disabled; enable it before switching"),
+            ));
+        }
+
         // OMO providers are switched through their own exclusive path.
-        if matches!(app_type, AppType::OpenCode) && _provider.category.as_deref() == Some("omo") {
-            return Self::switch_normal(state, app_type, id, &providers);
+        if matches!(app_type, AppType::OpenCode) && provider.category.as_deref() == Some("omo") {
+            return Self::switch_normal(state, app_type, id, &providers)
+                .map(|result| (result, SWITCH_MODE_LIVE));
         }
 
         // OMO Slim providers are switched through their own exclusive path.
-        if matches!(app_type, AppType::OpenCode)
-            && _provider.category.as_deref() == Some("omo-slim")
+        if matches!(app_type, AppType::OpenCode) && provider.category.as_deref() == Some("omo-slim")
         {
-            return Self::switch_normal(state, app_type, id, &providers);
+            return Self::switch_normal(state, app_type, id, &providers)
+                .map(|result| (result, SWITCH_MODE_LIVE));
         }
 
         // Check if proxy takeover mode is active AND proxy server is actually running
@@ -480,11 +737,6 @@ impl ProviderService {
                 id
             );
 
-            // 获取新供应商的完整配置（用于更新备份）
-            let provider = providers
-                .get(id)
-                .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
-
             // Update database is_current
             state.db.set_current_provider(app_type.as_str(), id)?;
 
@@ -509,11 +761,12 @@ impl ProviderService {
 
             // Note: No Live config write, no MCP sync
             // The proxy server will route requests to the new provider via is_current
-            return Ok(SwitchResult::default());
+            return Ok((SwitchResult::default(), SWITCH_MODE_HOT));
         }
 
         // Normal mode: full switch with Live config write
         Self::switch_normal(state, app_type, id, &providers)
+            .map(|result| (result, SWITCH_MODE_LIVE))
     }
 
     /// Normal switch flow (non-proxy mode)
@@ -563,9 +816,14 @@ impl ProviderService {
                 // Additive mode apps - all providers coexist in the same file,
                 // no backfill needed (backfill is for exclusive mode apps like Claude/Codex/Gemini)
                 if !app_type.is_additive_mode() {
-                    // Only backfill when switching to a different provider
+                    // Only backfill when switching to a different provider;
+                    // frozen providers keep their stored config
                     if let Ok(live_config) = read_live_settings(app_type.clone()) {
-                        if let Some(mut current_provider) = providers.get(&current_id).cloned() {
+                        if let Some(mut current_provider) = providers
+                            .get(&current_id)
+                            .filter(|provider| !provider.is_frozen())
+                            .cloned()
+                        {
                             current_provider.settings_config =
                                 strip_common_config_from_live_settings(
                                     state.db.as_ref(),
@@ -600,16 +858,626 @@ impl ProviderService {
         write_live_with_common_config(state.db.as_ref(), &app_type, provider)?;
 
         // Sync MCP
-        McpService::sync_all_enabled(state)?;
+        Self::sync_mcp_unless_skipped(state, provider)?;
 
         Ok(result)
     }
 
+    /// 同步 MCP 配置，供应商设置了 `meta.skipMcpSync` 时跳过（用户手动管理 MCP）
+    fn sync_mcp_unless_skipped(state: &AppState, provider: &Provider) -> Result<(), AppError> {
+        let skip = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.skip_mcp_sync)
+            .unwrap_or(false);
+        if skip {
+            log::info!("供应商 {} 已设置跳过 MCP 同步", provider.name);
+            return Ok(());
+        }
+        McpService::sync_all_enabled(state)
+    }
+
+    /// Switch to a provider, then open a terminal running the CLI
+    ///
+    /// 切换成功但终端启动失败时返回错误，并在错误信息中说明切换已完成，
+    /// 避免用户误以为仍在使用旧供应商。终端启动在阻塞线程池中执行。
+    pub async fn switch_and_launch(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        terminal_target: String,
+        launch_command: String,
+        cwd: Option<String>,
+        custom_config: Option<String>,
+    ) -> Result<(), AppError> {
+        Self::switch_for_launch(state, app_type, provider_id)?;
+
+        tauri::async_runtime::spawn_blocking(move || {
+            crate::session_manager::terminal::launch_terminal(
+                &terminal_target,
+                &launch_command,
+                cwd.as_deref(),
+                custom_config.as_deref(),
+            )
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result)
+        .map_err(|e| Self::launch_failed(provider_id, e))
+    }
+
+    /// Same as [`Self::switch_and_launch`], with the terminal launch step injected
+    pub fn switch_and_launch_with<F>(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        launch_command: &str,
+        cwd: Option<&str>,
+        launch: F,
+    ) -> Result<(), AppError>
+    where
+        F: FnOnce(&str, Option<&str>) -> Result<(), String>,
+    {
+        Self::switch_for_launch(state, app_type, provider_id)?;
+        launch(launch_command, cwd).map_err(|e| Self::launch_failed(provider_id, e))
+    }
+
+    fn switch_for_launch(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<(), AppError> {
+        let result = Self::switch(state, app_type.clone(), provider_id)?;
+        for warning in &result.warnings {
+            log::warn!(
+                "Switch {} to '{provider_id}' completed with warning: {warning}",
+                app_type.as_str()
+            );
+        }
+        Ok(())
+    }
+
+    fn launch_failed(provider_id: &str, e: String) -> AppError {
+        AppError::localized(
+            "provider.switch_and_launch.launch_failed",
+            format!("已切换到供应商 {provider_id}，但启动终端失败: {e}"),
+            format!("Switched to provider {provider_id}, but failed to launch terminal: {e}"),
+        )
+    }
+
+    /// Enable or disable a provider
+    ///
+    /// 禁用的供应商保留配置，但不会被代理路由与故障转移选中，也不能被切换为当前供应商。
+    /// 启用状态与切换一样属于路由选择而非配置编辑，冻结的供应商同样可以启用或禁用。
+    pub fn set_enabled(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        if !state
+            .db
+            .set_provider_enabled(app_type.as_str(), id, enabled)?
+        {
+            return Err(AppError::localized(
+                "provider.not_found",
+                format!("供应商不存在: {id}"),
+                format!("Provider not found: {id}"),
+            ));
+        }
+        log::info!(
+            "供应商 {id} ({}) 已{}",
+            app_type.as_str(),
+            if enabled { "启用" } else { "禁用" }
+        );
+        Ok(())
+    }
+
+    /// Freeze or unfreeze a provider
+    ///
+    /// 冻结的供应商拒绝编辑（含从历史恢复、批量规范化、自定义端点增删）与删除，直到解除冻结；
+    /// 切换、启用/禁用与代理路由不受影响。
+    pub fn set_frozen(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        frozen: bool,
+    ) -> Result<(), AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {id}"),
+                    format!("Provider not found: {id}"),
+                )
+            })?;
+        provider
+            .meta
+            .get_or_insert_with(ProviderMeta::default)
+            .frozen = frozen.then_some(true);
+        state.db.save_provider(app_type.as_str(), &provider)?;
+        log::info!(
+            "供应商 {id} ({}) 已{}",
+            app_type.as_str(),
+            if frozen { "冻结" } else { "解除冻结" }
+        );
+        Ok(())
+    }
+
+    /// 已冻结的供应商拒绝修改
+    pub(super) fn ensure_not_frozen(
+        state: &AppState,
+        app_type: &AppType,
+        id: &str,
+    ) -> Result<(), AppError> {
+        let frozen = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .is_some_and(|p| p.is_frozen());
+        if frozen {
+            return Err(AppError::localized(
+                "provider.frozen",
+                format!("供应商 {id} 已冻结，请先解除冻结再修改"),
+                format!("Provider {id} is frozen; unfreeze it before making changes"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build a provider from a dotenv file
+    ///
+    /// 识别的键映射到对应应用的配置结构；Claude / Gemini 支持任意环境变量，未知键原样保留，
+    /// Codex 仅保留 API Key、base_url 与模型，其余键会被丢弃并记录警告。
+    /// 返回的供应商已通过校验但尚未保存，名称为空时使用文件名。
+    pub fn import_from_env_file(
+        app_type: AppType,
+        path: &Path,
+        name: &str,
+    ) -> Result<Provider, AppError> {
+        let content = std::fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+        let env = env_import::parse_dotenv(&content);
+
+        let name = name.trim();
+        let name = if name.is_empty() {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| ".env".to_string())
+        } else {
+            name.to_string()
+        };
+
+        let mut provider = env_import::build_provider_from_env(&app_type, &env, &name)?;
+        Self::normalize_provider_if_claude(&app_type, &mut provider);
+        Self::validate_provider_settings(&app_type, &provider)?;
+        Ok(provider)
+    }
+
+    /// Build a provider from a curl command
+    ///
+    /// 从 URL 推断 base_url，从 `x-api-key` / `Authorization: Bearer` / `x-goog-api-key`
+    /// 请求头提取凭证，请求体或路径中的模型一并带入。
+    /// 返回的供应商已通过校验但尚未保存，名称为空时使用请求地址的主机名。
+    pub fn import_from_curl(
+        app_type: AppType,
+        curl: &str,
+        name: &str,
+    ) -> Result<Provider, AppError> {
+        let mut provider = curl_import::build_provider_from_curl(&app_type, curl, name)?;
+        Self::normalize_provider_if_claude(&app_type, &mut provider);
+        Self::validate_provider_settings(&app_type, &provider)?;
+        Ok(provider)
+    }
+
+    /// Import a set of providers in merge mode
+    ///
+    /// 已存在的 ID 不覆盖；ID 不同但内容相同（按 app_type + base_url + token 哈希判断）的供应商
+    /// 也视为重复并跳过，因此重复导入同一份供应商包不会产生副本。无法提取凭证的供应商仅按 ID 去重。
+    pub fn import_merge(
+        state: &AppState,
+        app_type: AppType,
+        providers: Vec<Provider>,
+    ) -> Result<ProviderImportResult, AppError> {
+        let existing = state.db.get_all_providers(app_type.as_str())?;
+        let mut seen_keys: HashSet<String> = existing
+            .values()
+            .filter_map(|p| Self::content_key(&app_type, p))
+            .collect();
+
+        let mut result = ProviderImportResult::default();
+        for provider in providers {
+            if existing.contains_key(&provider.id) || result.imported.contains(&provider.id) {
+                log::debug!("导入跳过已存在的供应商: {}", provider.id);
+                result.skipped.push(provider.id);
+                continue;
+            }
+            if let Some(key) = Self::content_key(&app_type, &provider) {
+                if !seen_keys.insert(key) {
+                    log::info!("导入跳过内容重复的供应商: {}", provider.id);
+                    result.skipped.push(provider.id);
+                    continue;
+                }
+            }
+
+            let id = provider.id.clone();
+            Self::add(state, app_type.clone(), provider)?;
+            result.imported.push(id);
+        }
+
+        log::info!(
+            "[{}] 合并导入供应商: 新增 {} 个，跳过 {} 个",
+            app_type.as_str(),
+            result.imported.len(),
+            result.skipped.len()
+        );
+        Ok(result)
+    }
+
+    /// List providers not verified within `max_age`
+    ///
+    /// 健康检查成功会刷新 `last_verified_at`；超过 `max_age` 未验证（或从未验证）的供应商
+    /// 视为过期，提示用户重新测试（中转站可能已轮换密钥）。按列表顺序返回。
+    pub fn stale_providers(
+        state: &AppState,
+        app_type: AppType,
+        max_age: std::time::Duration,
+    ) -> Result<Vec<StaleProvider>, AppError> {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let verified = state.db.get_providers_last_verified(app_type.as_str())?;
+        let cutoff = chrono::Utc::now().timestamp() - max_age.as_secs() as i64;
+
+        Ok(providers
+            .into_values()
+            .filter_map(|provider| {
+                let last_verified_at = verified.get(&provider.id).copied().flatten();
+                if last_verified_at.is_some_and(|ts| ts >= cutoff) {
+                    return None;
+                }
+                Some(StaleProvider {
+                    provider_id: provider.id,
+                    name: provider.name,
+                    last_verified_at,
+                })
+            })
+            .collect())
+    }
+
+    /// Content identity of a provider: app_type + normalized base_url + token hash
+    fn content_key(app_type: &AppType, provider: &Provider) -> Option<String> {
+        let (api_key, base_url) = Self::extract_credentials(provider, app_type).ok()?;
+        let api_key = api_key.trim();
+        if api_key.is_empty() {
+            return None;
+        }
+        let token_hash = format!("{:x}", Sha256::digest(api_key.as_bytes()));
+        let base_url = base_url.trim().trim_end_matches('/').to_ascii_lowercase();
+        Some(format!("{}|{base_url}|{token_hash}", app_type.as_str()))
+    }
+
+    /// Check a provider for likely misconfigurations
+    ///
+    /// 仅返回提示（缺少协议头的 base_url、重复的 /v1、带空白的 token、Bearer-only 中转站
+    /// 使用 ANTHROPIC_API_KEY 等），不影响保存。
+    pub fn lint_provider(app_type: &AppType, provider: &Provider) -> Vec<LintWarning> {
+        lint::lint_provider(app_type, provider)
+    }
+
+    /// Canonicalize a Codex config.toml for the editor
+    ///
+    /// 统一键值间距、顶层键按字母序排列，保留注释与表顺序；语法错误时返回带行列号的错误。
+    pub fn format_codex_config(config_text: &str) -> Result<String, AppError> {
+        crate::codex_config::format_codex_config_toml(config_text)
+    }
+
+    fn log_lint_warnings(app_type: &AppType, provider: &Provider) {
+        for warning in Self::lint_provider(app_type, provider) {
+            log::warn!(
+                "[{}] 供应商 {} 配置可能有误 ({}): {}",
+                app_type.as_str(),
+                provider.id,
+                warning.field,
+                warning.message
+            );
+        }
+    }
+
+    /// List configuration history snapshots of a provider (newest first)
+    pub fn list_history(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+    ) -> Result<Vec<ProviderHistoryEntry>, AppError> {
+        state.db.get_provider_history(app_type.as_str(), id)
+    }
+
+    /// Restore a provider's settings_config from a history snapshot
+    ///
+    /// `history_index` 对应 [`Self::list_history`] 返回列表的下标（0 为最近一次编辑前的配置）。
+    /// 恢复走与编辑相同的 [`Self::update`] 流程：重新校验配置，若为当前供应商则同步 live 配置；
+    /// 恢复前的配置同样会被记录为一条历史，因此撤销本身也可以再撤销。
+    pub fn revert_to(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        history_index: usize,
+    ) -> Result<Provider, AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {id}"),
+                    format!("Provider not found: {id}"),
+                )
+            })?;
+
+        let history = state.db.get_provider_history(app_type.as_str(), id)?;
+        let entry = history.into_iter().nth(history_index).ok_or_else(|| {
+            AppError::localized(
+                "provider.history.not_found",
+                format!("历史记录不存在: {id} #{history_index}"),
+                format!("History entry not found: {id} #{history_index}"),
+            )
+        })?;
+
+        provider.settings_config = entry.settings_config;
+        Self::update(state, app_type, provider.clone())?;
+        Ok(provider)
+    }
+
+    /// Broadcast the shared part of the current provider's common config to other apps
+    ///
+    /// 从源应用当前供应商的通用配置中提取与厂商无关的环境变量（代理、遥测开关等），
+    /// 合并到目标应用当前供应商的 env 中；凭证、端点、模型以及厂商前缀的变量不会传播。
+    /// Codex 的配置没有环境变量段、累加模式应用没有当前供应商，这些目标会被跳过并列入 `unsupported`；
+    /// 当前供应商已冻结的目标不修改，在结果中单独列出。
+    pub fn broadcast_common_config(
+        state: &AppState,
+        from_app: AppType,
+        to_apps: Vec<AppType>,
+    ) -> Result<BroadcastResult, AppError> {
+        let snippet = Self::extract_common_config_snippet(state, from_app.clone(), false)?;
+        let shared = common_broadcast::shared_env_from_snippet(&from_app, &snippet)?;
+        let mut result = BroadcastResult::default();
+        if shared.is_empty() {
+            log::info!("{} 的通用配置中没有可共享的字段", from_app.as_str());
+            return Ok(result);
+        }
+
+        for to_app in to_apps {
+            if to_app == from_app {
+                continue;
+            }
+            if !common_broadcast::accepts_shared_env(&to_app) {
+                log::warn!(
+                    "跳过通用配置广播目标 {}：无对应的环境变量配置",
+                    to_app.as_str()
+                );
+                result.unsupported.push(to_app.as_str().to_string());
+                continue;
+            }
+
+            let current_id = Self::current(state, to_app.clone())?;
+            let Some(mut provider) = state.db.get_provider_by_id(&current_id, to_app.as_str())?
+            else {
+                log::info!("{} 没有当前供应商，跳过通用配置广播", to_app.as_str());
+                continue;
+            };
+
+            if !common_broadcast::merge_shared_env(&mut provider.settings_config, &shared) {
+                continue;
+            }
+            if provider.is_frozen() {
+                log::info!(
+                    "{} 当前供应商 {current_id} 已冻结，跳过通用配置广播",
+                    to_app.as_str()
+                );
+                result.frozen.push(to_app.as_str().to_string());
+                continue;
+            }
+            Self::update(state, to_app.clone(), provider)?;
+            log::info!(
+                "已将 {} 的通用配置（{} 项）广播到 {} 当前供应商 {current_id}",
+                from_app.as_str(),
+                shared.len(),
+                to_app.as_str()
+            );
+            result.updated.push(to_app.as_str().to_string());
+        }
+
+        Ok(result)
+    }
+
+    /// Copy a provider into another app type
+    ///
+    /// 仅迁移 base_url 与 API Key（Claude env ↔ Codex auth + config.toml ↔ Gemini env），
+    /// 模型等无法跨应用映射的字段会被丢弃并记录警告。新供应商保存到目标应用后返回。
+    pub fn convert_provider(
+        state: &AppState,
+        from_app: AppType,
+        to_app: AppType,
+        provider_id: &str,
+        new_name: &str,
+    ) -> Result<Provider, AppError> {
+        for app in [&from_app, &to_app] {
+            if !matches!(app, AppType::Claude | AppType::Codex | AppType::Gemini) {
+                return Err(AppError::localized(
+                    "provider.convert.unsupported_app",
+                    format!("不支持转换 {} 应用的供应商", app.as_str()),
+                    format!("Provider conversion is not supported for {}", app.as_str()),
+                ));
+            }
+        }
+        if from_app == to_app {
+            return Err(AppError::localized(
+                "provider.convert.same_app",
+                "源应用与目标应用相同，无需转换",
+                "Source and target app are the same, nothing to convert",
+            ));
+        }
+
+        let source = state
+            .db
+            .get_provider_by_id(provider_id, from_app.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let (api_key, base_url) = Self::extract_credentials(&source, &from_app)?;
+        for field in Self::unmapped_fields(&source, &from_app) {
+            log::warn!(
+                "Converting provider '{provider_id}' from {} to {}: dropped unmappable field '{field}'",
+                from_app.as_str(),
+                to_app.as_str()
+            );
+        }
+
+        // Codex 地址通常带 /v1 版本前缀，Claude / Gemini 客户端会自行拼接路径
+        let base_url = base_url.trim_end_matches('/');
+        let base_url = if from_app == AppType::Codex {
+            base_url.strip_suffix("/v1").unwrap_or(base_url)
+        } else {
+            base_url
+        };
+
+        let name = new_name.trim();
+        let name = if name.is_empty() {
+            source.name.as_str()
+        } else {
+            name
+        };
+
+        let mut universal = UniversalProvider::new(
+            uuid::Uuid::new_v4().to_string(),
+            name.to_string(),
+            "custom".to_string(),
+            base_url.to_string(),
+            api_key,
+        );
+        let converted = match to_app {
+            AppType::Claude => {
+                universal.apps.claude = true;
+                universal.to_claude_provider()
+            }
+            AppType::Codex => {
+                universal.apps.codex = true;
+                universal.to_codex_provider()
+            }
+            _ => {
+                universal.apps.gemini = true;
+                universal.to_gemini_provider()
+            }
+        };
+        let Some(mut provider) = converted else {
+            return Err(AppError::Message(format!(
+                "Failed to build {} provider config",
+                to_app.as_str()
+            )));
+        };
+
+        provider.id = universal.id;
+        provider.category = source.category.clone();
+        provider.website_url = source.website_url.clone();
+        provider.notes = source.notes.clone();
+        provider.icon = source.icon.clone();
+        provider.icon_color = source.icon_color.clone();
+        provider.sort_index = None;
+
+        Self::add(state, to_app, provider.clone())?;
+        Ok(provider)
+    }
+
+    /// 列出转换时无法映射到其它应用的配置字段
+    fn unmapped_fields(provider: &Provider, app_type: &AppType) -> Vec<String> {
+        let settings = &provider.settings_config;
+        let object_keys = |value: Option<&Value>, keep: &[&str], prefix: &str| -> Vec<String> {
+            value
+                .and_then(|v| v.as_object())
+                .map(|obj| {
+                    obj.keys()
+                        .filter(|k| !keep.contains(&k.as_str()))
+                        .map(|k| format!("{prefix}{k}"))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        match app_type {
+            AppType::Claude => {
+                let mut fields = object_keys(Some(settings), &["env"], "");
+                fields.extend(object_keys(
+                    settings.get("env"),
+                    &[
+                        "ANTHROPIC_AUTH_TOKEN",
+                        "ANTHROPIC_API_KEY",
+                        "ANTHROPIC_BASE_URL",
+                    ],
+                    "env.",
+                ));
+                fields
+            }
+            AppType::Codex => {
+                let mut fields = object_keys(Some(settings), &["auth", "config"], "");
+                fields.extend(object_keys(
+                    settings.get("auth"),
+                    &["OPENAI_API_KEY"],
+                    "auth.",
+                ));
+                if let Some(doc) = settings
+                    .get("config")
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| s.parse::<toml_edit::DocumentMut>().ok())
+                {
+                    fields.extend(
+                        doc.iter()
+                            .map(|(k, _)| k)
+                            .filter(|k| !matches!(*k, "model_provider" | "model_providers"))
+                            .map(|k| format!("config.{k}")),
+                    );
+                }
+                fields
+            }
+            AppType::Gemini => {
+                let mut fields = object_keys(Some(settings), &["env"], "");
+                fields.extend(object_keys(
+                    settings.get("env"),
+                    &["GEMINI_API_KEY", "GOOGLE_GEMINI_BASE_URL"],
+                    "env.",
+                ));
+                fields
+            }
+            AppType::OpenCode | AppType::OpenClaw => Vec::new(),
+        }
+    }
+
     /// Sync current provider to live configuration (re-export)
     pub fn sync_current_to_live(state: &AppState) -> Result<(), AppError> {
         sync_current_to_live(state)
     }
 
+    /// Check whether the live config was modified outside CC Switch
+    ///
+    /// 比较 live 文件当前指纹与 CC Switch 最近一次写入时记录的指纹，供 UI 在切换覆盖前提示。
+    /// 从未记录过指纹、live 文件缺失或累加模式应用均返回 `false`。
+    pub fn live_has_unmanaged_changes(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<bool, AppError> {
+        if app_type.is_additive_mode() {
+            return Ok(false);
+        }
+        let Some(recorded) = recorded_live_fingerprint(state.db.as_ref(), &app_type)? else {
+            return Ok(false);
+        };
+        Ok(live_fingerprint(&app_type)?.is_some_and(|current| current != recorded))
+    }
+
     pub fn sync_current_provider_for_app(
         state: &AppState,
         app_type: AppType,
@@ -734,9 +1602,13 @@ impl ProviderService {
     ///
     /// Extracts the current provider's configuration and removes provider-specific fields
     /// (API keys, model settings, endpoints) to create a reusable common config snippet.
+    ///
+    /// `preserve_non_string_env` only affects Gemini: non-string env values are kept as
+    /// their JSON text instead of being dropped.
     pub fn extract_common_config_snippet(
         state: &AppState,
         app_type: AppType,
+        preserve_non_string_env: bool,
     ) -> Result<String, AppError> {
         // Get current provider
         let current_id = Self::current(state, app_type.clone())?;
@@ -752,7 +1624,10 @@ impl ProviderService {
         match app_type {
             AppType::Claude => Self::extract_claude_common_config(&provider.settings_config),
             AppType::Codex => Self::extract_codex_common_config(&provider.settings_config),
-            AppType::Gemini => Self::extract_gemini_common_config(&provider.settings_config),
+            AppType::Gemini => Self::extract_gemini_common_config(
+                &provider.settings_config,
+                preserve_non_string_env,
+            ),
             AppType::OpenCode => Self::extract_opencode_common_config(&provider.settings_config),
             AppType::OpenClaw => Self::extract_openclaw_common_config(&provider.settings_config),
         }
@@ -762,11 +1637,14 @@ impl ProviderService {
     pub fn extract_common_config_snippet_from_settings(
         app_type: AppType,
         settings_config: &Value,
+        preserve_non_string_env: bool,
     ) -> Result<String, AppError> {
         match app_type {
             AppType::Claude => Self::extract_claude_common_config(settings_config),
             AppType::Codex => Self::extract_codex_common_config(settings_config),
-            AppType::Gemini => Self::extract_gemini_common_config(settings_config),
+            AppType::Gemini => {
+                Self::extract_gemini_common_config(settings_config, preserve_non_string_env)
+            }
             AppType::OpenCode => Self::extract_opencode_common_config(settings_config),
             AppType::OpenClaw => Self::extract_openclaw_common_config(settings_config),
         }
@@ -875,7 +1753,14 @@ impl ProviderService {
     /// Extracts `.env` values while excluding provider-specific credentials:
     /// - GOOGLE_GEMINI_BASE_URL
     /// - GEMINI_API_KEY
-    fn extract_gemini_common_config(settings: &Value) -> Result<String, AppError> {
+    ///
+    /// Non-string values are skipped by default; with `preserve_non_string` they are
+    /// stored as their JSON text (null values are still skipped), since `.env` only
+    /// holds strings and non-string values would be dropped when writing live config.
+    fn extract_gemini_common_config(
+        settings: &Value,
+        preserve_non_string: bool,
+    ) -> Result<String, AppError> {
         let env = settings.get("env").and_then(|v| v.as_object());
 
         let mut snippet = serde_json::Map::new();
@@ -884,12 +1769,18 @@ impl ProviderService {
                 if key == "GOOGLE_GEMINI_BASE_URL" || key == "GEMINI_API_KEY" {
                     continue;
                 }
-                let Value::String(v) = value else {
-                    continue;
-                };
-                let trimmed = v.trim();
-                if !trimmed.is_empty() {
-                    snippet.insert(key.to_string(), Value::String(trimmed.to_string()));
+                match value {
+                    Value::String(v) => {
+                        let trimmed = v.trim();
+                        if !trimmed.is_empty() {
+                            snippet.insert(key.to_string(), Value::String(trimmed.to_string()));
+                        }
+                    }
+                    Value::Null => {}
+                    other if preserve_non_string => {
+                        snippet.insert(key.to_string(), Value::String(other.to_string()));
+                    }
+                    _ => {}
                 }
             }
         }
@@ -953,11 +1844,61 @@ impl ProviderService {
         import_default_config(state, app_type)
     }
 
+    /// Adopt the current live config as the current provider (re-export)
+    ///
+    /// 用于"还没有任何供应商"的首次使用场景，不会重写 live 文件。
+    pub fn adopt_current_live(
+        state: &AppState,
+        app_type: AppType,
+        name: &str,
+    ) -> Result<Provider, AppError> {
+        adopt_current_live(state, app_type, name)
+    }
+
+    /// Build a provider from the official CLI config location
+    ///
+    /// 读取官方 CLI 默认目录（Codex `~/.codex/config.toml` + `auth.json`、Gemini `~/.gemini/.env`、
+    /// Claude `~/.claude/settings.json`），不受配置目录覆盖设置影响。返回的供应商未保存，
+    /// 官方配置不存在时返回 None。
+    pub fn import_official(app_type: AppType) -> Result<Option<Provider>, AppError> {
+        let provider = official::import_official(&app_type)?;
+        if let Some(provider) = provider.as_ref() {
+            Self::log_lint_warnings(&app_type, provider);
+        }
+        Ok(provider)
+    }
+
     /// Read current live settings (re-export)
     pub fn read_live_settings(app_type: AppType) -> Result<Value, AppError> {
         read_live_settings(app_type)
     }
 
+    /// 导出供应商的原生配置文件（与切换时写入 Live 的内容一致，包含通用配置）
+    pub fn export_native(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<NativeExport, AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+        provider.settings_config =
+            build_effective_settings_with_common_config(state.db.as_ref(), &app_type, &provider)?;
+
+        Ok(NativeExport {
+            app_type: app_type.as_str().to_string(),
+            provider_id: provider_id.to_string(),
+            files: render_live_files(&app_type, &provider)?,
+        })
+    }
+
     /// Get custom endpoints list (re-export)
     pub fn get_custom_endpoints(
         state: &AppState,
@@ -997,6 +1938,27 @@ impl ProviderService {
         endpoints::update_endpoint_last_used(state, app_type, provider_id, url)
     }
 
+    /// Append speedtest results to the endpoint latency history (re-export)
+    pub fn record_endpoint_latencies(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        results: &[crate::services::speedtest::EndpointLatency],
+    ) -> Result<(), AppError> {
+        endpoints::record_endpoint_latencies(state, app_type, provider_id, results)
+    }
+
+    /// Endpoint latency trend for a sparkline, oldest first (re-export)
+    pub fn endpoint_latency_trend(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        url: &str,
+        limit: usize,
+    ) -> Result<Vec<crate::database::EndpointLatencySample>, AppError> {
+        endpoints::endpoint_latency_trend(state, app_type, provider_id, url, limit)
+    }
+
     /// Update provider sort order
     pub fn update_sort_order(
         state: &AppState,
@@ -1057,6 +2019,37 @@ impl ProviderService {
         write_gemini_live(provider)
     }
 
+    /// 每个应用最多一个兜底供应商（`meta.isSafetyNet`）
+    fn ensure_single_safety_net(
+        state: &AppState,
+        app_type: &AppType,
+        provider: &Provider,
+    ) -> Result<(), AppError> {
+        if !provider.is_safety_net() {
+            return Ok(());
+        }
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        if let Some(existing) = providers
+            .values()
+            .find(|p| p.id != provider.id && p.is_safety_net())
+        {
+            return Err(AppError::localized(
+                "provider.safety_net.duplicate",
+                format!(
+                    "{} 已将「{}」设为兜底供应商，每个应用最多一个",
+                    app_type.as_str(),
+                    existing.name
+                ),
+                format!(
+                    "{} already uses '{}' as its safety-net provider; only one is allowed",
+                    app_type.as_str(),
+                    existing.name
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     fn validate_provider_settings(app_type: &AppType, provider: &Provider) -> Result<(), AppError> {
         match app_type {
             AppType::Claude => {
@@ -1146,7 +2139,6 @@ impl ProviderService {
         Ok(())
     }
 
-    #[allow(dead_code)]
     fn extract_credentials(
         provider: &Provider,
         app_type: &AppType,
@@ -1223,31 +2215,7 @@ impl ProviderService {
                     .and_then(|v| v.as_str())
                     .unwrap_or("");
 
-                let base_url = if config_toml.contains("base_url") {
-                    let re = Regex::new(r#"base_url\s*=\s*["']([^"']+)["']"#).map_err(|e| {
-                        AppError::localized(
-                            "provider.regex_init_failed",
-                            format!("正则初始化失败: {e}"),
-                            format!("Failed to initialize regex: {e}"),
-                        )
-                    })?;
-                    re.captures(config_toml)
-                        .and_then(|caps| caps.get(1))
-                        .map(|m| m.as_str().to_string())
-                        .ok_or_else(|| {
-                            AppError::localized(
-                                "provider.codex.base_url.invalid",
-                                "config.toml 中 base_url 格式错误",
-                                "base_url in config.toml has invalid format",
-                            )
-                        })?
-                } else {
-                    return Err(AppError::localized(
-                        "provider.codex.base_url.missing",
-                        "config.toml 中缺少 base_url 配置",
-                        "base_url is missing from config.toml",
-                    ));
-                };
+                let base_url = crate::codex_config::resolve_codex_base_url(config_toml)?;
 
                 Ok((api_key, base_url))
             }
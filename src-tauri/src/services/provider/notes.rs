@@ -0,0 +1,135 @@
+//! Provider notes suggestions
+//!
+//! 新建供应商时备注为空，按 base_url 的域名识别常见的官方接口与中转站，预填一句简短说明。
+
+use super::url_normalize::read_base_url;
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::provider::Provider;
+
+/// 已知域名（精确匹配或其子域名）及对应说明
+const KNOWN_HOSTS: &[(&str, &str)] = &[
+    ("api.anthropic.com", "Anthropic official API"),
+    ("api.openai.com", "OpenAI official API"),
+    ("generativelanguage.googleapis.com", "Google Gemini API"),
+    ("openrouter.ai", "OpenRouter multi-model router"),
+    ("api.deepseek.com", "DeepSeek API"),
+    ("open.bigmodel.cn", "Zhipu GLM (BigModel)"),
+    ("api.z.ai", "Z.ai GLM"),
+    ("api.moonshot.cn", "Moonshot Kimi"),
+    ("api.moonshot.ai", "Moonshot Kimi"),
+    (
+        "dashscope.aliyuncs.com",
+        "Alibaba Cloud Bailian (DashScope)",
+    ),
+    ("api.siliconflow.cn", "SiliconFlow"),
+    ("api.minimaxi.com", "MiniMax"),
+    ("api.minimax.io", "MiniMax"),
+    ("volces.com", "Volcengine Ark (Doubao)"),
+    ("api.x.ai", "xAI Grok"),
+    ("api.githubcopilot.com", "GitHub Copilot"),
+];
+
+impl ProviderService {
+    /// Suggest a short description for a provider from its base_url host
+    ///
+    /// 未知域名或无法解析的地址返回 None。
+    pub fn suggest_notes(base_url: &str) -> Option<String> {
+        let trimmed = base_url.trim();
+        let parsed = if trimmed.contains("://") {
+            url::Url::parse(trimmed)
+        } else {
+            url::Url::parse(&format!("https://{trimmed}"))
+        }
+        .ok()?;
+        let host = parsed.host_str()?.to_ascii_lowercase();
+
+        KNOWN_HOSTS
+            .iter()
+            .find(|(known, _)| {
+                host == *known
+                    || host
+                        .strip_suffix(known)
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+            .map(|(_, description)| description.to_string())
+    }
+}
+
+/// 备注为空时按 base_url 预填说明
+pub(super) fn prefill_notes(app_type: &AppType, provider: &mut Provider) {
+    if provider
+        .notes
+        .as_deref()
+        .is_some_and(|notes| !notes.trim().is_empty())
+    {
+        return;
+    }
+    if let Some(notes) = read_base_url(app_type, provider)
+        .and_then(|(_, base_url)| ProviderService::suggest_notes(&base_url))
+    {
+        provider.notes = Some(notes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn known_hosts_map_to_descriptions() {
+        for (base_url, expected) in [
+            ("https://api.anthropic.com", "Anthropic official API"),
+            ("https://openrouter.ai/api", "OpenRouter multi-model router"),
+            ("https://api.deepseek.com/anthropic/", "DeepSeek API"),
+            (
+                "https://open.bigmodel.cn/api/anthropic",
+                "Zhipu GLM (BigModel)",
+            ),
+            (
+                "https://ark.cn-beijing.volces.com/api/v3",
+                "Volcengine Ark (Doubao)",
+            ),
+            ("API.Moonshot.CN/anthropic", "Moonshot Kimi"),
+        ] {
+            assert_eq!(
+                ProviderService::suggest_notes(base_url).as_deref(),
+                Some(expected),
+                "{base_url}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_or_lookalike_hosts_return_none() {
+        assert_eq!(
+            ProviderService::suggest_notes("https://relay.example/v1"),
+            None
+        );
+        assert_eq!(
+            ProviderService::suggest_notes("https://notopenrouter.ai/api"),
+            None
+        );
+        assert_eq!(ProviderService::suggest_notes(""), None);
+    }
+
+    #[test]
+    fn prefill_only_fills_empty_notes() {
+        let mut provider = Provider::with_id(
+            "p".to_string(),
+            "P".to_string(),
+            json!({ "env": { "ANTHROPIC_BASE_URL": "https://openrouter.ai/api" } }),
+            None,
+        );
+        prefill_notes(&AppType::Claude, &mut provider);
+        assert_eq!(
+            provider.notes.as_deref(),
+            Some("OpenRouter multi-model router")
+        );
+
+        provider.notes = Some("my relay".to_string());
+        prefill_notes(&AppType::Claude, &mut provider);
+        assert_eq!(provider.notes.as_deref(), Some("my relay"));
+    }
+}
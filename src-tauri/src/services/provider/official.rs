@@ -0,0 +1,217 @@
+//! Import from official CLI config locations
+//!
+//! 设置中可以把 Claude / Codex / Gemini 的配置目录改到其他位置，此时官方 CLI 默认目录
+//! （`~/.claude`、`~/.codex`、`~/.gemini`）里的配置不会被 Live 导入读到。
+//! 这里始终读取官方默认位置，生成名为 "Imported (official)" 的供应商（不保存）。
+
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::config::{get_home_dir, read_json_file};
+use crate::error::AppError;
+use crate::provider::Provider;
+
+use super::normalize_claude_models_in_value;
+
+/// 导入的供应商名称
+pub(crate) const OFFICIAL_PROVIDER_NAME: &str = "Imported (official)";
+
+/// 官方 CLI 的默认配置目录（忽略 CC Switch 中的目录覆盖设置）
+fn official_dir(app_type: &AppType) -> Option<PathBuf> {
+    let dir = match app_type {
+        AppType::Claude => ".claude",
+        AppType::Codex => ".codex",
+        AppType::Gemini => ".gemini",
+        AppType::OpenCode | AppType::OpenClaw => return None,
+    };
+    Some(get_home_dir().join(dir))
+}
+
+/// 不支持从官方配置导入的应用（OpenCode / OpenClaw）
+fn unsupported_app(app_type: &AppType) -> AppError {
+    AppError::localized(
+        "provider.import_official.unsupported",
+        format!("{} 不支持从官方配置导入", app_type.as_str()),
+        format!(
+            "Importing official config is not supported for {}",
+            app_type.as_str()
+        ),
+    )
+}
+
+/// 读取官方默认位置的配置并构建供应商；不存在官方配置时返回 None
+pub(crate) fn import_official(app_type: &AppType) -> Result<Option<Provider>, AppError> {
+    let Some(dir) = official_dir(app_type) else {
+        return Err(unsupported_app(app_type));
+    };
+
+    let settings_config = match app_type {
+        AppType::Claude => {
+            let path = dir.join("settings.json");
+            if !path.exists() {
+                return Ok(None);
+            }
+            let mut settings: Value = read_json_file(&path)?;
+            let _ = normalize_claude_models_in_value(&mut settings);
+            settings
+        }
+        AppType::Codex => {
+            let auth_path = dir.join("auth.json");
+            let config_path = dir.join("config.toml");
+            if !auth_path.exists() && !config_path.exists() {
+                return Ok(None);
+            }
+            let auth: Value = if auth_path.exists() {
+                read_json_file(&auth_path)?
+            } else {
+                json!({})
+            };
+            let config = if config_path.exists() {
+                let text = std::fs::read_to_string(&config_path)
+                    .map_err(|e| AppError::io(&config_path, e))?;
+                crate::codex_config::validate_config_toml(&text)?;
+                text
+            } else {
+                String::new()
+            };
+            json!({ "auth": auth, "config": config })
+        }
+        AppType::Gemini => {
+            let env_path = dir.join(".env");
+            if !env_path.exists() {
+                return Ok(None);
+            }
+            let content =
+                std::fs::read_to_string(&env_path).map_err(|e| AppError::io(&env_path, e))?;
+            let env_map = crate::gemini_config::parse_env_file(&content);
+            let env = crate::gemini_config::env_to_json(&env_map)
+                .get("env")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+
+            let settings_path = dir.join("settings.json");
+            let config: Value = if settings_path.exists() {
+                read_json_file(&settings_path)?
+            } else {
+                json!({})
+            };
+            json!({ "env": env, "config": config })
+        }
+        AppType::OpenCode | AppType::OpenClaw => return Err(unsupported_app(app_type)),
+    };
+
+    let mut provider = Provider::with_id(
+        uuid::Uuid::new_v4().to_string(),
+        OFFICIAL_PROVIDER_NAME.to_string(),
+        settings_config,
+        None,
+    );
+    provider.category = Some("custom".to_string());
+    Ok(Some(provider))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::fs;
+
+    /// 在临时 HOME 中执行，结束后恢复环境变量
+    fn with_temp_home<T>(test: impl FnOnce(&std::path::Path) -> T) -> T {
+        let temp = tempfile::tempdir().unwrap();
+        let old_test_home = std::env::var_os("CC_SWITCH_TEST_HOME");
+        std::env::set_var("CC_SWITCH_TEST_HOME", temp.path());
+        let result = test(temp.path());
+        match old_test_home {
+            Some(value) => std::env::set_var("CC_SWITCH_TEST_HOME", value),
+            None => std::env::remove_var("CC_SWITCH_TEST_HOME"),
+        }
+        result
+    }
+
+    #[test]
+    #[serial]
+    fn missing_official_config_returns_none() {
+        with_temp_home(|_| {
+            for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+                assert!(import_official(&app_type).unwrap().is_none());
+            }
+            assert!(import_official(&AppType::OpenCode).is_err());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn codex_official_config_is_imported() {
+        with_temp_home(|home| {
+            let dir = home.join(".codex");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("auth.json"), r#"{"OPENAI_API_KEY":"sk-official"}"#).unwrap();
+            let config = "model_provider = \"openai\"\nmodel = \"gpt-5.1-codex\"\n";
+            fs::write(dir.join("config.toml"), config).unwrap();
+
+            let provider = import_official(&AppType::Codex).unwrap().unwrap();
+            assert_eq!(provider.name, OFFICIAL_PROVIDER_NAME);
+            assert_eq!(
+                provider.settings_config["auth"]["OPENAI_API_KEY"],
+                "sk-official"
+            );
+            assert_eq!(provider.settings_config["config"], config);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_codex_toml_is_rejected() {
+        with_temp_home(|home| {
+            let dir = home.join(".codex");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("config.toml"), "model = ").unwrap();
+
+            assert!(import_official(&AppType::Codex).is_err());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn gemini_official_env_and_settings_are_imported() {
+        with_temp_home(|home| {
+            let dir = home.join(".gemini");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(".env"),
+                "GEMINI_API_KEY=gm-official\nGEMINI_MODEL=gemini-2.5-pro\n",
+            )
+            .unwrap();
+            fs::write(dir.join("settings.json"), r#"{"mcpServers":{}}"#).unwrap();
+
+            let provider = import_official(&AppType::Gemini).unwrap().unwrap();
+            let settings = &provider.settings_config;
+            assert_eq!(settings["env"]["GEMINI_API_KEY"], "gm-official");
+            assert_eq!(settings["env"]["GEMINI_MODEL"], "gemini-2.5-pro");
+            assert!(settings["config"]["mcpServers"].is_object());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn claude_official_settings_are_imported() {
+        with_temp_home(|home| {
+            let dir = home.join(".claude");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("settings.json"),
+                r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"sk-ant-official"}}"#,
+            )
+            .unwrap();
+
+            let provider = import_official(&AppType::Claude).unwrap().unwrap();
+            assert_eq!(
+                provider.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+                "sk-ant-official"
+            );
+        });
+    }
+}
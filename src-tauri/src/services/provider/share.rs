@@ -0,0 +1,277 @@
+//! Provider share code
+//!
+//! 分享码是 `ccswitch://v1/import?resource=provider&...` 深链接的 URL-safe Base64（无填充），
+//! 解码后可直接交给深链接解析器，前端据此生成二维码。
+//!
+//! 为控制二维码尺寸，只写入导入时用得到的扁平字段（端点、模型、图标、备注等），
+//! 不携带完整 settings_config；空字段、可由端点推断的主页均省略。
+//! 因此其它设置（额外的环境变量、Codex config.toml 中的其它项、meta 中的代理/请求头等）
+//! 不会出现在分享码中，接收方导入的是按扁平字段生成的默认配置。需要完整配置时应使用导出文件。
+
+use base64::prelude::*;
+use serde_json::Value;
+use url::form_urlencoded;
+
+use super::url_normalize::read_base_url;
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::deeplink::infer_homepage_from_endpoint;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+impl ProviderService {
+    /// Export a provider as a compact share code (URL-safe Base64 of a deep link)
+    ///
+    /// `include_secret` 为 false 时不包含 API Key，接收方导入前需自行填写。
+    /// 只包含端点、API Key、模型、主页、图标与备注，其它设置不会被分享（见模块说明）。
+    pub fn export_share_code(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        include_secret: bool,
+    ) -> Result<String, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let link = build_share_link(&app_type, &provider, include_secret);
+        Ok(BASE64_URL_SAFE_NO_PAD.encode(link))
+    }
+}
+
+/// 构造分享用的深链接（字段名与深链接解析器一致）
+fn build_share_link(app_type: &AppType, provider: &Provider, include_secret: bool) -> String {
+    let mut query = form_urlencoded::Serializer::new(String::new());
+    query
+        .append_pair("resource", "provider")
+        .append_pair("app", app_type.as_str())
+        .append_pair("name", &provider.name);
+
+    let endpoint = read_base_url(app_type, provider)
+        .map(|(_, url)| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+    if let Some(endpoint) = &endpoint {
+        query.append_pair("endpoint", endpoint);
+    }
+
+    let inferred_homepage = endpoint.as_deref().and_then(infer_homepage_from_endpoint);
+    if let Some(homepage) = non_empty(provider.website_url.as_deref())
+        .filter(|homepage| Some(*homepage) != inferred_homepage.as_deref())
+    {
+        query.append_pair("homepage", homepage);
+    }
+
+    if include_secret {
+        if let Some(api_key) = read_api_key(app_type, &provider.settings_config) {
+            query.append_pair("apiKey", &api_key);
+        }
+    }
+
+    for (param, model) in read_models(app_type, &provider.settings_config) {
+        query.append_pair(param, &model);
+    }
+    if let Some(icon) = non_empty(provider.icon.as_deref()) {
+        query.append_pair("icon", icon);
+    }
+    if let Some(notes) = non_empty(provider.notes.as_deref()) {
+        query.append_pair("notes", notes);
+    }
+
+    format!("ccswitch://v1/import?{}", query.finish())
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+fn string_at(settings: &Value, path: &[&str]) -> Option<String> {
+    let value = path
+        .iter()
+        .try_fold(settings, |value, key| value.get(*key))?
+        .as_str()?;
+    non_empty(Some(value)).map(str::to_string)
+}
+
+fn read_api_key(app_type: &AppType, settings: &Value) -> Option<String> {
+    match app_type {
+        AppType::Claude => string_at(settings, &["env", "ANTHROPIC_AUTH_TOKEN"])
+            .or_else(|| string_at(settings, &["env", "ANTHROPIC_API_KEY"])),
+        AppType::Codex => string_at(settings, &["auth", "OPENAI_API_KEY"]),
+        AppType::Gemini => string_at(settings, &["env", "GEMINI_API_KEY"]),
+        AppType::OpenCode => string_at(settings, &["options", "apiKey"]),
+        AppType::OpenClaw => string_at(settings, &["apiKey"]),
+    }
+}
+
+/// 读取模型字段，返回 (深链接参数名, 模型名)
+fn read_models(app_type: &AppType, settings: &Value) -> Vec<(&'static str, String)> {
+    let candidates: Vec<(&'static str, Option<String>)> = match app_type {
+        AppType::Claude => vec![
+            ("model", string_at(settings, &["env", "ANTHROPIC_MODEL"])),
+            (
+                "haikuModel",
+                string_at(settings, &["env", "ANTHROPIC_DEFAULT_HAIKU_MODEL"]),
+            ),
+            (
+                "sonnetModel",
+                string_at(settings, &["env", "ANTHROPIC_DEFAULT_SONNET_MODEL"]),
+            ),
+            (
+                "opusModel",
+                string_at(settings, &["env", "ANTHROPIC_DEFAULT_OPUS_MODEL"]),
+            ),
+        ],
+        AppType::Codex => vec![(
+            "model",
+            settings
+                .get("config")
+                .and_then(Value::as_str)
+                .and_then(|config| toml::from_str::<toml::Value>(config).ok())
+                .and_then(|config| {
+                    config
+                        .get("model")
+                        .and_then(|model| model.as_str())
+                        .map(str::to_string)
+                }),
+        )],
+        AppType::Gemini => vec![("model", string_at(settings, &["env", "GEMINI_MODEL"]))],
+        AppType::OpenCode => vec![(
+            "model",
+            settings
+                .get("models")
+                .and_then(Value::as_object)
+                .and_then(|models| models.keys().next().cloned()),
+        )],
+        AppType::OpenClaw => vec![(
+            "model",
+            settings
+                .get("models")
+                .and_then(Value::as_array)
+                .and_then(|models| models.first())
+                .and_then(|model| string_at(model, &["id"])),
+        )],
+    };
+
+    candidates
+        .into_iter()
+        .filter_map(|(param, model)| model.map(|model| (param, model)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deeplink::{parse_and_merge_config, parse_share_code};
+    use serde_json::json;
+
+    fn claude_provider() -> Provider {
+        let mut provider = Provider::with_id(
+            "relay".to_string(),
+            "My Relay".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": "https://api.relay.example/v1",
+                    "ANTHROPIC_AUTH_TOKEN": "sk-secret",
+                    "ANTHROPIC_MODEL": "claude-sonnet-4",
+                    "ANTHROPIC_DEFAULT_HAIKU_MODEL": ""
+                }
+            }),
+            Some("https://relay.example".to_string()),
+        );
+        provider.notes = Some("team relay".to_string());
+        provider
+    }
+
+    #[test]
+    fn share_code_round_trips_through_deeplink_parser() {
+        let provider = claude_provider();
+        let code =
+            BASE64_URL_SAFE_NO_PAD.encode(build_share_link(&AppType::Claude, &provider, true));
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        let request = parse_and_merge_config(&parse_share_code(&code).unwrap()).unwrap();
+        assert_eq!(request.resource, "provider");
+        assert_eq!(request.app.as_deref(), Some("claude"));
+        assert_eq!(request.name.as_deref(), Some("My Relay"));
+        assert_eq!(
+            request.endpoint.as_deref(),
+            Some("https://api.relay.example/v1")
+        );
+        assert_eq!(request.api_key.as_deref(), Some("sk-secret"));
+        assert_eq!(request.model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(request.notes.as_deref(), Some("team relay"));
+        // 主页可由端点推断，分享码中省略；空模型字段同样省略
+        assert_eq!(request.homepage, None);
+        assert_eq!(request.haiku_model, None);
+        assert_eq!(request.config, None);
+    }
+
+    #[test]
+    fn share_code_omits_secret_unless_requested() {
+        let provider = claude_provider();
+        let link = build_share_link(&AppType::Claude, &provider, false);
+        assert!(!link.contains("sk-secret"));
+
+        let code = BASE64_URL_SAFE_NO_PAD.encode(link);
+        let request = parse_share_code(&code).unwrap();
+        assert_eq!(request.api_key, None);
+    }
+
+    #[test]
+    fn codex_share_code_carries_base_url_and_model() {
+        let mut provider = Provider::with_id(
+            "c".to_string(),
+            "Codex Relay".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-codex" },
+                "config": "model_provider = \"relay\"\nmodel = \"gpt-5\"\n\n[model_providers.relay]\nbase_url = \"https://relay.example/v1\"\n"
+            }),
+            Some("https://docs.relay.example".to_string()),
+        );
+        provider.icon = Some("openai".to_string());
+
+        let code =
+            BASE64_URL_SAFE_NO_PAD.encode(build_share_link(&AppType::Codex, &provider, true));
+        let request = parse_share_code(&code).unwrap();
+        assert_eq!(request.app.as_deref(), Some("codex"));
+        assert_eq!(
+            request.endpoint.as_deref(),
+            Some("https://relay.example/v1")
+        );
+        assert_eq!(request.api_key.as_deref(), Some("sk-codex"));
+        assert_eq!(request.model.as_deref(), Some("gpt-5"));
+        assert_eq!(request.icon.as_deref(), Some("openai"));
+        // 与推断结果不同的主页需要保留
+        assert_eq!(
+            request.homepage.as_deref(),
+            Some("https://docs.relay.example")
+        );
+    }
+
+    #[test]
+    fn share_code_omits_settings_outside_flat_fields() {
+        let mut provider = claude_provider();
+        provider.settings_config["env"]["HTTPS_PROXY"] = json!("http://127.0.0.1:7890");
+        provider.settings_config["permissions"] = json!({ "allow": ["Bash"] });
+
+        let link = build_share_link(&AppType::Claude, &provider, true);
+        assert!(!link.contains("HTTPS_PROXY"));
+        assert!(!link.contains("7890"));
+        assert!(!link.contains("permissions"));
+
+        // 导入时按扁平字段生成配置，未分享的设置不会出现
+        let request = parse_share_code(&BASE64_URL_SAFE_NO_PAD.encode(link)).unwrap();
+        assert_eq!(request.config, None);
+        assert_eq!(request.config_url, None);
+    }
+}
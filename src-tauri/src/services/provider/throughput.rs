@@ -0,0 +1,511 @@
+//! Provider token throughput benchmark
+//!
+//! 延迟测试只能反映首字速度；吞吐测试发送一个要求输出约 `target_tokens` 的流式请求，
+//! 按首个内容增量到最后一个内容增量之间的时间计算生成速度（tokens/s），并记录首 token 耗时（TTFT）。
+//! 输出 token 数优先使用流中上报的 usage，上游未返回时按内容增量个数估算。
+//! 请求地址与请求头复用流式健康检查的构建方法，测试结果写入 `meta.throughput`。
+
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::{Provider, ProviderMeta, ThroughputReport};
+use crate::proxy::providers::{get_adapter, get_claude_api_format, AuthInfo, AuthStrategy};
+use crate::proxy::usage::TokenUsage;
+use crate::services::stream_check::{StreamCheckConfig, StreamCheckService};
+use crate::store::AppState;
+
+/// 吞吐测试请求超时（生成较长输出需要更宽裕的时间）
+const BENCHMARK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 单次测试允许的最大输出 token 数
+const MAX_TARGET_TOKENS: u32 = 16_384;
+
+/// 测试请求使用的上游协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamProtocol {
+    /// Anthropic Messages API
+    Anthropic,
+    /// OpenAI Chat Completions
+    OpenAIChat,
+    /// OpenAI Responses API
+    OpenAIResponses,
+    /// Gemini streamGenerateContent
+    Gemini,
+}
+
+impl StreamProtocol {
+    fn for_provider(
+        app_type: &AppType,
+        provider: &Provider,
+        auth: &AuthInfo,
+    ) -> Result<Self, AppError> {
+        match app_type {
+            // GitHub Copilot 只提供 Chat Completions
+            AppType::Claude if auth.strategy == AuthStrategy::GitHubCopilot => Ok(Self::OpenAIChat),
+            AppType::Claude => Ok(match get_claude_api_format(provider) {
+                "openai_chat" => Self::OpenAIChat,
+                "openai_responses" => Self::OpenAIResponses,
+                _ => Self::Anthropic,
+            }),
+            AppType::Codex => Ok(Self::OpenAIResponses),
+            AppType::Gemini => Ok(Self::Gemini),
+            AppType::OpenCode | AppType::OpenClaw => Err(AppError::localized(
+                "provider.benchmark.unsupported",
+                format!("{} 暂不支持吞吐测试", app_type.as_str()),
+                format!(
+                    "Throughput benchmark is not supported for {}",
+                    app_type.as_str()
+                ),
+            )),
+        }
+    }
+
+    /// 候选请求地址（与流式健康检查一致，首个地址返回 404 时回退到下一个）
+    fn urls(self, base_url: &str, auth: &AuthInfo, model: &str) -> Vec<String> {
+        match self {
+            Self::Anthropic => vec![StreamCheckService::resolve_claude_stream_url(
+                base_url,
+                auth.strategy,
+                "anthropic",
+            )],
+            Self::OpenAIChat => vec![StreamCheckService::resolve_claude_stream_url(
+                base_url,
+                auth.strategy,
+                "openai_chat",
+            )],
+            Self::OpenAIResponses => StreamCheckService::resolve_responses_urls(base_url),
+            Self::Gemini => vec![StreamCheckService::resolve_gemini_url(
+                base_url, model, true,
+            )],
+        }
+    }
+
+    /// 构建带认证与客户端请求头的流式请求（与流式健康检查一致）
+    fn request(
+        self,
+        app_type: &AppType,
+        client: &Client,
+        url: &str,
+        auth: &AuthInfo,
+    ) -> RequestBuilder {
+        match self {
+            Self::Anthropic => StreamCheckService::claude_request(client, url, auth, false, true),
+            Self::OpenAIResponses if *app_type == AppType::Codex => {
+                StreamCheckService::codex_request(client, url, auth, true)
+            }
+            Self::OpenAIChat | Self::OpenAIResponses => {
+                StreamCheckService::claude_request(client, url, auth, true, true)
+            }
+            Self::Gemini => StreamCheckService::gemini_request(client, url, auth, true),
+        }
+    }
+
+    fn request_body(self, model: &str, prompt: &str, max_tokens: u32) -> Value {
+        match self {
+            Self::Anthropic => json!({
+                "model": model,
+                "max_tokens": max_tokens,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": true
+            }),
+            Self::OpenAIChat => json!({
+                "model": model,
+                "max_tokens": max_tokens,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": true,
+                "stream_options": { "include_usage": true }
+            }),
+            Self::OpenAIResponses => json!({
+                "model": model,
+                "max_output_tokens": max_tokens,
+                "input": [{ "role": "user", "content": prompt }],
+                "stream": true
+            }),
+            Self::Gemini => json!({
+                "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+                "generationConfig": { "maxOutputTokens": max_tokens }
+            }),
+        }
+    }
+
+    /// 事件是否携带生成内容（用于计时）
+    fn is_content_delta(self, event: &Value) -> bool {
+        let non_empty = |pointer: &str| {
+            event
+                .pointer(pointer)
+                .and_then(|v| v.as_str())
+                .is_some_and(|text| !text.is_empty())
+        };
+        match self {
+            Self::Anthropic => event["type"] == "content_block_delta",
+            Self::OpenAIChat => non_empty("/choices/0/delta/content"),
+            Self::OpenAIResponses => event["type"] == "response.output_text.delta",
+            Self::Gemini => non_empty("/candidates/0/content/parts/0/text"),
+        }
+    }
+
+    fn parse_usage(self, events: &[Value]) -> Option<TokenUsage> {
+        match self {
+            Self::Anthropic => TokenUsage::from_claude_stream_events(events),
+            Self::OpenAIChat => TokenUsage::from_openai_stream_events(events),
+            Self::OpenAIResponses => TokenUsage::from_codex_stream_events_auto(events),
+            Self::Gemini => TokenUsage::from_gemini_stream_chunks(events),
+        }
+    }
+}
+
+/// 让模型持续输出直到达到 token 上限的提示词
+fn benchmark_prompt(target_tokens: u32) -> String {
+    format!(
+        "Count upward from 1, writing each number as an English word on its own line. \
+         Do not stop or add commentary; keep going for about {target_tokens} tokens."
+    )
+}
+
+/// 流式响应中内容增量的计时
+#[derive(Debug, Default)]
+struct DeltaTiming {
+    first: Option<Instant>,
+    last: Option<Instant>,
+    count: u32,
+}
+
+impl DeltaTiming {
+    fn record(&mut self, at: Instant) {
+        self.first.get_or_insert(at);
+        self.last = Some(at);
+        self.count += 1;
+    }
+}
+
+impl ProviderService {
+    /// Measure a provider's generation speed (tokens/sec) and time to first token
+    ///
+    /// 测试使用与模型测试相同的模型（`meta.testConfig` 或应用配置中的模型），
+    /// 结果保存到供应商的 `meta.throughput`。
+    pub async fn benchmark_throughput(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        target_tokens: u32,
+    ) -> Result<ThroughputReport, AppError> {
+        if target_tokens == 0 || target_tokens > MAX_TARGET_TOKENS {
+            return Err(AppError::localized(
+                "provider.benchmark.invalid_target",
+                format!("目标输出 token 数需在 1 到 {MAX_TARGET_TOKENS} 之间"),
+                format!("Target tokens must be between 1 and {MAX_TARGET_TOKENS}"),
+            ));
+        }
+
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let adapter = get_adapter(&app_type);
+        let base_url = adapter
+            .extract_base_url(&provider)
+            .map_err(|e| AppError::Message(format!("Failed to extract base_url: {e}")))?;
+        let auth = adapter
+            .extract_auth(&provider)
+            .ok_or_else(|| AppError::Message("API Key not found".to_string()))?;
+        let protocol = StreamProtocol::for_provider(&app_type, &provider, &auth)?;
+
+        let config =
+            StreamCheckService::merge_provider_config(&provider, &StreamCheckConfig::default());
+        let (model, _) = StreamCheckService::parse_model_with_effort(
+            &StreamCheckService::resolve_test_model(&app_type, &provider, &config),
+        );
+
+        let client = crate::proxy::http_client::get_for_provider_meta(provider.meta.as_ref())
+            .map_err(AppError::Message)?;
+        let body = protocol.request_body(&model, &benchmark_prompt(target_tokens), target_tokens);
+        let urls = protocol.urls(&base_url, &auth, &model);
+
+        let start = Instant::now();
+        let mut response = None;
+        for (i, url) in urls.iter().enumerate() {
+            let attempt = protocol
+                .request(&app_type, &client, url, &auth)
+                .timeout(BENCHMARK_TIMEOUT)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| {
+                    AppError::Message(format!(
+                        "Request failed: {}",
+                        crate::proxy::http_client::describe_error(&e)
+                    ))
+                })?;
+
+            let status = attempt.status().as_u16();
+            if !attempt.status().is_success() {
+                // 与流式健康检查一致：仅当首选地址返回 404 时尝试下一个
+                if i == 0 && status == 404 && urls.len() > 1 {
+                    continue;
+                }
+                let error_text = attempt.text().await.unwrap_or_default();
+                return Err(AppError::Message(format!("HTTP {status}: {error_text}")));
+            }
+            response = Some(attempt);
+            break;
+        }
+        let Some(response) = response else {
+            return Err(AppError::Message(
+                "No valid responses endpoint found".to_string(),
+            ));
+        };
+
+        let mut timing = DeltaTiming::default();
+        let mut usage_events = Vec::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| AppError::Message(format!("Stream read failed: {e}")))?;
+            let received_at = Instant::now();
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim_end().strip_prefix("data:").map(str::trim_start) else {
+                    continue;
+                };
+                if data.is_empty() || data == "[DONE]" {
+                    continue;
+                }
+                let Ok(event) = serde_json::from_str::<Value>(data) else {
+                    continue;
+                };
+                if protocol.is_content_delta(&event) {
+                    timing.record(received_at);
+                }
+                // 与代理轻量模式一致，只保留可能携带 usage 的事件
+                if data.contains("\"usage") {
+                    usage_events.push(event);
+                }
+            }
+        }
+        let total = start.elapsed();
+
+        let (Some(first), Some(last)) = (timing.first, timing.last) else {
+            return Err(AppError::localized(
+                "provider.benchmark.no_output",
+                "未收到任何生成内容",
+                "No generated content was received",
+            ));
+        };
+
+        let usage = protocol
+            .parse_usage(&usage_events)
+            .filter(|usage| usage.output_tokens > 0);
+        let output_tokens = usage
+            .as_ref()
+            .map_or(timing.count, |usage| usage.output_tokens);
+        // 只有一个增量时无法得到生成区间，退化为按总耗时计算
+        let generation = last.duration_since(first);
+        let window = if generation.is_zero() {
+            total
+        } else {
+            generation
+        };
+
+        let report = ThroughputReport {
+            model,
+            output_tokens,
+            tokens_per_second: output_tokens as f64 / window.as_secs_f64().max(f64::EPSILON),
+            ttft_ms: first.duration_since(start).as_millis() as u64,
+            total_ms: total.as_millis() as u64,
+            usage_reported: usage.is_some(),
+            tested_at: chrono::Utc::now().timestamp(),
+        };
+        log::info!(
+            "[{}] 供应商 {provider_id} 吞吐测试: {} tokens, {:.1} tokens/s, TTFT {}ms",
+            app_type.as_str(),
+            report.output_tokens,
+            report.tokens_per_second,
+            report.ttft_ms
+        );
+
+        // 重新读取，避免覆盖测试期间的编辑
+        if let Some(mut latest) = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+        {
+            latest
+                .meta
+                .get_or_insert_with(ProviderMeta::default)
+                .throughput = Some(report.clone());
+            state.db.save_provider(app_type.as_str(), &latest)?;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use axum::{body::Body, extract::RawQuery, http::HeaderMap, routing::post, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    const DELTA_COUNT: usize = 10;
+    const DELTA_INTERVAL: Duration = Duration::from_millis(40);
+    const REPORTED_OUTPUT_TOKENS: u32 = 200;
+
+    fn sse(event: Value) -> String {
+        format!("data: {event}\n\n")
+    }
+
+    /// 捕获的上游请求：(请求体, 请求头, 查询参数)
+    type Captured = Arc<Mutex<Option<(Value, HeaderMap, Option<String>)>>>;
+
+    /// 模拟上游：首个增量前等待 80ms，之后每 40ms 输出一个增量，共 10 个，最终报告 200 个输出 token
+    async fn spawn_upstream(captured: Captured) -> String {
+        let router = Router::new().route(
+            "/v1/messages",
+            post(move |headers: HeaderMap, RawQuery(query): RawQuery, Json(body): Json<Value>| {
+                let captured = captured.clone();
+                async move {
+                    *captured.lock().expect("capture lock") = Some((body, headers, query));
+                    let stream = async_stream::stream! {
+                        yield Ok::<_, std::convert::Infallible>(sse(json!({
+                            "type": "message_start",
+                            "message": { "model": "mock-model", "usage": { "input_tokens": 30 } }
+                        })));
+                        tokio::time::sleep(Duration::from_millis(80)).await;
+                        for i in 0..DELTA_COUNT {
+                            if i > 0 {
+                                tokio::time::sleep(DELTA_INTERVAL).await;
+                            }
+                            yield Ok(sse(json!({
+                                "type": "content_block_delta",
+                                "index": 0,
+                                "delta": { "type": "text_delta", "text": "one two three " }
+                            })));
+                        }
+                        yield Ok(sse(json!({
+                            "type": "message_delta",
+                            "delta": { "stop_reason": "max_tokens" },
+                            "usage": { "output_tokens": REPORTED_OUTPUT_TOKENS }
+                        })));
+                        yield Ok(sse(json!({ "type": "message_stop" })));
+                    };
+                    axum::response::Response::builder()
+                        .header("content-type", "text/event-stream")
+                        .body(Body::from_stream(stream))
+                        .expect("build sse response")
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind upstream");
+        let addr = listener.local_addr().expect("upstream addr");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.ok();
+        });
+        format!("http://{addr}")
+    }
+
+    fn setup(base_url: &str) -> AppState {
+        let db = Arc::new(Database::memory().expect("init db"));
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": base_url,
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test",
+                    "ANTHROPIC_MODEL": "mock-model"
+                }
+            }),
+            None,
+        );
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        AppState::new(db)
+    }
+
+    #[tokio::test]
+    async fn benchmark_measures_throughput_and_stores_report() {
+        let captured = Arc::new(Mutex::new(None));
+        let base_url = spawn_upstream(captured.clone()).await;
+        let state = setup(&base_url);
+
+        let report = ProviderService::benchmark_throughput(&state, AppType::Claude, "p1", 256)
+            .await
+            .expect("benchmark throughput");
+
+        let (body, headers, query) = captured
+            .lock()
+            .expect("capture lock")
+            .clone()
+            .expect("request captured");
+        assert_eq!(body["max_tokens"], 256);
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["model"], "mock-model");
+        // 与流式健康检查使用相同的地址与 Claude CLI 请求头
+        assert_eq!(query.as_deref(), Some("beta=true"));
+        assert_eq!(headers["anthropic-version"], "2023-06-01");
+        assert_eq!(headers["x-app"], "cli");
+        assert_eq!(headers["authorization"], "Bearer sk-test");
+
+        assert_eq!(report.output_tokens, REPORTED_OUTPUT_TOKENS);
+        assert!(report.usage_reported);
+        assert!(report.ttft_ms >= 80, "ttft was {}ms", report.ttft_ms);
+        assert!(report.total_ms >= report.ttft_ms);
+        // 增量区间至少 9 × 40ms，因此速度不会超过 200 / 0.36s
+        let upper = REPORTED_OUTPUT_TOKENS as f64
+            / (DELTA_INTERVAL * (DELTA_COUNT as u32 - 1)).as_secs_f64();
+        assert!(
+            report.tokens_per_second <= upper + 0.01,
+            "tokens/s {} exceeds {upper}",
+            report.tokens_per_second
+        );
+        assert!(
+            report.tokens_per_second > upper / 3.0,
+            "tokens/s {} is implausibly low",
+            report.tokens_per_second
+        );
+
+        let stored = state
+            .db
+            .get_provider_by_id("p1", "claude")
+            .expect("read provider")
+            .expect("provider exists")
+            .meta
+            .and_then(|meta| meta.throughput)
+            .expect("throughput stored");
+        assert_eq!(stored.output_tokens, REPORTED_OUTPUT_TOKENS);
+        assert_eq!(stored.tested_at, report.tested_at);
+    }
+
+    #[tokio::test]
+    async fn benchmark_rejects_invalid_target() {
+        let state = setup("http://127.0.0.1:9");
+        let err = ProviderService::benchmark_throughput(&state, AppType::Claude, "p1", 0)
+            .await
+            .expect_err("zero target must be rejected");
+        assert!(matches!(
+            err,
+            AppError::Localized {
+                key: "provider.benchmark.invalid_target",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+//! Provider token freshness heuristics
+//!
+//! 不发送请求，仅凭令牌格式判断是否可能已失效：
+//! - 空令牌
+//! - 代理接管遗留的占位符（`PROXY_MANAGED`），通常是接管期间把 Live 配置回填到了供应商
+//! - Google OAuth 访问令牌（`ya29.`）有效期只有约 1 小时，保存在配置里基本都已过期
+//! - JWT 形式的令牌（如 Codex 登录后的 `tokens.access_token`）按 `exp` 字段判断
+//!
+//! 判断为 `Fresh` 不代表令牌一定有效。
+
+use base64::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::proxy::PROXY_TOKEN_PLACEHOLDER;
+use crate::store::AppState;
+
+/// Google OAuth 访问令牌前缀
+const GOOGLE_ACCESS_TOKEN_PREFIX: &str = "ya29.";
+
+/// 令牌新鲜度检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum TokenFreshness {
+    /// 未发现可疑之处
+    Fresh,
+    /// 令牌为空或缺失
+    Empty { field: String },
+    /// 代理接管遗留的占位符
+    Placeholder { field: String },
+    /// 很可能已过期
+    LikelyExpired { field: String, reason: String },
+}
+
+impl ProviderService {
+    /// Heuristically check whether a provider's tokens look stale
+    ///
+    /// 逐个检查该应用的令牌字段，返回第一个可疑结果；全部正常时返回 `Fresh`。
+    pub fn check_token_freshness(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<TokenFreshness, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+        Ok(check_provider_tokens(
+            &app_type,
+            &provider,
+            chrono::Utc::now().timestamp(),
+        ))
+    }
+}
+
+/// 各应用的令牌字段（按 `.` 分隔的路径），第一个为主字段
+fn token_fields(app_type: &AppType) -> &'static [&'static str] {
+    match app_type {
+        AppType::Claude => &["env.ANTHROPIC_AUTH_TOKEN", "env.ANTHROPIC_API_KEY"],
+        AppType::Codex => &["auth.OPENAI_API_KEY", "auth.tokens.access_token"],
+        AppType::Gemini => &["env.GEMINI_API_KEY", "env.GOOGLE_API_KEY"],
+        AppType::OpenCode => &["options.apiKey"],
+        AppType::OpenClaw => &["apiKey"],
+    }
+}
+
+/// 检查供应商配置中的令牌（`now` 为 Unix 秒）
+pub(crate) fn check_provider_tokens(
+    app_type: &AppType,
+    provider: &Provider,
+    now: i64,
+) -> TokenFreshness {
+    let fields = token_fields(app_type);
+    let tokens: Vec<(&str, &str)> = fields
+        .iter()
+        .filter_map(|field| {
+            field
+                .split('.')
+                .try_fold(&provider.settings_config, |value, key| value.get(key))
+                .and_then(Value::as_str)
+                .map(|token| (*field, token))
+        })
+        .collect();
+
+    if tokens.is_empty() {
+        return TokenFreshness::Empty {
+            field: fields[0].to_string(),
+        };
+    }
+    tokens
+        .into_iter()
+        .map(|(field, token)| check_token(field, token, now))
+        .find(|result| *result != TokenFreshness::Fresh)
+        .unwrap_or(TokenFreshness::Fresh)
+}
+
+fn check_token(field: &str, token: &str, now: i64) -> TokenFreshness {
+    let token = token.trim();
+    let field = field.to_string();
+    if token.is_empty() {
+        return TokenFreshness::Empty { field };
+    }
+    if token == PROXY_TOKEN_PLACEHOLDER {
+        return TokenFreshness::Placeholder { field };
+    }
+    if token.starts_with(GOOGLE_ACCESS_TOKEN_PREFIX) {
+        return TokenFreshness::LikelyExpired {
+            field,
+            reason: "Google OAuth 访问令牌（ya29.）有效期约 1 小时，请改用 API Key 或重新登录"
+                .to_string(),
+        };
+    }
+    match jwt_expiry(token) {
+        Some(exp) if exp <= now => TokenFreshness::LikelyExpired {
+            field,
+            reason: format!(
+                "令牌已于 {} 过期",
+                chrono::DateTime::from_timestamp(exp, 0)
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_else(|| exp.to_string())
+            ),
+        },
+        _ => TokenFreshness::Fresh,
+    }
+}
+
+/// 解析 JWT 载荷中的 `exp`（Unix 秒），不是 JWT 或没有 `exp` 时返回 None
+fn jwt_expiry(token: &str) -> Option<i64> {
+    let mut parts = token.split('.');
+    let (_, payload, _) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let bytes = BASE64_URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("exp")?.as_i64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: i64 = 1_760_000_000;
+
+    fn provider(settings: Value) -> Provider {
+        Provider::with_id("p1".into(), "P1".into(), settings, None)
+    }
+
+    fn jwt(exp: i64) -> String {
+        let encode = |value: Value| BASE64_URL_SAFE_NO_PAD.encode(value.to_string());
+        format!(
+            "{}.{}.signature",
+            encode(json!({ "alg": "RS256" })),
+            encode(json!({ "exp": exp }))
+        )
+    }
+
+    #[test]
+    fn placeholder_left_by_takeover_is_flagged() {
+        let result = check_provider_tokens(
+            &AppType::Claude,
+            &provider(json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "PROXY_MANAGED" } })),
+            NOW,
+        );
+        assert_eq!(
+            result,
+            TokenFreshness::Placeholder {
+                field: "env.ANTHROPIC_AUTH_TOKEN".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn empty_or_missing_token_is_flagged() {
+        let result = check_provider_tokens(
+            &AppType::Codex,
+            &provider(json!({ "auth": { "OPENAI_API_KEY": "  " } })),
+            NOW,
+        );
+        assert_eq!(
+            result,
+            TokenFreshness::Empty {
+                field: "auth.OPENAI_API_KEY".to_string()
+            }
+        );
+
+        let result = check_provider_tokens(&AppType::Gemini, &provider(json!({ "env": {} })), NOW);
+        assert_eq!(
+            result,
+            TokenFreshness::Empty {
+                field: "env.GEMINI_API_KEY".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn google_access_token_is_likely_expired() {
+        let result = check_provider_tokens(
+            &AppType::Gemini,
+            &provider(json!({ "env": { "GEMINI_API_KEY": "ya29.a0AfB_byC-example" } })),
+            NOW,
+        );
+        assert!(matches!(
+            result,
+            TokenFreshness::LikelyExpired { ref field, .. } if field == "env.GEMINI_API_KEY"
+        ));
+
+        let result = check_provider_tokens(
+            &AppType::Gemini,
+            &provider(json!({ "env": { "GEMINI_API_KEY": "AIzaSyExampleKey" } })),
+            NOW,
+        );
+        assert_eq!(result, TokenFreshness::Fresh);
+    }
+
+    #[test]
+    fn jwt_expiry_is_checked() {
+        let settings = |token: String| json!({ "auth": { "OPENAI_API_KEY": "sk-live", "tokens": { "access_token": token } } });
+
+        let result =
+            check_provider_tokens(&AppType::Codex, &provider(settings(jwt(NOW - 60))), NOW);
+        assert!(matches!(
+            result,
+            TokenFreshness::LikelyExpired { ref field, .. } if field == "auth.tokens.access_token"
+        ));
+
+        let result =
+            check_provider_tokens(&AppType::Codex, &provider(settings(jwt(NOW + 3600))), NOW);
+        assert_eq!(result, TokenFreshness::Fresh);
+    }
+}
@@ -0,0 +1,302 @@
+//! Provider base_url normalization
+//!
+//! 批量检查所有供应商的 base_url，只修正含义明确的写法：
+//! - 重复的版本段（`/v1/v1` → `/v1`）
+//! - 缺少协议头的域名（`relay.example` → `https://relay.example`）
+//! - 末尾多余的 `/` 与首尾空白
+//!
+//! 单个结尾 `/v1` 是否多余取决于中转站，不做改动；localhost、IP、带端口的地址
+//! 可能是 http 服务，缺少协议头时同样保持原样。
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::codex_config::{resolve_codex_base_url, update_codex_toml_field};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// 一条 base_url 修正建议
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlChange {
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 对应的配置字段（如 `env.ANTHROPIC_BASE_URL`）
+    pub field: String,
+    pub before: String,
+    pub after: String,
+    /// 命中的规则：`whitespace`、`missing_scheme`、`duplicate_v1`、`trailing_slash`
+    pub fixes: Vec<String>,
+    /// 供应商已冻结，修正不会被应用
+    pub frozen: bool,
+}
+
+impl ProviderService {
+    /// Detect (and unless `dry_run`, apply) unambiguous base_url fixes for all providers
+    ///
+    /// 修正通过 [`ProviderService::update`] 保存，当前供应商会同步写入 Live 配置；
+    /// 已冻结的供应商只报告建议（`frozen` 为 true），不保存。
+    pub fn normalize_base_urls(
+        state: &AppState,
+        app_type: AppType,
+        dry_run: bool,
+    ) -> Result<Vec<UrlChange>, AppError> {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let mut changes = Vec::new();
+
+        for provider in providers.into_values() {
+            let Some((field, before)) = read_base_url(&app_type, &provider) else {
+                continue;
+            };
+            let Some((after, fixes)) = normalize_base_url(&before) else {
+                continue;
+            };
+            let Some(updated) = write_base_url(&app_type, &provider, &after) else {
+                log::warn!(
+                    "[{}] 供应商 {} 的 base_url 无法安全改写，跳过: {before}",
+                    app_type.as_str(),
+                    provider.id
+                );
+                continue;
+            };
+
+            changes.push(UrlChange {
+                provider_id: provider.id.clone(),
+                provider_name: provider.name.clone(),
+                field: field.to_string(),
+                before,
+                after,
+                fixes: fixes.into_iter().map(str::to_string).collect(),
+                frozen: provider.is_frozen(),
+            });
+            if provider.is_frozen() {
+                log::info!(
+                    "[{}] 供应商 {} 已冻结，跳过 base_url 修正",
+                    app_type.as_str(),
+                    provider.id
+                );
+            } else if !dry_run {
+                Self::update(state, app_type.clone(), updated)?;
+            }
+        }
+
+        changes.sort_by(|a, b| a.provider_id.cmp(&b.provider_id));
+        Ok(changes)
+    }
+}
+
+/// 各应用的 base_url 字段路径（Codex 的 base_url 在 config.toml 文本中）
+fn base_url_field(app_type: &AppType) -> &'static str {
+    match app_type {
+        AppType::Claude => "env.ANTHROPIC_BASE_URL",
+        AppType::Codex => "config.base_url",
+        AppType::Gemini => "env.GOOGLE_GEMINI_BASE_URL",
+        AppType::OpenCode => "options.baseURL",
+        AppType::OpenClaw => "baseUrl",
+    }
+}
+
+pub(super) fn read_base_url(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Option<(&'static str, String)> {
+    let field = base_url_field(app_type);
+    let settings = &provider.settings_config;
+    let url = match app_type {
+        AppType::Codex => settings
+            .get("config")
+            .and_then(Value::as_str)
+            .and_then(|config| resolve_codex_base_url(config).ok())?,
+        _ => field
+            .split('.')
+            .try_fold(settings, |value, key| value.get(key))?
+            .as_str()?
+            .to_string(),
+    };
+    Some((field, url))
+}
+
+/// 返回写入新 base_url 后的供应商；无法确认改写位置时返回 None
+fn write_base_url(app_type: &AppType, provider: &Provider, url: &str) -> Option<Provider> {
+    let mut updated = provider.clone();
+    let settings = &mut updated.settings_config;
+    match app_type {
+        AppType::Codex => {
+            let config = settings.get("config")?.as_str()?;
+            let rewritten = update_codex_toml_field(config, "base_url", url).ok()?;
+            // 未指定 model_provider 时写入位置可能与实际生效的 base_url 不一致
+            if resolve_codex_base_url(&rewritten).ok()? != url {
+                return None;
+            }
+            settings["config"] = Value::String(rewritten);
+        }
+        _ => {
+            let mut target = settings;
+            for key in base_url_field(app_type).split('.') {
+                target = target.get_mut(key)?;
+            }
+            *target = Value::String(url.to_string());
+        }
+    }
+    Some(updated)
+}
+
+/// 计算规范化后的 base_url，无需修改或无法确定正确写法时返回 None
+fn normalize_base_url(url: &str) -> Option<(String, Vec<&'static str>)> {
+    let trimmed = url.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let mut fixes = Vec::new();
+    if trimmed != url {
+        fixes.push("whitespace");
+    }
+    let (scheme, rest) = match trimmed.split_once("://") {
+        Some((scheme, rest)) if scheme == "http" || scheme == "https" => (scheme, rest),
+        Some(_) => return None,
+        None => {
+            let host = trimmed.split('/').next().unwrap_or_default();
+            if !is_public_domain(host) {
+                return None;
+            }
+            fixes.push("missing_scheme");
+            ("https", trimmed)
+        }
+    };
+
+    if rest.contains(['?', '#']) {
+        return None;
+    }
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    if host.is_empty() {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        if segment == "v1" && segments.last() == Some(&"v1") {
+            if !fixes.contains(&"duplicate_v1") {
+                fixes.push("duplicate_v1");
+            }
+            continue;
+        }
+        segments.push(segment);
+    }
+    while segments.last() == Some(&"") {
+        segments.pop();
+    }
+    if segments.iter().any(|segment| segment.is_empty()) {
+        // 路径中间的 `//` 含义不明，整体保持原样
+        return None;
+    }
+    if rest.ends_with('/') {
+        fixes.push("trailing_slash");
+    }
+
+    let normalized = if segments.is_empty() {
+        format!("{scheme}://{host}")
+    } else {
+        format!("{scheme}://{host}/{}", segments.join("/"))
+    };
+    (normalized != url).then_some((normalized, fixes))
+}
+
+/// 看起来是公网域名（非 localhost、非 IP、不带端口），默认使用 https
+fn is_public_domain(host: &str) -> bool {
+    !host.is_empty()
+        && host.contains('.')
+        && !host.contains([':', '@', '[', ']'])
+        && host.parse::<std::net::Ipv4Addr>().is_err()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fix(url: &str) -> Option<(String, Vec<&'static str>)> {
+        normalize_base_url(url)
+    }
+
+    #[test]
+    fn duplicate_v1_is_collapsed() {
+        assert_eq!(
+            fix("https://relay.example/v1/v1"),
+            Some(("https://relay.example/v1".to_string(), vec!["duplicate_v1"]))
+        );
+        assert_eq!(
+            fix("https://relay.example/api/v1/v1/"),
+            Some((
+                "https://relay.example/api/v1".to_string(),
+                vec!["duplicate_v1", "trailing_slash"]
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_scheme_defaults_to_https_for_domains() {
+        assert_eq!(
+            fix("relay.example/v1"),
+            Some((
+                "https://relay.example/v1".to_string(),
+                vec!["missing_scheme"]
+            ))
+        );
+    }
+
+    #[test]
+    fn trailing_slash_is_removed() {
+        assert_eq!(
+            fix("https://relay.example/"),
+            Some(("https://relay.example".to_string(), vec!["trailing_slash"]))
+        );
+        assert_eq!(
+            fix("http://127.0.0.1:8080/v1//"),
+            Some((
+                "http://127.0.0.1:8080/v1".to_string(),
+                vec!["trailing_slash"]
+            ))
+        );
+    }
+
+    #[test]
+    fn ambiguous_urls_are_left_alone() {
+        // 单个结尾 /v1：有的中转站需要，有的不需要
+        assert_eq!(fix("https://relay.example/v1"), None);
+        // 本地服务可能只提供 http
+        assert_eq!(fix("localhost:3000/v1"), None);
+        assert_eq!(fix("192.168.1.10/v1"), None);
+        // 路径中间的空段、查询参数、非 http 协议
+        assert_eq!(fix("https://relay.example//v1"), None);
+        assert_eq!(fix("https://relay.example/v1/?key=1"), None);
+        assert_eq!(fix("ws://relay.example/"), None);
+    }
+
+    #[test]
+    fn codex_base_url_is_rewritten_in_active_model_provider() {
+        let provider = Provider::with_id(
+            "c".to_string(),
+            "C".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk" },
+                "config": "model_provider = \"relay\"\n\n[model_providers.relay]\nbase_url = \"https://relay.example/v1/v1\"\n"
+            }),
+            None,
+        );
+        let (field, before) = read_base_url(&AppType::Codex, &provider).unwrap();
+        assert_eq!(field, "config.base_url");
+        let (after, _) = normalize_base_url(&before).unwrap();
+        let updated = write_base_url(&AppType::Codex, &provider, &after).unwrap();
+        assert_eq!(
+            read_base_url(&AppType::Codex, &updated).unwrap().1,
+            "https://relay.example/v1"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Provider connection warm-up
+//!
+//! 切换到新的中转站后，第一个请求需要承担 DNS 解析与 TLS 握手的开销。
+//! 预热向 base_url 发送一个 HEAD 请求，使全局 reqwest 客户端的连接池中留下可复用的连接；
+//! 只要收到任意 HTTP 响应（包括 404/405）即视为成功，并在健康状态中记录 `warmed_at`。
+//!
+//! 预热只能填充 cc-switch 自身的连接池，因此切换后的自动预热（`warmUpOnSwitch`）
+//! 仅在该应用处于代理接管时进行：未接管时 CLI 直连供应商，不经过这里的连接池。
+//! 配置了单独代理的供应商每次请求都会新建客户端，同样跳过；
+//! 手动预热对这些供应商仍可用来验证连通性。
+
+use std::time::Duration;
+
+use super::url_normalize::read_base_url;
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::provider_tls::ProviderTls;
+use crate::store::AppState;
+
+/// 预热请求超时
+const WARM_UP_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl ProviderService {
+    /// 切换供应商后的自动预热，不满足条件（见模块文档）时跳过并返回 false
+    pub async fn warm_up_on_switch(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<bool, AppError> {
+        let taken_over = state
+            .db
+            .get_proxy_config_for_app(app_type.as_str())
+            .await
+            .map(|config| config.enabled)
+            .unwrap_or(false);
+        if !taken_over {
+            log::debug!("[{}] 未接管 Live 配置，跳过切换后预热", app_type.as_str());
+            return Ok(false);
+        }
+
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?;
+        if provider.as_ref().is_some_and(uses_dedicated_client) {
+            log::debug!(
+                "[{}] 供应商 {provider_id} 使用独立的 HTTP 客户端，跳过切换后预热",
+                app_type.as_str()
+            );
+            return Ok(false);
+        }
+
+        Self::warm_up(state, app_type, provider_id).await
+    }
+
+    /// Prime the HTTP connection pool for a provider's base_url
+    ///
+    /// 返回是否预热成功；网络错误只记录警告，不影响供应商健康状态。
+    pub async fn warm_up(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<bool, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+        let base_url = read_base_url(&app_type, &provider)
+            .map(|(_, url)| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.warm_up.no_base_url",
+                    format!("供应商 {provider_id} 未配置 base_url，无法预热"),
+                    format!("Provider {provider_id} has no base_url to warm up"),
+                )
+            })?;
+
+        // 与转发请求使用同一个客户端，TLS 设置的供应商才能预热到缓存的连接池
+        let client = crate::proxy::http_client::get_for_provider_meta(provider.meta.as_ref())
+            .map_err(|e| {
+                AppError::localized(
+                    "provider.warm_up.client",
+                    format!("创建 HTTP 客户端失败: {e}"),
+                    format!("Failed to create HTTP client: {e}"),
+                )
+            })?;
+        match client.head(&base_url).timeout(WARM_UP_TIMEOUT).send().await {
+            Ok(response) => {
+                log::debug!(
+                    "[{}] 供应商 {provider_id} 预热完成 (HTTP {})",
+                    app_type.as_str(),
+                    response.status().as_u16()
+                );
+                state
+                    .db
+                    .record_provider_warm_up(provider_id, app_type.as_str())
+                    .await?;
+                Ok(true)
+            }
+            Err(e) => {
+                log::warn!("[{}] 供应商 {provider_id} 预热失败: {e}", app_type.as_str());
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// 供应商的请求是否使用每次新建的专用客户端（单独代理且没有 TLS 设置）
+///
+/// 带 TLS 设置的客户端按（TLS 要求, 代理 URL）缓存，见 `http_client::get_for_provider_meta`。
+fn uses_dedicated_client(provider: &Provider) -> bool {
+    let meta = provider.meta.as_ref();
+    meta.and_then(|m| m.proxy_config.as_ref())
+        .is_some_and(|config| config.enabled)
+        && matches!(ProviderTls::from_meta(meta), Ok(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use axum::{http::StatusCode, routing::head, Router};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    async fn spawn_upstream(hits: Arc<AtomicUsize>) -> String {
+        let router = Router::new().route(
+            "/api",
+            head(move || {
+                let hits = hits.clone();
+                async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    StatusCode::METHOD_NOT_ALLOWED
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind upstream");
+        let addr = listener.local_addr().expect("upstream addr");
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.ok();
+        });
+        format!("http://{addr}/api")
+    }
+
+    fn setup(base_url: &str) -> AppState {
+        let db = Arc::new(Database::memory().expect("init db"));
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": base_url,
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test"
+                }
+            }),
+            None,
+        );
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        AppState::new(db)
+    }
+
+    #[tokio::test]
+    async fn warm_up_sends_request_and_records_timestamp() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = spawn_upstream(hits.clone()).await;
+        let state = setup(&base_url);
+
+        let before = state
+            .db
+            .get_provider_health("p1", "claude")
+            .await
+            .expect("health before");
+        assert_eq!(before.warmed_at, None);
+
+        let warmed = ProviderService::warm_up(&state, AppType::Claude, "p1")
+            .await
+            .expect("warm up");
+
+        assert!(warmed, "any HTTP response counts as a warm connection");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        let health = state
+            .db
+            .get_provider_health("p1", "claude")
+            .await
+            .expect("health after");
+        assert!(health.warmed_at.is_some());
+        assert!(health.is_healthy);
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn warm_up_on_switch_requires_takeover() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = spawn_upstream(hits.clone()).await;
+        let state = setup(&base_url);
+
+        let warmed = ProviderService::warm_up_on_switch(&state, AppType::Claude, "p1")
+            .await
+            .expect("warm up without takeover");
+        assert!(
+            !warmed,
+            "CLI talks to the provider directly without takeover"
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let mut config = state
+            .db
+            .get_proxy_config_for_app("claude")
+            .await
+            .expect("proxy config");
+        config.enabled = true;
+        state
+            .db
+            .update_proxy_config_for_app(config)
+            .await
+            .expect("enable takeover");
+
+        let warmed = ProviderService::warm_up_on_switch(&state, AppType::Claude, "p1")
+            .await
+            .expect("warm up with takeover");
+        assert!(warmed);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn warm_up_failure_keeps_health_untouched() {
+        // 绑定后立即释放端口，连接会被拒绝
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        drop(listener);
+        let state = setup(&format!("http://{addr}"));
+
+        let warmed = ProviderService::warm_up(&state, AppType::Claude, "p1")
+            .await
+            .expect("warm up");
+
+        assert!(!warmed);
+        let health = state
+            .db
+            .get_provider_health("p1", "claude")
+            .await
+            .expect("health");
+        assert_eq!(health.warmed_at, None);
+        assert_eq!(health.consecutive_failures, 0);
+    }
+}
@@ -5,19 +5,27 @@
 use crate::app_config::AppType;
 use crate::config::{get_claude_settings_path, read_json_file, write_json_file};
 use crate::database::Database;
+use crate::error::AppError;
 use crate::provider::Provider;
+use crate::proxy::handler_context::BENCHMARK_HEADER;
+use crate::proxy::provider_router::ProviderRouter;
+use crate::proxy::providers::get_adapter;
+use crate::proxy::request_feed::RequestFeed;
 use crate::proxy::server::ProxyServer;
 use crate::proxy::types::*;
 use crate::services::provider::{
-    build_effective_settings_with_common_config, write_live_with_common_config,
+    build_effective_settings_with_common_config, record_live_fingerprint,
+    write_live_with_common_config,
 };
+use crate::services::stream_check::StreamCheckService;
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// 用于接管 Live 配置时的占位符（避免客户端提示缺少 key，同时不泄露真实 Token）
-const PROXY_TOKEN_PLACEHOLDER: &str = "PROXY_MANAGED";
+pub(crate) const PROXY_TOKEN_PLACEHOLDER: &str = "PROXY_MANAGED";
 
 /// 代理接管模式下需要从 Claude Live 配置中移除的"模型覆盖"字段。
 ///
@@ -33,12 +41,48 @@ const CLAUDE_MODEL_OVERRIDE_ENV_KEYS: [&str; 6] = [
     "ANTHROPIC_SMALL_FAST_MODEL",
 ];
 
+/// 启动时的代理恢复计划
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct StartupProxyPlan {
+    /// 是否直接启动代理服务器（即使没有应用需要接管）
+    pub start_server: bool,
+    /// 需要重新接管 Live 配置的应用
+    pub takeover_apps: Vec<String>,
+}
+
+impl StartupProxyPlan {
+    /// 根据持久化状态决定启动时如何恢复代理
+    ///
+    /// - `proxy_config.enabled` 为 true 的应用始终重新接管（接管会自动启动代理），
+    ///   否则前端会显示已接管但实际未接管
+    /// - 开启 `auto_start_proxy` 时额外启动代理服务器；但上次存在接管残留（异常退出）时跳过，
+    ///   避免在未知状态下反复启动
+    pub fn decide(
+        auto_start_proxy: bool,
+        recovered_from_crash: bool,
+        enabled_apps: &[&str],
+    ) -> Self {
+        Self {
+            start_server: auto_start_proxy && !recovered_from_crash,
+            takeover_apps: enabled_apps.iter().map(|app| app.to_string()).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.start_server && self.takeover_apps.is_empty()
+    }
+}
+
 #[derive(Clone)]
 pub struct ProxyService {
     db: Arc<Database>,
     server: Arc<RwLock<Option<ProxyServer>>>,
     /// AppHandle，用于传递给 ProxyServer 以支持故障转移时的 UI 更新
     app_handle: Arc<RwLock<Option<tauri::AppHandle>>>,
+    /// 实时请求流（订阅状态跨代理重启保持）
+    request_feed: Arc<RequestFeed>,
+    /// Gemini OAuth token 定时刷新任务（随代理启停）
+    gemini_token_refresher: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
 }
 
 impl ProxyService {
@@ -47,6 +91,8 @@ impl ProxyService {
             db,
             server: Arc::new(RwLock::new(None)),
             app_handle: Arc::new(RwLock::new(None)),
+            request_feed: Arc::new(RequestFeed::default()),
+            gemini_token_refresher: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -77,13 +123,33 @@ impl ProxyService {
 
     /// 设置 AppHandle（在应用初始化时调用）
     pub fn set_app_handle(&self, handle: tauri::AppHandle) {
+        self.request_feed.forward_to(handle.clone());
         futures::executor::block_on(async {
             *self.app_handle.write().await = Some(handle);
         });
     }
 
+    /// 启动前检查监听端口是否可用
+    ///
+    /// 代理已在运行时跳过（端口由自身占用）。接管流程会先改写 Live 配置，
+    /// 因此必须在任何改动之前确认端口可绑定，避免端口冲突时白白接管再回滚。
+    async fn ensure_listen_port_available(&self) -> Result<(), String> {
+        if self.server.read().await.is_some() {
+            return Ok(());
+        }
+        let config = self
+            .db
+            .get_proxy_config()
+            .await
+            .map_err(|e| format!("获取代理配置失败: {e}"))?;
+        check_port_available(&config.listen_address, config.listen_port).map_err(String::from)
+    }
+
     /// 启动代理服务器
     pub async fn start(&self) -> Result<ProxyServerInfo, String> {
+        // 0. 端口被占用时直接给出明确提示，不改动任何状态
+        self.ensure_listen_port_available().await?;
+
         // 1. 启动时自动设置 proxy_enabled = true
         let mut global_config = self
             .db
@@ -112,6 +178,7 @@ impl ProxyService {
             return Ok(ProxyServerInfo {
                 address: status.address,
                 port: status.port,
+                socket_path: server.socket_path().await,
                 // 无法精确取回首次启动时间，返回当前时间用于 UI 展示即可
                 started_at: chrono::Utc::now().to_rfc3339(),
             });
@@ -119,7 +186,8 @@ impl ProxyService {
 
         // 4. 创建并启动服务器
         let app_handle = self.app_handle.read().await.clone();
-        let server = ProxyServer::new(config.clone(), self.db.clone(), app_handle);
+        let server = ProxyServer::new(config.clone(), self.db.clone(), app_handle)
+            .with_request_feed(self.request_feed.clone());
         let info = server
             .start()
             .await
@@ -127,6 +195,7 @@ impl ProxyService {
 
         // 5. 保存服务器实例
         *self.server.write().await = Some(server);
+        self.start_gemini_token_refresh().await;
 
         log::info!("代理服务器已启动: {}:{}", info.address, info.port);
         Ok(info)
@@ -134,6 +203,9 @@ impl ProxyService {
 
     /// 启动代理服务器（带 Live 配置接管）
     pub async fn start_with_takeover(&self) -> Result<ProxyServerInfo, String> {
+        // 0. 先确认端口可用，再改动 Live 配置
+        self.ensure_listen_port_available().await?;
+
         // 1. 备份各应用的 Live 配置
         self.backup_live_configs().await?;
 
@@ -627,8 +699,32 @@ impl ProxyService {
         Ok(())
     }
 
+    /// 启动 Gemini OAuth token 定时刷新
+    ///
+    /// 代理接管后空闲期间 access_token 可能过期，定期在过期前用 refresh_token 换新。
+    async fn start_gemini_token_refresh(&self) {
+        let mut refresher = self.gemini_token_refresher.write().await;
+        if refresher.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let db = self.db.clone();
+        *refresher = Some(tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(crate::proxy::gemini_token_refresh::REFRESH_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now_ms = chrono::Utc::now().timestamp_millis();
+                crate::proxy::gemini_token_refresh::refresh_expiring_gemini_tokens(&db, now_ms)
+                    .await;
+            }
+        }));
+    }
+
     /// 停止代理服务器
     pub async fn stop(&self) -> Result<(), String> {
+        if let Some(task) = self.gemini_token_refresher.write().await.take() {
+            task.abort();
+        }
         if let Some(server) = self.server.write().await.take() {
             server
                 .stop()
@@ -801,7 +897,7 @@ impl ProxyService {
     }
 
     /// 构造写入 Live 的代理地址（处理 0.0.0.0 / IPv6 等特殊情况）
-    async fn build_proxy_urls(&self) -> Result<(String, String), String> {
+    pub(crate) async fn build_proxy_urls(&self) -> Result<(String, String), String> {
         let config = self
             .db
             .get_proxy_config()
@@ -835,82 +931,28 @@ impl ProxyService {
     /// - `/v1/chat/completions`, `/v1/responses` → Codex
     /// - `/v1beta/*` → Gemini
     ///
-    /// 因此不需要在 URL 中添加应用前缀。
+    /// 因此不需要在 URL 中添加应用前缀。`/v1/chat/completions` 也可能来自 Claude 兼容客户端，
+    /// 由 `x-cc-app` 头、认证头风格与 `target_app` 配置区分。
     async fn takeover_live_configs(&self) -> Result<(), String> {
         let (proxy_url, proxy_codex_base_url) = self.build_proxy_urls().await?;
 
         // Claude: 修改 ANTHROPIC_BASE_URL，使用占位符替代真实 Token（代理会注入真实 Token）
         if let Ok(mut live_config) = self.read_claude_live() {
-            if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                env.insert("ANTHROPIC_BASE_URL".to_string(), json!(&proxy_url));
-                // 关键：接管模式下移除模型覆盖字段，避免切换供应商后仍用旧模型名发起请求
-                for key in CLAUDE_MODEL_OVERRIDE_ENV_KEYS {
-                    env.remove(key);
-                }
-                // 仅覆盖已存在的 Token 字段，避免新增字段导致用户困惑；
-                // 若完全没有 Token 字段，则写入 ANTHROPIC_AUTH_TOKEN 占位符用于避免客户端警告。
-                let token_keys = [
-                    "ANTHROPIC_AUTH_TOKEN",
-                    "ANTHROPIC_API_KEY",
-                    "OPENROUTER_API_KEY",
-                    "OPENAI_API_KEY",
-                ];
-
-                let mut replaced_any = false;
-                for key in token_keys {
-                    if env.contains_key(key) {
-                        env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                        replaced_any = true;
-                    }
-                }
-
-                if !replaced_any {
-                    env.insert(
-                        "ANTHROPIC_AUTH_TOKEN".to_string(),
-                        json!(PROXY_TOKEN_PLACEHOLDER),
-                    );
-                }
-            } else {
-                live_config["env"] = json!({
-                    "ANTHROPIC_BASE_URL": &proxy_url,
-                    "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
-                });
-            }
+            Self::apply_claude_takeover(&mut live_config, &proxy_url);
             self.write_claude_live(&live_config)?;
             log::info!("Claude Live 配置已接管，代理地址: {proxy_url}");
         }
 
         // Codex: 修改 config.toml 的 base_url，auth.json 的 OPENAI_API_KEY（代理会注入真实 Token）
         if let Ok(mut live_config) = self.read_codex_live() {
-            // 1. 修改 auth.json 中的 OPENAI_API_KEY（使用占位符）
-            if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut()) {
-                auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-            }
-
-            // 2. 修改 config.toml 中的 base_url
-            let config_str = live_config
-                .get("config")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let updated_config = Self::update_toml_base_url(config_str, &proxy_codex_base_url);
-            live_config["config"] = json!(updated_config);
-
+            Self::apply_codex_takeover(&mut live_config, &proxy_codex_base_url);
             self.write_codex_live(&live_config)?;
             log::info!("Codex Live 配置已接管，代理地址: {proxy_codex_base_url}");
         }
 
         // Gemini: 修改 GOOGLE_GEMINI_BASE_URL，使用占位符替代真实 Token（代理会注入真实 Token）
         if let Ok(mut live_config) = self.read_gemini_live() {
-            if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&proxy_url));
-                // 使用占位符，避免显示缺少 key 的警告
-                env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-            } else {
-                live_config["env"] = json!({
-                    "GOOGLE_GEMINI_BASE_URL": &proxy_url,
-                    "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
-                });
-            }
+            Self::apply_gemini_takeover(&mut live_config, &proxy_url);
             self.write_gemini_live(&live_config)?;
             log::info!("Gemini Live 配置已接管，代理地址: {proxy_url}");
         }
@@ -925,74 +967,19 @@ impl ProxyService {
         match app_type {
             AppType::Claude => {
                 let mut live_config = self.read_claude_live()?;
-                if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                    env.insert("ANTHROPIC_BASE_URL".to_string(), json!(&proxy_url));
-                    // 关键：接管模式下移除模型覆盖字段，避免切换供应商后仍用旧模型名发起请求
-                    for key in CLAUDE_MODEL_OVERRIDE_ENV_KEYS {
-                        env.remove(key);
-                    }
-
-                    let token_keys = [
-                        "ANTHROPIC_AUTH_TOKEN",
-                        "ANTHROPIC_API_KEY",
-                        "OPENROUTER_API_KEY",
-                        "OPENAI_API_KEY",
-                    ];
-
-                    let mut replaced_any = false;
-                    for key in token_keys {
-                        if env.contains_key(key) {
-                            env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                            replaced_any = true;
-                        }
-                    }
-
-                    if !replaced_any {
-                        env.insert(
-                            "ANTHROPIC_AUTH_TOKEN".to_string(),
-                            json!(PROXY_TOKEN_PLACEHOLDER),
-                        );
-                    }
-                } else {
-                    live_config["env"] = json!({
-                        "ANTHROPIC_BASE_URL": &proxy_url,
-                        "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
-                    });
-                }
-
+                Self::apply_claude_takeover(&mut live_config, &proxy_url);
                 self.write_claude_live(&live_config)?;
                 log::info!("Claude Live 配置已接管，代理地址: {proxy_url}");
             }
             AppType::Codex => {
                 let mut live_config = self.read_codex_live()?;
-
-                if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut()) {
-                    auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                }
-
-                let config_str = live_config
-                    .get("config")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let updated_config = Self::update_toml_base_url(config_str, &proxy_codex_base_url);
-                live_config["config"] = json!(updated_config);
-
+                Self::apply_codex_takeover(&mut live_config, &proxy_codex_base_url);
                 self.write_codex_live(&live_config)?;
                 log::info!("Codex Live 配置已接管，代理地址: {proxy_codex_base_url}");
             }
             AppType::Gemini => {
                 let mut live_config = self.read_gemini_live()?;
-
-                if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                    env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&proxy_url));
-                    env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                } else {
-                    live_config["env"] = json!({
-                        "GOOGLE_GEMINI_BASE_URL": &proxy_url,
-                        "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
-                    });
-                }
-
+                Self::apply_gemini_takeover(&mut live_config, &proxy_url);
                 self.write_gemini_live(&live_config)?;
                 log::info!("Gemini Live 配置已接管，代理地址: {proxy_url}");
             }
@@ -1016,74 +1003,19 @@ impl ProxyService {
         match app_type {
             AppType::Claude => {
                 if let Ok(mut live_config) = self.read_claude_live() {
-                    if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                        env.insert("ANTHROPIC_BASE_URL".to_string(), json!(&proxy_url));
-                        // 关键：接管模式下移除模型覆盖字段，避免切换供应商后仍用旧模型名发起请求
-                        for key in CLAUDE_MODEL_OVERRIDE_ENV_KEYS {
-                            env.remove(key);
-                        }
-
-                        let token_keys = [
-                            "ANTHROPIC_AUTH_TOKEN",
-                            "ANTHROPIC_API_KEY",
-                            "OPENROUTER_API_KEY",
-                            "OPENAI_API_KEY",
-                        ];
-
-                        let mut replaced_any = false;
-                        for key in token_keys {
-                            if env.contains_key(key) {
-                                env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                                replaced_any = true;
-                            }
-                        }
-
-                        if !replaced_any {
-                            env.insert(
-                                "ANTHROPIC_AUTH_TOKEN".to_string(),
-                                json!(PROXY_TOKEN_PLACEHOLDER),
-                            );
-                        }
-                    } else {
-                        live_config["env"] = json!({
-                            "ANTHROPIC_BASE_URL": &proxy_url,
-                            "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
-                        });
-                    }
-
+                    Self::apply_claude_takeover(&mut live_config, &proxy_url);
                     let _ = self.write_claude_live(&live_config);
                 }
             }
             AppType::Codex => {
                 if let Ok(mut live_config) = self.read_codex_live() {
-                    if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut())
-                    {
-                        auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                    }
-
-                    let config_str = live_config
-                        .get("config")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("");
-                    let updated_config =
-                        Self::update_toml_base_url(config_str, &proxy_codex_base_url);
-                    live_config["config"] = json!(updated_config);
-
+                    Self::apply_codex_takeover(&mut live_config, &proxy_codex_base_url);
                     let _ = self.write_codex_live(&live_config);
                 }
             }
             AppType::Gemini => {
                 if let Ok(mut live_config) = self.read_gemini_live() {
-                    if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
-                        env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(&proxy_url));
-                        env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
-                    } else {
-                        live_config["env"] = json!({
-                            "GOOGLE_GEMINI_BASE_URL": &proxy_url,
-                            "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
-                        });
-                    }
-
+                    Self::apply_gemini_takeover(&mut live_config, &proxy_url);
                     let _ = self.write_gemini_live(&live_config);
                 }
             }
@@ -1098,6 +1030,72 @@ impl ProxyService {
         Ok(())
     }
 
+    /// Claude：写入代理地址，Token 字段替换为占位符
+    fn apply_claude_takeover(live_config: &mut Value, proxy_url: &str) {
+        if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
+            env.insert("ANTHROPIC_BASE_URL".to_string(), json!(proxy_url));
+            // 关键：接管模式下移除模型覆盖字段，避免切换供应商后仍用旧模型名发起请求
+            for key in CLAUDE_MODEL_OVERRIDE_ENV_KEYS {
+                env.remove(key);
+            }
+            // 仅覆盖已存在的 Token 字段，避免新增字段导致用户困惑；
+            // 若完全没有 Token 字段，则写入 ANTHROPIC_AUTH_TOKEN 占位符用于避免客户端警告。
+            let token_keys = [
+                "ANTHROPIC_AUTH_TOKEN",
+                "ANTHROPIC_API_KEY",
+                "OPENROUTER_API_KEY",
+                "OPENAI_API_KEY",
+            ];
+
+            let mut replaced_any = false;
+            for key in token_keys {
+                if env.contains_key(key) {
+                    env.insert(key.to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+                    replaced_any = true;
+                }
+            }
+
+            if !replaced_any {
+                env.insert(
+                    "ANTHROPIC_AUTH_TOKEN".to_string(),
+                    json!(PROXY_TOKEN_PLACEHOLDER),
+                );
+            }
+        } else {
+            live_config["env"] = json!({
+                "ANTHROPIC_BASE_URL": proxy_url,
+                "ANTHROPIC_AUTH_TOKEN": PROXY_TOKEN_PLACEHOLDER
+            });
+        }
+    }
+
+    /// Codex：修改 config.toml 的 base_url，auth.json 的 OPENAI_API_KEY 替换为占位符
+    fn apply_codex_takeover(live_config: &mut Value, proxy_codex_base_url: &str) {
+        if let Some(auth) = live_config.get_mut("auth").and_then(|v| v.as_object_mut()) {
+            auth.insert("OPENAI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+        }
+
+        let config_str = live_config
+            .get("config")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let updated_config = Self::update_toml_base_url(config_str, proxy_codex_base_url);
+        live_config["config"] = json!(updated_config);
+    }
+
+    /// Gemini：写入代理地址，GEMINI_API_KEY 替换为占位符（避免显示缺少 key 的警告）
+    fn apply_gemini_takeover(live_config: &mut Value, proxy_url: &str) {
+        if let Some(env) = live_config.get_mut("env").and_then(|v| v.as_object_mut()) {
+            env.insert("GOOGLE_GEMINI_BASE_URL".to_string(), json!(proxy_url));
+            env.insert("GEMINI_API_KEY".to_string(), json!(PROXY_TOKEN_PLACEHOLDER));
+        } else {
+            live_config["env"] = json!({
+                "GOOGLE_GEMINI_BASE_URL": proxy_url,
+                "GEMINI_API_KEY": PROXY_TOKEN_PLACEHOLDER
+            });
+        }
+    }
+
     /// 恢复指定应用的 Live 配置（若无备份则不做任何操作）
     async fn restore_live_config_for_app(&self, app_type: &AppType) -> Result<(), String> {
         match app_type {
@@ -1393,6 +1391,46 @@ impl ProxyService {
         Ok(status.claude || status.codex || status.gemini)
     }
 
+    /// 将代理恢复为出厂状态（用于排障支持）
+    ///
+    /// 停止服务器、恢复 Live 配置、清除接管标志与备份，把 proxy_config 重置为默认值，
+    /// 并清空健康状态。熔断器只存在于运行中的服务器内存里，停止服务器即随之清空。
+    /// 可重复调用，代理未运行时同样安全。
+    pub async fn factory_reset_proxy(&self) -> Result<(), String> {
+        // 1. 停止代理服务器
+        if self.is_running().await {
+            self.stop().await?;
+        }
+
+        // 2. 恢复原始 Live 配置（无备份时按接管占位符兜底清理）
+        self.restore_live_configs().await?;
+
+        // 3. 清除接管标志并删除备份
+        self.db
+            .set_live_takeover_active(false)
+            .await
+            .map_err(|e| format!("清除接管状态失败: {e}"))?;
+        self.db
+            .delete_all_live_backups()
+            .await
+            .map_err(|e| format!("删除备份失败: {e}"))?;
+
+        // 4. 代理配置恢复默认值（同时关闭各应用的代理与故障转移开关）
+        self.db
+            .reset_proxy_config_to_defaults()
+            .await
+            .map_err(|e| format!("重置代理配置失败: {e}"))?;
+
+        // 5. 清空健康状态
+        self.db
+            .clear_all_provider_health()
+            .await
+            .map_err(|e| format!("重置健康状态失败: {e}"))?;
+
+        log::info!("代理已恢复为默认状态");
+        Ok(())
+    }
+
     /// 从异常退出中恢复（启动时调用）
     ///
     /// 检测到 Live 备份残留时调用此方法。
@@ -1443,7 +1481,7 @@ impl ProxyService {
         false
     }
 
-    fn is_claude_live_taken_over(config: &Value) -> bool {
+    pub(crate) fn is_claude_live_taken_over(config: &Value) -> bool {
         let env = match config.get("env").and_then(|v| v.as_object()) {
             Some(env) => env,
             None => return false,
@@ -1463,7 +1501,7 @@ impl ProxyService {
         false
     }
 
-    fn is_codex_live_taken_over(config: &Value) -> bool {
+    pub(crate) fn is_codex_live_taken_over(config: &Value) -> bool {
         let auth = match config.get("auth").and_then(|v| v.as_object()) {
             Some(auth) => auth,
             None => return false,
@@ -1471,7 +1509,7 @@ impl ProxyService {
         auth.get("OPENAI_API_KEY").and_then(|v| v.as_str()) == Some(PROXY_TOKEN_PLACEHOLDER)
     }
 
-    fn is_gemini_live_taken_over(config: &Value) -> bool {
+    pub(crate) fn is_gemini_live_taken_over(config: &Value) -> bool {
         let env = match config.get("env").and_then(|v| v.as_object()) {
             Some(env) => env,
             None => return false,
@@ -1660,13 +1698,16 @@ impl ProxyService {
     }
 
     fn read_claude_live(&self) -> Result<Value, String> {
-        let path = get_claude_settings_path();
+        Self::read_claude_live_at(&get_claude_settings_path())
+    }
+
+    fn read_claude_live_at(path: &Path) -> Result<Value, String> {
         if !path.exists() {
             return Err("Claude 配置文件不存在".to_string());
         }
 
         let mut value: Value =
-            read_json_file(&path).map_err(|e| format!("读取 Claude 配置失败: {e}"))?;
+            read_json_file(path).map_err(|e| format!("读取 Claude 配置失败: {e}"))?;
 
         if value.is_null() {
             value = json!({});
@@ -1691,25 +1732,39 @@ impl ProxyService {
     }
 
     fn write_claude_live(&self, config: &Value) -> Result<(), String> {
-        let path = get_claude_settings_path();
+        Self::write_claude_live_at(&get_claude_settings_path(), config)?;
+        self.refresh_live_fingerprint(&AppType::Claude);
+        Ok(())
+    }
+
+    /// 接管/恢复写入 Live 后刷新指纹，避免被误判为 CC Switch 之外的修改
+    fn refresh_live_fingerprint(&self, app_type: &AppType) {
+        if let Err(e) = record_live_fingerprint(self.db.as_ref(), app_type) {
+            log::warn!("记录 {} live 配置指纹失败: {e}", app_type.as_str());
+        }
+    }
+
+    fn write_claude_live_at(path: &Path, config: &Value) -> Result<(), String> {
         let settings = crate::services::provider::sanitize_claude_settings_for_live(config);
-        write_json_file(&path, &settings).map_err(|e| format!("写入 Claude 配置失败: {e}"))
+        write_json_file(path, &settings).map_err(|e| format!("写入 Claude 配置失败: {e}"))
     }
 
     fn read_codex_live(&self) -> Result<Value, String> {
         use crate::codex_config::{get_codex_auth_path, get_codex_config_path};
 
-        let auth_path = get_codex_auth_path();
+        Self::read_codex_live_at(&get_codex_auth_path(), &get_codex_config_path())
+    }
+
+    fn read_codex_live_at(auth_path: &Path, config_path: &Path) -> Result<Value, String> {
         if !auth_path.exists() {
             return Err("Codex auth.json 不存在".to_string());
         }
 
         let auth: Value =
-            read_json_file(&auth_path).map_err(|e| format!("读取 Codex auth 失败: {e}"))?;
+            read_json_file(auth_path).map_err(|e| format!("读取 Codex auth 失败: {e}"))?;
 
-        let config_path = get_codex_config_path();
         let config_str = if config_path.exists() {
-            std::fs::read_to_string(&config_path)
+            std::fs::read_to_string(config_path)
                 .map_err(|e| format!("读取 Codex config 失败: {e}"))?
         } else {
             String::new()
@@ -1722,24 +1777,34 @@ impl ProxyService {
     }
 
     fn write_codex_live(&self, config: &Value) -> Result<(), String> {
-        use crate::codex_config::{
-            get_codex_auth_path, get_codex_config_path, write_codex_live_atomic,
-        };
+        use crate::codex_config::{get_codex_auth_path, get_codex_config_path};
+
+        Self::write_codex_live_at(&get_codex_auth_path(), &get_codex_config_path(), config)?;
+        self.refresh_live_fingerprint(&AppType::Codex);
+        Ok(())
+    }
+
+    fn write_codex_live_at(
+        auth_path: &Path,
+        config_path: &Path,
+        config: &Value,
+    ) -> Result<(), String> {
+        use crate::codex_config::write_codex_live_atomic_at;
 
         let auth = config.get("auth");
         let config_str = config.get("config").and_then(|v| v.as_str());
 
         match (auth, config_str) {
-            (Some(auth), Some(cfg)) => write_codex_live_atomic(auth, Some(cfg))
-                .map_err(|e| format!("写入 Codex 配置失败: {e}"))?,
+            (Some(auth), Some(cfg)) => {
+                write_codex_live_atomic_at(auth_path, config_path, auth, Some(cfg))
+                    .map_err(|e| format!("写入 Codex 配置失败: {e}"))?
+            }
             (Some(auth), None) => {
-                let auth_path = get_codex_auth_path();
-                write_json_file(&auth_path, auth)
+                write_json_file(auth_path, auth)
                     .map_err(|e| format!("写入 Codex auth 失败: {e}"))?;
             }
             (None, Some(cfg)) => {
-                let config_path = get_codex_config_path();
-                crate::config::write_text_file(&config_path, cfg)
+                crate::config::write_text_file(config_path, cfg)
                     .map_err(|e| format!("写入 Codex config 失败: {e}"))?;
             }
             (None, None) => {}
@@ -1749,25 +1814,373 @@ impl ProxyService {
     }
 
     fn read_gemini_live(&self) -> Result<Value, String> {
-        use crate::gemini_config::{env_to_json, get_gemini_env_path, read_gemini_env};
+        Self::read_gemini_live_at(&crate::gemini_config::get_gemini_env_path())
+    }
+
+    fn read_gemini_live_at(env_path: &Path) -> Result<Value, String> {
+        use crate::gemini_config::{env_to_json, read_gemini_env_at};
 
-        let env_path = get_gemini_env_path();
         if !env_path.exists() {
             return Err("Gemini .env 文件不存在".to_string());
         }
 
-        let env_map = read_gemini_env().map_err(|e| format!("读取 Gemini env 失败: {e}"))?;
+        let env_map =
+            read_gemini_env_at(env_path).map_err(|e| format!("读取 Gemini env 失败: {e}"))?;
         Ok(env_to_json(&env_map))
     }
 
     fn write_gemini_live(&self, config: &Value) -> Result<(), String> {
-        use crate::gemini_config::{json_to_env, write_gemini_env_atomic};
+        Self::write_gemini_live_at(&crate::gemini_config::get_gemini_env_path(), config)?;
+        self.refresh_live_fingerprint(&AppType::Gemini);
+        Ok(())
+    }
+
+    fn write_gemini_live_at(env_path: &Path, config: &Value) -> Result<(), String> {
+        use crate::gemini_config::{json_to_env, write_gemini_env_atomic_at};
 
         let env_map = json_to_env(config).map_err(|e| format!("转换 Gemini 配置失败: {e}"))?;
-        write_gemini_env_atomic(&env_map).map_err(|e| format!("写入 Gemini env 失败: {e}"))?;
+        write_gemini_env_atomic_at(env_path, &env_map)
+            .map_err(|e| format!("写入 Gemini env 失败: {e}"))?;
+        Ok(())
+    }
+
+    // ==================== 接管自检 ====================
+
+    /// 接管自检：在临时目录中对各应用 Live 配置的副本执行 备份 → 接管 → 恢复
+    ///
+    /// 真实 Live 文件只被读取一次用于复制，之后的读写全部针对副本，备份也只保存在内存中。
+    /// 检查接管后能否识别出 Token 占位符，以及恢复后各文件是否与原文件逐字节一致；
+    /// Live 配置不存在的应用标记为跳过。
+    pub async fn takeover_self_test(&self) -> Result<TakeoverTestReport, AppError> {
+        let (proxy_url, proxy_codex_base_url) =
+            self.build_proxy_urls().await.map_err(AppError::Message)?;
+        let sandbox = tempfile::Builder::new()
+            .prefix("cc-switch-takeover-test-")
+            .tempdir()
+            .map_err(|e| AppError::Message(format!("创建接管自检临时目录失败: {e}")))?;
+
+        let apps: Vec<TakeoverAppTestResult> = [AppType::Claude, AppType::Codex, AppType::Gemini]
+            .iter()
+            .map(|app_type| {
+                Self::takeover_self_test_app(
+                    app_type,
+                    &Self::live_file_paths(app_type),
+                    &sandbox.path().join(app_type.as_str()),
+                    &proxy_url,
+                    &proxy_codex_base_url,
+                )
+            })
+            .collect();
+        let passed = apps.iter().all(|app| app.skipped || app.passed());
+
+        Ok(TakeoverTestReport {
+            proxy_url,
+            apps,
+            passed,
+        })
+    }
+
+    /// 各应用的 Live 文件路径（第一个为主文件，不存在时视为未配置）
+    fn live_file_paths(app_type: &AppType) -> Vec<PathBuf> {
+        match app_type {
+            AppType::Claude => vec![get_claude_settings_path()],
+            AppType::Codex => vec![
+                crate::codex_config::get_codex_auth_path(),
+                crate::codex_config::get_codex_config_path(),
+            ],
+            AppType::Gemini => vec![crate::gemini_config::get_gemini_env_path()],
+            AppType::OpenCode | AppType::OpenClaw => Vec::new(),
+        }
+    }
+
+    fn takeover_self_test_app(
+        app_type: &AppType,
+        live_files: &[PathBuf],
+        sandbox_dir: &Path,
+        proxy_url: &str,
+        proxy_codex_base_url: &str,
+    ) -> TakeoverAppTestResult {
+        let mut result = TakeoverAppTestResult {
+            app_type: app_type.as_str().to_string(),
+            ..Default::default()
+        };
+        if !live_files.first().is_some_and(|path| path.exists()) {
+            result.skipped = true;
+            return result;
+        }
+
+        if let Err(e) = Self::run_takeover_self_test(
+            app_type,
+            live_files,
+            sandbox_dir,
+            proxy_url,
+            proxy_codex_base_url,
+            &mut result,
+        ) {
+            result.error = Some(e);
+        }
+        result
+    }
+
+    fn run_takeover_self_test(
+        app_type: &AppType,
+        live_files: &[PathBuf],
+        sandbox_dir: &Path,
+        proxy_url: &str,
+        proxy_codex_base_url: &str,
+        result: &mut TakeoverAppTestResult,
+    ) -> Result<(), String> {
+        std::fs::create_dir_all(sandbox_dir).map_err(|e| format!("创建临时目录失败: {e}"))?;
+
+        // 1. 复制 Live 文件（原本不存在的文件不复制，也不参与比对）
+        let copies: Vec<PathBuf> = live_files
+            .iter()
+            .map(|path| sandbox_dir.join(path.file_name().unwrap_or_default()))
+            .collect();
+        let mut originals = Vec::new();
+        for (live, copy) in live_files.iter().zip(&copies) {
+            if !live.exists() {
+                continue;
+            }
+            let bytes =
+                std::fs::read(live).map_err(|e| format!("读取 {} 失败: {e}", live.display()))?;
+            std::fs::write(copy, &bytes)
+                .map_err(|e| format!("复制 {} 失败: {e}", live.display()))?;
+            originals.push((copy, bytes));
+        }
+
+        // 2. 备份：与 backup_live_config_strict 相同的序列化，仅保存在内存中
+        let backup = serde_json::to_string(&Self::read_live_at(app_type, &copies)?)
+            .map_err(|e| format!("序列化 {} 配置失败: {e}", app_type.as_str()))?;
+
+        // 3. 接管，并确认重新读取后能识别出占位符
+        let mut live_config = Self::read_live_at(app_type, &copies)?;
+        match app_type {
+            AppType::Claude => Self::apply_claude_takeover(&mut live_config, proxy_url),
+            AppType::Codex => Self::apply_codex_takeover(&mut live_config, proxy_codex_base_url),
+            _ => Self::apply_gemini_takeover(&mut live_config, proxy_url),
+        }
+        Self::write_live_at(app_type, &copies, &live_config)?;
+        let taken_over = Self::read_live_at(app_type, &copies)?;
+        result.placeholder_detected = match app_type {
+            AppType::Claude => Self::is_claude_live_taken_over(&taken_over),
+            AppType::Codex => Self::is_codex_live_taken_over(&taken_over),
+            _ => Self::is_gemini_live_taken_over(&taken_over),
+        };
+
+        // 4. 从备份恢复，并与原文件逐字节比对
+        let restored: Value = serde_json::from_str(&backup)
+            .map_err(|e| format!("解析 {} 备份失败: {e}", app_type.as_str()))?;
+        Self::write_live_at(app_type, &copies, &restored)?;
+        result.mismatched_files = originals
+            .into_iter()
+            .filter(|(copy, bytes)| std::fs::read(copy).ok().as_ref() != Some(bytes))
+            .map(|(copy, _)| {
+                copy.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        result.round_trip_ok = result.mismatched_files.is_empty();
+
         Ok(())
     }
 
+    /// 读取指定路径下的 Live 配置（路径顺序同 `live_file_paths`）
+    fn read_live_at(app_type: &AppType, files: &[PathBuf]) -> Result<Value, String> {
+        match (app_type, files) {
+            (AppType::Claude, [settings, ..]) => Self::read_claude_live_at(settings),
+            (AppType::Codex, [auth, config, ..]) => Self::read_codex_live_at(auth, config),
+            (AppType::Gemini, [env, ..]) => Self::read_gemini_live_at(env),
+            _ => Err(format!("{} 不支持代理功能", app_type.as_str())),
+        }
+    }
+
+    /// 写入指定路径下的 Live 配置（路径顺序同 `live_file_paths`）
+    fn write_live_at(app_type: &AppType, files: &[PathBuf], config: &Value) -> Result<(), String> {
+        match (app_type, files) {
+            (AppType::Claude, [settings, ..]) => Self::write_claude_live_at(settings, config),
+            (AppType::Codex, [auth, cfg, ..]) => Self::write_codex_live_at(auth, cfg, config),
+            (AppType::Gemini, [env, ..]) => Self::write_gemini_live_at(env, config),
+            _ => Err(format!("{} 不支持代理功能", app_type.as_str())),
+        }
+    }
+
+    // ==================== 代理开销测量 ====================
+
+    /// 测量经本地代理转发相比直连供应商额外增加的延迟
+    ///
+    /// 交替向供应商直连和经代理各发送 `samples` 次相同的最小请求（读完完整响应体计时），
+    /// 正式计时前各发送一次预热请求。仅在代理运行时可用，且该供应商必须是代理当前的
+    /// 路由目标、无需格式转换，否则两条路径请求的并不是同一个上游接口。
+    /// 经代理的请求带 `x-cc-benchmark` 标记头，不写入请求日志，也不计入用量统计。
+    pub async fn benchmark_overhead(
+        &self,
+        app_type: &AppType,
+        provider_id: &str,
+        samples: usize,
+    ) -> Result<OverheadReport, AppError> {
+        let proxy_base = match self.server.read().await.as_ref() {
+            Some(server) => {
+                let status = server.get_status().await;
+                let host = match status.address.as_str() {
+                    "0.0.0.0" | "::" | "" => "127.0.0.1".to_string(),
+                    addr if addr.contains(':') => format!("[{addr}]"),
+                    addr => addr.to_string(),
+                };
+                format!("http://{host}:{}", status.port)
+            }
+            None => {
+                return Err(AppError::localized(
+                    "proxy.benchmark.not_running",
+                    "代理服务器未运行，无法测量代理开销",
+                    "Proxy server is not running; start it before measuring overhead",
+                ))
+            }
+        };
+
+        if matches!(app_type, AppType::OpenCode | AppType::OpenClaw) {
+            return Err(AppError::localized(
+                "proxy.benchmark.unsupported_app",
+                format!("{} 不支持代理，无法测量代理开销", app_type.as_str()),
+                format!("{} does not support the proxy", app_type.as_str()),
+            ));
+        }
+
+        // 命中响应缓存的请求不会到达上游，测出的耗时没有意义
+        if self.db.get_proxy_config().await?.enable_response_cache {
+            return Err(AppError::localized(
+                "proxy.benchmark.response_cache_enabled",
+                "已开启响应缓存，请先关闭后再测量代理开销",
+                "Disable the response cache before measuring proxy overhead",
+            ));
+        }
+
+        let provider = self
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.not_found",
+                    format!("供应商不存在: {provider_id}"),
+                    format!("Provider not found: {provider_id}"),
+                )
+            })?;
+
+        let routed = ProviderRouter::new(self.db.clone())
+            .select_providers(app_type.as_str())
+            .await?;
+        if routed.first().map(|p| p.id.as_str()) != Some(provider_id) {
+            return Err(AppError::localized(
+                "proxy.benchmark.not_routed",
+                format!("供应商 {} 不是代理当前的路由目标", provider.name),
+                format!(
+                    "Provider {} is not the proxy's current routing target",
+                    provider.name
+                ),
+            ));
+        }
+
+        let adapter = get_adapter(app_type);
+        if adapter.needs_transform(&provider) {
+            return Err(AppError::localized(
+                "proxy.benchmark.needs_transform",
+                "该供应商经代理时需要格式转换，无法与直连请求对比",
+                "This provider requires format conversion in the proxy and cannot be compared with direct requests",
+            ));
+        }
+
+        let base_url = adapter
+            .extract_base_url(&provider)
+            .map_err(|e| AppError::Message(e.to_string()))?;
+        let auth = adapter.extract_auth(&provider).ok_or_else(|| {
+            AppError::localized(
+                "proxy.benchmark.missing_auth",
+                "供应商未配置 API Key",
+                "Provider has no API key configured",
+            )
+        })?;
+
+        let check_config = self.db.get_stream_check_config().unwrap_or_default();
+        let (model, _) = StreamCheckService::parse_model_with_effort(
+            &StreamCheckService::resolve_test_model(app_type, &provider, &check_config),
+        );
+        let (endpoint, body) = benchmark_request(app_type, &model);
+
+        // 直连请求沿用供应商的代理与 TLS 设置，地址与请求头复用流式健康检查的构建方法
+        let direct_client =
+            crate::proxy::http_client::get_for_provider_meta(provider.meta.as_ref())
+                .map_err(AppError::Message)?;
+        let direct_urls = match app_type {
+            AppType::Gemini => vec![StreamCheckService::resolve_gemini_url(
+                &base_url, &model, false,
+            )],
+            AppType::Codex => StreamCheckService::resolve_responses_urls(&base_url),
+            _ => vec![StreamCheckService::resolve_claude_stream_url(
+                &base_url,
+                auth.strategy,
+                "anthropic",
+            )],
+        };
+        let direct_request = |url: &str| match app_type {
+            AppType::Gemini => {
+                StreamCheckService::gemini_request(&direct_client, url, &auth, false)
+            }
+            AppType::Codex => StreamCheckService::codex_request(&direct_client, url, &auth, false),
+            _ => StreamCheckService::claude_request(&direct_client, url, &auth, false, false),
+        };
+        let client = crate::proxy::http_client::get();
+        let proxy_url = format!("{proxy_base}{endpoint}");
+        let proxy_request = || client.post(&proxy_url).header(BENCHMARK_HEADER, "1");
+
+        let samples = samples.clamp(1, MAX_BENCHMARK_SAMPLES);
+        let mut direct_ms = Vec::with_capacity(samples);
+        let mut proxy_ms = Vec::with_capacity(samples);
+
+        // 预热：建立连接池中的连接，避免首次握手计入结果；
+        // 与流式健康检查一致，首选直连地址返回 404 时回退到下一个
+        let mut direct_url = None;
+        for (i, url) in direct_urls.iter().enumerate() {
+            let status = send_request("direct", direct_request(url), &body).await?;
+            if status == reqwest::StatusCode::NOT_FOUND && i + 1 < direct_urls.len() {
+                continue;
+            }
+            if !status.is_success() {
+                return Err(AppError::Message(format!("direct 请求返回 HTTP {status}")));
+            }
+            direct_url = Some(url.as_str());
+            break;
+        }
+        let Some(direct_url) = direct_url else {
+            return Err(AppError::Message("direct 请求没有可用的地址".to_string()));
+        };
+        timed_request("proxy", proxy_request(), &body).await?;
+
+        for _ in 0..samples {
+            direct_ms.push(timed_request("direct", direct_request(direct_url), &body).await?);
+            proxy_ms.push(timed_request("proxy", proxy_request(), &body).await?);
+        }
+
+        let overheads: Vec<f64> = proxy_ms
+            .iter()
+            .zip(&direct_ms)
+            .map(|(proxy, direct)| proxy - direct)
+            .collect();
+
+        Ok(OverheadReport {
+            app_type: app_type.as_str().to_string(),
+            provider_id: provider_id.to_string(),
+            samples,
+            direct_median_ms: median(&direct_ms),
+            proxy_median_ms: median(&proxy_ms),
+            median_overhead_ms: median(&overheads),
+            overhead_variance_ms2: variance(&overheads),
+            direct_ms,
+            proxy_ms,
+        })
+    }
+
     // ==================== 原有方法 ====================
 
     /// 获取服务器状态
@@ -1815,9 +2228,10 @@ impl ProxyService {
             return Ok(());
         }
 
-        // 判断是否需要重启（地址或端口变更）
+        // 判断是否需要重启（地址、端口或 Unix Socket 路径变更）
         let require_restart = new_config.listen_address != previous.listen_address
-            || new_config.listen_port != previous.listen_port;
+            || new_config.listen_port != previous.listen_port
+            || new_config.listen_socket_path != previous.listen_socket_path;
 
         if require_restart {
             if let Some(server) = server_guard.take() {
@@ -1828,7 +2242,8 @@ impl ProxyService {
             }
 
             let app_handle = self.app_handle.read().await.clone();
-            let new_server = ProxyServer::new(new_config, self.db.clone(), app_handle);
+            let new_server = ProxyServer::new(new_config, self.db.clone(), app_handle)
+                .with_request_feed(self.request_feed.clone());
             new_server
                 .start()
                 .await
@@ -1893,6 +2308,49 @@ impl ProxyService {
         Ok(())
     }
 
+    /// 清除单个 Provider 的健康状态
+    ///
+    /// 删除数据库中的健康记录，代理运行中时同时重置内存中的熔断器
+    pub async fn clear_provider_health(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<(), String> {
+        self.db
+            .clear_provider_health(provider_id, app_type)
+            .await
+            .map_err(|e| format!("清除健康状态失败: {e}"))?;
+        self.reset_provider_circuit_breaker(provider_id, app_type)
+            .await
+    }
+
+    /// 暂停/恢复使用量记录（仅运行时生效，代理重启后恢复记录）
+    ///
+    /// 暂停期间请求照常转发但不写入使用日志；`duration_secs` 指定后到期自动恢复
+    pub async fn set_logging_paused(
+        &self,
+        paused: bool,
+        duration_secs: Option<u64>,
+    ) -> Result<(), String> {
+        let guard = self.server.read().await;
+        let server = guard.as_ref().ok_or("代理服务器未运行")?;
+        let duration = duration_secs.map(std::time::Duration::from_secs);
+        server.set_logging_paused(paused, duration);
+        match (paused, duration_secs) {
+            (true, Some(secs)) => log::info!("已暂停使用量记录，{secs} 秒后自动恢复"),
+            (true, None) => log::info!("已暂停使用量记录"),
+            (false, _) => log::info!("已恢复使用量记录"),
+        }
+        Ok(())
+    }
+
+    /// 订阅/取消订阅实时请求流（`proxy-request-logged` 事件）
+    ///
+    /// 无界面监听时不推送；订阅状态不依赖代理是否运行
+    pub fn set_request_feed_subscribed(&self, subscribed: bool) {
+        self.request_feed.set_subscribed(subscribed);
+    }
+
     /// 重置指定 Provider 的熔断器
     ///
     /// 如果代理服务器正在运行，立即重置内存中的熔断器状态
@@ -1911,6 +2369,110 @@ impl ProxyService {
     }
 }
 
+/// 尝试绑定监听地址以确认端口未被占用（绑定成功后立即释放）
+fn check_port_available(address: &str, port: u16) -> Result<(), AppError> {
+    match std::net::TcpListener::bind((address, port)) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => Err(AppError::localized(
+            "proxy.port_in_use",
+            format!("端口 {port} 已被占用，请在代理设置中更换监听端口"),
+            format!("Port {port} is already in use, choose another listen port in proxy settings"),
+        )),
+        Err(e) => Err(AppError::localized(
+            "proxy.port_bind_failed",
+            format!("无法监听 {address}:{port}: {e}"),
+            format!("Cannot listen on {address}:{port}: {e}"),
+        )),
+    }
+}
+
+/// 单次测量的最大样本数
+const MAX_BENCHMARK_SAMPLES: usize = 50;
+
+/// 单个测量请求的超时时间
+const BENCHMARK_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// 构造用于测量的最小请求（端点, 请求体）
+fn benchmark_request(app_type: &AppType, model: &str) -> (String, Value) {
+    match app_type {
+        AppType::Gemini => (
+            format!("/v1beta/models/{model}:generateContent"),
+            json!({
+                "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }],
+                "generationConfig": { "maxOutputTokens": 1 }
+            }),
+        ),
+        AppType::Codex | AppType::OpenCode | AppType::OpenClaw => (
+            "/responses".to_string(),
+            json!({ "model": model, "input": "hi", "max_output_tokens": 16 }),
+        ),
+        AppType::Claude => (
+            "/v1/messages".to_string(),
+            json!({
+                "model": model,
+                "max_tokens": 1,
+                "messages": [{ "role": "user", "content": "hi" }]
+            }),
+        ),
+    }
+}
+
+/// 发送请求并读完响应体，返回响应状态
+async fn send_request(
+    label: &str,
+    request: reqwest::RequestBuilder,
+    body: &Value,
+) -> Result<reqwest::StatusCode, AppError> {
+    let response = request
+        .json(body)
+        .timeout(BENCHMARK_REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| AppError::Message(format!("{label} 请求失败: {e}")))?;
+    let status = response.status();
+    response
+        .bytes()
+        .await
+        .map_err(|e| AppError::Message(format!("{label} 读取响应失败: {e}")))?;
+    Ok(status)
+}
+
+/// 发送请求并读完响应体，返回耗时（毫秒）；非 2xx 视为失败
+async fn timed_request(
+    label: &str,
+    request: reqwest::RequestBuilder,
+    body: &Value,
+) -> Result<f64, AppError> {
+    let started = std::time::Instant::now();
+    let status = send_request(label, request, body).await?;
+    if !status.is_success() {
+        return Err(AppError::Message(format!("{label} 请求返回 HTTP {status}")));
+    }
+    Ok(started.elapsed().as_secs_f64() * 1000.0)
+}
+
+fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn variance(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1957,6 +2519,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn startup_plan_without_auto_start_only_restores_takeover() {
+        let plan = StartupProxyPlan::decide(false, false, &["claude"]);
+        assert!(!plan.start_server);
+        assert_eq!(plan.takeover_apps, vec!["claude".to_string()]);
+
+        assert!(StartupProxyPlan::decide(false, false, &[]).is_empty());
+    }
+
+    #[test]
+    fn startup_plan_with_auto_start_starts_server() {
+        let plan = StartupProxyPlan::decide(true, false, &[]);
+        assert!(plan.start_server);
+        assert!(plan.takeover_apps.is_empty());
+
+        let plan = StartupProxyPlan::decide(true, false, &["claude", "codex"]);
+        assert!(plan.start_server);
+        assert_eq!(
+            plan.takeover_apps,
+            vec!["claude".to_string(), "codex".to_string()]
+        );
+    }
+
+    #[test]
+    fn startup_plan_skips_auto_start_after_crash() {
+        assert!(StartupProxyPlan::decide(true, true, &[]).is_empty());
+
+        // 已接管的应用仍需恢复，保证接管状态与 proxy_config.enabled 一致
+        let plan = StartupProxyPlan::decide(true, true, &["gemini"]);
+        assert!(!plan.start_server);
+        assert_eq!(plan.takeover_apps, vec!["gemini".to_string()]);
+    }
+
     #[test]
     fn update_toml_base_url_updates_active_model_provider_base_url() {
         let input = r#"
@@ -2019,6 +2614,56 @@ model = "gpt-5.1-codex"
         assert_eq!(base_url, new_url);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn takeover_and_restore_refresh_live_fingerprint() {
+        use crate::services::provider::{live_fingerprint, recorded_live_fingerprint};
+
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        let db = Arc::new(Database::memory().expect("init db"));
+        let service = ProxyService::new(db.clone());
+        write_json_file(
+            &get_claude_settings_path(),
+            &json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": "https://api.anthropic.com",
+                    "ANTHROPIC_AUTH_TOKEN": "real-token"
+                }
+            }),
+        )
+        .expect("seed claude live");
+        let original = live_fingerprint(&AppType::Claude).expect("fingerprint original");
+
+        service
+            .backup_live_config_strict(&AppType::Claude)
+            .await
+            .expect("backup live");
+        service
+            .takeover_live_config_strict(&AppType::Claude)
+            .await
+            .expect("takeover live");
+        let taken_over = live_fingerprint(&AppType::Claude).expect("fingerprint takeover");
+        assert_ne!(taken_over, original);
+        assert_eq!(
+            recorded_live_fingerprint(&db, &AppType::Claude).expect("recorded"),
+            taken_over,
+            "takeover should not be reported as an unmanaged change"
+        );
+
+        service
+            .restore_live_config_for_app(&AppType::Claude)
+            .await
+            .expect("restore live");
+        let restored = live_fingerprint(&AppType::Claude).expect("fingerprint restored");
+        assert_eq!(
+            recorded_live_fingerprint(&db, &AppType::Claude).expect("recorded"),
+            restored,
+            "restore should not be reported as an unmanaged change"
+        );
+    }
+
     #[tokio::test]
     #[serial]
     async fn sync_claude_token_does_not_add_anthropic_api_key() {
@@ -2193,6 +2838,125 @@ model = "gpt-5.1-codex"
         assert_eq!(backup.original_config, expected);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn start_with_takeover_rejects_port_in_use_before_touching_live() {
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        let db = Arc::new(Database::memory().expect("init db"));
+        let service = ProxyService::new(db.clone());
+
+        // 占用一个端口并配置为代理监听端口
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").expect("bind port");
+        let port = occupied.local_addr().expect("local addr").port();
+        let mut global = db.get_global_proxy_config().await.expect("get global");
+        global.listen_address = "127.0.0.1".to_string();
+        global.listen_port = port;
+        db.update_global_proxy_config(global)
+            .await
+            .expect("update global");
+
+        let original = json!({ "env": { "ANTHROPIC_API_KEY": "original" } });
+        service
+            .write_claude_live(&original)
+            .expect("seed claude live");
+
+        let err = service
+            .start_with_takeover()
+            .await
+            .expect_err("port in use should fail");
+        assert!(err.contains(&port.to_string()), "unexpected error: {err}");
+        assert!(err.contains("已被占用"), "unexpected error: {err}");
+
+        // 未发生任何接管
+        assert!(!db
+            .is_live_takeover_active()
+            .await
+            .expect("read takeover flag"));
+        assert!(!db.has_any_live_backup().await.expect("check backups"));
+        assert_eq!(service.read_claude_live().expect("read live"), original);
+        assert!(!service.is_running().await);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn factory_reset_proxy_restores_defaults_and_live_config() {
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        let db = Arc::new(Database::memory().expect("init db"));
+        let service = ProxyService::new(db.clone());
+
+        let provider = Provider::with_id(
+            "a".to_string(),
+            "A".to_string(),
+            json!({ "env": { "ANTHROPIC_API_KEY": "a-key" } }),
+            None,
+        );
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+
+        // 模拟接管中：自定义配置、Live 备份与失败记录
+        let mut codex = db
+            .get_proxy_config_for_app("codex")
+            .await
+            .expect("get codex config");
+        codex.enabled = true;
+        codex.auto_failover_enabled = true;
+        codex.max_retries = 9;
+        db.update_proxy_config_for_app(codex)
+            .await
+            .expect("update codex config");
+        let mut global = db.get_global_proxy_config().await.expect("get global");
+        global.listen_port = 18888;
+        db.update_global_proxy_config(global)
+            .await
+            .expect("update global");
+        let original = json!({ "env": { "ANTHROPIC_API_KEY": "original" } });
+        db.save_live_backup("claude", &original.to_string())
+            .await
+            .expect("seed live backup");
+        db.update_provider_health("a", "claude", false, Some("boom".to_string()), None)
+            .await
+            .expect("record failure");
+
+        service.factory_reset_proxy().await.expect("factory reset");
+        // 幂等：再次执行不报错
+        service.factory_reset_proxy().await.expect("second reset");
+
+        let codex = db
+            .get_proxy_config_for_app("codex")
+            .await
+            .expect("get codex config");
+        assert!(!codex.enabled);
+        assert!(!codex.auto_failover_enabled);
+        assert_eq!(codex.max_retries, 3);
+        let claude = db
+            .get_proxy_config_for_app("claude")
+            .await
+            .expect("get claude config");
+        assert_eq!(claude.max_retries, 6);
+        assert_eq!(
+            db.get_global_proxy_config()
+                .await
+                .expect("get global")
+                .listen_port,
+            15721
+        );
+
+        assert!(!db.has_any_live_backup().await.expect("check backups"));
+        let live = service.read_claude_live().expect("read live");
+        assert_eq!(live["env"]["ANTHROPIC_API_KEY"], "original");
+
+        let health = db
+            .get_provider_health("a", "claude")
+            .await
+            .expect("get health");
+        assert!(health.is_healthy);
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
     #[tokio::test]
     #[serial]
     async fn update_live_backup_from_provider_applies_claude_common_config() {
@@ -2473,4 +3237,251 @@ command = "latest-command"
             "new MCP entries should remain in the restore backup"
         );
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn benchmark_overhead_reports_direct_and_proxy_samples() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        // 模拟上游：记录收到的请求数
+        let hits = Arc::new(AtomicUsize::new(0));
+        let upstream_hits = hits.clone();
+        let upstream = axum::Router::new().route(
+            "/v1/messages",
+            axum::routing::post(move || {
+                let hits = upstream_hits.clone();
+                async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    axum::Json(json!({
+                        "id": "msg_1",
+                        "type": "message",
+                        "role": "assistant",
+                        "model": "claude-haiku-4-5-20251001",
+                        "content": [{ "type": "text", "text": "h" }],
+                        "stop_reason": "max_tokens",
+                        "usage": { "input_tokens": 1, "output_tokens": 1 }
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind upstream");
+        let upstream_addr = listener.local_addr().expect("upstream addr");
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.ok();
+        });
+
+        let db = Arc::new(Database::memory().expect("init db"));
+        let service = ProxyService::new(db.clone());
+
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": format!("http://{upstream_addr}"),
+                    "ANTHROPIC_AUTH_TOKEN": "sk-test"
+                }
+            }),
+            None,
+        );
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        db.set_current_provider("claude", "p1")
+            .expect("set current provider");
+
+        let err = service
+            .benchmark_overhead(&AppType::Claude, "p1", 3)
+            .await
+            .expect_err("proxy not running");
+        assert!(err.to_string().contains("未运行") || err.to_string().contains("not running"));
+
+        let free_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("free port")
+            .port();
+        let mut config = db.get_proxy_config().await.expect("proxy config");
+        config.listen_address = "127.0.0.1".to_string();
+        config.listen_port = free_port;
+        db.update_proxy_config(config)
+            .await
+            .expect("update proxy config");
+        service.start().await.expect("start proxy");
+
+        let report = service.benchmark_overhead(&AppType::Claude, "p1", 3).await;
+
+        // 测量请求不记录日志：再发一条普通请求，等它落盘后日志表中只有这一条
+        let (endpoint, body) = benchmark_request(&AppType::Claude, "claude-haiku-4-5-20251001");
+        crate::proxy::http_client::get()
+            .post(format!("http://127.0.0.1:{free_port}{endpoint}"))
+            .json(&body)
+            .send()
+            .await
+            .expect("plain proxy request");
+        let log_count = || -> i64 {
+            let conn = crate::database::lock_conn!(db.conn);
+            conn.query_row("SELECT COUNT(*) FROM proxy_request_logs", [], |row| {
+                row.get(0)
+            })
+            .expect("count request logs")
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while log_count() == 0 && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        service.stop().await.expect("stop proxy");
+        let report = report.expect("benchmark");
+        assert_eq!(log_count(), 1);
+
+        assert_eq!(report.app_type, "claude");
+        assert_eq!(report.provider_id, "p1");
+        assert_eq!(report.samples, 3);
+        assert_eq!(report.direct_ms.len(), 3);
+        assert_eq!(report.proxy_ms.len(), 3);
+        assert!(report
+            .direct_ms
+            .iter()
+            .chain(&report.proxy_ms)
+            .all(|ms| *ms > 0.0));
+        assert!(report.direct_median_ms > 0.0 && report.proxy_median_ms > 0.0);
+        assert!(report.overhead_variance_ms2 >= 0.0);
+        // 3 组样本 + 1 组预热，直连与代理各占一半，外加一条普通请求
+        assert_eq!(hits.load(Ordering::SeqCst), 9);
+    }
+
+    /// 写入 Live 文件并返回其原始字节
+    fn write_live_fixture(path: &Path, content: &str) -> Vec<u8> {
+        std::fs::create_dir_all(path.parent().expect("parent dir")).expect("create dir");
+        std::fs::write(path, content).expect("write live file");
+        content.as_bytes().to_vec()
+    }
+
+    async fn self_test_result(app: &str) -> TakeoverAppTestResult {
+        let db = Arc::new(Database::memory().expect("init db"));
+        let service = ProxyService::new(db);
+        let report = service.takeover_self_test().await.expect("self test");
+        assert_eq!(report.apps.len(), 3);
+        assert!(report.passed, "self test failed: {report:?}");
+        report
+            .apps
+            .into_iter()
+            .find(|result| result.app_type == app)
+            .expect("app result")
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn takeover_self_test_round_trips_claude_live() {
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        let path = get_claude_settings_path();
+        let settings = json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "https://api.anthropic.com",
+                "ANTHROPIC_AUTH_TOKEN": "sk-real-token",
+                "ANTHROPIC_MODEL": "claude-sonnet-4"
+            },
+            "permissions": { "allow": ["Bash(ls)"] }
+        });
+        let original = write_live_fixture(
+            &path,
+            &serde_json::to_string_pretty(&settings).expect("serialize"),
+        );
+
+        let result = self_test_result("claude").await;
+        assert!(result.passed(), "{result:?}");
+        assert!(result.placeholder_detected);
+        assert!(result.round_trip_ok);
+        assert_eq!(std::fs::read(&path).expect("read live"), original);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn takeover_self_test_round_trips_codex_live() {
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        let auth_path = crate::codex_config::get_codex_auth_path();
+        let config_path = crate::codex_config::get_codex_config_path();
+        let auth = write_live_fixture(
+            &auth_path,
+            &serde_json::to_string_pretty(&json!({ "OPENAI_API_KEY": "sk-real-key" }))
+                .expect("serialize"),
+        );
+        let config = write_live_fixture(
+            &config_path,
+            r#"model_provider = "any"
+model = "gpt-5.1-codex"
+
+[model_providers.any]
+name = "any"
+base_url = "https://anyrouter.top/v1"
+wire_api = "responses"
+"#,
+        );
+
+        let result = self_test_result("codex").await;
+        assert!(result.passed(), "{result:?}");
+        assert!(result.mismatched_files.is_empty());
+        assert_eq!(std::fs::read(&auth_path).expect("read auth"), auth);
+        assert_eq!(std::fs::read(&config_path).expect("read config"), config);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn takeover_self_test_round_trips_gemini_live() {
+        let _home = TempHome::new();
+        crate::settings::reload_settings().expect("reload settings");
+
+        let path = crate::gemini_config::get_gemini_env_path();
+        let original = write_live_fixture(
+            &path,
+            "GEMINI_API_KEY=real-key\nGOOGLE_GEMINI_BASE_URL=https://generativelanguage.googleapis.com",
+        );
+
+        let result = self_test_result("gemini").await;
+        assert!(result.passed(), "{result:?}");
+        assert_eq!(std::fs::read(&path).expect("read env"), original);
+
+        // 未配置的应用跳过，不会在真实位置生成文件
+        assert!(!get_claude_settings_path().exists());
+        assert!(!crate::codex_config::get_codex_auth_path().exists());
+    }
+
+    #[test]
+    fn takeover_self_test_reports_files_that_do_not_round_trip() {
+        let live = TempDir::new().expect("live dir");
+        let sandbox = TempDir::new().expect("sandbox dir");
+        // 注释与行序在 .env 重写后无法保留
+        let path = live.path().join(".env");
+        let original = write_live_fixture(
+            &path,
+            "# gemini\nGOOGLE_GEMINI_BASE_URL=https://g.example\nGEMINI_API_KEY=k\n",
+        );
+
+        let result = ProxyService::takeover_self_test_app(
+            &AppType::Gemini,
+            std::slice::from_ref(&path),
+            sandbox.path(),
+            "http://127.0.0.1:15721",
+            "http://127.0.0.1:15721/v1",
+        );
+        assert!(result.placeholder_detected);
+        assert!(!result.round_trip_ok);
+        assert_eq!(result.mismatched_files, vec![".env".to_string()]);
+        assert_eq!(std::fs::read(&path).expect("read env"), original);
+    }
+
+    #[test]
+    fn median_and_variance_of_samples() {
+        assert_eq!(median(&[3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+        assert_eq!(variance(&[2.0, 4.0]), 1.0);
+        assert_eq!(variance(&[]), 0.0);
+    }
 }
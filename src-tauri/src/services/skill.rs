@@ -34,6 +34,26 @@ pub enum SyncMethod {
     Copy,
 }
 
+impl SyncMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncMethod::Auto => "auto",
+            SyncMethod::Symlink => "symlink",
+            SyncMethod::Copy => "copy",
+        }
+    }
+
+    /// 解析数据库中保存的同步方式，未知值返回 None
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(SyncMethod::Auto),
+            "symlink" => Some(SyncMethod::Symlink),
+            "copy" => Some(SyncMethod::Copy),
+            _ => None,
+        }
+    }
+}
+
 /// 可发现的技能（来自仓库）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoverableSkill {
@@ -195,6 +215,60 @@ pub struct ImportSkillSelection {
     pub apps: SkillApps,
 }
 
+/// 已安装 Skills 清单（用于在另一台机器上复现相同的安装集合）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillManifest {
+    /// 清单格式版本
+    pub version: u32,
+    pub skills: Vec<SkillManifestEntry>,
+}
+
+/// 清单中的单个 Skill（来源仓库 + 各应用启用状态）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillManifestEntry {
+    /// 唯一标识（"owner/repo:directory" 或 "local:directory"）
+    pub key: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub directory: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme_url: Option<String>,
+    #[serde(default)]
+    pub apps: SkillApps,
+}
+
+/// 应用清单的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillManifestApplyResult {
+    /// 新安装的 Skill key
+    pub installed: Vec<String>,
+    /// 已安装但调整了应用启用状态的 Skill key
+    pub updated: Vec<String>,
+    /// 未安装的 Skill key（不允许安装、本地 Skill 或安装失败）
+    pub skipped: Vec<String>,
+}
+
+/// 当前清单格式版本
+const SKILL_MANIFEST_VERSION: u32 = 1;
+
+/// 清单可管理的应用（OpenClaw 不支持 Skills）
+const SKILL_MANIFEST_APPS: [AppType; 4] = [
+    AppType::Claude,
+    AppType::Codex,
+    AppType::Gemini,
+    AppType::OpenCode,
+];
+
 #[derive(Debug, Clone, Deserialize)]
 struct LegacySkillMigrationRow {
     directory: String,
@@ -504,7 +578,11 @@ impl SkillService {
                     let mut updated = existing.clone();
                     updated.apps.set_enabled_for(current_app, true);
                     db.save_skill(&updated)?;
-                    Self::sync_to_app_dir(&updated.directory, current_app)?;
+                    Self::sync_to_app_dir(
+                        &updated.directory,
+                        current_app,
+                        updated.sync_method_override,
+                    )?;
                     log::info!(
                         "Skill {} 已存在，更新 {:?} 启用状态",
                         updated.name,
@@ -647,13 +725,14 @@ impl SkillService {
             readme_url,
             apps: SkillApps::only(current_app),
             installed_at: chrono::Utc::now().timestamp(),
+            sync_method_override: None,
         };
 
         // 保存到数据库
         db.save_skill(&installed_skill)?;
 
         // 同步到当前应用目录
-        Self::sync_to_app_dir(&install_name, current_app)?;
+        Self::sync_to_app_dir(&install_name, current_app, None)?;
 
         log::info!(
             "Skill {} 安装成功，已启用 {:?}",
@@ -809,7 +888,11 @@ impl SkillService {
         }
 
         if !restored_skill.apps.is_empty() {
-            if let Err(err) = Self::sync_to_app_dir(&restored_skill.directory, current_app) {
+            if let Err(err) = Self::sync_to_app_dir(
+                &restored_skill.directory,
+                current_app,
+                restored_skill.sync_method_override,
+            ) {
                 let _ = db.delete_skill(&restored_skill.id);
                 let _ = fs::remove_dir_all(&restore_path);
                 return Err(err);
@@ -840,7 +923,7 @@ impl SkillService {
 
         // 同步文件
         if enabled {
-            Self::sync_to_app_dir(&skill.directory, app)?;
+            Self::sync_to_app_dir(&skill.directory, app, skill.sync_method_override)?;
         } else {
             Self::remove_from_app(&skill.directory, app)?;
         }
@@ -853,6 +936,302 @@ impl SkillService {
         Ok(())
     }
 
+    /// 批量设置应用启用的 Skills
+    ///
+    /// 使应用的启用集合恰好等于 `directories`：
+    /// 新列出的 Skill 启用并同步到应用目录，不在列表中的 Skill 禁用并从应用目录删除
+    pub fn set_app_skills(
+        db: &Arc<Database>,
+        app: &AppType,
+        directories: Vec<String>,
+    ) -> Result<()> {
+        let skills = db.get_all_installed_skills()?;
+
+        let known: HashSet<String> = skills
+            .values()
+            .map(|skill| skill.directory.to_lowercase())
+            .collect();
+        if let Some(missing) = directories
+            .iter()
+            .find(|directory| !known.contains(&directory.to_lowercase()))
+        {
+            return Err(anyhow!("Skill not found: {missing}"));
+        }
+
+        let wanted: HashSet<String> = directories
+            .iter()
+            .map(|directory| directory.to_lowercase())
+            .collect();
+
+        let mut enabled = Vec::new();
+        let mut disabled = Vec::new();
+        for skill in skills.values() {
+            let should_enable = wanted.contains(&skill.directory.to_lowercase());
+            let was_enabled = skill.apps.is_enabled_for(app);
+
+            // 已启用的也重新同步一次，保证应用目录与数据库一致；
+            // 只删除之前由我们同步过去的目录，未启用的同名目录可能是用户自己管理的
+            if should_enable {
+                Self::sync_to_app_dir(&skill.directory, app, skill.sync_method_override)?;
+            } else if was_enabled {
+                Self::remove_from_app(&skill.directory, app)?;
+            }
+
+            if was_enabled == should_enable {
+                continue;
+            }
+
+            let mut apps = skill.apps.clone();
+            apps.set_enabled_for(app, should_enable);
+            db.update_skill_apps(&skill.id, &apps)?;
+
+            if should_enable {
+                enabled.push(skill.name.as_str());
+            } else {
+                disabled.push(skill.name.as_str());
+            }
+        }
+
+        log::info!(
+            "{:?} 的 Skills 已批量更新：启用 {:?}，禁用 {:?}",
+            app,
+            enabled,
+            disabled
+        );
+
+        Ok(())
+    }
+
+    /// 设置 Skill 单独的同步方式（None 表示跟随全局设置）
+    ///
+    /// 已启用的应用目录会按新方式重新同步。
+    pub fn set_sync_method_override(
+        db: &Arc<Database>,
+        id: &str,
+        method: Option<SyncMethod>,
+    ) -> Result<()> {
+        let mut skill = db
+            .get_installed_skill(id)?
+            .ok_or_else(|| anyhow!("Skill not found: {id}"))?;
+
+        skill.sync_method_override = method;
+        db.update_skill_sync_method(id, method)?;
+
+        for app in AppType::all() {
+            if skill.apps.is_enabled_for(&app) {
+                Self::sync_to_app_dir(&skill.directory, &app, skill.sync_method_override)?;
+            }
+        }
+
+        log::info!(
+            "Skill {} 的同步方式已设置为 {}",
+            skill.name,
+            method.map(SyncMethod::as_str).unwrap_or("跟随全局")
+        );
+        Ok(())
+    }
+
+    /// 从 SSOT 中的 SKILL.md 刷新 Skill 的名称和描述
+    ///
+    /// 仓库更新 SKILL.md 后数据库中的名称/描述会过期；SKILL.md 未声明名称时保留原名称。
+    /// 返回记录是否有变化。
+    pub fn refresh_metadata(db: &Arc<Database>, id: &str) -> Result<bool> {
+        let skill = db
+            .get_installed_skill(id)?
+            .ok_or_else(|| anyhow!("Skill not found: {id}"))?;
+
+        let skill_md = Self::get_ssot_dir()?
+            .join(&skill.directory)
+            .join("SKILL.md");
+        if !skill_md.exists() {
+            return Err(anyhow!("SKILL.md not found: {}", skill_md.display()));
+        }
+        let meta = Self::parse_skill_metadata_static(&skill_md)?;
+
+        let name = meta
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| skill.name.clone());
+        let description = meta
+            .description
+            .map(|desc| desc.trim().to_string())
+            .filter(|desc| !desc.is_empty());
+
+        if name == skill.name && description == skill.description {
+            return Ok(false);
+        }
+
+        db.update_skill_metadata(id, &name, description.as_deref())?;
+        log::info!(
+            "Skill {id} 元数据已从 SKILL.md 刷新: {} -> {name}",
+            skill.name
+        );
+        Ok(true)
+    }
+
+    /// 刷新所有已安装 Skill 的元数据，返回有变化的 Skill id
+    ///
+    /// 单个 Skill 刷新失败（如 SKILL.md 缺失）只记录警告，不影响其他 Skill
+    pub fn refresh_all_metadata(db: &Arc<Database>) -> Result<Vec<String>> {
+        let mut ids: Vec<String> = db.get_all_installed_skills()?.into_keys().collect();
+        ids.sort();
+
+        let mut changed = Vec::new();
+        for id in ids {
+            match Self::refresh_metadata(db, &id) {
+                Ok(true) => changed.push(id),
+                Ok(false) => {}
+                Err(e) => log::warn!("刷新 Skill {id} 元数据失败: {e}"),
+            }
+        }
+        Ok(changed)
+    }
+
+    /// 导出已安装 Skills 清单（按 key 排序，便于比对）
+    pub fn export_manifest(db: &Arc<Database>) -> Result<SkillManifest> {
+        let mut skills: Vec<SkillManifestEntry> = db
+            .get_all_installed_skills()?
+            .into_values()
+            .map(|skill| SkillManifestEntry {
+                key: skill.id,
+                name: skill.name,
+                description: skill.description,
+                directory: skill.directory,
+                repo_owner: skill.repo_owner,
+                repo_name: skill.repo_name,
+                repo_branch: skill.repo_branch,
+                readme_url: skill.readme_url,
+                apps: skill.apps,
+            })
+            .collect();
+        skills.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(SkillManifest {
+            version: SKILL_MANIFEST_VERSION,
+            skills,
+        })
+    }
+
+    /// 按清单查找已安装的 Skill：优先按 key 匹配，其次按目录名匹配
+    fn find_manifest_skill(
+        db: &Arc<Database>,
+        entry: &SkillManifestEntry,
+    ) -> Result<Option<InstalledSkill>> {
+        if let Some(skill) = db.get_installed_skill(&entry.key)? {
+            return Ok(Some(skill));
+        }
+        Ok(db
+            .get_all_installed_skills()?
+            .into_values()
+            .find(|skill| skill.directory.eq_ignore_ascii_case(&entry.directory)))
+    }
+
+    /// 应用 Skills 清单
+    ///
+    /// 缺失的仓库 Skill 在 `install_missing` 为 true 时安装；
+    /// 所有已安装（含新安装）的 Skill 的应用启用状态调整为与清单一致。
+    /// 清单之外的已安装 Skill 保持不变。
+    pub async fn apply_manifest(
+        &self,
+        db: &Arc<Database>,
+        manifest: &SkillManifest,
+        install_missing: bool,
+    ) -> Result<SkillManifestApplyResult> {
+        if manifest.version > SKILL_MANIFEST_VERSION {
+            return Err(anyhow!(
+                "Unsupported skill manifest version: {}",
+                manifest.version
+            ));
+        }
+
+        let mut result = SkillManifestApplyResult::default();
+        for entry in &manifest.skills {
+            let (skill, newly_installed) = match Self::find_manifest_skill(db, entry)? {
+                Some(skill) => (skill, false),
+                None => match self
+                    .install_manifest_entry(db, entry, install_missing)
+                    .await
+                {
+                    Some(skill) => (skill, true),
+                    None => {
+                        result.skipped.push(entry.key.clone());
+                        continue;
+                    }
+                },
+            };
+
+            let mut changed = false;
+            for app in &SKILL_MANIFEST_APPS {
+                let wanted = entry.apps.is_enabled_for(app);
+                if skill.apps.is_enabled_for(app) != wanted {
+                    Self::toggle_app(db, &skill.id, app, wanted)?;
+                    changed = true;
+                }
+            }
+
+            if newly_installed {
+                result.installed.push(entry.key.clone());
+            } else if changed {
+                result.updated.push(entry.key.clone());
+            }
+        }
+
+        log::info!(
+            "Skills 清单已应用：安装 {:?}，更新 {:?}，跳过 {:?}",
+            result.installed,
+            result.updated,
+            result.skipped
+        );
+        Ok(result)
+    }
+
+    /// 安装清单中缺失的 Skill；无法安装时返回 None（原因记录到日志）
+    async fn install_manifest_entry(
+        &self,
+        db: &Arc<Database>,
+        entry: &SkillManifestEntry,
+        install_missing: bool,
+    ) -> Option<InstalledSkill> {
+        let (Some(repo_owner), Some(repo_name)) = (&entry.repo_owner, &entry.repo_name) else {
+            log::warn!("Skill {} 为本地 Skill，无法从清单安装，已跳过", entry.key);
+            return None;
+        };
+        if !install_missing {
+            log::info!("Skill {} 未安装，按设置跳过安装", entry.key);
+            return None;
+        }
+
+        let skill = DiscoverableSkill {
+            key: entry.key.clone(),
+            name: entry.name.clone(),
+            description: entry.description.clone().unwrap_or_default(),
+            directory: entry.directory.clone(),
+            readme_url: entry.readme_url.clone(),
+            repo_owner: repo_owner.clone(),
+            repo_name: repo_name.clone(),
+            repo_branch: entry
+                .repo_branch
+                .clone()
+                .unwrap_or_else(|| "main".to_string()),
+        };
+        // 安装时需要一个初始启用的应用，随后统一按清单调整
+        let initial_app = entry
+            .apps
+            .enabled_apps()
+            .into_iter()
+            .next()
+            .unwrap_or(AppType::Claude);
+
+        match self.install(db, &skill, &initial_app).await {
+            Ok(installed) => Some(installed),
+            Err(e) => {
+                log::warn!("按清单安装 Skill {} 失败，已跳过: {e}", entry.key);
+                None
+            }
+        }
+    }
+
     /// 扫描未管理的 Skills
     ///
     /// 扫描各应用目录，找出未被 CC Switch 管理的 Skills
@@ -1003,6 +1382,7 @@ impl SkillService {
                 readme_url,
                 apps,
                 installed_at: chrono::Utc::now().timestamp(),
+                sync_method_override: None,
             };
 
             // 保存到数据库
@@ -1047,11 +1427,15 @@ impl SkillService {
 
     /// 同步 Skill 到应用目录（使用 symlink 或 copy）
     ///
-    /// 根据配置和平台选择最佳同步方式：
+    /// `method_override` 为 Skill 单独指定的同步方式，未指定时使用全局配置：
     /// - Auto: 优先尝试 symlink，失败时回退到 copy
     /// - Symlink: 仅使用 symlink
     /// - Copy: 仅使用文件复制
-    pub fn sync_to_app_dir(directory: &str, app: &AppType) -> Result<()> {
+    pub fn sync_to_app_dir(
+        directory: &str,
+        app: &AppType,
+        method_override: Option<SyncMethod>,
+    ) -> Result<()> {
         let ssot_dir = Self::get_ssot_dir()?;
         let source = ssot_dir.join(directory);
 
@@ -1069,7 +1453,7 @@ impl SkillService {
             Self::remove_path(&dest)?;
         }
 
-        let sync_method = Self::get_sync_method();
+        let sync_method = method_override.unwrap_or_else(Self::get_sync_method);
 
         match sync_method {
             SyncMethod::Auto => {
@@ -1107,7 +1491,7 @@ impl SkillService {
     /// 复制 Skill 到应用目录（保留用于向后兼容）
     #[deprecated(note = "请使用 sync_to_app_dir() 代替")]
     pub fn copy_to_app(directory: &str, app: &AppType) -> Result<()> {
-        Self::sync_to_app_dir(directory, app)
+        Self::sync_to_app_dir(directory, app, None)
     }
 
     /// 删除路径（支持 symlink 和真实目录）
@@ -1204,7 +1588,7 @@ impl SkillService {
 
         for skill in skills.values() {
             if skill.apps.is_enabled_for(app) {
-                Self::sync_to_app_dir(&skill.directory, app)?;
+                Self::sync_to_app_dir(&skill.directory, app, skill.sync_method_override)?;
             }
         }
 
@@ -1977,13 +2361,14 @@ impl SkillService {
                 readme_url: None,
                 apps: SkillApps::only(current_app),
                 installed_at: chrono::Utc::now().timestamp(),
+                sync_method_override: None,
             };
 
             // 保存到数据库
             db.save_skill(&skill)?;
 
             // 同步到当前应用目录
-            Self::sync_to_app_dir(&install_name, current_app)?;
+            Self::sync_to_app_dir(&install_name, current_app, None)?;
 
             log::info!(
                 "Skill {} installed from ZIP, enabled for {:?}",
@@ -2299,6 +2684,7 @@ pub fn migrate_skills_to_ssot(db: &Arc<Database>) -> Result<usize> {
             readme_url,
             apps,
             installed_at: chrono::Utc::now().timestamp(),
+            sync_method_override: None,
         };
 
         db.save_skill(&skill)?;
@@ -3,7 +3,11 @@ use reqwest::{Client, Url};
 use serde::Serialize;
 use std::time::Instant;
 
+use crate::app_config::AppType;
 use crate::error::AppError;
+use crate::provider::ProviderMeta;
+use crate::services::ProviderService;
+use crate::store::AppState;
 
 const DEFAULT_TIMEOUT_SECS: u64 = 8;
 const MAX_TIMEOUT_SECS: u64 = 30;
@@ -18,6 +22,19 @@ pub struct EndpointLatency {
     pub error: Option<String>,
 }
 
+/// 供应商自定义端点的可达性
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointStatus {
+    pub url: String,
+    /// 收到任意 HTTP 响应即视为可达
+    pub reachable: bool,
+    pub latency: Option<u128>,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub last_used: Option<i64>,
+}
+
 /// 网络测速相关业务
 pub struct SpeedtestService;
 
@@ -26,6 +43,15 @@ impl SpeedtestService {
     pub async fn test_endpoints(
         urls: Vec<String>,
         timeout_secs: Option<u64>,
+    ) -> Result<Vec<EndpointLatency>, AppError> {
+        Self::test_endpoints_with_meta(urls, timeout_secs, None).await
+    }
+
+    /// 测试一组端点的响应延迟，提供供应商 meta 时沿用其代理与 TLS 设置
+    async fn test_endpoints_with_meta(
+        urls: Vec<String>,
+        timeout_secs: Option<u64>,
+        meta: Option<&ProviderMeta>,
     ) -> Result<Vec<EndpointLatency>, AppError> {
         if urls.is_empty() {
             return Ok(vec![]);
@@ -65,7 +91,7 @@ impl SpeedtestService {
         }
 
         let timeout = Self::sanitize_timeout(timeout_secs);
-        let (client, request_timeout) = Self::build_client(timeout)?;
+        let (client, request_timeout) = Self::build_client(meta, timeout)?;
 
         let tasks = valid_targets.into_iter().map(|(idx, trimmed, parsed_url)| {
             let client = client.clone();
@@ -116,11 +142,53 @@ impl SpeedtestService {
         Ok(results.into_iter().flatten().collect::<Vec<_>>())
     }
 
-    fn build_client(timeout_secs: u64) -> Result<(Client, std::time::Duration), AppError> {
-        // 使用全局 HTTP 客户端（已包含代理配置）
+    /// 并发探测供应商的全部自定义端点，可达的按延迟从快到慢排在前面，不可达的按 URL 排在最后
+    pub async fn endpoint_matrix(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<Vec<EndpointStatus>, AppError> {
+        let endpoints =
+            ProviderService::get_custom_endpoints(state, app_type.clone(), provider_id)?;
+        // 探测沿用供应商的代理与 TLS 设置
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?;
+        let meta = provider.as_ref().and_then(|p| p.meta.as_ref());
+        let urls = endpoints.iter().map(|e| e.url.clone()).collect();
+        let latencies = Self::test_endpoints_with_meta(urls, None, meta).await?;
+
+        let mut matrix: Vec<EndpointStatus> = endpoints
+            .into_iter()
+            .zip(latencies)
+            .map(|(endpoint, result)| EndpointStatus {
+                url: endpoint.url,
+                reachable: result.latency.is_some(),
+                latency: result.latency,
+                status: result.status,
+                error: result.error,
+                last_used: endpoint.last_used,
+            })
+            .collect();
+        matrix.sort_by(|a, b| match (a.latency, b.latency) {
+            (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.url.cmp(&b.url)),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.url.cmp(&b.url),
+        });
+        Ok(matrix)
+    }
+
+    fn build_client(
+        meta: Option<&ProviderMeta>,
+        timeout_secs: u64,
+    ) -> Result<(Client, std::time::Duration), AppError> {
+        // 使用供应商的 HTTP 客户端（未提供 meta 时即全局客户端，已包含代理配置）
         // 返回 timeout Duration 供请求级别使用
         let timeout = std::time::Duration::from_secs(timeout_secs);
-        Ok((crate::proxy::http_client::get(), timeout))
+        let client =
+            crate::proxy::http_client::get_for_provider_meta(meta).map_err(AppError::Message)?;
+        Ok((client, timeout))
     }
 
     fn sanitize_timeout(timeout_secs: Option<u64>) -> u64 {
@@ -184,4 +252,79 @@ mod tests {
             "empty url should report validation error"
         );
     }
+
+    #[tokio::test]
+    async fn endpoint_matrix_sorts_reachable_endpoints_first() {
+        use crate::database::Database;
+        use crate::provider::{Provider, ProviderMeta};
+        use crate::settings::CustomEndpoint;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        async fn spawn_upstream(delay: Duration) -> String {
+            let router = axum::Router::new().route(
+                "/",
+                axum::routing::get(move || async move {
+                    tokio::time::sleep(delay).await;
+                    "ok"
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("bind upstream");
+            let addr = listener.local_addr().expect("upstream addr");
+            tokio::spawn(async move {
+                axum::serve(listener, router).await.ok();
+            });
+            format!("http://{addr}")
+        }
+
+        let slow = spawn_upstream(Duration::from_millis(300)).await;
+        let fast = spawn_upstream(Duration::ZERO).await;
+        // 绑定后立即释放端口，连接会被拒绝
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+            format!("http://{}", listener.local_addr().expect("addr"))
+        };
+
+        let mut custom_endpoints = HashMap::new();
+        for (idx, url) in [&slow, &closed, &fast].into_iter().enumerate() {
+            custom_endpoints.insert(
+                url.clone(),
+                CustomEndpoint {
+                    url: url.clone(),
+                    added_at: idx as i64,
+                    last_used: (url == &fast).then_some(1_700_000_000_000),
+                },
+            );
+        }
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "P1".to_string(),
+            serde_json::json!({ "env": { "ANTHROPIC_BASE_URL": fast } }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            custom_endpoints,
+            ..Default::default()
+        });
+        let db = Arc::new(Database::memory().expect("init db"));
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        let state = AppState::new(db);
+
+        let matrix = SpeedtestService::endpoint_matrix(&state, AppType::Claude, "p1")
+            .await
+            .expect("probe endpoints");
+
+        let urls: Vec<&str> = matrix.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(urls, vec![fast.as_str(), slow.as_str(), closed.as_str()]);
+        assert!(matrix[0].reachable && matrix[1].reachable);
+        assert_eq!(matrix[0].status, Some(200));
+        assert_eq!(matrix[0].last_used, Some(1_700_000_000_000));
+        assert!(!matrix[2].reachable);
+        assert_eq!(matrix[2].latency, None);
+        assert!(matrix[2].error.is_some());
+    }
 }
@@ -4,7 +4,7 @@
 
 use futures::StreamExt;
 use regex::Regex;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::time::Instant;
@@ -140,7 +140,7 @@ impl StreamCheckService {
     /// 合并供应商单独配置和全局配置
     ///
     /// 如果供应商配置了 meta.testConfig 且 enabled 为 true，则使用供应商配置覆盖全局配置
-    fn merge_provider_config(
+    pub(crate) fn merge_provider_config(
         provider: &Provider,
         global_config: &StreamCheckConfig,
     ) -> StreamCheckConfig {
@@ -302,7 +302,6 @@ impl StreamCheckService {
         timeout: std::time::Duration,
         provider: &Provider,
     ) -> Result<(u16, String), AppError> {
-        let base = base_url.trim_end_matches('/');
         let is_github_copilot = auth.strategy == AuthStrategy::GitHubCopilot;
 
         // Detect api_format: meta.api_format > settings_config.api_format > default "anthropic"
@@ -319,27 +318,7 @@ impl StreamCheckService {
             .unwrap_or("anthropic");
 
         let is_openai_chat = is_github_copilot || api_format == "openai_chat";
-
-        // URL:
-        // - GitHub Copilot: /chat/completions (no /v1 prefix)
-        // - OpenAI-compatible: /v1/chat/completions
-        // - Anthropic native: /v1/messages?beta=true
-        let url = if is_github_copilot {
-            format!("{base}/chat/completions")
-        } else if is_openai_chat {
-            if base.ends_with("/v1") {
-                format!("{base}/chat/completions")
-            } else {
-                format!("{base}/v1/chat/completions")
-            }
-        } else {
-            // ?beta=true is required by some relay services to verify request origin
-            if base.ends_with("/v1") {
-                format!("{base}/messages?beta=true")
-            } else {
-                format!("{base}/v1/messages?beta=true")
-            }
-        };
+        let url = Self::resolve_claude_stream_url(base_url, auth.strategy, api_format);
 
         // Build from Anthropic-native shape first, then convert for OpenAI-compatible targets.
         let anthropic_body = json!({
@@ -355,71 +334,7 @@ impl StreamCheckService {
             anthropic_body
         };
 
-        let mut request_builder = client.post(&url);
-
-        if is_github_copilot {
-            request_builder = request_builder
-                .header("authorization", format!("Bearer {}", auth.api_key))
-                .header("content-type", "application/json")
-                .header("accept", "text/event-stream")
-                .header("accept-encoding", "identity")
-                .header("user-agent", copilot_auth::COPILOT_USER_AGENT)
-                .header("editor-version", copilot_auth::COPILOT_EDITOR_VERSION)
-                .header("editor-plugin-version", copilot_auth::COPILOT_PLUGIN_VERSION)
-                .header("copilot-integration-id", copilot_auth::COPILOT_INTEGRATION_ID)
-                .header("x-github-api-version", copilot_auth::COPILOT_API_VERSION)
-                .header("openai-intent", "conversation-panel");
-        } else if is_openai_chat {
-            // OpenAI-compatible: Bearer auth + standard headers only
-            request_builder = request_builder
-                .header("authorization", format!("Bearer {}", auth.api_key))
-                .header("content-type", "application/json")
-                .header("accept", "text/event-stream")
-                .header("accept-encoding", "identity");
-        } else {
-            // Anthropic native: full Claude CLI headers
-            let os_name = Self::get_os_name();
-            let arch_name = Self::get_arch_name();
-
-            request_builder =
-                request_builder.header("authorization", format!("Bearer {}", auth.api_key));
-
-            // Only Anthropic official strategy adds x-api-key
-            if auth.strategy == AuthStrategy::Anthropic {
-                request_builder = request_builder.header("x-api-key", &auth.api_key);
-            }
-
-            request_builder = request_builder
-                // Anthropic required headers
-                .header("anthropic-version", "2023-06-01")
-                .header(
-                    "anthropic-beta",
-                    "claude-code-20250219,interleaved-thinking-2025-05-14",
-                )
-                .header("anthropic-dangerous-direct-browser-access", "true")
-                // Content type headers
-                .header("content-type", "application/json")
-                .header("accept", "application/json")
-                .header("accept-encoding", "identity")
-                .header("accept-language", "*")
-                // Client identification headers
-                .header("user-agent", "claude-cli/2.1.2 (external, cli)")
-                .header("x-app", "cli")
-                // x-stainless SDK headers (dynamic local system info)
-                .header("x-stainless-lang", "js")
-                .header("x-stainless-package-version", "0.70.0")
-                .header("x-stainless-os", os_name)
-                .header("x-stainless-arch", arch_name)
-                .header("x-stainless-runtime", "node")
-                .header("x-stainless-runtime-version", "v22.20.0")
-                .header("x-stainless-retry-count", "0")
-                .header("x-stainless-timeout", "600")
-                // Other headers
-                .header("sec-fetch-mode", "cors")
-                .header("connection", "keep-alive");
-        }
-
-        let response = request_builder
+        let response = Self::claude_request(client, &url, auth, is_openai_chat, true)
             .timeout(timeout)
             .json(&body)
             .send()
@@ -456,25 +371,11 @@ impl StreamCheckService {
         test_prompt: &str,
         timeout: std::time::Duration,
     ) -> Result<(u16, String), AppError> {
-        let base = base_url.trim_end_matches('/');
-        // Codex CLI 的 base_url 语义：base_url 是 API base（可能已包含 /v1 或其他自定义前缀），
-        // Responses 端点为 `/responses`。
-        //
-        // 兼容：如果 base_url 配成纯 origin（如 https://api.openai.com），则需要补 `/v1`。
-        // 优先尝试 `{base}/responses`，若 404 再回退 `{base}/v1/responses`。
-        let urls = if base.ends_with("/v1") {
-            vec![format!("{base}/responses")]
-        } else {
-            vec![format!("{base}/responses"), format!("{base}/v1/responses")]
-        };
+        let urls = Self::resolve_responses_urls(base_url);
 
         // 解析模型名和推理等级 (支持 model@level 或 model#level 格式)
         let (actual_model, reasoning_effort) = Self::parse_model_with_effort(model);
 
-        // 获取本地系统信息
-        let os_name = Self::get_os_name();
-        let arch_name = Self::get_arch_name();
-
         // Responses API 请求体格式 (input 必须是数组)
         let mut body = json!({
             "model": actual_model,
@@ -488,18 +389,7 @@ impl StreamCheckService {
         }
 
         for (i, url) in urls.iter().enumerate() {
-            // 严格按照 Codex CLI 请求格式设置 headers
-            let response = client
-                .post(url)
-                .header("authorization", format!("Bearer {}", auth.api_key))
-                .header("content-type", "application/json")
-                .header("accept", "text/event-stream")
-                .header("accept-encoding", "identity")
-                .header(
-                    "user-agent",
-                    format!("codex_cli_rs/0.80.0 ({os_name} 15.7.2; {arch_name}) Terminal"),
-                )
-                .header("originator", "codex_cli_rs")
+            let response = Self::codex_request(client, url, auth, true)
                 .timeout(timeout)
                 .json(&body)
                 .send()
@@ -544,15 +434,7 @@ impl StreamCheckService {
         test_prompt: &str,
         timeout: std::time::Duration,
     ) -> Result<(u16, String), AppError> {
-        let base = base_url.trim_end_matches('/');
-        // Gemini 原生 API: /v1beta/models/{model}:streamGenerateContent?alt=sse
-        // 智能处理 /v1beta 路径：如果 base_url 不包含版本路径，则添加 /v1beta
-        // alt=sse 参数使 API 返回 SSE 格式（text/event-stream）而非 JSON 数组
-        let url = if base.contains("/v1beta") || base.contains("/v1/") {
-            format!("{base}/models/{model}:streamGenerateContent?alt=sse")
-        } else {
-            format!("{base}/v1beta/models/{model}:streamGenerateContent?alt=sse")
-        };
+        let url = Self::resolve_gemini_url(base_url, model, true);
 
         // Gemini 原生请求体格式
         let body = json!({
@@ -562,11 +444,7 @@ impl StreamCheckService {
             }]
         });
 
-        let response = client
-            .post(&url)
-            .header("x-goog-api-key", &auth.api_key)
-            .header("Content-Type", "application/json")
-            .header("Accept", "text/event-stream")
+        let response = Self::gemini_request(client, &url, auth, true)
             .timeout(timeout)
             .json(&body)
             .send()
@@ -601,7 +479,7 @@ impl StreamCheckService {
 
     /// 解析模型名和推理等级 (支持 model@level 或 model#level 格式)
     /// 返回 (实际模型名, Option<推理等级>)
-    fn parse_model_with_effort(model: &str) -> (String, Option<String>) {
+    pub(crate) fn parse_model_with_effort(model: &str) -> (String, Option<String>) {
         if let Some(pos) = model.find('@').or_else(|| model.find('#')) {
             let actual_model = model[..pos].to_string();
             let effort = model[pos + 1..].to_string();
@@ -627,7 +505,7 @@ impl StreamCheckService {
         }
     }
 
-    fn resolve_test_model(
+    pub(crate) fn resolve_test_model(
         app_type: &AppType,
         provider: &Provider,
         config: &StreamCheckConfig,
@@ -724,8 +602,11 @@ impl StreamCheckService {
         }
     }
 
-    #[cfg(test)]
-    fn resolve_claude_stream_url(
+    /// Claude 请求地址：
+    /// - GitHub Copilot: /chat/completions (no /v1 prefix)
+    /// - OpenAI-compatible: /v1/chat/completions
+    /// - Anthropic native: /v1/messages?beta=true
+    pub(crate) fn resolve_claude_stream_url(
         base_url: &str,
         auth_strategy: AuthStrategy,
         api_format: &str,
@@ -743,11 +624,164 @@ impl StreamCheckService {
                 format!("{base}/v1/chat/completions")
             }
         } else if base.ends_with("/v1") {
+            // ?beta=true is required by some relay services to verify request origin
             format!("{base}/messages?beta=true")
         } else {
             format!("{base}/v1/messages?beta=true")
         }
     }
+
+    /// 构建 Claude 请求：Copilot 与 OpenAI 兼容格式只带标准请求头，Anthropic 原生格式带完整的 Claude CLI 请求头
+    pub(crate) fn claude_request(
+        client: &Client,
+        url: &str,
+        auth: &AuthInfo,
+        is_openai_compatible: bool,
+        stream: bool,
+    ) -> RequestBuilder {
+        let accept = Self::accept_header(stream);
+        let request_builder = client.post(url);
+
+        if auth.strategy == AuthStrategy::GitHubCopilot {
+            request_builder
+                .header("authorization", format!("Bearer {}", auth.api_key))
+                .header("content-type", "application/json")
+                .header("accept", accept)
+                .header("accept-encoding", "identity")
+                .header("user-agent", copilot_auth::COPILOT_USER_AGENT)
+                .header("editor-version", copilot_auth::COPILOT_EDITOR_VERSION)
+                .header("editor-plugin-version", copilot_auth::COPILOT_PLUGIN_VERSION)
+                .header("copilot-integration-id", copilot_auth::COPILOT_INTEGRATION_ID)
+                .header("x-github-api-version", copilot_auth::COPILOT_API_VERSION)
+                .header("openai-intent", "conversation-panel")
+        } else if is_openai_compatible {
+            // OpenAI-compatible: Bearer auth + standard headers only
+            request_builder
+                .header("authorization", format!("Bearer {}", auth.api_key))
+                .header("content-type", "application/json")
+                .header("accept", accept)
+                .header("accept-encoding", "identity")
+        } else {
+            // Anthropic native: full Claude CLI headers
+            let os_name = Self::get_os_name();
+            let arch_name = Self::get_arch_name();
+
+            let mut request_builder =
+                request_builder.header("authorization", format!("Bearer {}", auth.api_key));
+
+            // Only Anthropic official strategy adds x-api-key
+            if auth.strategy == AuthStrategy::Anthropic {
+                request_builder = request_builder.header("x-api-key", &auth.api_key);
+            }
+
+            request_builder
+                // Anthropic required headers
+                .header("anthropic-version", "2023-06-01")
+                .header(
+                    "anthropic-beta",
+                    "claude-code-20250219,interleaved-thinking-2025-05-14",
+                )
+                .header("anthropic-dangerous-direct-browser-access", "true")
+                // Content type headers
+                .header("content-type", "application/json")
+                .header("accept", "application/json")
+                .header("accept-encoding", "identity")
+                .header("accept-language", "*")
+                // Client identification headers
+                .header("user-agent", "claude-cli/2.1.2 (external, cli)")
+                .header("x-app", "cli")
+                // x-stainless SDK headers (dynamic local system info)
+                .header("x-stainless-lang", "js")
+                .header("x-stainless-package-version", "0.70.0")
+                .header("x-stainless-os", os_name)
+                .header("x-stainless-arch", arch_name)
+                .header("x-stainless-runtime", "node")
+                .header("x-stainless-runtime-version", "v22.20.0")
+                .header("x-stainless-retry-count", "0")
+                .header("x-stainless-timeout", "600")
+                // Other headers
+                .header("sec-fetch-mode", "cors")
+                .header("connection", "keep-alive")
+        }
+    }
+
+    /// Responses API 候选地址（按顺序尝试，首个返回 404 时回退到下一个）
+    ///
+    /// Codex CLI 的 base_url 语义：base_url 是 API base（可能已包含 /v1 或其他自定义前缀），
+    /// Responses 端点为 `/responses`。
+    ///
+    /// 兼容：如果 base_url 配成纯 origin（如 https://api.openai.com），则需要补 `/v1`。
+    /// 优先尝试 `{base}/responses`，若 404 再回退 `{base}/v1/responses`。
+    pub(crate) fn resolve_responses_urls(base_url: &str) -> Vec<String> {
+        let base = base_url.trim_end_matches('/');
+        if base.ends_with("/v1") {
+            vec![format!("{base}/responses")]
+        } else {
+            vec![format!("{base}/responses"), format!("{base}/v1/responses")]
+        }
+    }
+
+    /// 严格按照 Codex CLI 请求格式构建请求
+    pub(crate) fn codex_request(
+        client: &Client,
+        url: &str,
+        auth: &AuthInfo,
+        stream: bool,
+    ) -> RequestBuilder {
+        let os_name = Self::get_os_name();
+        let arch_name = Self::get_arch_name();
+        client
+            .post(url)
+            .header("authorization", format!("Bearer {}", auth.api_key))
+            .header("content-type", "application/json")
+            .header("accept", Self::accept_header(stream))
+            .header("accept-encoding", "identity")
+            .header(
+                "user-agent",
+                format!("codex_cli_rs/0.80.0 ({os_name} 15.7.2; {arch_name}) Terminal"),
+            )
+            .header("originator", "codex_cli_rs")
+    }
+
+    /// Gemini 原生 API 地址：/v1beta/models/{model}:streamGenerateContent?alt=sse
+    ///
+    /// 智能处理 /v1beta 路径：如果 base_url 不包含版本路径，则添加 /v1beta；
+    /// alt=sse 参数使 API 返回 SSE 格式（text/event-stream）而非 JSON 数组
+    pub(crate) fn resolve_gemini_url(base_url: &str, model: &str, stream: bool) -> String {
+        let base = base_url.trim_end_matches('/');
+        let method = if stream {
+            "streamGenerateContent?alt=sse"
+        } else {
+            "generateContent"
+        };
+        if base.contains("/v1beta") || base.contains("/v1/") {
+            format!("{base}/models/{model}:{method}")
+        } else {
+            format!("{base}/v1beta/models/{model}:{method}")
+        }
+    }
+
+    /// 构建 Gemini 原生请求
+    pub(crate) fn gemini_request(
+        client: &Client,
+        url: &str,
+        auth: &AuthInfo,
+        stream: bool,
+    ) -> RequestBuilder {
+        client
+            .post(url)
+            .header("x-goog-api-key", &auth.api_key)
+            .header("Content-Type", "application/json")
+            .header("Accept", Self::accept_header(stream))
+    }
+
+    fn accept_header(stream: bool) -> &'static str {
+        if stream {
+            "text/event-stream"
+        } else {
+            "application/json"
+        }
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,7 @@
 
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
+use crate::proxy::usage::{CostBreakdown, CostCalculator, TokenUsage, UsageLogger};
 use chrono::{Local, TimeZone};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
@@ -101,12 +102,29 @@ pub struct RequestLogDetail {
     pub output_tokens: u32,
     pub cache_read_tokens: u32,
     pub cache_creation_tokens: u32,
+    pub reasoning_tokens: u32,
     pub input_cost_usd: String,
     pub output_cost_usd: String,
     pub cache_read_cost_usd: String,
     pub cache_creation_cost_usd: String,
+    pub reasoning_cost_usd: String,
     pub total_cost_usd: String,
     pub is_streaming: bool,
+    /// 响应缓存命中（未请求上游）
+    #[serde(default)]
+    pub is_cached: bool,
+    /// 影子请求（镜像发往影子供应商，客户端不可见）
+    #[serde(default)]
+    pub is_shadow: bool,
+    /// 使用的 Key 池序号（供应商未配置 Key 池时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_index: Option<u32>,
+    /// 由兜底供应商完成的请求
+    #[serde(default)]
+    pub is_safety_net: bool,
+    /// 供应商输出 token 上限生效后的最大输出 token 数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_max_tokens: Option<u64>,
     pub latency_ms: u64,
     pub first_token_ms: Option<u64>,
     pub duration_ms: Option<u64>,
@@ -115,6 +133,80 @@ pub struct RequestLogDetail {
     pub created_at: i64,
 }
 
+/// 会话请求日志导出文档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogExport {
+    pub session_id: String,
+    pub exported_at: i64,
+    pub request_count: u32,
+    pub logs: Vec<RequestLogDetail>,
+}
+
+/// 单个会话的费用汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCost {
+    pub session_id: String,
+    /// 会话内的请求（消息）数，不含影子请求
+    pub message_count: u32,
+    pub total_cost: String,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_cache_creation_tokens: u64,
+    pub total_cache_read_tokens: u64,
+}
+
+/// 供应商花费排行条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSpend {
+    pub provider_id: String,
+    pub app_type: String,
+    pub provider_name: String,
+    pub request_count: u64,
+    pub total_tokens: u64,
+    pub total_cost: String,
+}
+
+/// 将日志查询结果行映射为 [`RequestLogDetail`]（列顺序需与各查询的 SELECT 保持一致）
+fn request_log_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RequestLogDetail> {
+    Ok(RequestLogDetail {
+        request_id: row.get(0)?,
+        provider_id: row.get(1)?,
+        provider_name: row.get(2)?,
+        app_type: row.get(3)?,
+        model: row.get(4)?,
+        request_model: row.get(5)?,
+        cost_multiplier: row
+            .get::<_, Option<String>>(6)?
+            .unwrap_or_else(|| "1".to_string()),
+        input_tokens: row.get::<_, i64>(7)? as u32,
+        output_tokens: row.get::<_, i64>(8)? as u32,
+        cache_read_tokens: row.get::<_, i64>(9)? as u32,
+        cache_creation_tokens: row.get::<_, i64>(10)? as u32,
+        reasoning_tokens: row.get::<_, i64>(23)? as u32,
+        input_cost_usd: row.get(11)?,
+        output_cost_usd: row.get(12)?,
+        cache_read_cost_usd: row.get(13)?,
+        cache_creation_cost_usd: row.get(14)?,
+        reasoning_cost_usd: row.get(24)?,
+        total_cost_usd: row.get(15)?,
+        is_streaming: row.get::<_, i64>(16)? != 0,
+        is_cached: row.get::<_, i64>(25)? != 0,
+        is_shadow: row.get::<_, i64>(26)? != 0,
+        api_key_index: row.get::<_, Option<i64>>(27)?.map(|v| v as u32),
+        is_safety_net: row.get::<_, i64>(28)? != 0,
+        effective_max_tokens: row.get::<_, Option<i64>>(29)?.map(|v| v as u64),
+        latency_ms: row.get::<_, i64>(17)? as u64,
+        first_token_ms: row.get::<_, Option<i64>>(18)?.map(|v| v as u64),
+        duration_ms: row.get::<_, Option<i64>>(19)?.map(|v| v as u64),
+        status_code: row.get::<_, i64>(20)? as u16,
+        error_message: row.get(21)?,
+        created_at: row.get(22)?,
+    })
+}
+
 impl Database {
     /// 获取使用量汇总
     pub fn get_usage_summary(
@@ -124,23 +216,18 @@ impl Database {
     ) -> Result<UsageSummary, AppError> {
         let conn = lock_conn!(self.conn);
 
-        let (where_clause, params_vec) = if start_date.is_some() || end_date.is_some() {
-            let mut conditions = Vec::new();
-            let mut params = Vec::new();
-
-            if let Some(start) = start_date {
-                conditions.push("created_at >= ?");
-                params.push(start);
-            }
-            if let Some(end) = end_date {
-                conditions.push("created_at <= ?");
-                params.push(end);
-            }
-
-            (format!("WHERE {}", conditions.join(" AND ")), params)
-        } else {
-            (String::new(), Vec::new())
-        };
+        // 影子请求只用于对比，不计入用量统计
+        let mut conditions = vec!["is_shadow = 0"];
+        let mut params_vec = Vec::new();
+        if let Some(start) = start_date {
+            conditions.push("created_at >= ?");
+            params_vec.push(start);
+        }
+        if let Some(end) = end_date {
+            conditions.push("created_at <= ?");
+            params_vec.push(end);
+        }
+        let where_clause = format!("WHERE {}", conditions.join(" AND "));
 
         // Build rollup WHERE clause using date strings (use ? for sequential binding)
         let (rollup_where, rollup_params) = if start_date.is_some() || end_date.is_some() {
@@ -272,7 +359,7 @@ impl Database {
                 COALESCE(SUM(cache_creation_tokens), 0) as total_cache_creation_tokens,
                 COALESCE(SUM(cache_read_tokens), 0) as total_cache_read_tokens
             FROM proxy_request_logs
-            WHERE created_at >= ?1 AND created_at <= ?2
+            WHERE created_at >= ?1 AND created_at <= ?2 AND is_shadow = 0
             GROUP BY bucket_idx
             ORDER BY bucket_idx ASC";
 
@@ -421,6 +508,7 @@ impl Database {
                     COALESCE(SUM(l.latency_ms), 0) as latency_sum
                 FROM proxy_request_logs l
                 LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
+                WHERE l.is_shadow = 0
                 GROUP BY l.provider_id, l.app_type
                 UNION ALL
                 SELECT r.provider_id, r.app_type,
@@ -468,59 +556,164 @@ impl Database {
         Ok(stats)
     }
 
-    /// 获取模型统计
-    pub fn get_model_stats(&self) -> Result<Vec<ModelStats>, AppError> {
+    /// 按花费从高到低列出供应商，用于定位成本热点
+    ///
+    /// 合并明细日志（`created_at` 落在可选的闭区间内）与已清理明细的日聚合数据（按本地日期过滤），
+    /// 影子请求不计入；`app_type` 为空时汇总所有应用。
+    pub fn get_providers_by_spend(
+        &self,
+        app_type: Option<String>,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+    ) -> Result<Vec<ProviderSpend>, AppError> {
         let conn = lock_conn!(self.conn);
 
-        // UNION detail logs + rollup data
-        let sql = "SELECT
-                model,
-                SUM(request_count) as request_count,
-                SUM(total_tokens) as total_tokens,
-                SUM(total_cost) as total_cost
+        let mut conditions = vec!["is_shadow = 0".to_string()];
+        let mut rollup_conditions: Vec<String> = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let mut rollup_params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(app_type) = app_type {
+            conditions.push("app_type = ?".to_string());
+            params.push(Box::new(app_type.clone()));
+            rollup_conditions.push("app_type = ?".to_string());
+            rollup_params.push(Box::new(app_type));
+        }
+        if let Some(start) = start_ts {
+            conditions.push("created_at >= ?".to_string());
+            params.push(Box::new(start));
+            rollup_conditions.push("date >= date(?, 'unixepoch', 'localtime')".to_string());
+            rollup_params.push(Box::new(start));
+        }
+        if let Some(end) = end_ts {
+            conditions.push("created_at <= ?".to_string());
+            params.push(Box::new(end));
+            rollup_conditions.push("date <= date(?, 'unixepoch', 'localtime')".to_string());
+            rollup_params.push(Box::new(end));
+        }
+
+        let rollup_where = if rollup_conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", rollup_conditions.join(" AND "))
+        };
+
+        // UNION detail logs + rollup data, then aggregate
+        let sql = format!(
+            "SELECT s.provider_id, s.app_type, p.name,
+                SUM(s.request_count) as request_count,
+                SUM(s.total_tokens) as total_tokens,
+                SUM(s.total_cost) as total_cost
             FROM (
-                SELECT model,
+                SELECT provider_id, app_type,
                     COUNT(*) as request_count,
                     COALESCE(SUM(input_tokens + output_tokens), 0) as total_tokens,
                     COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0) as total_cost
                 FROM proxy_request_logs
-                GROUP BY model
+                WHERE {}
+                GROUP BY provider_id, app_type
+                UNION ALL
+                SELECT provider_id, app_type,
+                    COALESCE(SUM(request_count), 0),
+                    COALESCE(SUM(input_tokens + output_tokens), 0),
+                    COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+                FROM usage_daily_rollups
+                {rollup_where}
+                GROUP BY provider_id, app_type
+            ) s
+            LEFT JOIN providers p ON s.provider_id = p.id AND s.app_type = p.app_type
+            GROUP BY s.provider_id, s.app_type
+            ORDER BY total_cost DESC, request_count DESC",
+            conditions.join(" AND ")
+        );
+        // 参数顺序：先明细再聚合
+        params.extend(rollup_params);
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok(ProviderSpend {
+                provider_id: row.get(0)?,
+                app_type: row.get(1)?,
+                provider_name: row
+                    .get::<_, Option<String>>(2)?
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                request_count: row.get::<_, i64>(3)? as u64,
+                total_tokens: row.get::<_, i64>(4)? as u64,
+                total_cost: format!("{:.6}", row.get::<_, f64>(5)?),
+            })
+        })?;
+
+        let mut spend = Vec::new();
+        for row in rows {
+            spend.push(row?);
+        }
+
+        Ok(spend)
+    }
+
+    /// 获取模型统计（明细日志按清洗后的模型名归并）
+    pub fn get_model_stats(&self) -> Result<Vec<ModelStats>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        // UNION detail logs + rollup data
+        // 聚合表保存的是原始模型名，两部分都在下面按 normalize_model_name 归一化后再合并
+        let sql = "SELECT COALESCE(model_normalized, model),
+                    COUNT(*),
+                    COALESCE(SUM(input_tokens + output_tokens), 0),
+                    COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+                FROM proxy_request_logs
+                WHERE is_shadow = 0
+                GROUP BY COALESCE(model_normalized, model)
                 UNION ALL
                 SELECT model,
                     COALESCE(SUM(request_count), 0),
                     COALESCE(SUM(input_tokens + output_tokens), 0),
                     COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
                 FROM usage_daily_rollups
-                GROUP BY model
-            )
-            GROUP BY model
-            ORDER BY total_cost DESC";
+                GROUP BY model";
 
         let mut stmt = conn.prepare(sql)?;
         let rows = stmt.query_map([], |row| {
-            let request_count: i64 = row.get(1)?;
-            let total_cost: f64 = row.get(3)?;
-            let avg_cost = if request_count > 0 {
-                total_cost / request_count as f64
-            } else {
-                0.0
-            };
-
-            Ok(ModelStats {
-                model: row.get(0)?,
-                request_count: request_count as u64,
-                total_tokens: row.get::<_, i64>(2)? as u64,
-                total_cost: format!("{total_cost:.6}"),
-                avg_cost_per_request: format!("{avg_cost:.6}"),
-            })
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
         })?;
 
-        let mut stats = Vec::new();
+        let mut totals: HashMap<String, (i64, i64, f64)> = HashMap::new();
         for row in rows {
-            stats.push(row?);
+            let (model, request_count, total_tokens, total_cost) = row?;
+            let entry = totals.entry(normalize_model_name(&model)).or_default();
+            entry.0 += request_count;
+            entry.1 += total_tokens;
+            entry.2 += total_cost;
         }
 
-        Ok(stats)
+        let mut stats: Vec<(String, i64, i64, f64)> = totals
+            .into_iter()
+            .map(|(model, (count, tokens, cost))| (model, count, tokens, cost))
+            .collect();
+        stats.sort_by(|a, b| b.3.total_cmp(&a.3).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(stats
+            .into_iter()
+            .map(|(model, request_count, total_tokens, total_cost)| {
+                let avg_cost = if request_count > 0 {
+                    total_cost / request_count as f64
+                } else {
+                    0.0
+                };
+                ModelStats {
+                    model,
+                    request_count: request_count as u64,
+                    total_tokens: total_tokens as u64,
+                    total_cost: format!("{total_cost:.6}"),
+                    avg_cost_per_request: format!("{avg_cost:.6}"),
+                }
+            })
+            .collect())
     }
 
     /// 获取请求日志列表（分页）
@@ -588,7 +781,9 @@ impl Database {
                     l.input_tokens, l.output_tokens, l.cache_read_tokens, l.cache_creation_tokens,
                     l.input_cost_usd, l.output_cost_usd, l.cache_read_cost_usd, l.cache_creation_cost_usd, l.total_cost_usd,
                     l.is_streaming, l.latency_ms, l.first_token_ms, l.duration_ms,
-                    l.status_code, l.error_message, l.created_at
+                    l.status_code, l.error_message, l.created_at,
+                    l.reasoning_tokens, l.reasoning_cost_usd, l.is_cached, l.is_shadow,
+                    l.api_key_index, l.is_safety_net, l.effective_max_tokens
              FROM proxy_request_logs l
              LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
              {where_clause}
@@ -598,35 +793,7 @@ impl Database {
 
         let mut stmt = conn.prepare(&sql)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
-        let rows = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(RequestLogDetail {
-                request_id: row.get(0)?,
-                provider_id: row.get(1)?,
-                provider_name: row.get(2)?,
-                app_type: row.get(3)?,
-                model: row.get(4)?,
-                request_model: row.get(5)?,
-                cost_multiplier: row
-                    .get::<_, Option<String>>(6)?
-                    .unwrap_or_else(|| "1".to_string()),
-                input_tokens: row.get::<_, i64>(7)? as u32,
-                output_tokens: row.get::<_, i64>(8)? as u32,
-                cache_read_tokens: row.get::<_, i64>(9)? as u32,
-                cache_creation_tokens: row.get::<_, i64>(10)? as u32,
-                input_cost_usd: row.get(11)?,
-                output_cost_usd: row.get(12)?,
-                cache_read_cost_usd: row.get(13)?,
-                cache_creation_cost_usd: row.get(14)?,
-                total_cost_usd: row.get(15)?,
-                is_streaming: row.get::<_, i64>(16)? != 0,
-                latency_ms: row.get::<_, i64>(17)? as u64,
-                first_token_ms: row.get::<_, Option<i64>>(18)?.map(|v| v as u64),
-                duration_ms: row.get::<_, Option<i64>>(19)?.map(|v| v as u64),
-                status_code: row.get::<_, i64>(20)? as u16,
-                error_message: row.get(21)?,
-                created_at: row.get(22)?,
-            })
-        })?;
+        let rows = stmt.query_map(params_refs.as_slice(), request_log_from_row)?;
 
         let mut logs = Vec::new();
         let mut provider_cache = HashMap::new();
@@ -664,38 +831,14 @@ impl Database {
                     input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
                     input_cost_usd, output_cost_usd, cache_read_cost_usd, cache_creation_cost_usd, total_cost_usd,
                     is_streaming, latency_ms, first_token_ms, duration_ms,
-                    status_code, error_message, created_at
+                    status_code, error_message, created_at,
+                    reasoning_tokens, reasoning_cost_usd, is_cached, is_shadow, api_key_index,
+                    is_safety_net, effective_max_tokens
              FROM proxy_request_logs l
              LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
              WHERE l.request_id = ?",
             [request_id],
-            |row| {
-                Ok(RequestLogDetail {
-                    request_id: row.get(0)?,
-                    provider_id: row.get(1)?,
-                    provider_name: row.get(2)?,
-                    app_type: row.get(3)?,
-                    model: row.get(4)?,
-                    request_model: row.get(5)?,
-                    cost_multiplier: row.get::<_, Option<String>>(6)?.unwrap_or_else(|| "1".to_string()),
-                    input_tokens: row.get::<_, i64>(7)? as u32,
-                    output_tokens: row.get::<_, i64>(8)? as u32,
-                    cache_read_tokens: row.get::<_, i64>(9)? as u32,
-                    cache_creation_tokens: row.get::<_, i64>(10)? as u32,
-                    input_cost_usd: row.get(11)?,
-                    output_cost_usd: row.get(12)?,
-                    cache_read_cost_usd: row.get(13)?,
-                    cache_creation_cost_usd: row.get(14)?,
-                    total_cost_usd: row.get(15)?,
-                    is_streaming: row.get::<_, i64>(16)? != 0,
-                    latency_ms: row.get::<_, i64>(17)? as u64,
-                    first_token_ms: row.get::<_, Option<i64>>(18)?.map(|v| v as u64),
-                    duration_ms: row.get::<_, Option<i64>>(19)?.map(|v| v as u64),
-                    status_code: row.get::<_, i64>(20)? as u16,
-                    error_message: row.get(21)?,
-                    created_at: row.get(22)?,
-                })
-            },
+            request_log_from_row,
         );
 
         match result {
@@ -715,6 +858,113 @@ impl Database {
         }
     }
 
+    /// 获取同一会话的全部请求日志（按时间先后排序）
+    pub fn get_session_logs(&self, session_id: &str) -> Result<Vec<RequestLogDetail>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut stmt = conn.prepare(
+            "SELECT l.request_id, l.provider_id, p.name as provider_name, l.app_type, l.model,
+                    l.request_model, l.cost_multiplier,
+                    l.input_tokens, l.output_tokens, l.cache_read_tokens, l.cache_creation_tokens,
+                    l.input_cost_usd, l.output_cost_usd, l.cache_read_cost_usd, l.cache_creation_cost_usd, l.total_cost_usd,
+                    l.is_streaming, l.latency_ms, l.first_token_ms, l.duration_ms,
+                    l.status_code, l.error_message, l.created_at,
+                    l.reasoning_tokens, l.reasoning_cost_usd, l.is_cached, l.is_shadow,
+                    l.api_key_index, l.is_safety_net, l.effective_max_tokens
+             FROM proxy_request_logs l
+             LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
+             WHERE l.session_id = ?
+             ORDER BY l.created_at ASC, l.rowid ASC",
+        )?;
+        let rows = stmt.query_map([session_id], request_log_from_row)?;
+
+        let mut logs = Vec::new();
+        let mut provider_cache = HashMap::new();
+        let mut pricing_cache = HashMap::new();
+
+        for row in rows {
+            let mut log = row?;
+            Self::maybe_backfill_log_costs(
+                &conn,
+                &mut log,
+                &mut provider_cache,
+                &mut pricing_cache,
+            )?;
+            logs.push(log);
+        }
+
+        Ok(logs)
+    }
+
+    /// 导出同一会话的请求日志为单个 JSON 文档（用于分享复现）
+    pub fn export_session_logs(&self, session_id: &str) -> Result<SessionLogExport, AppError> {
+        let logs = self.get_session_logs(session_id)?;
+        Ok(SessionLogExport {
+            session_id: session_id.to_string(),
+            exported_at: chrono::Utc::now().timestamp(),
+            request_count: logs.len() as u32,
+            logs,
+        })
+    }
+
+    /// 汇总同一会话的费用与 Token 用量（成本为 0 的日志按当前定价回填后计入）
+    ///
+    /// 影子请求是镜像对比用的额外请求，不属于会话本身，不计入汇总。
+    pub fn get_session_cost(&self, session_id: &str) -> Result<SessionCost, AppError> {
+        let logs = self.get_session_logs(session_id)?;
+
+        let mut summary = SessionCost {
+            session_id: session_id.to_string(),
+            message_count: 0,
+            total_cost: String::new(),
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_cache_creation_tokens: 0,
+            total_cache_read_tokens: 0,
+        };
+        let mut total_cost = rust_decimal::Decimal::ZERO;
+        for log in logs.iter().filter(|log| !log.is_shadow) {
+            summary.message_count += 1;
+            total_cost += rust_decimal::Decimal::from_str(&log.total_cost_usd)
+                .unwrap_or(rust_decimal::Decimal::ZERO);
+            summary.total_input_tokens += log.input_tokens as u64;
+            summary.total_output_tokens += log.output_tokens as u64;
+            summary.total_cache_creation_tokens += log.cache_creation_tokens as u64;
+            summary.total_cache_read_tokens += log.cache_read_tokens as u64;
+        }
+        summary.total_cost = format!("{total_cost:.6}");
+
+        Ok(summary)
+    }
+
+    /// 删除 `created_at` 落在 [start_ts, end_ts]（闭区间，Unix 秒）内的请求日志，返回删除条数
+    ///
+    /// 统计与汇总均在查询时从明细实时计算，删除后自然生效；
+    /// 已汇总进 `usage_daily_rollups` 的历史数据按天聚合，不受影响。
+    pub fn delete_request_logs_between(
+        &self,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<usize, AppError> {
+        if start_ts > end_ts {
+            return Err(AppError::localized(
+                "usage.logs.invalid_range",
+                format!("无效的时间范围: 开始时间 {start_ts} 晚于结束时间 {end_ts}"),
+                format!("Invalid range: start {start_ts} is after end {end_ts}"),
+            ));
+        }
+
+        let conn = lock_conn!(self.conn);
+        let deleted = conn
+            .execute(
+                "DELETE FROM proxy_request_logs WHERE created_at >= ?1 AND created_at <= ?2",
+                params![start_ts, end_ts],
+            )
+            .map_err(|e| AppError::Database(format!("删除请求日志失败: {e}")))?;
+        log::info!("已删除 {deleted} 条请求日志 ({start_ts} ~ {end_ts})");
+        Ok(deleted)
+    }
+
     /// 检查 Provider 使用限额
     pub fn check_provider_limits(
         &self,
@@ -754,7 +1004,7 @@ impl Database {
                 "SELECT COALESCE(SUM(cost), 0) FROM (
                     SELECT CAST(total_cost_usd AS REAL) as cost
                     FROM proxy_request_logs
-                    WHERE provider_id = ? AND app_type = ?
+                    WHERE provider_id = ? AND app_type = ? AND is_shadow = 0
                       AND date(datetime(created_at, 'unixepoch', 'localtime')) = date('now', 'localtime')
                     UNION ALL
                     SELECT CAST(total_cost_usd AS REAL)
@@ -773,7 +1023,7 @@ impl Database {
                 "SELECT COALESCE(SUM(cost), 0) FROM (
                     SELECT CAST(total_cost_usd AS REAL) as cost
                     FROM proxy_request_logs
-                    WHERE provider_id = ? AND app_type = ?
+                    WHERE provider_id = ? AND app_type = ? AND is_shadow = 0
                       AND strftime('%Y-%m', datetime(created_at, 'unixepoch', 'localtime')) = strftime('%Y-%m', 'now', 'localtime')
                     UNION ALL
                     SELECT CAST(total_cost_usd AS REAL)
@@ -824,15 +1074,120 @@ struct PricingInfo {
     output: rust_decimal::Decimal,
     cache_read: rust_decimal::Decimal,
     cache_creation: rust_decimal::Decimal,
+    reasoning: rust_decimal::Decimal,
+}
+
+/// 请求成本预估（与请求日志中的成本字段格式一致）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub input_cost_usd: String,
+    pub output_cost_usd: String,
+    pub cache_read_cost_usd: String,
+    pub cache_creation_cost_usd: String,
+    pub reasoning_cost_usd: String,
+    pub total_cost_usd: String,
+}
+
+impl From<&CostBreakdown> for CostEstimate {
+    fn from(cost: &CostBreakdown) -> Self {
+        Self {
+            input_cost_usd: cost.input_cost.to_string(),
+            output_cost_usd: cost.output_cost.to_string(),
+            cache_read_cost_usd: cost.cache_read_cost.to_string(),
+            cache_creation_cost_usd: cost.cache_creation_cost.to_string(),
+            reasoning_cost_usd: cost.reasoning_cost.to_string(),
+            total_cost_usd: cost.total_cost.to_string(),
+        }
+    }
 }
 
 impl Database {
+    /// 发送前预估一次请求的成本
+    ///
+    /// 定价与倍率的解析与实际记录时完全一致（`model_pricing` + 供应商倍率，未配置时回退全局默认），
+    /// 因此相同 token 数下预估值等于实际记录的成本。模型未配置定价时返回错误。
+    pub async fn estimate_cost(
+        &self,
+        model: &str,
+        input_tokens: u32,
+        expected_output_tokens: u32,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<CostBreakdown, AppError> {
+        let logger = UsageLogger::new(self);
+        let pricing = logger.get_model_pricing(model)?.ok_or_else(|| {
+            AppError::localized(
+                "usage.pricing.not_found",
+                format!("未找到模型定价: {model}"),
+                format!("No pricing configured for model: {model}"),
+            )
+        })?;
+        let (multiplier, _) = logger.resolve_pricing_config(provider_id, app_type).await;
+
+        let usage = TokenUsage {
+            input_tokens,
+            output_tokens: expected_output_tokens,
+            ..Default::default()
+        };
+        Ok(CostCalculator::calculate(&usage, &pricing, multiplier))
+    }
+}
+
+impl Database {
+    /// 用当前定价重新计算所有“有用量但成本为 0”的日志，返回更新的行数
+    ///
+    /// 请求发生时缺少定价会把成本记为 0，此前只有查看日志时才会补算；
+    /// 定价更新后批量补算，保证统计汇总也能反映正确成本。
+    pub fn backfill_all_zero_cost_logs(&self) -> Result<usize, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut stmt = conn.prepare(
+            "SELECT l.request_id, l.provider_id, p.name as provider_name, l.app_type, l.model,
+                    l.request_model, l.cost_multiplier,
+                    l.input_tokens, l.output_tokens, l.cache_read_tokens, l.cache_creation_tokens,
+                    l.input_cost_usd, l.output_cost_usd, l.cache_read_cost_usd, l.cache_creation_cost_usd, l.total_cost_usd,
+                    l.is_streaming, l.latency_ms, l.first_token_ms, l.duration_ms,
+                    l.status_code, l.error_message, l.created_at,
+                    l.reasoning_tokens, l.reasoning_cost_usd, l.is_cached, l.is_shadow,
+                    l.api_key_index, l.is_safety_net, l.effective_max_tokens
+             FROM proxy_request_logs l
+             LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
+             WHERE CAST(COALESCE(l.total_cost_usd, '0') AS REAL) = 0
+               AND (l.input_tokens > 0 OR l.output_tokens > 0
+                    OR l.cache_read_tokens > 0 OR l.cache_creation_tokens > 0)",
+        )?;
+        let logs = stmt
+            .query_map([], request_log_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut provider_cache = HashMap::new();
+        let mut pricing_cache = HashMap::new();
+        let mut updated = 0;
+        for mut log in logs {
+            if Self::maybe_backfill_log_costs(
+                &conn,
+                &mut log,
+                &mut provider_cache,
+                &mut pricing_cache,
+            )? {
+                updated += 1;
+            }
+        }
+
+        if updated > 0 {
+            log::info!("已补算 {updated} 条请求日志的成本");
+        }
+        Ok(updated)
+    }
+
+    /// 成本为 0 且有用量时按当前定价补算并写回，返回是否更新
     fn maybe_backfill_log_costs(
         conn: &Connection,
         log: &mut RequestLogDetail,
         provider_cache: &mut HashMap<(String, String), rust_decimal::Decimal>,
         pricing_cache: &mut HashMap<String, PricingInfo>,
-    ) -> Result<(), AppError> {
+    ) -> Result<bool, AppError> {
         let total_cost = rust_decimal::Decimal::from_str(&log.total_cost_usd)
             .unwrap_or(rust_decimal::Decimal::ZERO);
         let has_cost = total_cost > rust_decimal::Decimal::ZERO;
@@ -842,12 +1197,12 @@ impl Database {
             || log.cache_creation_tokens > 0;
 
         if has_cost || !has_usage {
-            return Ok(());
+            return Ok(false);
         }
 
         let pricing = match Self::get_model_pricing_cached(conn, pricing_cache, &log.model)? {
             Some(info) => info,
-            None => return Ok(()),
+            None => return Ok(false),
         };
         let multiplier = Self::get_cost_multiplier_cached(
             conn,
@@ -866,22 +1221,30 @@ impl Database {
             (log.input_tokens as u64).saturating_sub(log.cache_read_tokens as u64);
         let input_cost =
             rust_decimal::Decimal::from(billable_input_tokens) * pricing.input / million;
+        // 推理 tokens 包含在 output_tokens 中，单独按推理价格计费
+        let reasoning_tokens = log.reasoning_tokens.min(log.output_tokens);
         let output_cost =
-            rust_decimal::Decimal::from(log.output_tokens as u64) * pricing.output / million;
+            rust_decimal::Decimal::from((log.output_tokens - reasoning_tokens) as u64)
+                * pricing.output
+                / million;
         let cache_read_cost = rust_decimal::Decimal::from(log.cache_read_tokens as u64)
             * pricing.cache_read
             / million;
         let cache_creation_cost = rust_decimal::Decimal::from(log.cache_creation_tokens as u64)
             * pricing.cache_creation
             / million;
+        let reasoning_cost =
+            rust_decimal::Decimal::from(reasoning_tokens as u64) * pricing.reasoning / million;
         // 总成本 = 基础成本之和 × 倍率
-        let base_total = input_cost + output_cost + cache_read_cost + cache_creation_cost;
+        let base_total =
+            input_cost + output_cost + cache_read_cost + cache_creation_cost + reasoning_cost;
         let total_cost = base_total * multiplier;
 
         log.input_cost_usd = format!("{input_cost:.6}");
         log.output_cost_usd = format!("{output_cost:.6}");
         log.cache_read_cost_usd = format!("{cache_read_cost:.6}");
         log.cache_creation_cost_usd = format!("{cache_creation_cost:.6}");
+        log.reasoning_cost_usd = format!("{reasoning_cost:.6}");
         log.total_cost_usd = format!("{total_cost:.6}");
 
         conn.execute(
@@ -890,20 +1253,22 @@ impl Database {
                  output_cost_usd = ?2,
                  cache_read_cost_usd = ?3,
                  cache_creation_cost_usd = ?4,
-                 total_cost_usd = ?5
-             WHERE request_id = ?6",
+                 reasoning_cost_usd = ?5,
+                 total_cost_usd = ?6
+             WHERE request_id = ?7",
             params![
                 log.input_cost_usd,
                 log.output_cost_usd,
                 log.cache_read_cost_usd,
                 log.cache_creation_cost_usd,
+                log.reasoning_cost_usd,
                 log.total_cost_usd,
                 log.request_id
             ],
         )
         .map_err(|e| AppError::Database(format!("更新请求成本失败: {e}")))?;
 
-        Ok(())
+        Ok(true)
     }
 
     fn get_cost_multiplier_cached(
@@ -949,19 +1314,26 @@ impl Database {
         }
 
         let row = find_model_pricing_row(conn, model)?;
-        let Some((input, output, cache_read, cache_creation)) = row else {
+        let Some((input, output, cache_read, cache_creation, reasoning)) = row else {
             return Ok(None);
         };
 
+        let output = rust_decimal::Decimal::from_str(&output)
+            .map_err(|e| AppError::Database(format!("解析输出价格失败: {e}")))?;
+        let reasoning = match reasoning.as_deref().map(str::trim) {
+            Some(value) if !value.is_empty() => rust_decimal::Decimal::from_str(value)
+                .map_err(|e| AppError::Database(format!("解析推理价格失败: {e}")))?,
+            _ => output,
+        };
         let pricing = PricingInfo {
             input: rust_decimal::Decimal::from_str(&input)
                 .map_err(|e| AppError::Database(format!("解析输入价格失败: {e}")))?,
-            output: rust_decimal::Decimal::from_str(&output)
-                .map_err(|e| AppError::Database(format!("解析输出价格失败: {e}")))?,
+            output,
             cache_read: rust_decimal::Decimal::from_str(&cache_read)
                 .map_err(|e| AppError::Database(format!("解析缓存读取价格失败: {e}")))?,
             cache_creation: rust_decimal::Decimal::from_str(&cache_creation)
                 .map_err(|e| AppError::Database(format!("解析缓存写入价格失败: {e}")))?,
+            reasoning,
         };
 
         cache.insert(model.to_string(), pricing.clone());
@@ -969,26 +1341,38 @@ impl Database {
     }
 }
 
-pub(crate) fn find_model_pricing_row(
-    conn: &Connection,
-    model_id: &str,
-) -> Result<Option<(String, String, String, String)>, AppError> {
-    // 清洗模型名称：去前缀(/)、去后缀(:)、@ 替换为 -
-    // 例如 moonshotai/gpt-5.2-codex@low:v2 → gpt-5.2-codex-low
-    let cleaned = model_id
+/// 模型定价行：(input, output, cache_read, cache_creation, reasoning)
+///
+/// reasoning 为 None 表示未单独配置推理价格，按输出价格计费
+pub(crate) type ModelPricingRow = (String, String, String, String, Option<String>);
+
+/// 清洗模型名称：去前缀(/)、去后缀(:)、@ 替换为 -
+///
+/// 例如 moonshotai/gpt-5.2-codex@low:v2 → gpt-5.2-codex-low。
+/// 定价查询与请求日志的 `model_normalized` 列共用此规则，使不同中转站的同一模型归并统计。
+pub(crate) fn normalize_model_name(model_id: &str) -> String {
+    model_id
         .rsplit_once('/')
         .map_or(model_id, |(_, r)| r)
         .split(':')
         .next()
         .unwrap_or(model_id)
         .trim()
-        .replace('@', "-");
+        .replace('@', "-")
+}
+
+pub(crate) fn find_model_pricing_row(
+    conn: &Connection,
+    model_id: &str,
+) -> Result<Option<ModelPricingRow>, AppError> {
+    let cleaned = normalize_model_name(model_id);
 
     // 精确匹配清洗后的名称
     let exact = conn
         .query_row(
             "SELECT input_cost_per_million, output_cost_per_million,
-                    cache_read_cost_per_million, cache_creation_cost_per_million
+                    cache_read_cost_per_million, cache_creation_cost_per_million,
+                    reasoning_cost_per_million
              FROM model_pricing
              WHERE model_id = ?1",
             [&cleaned],
@@ -998,6 +1382,7 @@ pub(crate) fn find_model_pricing_row(
                     row.get::<_, String>(1)?,
                     row.get::<_, String>(2)?,
                     row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
                 ))
             },
         )
@@ -1015,6 +1400,45 @@ pub(crate) fn find_model_pricing_row(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_delete_request_logs_between_keeps_surrounding_rows() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, ts) in [
+                ("before", 1000),
+                ("mid1", 2000),
+                ("mid2", 2500),
+                ("after", 3000),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, created_at
+                    ) VALUES (?, 'p1', 'claude', 'claude-3', 100, 50, '0.01', 100, 200, ?)",
+                    params![id, ts],
+                )?;
+            }
+        }
+
+        assert!(db.delete_request_logs_between(3000, 2000).is_err());
+        assert_eq!(db.delete_request_logs_between(2000, 2999)?, 2);
+
+        let summary = db.get_usage_summary(None, None)?;
+        assert_eq!(summary.total_requests, 2);
+        assert_eq!(summary.total_input_tokens, 200);
+
+        let conn = lock_conn!(db.conn);
+        let mut stmt =
+            conn.prepare("SELECT request_id FROM proxy_request_logs ORDER BY created_at")?;
+        let remaining: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        assert_eq!(remaining, vec!["before", "after"]);
+        Ok(())
+    }
+
     #[test]
     fn test_get_usage_summary() -> Result<(), AppError> {
         let db = Database::memory()?;
@@ -1047,6 +1471,370 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_session_logs_in_order() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (request_id, session_id, created_at) in [
+                ("req-b", "sess-1", 2000),
+                ("req-other", "sess-2", 1500),
+                ("req-a", "sess-1", 1000),
+                ("req-c", "sess-1", 3000),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, session_id, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        request_id, "p1", "claude", "claude-3", 100, 50, "0.01", 100, 200,
+                        session_id, created_at
+                    ],
+                )?;
+            }
+        }
+
+        let logs = db.get_session_logs("sess-1")?;
+        let ids: Vec<&str> = logs.iter().map(|l| l.request_id.as_str()).collect();
+        assert_eq!(ids, vec!["req-a", "req-b", "req-c"]);
+
+        let export = db.export_session_logs("sess-1")?;
+        assert_eq!(export.request_count, 3);
+        let json = serde_json::to_value(&export).expect("serialize export");
+        assert_eq!(json["sessionId"], "sess-1");
+        assert_eq!(json["logs"][0]["requestId"], "req-a");
+
+        assert!(db.get_session_logs("missing")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_session_cost_sums_session_logs() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (request_id, session_id, input, output, cost, is_shadow) in [
+                ("req-a", "sess-1", 100, 50, "0.01", 0),
+                ("req-b", "sess-1", 200, 80, "0.02", 0),
+                ("req-c", "sess-1", 300, 120, "0.03", 0),
+                ("req-shadow", "sess-1", 999, 999, "9.99", 1),
+                ("req-other", "sess-2", 400, 400, "0.50", 0),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, cache_read_tokens, total_cost_usd,
+                        latency_ms, status_code, session_id, is_shadow, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        request_id, "p1", "claude", "claude-3", input, output, 10, cost, 100, 200,
+                        session_id, is_shadow, 1000
+                    ],
+                )?;
+            }
+        }
+
+        let cost = db.get_session_cost("sess-1")?;
+        assert_eq!(cost.session_id, "sess-1");
+        assert_eq!(cost.message_count, 3);
+        assert_eq!(cost.total_cost, "0.060000");
+        assert_eq!(cost.total_input_tokens, 600);
+        assert_eq!(cost.total_output_tokens, 250);
+        assert_eq!(cost.total_cache_read_tokens, 30);
+        assert_eq!(cost.total_cache_creation_tokens, 0);
+
+        let empty = db.get_session_cost("missing")?;
+        assert_eq!(empty.message_count, 0);
+        assert_eq!(empty.total_cost, "0.000000");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_providers_by_spend_orders_by_cost() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        for (id, name) in [("cheap", "Cheap Relay"), ("pricey", "Pricey Relay")] {
+            db.save_provider(
+                "claude",
+                &crate::provider::Provider::with_id(
+                    id.to_string(),
+                    name.to_string(),
+                    serde_json::json!({}),
+                    None,
+                ),
+            )?;
+        }
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (request_id, provider_id, cost, is_shadow, created_at) in [
+                ("req-1", "cheap", "0.01", 0, 1000),
+                ("req-2", "cheap", "0.01", 0, 1100),
+                ("req-3", "cheap", "0.01", 0, 1200),
+                ("req-4", "pricey", "0.50", 0, 1300),
+                ("req-shadow", "cheap", "9.99", 1, 1400),
+                ("req-late", "cheap", "5.00", 0, 9000),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, is_shadow, created_at
+                    ) VALUES (?, ?, 'claude', 'claude-3', 100, 50, ?, 100, 200, ?, ?)",
+                    params![request_id, provider_id, cost, is_shadow, created_at],
+                )?;
+            }
+        }
+
+        let spend = db.get_providers_by_spend(Some("claude".to_string()), Some(0), Some(5000))?;
+        assert_eq!(spend.len(), 2);
+        assert_eq!(spend[0].provider_id, "pricey");
+        assert_eq!(spend[0].provider_name, "Pricey Relay");
+        assert_eq!(spend[0].request_count, 1);
+        assert_eq!(spend[0].total_cost, "0.500000");
+        assert_eq!(spend[1].provider_id, "cheap");
+        assert_eq!(spend[1].request_count, 3);
+        assert_eq!(spend[1].total_tokens, 450);
+        assert_eq!(spend[1].total_cost, "0.030000");
+
+        // 不限时间范围时，较晚的高额请求让 cheap 排到前面
+        let all = db.get_providers_by_spend(None, None, None)?;
+        assert_eq!(all[0].provider_id, "cheap");
+
+        assert!(db
+            .get_providers_by_spend(Some("codex".to_string()), None, None)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_providers_by_spend_includes_rollups() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (
+                    request_id, provider_id, app_type, model,
+                    input_tokens, output_tokens, total_cost_usd,
+                    latency_ms, status_code, created_at
+                ) VALUES ('req-1', 'detail', 'claude', 'claude-3', 100, 50, '0.10', 100, 200, 1000)",
+                [],
+            )?;
+            // 明细已被清理、只剩日聚合数据的一天
+            for (provider_id, app_type, cost) in [
+                ("rollup", "claude", "2.00"),
+                ("detail", "claude", "0.05"),
+                ("rollup", "codex", "7.00"),
+            ] {
+                conn.execute(
+                    "INSERT INTO usage_daily_rollups (
+                        date, app_type, provider_id, model, request_count, success_count,
+                        input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
+                        total_cost_usd, avg_latency_ms
+                    ) VALUES ('2026-03-01', ?, ?, 'claude-3', 4, 4, 400, 200, 0, 0, ?, 120)",
+                    params![app_type, provider_id, cost],
+                )?;
+            }
+        }
+
+        let spend = db.get_providers_by_spend(Some("claude".to_string()), None, None)?;
+        assert_eq!(spend.len(), 2);
+        assert_eq!(spend[0].provider_id, "rollup");
+        assert_eq!(spend[0].request_count, 4);
+        assert_eq!(spend[0].total_tokens, 600);
+        assert_eq!(spend[0].total_cost, "2.000000");
+        assert_eq!(spend[1].provider_id, "detail");
+        assert_eq!(spend[1].request_count, 5);
+        assert_eq!(spend[1].total_cost, "0.150000");
+
+        // 时间范围同样作用于聚合数据的日期
+        let early = db.get_providers_by_spend(Some("claude".to_string()), Some(0), Some(5000))?;
+        assert_eq!(early.len(), 1);
+        assert_eq!(early[0].provider_id, "detail");
+        assert_eq!(early[0].request_count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shadow_requests_are_excluded_from_stats() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            for (request_id, cost, is_shadow) in [("req-1", "0.01", 0), ("req-shadow", "9.99", 1)] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, is_shadow, created_at
+                    ) VALUES (?, 'p1', 'claude', 'claude-3', 100, 50, ?, 100, 200, ?, ?)",
+                    params![request_id, cost, is_shadow, Local::now().timestamp()],
+                )?;
+            }
+        }
+
+        let summary = db.get_usage_summary(None, None)?;
+        assert_eq!(summary.total_requests, 1);
+        assert_eq!(summary.total_cost, "0.010000");
+
+        let trends = db.get_daily_trends(None, None)?;
+        assert_eq!(trends.iter().map(|s| s.request_count).sum::<u64>(), 1);
+
+        let providers = db.get_provider_stats()?;
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].request_count, 1);
+
+        let models = db.get_model_stats()?;
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].request_count, 1);
+
+        let limits = db.check_provider_limits("p1", "claude")?;
+        assert_eq!(limits.daily_usage, "0.010000");
+        Ok(())
+    }
+
+    #[test]
+    fn test_backfill_all_zero_cost_logs_after_pricing_update() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (request_id, input_tokens, output_tokens) in
+                [("req-priced", 1_000_000, 0), ("req-empty", 0, 0)]
+            {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        request_id,
+                        "p1",
+                        "claude",
+                        "backfill-test-model",
+                        input_tokens,
+                        output_tokens,
+                        "0",
+                        100,
+                        200,
+                        1000
+                    ],
+                )?;
+            }
+        }
+
+        // 尚无定价：无法补算
+        assert_eq!(db.backfill_all_zero_cost_logs()?, 0);
+
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO model_pricing (
+                    model_id, display_name, input_cost_per_million, output_cost_per_million,
+                    cache_read_cost_per_million, cache_creation_cost_per_million
+                ) VALUES (?, ?, ?, ?, ?, ?)",
+                params![
+                    "backfill-test-model",
+                    "Backfill Test",
+                    "3",
+                    "15",
+                    "0.3",
+                    "3.75"
+                ],
+            )?;
+        }
+
+        assert_eq!(db.backfill_all_zero_cost_logs()?, 1);
+        let detail = db
+            .get_request_detail("req-priced")?
+            .expect("log should exist");
+        assert_eq!(detail.total_cost_usd, "3.000000");
+
+        // 已补算的日志不会重复处理
+        assert_eq!(db.backfill_all_zero_cost_logs()?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_estimate_cost_matches_recorded_cost() -> Result<(), AppError> {
+        use crate::provider::{Provider, ProviderMeta};
+        use rust_decimal::Decimal;
+
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO model_pricing (
+                    model_id, display_name, input_cost_per_million, output_cost_per_million,
+                    cache_read_cost_per_million, cache_creation_cost_per_million
+                ) VALUES (?, ?, ?, ?, ?, ?)",
+                params!["estimate-model", "Estimate", "3", "15", "0.3", "3.75"],
+            )?;
+        }
+        let mut provider = Provider::with_id(
+            "p-estimate".to_string(),
+            "Estimate".to_string(),
+            serde_json::json!({}),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            cost_multiplier: Some("1.5".to_string()),
+            ..Default::default()
+        });
+        db.save_provider("claude", &provider)?;
+
+        let estimate = db
+            .estimate_cost("estimate-model", 20_000, 4_000, "p-estimate", "claude")
+            .await?;
+        assert_eq!(estimate.total_cost, Decimal::from_str("0.18").unwrap());
+
+        let logger = UsageLogger::new(&db);
+        let (multiplier, _) = logger.resolve_pricing_config("p-estimate", "claude").await;
+        logger.log_with_calculation(
+            "req-estimate".to_string(),
+            "p-estimate".to_string(),
+            "claude".to_string(),
+            "estimate-model".to_string(),
+            "estimate-model".to_string(),
+            "estimate-model".to_string(),
+            TokenUsage {
+                input_tokens: 20_000,
+                output_tokens: 4_000,
+                ..Default::default()
+            },
+            multiplier,
+            100,
+            None,
+            200,
+            None,
+            None,
+            false,
+        )?;
+
+        let detail = db
+            .get_request_detail("req-estimate")?
+            .expect("log should exist");
+        let recorded = |value: &str| Decimal::from_str(value).unwrap();
+        assert_eq!(recorded(&detail.total_cost_usd), estimate.total_cost);
+        assert_eq!(recorded(&detail.input_cost_usd), estimate.input_cost);
+        assert_eq!(recorded(&detail.output_cost_usd), estimate.output_cost);
+
+        let err = db
+            .estimate_cost("unknown-model", 1, 1, "p-estimate", "claude")
+            .await
+            .expect_err("unknown model has no pricing");
+        assert!(err.to_string().contains("unknown-model"));
+        Ok(())
+    }
+
     #[test]
     fn test_get_model_stats() -> Result<(), AppError> {
         let db = Database::memory()?;
@@ -1083,6 +1871,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_model_stats_merge_rollups_by_normalized_model() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (
+                    request_id, provider_id, app_type, model, model_normalized,
+                    input_tokens, output_tokens, total_cost_usd,
+                    latency_ms, status_code, created_at
+                ) VALUES ('req1', 'p1', 'claude', 'claude-sonnet-4-5', 'claude-sonnet-4-5',
+                          100, 50, '0.01', 100, 200, 1000)",
+                [],
+            )?;
+            // 聚合表中保存的是原始（带前缀）的模型名
+            conn.execute(
+                "INSERT INTO usage_daily_rollups (
+                    date, app_type, provider_id, model, request_count, success_count,
+                    input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
+                    total_cost_usd, avg_latency_ms
+                ) VALUES ('2026-03-01', 'claude', 'p1', 'anthropic/claude-sonnet-4-5:beta',
+                          3, 3, 300, 150, 0, 0, '0.03', 120)",
+                [],
+            )?;
+        }
+
+        let stats = db.get_model_stats()?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].model, "claude-sonnet-4-5");
+        assert_eq!(stats[0].request_count, 4);
+        assert_eq!(stats[0].total_tokens, 600);
+        assert_eq!(stats[0].total_cost, "0.040000");
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_model_name() {
+        for alias in [
+            "gpt-5.2-codex-low",
+            "moonshotai/gpt-5.2-codex@low",
+            "gpt-5.2-codex@low:v2",
+            "openrouter/openai/gpt-5.2-codex-low:free",
+        ] {
+            assert_eq!(normalize_model_name(alias), "gpt-5.2-codex-low");
+        }
+    }
+
     #[test]
     fn test_model_pricing_matching() -> Result<(), AppError> {
         let db = Database::memory()?;
@@ -1,4 +1,10 @@
-use std::process::Command;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 自定义启动命令失败时保留的 stderr 上限
+const CUSTOM_STDERR_LIMIT: usize = 4096;
 
 pub fn launch_terminal(
     target: &str,
@@ -229,17 +235,83 @@ fn launch_custom(
         .replace("{command}", cmd_str)
         .replace("{cwd}", dir_str);
 
-    // Execute via sh -c
-    let status = Command::new("sh")
+    run_custom_launcher(
+        &final_cmd_line,
+        crate::settings::effective_custom_terminal_timeout(),
+    )
+}
+
+/// 通过 `sh -c` 执行启动命令
+///
+/// 启动命令正常退出即视为成功（终端由其自行拉起并脱离）；超时则终止子进程并返回错误，
+/// 失败时附带截断后的 stderr。
+fn run_custom_launcher(cmd_line: &str, timeout: Duration) -> Result<(), String> {
+    let mut child = Command::new("sh")
         .arg("-c")
-        .arg(&final_cmd_line)
-        .status()
+        .arg(cmd_line)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(|e| format!("Failed to execute custom terminal launcher: {e}"))?;
 
+    // 后台持续读取 stderr，避免管道写满阻塞子进程；只保留前 CUSTOM_STDERR_LIMIT 字节
+    let stderr_buf = Arc::new(Mutex::new(Vec::new()));
+    let (done_tx, done_rx) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        let buf = Arc::clone(&stderr_buf);
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 1024];
+            while let Ok(n) = stderr.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                let mut buf = buf.lock().unwrap_or_else(|e| e.into_inner());
+                let room = CUSTOM_STDERR_LIMIT.saturating_sub(buf.len());
+                buf.extend_from_slice(&chunk[..n.min(room)]);
+            }
+            let _ = done_tx.send(());
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Custom terminal launcher timed out after {}s and was killed",
+                    timeout.as_secs_f32()
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("Failed to wait for custom terminal launcher: {e}")),
+        }
+    };
+
     if status.success() {
-        Ok(())
+        return Ok(());
+    }
+
+    // 启动命令拉起的后台进程可能继承了 stderr，不无限等待读取结束
+    let _ = done_rx.recv_timeout(Duration::from_millis(500));
+    let stderr = {
+        let buf = stderr_buf.lock().unwrap_or_else(|e| e.into_inner());
+        String::from_utf8_lossy(&buf).trim().to_string()
+    };
+    let code = status
+        .code()
+        .map(|c| c.to_string())
+        .unwrap_or_else(|| "signal".to_string());
+    if stderr.is_empty() {
+        Err(format!(
+            "Custom terminal execution returned error code {code}"
+        ))
     } else {
-        Err("Custom terminal execution returned error code".to_string())
+        Err(format!(
+            "Custom terminal execution returned error code {code}: {stderr}"
+        ))
     }
 }
 
@@ -305,4 +377,40 @@ mod tests {
             "raw:echo foo\\\\\\\\bar\\npwd\\n"
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn custom_launcher_is_killed_after_timeout() {
+        let started = Instant::now();
+        let err = run_custom_launcher("sleep 30", Duration::from_millis(300)).unwrap_err();
+
+        assert!(err.contains("timed out"), "unexpected error: {err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn custom_launcher_failure_includes_stderr() {
+        let err = run_custom_launcher(
+            "echo 'no such terminal' >&2; exit 3",
+            Duration::from_secs(10),
+        )
+        .unwrap_err();
+
+        assert!(err.contains("error code 3"), "unexpected error: {err}");
+        assert!(err.contains("no such terminal"), "unexpected error: {err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn custom_launcher_succeeds_and_caps_stderr() {
+        assert!(run_custom_launcher("true", Duration::from_secs(10)).is_ok());
+
+        let err = run_custom_launcher(
+            "head -c 20000 /dev/zero | tr '\\0' x >&2; exit 1",
+            Duration::from_secs(10),
+        )
+        .unwrap_err();
+        assert!(err.len() < CUSTOM_STDERR_LIMIT + 100);
+    }
 }
@@ -190,6 +190,9 @@ pub struct AppSettings {
     /// 是否在主页面启用本地代理功能（默认关闭）
     #[serde(default)]
     pub enable_local_proxy: bool,
+    /// 启动时自动启动代理服务（上次异常退出时跳过）
+    #[serde(default)]
+    pub auto_start_proxy: bool,
     /// User has confirmed the local proxy first-run notice
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub proxy_confirmed: Option<bool>,
@@ -246,6 +249,11 @@ pub struct AppSettings {
     #[serde(default)]
     pub skill_sync_method: SyncMethod,
 
+    // ===== 供应商切换设置 =====
+    /// 切换供应商后在后台预热到新 base_url 的连接（仅在该应用处于代理接管时生效）
+    #[serde(default)]
+    pub warm_up_on_switch: bool,
+
     // ===== WebDAV 同步设置 =====
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub webdav_sync: Option<WebDavSyncSettings>,
@@ -262,6 +270,13 @@ pub struct AppSettings {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub backup_retain_count: Option<u32>,
 
+    // ===== 使用日志存储 =====
+    /// 独立的使用日志数据库文件（可选，重启后生效）
+    ///
+    /// 设置后请求日志、模型定价与日聚合统计存放在该文件中，不再占用主数据库
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_db_path: Option<String>,
+
     // ===== 终端设置 =====
     /// 首选终端应用（可选，默认使用系统默认终端）
     /// - macOS: "terminal" | "iterm2" | "warp" | "alacritty" | "kitty" | "ghostty"
@@ -269,6 +284,9 @@ pub struct AppSettings {
     /// - Linux: "gnome-terminal" | "konsole" | "xfce4-terminal" | "alacritty" | "kitty" | "ghostty"
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub preferred_terminal: Option<String>,
+    /// 自定义终端启动命令的超时秒数（默认 10），超时后终止启动进程
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_terminal_timeout_secs: Option<u32>,
 }
 
 fn default_show_in_tray() -> bool {
@@ -289,6 +307,7 @@ impl Default for AppSettings {
             launch_on_startup: false,
             silent_startup: false,
             enable_local_proxy: false,
+            auto_start_proxy: false,
             proxy_confirmed: None,
             usage_confirmed: None,
             stream_check_confirmed: None,
@@ -307,11 +326,14 @@ impl Default for AppSettings {
             current_provider_opencode: None,
             current_provider_openclaw: None,
             skill_sync_method: SyncMethod::default(),
+            warm_up_on_switch: false,
             webdav_sync: None,
             webdav_backup: None,
             backup_interval_hours: None,
             backup_retain_count: None,
+            usage_db_path: None,
             preferred_terminal: None,
+            custom_terminal_timeout_secs: None,
         }
     }
 }
@@ -326,6 +348,15 @@ impl AppSettings {
         )
     }
 
+    /// 解析后的独立使用日志库路径（未设置或为空时返回 None）
+    pub fn resolved_usage_db_path(&self) -> Option<PathBuf> {
+        self.usage_db_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(resolve_override_path)
+    }
+
     fn normalize_paths(&mut self) {
         self.claude_config_dir = self
             .claude_config_dir
@@ -522,6 +553,12 @@ pub fn reload_settings() -> Result<(), AppError> {
     Ok(())
 }
 
+/// 独立使用日志库路径（未设置或为空时返回 None）
+pub fn get_usage_db_path() -> Option<PathBuf> {
+    let settings = settings_store().read().ok()?;
+    settings.resolved_usage_db_path()
+}
+
 pub fn get_claude_override_dir() -> Option<PathBuf> {
     let settings = settings_store().read().ok()?;
     settings
@@ -632,6 +669,47 @@ pub fn get_effective_current_provider(
     db.get_current_provider(app_type.as_str())
 }
 
+/// 获取有效的当前供应商 ID，可选择在当前供应商不健康时读取健康的替代供应商
+///
+/// `prefer_healthy` 为 false 时与 [`get_effective_current_provider`] 完全相同。
+/// 为 true 且当前供应商已被标记为不健康时，按故障转移队列顺序返回第一个健康的已启用供应商；
+/// 队列中没有健康的替代者时仍返回原当前供应商。
+/// 仅用于读取：不会修改本地 settings 或数据库中存储的当前供应商。
+pub fn get_effective_current_provider_with(
+    db: &crate::database::Database,
+    app_type: &AppType,
+    prefer_healthy: bool,
+) -> Result<Option<String>, AppError> {
+    let current = get_effective_current_provider(db, app_type)?;
+    if !prefer_healthy {
+        return Ok(current);
+    }
+    let Some(current_id) = current else {
+        return Ok(None);
+    };
+
+    let is_healthy = |id: &str| {
+        futures::executor::block_on(db.get_provider_health(id, app_type.as_str()))
+            .map(|health| health.is_healthy)
+    };
+    if is_healthy(&current_id)? {
+        return Ok(Some(current_id));
+    }
+
+    for candidate in db.get_failover_providers(app_type.as_str())? {
+        if candidate.id != current_id && is_healthy(&candidate.id)? {
+            log::info!(
+                "当前供应商 {current_id} ({}) 不健康，读取时改用故障转移队列中的 {}",
+                app_type.as_str(),
+                candidate.id
+            );
+            return Ok(Some(candidate.id));
+        }
+    }
+
+    Ok(Some(current_id))
+}
+
 // ===== Skill 同步方式管理函数 =====
 
 /// 获取 Skill 同步方式配置
@@ -645,6 +723,17 @@ pub fn get_skill_sync_method() -> SyncMethod {
         .skill_sync_method
 }
 
+/// 切换供应商后是否预热连接
+pub fn get_warm_up_on_switch() -> bool {
+    settings_store()
+        .read()
+        .unwrap_or_else(|e| {
+            log::warn!("设置锁已毒化，使用恢复值: {e}");
+            e.into_inner()
+        })
+        .warm_up_on_switch
+}
+
 // ===== 备份策略管理函数 =====
 
 /// Get the effective auto-backup interval in hours (default 24)
@@ -686,6 +775,20 @@ pub fn get_preferred_terminal() -> Option<String> {
         .clone()
 }
 
+/// 获取自定义终端启动命令的超时时间（默认 10 秒，最少 1 秒）
+pub fn effective_custom_terminal_timeout() -> std::time::Duration {
+    let secs = settings_store()
+        .read()
+        .unwrap_or_else(|e| {
+            log::warn!("设置锁已毒化，使用恢复值: {e}");
+            e.into_inner()
+        })
+        .custom_terminal_timeout_secs
+        .map(|n| n.max(1))
+        .unwrap_or(10);
+    std::time::Duration::from_secs(u64::from(secs))
+}
+
 // ===== WebDAV 同步设置管理函数 =====
 
 /// 获取 WebDAV 同步设置
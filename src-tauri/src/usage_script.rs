@@ -20,8 +20,21 @@ pub async fn execute_usage_script(
     let is_custom_template = template_type.map(|t| t == "custom").unwrap_or(false);
 
     // 1. 替换模板变量，避免泄露敏感信息
-    let script_with_vars =
-        build_script_with_vars(script_code, api_key, base_url, access_token, user_id);
+    let vars = TemplateVars {
+        api_key,
+        base_url,
+        access_token,
+        user_id,
+        template_type,
+        now_iso: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    };
+    let (script_with_vars, unknown_vars) = build_script_with_vars(script_code, &vars);
+    if !unknown_vars.is_empty() {
+        log::warn!(
+            "[UsageScript] 脚本中存在未知模板变量，已保持原样: {}",
+            unknown_vars.join(", ")
+        );
+    }
 
     // 2. 验证 base_url 的安全性（仅当提供了 base_url 时）
     // 自定义模板模式下，用户可能不使用模板变量，而是直接在脚本中写完整 URL
@@ -398,26 +411,90 @@ fn validate_single_usage(result: &Value) -> Result<(), AppError> {
     Ok(())
 }
 
-/// 构建替换变量后的脚本，保持与旧版脚本的兼容性
-fn build_script_with_vars(
-    script_code: &str,
-    api_key: &str,
-    base_url: &str,
-    access_token: Option<&str>,
-    user_id: Option<&str>,
-) -> String {
-    let mut replaced = script_code
-        .replace("{{apiKey}}", api_key)
-        .replace("{{baseUrl}}", base_url);
+/// 脚本中可用的模板变量
+///
+/// | 变量 | 含义 |
+/// |------|------|
+/// | `{{api_key}}` / `{{apiKey}}` | 配置的 API Key |
+/// | `{{base_url}}` / `{{baseUrl}}` | 配置的 Base URL |
+/// | `{{access_token}}` / `{{accessToken}}` | 访问令牌（New API） |
+/// | `{{user_id}}` / `{{userId}}` | 用户 ID（New API） |
+/// | `{{template_type}}` / `{{templateType}}` | 模板类型 |
+/// | `{{now_iso}}` / `{{nowIso}}` | 当前 UTC 时间（RFC 3339） |
+///
+/// 变量值按 JS 字符串内容转义后再替换，只能出现在字符串字面量内部，
+/// 无法闭合引号注入代码。未设置的可选变量保持原样。
+struct TemplateVars<'a> {
+    api_key: &'a str,
+    base_url: &'a str,
+    access_token: Option<&'a str>,
+    user_id: Option<&'a str>,
+    template_type: Option<&'a str>,
+    now_iso: String,
+}
 
-    if let Some(token) = access_token {
-        replaced = replaced.replace("{{accessToken}}", token);
+impl TemplateVars<'_> {
+    /// 按变量名取值：外层 None 表示未知变量，内层 None 表示已知但未设置
+    fn lookup(&self, name: &str) -> Option<Option<&str>> {
+        let value = match name {
+            "api_key" | "apiKey" => Some(self.api_key),
+            "base_url" | "baseUrl" => Some(self.base_url),
+            "access_token" | "accessToken" => self.access_token,
+            "user_id" | "userId" => self.user_id,
+            "template_type" | "templateType" => self.template_type,
+            "now_iso" | "nowIso" => Some(self.now_iso.as_str()),
+            _ => return None,
+        };
+        Some(value)
     }
-    if let Some(uid) = user_id {
-        replaced = replaced.replace("{{userId}}", uid);
+}
+
+/// 转义为可安全嵌入 JS 字符串字面量（单引号、双引号、模板字符串）的内容
+fn escape_js_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\'' => escaped.push_str("\\'"),
+            '`' => escaped.push_str("\\`"),
+            '$' => escaped.push_str("\\$"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
     }
+    escaped
+}
 
-    replaced
+/// 构建替换变量后的脚本，返回脚本与未知变量名列表
+///
+/// 单次扫描替换，变量值中的 `{{...}}` 不会被再次展开。
+fn build_script_with_vars(script_code: &str, vars: &TemplateVars) -> (String, Vec<String>) {
+    static PLACEHOLDER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let placeholder = PLACEHOLDER.get_or_init(|| {
+        regex::Regex::new(r"\{\{([A-Za-z_][A-Za-z0-9_]*)\}\}").expect("valid placeholder regex")
+    });
+
+    let mut unknown = Vec::new();
+    let replaced = placeholder.replace_all(script_code, |caps: &regex::Captures| {
+        let name = &caps[1];
+        match vars.lookup(name) {
+            Some(Some(value)) => escape_js_string(value),
+            Some(None) => caps[0].to_string(),
+            None => {
+                if !unknown.iter().any(|n| n == name) {
+                    unknown.push(name.to_string());
+                }
+                caps[0].to_string()
+            }
+        }
+    });
+
+    (replaced.into_owned(), unknown)
 }
 
 /// 验证 base_url 的基本安全性
@@ -720,6 +797,82 @@ fn is_loopback_host(url: &Url) -> bool {
 mod tests {
     use super::*;
 
+    fn sample_vars() -> TemplateVars<'static> {
+        TemplateVars {
+            api_key: "sk-test",
+            base_url: "https://api.example.com",
+            access_token: Some("at-123"),
+            user_id: Some("42"),
+            template_type: Some("newapi"),
+            now_iso: "2026-01-02T03:04:05Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_template_vars_substituted() {
+        let vars = sample_vars();
+        let cases = [
+            ("{{api_key}}", "sk-test"),
+            ("{{apiKey}}", "sk-test"),
+            ("{{base_url}}", "https://api.example.com"),
+            ("{{baseUrl}}", "https://api.example.com"),
+            ("{{access_token}}", "at-123"),
+            ("{{accessToken}}", "at-123"),
+            ("{{user_id}}", "42"),
+            ("{{userId}}", "42"),
+            ("{{template_type}}", "newapi"),
+            ("{{now_iso}}", "2026-01-02T03:04:05Z"),
+        ];
+        for (script, expected) in cases {
+            let (replaced, unknown) = build_script_with_vars(script, &vars);
+            assert_eq!(replaced, expected, "变量替换错误: {script}");
+            assert!(unknown.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_unset_optional_var_left_literal() {
+        let vars = TemplateVars {
+            access_token: None,
+            ..sample_vars()
+        };
+        let (replaced, unknown) = build_script_with_vars("\"{{access_token}}\"", &vars);
+        assert_eq!(replaced, "\"{{access_token}}\"");
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_var_left_literal_with_warning() {
+        let (replaced, unknown) =
+            build_script_with_vars("\"{{api_key}}:{{secret}}:{{secret}}\"", &sample_vars());
+        assert_eq!(replaced, "\"sk-test:{{secret}}:{{secret}}\"");
+        assert_eq!(unknown, vec!["secret".to_string()]);
+    }
+
+    #[test]
+    fn test_template_substitution_is_injection_safe() {
+        let vars = TemplateVars {
+            api_key: "x\"; throw new Error('pwned'); \"",
+            user_id: Some("${globalThis}`\\{{api_key}}"),
+            ..sample_vars()
+        };
+        let script = r#"({ key: "{{api_key}}", uid: `{{user_id}}`, q: '{{api_key}}' })"#;
+        let (replaced, _) = build_script_with_vars(script, &vars);
+
+        let runtime = Runtime::new().unwrap();
+        let context = Context::full(&runtime).unwrap();
+        context.with(|ctx| {
+            let obj: rquickjs::Object = ctx.eval(replaced).unwrap();
+            let key: String = obj.get("key").unwrap();
+            let uid: String = obj.get("uid").unwrap();
+            let quoted: String = obj.get("q").unwrap();
+            assert_eq!(key, vars.api_key);
+            assert_eq!(quoted, vars.api_key);
+            // 变量值中的占位符不会被二次展开
+            assert_eq!(uid, "${globalThis}`\\{{api_key}}");
+        });
+    }
+
     #[test]
     fn test_private_ip_validation() {
         // 测试IPv4私网地址
@@ -3,8 +3,8 @@ use std::fs;
 use std::path::PathBuf;
 
 use cc_switch_lib::{
-    get_claude_settings_path, read_json_file, AppError, AppType, ConfigService, MultiAppConfig,
-    Provider, ProviderMeta,
+    get_claude_settings_path, read_json_file, update_settings, AppError, AppSettings, AppType,
+    ConfigService, IntegrityIssueKind, MultiAppConfig, Provider, ProviderMeta,
 };
 
 #[path = "support.rs"]
@@ -1074,3 +1074,405 @@ fn import_sql_accepts_cc_switch_exported_backup() {
         "imported providers should contain test-provider"
     );
 }
+
+#[test]
+fn live_status_reports_files_and_takeover_placeholders() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    // Claude: 代理占位符 → 视为已接管
+    let claude_path = get_claude_settings_path();
+    fs::create_dir_all(claude_path.parent().unwrap()).expect("create claude dir");
+    fs::write(
+        &claude_path,
+        serde_json::to_string(&json!({
+            "env": { "ANTHROPIC_AUTH_TOKEN": "PROXY_MANAGED" }
+        }))
+        .unwrap(),
+    )
+    .expect("write claude settings");
+
+    // Codex: 真实密钥 → 未接管
+    let codex_dir = home.join(".codex");
+    fs::create_dir_all(&codex_dir).expect("create codex dir");
+    fs::write(
+        codex_dir.join("auth.json"),
+        serde_json::to_string(&json!({ "OPENAI_API_KEY": "sk-real" })).unwrap(),
+    )
+    .expect("write codex auth");
+
+    // Gemini: 代理占位符 → 视为已接管
+    let gemini_dir = home.join(".gemini");
+    fs::create_dir_all(&gemini_dir).expect("create gemini dir");
+    fs::write(gemini_dir.join(".env"), "GEMINI_API_KEY=PROXY_MANAGED\n").expect("write gemini env");
+
+    let statuses = ConfigService::live_status().expect("live status");
+    let find = |app: &str| {
+        statuses
+            .iter()
+            .find(|status| status.app == app)
+            .unwrap_or_else(|| panic!("missing status for {app}"))
+    };
+
+    let claude = find("claude");
+    assert!(claude.taken_over, "placeholder token should mark takeover");
+    assert!(claude.files[0].exists);
+    assert!(claude.files[0].modified_at.is_some());
+
+    let codex = find("codex");
+    assert!(!codex.taken_over, "real key should not mark takeover");
+    assert!(codex.files[0].exists, "auth.json should exist");
+    assert!(!codex.files[1].exists, "config.toml was not written");
+    assert!(codex.files[1].modified_at.is_none());
+
+    assert!(find("gemini").taken_over);
+    assert!(!find("opencode").taken_over);
+    assert!(!find("openclaw").taken_over);
+}
+
+fn integrity_test_provider(id: &str) -> Provider {
+    Provider::with_id(
+        id.to_string(),
+        id.to_string(),
+        json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": format!("sk-{id}"),
+                "ANTHROPIC_BASE_URL": "https://api.test"
+            }
+        }),
+        None,
+    )
+}
+
+fn set_local_current_claude(id: Option<&str>) {
+    update_settings(AppSettings {
+        current_provider_claude: id.map(str::to_string),
+        ..AppSettings::default()
+    })
+    .expect("update settings");
+}
+
+// 测试使用 Mutex 进行串行化，跨 await 持锁是预期行为
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn integrity_check_keeps_cross_device_default_provider() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    ensure_test_home();
+    let state = create_test_state().expect("create state");
+
+    for id in ["a", "b"] {
+        state
+            .db
+            .save_provider("claude", &integrity_test_provider(id))
+            .expect("save provider");
+    }
+    state
+        .db
+        .set_current_provider("claude", "a")
+        .expect("set db current");
+    set_local_current_claude(Some("b"));
+
+    // 数据库 is_current 为跨设备默认值，与本设备的当前供应商不同属于正常状态
+    assert!(ConfigService::integrity_check(&state, false)
+        .await
+        .expect("integrity check")
+        .is_empty());
+    assert!(ConfigService::integrity_check(&state, true)
+        .await
+        .expect("integrity repair")
+        .is_empty());
+    assert_eq!(
+        state.db.get_current_provider("claude").unwrap().as_deref(),
+        Some("a"),
+        "auto repair must not overwrite the cross-device default"
+    );
+}
+
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn integrity_check_reports_and_repairs_missing_current() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    ensure_test_home();
+    let state = create_test_state().expect("create state");
+
+    set_local_current_claude(Some("ghost"));
+
+    let issues = ConfigService::integrity_check(&state, true)
+        .await
+        .expect("integrity check");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, IntegrityIssueKind::MissingCurrent);
+    assert!(issues[0].repaired);
+    assert!(ConfigService::integrity_check(&state, false)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn integrity_check_reports_and_repairs_live_takeover_without_proxy() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = create_test_state().expect("create state");
+
+    state
+        .db
+        .save_provider("claude", &integrity_test_provider("a"))
+        .expect("save provider");
+    state
+        .db
+        .set_current_provider("claude", "a")
+        .expect("set db current");
+
+    let claude_dir = home.join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create claude dir");
+    fs::write(
+        claude_dir.join("settings.json"),
+        serde_json::to_string(&json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "PROXY_MANAGED",
+                "ANTHROPIC_BASE_URL": "http://127.0.0.1:15721"
+            }
+        }))
+        .unwrap(),
+    )
+    .expect("write claude settings");
+
+    let issues = ConfigService::integrity_check(&state, true)
+        .await
+        .expect("integrity check");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].kind,
+        IntegrityIssueKind::LiveTakenOverWithoutProxy
+    );
+    assert!(issues[0].repaired);
+
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read live settings");
+    assert_ne!(live["env"]["ANTHROPIC_AUTH_TOKEN"], "PROXY_MANAGED");
+    assert!(ConfigService::integrity_check(&state, false)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn integrity_check_reports_and_repairs_orphaned_endpoints() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = create_test_state().expect("create state");
+
+    // 模拟关闭外键约束的导入留下的孤儿端点
+    {
+        let conn = rusqlite::Connection::open(home.join(".cc-switch").join("cc-switch.db"))
+            .expect("open db");
+        conn.execute(
+            "INSERT INTO provider_endpoints (provider_id, app_type, url) VALUES ('gone', 'claude', 'https://orphan.test')",
+            [],
+        )
+        .expect("insert orphan endpoint");
+    }
+
+    let issues = ConfigService::integrity_check(&state, true)
+        .await
+        .expect("integrity check");
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, IntegrityIssueKind::OrphanedEndpoint);
+    assert!(issues[0].detail.contains("https://orphan.test"));
+    assert!(issues[0].repaired);
+    assert!(state.db.list_orphaned_endpoints().unwrap().is_empty());
+}
+
+fn write_claude_live(env: serde_json::Value) {
+    let path = get_claude_settings_path();
+    fs::create_dir_all(path.parent().unwrap()).expect("create claude dir");
+    fs::write(
+        &path,
+        serde_json::to_string(&json!({ "env": env })).unwrap(),
+    )
+    .expect("write claude settings");
+}
+
+// 测试使用 Mutex 进行串行化，跨 await 持锁是预期行为
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn verify_live_matches_current_detects_divergence_and_takeover() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    ensure_test_home();
+    let state = create_test_state().expect("create state");
+    state
+        .db
+        .save_provider("claude", &integrity_test_provider("current-provider"))
+        .expect("save provider");
+    set_local_current_claude(Some("current-provider"));
+
+    // 一致：base_url 末尾的 / 不视为差异
+    write_claude_live(json!({
+        "ANTHROPIC_BASE_URL": "https://api.test/",
+        "ANTHROPIC_AUTH_TOKEN": "sk-current-provider"
+    }));
+    let result = ConfigService::verify_live_matches_current(&state, AppType::Claude)
+        .await
+        .expect("verify matching live");
+    assert!(result.matches);
+    assert!(!result.taken_over);
+    assert_eq!(result.provider_id, "current-provider");
+    assert!(result.diffs.is_empty());
+
+    // 被外部工具改写
+    write_claude_live(json!({
+        "ANTHROPIC_BASE_URL": "https://elsewhere.test",
+        "ANTHROPIC_AUTH_TOKEN": "sk-someone-else"
+    }));
+    let result = ConfigService::verify_live_matches_current(&state, AppType::Claude)
+        .await
+        .expect("verify diverged live");
+    assert!(!result.matches);
+    let fields: Vec<&str> = result.diffs.iter().map(|d| d.field.as_str()).collect();
+    assert_eq!(fields, vec!["base_url", "token"]);
+    assert_eq!(
+        result.diffs[0].expected.as_deref(),
+        Some("https://api.test")
+    );
+    assert_eq!(
+        result.diffs[0].actual.as_deref(),
+        Some("https://elsewhere.test")
+    );
+    assert_eq!(result.diffs[1].expected.as_deref(), Some("sk-c...ider"));
+    assert_eq!(result.diffs[1].actual.as_deref(), Some("sk-s...else"));
+
+    // 代理接管：占位符 + 本地代理地址视为一致
+    let proxy = state.db.get_proxy_config().await.expect("proxy config");
+    let proxy_url = format!("http://{}:{}", proxy.listen_address, proxy.listen_port);
+    write_claude_live(json!({
+        "ANTHROPIC_BASE_URL": proxy_url,
+        "ANTHROPIC_AUTH_TOKEN": "PROXY_MANAGED"
+    }));
+    let result = ConfigService::verify_live_matches_current(&state, AppType::Claude)
+        .await
+        .expect("verify taken-over live");
+    assert!(result.taken_over);
+    assert!(result.matches, "unexpected diffs: {:?}", result.diffs);
+
+    // 接管状态下地址被改走
+    write_claude_live(json!({
+        "ANTHROPIC_BASE_URL": "https://elsewhere.test",
+        "ANTHROPIC_AUTH_TOKEN": "PROXY_MANAGED"
+    }));
+    let result = ConfigService::verify_live_matches_current(&state, AppType::Claude)
+        .await
+        .expect("verify broken takeover");
+    assert!(result.taken_over);
+    assert!(!result.matches);
+    assert_eq!(result.diffs.len(), 1);
+    assert_eq!(
+        result.diffs[0].expected.as_deref(),
+        Some(proxy_url.as_str())
+    );
+}
+
+fn seed_claude_dir(dir: &std::path::Path, skills: &[&str], marker: &str) {
+    for skill in skills {
+        let skill_dir = dir.join("skills").join(skill);
+        fs::create_dir_all(&skill_dir).expect("create skill dir");
+        fs::write(skill_dir.join("SKILL.md"), marker).expect("write skill");
+    }
+}
+
+#[test]
+fn migrate_app_dir_moves_skills_and_rewrites_live() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let old_dir = home.join(".claude");
+    let new_dir = home.join(".config").join("claude-relocated");
+
+    seed_claude_dir(&old_dir, &["skill-a"], "old");
+    fs::write(old_dir.join("settings.json"), r#"{"env":{}}"#).expect("write old live");
+    // MCP 配置与目录同级：~/.claude -> ~/.claude.json
+    let mcp = r#"{"mcpServers":{"demo":{"command":"demo"}}}"#;
+    fs::write(home.join(".claude.json"), mcp).expect("write old mcp");
+
+    let state = create_test_state().expect("create state");
+    state
+        .db
+        .save_provider("claude", &integrity_test_provider("current-provider"))
+        .expect("save provider");
+    update_settings(AppSettings {
+        claude_config_dir: Some(new_dir.to_string_lossy().to_string()),
+        current_provider_claude: Some("current-provider".to_string()),
+        ..AppSettings::default()
+    })
+    .expect("update settings");
+
+    let report =
+        ConfigService::migrate_app_dir(&state, AppType::Claude, &old_dir, &new_dir, true, false)
+            .expect("migrate dir");
+
+    assert!(report.moved);
+    assert_eq!(report.skills, vec!["skill-a"]);
+    assert_eq!(report.live_files, vec!["settings.json", ".claude.json"]);
+    assert!(report.skipped.is_empty());
+    assert!(report.live_rewritten);
+
+    assert!(new_dir.join("skills/skill-a/SKILL.md").exists());
+    assert!(!old_dir.join("skills/skill-a").exists());
+    assert!(!old_dir.join("settings.json").exists());
+    assert!(!home.join(".claude.json").exists());
+    assert_eq!(
+        fs::read_to_string(home.join(".config").join("claude-relocated.json"))
+            .expect("read migrated mcp"),
+        mcp
+    );
+
+    let live: serde_json::Value =
+        read_json_file(&new_dir.join("settings.json")).expect("read migrated live");
+    assert_eq!(
+        live["env"]["ANTHROPIC_AUTH_TOKEN"],
+        json!("sk-current-provider")
+    );
+}
+
+#[test]
+fn migrate_app_dir_into_populated_dir_errors_or_merges() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+    let old_dir = home.join(".config").join("claude-old");
+    let new_dir = home.join(".config").join("claude-new");
+
+    seed_claude_dir(&old_dir, &["skill-a", "skill-b"], "old");
+    seed_claude_dir(&new_dir, &["skill-a"], "new");
+    let state = create_test_state().expect("create state");
+
+    // 不合并：报错且不移动任何条目
+    let err =
+        ConfigService::migrate_app_dir(&state, AppType::Claude, &old_dir, &new_dir, true, false)
+            .expect_err("populated dir should conflict");
+    assert!(err.to_string().contains("skills/skill-a"), "{err}");
+    assert!(old_dir.join("skills/skill-b").exists());
+    assert!(!new_dir.join("skills/skill-b").exists());
+
+    // 合并：跳过冲突条目，保留新目录中的版本
+    let report =
+        ConfigService::migrate_app_dir(&state, AppType::Claude, &old_dir, &new_dir, true, true)
+            .expect("merge into populated dir");
+    assert_eq!(report.skills, vec!["skill-b"]);
+    assert_eq!(report.skipped, vec!["skills/skill-a"]);
+    assert!(!report.live_rewritten);
+    assert_eq!(
+        fs::read_to_string(new_dir.join("skills/skill-a/SKILL.md")).unwrap(),
+        "new"
+    );
+    assert!(new_dir.join("skills/skill-b/SKILL.md").exists());
+    assert!(old_dir.join("skills/skill-a").exists());
+}
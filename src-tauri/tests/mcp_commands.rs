@@ -4,8 +4,9 @@ use std::fs;
 use serde_json::json;
 
 use cc_switch_lib::{
-    get_claude_mcp_path, get_claude_settings_path, import_default_config_test_hook, AppError,
-    AppType, McpApps, McpServer, McpService, MultiAppConfig,
+    get_claude_desktop_config_path, get_claude_mcp_path, get_claude_settings_path,
+    import_default_config_test_hook, AppError, AppType, McpApps, McpServer, McpService,
+    MultiAppConfig,
 };
 
 #[path = "support.rs"]
@@ -420,6 +421,134 @@ command = "echo"
     assert!(entry.apps.codex, "shared should enable Codex");
 }
 
+#[test]
+fn import_mcp_from_claude_desktop_skips_duplicates() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let desktop_path = get_claude_desktop_config_path();
+    fs::create_dir_all(desktop_path.parent().expect("desktop config dir"))
+        .expect("create claude desktop dir");
+    let desktop_json = json!({
+        "mcpServers": {
+            "filesystem": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]
+            },
+            "memory": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-memory"]
+            },
+            "invalid": {
+                "args": ["no-command"]
+            }
+        }
+    });
+    fs::write(
+        &desktop_path,
+        serde_json::to_string_pretty(&desktop_json).expect("serialize desktop config"),
+    )
+    .expect("seed claude_desktop_config.json");
+
+    let state = support::create_test_state().expect("create test state");
+    // 已存在同名同命令的服务器：视为重复
+    state
+        .db
+        .save_mcp_server(&McpServer {
+            id: "memory".to_string(),
+            name: "memory".to_string(),
+            server: json!({"type": "stdio", "command": "npx"}),
+            apps: McpApps {
+                codex: true,
+                ..Default::default()
+            },
+            description: None,
+            homepage: None,
+            docs: None,
+            tags: Vec::new(),
+        })
+        .expect("seed existing server");
+
+    let imported =
+        McpService::import_from_claude_desktop(&state).expect("import from claude desktop");
+    assert_eq!(imported, vec!["filesystem".to_string()]);
+
+    let servers = state.db.get_all_mcp_servers().expect("get all mcp servers");
+    let filesystem = servers.get("filesystem").expect("filesystem imported");
+    assert!(filesystem.apps.claude);
+    assert!(!filesystem.apps.codex);
+    assert_eq!(filesystem.server["command"], "npx");
+    let memory = servers.get("memory").expect("memory kept");
+    assert!(!memory.apps.claude, "duplicate should be left untouched");
+    assert!(!servers.contains_key("invalid"));
+
+    // 再次导入时全部视为重复
+    let again =
+        McpService::import_from_claude_desktop(&state).expect("re-import from claude desktop");
+    assert!(again.is_empty());
+}
+
+#[test]
+fn import_mcp_from_claude_desktop_keeps_existing_spec_for_same_id() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let desktop_path = get_claude_desktop_config_path();
+    fs::create_dir_all(desktop_path.parent().expect("desktop config dir"))
+        .expect("create claude desktop dir");
+    let desktop_json = json!({
+        "mcpServers": {
+            "filesystem": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]
+            }
+        }
+    });
+    fs::write(
+        &desktop_path,
+        serde_json::to_string_pretty(&desktop_json).expect("serialize desktop config"),
+    )
+    .expect("seed claude_desktop_config.json");
+
+    let state = support::create_test_state().expect("create test state");
+    // 同 id 但命令不同：不是重复，但也不能覆盖已有配置
+    state
+        .db
+        .save_mcp_server(&McpServer {
+            id: "filesystem".to_string(),
+            name: "Filesystem".to_string(),
+            server: json!({"type": "stdio", "command": "uvx", "args": ["mcp-server-fs"]}),
+            apps: McpApps {
+                codex: true,
+                ..Default::default()
+            },
+            description: Some("local fs".to_string()),
+            homepage: None,
+            docs: None,
+            tags: Vec::new(),
+        })
+        .expect("seed existing server");
+
+    let imported =
+        McpService::import_from_claude_desktop(&state).expect("import from claude desktop");
+    assert_eq!(imported, vec!["filesystem".to_string()]);
+
+    let servers = state.db.get_all_mcp_servers().expect("get all mcp servers");
+    let filesystem = servers.get("filesystem").expect("filesystem kept");
+    assert!(filesystem.apps.claude, "same id should enable Claude");
+    assert!(filesystem.apps.codex, "other apps should be kept");
+    assert_eq!(filesystem.server["command"], "uvx");
+    assert_eq!(filesystem.server["args"], json!(["mcp-server-fs"]));
+    assert_eq!(filesystem.description.as_deref(), Some("local fs"));
+
+    // Claude 已启用时再次导入不做任何修改
+    let again =
+        McpService::import_from_claude_desktop(&state).expect("re-import from claude desktop");
+    assert!(again.is_empty());
+}
+
 #[test]
 fn import_mcp_from_gemini_sse_url_only_is_valid() {
     let _guard = test_mutex().lock().expect("acquire test mutex");
@@ -1,8 +1,10 @@
 use serde_json::json;
 
 use cc_switch_lib::{
-    get_claude_settings_path, read_json_file, write_codex_live_atomic, AppError, AppType, McpApps,
+    get_claude_mcp_path, get_claude_settings_path, get_effective_current_provider_with,
+    read_json_file, write_codex_live_atomic, AppError, AppType, EndpointLatency, McpApps,
     McpServer, MultiAppConfig, Provider, ProviderMeta, ProviderService,
+    ENDPOINT_LATENCY_HISTORY_LIMIT, PROVIDER_HISTORY_LIMIT,
 };
 
 #[path = "support.rs"]
@@ -578,6 +580,83 @@ fn switch_google_official_gemini_sets_oauth_security() {
     );
 }
 
+#[test]
+fn switch_skips_mcp_sync_only_for_flagged_provider() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+    // Claude 已初始化时才会同步 MCP
+    if let Some(parent) = get_claude_settings_path().parent() {
+        std::fs::create_dir_all(parent).expect("create claude settings dir");
+    }
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        let mut manual = Provider::with_id(
+            "manual".to_string(),
+            "Manual MCP".to_string(),
+            json!({ "env": { "ANTHROPIC_API_KEY": "manual-key" } }),
+            None,
+        );
+        manual.meta = Some(ProviderMeta {
+            skip_mcp_sync: Some(true),
+            ..Default::default()
+        });
+        manager.providers.insert("manual".to_string(), manual);
+        manager.providers.insert(
+            "auto".to_string(),
+            Provider::with_id(
+                "auto".to_string(),
+                "Auto MCP".to_string(),
+                json!({ "env": { "ANTHROPIC_API_KEY": "auto-key" } }),
+                None,
+            ),
+        );
+    }
+    config
+        .mcp
+        .servers
+        .get_or_insert_with(Default::default)
+        .insert(
+            "echo-server".into(),
+            McpServer {
+                id: "echo-server".into(),
+                name: "Echo Server".into(),
+                server: json!({ "type": "stdio", "command": "echo" }),
+                apps: McpApps {
+                    claude: true,
+                    codex: false,
+                    gemini: false,
+                    opencode: false,
+                },
+                description: None,
+                homepage: None,
+                docs: None,
+                tags: Vec::new(),
+            },
+        );
+
+    let state = create_test_state_with_config(&config).expect("create test state");
+    let mcp_synced = || {
+        read_json_file::<serde_json::Value>(&get_claude_mcp_path())
+            .ok()
+            .and_then(|v| v.pointer("/mcpServers/echo-server").cloned())
+            .is_some()
+    };
+
+    ProviderService::switch(&state, AppType::Claude, "manual").expect("switch to manual");
+    assert!(
+        !mcp_synced(),
+        "MCP sync should be skipped for a provider with skipMcpSync"
+    );
+
+    ProviderService::switch(&state, AppType::Claude, "auto").expect("switch to auto");
+    assert!(mcp_synced(), "MCP sync should run for other providers");
+}
+
 #[test]
 fn provider_service_switch_claude_updates_live_and_state() {
     let _guard = test_mutex().lock().expect("acquire test mutex");
@@ -901,3 +980,1334 @@ fn provider_service_delete_current_provider_returns_error() {
         other => panic!("expected Config/Message error, got {other:?}"),
     }
 }
+
+fn claude_switch_test_config() -> MultiAppConfig {
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "old-provider".to_string();
+        for (id, key) in [("old-provider", "old-key"), ("new-provider", "new-key")] {
+            manager.providers.insert(
+                id.to_string(),
+                Provider::with_id(
+                    id.to_string(),
+                    id.to_string(),
+                    json!({ "env": { "ANTHROPIC_API_KEY": key } }),
+                    None,
+                ),
+            );
+        }
+    }
+    config
+}
+
+#[test]
+fn switch_and_launch_switches_then_launches_with_cwd() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    let mut launched = None;
+    ProviderService::switch_and_launch_with(
+        &state,
+        AppType::Claude,
+        "new-provider",
+        "claude",
+        Some("/tmp/project"),
+        |command, cwd| {
+            launched = Some((command.to_string(), cwd.map(str::to_string)));
+            Ok(())
+        },
+    )
+    .expect("switch and launch should succeed");
+
+    assert_eq!(
+        launched,
+        Some(("claude".to_string(), Some("/tmp/project".to_string())))
+    );
+    let current_id = state
+        .db
+        .get_current_provider(AppType::Claude.as_str())
+        .expect("get current provider");
+    assert_eq!(current_id.as_deref(), Some("new-provider"));
+
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read claude live settings");
+    assert_eq!(
+        live.pointer("/env/ANTHROPIC_API_KEY")
+            .and_then(|v| v.as_str()),
+        Some("new-key")
+    );
+}
+
+#[test]
+fn switch_and_launch_reports_completed_switch_when_launch_fails() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    let err = ProviderService::switch_and_launch_with(
+        &state,
+        AppType::Claude,
+        "new-provider",
+        "claude",
+        None,
+        |_, _| Err("terminal not installed".to_string()),
+    )
+    .expect_err("launch failure should be reported");
+
+    let msg = err.to_string();
+    assert!(msg.contains("Switched to provider new-provider"), "{msg}");
+    assert!(msg.contains("terminal not installed"), "{msg}");
+
+    let current_id = state
+        .db
+        .get_current_provider(AppType::Claude.as_str())
+        .expect("get current provider");
+    assert_eq!(
+        current_id.as_deref(),
+        Some("new-provider"),
+        "switch should stay applied even if launch fails"
+    );
+}
+
+#[test]
+fn switch_and_launch_skips_launch_when_switch_fails() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+
+    let mut launched = false;
+    ProviderService::switch_and_launch_with(
+        &state,
+        AppType::Claude,
+        "missing",
+        "claude",
+        None,
+        |_, _| {
+            launched = true;
+            Ok(())
+        },
+    )
+    .expect_err("switching missing provider should fail");
+
+    assert!(!launched, "launch must not run when switch fails");
+}
+
+#[test]
+fn convert_provider_claude_to_codex_maps_credentials() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "relay".to_string();
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({
+                    "env": {
+                        "ANTHROPIC_AUTH_TOKEN": "sk-relay",
+                        "ANTHROPIC_BASE_URL": "https://relay.example",
+                        "ANTHROPIC_MODEL": "claude-sonnet-4-5"
+                    }
+                }),
+                Some("https://relay.example".to_string()),
+            ),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+
+    let converted = ProviderService::convert_provider(
+        &state,
+        AppType::Claude,
+        AppType::Codex,
+        "relay",
+        "Relay (Codex)",
+    )
+    .expect("convert claude provider to codex");
+
+    assert_ne!(converted.id, "relay");
+    assert_eq!(converted.name, "Relay (Codex)");
+    assert_eq!(
+        converted.website_url.as_deref(),
+        Some("https://relay.example")
+    );
+    assert_eq!(
+        converted
+            .settings_config
+            .pointer("/auth/OPENAI_API_KEY")
+            .and_then(|v| v.as_str()),
+        Some("sk-relay")
+    );
+    let config_toml = converted
+        .settings_config
+        .get("config")
+        .and_then(|v| v.as_str())
+        .expect("codex config.toml");
+    assert!(
+        config_toml.contains("base_url = \"https://relay.example/v1\""),
+        "{config_toml}"
+    );
+    assert!(
+        !config_toml.contains("claude-sonnet-4-5"),
+        "claude model should not be carried over"
+    );
+
+    let saved = state
+        .db
+        .get_provider_by_id(&converted.id, AppType::Codex.as_str())
+        .expect("query converted provider");
+    assert!(saved.is_some(), "converted provider should be saved");
+}
+
+#[test]
+fn convert_provider_codex_to_gemini_strips_version_prefix() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Codex)
+            .expect("codex manager");
+        manager.current = "relay".to_string();
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({
+                    "auth": { "OPENAI_API_KEY": "sk-codex" },
+                    "config": "model_provider = \"relay\"\nmodel = \"gpt-5.2-codex\"\n\n[model_providers.relay]\nname = \"relay\"\nbase_url = \"https://relay.example/v1\"\nwire_api = \"responses\"\n"
+                }),
+                None,
+            ),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+
+    let converted =
+        ProviderService::convert_provider(&state, AppType::Codex, AppType::Gemini, "relay", "  ")
+            .expect("convert codex provider to gemini");
+
+    assert_eq!(converted.name, "Relay", "blank name falls back to source");
+    let env = converted
+        .settings_config
+        .get("env")
+        .and_then(|v| v.as_object())
+        .expect("gemini env");
+    assert_eq!(
+        env.get("GEMINI_API_KEY").and_then(|v| v.as_str()),
+        Some("sk-codex")
+    );
+    assert_eq!(
+        env.get("GOOGLE_GEMINI_BASE_URL").and_then(|v| v.as_str()),
+        Some("https://relay.example")
+    );
+
+    let saved = state
+        .db
+        .get_provider_by_id(&converted.id, AppType::Gemini.as_str())
+        .expect("query converted provider");
+    assert!(saved.is_some(), "converted provider should be saved");
+}
+
+#[test]
+fn convert_provider_rejects_additive_apps() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    let err = ProviderService::convert_provider(
+        &state,
+        AppType::Claude,
+        AppType::OpenCode,
+        "old-provider",
+        "Copy",
+    )
+    .expect_err("opencode is not a supported target");
+    assert!(err.to_string().contains("not supported"), "{err}");
+}
+
+#[test]
+fn update_provider_records_history_snapshot() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    let mut provider = state
+        .db
+        .get_provider_by_id("new-provider", AppType::Claude.as_str())
+        .expect("load provider")
+        .expect("provider exists");
+    provider.settings_config = json!({ "env": { "ANTHROPIC_API_KEY": "edited-key" } });
+    ProviderService::update(&state, AppType::Claude, provider.clone()).expect("update provider");
+
+    let history = ProviderService::list_history(&state, AppType::Claude, "new-provider")
+        .expect("list history");
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        history[0].settings_config["env"]["ANTHROPIC_API_KEY"],
+        "new-key"
+    );
+
+    // 配置未变化时不应产生新的历史记录
+    ProviderService::update(&state, AppType::Claude, provider).expect("update unchanged");
+    let history = ProviderService::list_history(&state, AppType::Claude, "new-provider")
+        .expect("list history");
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn revert_provider_restores_snapshot_and_syncs_live() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    let mut provider = state
+        .db
+        .get_provider_by_id("old-provider", AppType::Claude.as_str())
+        .expect("load provider")
+        .expect("provider exists");
+    provider.settings_config = json!({ "env": { "ANTHROPIC_API_KEY": "oops-key" } });
+    ProviderService::update(&state, AppType::Claude, provider).expect("update provider");
+
+    let restored = ProviderService::revert_to(&state, AppType::Claude, "old-provider", 0)
+        .expect("revert provider");
+    assert_eq!(
+        restored.settings_config["env"]["ANTHROPIC_API_KEY"],
+        "old-key"
+    );
+
+    let stored = state
+        .db
+        .get_provider_by_id("old-provider", AppType::Claude.as_str())
+        .expect("load provider")
+        .expect("provider exists");
+    assert_eq!(
+        stored.settings_config["env"]["ANTHROPIC_API_KEY"],
+        "old-key"
+    );
+
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read claude live settings");
+    assert_eq!(live["env"]["ANTHROPIC_API_KEY"], "old-key");
+
+    // 撤销本身也会留下快照，可以再次撤销
+    let history = ProviderService::list_history(&state, AppType::Claude, "old-provider")
+        .expect("list history");
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history[0].settings_config["env"]["ANTHROPIC_API_KEY"],
+        "oops-key"
+    );
+
+    ProviderService::revert_to(&state, AppType::Claude, "old-provider", 5)
+        .expect_err("out-of-range history index should fail");
+}
+
+#[test]
+fn provider_history_is_capped_per_provider() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    let mut provider = state
+        .db
+        .get_provider_by_id("new-provider", AppType::Claude.as_str())
+        .expect("load provider")
+        .expect("provider exists");
+    for i in 0..PROVIDER_HISTORY_LIMIT + 5 {
+        provider.settings_config = json!({ "env": { "ANTHROPIC_API_KEY": format!("key-{i}") } });
+        ProviderService::update(&state, AppType::Claude, provider.clone())
+            .expect("update provider");
+    }
+
+    let history = ProviderService::list_history(&state, AppType::Claude, "new-provider")
+        .expect("list history");
+    assert_eq!(history.len(), PROVIDER_HISTORY_LIMIT);
+    let last = PROVIDER_HISTORY_LIMIT + 3;
+    assert_eq!(
+        history[0].settings_config["env"]["ANTHROPIC_API_KEY"],
+        format!("key-{last}")
+    );
+}
+
+#[test]
+fn live_unmanaged_changes_detects_external_edit_only() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    assert!(
+        !ProviderService::live_has_unmanaged_changes(&state, AppType::Claude)
+            .expect("check before any write"),
+        "no recorded write yet should not be reported as a change"
+    );
+
+    ProviderService::switch(&state, AppType::Claude, "new-provider").expect("switch provider");
+    assert!(
+        !ProviderService::live_has_unmanaged_changes(&state, AppType::Claude)
+            .expect("check after switch"),
+        "a CC Switch write must not be reported as an unmanaged change"
+    );
+
+    let settings_path = get_claude_settings_path();
+    let mut live: serde_json::Value =
+        read_json_file(&settings_path).expect("read claude live settings");
+    live["env"]["ANTHROPIC_BASE_URL"] = json!("https://hand-edited.example");
+    std::fs::write(
+        &settings_path,
+        serde_json::to_string_pretty(&live).expect("serialize live settings"),
+    )
+    .expect("write live settings");
+
+    assert!(
+        ProviderService::live_has_unmanaged_changes(&state, AppType::Claude)
+            .expect("check after external edit"),
+        "hand edit should be detected"
+    );
+
+    ProviderService::switch(&state, AppType::Claude, "old-provider").expect("switch back");
+    assert!(
+        !ProviderService::live_has_unmanaged_changes(&state, AppType::Claude)
+            .expect("check after overwrite"),
+        "fingerprint should be refreshed by the next CC Switch write"
+    );
+}
+
+#[test]
+fn switch_refuses_disabled_provider_until_reenabled() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    ProviderService::set_enabled(&state, AppType::Claude, "new-provider", false)
+        .expect("disable provider");
+    let err = ProviderService::switch(&state, AppType::Claude, "new-provider")
+        .expect_err("disabled provider must not become current");
+    assert!(err.to_string().contains("disabled") || err.to_string().contains("禁用"));
+    assert_eq!(
+        state
+            .db
+            .get_current_provider(AppType::Claude.as_str())
+            .expect("read current"),
+        Some("old-provider".to_string())
+    );
+
+    ProviderService::set_enabled(&state, AppType::Claude, "new-provider", true)
+        .expect("re-enable provider");
+    ProviderService::switch(&state, AppType::Claude, "new-provider")
+        .expect("re-enabled provider can be switched to");
+}
+
+#[test]
+fn frozen_provider_refuses_edits_but_can_be_switched_to() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    ProviderService::set_frozen(&state, AppType::Claude, "new-provider", true)
+        .expect("freeze provider");
+
+    let mut edited = state
+        .db
+        .get_provider_by_id("new-provider", AppType::Claude.as_str())
+        .expect("read provider")
+        .expect("provider exists");
+    assert!(edited.is_frozen());
+    edited.name = "Renamed".to_string();
+    let err = ProviderService::update(&state, AppType::Claude, edited.clone())
+        .expect_err("frozen provider must not be edited");
+    assert!(err.to_string().contains("frozen") || err.to_string().contains("冻结"));
+    ProviderService::delete(&state, AppType::Claude, "new-provider")
+        .expect_err("frozen provider must not be deleted");
+    ProviderService::add_custom_endpoint(
+        &state,
+        AppType::Claude,
+        "new-provider",
+        "https://mirror.example".to_string(),
+    )
+    .expect_err("frozen provider endpoints must not be added");
+
+    // 启用状态属于路由选择，与切换一样不受冻结限制
+    ProviderService::set_enabled(&state, AppType::Claude, "new-provider", false)
+        .expect("frozen provider can be disabled");
+    ProviderService::set_enabled(&state, AppType::Claude, "new-provider", true)
+        .expect("frozen provider can be re-enabled");
+
+    ProviderService::switch(&state, AppType::Claude, "new-provider")
+        .expect("frozen provider can still be switched to");
+    assert_eq!(
+        state
+            .db
+            .get_current_provider(AppType::Claude.as_str())
+            .expect("read current"),
+        Some("new-provider".to_string())
+    );
+
+    // 切走时不把 Live 配置回填到冻结的供应商
+    let frozen_settings = edited.settings_config.clone();
+    std::fs::write(
+        get_claude_settings_path(),
+        json!({ "env": { "ANTHROPIC_API_KEY": "edited-in-live" } }).to_string(),
+    )
+    .expect("edit live settings");
+    ProviderService::switch(&state, AppType::Claude, "old-provider").expect("switch away");
+    assert_eq!(
+        state
+            .db
+            .get_provider_by_id("new-provider", AppType::Claude.as_str())
+            .expect("read provider")
+            .expect("provider exists")
+            .settings_config,
+        frozen_settings,
+        "frozen provider is not backfilled"
+    );
+
+    ProviderService::set_frozen(&state, AppType::Claude, "new-provider", false)
+        .expect("unfreeze provider");
+    edited.meta.get_or_insert_with(ProviderMeta::default).frozen = None;
+    ProviderService::update(&state, AppType::Claude, edited)
+        .expect("unfrozen provider is editable");
+    let saved = state
+        .db
+        .get_provider_by_id("new-provider", AppType::Claude.as_str())
+        .expect("read provider")
+        .expect("provider exists");
+    assert_eq!(saved.name, "Renamed");
+}
+
+#[test]
+fn prefer_healthy_reads_failover_alternative_without_switching() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+    ProviderService::switch(&state, AppType::Claude, "old-provider").expect("pin current");
+    let app = AppType::Claude;
+    let mark_unhealthy = |id: &str| {
+        futures::executor::block_on(state.db.update_provider_health_with_threshold(
+            id,
+            app.as_str(),
+            false,
+            Some("upstream down".to_string()),
+            None,
+            1,
+        ))
+        .expect("mark provider unhealthy");
+    };
+
+    // 当前供应商健康时两种模式一致
+    assert_eq!(
+        get_effective_current_provider_with(&state.db, &app, true).expect("read current"),
+        Some("old-provider".to_string())
+    );
+
+    mark_unhealthy("old-provider");
+    // 故障转移队列中没有替代者时仍返回原当前供应商
+    assert_eq!(
+        get_effective_current_provider_with(&state.db, &app, true).expect("read current"),
+        Some("old-provider".to_string())
+    );
+
+    state
+        .db
+        .add_to_failover_queue(app.as_str(), "old-provider")
+        .expect("queue old provider");
+    state
+        .db
+        .add_to_failover_queue(app.as_str(), "new-provider")
+        .expect("queue new provider");
+    assert_eq!(
+        get_effective_current_provider_with(&state.db, &app, true).expect("read current"),
+        Some("new-provider".to_string())
+    );
+    assert_eq!(
+        ProviderService::current_with(&state, app.clone(), true).expect("read current"),
+        "new-provider"
+    );
+    // 默认模式与存储的当前供应商均不受影响
+    assert_eq!(
+        ProviderService::current(&state, app.clone()).expect("read current"),
+        "old-provider"
+    );
+    assert_eq!(
+        get_effective_current_provider_with(&state.db, &app, false).expect("read current"),
+        Some("old-provider".to_string())
+    );
+    assert_eq!(
+        state
+            .db
+            .get_current_provider(app.as_str())
+            .expect("read stored current"),
+        Some("old-provider".to_string())
+    );
+
+    mark_unhealthy("new-provider");
+    assert_eq!(
+        get_effective_current_provider_with(&state.db, &app, true).expect("read current"),
+        Some("old-provider".to_string()),
+        "no healthy alternative falls back to the stored current"
+    );
+}
+
+#[test]
+fn import_from_env_file_builds_validated_provider() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let env_path = home.join("relay.env");
+    std::fs::write(
+        &env_path,
+        "# relay credentials\nexport ANTHROPIC_AUTH_TOKEN=\"sk-relay\"\nANTHROPIC_BASE_URL=https://relay.example\n",
+    )
+    .expect("write env file");
+
+    let provider = ProviderService::import_from_env_file(AppType::Claude, &env_path, "  ")
+        .expect("import claude env file");
+    assert_eq!(provider.name, "relay.env");
+    assert_eq!(
+        provider.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-relay"
+    );
+
+    let missing = home.join("missing.env");
+    ProviderService::import_from_env_file(AppType::Gemini, &missing, "Gemini")
+        .expect_err("missing file should fail");
+}
+
+#[test]
+fn broadcast_common_config_propagates_shared_env_and_keeps_credentials() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "claude-relay".to_string();
+        manager.providers.insert(
+            "claude-relay".to_string(),
+            Provider::with_id(
+                "claude-relay".to_string(),
+                "Claude Relay".to_string(),
+                json!({
+                    "env": {
+                        "ANTHROPIC_AUTH_TOKEN": "sk-claude",
+                        "ANTHROPIC_BASE_URL": "https://claude.example",
+                        "HTTPS_PROXY": "http://127.0.0.1:7890",
+                        "DISABLE_TELEMETRY": "1"
+                    }
+                }),
+                None,
+            ),
+        );
+    }
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Gemini)
+            .expect("gemini manager");
+        manager.current = "gemini-relay".to_string();
+        manager.providers.insert(
+            "gemini-relay".to_string(),
+            Provider::with_id(
+                "gemini-relay".to_string(),
+                "Gemini Relay".to_string(),
+                json!({
+                    "env": {
+                        "GEMINI_API_KEY": "gm-key",
+                        "GOOGLE_GEMINI_BASE_URL": "https://gemini.example",
+                        "HTTPS_PROXY": "http://old-proxy"
+                    },
+                    "config": {}
+                }),
+                None,
+            ),
+        );
+    }
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Codex)
+            .expect("codex manager");
+        manager.current = "codex-relay".to_string();
+        manager.providers.insert(
+            "codex-relay".to_string(),
+            Provider::with_id(
+                "codex-relay".to_string(),
+                "Codex Relay".to_string(),
+                json!({
+                    "auth": { "OPENAI_API_KEY": "sk-codex" },
+                    "config": "model = \"gpt-5-codex\"\n"
+                }),
+                None,
+            ),
+        );
+    }
+
+    let state = create_test_state_with_config(&config).expect("create test state");
+    let codex_before = state
+        .db
+        .get_provider_by_id("codex-relay", AppType::Codex.as_str())
+        .expect("load codex")
+        .expect("codex provider")
+        .settings_config;
+
+    let result = ProviderService::broadcast_common_config(
+        &state,
+        AppType::Claude,
+        vec![AppType::Gemini, AppType::Codex],
+    )
+    .expect("broadcast should succeed");
+    assert_eq!(result.updated, vec!["gemini".to_string()]);
+    assert!(result.frozen.is_empty());
+    assert_eq!(result.unsupported, vec!["codex".to_string()]);
+
+    let gemini = state
+        .db
+        .get_provider_by_id("gemini-relay", AppType::Gemini.as_str())
+        .expect("load gemini")
+        .expect("gemini provider");
+    let env = &gemini.settings_config["env"];
+    assert_eq!(env["HTTPS_PROXY"], "http://127.0.0.1:7890");
+    assert_eq!(env["DISABLE_TELEMETRY"], "1");
+    assert_eq!(env["GEMINI_API_KEY"], "gm-key", "credentials must be kept");
+    assert_eq!(env["GOOGLE_GEMINI_BASE_URL"], "https://gemini.example");
+    assert!(env.get("ANTHROPIC_AUTH_TOKEN").is_none());
+    assert!(env.get("ANTHROPIC_BASE_URL").is_none());
+
+    let codex_after = state
+        .db
+        .get_provider_by_id("codex-relay", AppType::Codex.as_str())
+        .expect("load codex")
+        .expect("codex provider")
+        .settings_config;
+    assert_eq!(
+        codex_before, codex_after,
+        "codex has no env section to merge into"
+    );
+}
+
+#[test]
+fn broadcast_common_config_skips_frozen_targets() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    for (app_type, id, env) in [
+        (
+            AppType::Claude,
+            "claude-relay",
+            json!({ "ANTHROPIC_AUTH_TOKEN": "sk-claude", "HTTPS_PROXY": "http://127.0.0.1:7890" }),
+        ),
+        (
+            AppType::Gemini,
+            "gemini-relay",
+            json!({ "GEMINI_API_KEY": "gm-key", "HTTPS_PROXY": "http://old-proxy" }),
+        ),
+    ] {
+        let manager = config.get_manager_mut(&app_type).expect("app manager");
+        manager.current = id.to_string();
+        manager.providers.insert(
+            id.to_string(),
+            Provider::with_id(id.to_string(), id.to_string(), json!({ "env": env }), None),
+        );
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+    ProviderService::set_frozen(&state, AppType::Gemini, "gemini-relay", true)
+        .expect("freeze gemini provider");
+
+    let result =
+        ProviderService::broadcast_common_config(&state, AppType::Claude, vec![AppType::Gemini])
+            .expect("broadcast reports frozen targets instead of failing");
+    assert!(result.updated.is_empty());
+    assert_eq!(result.frozen, vec!["gemini".to_string()]);
+
+    let gemini = state
+        .db
+        .get_provider_by_id("gemini-relay", AppType::Gemini.as_str())
+        .expect("load gemini")
+        .expect("gemini provider");
+    assert_eq!(
+        gemini.settings_config["env"]["HTTPS_PROXY"],
+        "http://old-proxy"
+    );
+}
+
+#[test]
+fn set_default_provider_updates_db_without_switching_local_device() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+    ProviderService::switch(&state, AppType::Claude, "new-provider")
+        .expect("switch to new provider");
+    let live_before: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read live before");
+
+    ProviderService::set_default_provider(&state, AppType::Claude, "old-provider")
+        .expect("set default provider");
+
+    let db_default = state
+        .db
+        .get_current_provider(AppType::Claude.as_str())
+        .expect("get db default");
+    assert_eq!(db_default.as_deref(), Some("old-provider"));
+
+    let effective = ProviderService::current(&state, AppType::Claude).expect("current provider");
+    assert_eq!(effective, "new-provider", "local device keeps its provider");
+
+    // 重启时的 is_current 修复不能把默认值改回本设备的当前供应商
+    assert_eq!(
+        state.db.repair_current_flags().expect("repair on startup"),
+        0
+    );
+    assert_eq!(
+        state
+            .db
+            .get_current_provider(AppType::Claude.as_str())
+            .expect("get db default after restart")
+            .as_deref(),
+        Some("old-provider")
+    );
+
+    let live_after: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read live after");
+    assert_eq!(live_after, live_before, "live config must not be rewritten");
+
+    let err = ProviderService::set_default_provider(&state, AppType::Claude, "missing")
+        .expect_err("missing provider should fail");
+    assert!(err.to_string().contains("missing"));
+}
+
+#[test]
+fn import_merge_twice_creates_no_duplicate_providers() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    let claude = |id: &str, token: &str, base_url: &str| {
+        Provider::with_id(
+            id.to_string(),
+            format!("Provider {id}"),
+            json!({
+                "env": {
+                    "ANTHROPIC_AUTH_TOKEN": token,
+                    "ANTHROPIC_BASE_URL": base_url
+                }
+            }),
+            None,
+        )
+    };
+    let bundle = vec![
+        claude("a", "token-a", "https://a.example"),
+        claude("b", "token-b", "https://b.example"),
+    ];
+
+    let first = ProviderService::import_merge(&state, AppType::Claude, bundle.clone())
+        .expect("first import");
+    assert_eq!(first.imported, vec!["a".to_string(), "b".to_string()]);
+    assert!(first.skipped.is_empty());
+
+    let second =
+        ProviderService::import_merge(&state, AppType::Claude, bundle).expect("second import");
+    assert!(second.imported.is_empty());
+    assert_eq!(second.skipped, vec!["a".to_string(), "b".to_string()]);
+
+    // 不同 ID、相同内容（base_url 尾部斜杠不影响）也视为重复
+    let renamed = vec![
+        claude("a-copy", "token-a", "https://a.example/"),
+        claude("c", "token-a", "https://c.example"),
+    ];
+    let third =
+        ProviderService::import_merge(&state, AppType::Claude, renamed).expect("third import");
+    assert_eq!(third.imported, vec!["c".to_string()]);
+    assert_eq!(third.skipped, vec!["a-copy".to_string()]);
+
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list providers");
+    assert_eq!(providers.len(), 3);
+}
+
+#[test]
+fn stale_providers_excludes_recently_verified() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    for id in ["fresh", "old", "never"] {
+        let provider = Provider::with_id(
+            id.to_string(),
+            format!("Provider {id}"),
+            json!({
+                "env": {
+                    "ANTHROPIC_AUTH_TOKEN": format!("token-{id}"),
+                    "ANTHROPIC_BASE_URL": "https://relay.example"
+                }
+            }),
+            None,
+        );
+        state
+            .db
+            .save_provider("claude", &provider)
+            .expect("save provider");
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    state
+        .db
+        .set_provider_last_verified("claude", "fresh", now)
+        .expect("mark fresh verified");
+    state
+        .db
+        .set_provider_last_verified("claude", "old", now - 30 * 24 * 60 * 60)
+        .expect("mark old verified");
+
+    let max_age = std::time::Duration::from_secs(7 * 24 * 60 * 60);
+    let stale =
+        ProviderService::stale_providers(&state, AppType::Claude, max_age).expect("stale list");
+    let ids: Vec<&str> = stale.iter().map(|p| p.provider_id.as_str()).collect();
+    assert_eq!(ids, vec!["old", "never"]);
+    assert_eq!(stale[0].last_verified_at, Some(now - 30 * 24 * 60 * 60));
+    assert_eq!(stale[1].last_verified_at, None);
+
+    // 编辑供应商不会清除验证时间
+    let mut fresh = state
+        .db
+        .get_provider_by_id("fresh", "claude")
+        .expect("load provider")
+        .expect("fresh exists");
+    fresh.name = "Renamed".to_string();
+    state
+        .db
+        .save_provider("claude", &fresh)
+        .expect("update provider");
+    let stale =
+        ProviderService::stale_providers(&state, AppType::Claude, max_age).expect("stale list");
+    assert!(stale.iter().all(|p| p.provider_id != "fresh"));
+}
+
+#[test]
+fn endpoint_latency_history_is_ordered_and_capped() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    config
+        .get_manager_mut(&AppType::Claude)
+        .expect("claude manager")
+        .providers
+        .insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({ "env": { "ANTHROPIC_API_KEY": "relay-key" } }),
+                None,
+            ),
+        );
+    let state = create_test_state_with_config(&config).expect("create test state");
+
+    let url = "https://relay.example.com";
+    let total = ENDPOINT_LATENCY_HISTORY_LIMIT + 5;
+    for i in 0..total {
+        let result = EndpointLatency {
+            // 末尾斜杠应与无斜杠的地址视为同一端点
+            url: format!("{url}/"),
+            latency: (i % 10 != 0).then_some(i as u128),
+            status: Some(200),
+            error: None,
+        };
+        ProviderService::record_endpoint_latencies(&state, AppType::Claude, "relay", &[result])
+            .expect("record latency");
+    }
+
+    let trend = ProviderService::endpoint_latency_trend(
+        &state,
+        AppType::Claude,
+        "relay",
+        url,
+        ENDPOINT_LATENCY_HISTORY_LIMIT,
+    )
+    .expect("read trend");
+    assert_eq!(trend.len(), ENDPOINT_LATENCY_HISTORY_LIMIT);
+    // 最旧的 5 条被裁剪，剩余按时间正序返回
+    assert_eq!(trend.first().and_then(|s| s.latency_ms), Some(5));
+    assert_eq!(
+        trend.last().and_then(|s| s.latency_ms),
+        Some((total - 1) as u64)
+    );
+    // 失败的测速保留为空延迟
+    assert!(trend.iter().any(|s| s.latency_ms.is_none()));
+    // 测速可达即刷新供应商的验证时间
+    let verified = state
+        .db
+        .get_providers_last_verified("claude")
+        .expect("load last verified");
+    assert!(verified.get("relay").copied().flatten().is_some());
+
+    let recent = ProviderService::endpoint_latency_trend(&state, AppType::Claude, "relay", url, 3)
+        .expect("read recent trend");
+    let latencies: Vec<_> = recent.iter().map(|s| s.latency_ms).collect();
+    assert_eq!(
+        latencies,
+        vec![
+            Some((total - 3) as u64),
+            Some((total - 2) as u64),
+            Some((total - 1) as u64)
+        ]
+    );
+
+    // 其他应用下同一供应商 ID 的历史互不影响
+    let other = ProviderService::endpoint_latency_trend(&state, AppType::Codex, "relay", url, 10)
+        .expect("read codex trend");
+    assert!(other.is_empty());
+}
+
+#[test]
+fn adopt_current_live_creates_current_provider_without_rewriting_live() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let live = json!({
+        "env": {
+            "ANTHROPIC_AUTH_TOKEN": "hand-written-key",
+            "ANTHROPIC_BASE_URL": "https://relay.example.com"
+        },
+        "permissions": { "allow": ["Bash"] }
+    });
+    let settings_path = get_claude_settings_path();
+    std::fs::create_dir_all(settings_path.parent().expect("settings dir"))
+        .expect("create claude dir");
+    let live_text = serde_json::to_string_pretty(&live).expect("serialize live");
+    std::fs::write(&settings_path, &live_text).expect("seed claude live");
+
+    let state = create_test_state().expect("create test state");
+    let provider = ProviderService::adopt_current_live(&state, AppType::Claude, "  My relay  ")
+        .expect("adopt live config");
+
+    assert_eq!(provider.name, "My relay");
+    assert_eq!(provider.settings_config, live);
+    let saved = state
+        .db
+        .get_provider_by_id(&provider.id, AppType::Claude.as_str())
+        .expect("read provider")
+        .expect("adopted provider saved");
+    assert_eq!(saved.settings_config, live);
+    assert_eq!(
+        ProviderService::current(&state, AppType::Claude).expect("read current"),
+        provider.id
+    );
+
+    assert_eq!(
+        std::fs::read_to_string(&settings_path).expect("read live"),
+        live_text,
+        "adoption must not rewrite the live file"
+    );
+    assert!(
+        !ProviderService::live_has_unmanaged_changes(&state, AppType::Claude)
+            .expect("check unmanaged changes"),
+        "adopted live config should count as managed"
+    );
+}
+
+#[test]
+fn adopt_current_live_is_refused_while_taken_over() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let settings_path = get_claude_settings_path();
+    std::fs::create_dir_all(settings_path.parent().expect("settings dir"))
+        .expect("create claude dir");
+    std::fs::write(
+        &settings_path,
+        r#"{"env":{"ANTHROPIC_BASE_URL":"http://127.0.0.1:15721"}}"#,
+    )
+    .expect("seed claude live");
+
+    let state = create_test_state().expect("create test state");
+    futures::executor::block_on(state.db.save_live_backup("claude", "{\"env\":{}}"))
+        .expect("seed live backup");
+
+    let err = ProviderService::adopt_current_live(&state, AppType::Claude, "proxy")
+        .expect_err("adopting a taken-over live config must fail");
+    assert!(matches!(
+        err,
+        AppError::Localized {
+            key: "provider.adopt.takeover_active",
+            ..
+        }
+    ));
+    assert!(state
+        .db
+        .get_all_providers(AppType::Claude.as_str())
+        .expect("read providers")
+        .is_empty());
+}
+
+#[test]
+fn adopt_current_live_codex_keeps_auth_and_config() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let auth = json!({ "OPENAI_API_KEY": "sk-adopted" });
+    let config = "model = \"gpt-5\"\n";
+    write_codex_live_atomic(&auth, Some(config)).expect("seed codex live");
+
+    let state = create_test_state().expect("create test state");
+    let provider =
+        ProviderService::adopt_current_live(&state, AppType::Codex, "").expect("adopt codex live");
+
+    assert_eq!(provider.name, "default");
+    assert_eq!(provider.settings_config["auth"], auth);
+    assert_eq!(provider.settings_config["config"], config);
+    assert_eq!(
+        state
+            .db
+            .get_current_provider(AppType::Codex.as_str())
+            .expect("read current"),
+        Some(provider.id.clone())
+    );
+}
+
+#[test]
+fn manual_switch_records_switch_event() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state =
+        create_test_state_with_config(&claude_switch_test_config()).expect("create test state");
+
+    ProviderService::switch(&state, AppType::Claude, "new-provider").expect("switch provider");
+    ProviderService::switch(&state, AppType::Claude, "missing-provider")
+        .expect_err("missing provider cannot be switched to");
+    ProviderService::switch(&state, AppType::Claude, "old-provider").expect("switch back");
+
+    let events = state
+        .db
+        .get_switch_events(AppType::Claude.as_str(), 10)
+        .expect("read switch events");
+    assert_eq!(events.len(), 2, "failed switch must not be recorded");
+
+    assert_eq!(events[0].from_id.as_deref(), Some("new-provider"));
+    assert_eq!(events[0].to_id, "old-provider");
+    assert_eq!(events[1].from_id.as_deref(), Some("old-provider"));
+    assert_eq!(events[1].to_id, "new-provider");
+    assert!(events
+        .iter()
+        .all(|e| e.mode == "live" && e.app_type == "claude"));
+    assert!(events[0].ts >= events[1].ts);
+
+    assert!(state
+        .db
+        .get_switch_events(AppType::Codex.as_str(), 10)
+        .expect("read codex switch events")
+        .is_empty());
+}
+
+#[test]
+fn normalize_base_urls_dry_run_reports_and_apply_saves() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "doubled".to_string();
+        for (id, url) in [
+            ("doubled", "https://relay.example/v1/v1/"),
+            ("ambiguous", "https://other.example/v1"),
+            ("frozen", "https://frozen.example/"),
+        ] {
+            manager.providers.insert(
+                id.to_string(),
+                Provider::with_id(
+                    id.to_string(),
+                    id.to_string(),
+                    json!({ "env": { "ANTHROPIC_BASE_URL": url, "ANTHROPIC_API_KEY": "sk" } }),
+                    None,
+                ),
+            );
+        }
+    }
+    let state = create_test_state_with_config(&config).expect("create test state");
+    let base_url = |id: &str| {
+        state
+            .db
+            .get_provider_by_id(id, AppType::Claude.as_str())
+            .expect("read provider")
+            .expect("provider exists")
+            .settings_config["env"]["ANTHROPIC_BASE_URL"]
+            .clone()
+    };
+
+    ProviderService::set_frozen(&state, AppType::Claude, "frozen", true).expect("freeze provider");
+
+    let changes =
+        ProviderService::normalize_base_urls(&state, AppType::Claude, true).expect("dry run");
+    assert_eq!(changes.len(), 2, "single trailing /v1 is ambiguous");
+    assert_eq!(changes[0].provider_id, "doubled");
+    assert_eq!(changes[0].after, "https://relay.example/v1");
+    assert_eq!(changes[0].fixes, vec!["duplicate_v1", "trailing_slash"]);
+    assert!(!changes[0].frozen);
+    assert_eq!(changes[1].provider_id, "frozen");
+    assert!(changes[1].frozen);
+    assert_eq!(base_url("doubled"), "https://relay.example/v1/v1/");
+
+    let applied =
+        ProviderService::normalize_base_urls(&state, AppType::Claude, false).expect("apply fixes");
+    assert_eq!(applied, changes);
+    assert_eq!(base_url("doubled"), "https://relay.example/v1");
+    assert_eq!(base_url("ambiguous"), "https://other.example/v1");
+    assert_eq!(
+        base_url("frozen"),
+        "https://frozen.example/",
+        "frozen provider is reported but not saved"
+    );
+
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read claude live settings");
+    assert_eq!(
+        live["env"]["ANTHROPIC_BASE_URL"], "https://relay.example/v1",
+        "current provider is synced to live"
+    );
+
+    let remaining = ProviderService::normalize_base_urls(&state, AppType::Claude, true)
+        .expect("second dry run");
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].frozen);
+}
+
+#[test]
+fn normalize_all_claude_models_migrates_legacy_small_fast_model() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    for (id, env) in [
+        (
+            "legacy",
+            json!({
+                "ANTHROPIC_API_KEY": "sk",
+                "ANTHROPIC_MODEL": "main-model",
+                "ANTHROPIC_SMALL_FAST_MODEL": "fast-model"
+            }),
+        ),
+        (
+            "modern",
+            json!({
+                "ANTHROPIC_API_KEY": "sk",
+                "ANTHROPIC_DEFAULT_HAIKU_MODEL": "h",
+                "ANTHROPIC_DEFAULT_SONNET_MODEL": "s",
+                "ANTHROPIC_DEFAULT_OPUS_MODEL": "o"
+            }),
+        ),
+        (
+            "frozen",
+            json!({
+                "ANTHROPIC_API_KEY": "sk",
+                "ANTHROPIC_SMALL_FAST_MODEL": "fast-model"
+            }),
+        ),
+    ] {
+        state
+            .db
+            .save_provider(
+                AppType::Claude.as_str(),
+                &Provider::with_id(id.to_string(), id.to_string(), json!({ "env": env }), None),
+            )
+            .expect("seed provider");
+    }
+
+    ProviderService::set_frozen(&state, AppType::Claude, "frozen", true).expect("freeze provider");
+
+    let result =
+        ProviderService::normalize_all_claude_models_report(&state).expect("normalize models");
+    assert_eq!(
+        result.changed, 1,
+        "only the legacy provider needs normalization"
+    );
+    assert_eq!(result.frozen, vec!["frozen".to_string()]);
+    let frozen = state
+        .db
+        .get_provider_by_id("frozen", AppType::Claude.as_str())
+        .expect("read provider")
+        .expect("provider exists");
+    assert_eq!(
+        frozen.settings_config["env"]["ANTHROPIC_SMALL_FAST_MODEL"], "fast-model",
+        "frozen provider is left untouched"
+    );
+
+    let legacy = state
+        .db
+        .get_provider_by_id("legacy", AppType::Claude.as_str())
+        .expect("read provider")
+        .expect("provider exists");
+    let env = &legacy.settings_config["env"];
+    assert!(env.get("ANTHROPIC_SMALL_FAST_MODEL").is_none());
+    assert_eq!(env["ANTHROPIC_DEFAULT_HAIKU_MODEL"], "fast-model");
+    assert_eq!(env["ANTHROPIC_DEFAULT_SONNET_MODEL"], "main-model");
+    assert_eq!(env["ANTHROPIC_DEFAULT_OPUS_MODEL"], "main-model");
+
+    assert_eq!(
+        ProviderService::normalize_all_claude_models(&state).expect("second pass"),
+        0
+    );
+}
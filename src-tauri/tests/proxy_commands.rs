@@ -1,6 +1,7 @@
 use cc_switch_lib::{
     get_default_cost_multiplier_test_hook, get_pricing_model_source_test_hook,
-    set_default_cost_multiplier_test_hook, set_pricing_model_source_test_hook, AppError,
+    recover_startup_state_test_hook, set_default_cost_multiplier_test_hook,
+    set_pricing_model_source_test_hook, AppError,
 };
 
 #[path = "support.rs"]
@@ -76,3 +77,49 @@ async fn pricing_model_source_commands_round_trip() {
         other => panic!("expected localized error, got {other:?}"),
     }
 }
+
+// 测试使用 Mutex 进行串行化，跨 await 持锁是预期行为
+#[allow(clippy::await_holding_lock)]
+#[tokio::test]
+async fn safe_mode_skips_takeover_recovery_and_proxy_restore() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    state
+        .db
+        .save_live_backup("claude", "{\"env\":{}}")
+        .await
+        .expect("seed live backup");
+    let mut proxy_config = state
+        .db
+        .get_proxy_config_for_app("claude")
+        .await
+        .expect("get proxy config");
+    proxy_config.enabled = true;
+    state
+        .db
+        .update_proxy_config_for_app(proxy_config)
+        .await
+        .expect("enable takeover");
+
+    recover_startup_state_test_hook(&state, Some("1")).await;
+
+    // 接管残留保持原样，留待正常启动时恢复
+    assert!(state
+        .db
+        .get_live_backup("claude")
+        .await
+        .expect("get live backup")
+        .is_some());
+    assert!(!state.proxy_service.is_running().await);
+    assert!(
+        state
+            .db
+            .get_proxy_config_for_app("claude")
+            .await
+            .expect("get proxy config")
+            .enabled
+    );
+}
@@ -1,7 +1,8 @@
 use std::fs;
 
 use cc_switch_lib::{
-    migrate_skills_to_ssot, AppType, ImportSkillSelection, InstalledSkill, SkillApps, SkillService,
+    migrate_skills_to_ssot, update_settings, AppSettings, AppType, ImportSkillSelection,
+    InstalledSkill, SkillApps, SkillManifest, SkillService, SyncMethod,
 };
 
 #[path = "support.rs"]
@@ -110,6 +111,7 @@ fn sync_to_app_removes_disabled_and_orphaned_ssot_symlinks() {
                 opencode: false,
             },
             installed_at: 0,
+            sync_method_override: None,
         })
         .expect("save disabled skill");
 
@@ -154,6 +156,7 @@ fn uninstall_skill_creates_backup_before_removing_ssot() {
                 opencode: false,
             },
             installed_at: 123,
+            sync_method_override: None,
         })
         .expect("save skill");
 
@@ -222,6 +225,7 @@ fn restore_skill_backup_restores_files_to_ssot_and_current_app() {
                 opencode: false,
             },
             installed_at: 456,
+            sync_method_override: None,
         })
         .expect("save skill");
 
@@ -303,6 +307,7 @@ fn delete_skill_backup_removes_backup_directory() {
                 opencode: false,
             },
             installed_at: 789,
+            sync_method_override: None,
         })
         .expect("save skill");
 
@@ -383,3 +388,309 @@ fn migration_snapshot_overrides_multi_source_directory_inference() {
         "migration should no longer infer OpenCode enablement from a duplicate directory alone"
     );
 }
+
+fn installed_skill(directory: &str, claude: bool) -> InstalledSkill {
+    InstalledSkill {
+        id: format!("local:{directory}"),
+        name: directory.to_string(),
+        description: None,
+        directory: directory.to_string(),
+        repo_owner: None,
+        repo_name: None,
+        repo_branch: None,
+        readme_url: None,
+        apps: SkillApps {
+            claude,
+            codex: false,
+            gemini: false,
+            opencode: false,
+        },
+        installed_at: 0,
+        sync_method_override: None,
+    }
+}
+
+#[test]
+fn set_app_skills_makes_enabled_set_exact() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let ssot_dir = home.join(".cc-switch").join("skills");
+    for name in ["alpha", "beta", "gamma"] {
+        write_skill(&ssot_dir.join(name), name);
+    }
+
+    let state = create_test_state().expect("create test state");
+    for (name, claude) in [("alpha", true), ("beta", true), ("gamma", false)] {
+        state
+            .db
+            .save_skill(&installed_skill(name, claude))
+            .expect("save skill");
+    }
+    SkillService::sync_to_app(&state.db, &AppType::Claude).expect("initial sync");
+
+    SkillService::set_app_skills(
+        &state.db,
+        &AppType::Claude,
+        vec!["beta".to_string(), "gamma".to_string()],
+    )
+    .expect("set app skills");
+
+    let claude_skills_dir = home.join(".claude").join("skills");
+    assert!(!claude_skills_dir.join("alpha").exists());
+    assert!(claude_skills_dir.join("beta").join("SKILL.md").exists());
+    assert!(claude_skills_dir.join("gamma").join("SKILL.md").exists());
+
+    let skills = state.db.get_all_installed_skills().expect("load skills");
+    let enabled: Vec<&str> = skills
+        .values()
+        .filter(|skill| skill.apps.claude)
+        .map(|skill| skill.directory.as_str())
+        .collect();
+    assert_eq!(enabled, vec!["beta", "gamma"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn skill_sync_method_override_forces_copy_when_global_is_symlink() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let ssot_dir = home.join(".cc-switch").join("skills");
+    for name in ["linked", "copied"] {
+        write_skill(&ssot_dir.join(name), name);
+    }
+
+    let state = create_test_state().expect("create test state");
+    update_settings(AppSettings {
+        skill_sync_method: SyncMethod::Symlink,
+        ..AppSettings::default()
+    })
+    .expect("set global sync method");
+
+    state
+        .db
+        .save_skill(&installed_skill("linked", true))
+        .expect("save linked skill");
+    let mut copied = installed_skill("copied", true);
+    copied.sync_method_override = Some(SyncMethod::Copy);
+    state.db.save_skill(&copied).expect("save copied skill");
+
+    SkillService::sync_to_app(&state.db, &AppType::Claude).expect("sync skills");
+
+    let claude_skills_dir = home.join(".claude").join("skills");
+    let is_symlink = |name: &str| {
+        fs::symlink_metadata(claude_skills_dir.join(name))
+            .expect("skill synced")
+            .file_type()
+            .is_symlink()
+    };
+    assert!(is_symlink("linked"), "global symlink method should apply");
+    assert!(
+        !is_symlink("copied"),
+        "per-skill override should force a real copy"
+    );
+    assert!(claude_skills_dir.join("copied").join("SKILL.md").exists());
+
+    // 清除覆盖后按全局方式重新同步
+    SkillService::set_sync_method_override(&state.db, "local:copied", None)
+        .expect("clear override");
+    assert!(is_symlink("copied"));
+    let stored = state
+        .db
+        .get_installed_skill("local:copied")
+        .expect("load skill")
+        .expect("skill exists");
+    assert_eq!(stored.sync_method_override, None);
+
+    SkillService::set_sync_method_override(&state.db, "local:copied", Some(SyncMethod::Copy))
+        .expect("set override");
+    assert!(!is_symlink("copied"));
+    let stored = state
+        .db
+        .get_installed_skill("local:copied")
+        .expect("load skill")
+        .expect("skill exists");
+    assert_eq!(stored.sync_method_override, Some(SyncMethod::Copy));
+}
+
+#[test]
+fn set_app_skills_keeps_directories_it_never_synced() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    for name in ["alpha", "beta"] {
+        write_skill(&home.join(".cc-switch").join("skills").join(name), name);
+    }
+    // 用户自己维护的同名目录，beta 从未对 Claude 启用
+    let claude_skills_dir = home.join(".claude").join("skills");
+    write_skill(&claude_skills_dir.join("beta"), "beta-user");
+
+    let state = create_test_state().expect("create test state");
+    for (name, claude) in [("alpha", true), ("beta", false)] {
+        state
+            .db
+            .save_skill(&installed_skill(name, claude))
+            .expect("save skill");
+    }
+    SkillService::set_app_skills(&state.db, &AppType::Claude, vec!["alpha".to_string()])
+        .expect("enable alpha");
+    assert!(claude_skills_dir.join("alpha").join("SKILL.md").exists());
+
+    SkillService::set_app_skills(&state.db, &AppType::Claude, Vec::new()).expect("disable all");
+
+    assert!(!claude_skills_dir.join("alpha").exists());
+    let user_skill = fs::read_to_string(claude_skills_dir.join("beta").join("SKILL.md"))
+        .expect("user-managed skill kept");
+    assert!(user_skill.contains("beta-user"));
+}
+
+#[test]
+fn set_app_skills_rejects_unknown_directory_without_changes() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    write_skill(
+        &home.join(".cc-switch").join("skills").join("alpha"),
+        "alpha",
+    );
+
+    let state = create_test_state().expect("create test state");
+    state
+        .db
+        .save_skill(&installed_skill("alpha", true))
+        .expect("save skill");
+
+    SkillService::set_app_skills(&state.db, &AppType::Claude, vec!["missing".to_string()])
+        .expect_err("unknown skill should be rejected");
+
+    let skill = state
+        .db
+        .get_installed_skill("local:alpha")
+        .expect("load skill")
+        .expect("skill exists");
+    assert!(skill.apps.claude, "existing state should be untouched");
+}
+
+#[test]
+fn skill_manifest_round_trips_and_restores_enabled_state() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let ssot_dir = home.join(".cc-switch").join("skills");
+    for name in ["alpha", "beta"] {
+        write_skill(&ssot_dir.join(name), name);
+    }
+
+    let state = create_test_state().expect("create test state");
+    for (name, claude) in [("alpha", true), ("beta", false)] {
+        state
+            .db
+            .save_skill(&installed_skill(name, claude))
+            .expect("save skill");
+    }
+    SkillService::toggle_app(&state.db, "local:beta", &AppType::Codex, true)
+        .expect("enable beta for codex");
+
+    let manifest = SkillService::export_manifest(&state.db).expect("export manifest");
+    let json = serde_json::to_string(&manifest).expect("serialize manifest");
+    let mut manifest: SkillManifest = serde_json::from_str(&json).expect("parse manifest");
+    let keys: Vec<&str> = manifest.skills.iter().map(|s| s.key.as_str()).collect();
+    assert_eq!(keys, vec!["local:alpha", "local:beta"]);
+
+    // 模拟另一台机器上的不同状态，以及一个尚未安装的仓库 Skill
+    SkillService::toggle_app(&state.db, "local:alpha", &AppType::Claude, false)
+        .expect("disable alpha");
+    SkillService::toggle_app(&state.db, "local:beta", &AppType::Codex, false)
+        .expect("disable beta");
+    let mut remote = manifest.skills[0].clone();
+    remote.key = "owner/repo:remote".to_string();
+    remote.directory = "remote".to_string();
+    remote.repo_owner = Some("owner".to_string());
+    remote.repo_name = Some("repo".to_string());
+    manifest.skills.push(remote);
+
+    let runtime = tokio::runtime::Runtime::new().expect("create runtime");
+    let result = runtime
+        .block_on(SkillService::new().apply_manifest(&state.db, &manifest, false))
+        .expect("apply manifest");
+
+    assert!(result.installed.is_empty());
+    assert_eq!(result.updated, vec!["local:alpha", "local:beta"]);
+    assert_eq!(result.skipped, vec!["owner/repo:remote"]);
+
+    let restored = SkillService::export_manifest(&state.db).expect("export again");
+    assert_eq!(restored.skills.len(), 2);
+    for (restored, original) in restored.skills.iter().zip(&manifest.skills) {
+        assert_eq!(restored.apps, original.apps, "{}", restored.key);
+    }
+    assert!(home
+        .join(".claude")
+        .join("skills")
+        .join("alpha")
+        .join("SKILL.md")
+        .exists());
+    assert!(home
+        .join(".codex")
+        .join("skills")
+        .join("beta")
+        .join("SKILL.md")
+        .exists());
+
+    // 再次应用无变化
+    let result = runtime
+        .block_on(SkillService::new().apply_manifest(&state.db, &manifest, false))
+        .expect("apply manifest again");
+    assert!(result.updated.is_empty());
+}
+
+#[test]
+fn refresh_metadata_picks_up_edited_skill_md() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let ssot_dir = home.join(".cc-switch").join("skills");
+    for name in ["alpha", "beta"] {
+        write_skill(&ssot_dir.join(name), name);
+    }
+
+    let state = create_test_state().expect("create test state");
+    for name in ["alpha", "beta"] {
+        let mut skill = installed_skill(name, false);
+        skill.description = Some("Test skill".to_string());
+        state.db.save_skill(&skill).expect("save skill");
+    }
+
+    assert!(!SkillService::refresh_metadata(&state.db, "local:alpha").expect("refresh unchanged"));
+
+    fs::write(
+        ssot_dir.join("alpha").join("SKILL.md"),
+        "---\nname: Alpha Renamed\ndescription: Updated upstream\n---\n",
+    )
+    .expect("edit SKILL.md");
+    assert!(SkillService::refresh_metadata(&state.db, "local:alpha").expect("refresh alpha"));
+
+    let alpha = state
+        .db
+        .get_installed_skill("local:alpha")
+        .expect("load skill")
+        .expect("skill exists");
+    assert_eq!(alpha.name, "Alpha Renamed");
+    assert_eq!(alpha.description.as_deref(), Some("Updated upstream"));
+
+    fs::write(
+        ssot_dir.join("beta").join("SKILL.md"),
+        "---\nname: beta\ndescription: New beta description\n---\n",
+    )
+    .expect("edit beta SKILL.md");
+    let changed = SkillService::refresh_all_metadata(&state.db).expect("refresh all");
+    assert_eq!(changed, vec!["local:beta"]);
+
+    SkillService::refresh_metadata(&state.db, "local:missing").expect_err("unknown skill");
+}
@@ -328,6 +328,9 @@ const EndpointSpeedTest: React.FC<EndpointSpeedTestProps> = ({
     try {
       const results = await vscodeApi.testApiEndpoints(urls, {
         timeoutSecs: ENDPOINT_TIMEOUT_SECS[appId],
+        // 编辑模式下记录测速历史
+        appId: providerId ? appId : undefined,
+        providerId,
       });
 
       const resultMap = new Map(
@@ -377,7 +380,15 @@ const EndpointSpeedTest: React.FC<EndpointSpeedTestProps> = ({
     } finally {
       setIsTesting(false);
     }
-  }, [entries, autoSelect, appId, normalizedSelected, onChange, t]);
+  }, [
+    entries,
+    autoSelect,
+    appId,
+    providerId,
+    normalizedSelected,
+    onChange,
+    t,
+  ]);
 
   const handleSelect = useCallback(
     (url: string) => {
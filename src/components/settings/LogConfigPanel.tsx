@@ -86,6 +86,22 @@ export function LogConfigPanel() {
         </Select>
       </div>
 
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5">
+          <Label>{t("settings.advanced.logConfig.redactSse")}</Label>
+          <p className="text-xs text-muted-foreground">
+            {t("settings.advanced.logConfig.redactSseDescription")}
+          </p>
+        </div>
+        <Switch
+          checked={config.redactSseContent ?? false}
+          disabled={!config.enabled}
+          onCheckedChange={(checked) =>
+            handleChange({ redactSseContent: checked })
+          }
+        />
+      </div>
+
       {/* 日志级别说明 */}
       <div className="rounded-lg bg-muted/50 p-4 text-xs space-y-1.5">
         <p className="font-medium text-muted-foreground mb-2">
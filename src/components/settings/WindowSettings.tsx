@@ -1,6 +1,6 @@
 import { useTranslation } from "react-i18next";
 import type { SettingsFormState } from "@/hooks/useSettings";
-import { AppWindow, MonitorUp, Power, EyeOff } from "lucide-react";
+import { AppWindow, MonitorUp, Power, EyeOff, Server } from "lucide-react";
 import { ToggleRow } from "@/components/ui/toggle-row";
 import { AnimatePresence, motion } from "framer-motion";
 
@@ -48,6 +48,14 @@ export function WindowSettings({ settings, onChange }: WindowSettingsProps) {
           )}
         </AnimatePresence>
 
+        <ToggleRow
+          icon={<Server className="h-4 w-4 text-emerald-500" />}
+          title={t("settings.autoStartProxy")}
+          description={t("settings.autoStartProxyDescription")}
+          checked={!!settings.autoStartProxy}
+          onCheckedChange={(value) => onChange({ autoStartProxy: value })}
+        />
+
         <ToggleRow
           icon={<MonitorUp className="h-4 w-4 text-purple-500" />}
           title={t("settings.enableClaudePluginIntegration")}
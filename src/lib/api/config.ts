@@ -55,11 +55,13 @@ export async function setCommonConfigSnippet(
  * 会自动排除差异化字段（API Key、模型配置、端点等），返回可复用的通用配置片段。
  *
  * @param appType - 应用类型（claude/codex/gemini）
- * @param options - 可选：提取来源
+ * @param options - 可选：提取来源，以及 Gemini 是否保留非字符串 env 值
  * @returns 提取的通用配置片段（JSON/TOML 字符串）
  */
 export type ExtractCommonConfigSnippetOptions = {
   settingsConfig?: string;
+  // 仅 Gemini：将 env 中的非字符串值转为字符串保留（默认丢弃）
+  preserveNonStringEnv?: boolean;
 };
 
 export async function extractCommonConfigSnippet(
@@ -72,6 +74,9 @@ export async function extractCommonConfigSnippet(
   if (typeof settingsConfig === "string" && settingsConfig.trim()) {
     args.settingsConfig = settingsConfig;
   }
+  if (options?.preserveNonStringEnv) {
+    args.preserveNonStringEnv = true;
+  }
 
   return invoke<string>("extract_common_config_snippet", args);
 }
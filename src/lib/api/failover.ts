@@ -4,6 +4,8 @@ import type {
   CircuitBreakerConfig,
   CircuitBreakerStats,
   FailoverQueueItem,
+  ProviderGroup,
+  RoutingMode,
 } from "@/types/proxy";
 
 export interface Provider {
@@ -39,6 +41,14 @@ export const failoverApi = {
     return invoke("reset_circuit_breaker", { providerId, appType });
   },
 
+  // 清除单个供应商的健康状态（同时重置其熔断器）
+  async clearProviderHealth(
+    providerId: string,
+    appType: string,
+  ): Promise<void> {
+    return invoke("clear_provider_health", { providerId, appType });
+  },
+
   // 获取熔断器配置
   async getCircuitBreakerConfig(): Promise<CircuitBreakerConfig> {
     return invoke("get_circuit_breaker_config");
@@ -96,4 +106,37 @@ export const failoverApi = {
   ): Promise<void> {
     return invoke("set_auto_failover_enabled", { appType, enabled });
   },
+
+  // 获取指定应用的供应商选择策略
+  async getRoutingStrategy(appType: string): Promise<RoutingMode> {
+    return invoke("get_routing_strategy", { appType });
+  },
+
+  // 设置指定应用的供应商选择策略
+  async setRoutingStrategy(
+    appType: string,
+    strategy: RoutingMode,
+  ): Promise<void> {
+    return invoke("set_routing_strategy", { appType, strategy });
+  },
+
+  // ========== 故障转移分组 API ==========
+
+  // 获取分组列表
+  async getProviderGroups(appType: string): Promise<ProviderGroup[]> {
+    return invoke("get_provider_groups", { appType });
+  },
+
+  // 新增或更新分组
+  async saveProviderGroup(
+    appType: string,
+    group: ProviderGroup,
+  ): Promise<void> {
+    return invoke("save_provider_group", { appType, group });
+  },
+
+  // 删除分组
+  async deleteProviderGroup(appType: string, name: string): Promise<boolean> {
+    return invoke("delete_provider_group", { appType, name });
+  },
 };
@@ -126,4 +126,11 @@ export const mcpApi = {
   async importFromApps(): Promise<number> {
     return await invoke("import_mcp_from_apps");
   },
+
+  /**
+   * 从 Claude Desktop 导入 MCP 服务器，返回导入的 id 列表
+   */
+  async importFromClaudeDesktop(): Promise<string[]> {
+    return await invoke("import_mcp_from_claude_desktop");
+  },
 };
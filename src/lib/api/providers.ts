@@ -1,7 +1,9 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type {
+  CapabilityReport,
   Provider,
+  ThroughputReport,
   UniversalProvider,
   UniversalProvidersMap,
 } from "@/types";
@@ -21,13 +23,103 @@ export interface SwitchResult {
   warnings: string[];
 }
 
+export interface LintWarning {
+  code: string;
+  severity: "warning" | "info";
+  message: string;
+  field: string;
+}
+
+export interface UrlChange {
+  providerId: string;
+  providerName: string;
+  field: string;
+  before: string;
+  after: string;
+  /** 命中的规则：whitespace / missing_scheme / duplicate_v1 / trailing_slash */
+  fixes: string[];
+  /** 供应商已冻结，修正不会被应用 */
+  frozen: boolean;
+}
+
+/** 广播通用配置的结果（应用 ID 列表） */
+export interface BroadcastResult {
+  updated: string[];
+  /** 当前供应商已冻结而跳过的目标应用 */
+  frozen: string[];
+  /** 没有环境变量配置段（如 Codex）而跳过的目标应用 */
+  unsupported: string[];
+}
+
+/** 批量规范化 Claude 模型字段的结果 */
+export interface ModelNormalizeResult {
+  changed: number;
+  /** 已冻结而跳过的供应商 ID */
+  frozen: string[];
+}
+
+/** 合并通用配置后的字段变化；before 为空表示新增 */
+export interface FieldDiff {
+  path: string;
+  before?: unknown;
+  after?: unknown;
+}
+
+export interface NativeFile {
+  fileName: string;
+  content: string;
+}
+
+export interface ProviderImportResult {
+  imported: string[];
+  skipped: string[];
+}
+
+export type TokenFreshness =
+  | { status: "fresh" }
+  | { status: "empty"; field: string }
+  | { status: "placeholder"; field: string }
+  | { status: "likelyExpired"; field: string; reason: string };
+
+export interface SwitchEvent {
+  id: number;
+  appType: AppId;
+  /** 切换前的供应商，首次切换为 null */
+  fromId: string | null;
+  toId: string;
+  /** live：写入 Live 配置；hot_switch：代理接管下热切换 */
+  mode: "live" | "hot_switch";
+  /** 切换时间（Unix 毫秒） */
+  ts: number;
+}
+
+export interface StaleProvider {
+  providerId: string;
+  name: string;
+  /** 最近一次验证成功的时间（Unix 秒），从未验证为 null */
+  lastVerifiedAt: number | null;
+}
+
+export interface NativeExport {
+  appType: AppId;
+  providerId: string;
+  files: NativeFile[];
+}
+
 export const providersApi = {
   async getAll(appId: AppId): Promise<Record<string, Provider>> {
     return await invoke("get_providers", { app: appId });
   },
 
-  async getCurrent(appId: AppId): Promise<string> {
-    return await invoke("get_current_provider", { app: appId });
+  // preferHealthy：当前供应商不健康时返回故障转移队列中健康的替代者（只读，不切换）
+  async getCurrent(
+    appId: AppId,
+    options?: { preferHealthy?: boolean },
+  ): Promise<string> {
+    return await invoke("get_current_provider", {
+      app: appId,
+      preferHealthy: options?.preferHealthy ?? false,
+    });
   },
 
   async add(provider: Provider, appId: AppId): Promise<boolean> {
@@ -38,6 +130,71 @@ export const providersApi = {
     return await invoke("update_provider", { provider, app: appId });
   },
 
+  async lint(provider: Provider, appId: AppId): Promise<LintWarning[]> {
+    return await invoke("lint_provider", { provider, app: appId });
+  },
+
+  // 检查并规范化所有供应商的 base_url（dryRun 时只返回修正建议）
+  async normalizeBaseUrls(appId: AppId, dryRun: boolean): Promise<UrlChange[]> {
+    return await invoke("normalize_base_urls", { app: appId, dryRun });
+  },
+
+  // 将旧版 ANTHROPIC_SMALL_FAST_MODEL 迁移为 DEFAULT_* 模型字段，返回修改的供应商数
+  async normalizeClaudeModels(): Promise<number> {
+    return await invoke("normalize_claude_models");
+  },
+
+  // 同上，同时返回跳过的冻结供应商
+  async normalizeClaudeModelsReport(): Promise<ModelNormalizeResult> {
+    return await invoke("normalize_claude_models_report");
+  },
+
+  // 预览合并通用配置片段后的字段差异（不保存）
+  async previewApplyCommonConfig(
+    id: string,
+    appId: AppId,
+    snippet: string,
+  ): Promise<FieldDiff[]> {
+    return await invoke("preview_apply_common_config", {
+      app: appId,
+      providerId: id,
+      snippet,
+    });
+  },
+
+  // 导出供应商分享码（URL-safe Base64，用于生成二维码）
+  async exportShareCode(
+    id: string,
+    appId: AppId,
+    includeSecret: boolean,
+  ): Promise<string> {
+    return await invoke("export_provider_share_code", {
+      app: appId,
+      id,
+      includeSecret,
+    });
+  },
+
+  async warmUp(id: string, appId: AppId): Promise<boolean> {
+    return await invoke("warm_up_provider", { app: appId, id });
+  },
+
+  async benchmarkThroughput(
+    id: string,
+    appId: AppId,
+    targetTokens: number,
+  ): Promise<ThroughputReport> {
+    return await invoke("benchmark_provider_throughput", {
+      app: appId,
+      id,
+      targetTokens,
+    });
+  },
+
+  async formatCodexConfig(configText: string): Promise<string> {
+    return await invoke("format_codex_config", { configText });
+  },
+
   async delete(id: string, appId: AppId): Promise<boolean> {
     return await invoke("delete_provider", { id, app: appId });
   },
@@ -54,10 +211,89 @@ export const providersApi = {
     return await invoke("switch_provider", { id, app: appId });
   },
 
+  // 设置跨设备默认供应商（不切换本设备当前供应商）
+  async setDefault(id: string, appId: AppId): Promise<boolean> {
+    return await invoke("set_default_provider", { id, app: appId });
+  },
+
+  // 导出原生配置文件（如 Claude settings.json，可直接放入 CLI 配置目录）
+  async exportNative(id: string, appId: AppId): Promise<NativeExport> {
+    return await invoke("export_provider_native", { id, app: appId });
+  },
+
+  async setEnabled(
+    id: string,
+    appId: AppId,
+    enabled: boolean,
+  ): Promise<boolean> {
+    return await invoke("set_provider_enabled", { id, app: appId, enabled });
+  },
+
+  async setFrozen(
+    id: string,
+    appId: AppId,
+    frozen: boolean,
+  ): Promise<boolean> {
+    return await invoke("set_provider_frozen", { id, app: appId, frozen });
+  },
+
+  // 返回已更新的目标应用，以及因冻结或不支持而跳过的目标应用
+  async broadcastCommonConfig(
+    fromApp: AppId,
+    toApps: AppId[],
+  ): Promise<BroadcastResult> {
+    return await invoke("broadcast_common_config", { fromApp, toApps });
+  },
+
   async importDefault(appId: AppId): Promise<boolean> {
     return await invoke("import_default_config", { app: appId });
   },
 
+  // 将当前 live 配置采用为供应商并设为当前（不会重写 live 文件）
+  async adoptCurrentLive(appId: AppId, name: string): Promise<Provider> {
+    return await invoke("adopt_current_live", { app: appId, name });
+  },
+
+  async importOfficial(appId: AppId): Promise<Provider | null> {
+    return await invoke("import_official_config", { app: appId });
+  },
+
+  // 合并导入：ID 已存在或内容（base_url + token）重复的供应商会被跳过
+  async importMerge(
+    appId: AppId,
+    providers: Provider[],
+  ): Promise<ProviderImportResult> {
+    return await invoke("import_providers_merge", { app: appId, providers });
+  },
+
+  async getStale(appId: AppId, maxAgeDays: number): Promise<StaleProvider[]> {
+    return await invoke("get_stale_providers", { app: appId, maxAgeDays });
+  },
+
+  // 按令牌格式判断是否可能已失效（不发送请求）
+  async checkTokenFreshness(
+    appId: AppId,
+    providerId: string,
+  ): Promise<TokenFreshness> {
+    return await invoke("check_token_freshness", { app: appId, providerId });
+  },
+
+  // 最近的手动切换记录（最新的在前）
+  async getSwitchEvents(appId: AppId, limit?: number): Promise<SwitchEvent[]> {
+    return await invoke("get_switch_events", { app: appId, limit });
+  },
+
+  // 探测流式/非流式支持以及工具调用、系统提示词是否生效，结果同时写入 meta.capabilities
+  async probeCapabilities(
+    appId: AppId,
+    providerId: string,
+  ): Promise<CapabilityReport> {
+    return await invoke("probe_provider_capabilities", {
+      app: appId,
+      providerId,
+    });
+  },
+
   async updateTrayMenu(): Promise<boolean> {
     return await invoke("update_tray_menu");
   },
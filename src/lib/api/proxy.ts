@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type {
   ProxyConfig,
   ProxyStatus,
@@ -6,6 +7,9 @@ import type {
   ProxyTakeoverStatus,
   GlobalProxyConfig,
   AppProxyConfig,
+  OverheadReport,
+  TakeoverTestReport,
+  RequestLogSummary,
 } from "@/types/proxy";
 
 export const proxyApi = {
@@ -21,6 +25,11 @@ export const proxyApi = {
     return invoke("stop_proxy_with_restore");
   },
 
+  // 将代理恢复为默认状态（停止服务器、恢复配置、重置代理设置）
+  async factoryResetProxy(): Promise<void> {
+    return invoke("factory_reset_proxy");
+  },
+
   // 获取代理服务器状态
   async getProxyStatus(): Promise<ProxyStatus> {
     return invoke("get_proxy_status");
@@ -44,6 +53,42 @@ export const proxyApi = {
     return invoke("switch_proxy_provider", { appType, providerId });
   },
 
+  // 测量代理额外增加的延迟（需代理运行中）
+  async benchmarkOverhead(
+    appType: string,
+    providerId: string,
+    samples?: number,
+  ): Promise<OverheadReport> {
+    return invoke("benchmark_proxy_overhead", { appType, providerId, samples });
+  },
+
+  // 接管自检：在临时目录中演练 备份 → 接管 → 恢复，不触碰真实 Live 文件
+  async takeoverSelfTest(): Promise<TakeoverTestReport> {
+    return invoke("proxy_takeover_self_test");
+  },
+
+  // 暂停/恢复使用量记录（压测时使用，可选自动恢复秒数；需代理运行中）
+  async setLoggingPaused(paused: boolean, durationSecs?: number): Promise<void> {
+    return invoke("set_logging_paused", { paused, durationSecs });
+  },
+
+  // 实时请求流：订阅后每条请求记录完成都会推送摘要（需开启日志）
+  async subscribeRequests(): Promise<void> {
+    return invoke("subscribe_proxy_requests");
+  },
+
+  async unsubscribeRequests(): Promise<void> {
+    return invoke("unsubscribe_proxy_requests");
+  },
+
+  async onRequestLogged(
+    handler: (summary: RequestLogSummary) => void,
+  ): Promise<UnlistenFn> {
+    return await listen("proxy-request-logged", (event) => {
+      handler(event.payload as RequestLogSummary);
+    });
+  },
+
   // ========== 接管状态 API ==========
 
   // 获取各应用接管状态
@@ -18,6 +18,21 @@ export interface WebDavSyncResult {
   status: string;
 }
 
+export interface SecretStoreStatus {
+  backend: "keychain" | "plaintext";
+  warning?: string;
+}
+
+export type StartupStep =
+  | "takeoverRecovery"
+  | "proxyAutoStart"
+  | "skillMigration";
+
+export interface SafeModeStatus {
+  enabled: boolean;
+  skippedSteps: StartupStep[];
+}
+
 export const settingsApi = {
   async get(): Promise<Settings> {
     return await invoke("get_settings");
@@ -39,6 +54,14 @@ export const settingsApi = {
     return await invoke("is_portable_mode");
   },
 
+  async getSecretStoreStatus(): Promise<SecretStoreStatus> {
+    return await invoke("secret_store_status");
+  },
+
+  async getSafeModeStatus(): Promise<SafeModeStatus> {
+    return await invoke("get_safe_mode_status");
+  },
+
   async getConfigDir(appId: AppId): Promise<string> {
     return await invoke("get_config_dir", { app: appId });
   },
@@ -211,6 +234,16 @@ export const settingsApi = {
   async setLogConfig(config: LogConfig): Promise<boolean> {
     return await invoke("set_log_config", { config });
   },
+
+  /** 设置单个模块的日志级别，level 为空字符串时恢复默认 */
+  async setLogLevel(module: string, level: string): Promise<boolean> {
+    return await invoke("set_log_level", { module, level });
+  },
+
+  /** 导出全部设置（用于问题反馈），redact 为 true 时隐藏密钥类信息 */
+  async dumpSettings(redact = true): Promise<Record<string, unknown>> {
+    return await invoke("dump_settings", { redact });
+  },
 };
 
 export interface RectifierConfig {
@@ -229,6 +262,9 @@ export interface OptimizerConfig {
 export interface LogConfig {
   enabled: boolean;
   level: "error" | "warn" | "info" | "debug" | "trace";
+  redactSseContent?: boolean;
+  /** 模块路径 → 级别，如 { "proxy::handlers": "warn" } */
+  moduleLevels?: Record<string, string>;
 }
 
 export interface BackupEntry {
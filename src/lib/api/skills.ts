@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 
 import type { AppId } from "@/lib/api/types";
+import type { SkillSyncMethod } from "@/types";
 
 export type AppType = "claude" | "codex" | "gemini" | "opencode" | "openclaw";
 
@@ -25,6 +26,8 @@ export interface InstalledSkill {
   readmeUrl?: string;
   apps: SkillApps;
   installedAt: number;
+  /** 单独指定的同步方式，未设置时跟随全局 skillSyncMethod */
+  syncMethodOverride?: SkillSyncMethod;
 }
 
 export interface SkillUninstallResult {
@@ -78,6 +81,32 @@ export interface Skill {
   repoBranch?: string;
 }
 
+/** 已安装 Skills 清单中的单个 Skill */
+export interface SkillManifestEntry {
+  key: string;
+  name: string;
+  description?: string;
+  directory: string;
+  repoOwner?: string;
+  repoName?: string;
+  repoBranch?: string;
+  readmeUrl?: string;
+  apps: SkillApps;
+}
+
+/** 已安装 Skills 清单（用于在其他机器上复现） */
+export interface SkillManifest {
+  version: number;
+  skills: SkillManifestEntry[];
+}
+
+/** 应用清单的结果 */
+export interface SkillManifestApplyResult {
+  installed: string[];
+  updated: string[];
+  skipped: string[];
+}
+
 /** 仓库配置 */
 export interface SkillRepo {
   owner: string;
@@ -132,6 +161,37 @@ export const skillsApi = {
     return await invoke("toggle_skill_app", { id, app, enabled });
   },
 
+  /** 设置 Skill 单独的同步方式（传 null 恢复跟随全局设置） */
+  async setSyncMethod(
+    id: string,
+    method: SkillSyncMethod | null,
+  ): Promise<boolean> {
+    return await invoke("set_skill_sync_method", { id, method });
+  },
+
+  /** 从 SKILL.md 刷新 Skill 的名称和描述，返回是否有变化 */
+  async refreshMetadata(id: string): Promise<boolean> {
+    return await invoke("refresh_skill_metadata", { id });
+  },
+
+  /** 刷新所有 Skill 的元数据，返回有变化的 Skill id */
+  async refreshAllMetadata(): Promise<string[]> {
+    return await invoke("refresh_all_skill_metadata");
+  },
+
+  /** 导出已安装 Skills 清单 */
+  async exportManifest(): Promise<SkillManifest> {
+    return await invoke("export_skill_manifest");
+  },
+
+  /** 应用 Skills 清单（installMissing 为 true 时安装缺失的 Skill） */
+  async applyManifest(
+    manifest: SkillManifest,
+    installMissing: boolean,
+  ): Promise<SkillManifestApplyResult> {
+    return await invoke("apply_skill_manifest", { manifest, installMissing });
+  },
+
   /** 扫描未管理的 Skills */
   async scanUnmanaged(): Promise<UnmanagedSkill[]> {
     return await invoke("scan_unmanaged_skills");
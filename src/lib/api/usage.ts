@@ -3,12 +3,15 @@ import type {
   UsageSummary,
   DailyStats,
   ProviderStats,
+  ProviderSpend,
   ModelStats,
   RequestLog,
   LogFilters,
   ModelPricing,
   ProviderLimitStatus,
   PaginatedLogs,
+  CostEstimate,
+  SessionCost,
 } from "@/types/usage";
 import type { UsageResult } from "@/types";
 import type { AppId } from "./types";
@@ -63,6 +66,14 @@ export const usageApi = {
     return invoke("get_provider_stats");
   },
 
+  getProvidersBySpend: async (
+    appType?: string,
+    startTs?: number,
+    endTs?: number,
+  ): Promise<ProviderSpend[]> => {
+    return invoke("get_providers_by_spend", { appType, startTs, endTs });
+  },
+
   getModelStats: async (): Promise<ModelStats[]> => {
     return invoke("get_model_stats");
   },
@@ -83,6 +94,21 @@ export const usageApi = {
     return invoke("get_request_detail", { requestId });
   },
 
+  getSessionLogs: async (sessionId: string): Promise<RequestLog[]> => {
+    return invoke("get_session_logs", { sessionId });
+  },
+
+  getSessionCost: async (sessionId: string): Promise<SessionCost> => {
+    return invoke("get_session_cost", { sessionId });
+  },
+
+  exportSessionLogs: async (
+    sessionId: string,
+    filePath: string,
+  ): Promise<number> => {
+    return invoke("export_session_logs", { sessionId, filePath });
+  },
+
   getModelPricing: async (): Promise<ModelPricing[]> => {
     return invoke("get_model_pricing");
   },
@@ -94,6 +120,7 @@ export const usageApi = {
     outputCost: string,
     cacheReadCost: string,
     cacheCreationCost: string,
+    reasoningCost?: string,
   ): Promise<void> => {
     return invoke("update_model_pricing", {
       modelId,
@@ -102,6 +129,7 @@ export const usageApi = {
       outputCost,
       cacheReadCost,
       cacheCreationCost,
+      reasoningCost,
     });
   },
 
@@ -115,4 +143,27 @@ export const usageApi = {
   ): Promise<ProviderLimitStatus> => {
     return invoke("check_provider_limits", { providerId, appType });
   },
+
+  estimateCost: async (
+    model: string,
+    inputTokens: number,
+    expectedOutputTokens: number,
+    providerId: string,
+    appType: string,
+  ): Promise<CostEstimate> => {
+    return invoke("estimate_request_cost", {
+      model,
+      inputTokens,
+      expectedOutputTokens,
+      providerId,
+      appType,
+    });
+  },
+
+  deleteRequestLogsBetween: async (
+    startTs: number,
+    endTs: number,
+  ): Promise<number> => {
+    return invoke("delete_request_logs_between", { startTs, endTs });
+  },
 };
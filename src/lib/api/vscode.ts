@@ -9,6 +9,23 @@ export interface EndpointLatencyResult {
   error?: string;
 }
 
+// 端点测速历史样本（latencyMs 为空表示该次测速失败）
+export interface EndpointLatencySample {
+  latencyMs: number | null;
+  status?: number | null;
+  testedAt: number;
+}
+
+// 自定义端点可达性（可达的按延迟从快到慢排在前面）
+export interface EndpointStatus {
+  url: string;
+  reachable: boolean;
+  latency: number | null;
+  status: number | null;
+  error: string | null;
+  lastUsed: number | null;
+}
+
 export const vscodeApi = {
   async getLiveProviderSettings(appId: AppId) {
     return await invoke("read_live_provider_settings", { app: appId });
@@ -16,14 +33,38 @@ export const vscodeApi = {
 
   async testApiEndpoints(
     urls: string[],
-    options?: { timeoutSecs?: number },
+    // 传入 appId + providerId 时，测速结果会追加到该供应商的端点测速历史
+    options?: { timeoutSecs?: number; appId?: AppId; providerId?: string },
   ): Promise<EndpointLatencyResult[]> {
     return await invoke("test_api_endpoints", {
       urls,
       timeoutSecs: options?.timeoutSecs,
+      app: options?.appId,
+      providerId: options?.providerId,
+    });
+  },
+
+  async getEndpointLatencyTrend(
+    appId: AppId,
+    providerId: string,
+    url: string,
+    limit?: number,
+  ): Promise<EndpointLatencySample[]> {
+    return await invoke("get_endpoint_latency_trend", {
+      app: appId,
+      providerId,
+      url,
+      limit,
     });
   },
 
+  async getEndpointMatrix(
+    appId: AppId,
+    providerId: string,
+  ): Promise<EndpointStatus[]> {
+    return await invoke("get_endpoint_matrix", { app: appId, providerId });
+  },
+
   async getCustomEndpoints(
     appId: AppId,
     providerId: string,
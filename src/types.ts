@@ -28,6 +28,8 @@ export interface Provider {
   iconColor?: string; // 图标颜色（Hex 格式，如 "#00A67E"）
   // 是否加入故障转移队列
   inFailoverQueue?: boolean;
+  // 是否启用（禁用后不参与路由与故障转移）
+  enabled?: boolean;
 }
 
 export interface AppConfig {
@@ -108,6 +110,33 @@ export interface ProviderTestConfig {
   maxRetries?: number;
 }
 
+// 供应商能力探测结果
+export interface CapabilityReport {
+  streaming: boolean;
+  nonStreaming: boolean;
+  // 非流式请求失败时无法判断
+  toolUse?: boolean;
+  systemPrompt?: boolean;
+  model: string;
+  streamError?: string;
+  nonStreamError?: string;
+  // 探测时间（Unix 秒）
+  testedAt: number;
+}
+
+// 供应商吞吐测试结果
+export interface ThroughputReport {
+  model: string;
+  // 优先取上游 usage，否则为内容增量个数
+  outputTokens: number;
+  tokensPerSecond: number;
+  ttftMs: number;
+  totalMs: number;
+  usageReported: boolean;
+  // 测试时间（Unix 秒）
+  testedAt: number;
+}
+
 // 供应商单独的代理配置
 export interface ProviderProxyConfig {
   // 是否启用单独配置（false 时使用全局/系统代理）
@@ -154,10 +183,11 @@ export interface ProviderMeta {
   costMultiplier?: string;
   // 供应商计费模式来源
   pricingModelSource?: string;
-  // Claude API 格式（仅 Claude 供应商使用）
+  // 上游 API 格式（Claude 供应商）
   // - "anthropic": 原生 Anthropic Messages API 格式，直接透传
   // - "openai_chat": OpenAI Chat Completions 格式，需要格式转换
   // - "openai_responses": OpenAI Responses API 格式，需要格式转换
+  // Codex 供应商：声明上游仅支持的协议，代理会在 Chat Completions 与 Responses 之间桥接
   apiFormat?: "anthropic" | "openai_chat" | "openai_responses";
   // 通用认证绑定
   authBinding?: AuthBinding;
@@ -165,6 +195,50 @@ export interface ProviderMeta {
   apiKeyField?: ClaudeApiKeyField;
   // Prompt cache key for OpenAI-compatible endpoints (improves cache hit rate)
   promptCacheKey?: string;
+  // 系统提示词前缀（代理转发时注入到系统提示词最前面）
+  systemPromptPrefix?: string;
+  // 最大输出 token 上限（代理转发时请求值超过上限或未设置则改写为上限）
+  maxTokensCap?: number;
+  // 模型白名单/黑名单（代理转发时校验，黑名单优先；支持 `*` 结尾的前缀匹配）
+  modelAllowlist?: string[];
+  modelDenylist?: string[];
+  // 所属故障转移分组（未设置时归入默认分组，请求头 x-cc-group 选择分组）
+  group?: string;
+  // 请求/响应体转换脚本（JavaScript 函数，接收 JSON 体并返回修改后的对象）
+  requestTransform?: string;
+  responseTransform?: string;
+  // Gemini API Key 池（代理遇到 429/配额错误时轮换到下一个 Key）
+  apiKeyPool?: string[];
+  // Azure OpenAI 模型名 → 部署名映射（providerType 为 "azure_openai" 时使用）
+  azureDeployments?: Record<string, string>;
+  // Azure OpenAI api-version 查询参数
+  azureApiVersion?: string;
+  // 影子供应商 ID：请求异步镜像到该供应商用于对比（客户端只收到主供应商响应）
+  shadowProviderId?: string;
+  // 最近一次能力探测结果
+  capabilities?: CapabilityReport;
+  // 最近一次吞吐测试结果
+  throughput?: ThroughputReport;
+  // 切换到该供应商时跳过 MCP 同步（仅影响该供应商自身的切换）
+  skipMcpSync?: boolean;
+  // 是否记录该供应商的请求日志（未设置时沿用全局日志开关）
+  enableRequestLogging?: boolean;
+  // 原样透传：不改写请求、不解析用量，仅记录状态码与延迟
+  rawPassthrough?: boolean;
+  // 加权路由权重（路由策略为 weighted 时生效，默认 1）
+  routingWeight?: number;
+  // 兜底供应商：故障转移候选全部失败后再尝试一次（每个应用最多一个）
+  isSafetyNet?: boolean;
+  // 冻结：拒绝编辑与删除，直到解除冻结；切换不受影响
+  frozen?: boolean;
+  // 代理转发时丢弃的入站请求头（如 anthropic-beta），不区分大小写
+  stripHeaders?: string[];
+  // 代理转发时额外附加的请求头
+  addHeaders?: Record<string, string>;
+  // 代理转发时要求的最低 TLS 版本（"1.2" / "1.3"）
+  tlsMinVersion?: string;
+  // 固定的上游证书 SHA-256 指纹（十六进制），不匹配时拒绝连接
+  pinnedCertSha256?: string;
   // 供应商类型（用于识别 Copilot 等特殊供应商）
   providerType?: string;
   // GitHub Copilot 关联账号 ID（旧字段，保留兼容读取）
@@ -248,6 +322,8 @@ export interface Settings {
   silentStartup?: boolean;
   // 是否启用主页面本地代理功能（默认关闭）
   enableLocalProxy?: boolean;
+  // 启动时自动启动代理服务（上次异常退出时跳过）
+  autoStartProxy?: boolean;
   // User has confirmed the local proxy first-run notice
   proxyConfirmed?: boolean;
   // User has confirmed the usage query first-run notice
@@ -290,6 +366,10 @@ export interface Settings {
   // Skill 同步方式：auto（默认，优先 symlink）、symlink、copy
   skillSyncMethod?: SkillSyncMethod;
 
+  // ===== 供应商切换设置 =====
+  // 切换供应商后在后台预热连接
+  warmUpOnSwitch?: boolean;
+
   // ===== WebDAV v2 同步设置 =====
   webdavSync?: WebDavSyncSettings;
 
@@ -299,12 +379,18 @@ export interface Settings {
   // Maximum backup files to retain (default 10)
   backupRetainCount?: number;
 
+  // ===== 使用日志存储 =====
+  // 独立的使用日志数据库文件（可选，重启后生效）
+  usageDbPath?: string;
+
   // ===== 终端设置 =====
   // 首选终端应用（可选，默认使用系统默认终端）
   // macOS: "terminal" | "iterm2" | "warp" | "alacritty" | "kitty" | "ghostty"
   // Windows: "cmd" | "powershell" | "wt"
   // Linux: "gnome-terminal" | "konsole" | "xfce4-terminal" | "alacritty" | "kitty" | "ghostty"
   preferredTerminal?: string;
+  // 自定义终端启动命令的超时秒数（默认 10）
+  customTerminalTimeoutSecs?: number;
 }
 
 export interface SessionMeta {
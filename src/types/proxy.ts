@@ -9,6 +9,14 @@ export interface ProxyConfig {
   streaming_first_byte_timeout: number;
   streaming_idle_timeout: number;
   non_streaming_timeout: number;
+  enable_response_cache?: boolean;
+  lightweight_streaming?: boolean;
+  // 最大并发请求数，0 表示不限制
+  max_concurrent_requests?: number;
+  // 额外监听的 Unix Socket 路径（仅 Linux/macOS）
+  listen_socket_path?: string | null;
+  // /v1/chat/completions 等共用路径的默认应用（请求头 x-cc-app 可覆盖）
+  target_app?: "claude" | "codex";
 }
 
 export interface ProxyStatus {
@@ -26,6 +34,7 @@ export interface ProxyStatus {
   last_request_at: string | null;
   last_error: string | null;
   failover_count: number;
+  safety_net_attempts?: number;
   active_targets?: ActiveTarget[];
 }
 
@@ -38,9 +47,51 @@ export interface ActiveTarget {
 export interface ProxyServerInfo {
   address: string;
   port: number;
+  socket_path?: string;
   started_at: string;
 }
 
+export interface OverheadReport {
+  app_type: string;
+  provider_id: string;
+  samples: number;
+  direct_median_ms: number;
+  proxy_median_ms: number;
+  median_overhead_ms: number;
+  overhead_variance_ms2: number;
+  direct_ms: number[];
+  proxy_ms: number[];
+}
+
+// 接管自检结果（在临时目录中对 Live 配置副本执行 备份 → 接管 → 恢复）
+export interface TakeoverTestReport {
+  proxy_url: string;
+  apps: TakeoverAppTestResult[];
+  passed: boolean;
+}
+
+export interface TakeoverAppTestResult {
+  app_type: string;
+  skipped: boolean;
+  placeholder_detected: boolean;
+  round_trip_ok: boolean;
+  mismatched_files: string[];
+  error?: string | null;
+}
+
+// 实时请求流中的单条请求摘要（proxy-request-logged 事件）
+export interface RequestLogSummary {
+  requestId: string;
+  providerId: string;
+  appType: string;
+  model: string;
+  inputTokens: number;
+  outputTokens: number;
+  statusCode: number;
+  latencyMs: number;
+  isStreaming: boolean;
+}
+
 export interface ProxyTakeoverStatus {
   claude: boolean;
   codex: boolean;
@@ -57,9 +108,20 @@ export interface ProviderHealth {
   last_success_at: string | null;
   last_failure_at: string | null;
   last_error: string | null;
+  last_error_kind?: ProviderErrorKind | null;
   updated_at: string;
+  // 最近一次连接预热成功的时间
+  warmed_at?: string | null;
 }
 
+// 最近一次失败的错误类别：auth 需要修正密钥，其余类别通常等待恢复即可
+export type ProviderErrorKind =
+  | "auth"
+  | "rate_limit"
+  | "server_error"
+  | "network"
+  | "unknown";
+
 // 熔断器相关类型
 export interface CircuitBreakerConfig {
   failureThreshold: number;
@@ -112,6 +174,19 @@ export interface FailoverQueueItem {
   sortIndex?: number;
 }
 
+// 故障转移分组（请求头 x-cc-group 选择分组，未设置分组的供应商归入默认分组）
+export interface ProviderGroup {
+  name: string;
+  isDefault: boolean;
+}
+
+// 供应商选择策略（仅在开启故障转移时生效）
+export type RoutingMode =
+  | "sticky"
+  | "round_robin"
+  | "weighted"
+  | "lowest_latency";
+
 // 全局代理配置（统一字段，三行镜像）
 export interface GlobalProxyConfig {
   proxyEnabled: boolean;
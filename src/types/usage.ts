@@ -19,12 +19,19 @@ export interface RequestLog {
   outputTokens: number;
   cacheReadTokens: number;
   cacheCreationTokens: number;
+  reasoningTokens: number;
   inputCostUsd: string;
   outputCostUsd: string;
   cacheReadCostUsd: string;
   cacheCreationCostUsd: string;
+  reasoningCostUsd: string;
   totalCostUsd: string;
   isStreaming: boolean;
+  isCached?: boolean;
+  isShadow?: boolean;
+  apiKeyIndex?: number;
+  isSafetyNet?: boolean;
+  effectiveMaxTokens?: number;
   latencyMs: number;
   firstTokenMs?: number;
   durationMs?: number;
@@ -40,6 +47,23 @@ export interface PaginatedLogs {
   pageSize: number;
 }
 
+export interface SessionLogExport {
+  sessionId: string;
+  exportedAt: number;
+  requestCount: number;
+  logs: RequestLog[];
+}
+
+export interface SessionCost {
+  sessionId: string;
+  messageCount: number;
+  totalCost: string;
+  totalInputTokens: number;
+  totalOutputTokens: number;
+  totalCacheCreationTokens: number;
+  totalCacheReadTokens: number;
+}
+
 export interface ModelPricing {
   modelId: string;
   displayName: string;
@@ -47,6 +71,7 @@ export interface ModelPricing {
   outputCostPerMillion: string;
   cacheReadCostPerMillion: string;
   cacheCreationCostPerMillion: string;
+  reasoningCostPerMillion?: string;
 }
 
 export interface UsageSummary {
@@ -80,6 +105,15 @@ export interface ProviderStats {
   avgLatencyMs: number;
 }
 
+export interface ProviderSpend {
+  providerId: string;
+  appType: string;
+  providerName: string;
+  requestCount: number;
+  totalTokens: number;
+  totalCost: string;
+}
+
 export interface ModelStats {
   model: string;
   requestCount: number;
@@ -107,6 +141,15 @@ export interface ProviderLimitStatus {
   monthlyExceeded: boolean;
 }
 
+export interface CostEstimate {
+  inputCostUsd: string;
+  outputCostUsd: string;
+  cacheReadCostUsd: string;
+  cacheCreationCostUsd: string;
+  reasoningCostUsd: string;
+  totalCostUsd: string;
+}
+
 export type TimeRange = "1d" | "7d" | "30d";
 
 export interface StatsFilters {